@@ -6,11 +6,17 @@ use console::style;
 use std::path::PathBuf;
 
 use pox_pm::{
+    Composer,
     ComposerBuilder,
     config::Config,
     installer::Installer,
     json::{ComposerJson, ComposerLock},
+    package::{
+        version_selector::{find_recommended_require_version, VersionSelector},
+        Stability,
+    },
 };
+use pox_pm::config::{composer_file_name, lock_file_name};
 use crate::pm::platform::PlatformInfo;
 
 #[derive(Args, Debug)]
@@ -31,6 +37,10 @@ pub struct AddArgs {
     #[arg(long)]
     pub prefer_dist: bool,
 
+    /// Skip checksum and signature verification of downloaded archives
+    #[arg(long)]
+    pub no_verify: bool,
+
     /// Run in dry-run mode
     #[arg(long)]
     pub dry_run: bool,
@@ -57,21 +67,23 @@ pub struct AddArgs {
 }
 
 pub async fn execute(args: AddArgs) -> Result<i32> {
+    let io = crate::io::io();
+
     let working_dir = args.working_dir.canonicalize()
         .context("Failed to resolve working directory")?;
 
     // Load composer.json
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
     let composer_json: ComposerJson = if json_path.exists() {
         let content = std::fs::read_to_string(&json_path)?;
         serde_json::from_str(&content)?
     } else {
-        println!("{} No composer.json found. Creating one.", style("Info:").cyan());
+        io.write(&format!("{} No composer.json found. Creating one.", style("Info:").cyan()));
         ComposerJson::default()
     };
 
     // Load composer.lock
-    let lock_path = working_dir.join("composer.lock");
+    let lock_path = working_dir.join(lock_file_name());
     let lock: Option<ComposerLock> = if lock_path.exists() {
         let content = std::fs::read_to_string(&lock_path)
             .context("Failed to read composer.lock")?;
@@ -84,15 +96,18 @@ pub async fn execute(args: AddArgs) -> Result<i32> {
     let config = Config::build(Some(&working_dir), true)?;
 
     // Detect platform
-    let platform = PlatformInfo::detect();
+    let platform = PlatformInfo::detect_for(&working_dir);
 
     // Create Composer using builder
     let mut builder = ComposerBuilder::new(working_dir.clone())
+        .with_io(io.clone())
+        .with_profiler(crate::io::profiler())
         .with_config(config)
         .with_composer_json(composer_json)
         .with_composer_lock(lock)
         .with_platform_packages(platform.to_packages())
-        .dry_run(args.dry_run);
+        .dry_run(args.dry_run)
+        .no_verify(args.no_verify);
 
     // Apply prefer_source/prefer_dist flags
     if args.prefer_source {
@@ -103,20 +118,33 @@ pub async fn execute(args: AddArgs) -> Result<i32> {
 
     let mut composer = builder.build()?;
 
-    println!("{} Adding packages", style("Composer").green().bold());
+    io.write(&format!("{} Adding packages", style("Composer").green().bold()));
     if args.dry_run {
-        println!("{} Running in dry-run mode", style("Info:").cyan());
+        io.write(&format!("{} Running in dry-run mode", style("Info:").cyan()));
     }
 
     // Modify composer.json (in-memory)
     for spec in &args.packages {
         let (name, constraint) = parse_package_spec(spec);
-
-        println!("  {} {} {}",
+        let constraint = match constraint {
+            Some(constraint) => constraint,
+            None => match recommend_constraint(&composer, &name).await {
+                Ok(constraint) => {
+                    io.write(&format!("Using version {} for {}", style(&constraint).green(), name));
+                    constraint
+                }
+                Err(message) => {
+                    io.write_error(&format!("{} {}", style("Error:").red().bold(), message));
+                    return Ok(1);
+                }
+            },
+        };
+
+        io.write(&format!("  {} {} {}",
             style("+").green(),
             style(&name).white().bold(),
             style(&constraint).yellow()
-        );
+        ));
 
         if args.dev {
             composer.composer_json.require_dev.insert(name, constraint);
@@ -148,19 +176,52 @@ pub async fn execute(args: AddArgs) -> Result<i32> {
             Some(new_packages),
         ).await
     } else {
-        println!("{} Packages added to composer.json", style("Success:").green().bold());
+        io.write(&format!("{} Packages added to composer.json", style("Success:").green().bold()));
         Ok(0)
     }
 }
 
 /// Parse a package specification (vendor/package:^1.0 or vendor/package)
-fn parse_package_spec(spec: &str) -> (String, String) {
+fn parse_package_spec(spec: &str) -> (String, Option<String>) {
     if let Some(pos) = spec.find(':') {
         let name = spec[..pos].to_string();
         let constraint = spec[pos + 1..].to_string();
-        (name, constraint)
+        (name, Some(constraint))
     } else {
-        // Default to any version
-        (spec.to_string(), "*".to_string())
+        (spec.to_string(), None)
     }
 }
+
+/// Constraint for a package added without one, based on its best version for this platform
+async fn recommend_constraint(composer: &Composer, name: &str) -> std::result::Result<String, String> {
+    let minimum_stability: Stability = composer.composer_json.minimum_stability
+        .as_deref()
+        .unwrap_or("stable")
+        .parse()
+        .unwrap_or(Stability::Stable);
+    let preferred_stability = if composer.composer_json.prefer_stable.unwrap_or(false) {
+        Stability::Stable
+    } else {
+        minimum_stability
+    };
+
+    let selector = VersionSelector::new(composer.repository_manager.clone())
+        .with_platform_packages(&composer.platform_packages);
+    if let Some(package) = selector.find_best_candidate(name, None, minimum_stability, preferred_stability).await {
+        return Ok(find_recommended_require_version(&package));
+    }
+
+    let any_platform = VersionSelector::new(composer.repository_manager.clone());
+    if any_platform.find_best_candidate(name, None, minimum_stability, preferred_stability).await.is_some() {
+        return Err(format!(
+            "Package {} has requirements incompatible with your PHP version and PHP extensions",
+            name
+        ));
+    }
+
+    Err(format!(
+        "Could not find a version of package {} matching your minimum-stability ({}). \
+         Require it with an explicit version constraint allowing its desired stability.",
+        name, minimum_stability
+    ))
+}
@@ -0,0 +1,93 @@
+//! Sticky worker sessions for worker mode.
+//!
+//! Worker scripts may keep state in memory between requests, like caches of
+//! the current user. With an affinity source the requests of a client are
+//! routed to the same worker thread, keyed by:
+//!
+//! - `header:<name>`: the value of a request header, e.g. `header:X-User-Id`
+//! - `cookie:<name>`: the value of a cookie, e.g. `cookie:PHPSESSID`
+//! - `ip`: the client IP address
+//!
+//! Requests without the header or cookie are handled by any worker.
+
+use anyhow::{bail, Result};
+
+/// Where the affinity key of a request comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Affinity {
+    Header(String),
+    Cookie(String),
+    ClientIp,
+}
+
+impl Affinity {
+    /// Parse `header:<name>`, `cookie:<name>` or `ip`
+    pub fn parse(source: &str) -> Result<Self> {
+        let source = source.trim();
+        if source.eq_ignore_ascii_case("ip") {
+            return Ok(Affinity::ClientIp);
+        }
+
+        match source.split_once(':') {
+            Some((kind, name)) if !name.trim().is_empty() => match kind.to_ascii_lowercase().as_str() {
+                "header" => Ok(Affinity::Header(name.trim().to_string())),
+                "cookie" => Ok(Affinity::Cookie(name.trim().to_string())),
+                _ => bail!("Invalid worker affinity \"{}\", expected header:<name>, cookie:<name> or ip", source),
+            },
+            _ => bail!("Invalid worker affinity \"{}\", expected header:<name>, cookie:<name> or ip", source),
+        }
+    }
+
+    /// The affinity key of a request, `None` if the request does not carry one
+    pub fn key(&self, headers: &[(String, String)], remote_addr: &str) -> Option<String> {
+        match self {
+            Affinity::ClientIp => Some(remote_addr.to_string()),
+            Affinity::Header(name) => headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.trim().to_string())
+                .filter(|v| !v.is_empty()),
+            Affinity::Cookie(name) => headers
+                .iter()
+                .filter(|(k, _)| k.eq_ignore_ascii_case("cookie"))
+                .flat_map(|(_, v)| v.split(';'))
+                .filter_map(|pair| pair.trim().split_once('='))
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.to_string())
+                .filter(|v| !v.is_empty()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Affinity::parse("ip").unwrap(), Affinity::ClientIp);
+        assert_eq!(Affinity::parse("header:X-User-Id").unwrap(), Affinity::Header("X-User-Id".to_string()));
+        assert_eq!(Affinity::parse("cookie:PHPSESSID").unwrap(), Affinity::Cookie("PHPSESSID".to_string()));
+        assert!(Affinity::parse("cookie:").is_err());
+        assert!(Affinity::parse("query:id").is_err());
+        assert!(Affinity::parse("session").is_err());
+    }
+
+    #[test]
+    fn test_key() {
+        let request = headers(&[
+            ("x-user-id", " 42 "),
+            ("Cookie", "theme=dark; PHPSESSID=abc123"),
+        ]);
+
+        assert_eq!(Affinity::ClientIp.key(&request, "10.0.0.1"), Some("10.0.0.1".to_string()));
+        assert_eq!(Affinity::Header("X-User-Id".to_string()).key(&request, "10.0.0.1"), Some("42".to_string()));
+        assert_eq!(Affinity::Cookie("PHPSESSID".to_string()).key(&request, "10.0.0.1"), Some("abc123".to_string()));
+        assert_eq!(Affinity::Cookie("missing".to_string()).key(&request, "10.0.0.1"), None);
+        assert_eq!(Affinity::Header("X-Tenant".to_string()).key(&request, "10.0.0.1"), None);
+    }
+}
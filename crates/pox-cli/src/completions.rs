@@ -0,0 +1,223 @@
+//! Shell completions - completion scripts and the package names they complete.
+//!
+//! The scripts generated by clap_complete only know the static command line.
+//! For bash, zsh and fish a small wrapper is appended that asks the hidden
+//! `pox __complete-package` command for package names, which are read from
+//! the project and the local repository cache, so completion works offline.
+
+use anyhow::Result;
+use clap::Args;
+use clap_complete::{generate, Shell};
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::Path;
+
+use pox_pm::config::{composer_file_name, Config};
+use pox_pm::util::is_platform_package;
+
+/// Subcommands completing packages of the project
+const PROJECT_COMMANDS: &[&str] = &["remove", "update"];
+
+/// Subcommands completing any package known locally
+const AVAILABLE_COMMANDS: &[&str] = &["add"];
+
+#[derive(Args, Debug)]
+pub struct CompletePackageArgs {
+    /// Subcommand whose arguments are completed
+    pub command: String,
+
+    /// Beginning of the package name
+    #[arg(default_value = "")]
+    pub prefix: String,
+}
+
+/// Write the completion script for `shell`, including package name completion where supported
+pub fn write_script(shell: Shell, cmd: &mut clap::Command, out: &mut dyn Write) -> std::io::Result<()> {
+    generate(shell, cmd, "pox", out);
+
+    if let Some(script) = package_script(shell) {
+        out.write_all(script.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Wrapper completing package names, PowerShell and Elvish only get the static completions
+fn package_script(shell: Shell) -> Option<String> {
+    let commands: Vec<&str> = PROJECT_COMMANDS.iter().chain(AVAILABLE_COMMANDS).copied().collect();
+
+    let script = match shell {
+        Shell::Bash => format!(
+            r#"
+_pox_packages() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    case "${{COMP_WORDS[1]}}" in
+        {})
+            if [[ ${{COMP_CWORD}} -ge 2 && "${{cur}}" != -* ]]; then
+                COMPREPLY=( $(pox __complete-package "${{COMP_WORDS[1]}}" -- "${{cur}}" 2>/dev/null) )
+                return 0
+            fi
+            ;;
+    esac
+    _pox "$@"
+}}
+complete -F _pox_packages -o bashdefault -o default pox
+"#,
+            commands.join("|")
+        ),
+        Shell::Zsh => format!(
+            r#"
+_pox_packages() {{
+    if (( CURRENT > 2 )) && [[ "${{words[2]}}" == ({}) && "${{PREFIX}}" != -* ]]; then
+        local -a packages
+        packages=(${{(f)"$(pox __complete-package "${{words[2]}}" -- "${{PREFIX}}" 2>/dev/null)"}})
+        compadd -a packages
+        return
+    fi
+    _pox "$@"
+}}
+compdef _pox_packages pox
+"#,
+            commands.join("|")
+        ),
+        Shell::Fish => format!(
+            "complete -c pox -n \"__fish_seen_subcommand_from {}\" -f -a \"(pox __complete-package (commandline -opc)[2] -- (commandline -ct) 2>/dev/null)\"\n",
+            commands.join(" ")
+        ),
+        _ => return None,
+    };
+
+    Some(script)
+}
+
+/// Print the package names completing `args.prefix`, one per line
+pub fn complete_package(args: CompletePackageArgs) -> Result<i32> {
+    let working_dir = std::env::current_dir()?;
+    let config = Config::build(Some(&working_dir), true).ok();
+
+    let mut names = project_packages(&working_dir, config.as_ref());
+    if AVAILABLE_COMMANDS.contains(&args.command.as_str()) {
+        if let Some(cache_dir) = config.as_ref().and_then(|c| c.cache_repo_dir.as_ref()) {
+            names.extend(cached_packages(cache_dir));
+        }
+    } else if !PROJECT_COMMANDS.contains(&args.command.as_str()) {
+        return Ok(0);
+    }
+
+    let prefix = args.prefix.to_lowercase();
+    let mut out = std::io::stdout().lock();
+    for name in names.iter().filter(|name| name.starts_with(&prefix)) {
+        writeln!(out, "{}", name)?;
+    }
+    Ok(0)
+}
+
+/// Packages required in composer.json or installed in the vendor directory
+fn project_packages(working_dir: &Path, config: Option<&Config>) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+
+    if let Some(json) = read_json(&working_dir.join(composer_file_name())) {
+        for section in ["require", "require-dev"] {
+            if let Some(requires) = json.get(section).and_then(|r| r.as_object()) {
+                names.extend(requires.keys().map(|name| name.to_lowercase()));
+            }
+        }
+    }
+
+    let vendor_dir = config.map_or_else(|| Path::new("vendor").to_path_buf(), |c| c.vendor_dir.clone());
+    if let Some(installed) = read_json(&working_dir.join(vendor_dir).join("composer/installed.json")) {
+        // Composer 2 wraps the packages in an object, Composer 1 wrote a plain list
+        let packages = installed.get("packages").unwrap_or(&installed);
+        if let Some(packages) = packages.as_array() {
+            names.extend(
+                packages
+                    .iter()
+                    .filter_map(|p| p.get("name")?.as_str())
+                    .map(str::to_lowercase),
+            );
+        }
+    }
+
+    names.retain(|name| !is_platform_package(name));
+    names
+}
+
+/// Packages with metadata in the repository cache, stored as `<repo>/provider-vendor~package.json`
+fn cached_packages(cache_repo_dir: &Path) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+
+    let Ok(repos) = std::fs::read_dir(cache_repo_dir) else {
+        return names;
+    };
+    for repo in repos.flatten() {
+        let Ok(files) = std::fs::read_dir(repo.path()) else {
+            continue;
+        };
+        for file in files.flatten() {
+            let file_name = file.file_name();
+            let Some(key) = file_name
+                .to_str()
+                .and_then(|f| f.strip_prefix("provider-")?.strip_suffix(".json"))
+            else {
+                continue;
+            };
+            let key = key.strip_suffix("~dev").unwrap_or(key);
+            if key.contains('~') {
+                names.insert(key.replacen('~', "/", 1).to_lowercase());
+            }
+        }
+    }
+
+    names
+}
+
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_project_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            &dir.path().join("composer.json"),
+            r#"{"require": {"php": ">=8.2", "Acme/Lib": "^1.0"}, "require-dev": {"acme/test": "^2.0"}}"#,
+        );
+        write(
+            &dir.path().join("vendor/composer/installed.json"),
+            r#"{"packages": [{"name": "acme/lib"}, {"name": "acme/util"}]}"#,
+        );
+
+        let names: Vec<_> = project_packages(dir.path(), None).into_iter().collect();
+        assert_eq!(names, vec!["acme/lib", "acme/test", "acme/util"]);
+    }
+
+    #[test]
+    fn test_cached_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("https---repo.packagist.org");
+        write(&repo.join("provider-acme~lib.json"), "{}");
+        write(&repo.join("provider-acme~lib~dev.json"), "{}");
+        write(&repo.join("provider-vendor~tool.json"), "{}");
+        write(&repo.join("packages.json"), "{}");
+
+        let names: Vec<_> = cached_packages(dir.path()).into_iter().collect();
+        assert_eq!(names, vec!["acme/lib", "vendor/tool"]);
+    }
+
+    #[test]
+    fn test_package_script() {
+        let script = package_script(Shell::Bash).unwrap();
+        assert!(script.contains("remove|update|add)"));
+        assert!(script.contains("complete -F _pox_packages"));
+        assert!(package_script(Shell::Fish).unwrap().contains("__fish_seen_subcommand_from remove update add"));
+        assert!(package_script(Shell::PowerShell).is_none());
+    }
+}
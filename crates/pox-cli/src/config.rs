@@ -1,7 +1,7 @@
 use anyhow::Result;
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// The main pox configuration file structure (pox.toml)
 #[derive(Debug, Default, Deserialize)]
@@ -18,6 +18,9 @@ pub struct PoxConfig {
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
 pub struct PhpConfig {
+    /// PHP version of the project (e.g. "8.2"), selects an installed PHP runtime
+    pub version: Option<String>,
+
     /// PHP INI settings (e.g., memory_limit = "256M")
     #[serde(default)]
     pub ini: HashMap<String, String>,
@@ -48,6 +51,72 @@ pub struct ServerConfig {
     /// Watch patterns for file changes
     #[serde(default)]
     pub watch: Vec<String>,
+
+    /// Opcache preload script
+    pub preload: Option<String>,
+
+    /// Expose opcache statistics at /__pox/opcache
+    pub opcache_status: Option<bool>,
+
+    /// Number of request threads without worker mode
+    pub threads: Option<usize>,
+
+    /// Maximum execution time per request in seconds
+    pub request_timeout: Option<u64>,
+
+    /// PHP memory limit (e.g. "256M")
+    pub memory_limit: Option<String>,
+
+    /// Requests after which a worker is recycled
+    pub max_requests_per_worker: Option<u64>,
+
+    /// Route requests of a client to the same worker ("header:<name>", "cookie:<name>" or "ip")
+    pub worker_affinity: Option<String>,
+
+    /// Expose Prometheus metrics at /__pox/metrics
+    pub metrics: Option<bool>,
+
+    /// Serve Prometheus metrics on a separate port
+    pub metrics_port: Option<u16>,
+
+    /// Largest accepted request body (e.g. "512M")
+    pub upload_max_filesize: Option<String>,
+
+    /// Trigger Xdebug for every request
+    pub xdebug: Option<bool>,
+
+    /// Directory for PHP session files, kept across restarts
+    pub session_path: Option<String>,
+
+    /// Maximum number of open client connections
+    pub max_connections: Option<usize>,
+
+    /// Seconds an idle keep-alive connection is kept open
+    pub keep_alive_timeout: Option<u64>,
+
+    /// Denied, static-only and PHP paths
+    pub routes: RoutesConfig,
+
+    /// Environment variables set before PHP starts
+    pub env: HashMap<String, String>,
+
+    /// Proxy addresses or CIDR ranges whose Forwarded/X-Forwarded-* headers are trusted
+    pub trusted_proxies: Vec<String>,
+}
+
+/// Route rules of the server, glob patterns on the request path
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct RoutesConfig {
+    /// Paths answered with 403 Forbidden (e.g. "/.git/**")
+    pub deny: Vec<String>,
+
+    /// Paths only served as static files, never by PHP (e.g. "/assets/**")
+    #[serde(rename = "static")]
+    pub static_paths: Vec<String>,
+
+    /// Scripts PHP may execute, all scripts when empty (e.g. "/index.php")
+    pub php: Vec<String>,
 }
 
 impl Default for ServerConfig {
@@ -60,6 +129,23 @@ impl Default for ServerConfig {
             worker: None,
             workers: None,
             watch: Vec::new(),
+            preload: None,
+            opcache_status: None,
+            threads: None,
+            request_timeout: None,
+            memory_limit: None,
+            max_requests_per_worker: None,
+            worker_affinity: None,
+            metrics: None,
+            metrics_port: None,
+            upload_max_filesize: None,
+            xdebug: None,
+            session_path: None,
+            max_connections: None,
+            keep_alive_timeout: None,
+            routes: RoutesConfig::default(),
+            env: HashMap::new(),
+            trusted_proxies: Vec::new(),
         }
     }
 }
@@ -67,21 +153,30 @@ impl Default for ServerConfig {
 impl PoxConfig {
     /// Load configuration from pox.toml, searching upward from the given directory
     pub fn load(start_dir: &Path) -> Result<Option<Self>> {
+        let Some(config_path) = Self::find(start_dir) else {
+            return Ok(None);
+        };
+
+        let content = std::fs::read_to_string(&config_path)?;
+        let config: PoxConfig = toml::from_str(&content)?;
+        Ok(Some(config))
+    }
+
+    /// Path of the pox.toml in the given directory or the closest parent directory
+    pub fn find(start_dir: &Path) -> Option<PathBuf> {
         let mut current = start_dir.to_path_buf();
 
         loop {
             let config_path = current.join("pox.toml");
 
             if config_path.exists() {
-                let content = std::fs::read_to_string(&config_path)?;
-                let config: PoxConfig = toml::from_str(&content)?;
-                return Ok(Some(config));
+                return Some(config_path);
             }
 
             // Move to parent directory
             if !current.pop() {
                 // Reached filesystem root, no config found
-                return Ok(None);
+                return None;
             }
         }
     }
@@ -91,6 +186,12 @@ impl PoxConfig {
         let cwd = std::env::current_dir()?;
         Self::load(&cwd)
     }
+
+    /// Directory of the pox.toml found from the current working directory
+    pub fn dir_from_cwd() -> Result<Option<PathBuf>> {
+        let cwd = std::env::current_dir()?;
+        Ok(Self::find(&cwd).and_then(|path| path.parent().map(Path::to_path_buf)))
+    }
 }
 
 #[cfg(test)]
@@ -115,6 +216,13 @@ display_errors = "On"
         assert_eq!(config.php.ini.get("memory_limit"), Some(&"256M".to_string()));
         assert_eq!(config.php.ini.get("max_execution_time"), Some(&"30".to_string()));
         assert_eq!(config.php.ini.get("display_errors"), Some(&"On".to_string()));
+        assert_eq!(config.php.version, None);
+    }
+
+    #[test]
+    fn test_parse_php_version() {
+        let config: PoxConfig = toml::from_str("[php]\nversion = \"8.2\"\n").unwrap();
+        assert_eq!(config.php.version.as_deref(), Some("8.2"));
     }
 
     #[test]
@@ -139,4 +247,18 @@ watch = ["**/*.php", "config/**/*"]
         assert_eq!(config.server.watch, vec!["**/*.php", "config/**/*"]);
     }
 
+    #[test]
+    fn test_parse_server_env() {
+        let toml = r#"
+[server]
+trusted_proxies = ["10.0.0.1", "172.16.0.0/12"]
+
+[server.env]
+APP_ENV = "prod"
+"#;
+        let config: PoxConfig = toml::from_str(toml).unwrap();
+        assert_eq!(config.server.env.get("APP_ENV"), Some(&"prod".to_string()));
+        assert_eq!(config.server.trusted_proxies, vec!["10.0.0.1", "172.16.0.0/12"]);
+    }
+
 }
@@ -8,13 +8,14 @@ use std::sync::Arc;
 
 use pox_pm::{
     ComposerBuilder,
-    Repository,
+    RepositoryManager,
     config::Config,
     downloader::{DownloadConfig, DownloadManager},
-    http::HttpClient,
+    http::{HttpClient, HttpClientConfig},
     installer::Installer,
-    json::ComposerJson,
+    json::{ComposerJson, Repository as JsonRepository},
     repository::ComposerRepository,
+    scripts::run_event_script,
     Package,
 };
 use pox_semver::VersionParser;
@@ -47,6 +48,10 @@ pub struct CreateProjectArgs {
     #[arg(long)]
     pub prefer_dist: bool,
 
+    /// Skip checksum and signature verification of downloaded archives
+    #[arg(long)]
+    pub no_verify: bool,
+
     /// Add custom repositories
     #[arg(long, action = clap::ArgAction::Append)]
     pub repository: Vec<String>,
@@ -83,18 +88,6 @@ pub struct CreateProjectArgs {
     #[arg(long = "ignore-platform-req", value_name = "REQ")]
     pub ignore_platform_req: Vec<String>,
 
-    /// Do not ask any interactive question
-    #[arg(short = 'n', long)]
-    pub no_interaction: bool,
-
-    /// Do not output any message
-    #[arg(short = 'q', long)]
-    pub quiet: bool,
-
-    /// Increase verbosity (-v, -vv, -vvv)
-    #[arg(short = 'v', long, action = clap::ArgAction::Count)]
-    pub verbose: u8,
-
     /// Disable plugins
     #[arg(long)]
     pub no_plugins: bool,
@@ -122,6 +115,88 @@ fn parse_package_spec(package: &str) -> (String, Option<String>) {
     }
 }
 
+/// Split a `@stability` flag off a version constraint.
+///
+/// Without an explicit `--stability`, the flag or the stability of the
+/// requested version itself (e.g. `dev-main`, `2.0.0-beta1`) is used, so
+/// that asking for an unstable version does not require a separate option.
+fn resolve_stability(
+    stability: Option<&str>,
+    version_constraint: Option<&str>,
+) -> (String, Option<String>) {
+    let (constraint, flag) = match version_constraint {
+        Some(constraint) => match constraint.rsplit_once('@') {
+            Some((constraint, flag)) if !flag.is_empty() => {
+                let constraint = constraint.trim();
+                let constraint = if constraint.is_empty() { "*" } else { constraint };
+                (Some(constraint.to_string()), Some(flag.to_lowercase()))
+            }
+            _ => (Some(constraint.to_string()), None),
+        },
+        None => (None, None),
+    };
+
+    let stability = match (stability, flag) {
+        (Some(stability), _) => stability.to_lowercase(),
+        (None, Some(flag)) => flag,
+        (None, None) => match &constraint {
+            Some(constraint) => get_version_stability(constraint),
+            None => "stable".to_string(),
+        },
+    };
+
+    (stability, constraint)
+}
+
+/// Parse `--repository` values into repository definitions.
+///
+/// Each value is a URL of a composer repository or JSON: a repository
+/// object, an array of them, or `{"packagist.org": false}` to disable
+/// Packagist. Returns the repositories and whether Packagist stays enabled.
+fn parse_repositories(values: &[String]) -> Result<(Vec<JsonRepository>, bool)> {
+    let mut repositories = Vec::new();
+    let mut packagist = true;
+
+    for value in values {
+        let json = match serde_json::from_str::<serde_json::Value>(value) {
+            Ok(json @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) => json,
+            _ => {
+                repositories.push(JsonRepository::Composer {
+                    url: value.clone(),
+                    options: Default::default(),
+                    signing_keys: Vec::new(),
+                    notify_on_install: false,
+                    filter: Default::default(),
+                });
+                continue;
+            }
+        };
+
+        let entries = match json {
+            serde_json::Value::Array(entries) => entries,
+            other => vec![other],
+        };
+
+        for entry in entries {
+            let disables_packagist = entry.as_object().is_some_and(|obj| {
+                ["packagist.org", "packagist"]
+                    .iter()
+                    .any(|key| obj.get(*key) == Some(&serde_json::Value::Bool(false)))
+            });
+            if disables_packagist {
+                packagist = false;
+                continue;
+            }
+
+            let repository: JsonRepository = serde_json::from_value(entry)
+                .with_context(|| format!("Invalid repository definition: {}", value))?;
+            repositories.push(repository);
+        }
+    }
+
+    Ok((repositories, packagist))
+}
+
 fn find_best_version(
     packages: &[Arc<Package>],
     version_constraint: Option<&str>,
@@ -207,9 +282,12 @@ fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
 
 pub async fn execute(args: CreateProjectArgs) -> Result<i32> {
     let (package_name, version_from_spec) = parse_package_spec(&args.package);
-    let version_constraint = args.version.as_deref().or(version_from_spec.as_deref());
-
-    let stability = args.stability.as_deref().unwrap_or("stable");
+    let (stability, version_constraint) = resolve_stability(
+        args.stability.as_deref(),
+        args.version.as_deref().or(version_from_spec.as_deref()),
+    );
+    let stability = stability.as_str();
+    let version_constraint = version_constraint.as_deref();
 
     let directory = args.directory.clone().unwrap_or_else(|| {
         package_name
@@ -245,11 +323,17 @@ pub async fn execute(args: CreateProjectArgs) -> Result<i32> {
 
     let config = Config::build(None::<&std::path::Path>, true)?;
 
-    let repo = if let Some(cache_dir) = &config.cache_dir {
-        ComposerRepository::packagist_with_cache(cache_dir.join("repo"))
-    } else {
-        ComposerRepository::packagist()
-    };
+    let (repositories, use_packagist) = parse_repositories(&args.repository)?;
+    let mut repo = RepositoryManager::new();
+    repo.add_from_json_repositories(&repositories);
+    if use_packagist {
+        let packagist = if let Some(cache_dir) = &config.cache_dir {
+            ComposerRepository::packagist_with_cache(cache_dir.join("repo"))
+        } else {
+            ComposerRepository::packagist()
+        };
+        repo.add_repository(Arc::new(packagist));
+    }
 
     let packages = repo.find_packages(&package_name).await;
 
@@ -285,12 +369,23 @@ pub async fn execute(args: CreateProjectArgs) -> Result<i32> {
 
     std::fs::create_dir_all(&target_dir)?;
 
-    let http_client = Arc::new(HttpClient::new().context("Failed to create HTTP client")?);
+    let http_client = Arc::new(
+        HttpClient::with_config(HttpClientConfig::new().with_proxy_config(config.proxy.clone()))
+            .context("Failed to create HTTP client")?,
+    );
     let download_config = DownloadConfig {
         prefer_source: args.prefer_source,
         prefer_dist: args.prefer_dist || !args.prefer_source,
+        preferred_install: Vec::new(),
+        verify: !args.no_verify,
+        signing_keys: Vec::new(),
+        secure_http: config.secure_http,
         cache_dir: config.cache_dir.clone().unwrap_or_else(|| PathBuf::from(".composer/cache")),
+        cache_vcs_dir: config.cache_vcs_dir.clone().filter(|_| !config.cache_read_only),
         vendor_dir: target_dir.clone(),
+        max_parallel_extractions: 1,
+        download_retries: config.download_retries,
+        installer_paths: Default::default(),
     };
     let download_manager = DownloadManager::new(http_client, download_config);
 
@@ -315,21 +410,14 @@ pub async fn execute(args: CreateProjectArgs) -> Result<i32> {
 
     println!("{} Created project in {}", style("Info:").cyan(), target_dir.display());
 
-    if !args.keep_vcs && !args.prefer_source {
+    if !args.keep_vcs {
+        let io = crate::io::io();
         let vcs_dirs = [".git", ".svn", ".hg", ".bzr", "_darcs", "CVS"];
         for vcs_dir in &vcs_dirs {
             let vcs_path = target_dir.join(vcs_dir);
             if vcs_path.exists() {
                 let should_remove = args.remove_vcs
-                    || args.no_interaction
-                    || {
-                        use dialoguer::Confirm;
-                        Confirm::new()
-                            .with_prompt("Do you want to remove the existing VCS (.git, .svn..) history?")
-                            .default(true)
-                            .interact()
-                            .unwrap_or(true)
-                    };
+                    || io.ask_confirmation("Do you want to remove the existing VCS (.git, .svn..) history?", true);
 
                 if should_remove {
                     std::fs::remove_dir_all(&vcs_path).ok();
@@ -338,15 +426,6 @@ pub async fn execute(args: CreateProjectArgs) -> Result<i32> {
         }
     }
 
-    if args.no_install {
-        println!(
-            "{} Skipping installation. Run 'pox install' in {} to install dependencies.",
-            style("Info:").cyan(),
-            directory
-        );
-        return Ok(0);
-    }
-
     let composer_json_path = target_dir.join("composer.json");
     if !composer_json_path.exists() {
         println!(
@@ -356,23 +435,49 @@ pub async fn execute(args: CreateProjectArgs) -> Result<i32> {
         return Ok(0);
     }
 
-    println!("{} Installing dependencies...", style("Info:").cyan());
-
     let json_content = std::fs::read_to_string(&composer_json_path)?;
     let composer_json: ComposerJson = serde_json::from_str(&json_content)?;
 
+    if !args.no_scripts {
+        let code = run_event_script("post-root-package-install", &composer_json, &target_dir, crate::io::io().is_quiet())?;
+        if code != 0 {
+            return Ok(code);
+        }
+    }
+
+    // Scripts may have changed the root package
+    let composer_json: ComposerJson = if args.no_scripts {
+        composer_json
+    } else {
+        serde_json::from_str(&std::fs::read_to_string(&composer_json_path)?)?
+    };
+
+    if args.no_install {
+        println!(
+            "{} Skipping installation. Run 'pox install' in {} to install dependencies.",
+            style("Info:").cyan(),
+            directory
+        );
+        return run_post_create_project(&args, &composer_json, &target_dir);
+    }
+
+    println!("{} Installing dependencies...", style("Info:").cyan());
+
     let project_config = Config::build(Some(&target_dir), true)?;
 
     let lock_path = target_dir.join("composer.lock");
     let has_lock = lock_path.exists();
 
-    let platform = PlatformInfo::detect();
+    let platform = PlatformInfo::detect_for(&target_dir);
 
     let mut builder = ComposerBuilder::new(target_dir.clone())
+        .with_io(crate::io::io())
+        .with_profiler(crate::io::profiler())
         .with_config(project_config)
         .with_composer_json(composer_json)
         .with_platform_packages(platform.to_packages())
-        .no_dev(args.no_dev);
+        .no_dev(args.no_dev)
+        .no_verify(args.no_verify);
 
     if args.prefer_source {
         builder = builder.prefer_source(true);
@@ -381,7 +486,8 @@ pub async fn execute(args: CreateProjectArgs) -> Result<i32> {
     }
 
     let composer = builder.build()?;
-    let installer = Installer::new(composer);
+    let composer_json = composer.composer_json.clone();
+    let installer = Installer::new(composer).audit(!args.no_audit);
 
     let result = if has_lock {
         installer
@@ -405,6 +511,16 @@ pub async fn execute(args: CreateProjectArgs) -> Result<i32> {
         }
     }
 
+    let code = result?;
+    if code != 0 {
+        return Ok(code);
+    }
+
+    let code = run_post_create_project(&args, &composer_json, &target_dir)?;
+    if code != 0 {
+        return Ok(code);
+    }
+
     println!(
         "\n{} Project {} successfully created in {}",
         style("Success:").green().bold(),
@@ -412,7 +528,20 @@ pub async fn execute(args: CreateProjectArgs) -> Result<i32> {
         style(&directory).cyan()
     );
 
-    result
+    Ok(0)
+}
+
+/// Run the `post-create-project-cmd` scripts unless scripts are disabled
+fn run_post_create_project(
+    args: &CreateProjectArgs,
+    composer_json: &ComposerJson,
+    target_dir: &std::path::Path,
+) -> Result<i32> {
+    if args.no_scripts {
+        return Ok(0);
+    }
+
+    run_event_script("post-create-project-cmd", composer_json, target_dir, crate::io::io().is_quiet())
 }
 
 #[cfg(test)]
@@ -440,6 +569,73 @@ mod tests {
         assert_eq!(version, None);
     }
 
+    #[test]
+    fn test_resolve_stability_defaults_to_stable() {
+        assert_eq!(resolve_stability(None, None), ("stable".to_string(), None));
+        assert_eq!(
+            resolve_stability(None, Some("^2.0")),
+            ("stable".to_string(), Some("^2.0".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_resolve_stability_from_flag() {
+        assert_eq!(
+            resolve_stability(None, Some("^2.0@beta")),
+            ("beta".to_string(), Some("^2.0".to_string()))
+        );
+        assert_eq!(
+            resolve_stability(None, Some("@dev")),
+            ("dev".to_string(), Some("*".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_resolve_stability_from_version() {
+        assert_eq!(
+            resolve_stability(None, Some("dev-main")),
+            ("dev".to_string(), Some("dev-main".to_string()))
+        );
+        assert_eq!(
+            resolve_stability(None, Some("2.0.0-RC1")),
+            ("rc".to_string(), Some("2.0.0-RC1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_resolve_stability_option_wins() {
+        assert_eq!(
+            resolve_stability(Some("alpha"), Some("^2.0@beta")),
+            ("alpha".to_string(), Some("^2.0".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_repositories_url() {
+        let (repos, packagist) = parse_repositories(&["https://repo.example.com".to_string()]).unwrap();
+        assert!(packagist);
+        assert_eq!(repos.len(), 1);
+        assert!(matches!(&repos[0], JsonRepository::Composer { url, .. } if url == "https://repo.example.com"));
+    }
+
+    #[test]
+    fn test_parse_repositories_json() {
+        let (repos, packagist) = parse_repositories(&[
+            r#"{"type": "vcs", "url": "git@example.com:acme/skeleton.git"}"#.to_string(),
+            r#"[{"type": "path", "url": "../skeleton"}, {"packagist.org": false}]"#.to_string(),
+        ])
+        .unwrap();
+        assert!(!packagist);
+        assert_eq!(repos.len(), 2);
+        assert!(matches!(&repos[0], JsonRepository::Vcs { .. }));
+        assert!(matches!(&repos[1], JsonRepository::Path { .. }));
+    }
+
+    #[test]
+    fn test_parse_repositories_invalid_json() {
+        assert!(parse_repositories(&[r#"{"type": "unknown"}"#.to_string()]).is_err());
+    }
+
     #[test]
     fn test_get_version_stability_stable() {
         assert_eq!(get_version_stability("1.0.0"), "stable");
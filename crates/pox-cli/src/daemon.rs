@@ -0,0 +1,858 @@
+//! Persistent daemon with a JSON-RPC control socket.
+//!
+//! `pox daemon` listens on a unix socket and answers JSON-RPC 2.0 requests,
+//! one JSON object per line, so editors and IDE plugins can run package
+//! operations without starting pox for each of them. Projects stay loaded
+//! between requests together with their repository metadata and detected
+//! platform, and development servers started through the socket keep running
+//! until they are stopped or the daemon exits.
+//!
+//! Methods: `ping`, `solve`, `install`, `show`, `audit`, `server.start`,
+//! `server.stop`, `server.list` and `shutdown`. Operations report progress as
+//! `$/progress` notifications carrying the request id, and a `$/cancelRequest`
+//! notification with that id cancels the request. Only `--max-operations`
+//! solves, installs and audits run at once, further ones wait for a slot.
+//!
+//! A cancelled request stops at its next await point. A project whose
+//! operation was cancelled midway is opened again by the next request, as
+//! its in-memory state may be half updated.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::rc::Rc;
+use std::time::SystemTime;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, Mutex, MutexGuard, Notify, Semaphore};
+use tokio::task::{spawn_local, LocalSet};
+use tokio_util::sync::CancellationToken;
+
+use pox_pm::config::{composer_file_name, lock_file_name};
+use pox_pm::facade::{InstallOptions, OperationReport, ProgressEvent, UpdateOptions};
+use pox_pm::{Io, Verbosity};
+use pox_pm::solver::Operation;
+use pox_pm::Composer;
+
+use crate::pm::audit::find_advisories;
+use crate::pm::platform::PlatformInfo;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+/// A method failed, e.g. dependencies could not be resolved
+const OPERATION_FAILED: i64 = -32000;
+/// Same code as the Language Server Protocol uses
+const REQUEST_CANCELLED: i64 = -32800;
+/// Longest request line, a client sending more is disconnected
+const MAX_LINE_LENGTH: usize = 1024 * 1024;
+
+#[derive(Args, Debug)]
+pub struct DaemonArgs {
+    /// Path of the control socket [default: $XDG_RUNTIME_DIR/pox.sock or ~/.pox/daemon.sock]
+    #[arg(long)]
+    pub socket: Option<PathBuf>,
+
+    /// Number of solve, install and audit requests running at the same time
+    #[arg(long, default_value = "2", value_parser = clap::value_parser!(u16).range(1..))]
+    pub max_operations: u16,
+}
+
+pub fn execute(args: DaemonArgs) -> Result<i32> {
+    let socket = match args.socket {
+        Some(socket) => socket,
+        None => default_socket_path().context("Could not determine the socket path, pass --socket")?,
+    };
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to create async runtime")?;
+
+    // Package operations hold non-Send futures, so everything runs on one thread
+    LocalSet::new().block_on(&rt, async move {
+        let listener = bind(&socket)?;
+        eprintln!("pox daemon listening on {}", socket.display());
+
+        let daemon = Rc::new(Daemon::new(args.max_operations as usize));
+        let result = daemon.serve(listener).await;
+        std::fs::remove_file(&socket).ok();
+        result.map(|_| 0)
+    })
+}
+
+fn default_socket_path() -> Option<PathBuf> {
+    if let Some(runtime_dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+        return Some(PathBuf::from(runtime_dir).join("pox.sock"));
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".pox").join("daemon.sock"))
+}
+
+/// Listen on `socket`, replacing a socket file left behind by a daemon that is gone
+fn bind(socket: &Path) -> Result<UnixListener> {
+    if let Ok(metadata) = std::fs::symlink_metadata(socket) {
+        use std::os::unix::fs::FileTypeExt;
+        if !metadata.file_type().is_socket() {
+            anyhow::bail!("{} exists and is not a socket", socket.display());
+        }
+        if std::os::unix::net::UnixStream::connect(socket).is_ok() {
+            anyhow::bail!("A daemon is already listening on {}", socket.display());
+        }
+        std::fs::remove_file(socket)
+            .with_context(|| format!("Failed to remove stale socket {}", socket.display()))?;
+    }
+    if let Some(parent) = socket.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let listener = UnixListener::bind(socket)
+        .with_context(|| format!("Failed to listen on {}", socket.display()))?;
+
+    // The socket can install packages and start servers, keep it to the current user
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+
+    Ok(listener)
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(error: anyhow::Error) -> Self {
+        Self::new(OPERATION_FAILED, format!("{:#}", error))
+    }
+}
+
+type RpcResult = std::result::Result<Value, RpcError>;
+
+fn response(id: Value, result: RpcResult) -> Value {
+    match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {"code": error.code, "message": error.message},
+        }),
+    }
+}
+
+fn params<T: DeserializeOwned>(params: Value) -> std::result::Result<T, RpcError> {
+    // A request without params is the same as one with empty params
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+/// Sends progress of one request to its client as `$/progress` notifications
+struct RpcIo {
+    id: Value,
+    tx: mpsc::UnboundedSender<Value>,
+}
+
+impl RpcIo {
+    fn notify(&self, event: Value) {
+        self.tx.send(json!({
+            "jsonrpc": "2.0",
+            "method": "$/progress",
+            "params": {"id": self.id, "event": event},
+        })).ok();
+    }
+}
+
+/// Requests are never interactive, warnings are the only output of the facade
+impl Io for RpcIo {
+    fn verbosity(&self) -> Verbosity {
+        Verbosity::Normal
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+
+    fn write_at(&self, message: &str, _verbosity: Verbosity) {
+        self.notify(json!({"type": "output", "message": message}));
+    }
+
+    fn write_error_at(&self, message: &str, _verbosity: Verbosity) {
+        self.notify(json!({"type": "warning", "message": message}));
+    }
+
+    fn ask(&self, _question: &str, default: Option<&str>) -> Option<String> {
+        default.map(String::from)
+    }
+
+    fn on_progress(&self, event: &ProgressEvent) {
+        self.notify(progress_json(event));
+    }
+}
+
+fn progress_json(event: &ProgressEvent) -> Value {
+    match event {
+        ProgressEvent::Resolving => json!({"type": "resolving"}),
+        ProgressEvent::MetadataLoading { done, total } => json!({"type": "metadata_loading", "done": done, "total": total}),
+        ProgressEvent::Solving { packages } => json!({"type": "solving", "packages": packages}),
+        ProgressEvent::Resolved { operations } => json!({"type": "resolved", "operations": operations}),
+        ProgressEvent::LockFileWritten { path } => json!({"type": "lock_file_written", "path": path}),
+        ProgressEvent::Installing { packages } => json!({"type": "installing", "packages": packages}),
+        ProgressEvent::Downloading { package, bytes, total } => {
+            json!({"type": "downloading", "package": package, "bytes": bytes, "total": total})
+        }
+        ProgressEvent::Extracting { package } => json!({"type": "extracting", "package": package}),
+        ProgressEvent::PackageInstalled { name, version } => {
+            json!({"type": "package_installed", "name": name, "version": version})
+        }
+        ProgressEvent::PackageUpdated { name, from, to } => {
+            json!({"type": "package_updated", "name": name, "from": from, "to": to})
+        }
+        ProgressEvent::PackageRemoved { name, version } => {
+            json!({"type": "package_removed", "name": name, "version": version})
+        }
+        ProgressEvent::GeneratingAutoload => json!({"type": "generating_autoload"}),
+        ProgressEvent::Finished => json!({"type": "finished"}),
+    }
+}
+
+fn report_json(report: &OperationReport) -> Value {
+    let operations: Vec<Value> = report
+        .transaction
+        .operations
+        .iter()
+        .filter_map(|operation| match operation {
+            Operation::Install(package) => Some(json!({
+                "type": "install",
+                "name": package.name,
+                "version": package.pretty_version(),
+            })),
+            Operation::Update { from, to } => Some(json!({
+                "type": "update",
+                "name": to.name,
+                "from": from.pretty_version(),
+                "to": to.pretty_version(),
+            })),
+            Operation::Uninstall(package) => Some(json!({
+                "type": "uninstall",
+                "name": package.name,
+                "version": package.pretty_version(),
+            })),
+            _ => None,
+        })
+        .collect();
+
+    json!({
+        "operations": operations,
+        "lock_file_changed": report.lock_file_changed,
+        "warnings": report.warnings,
+        "timings": {
+            "resolve_ms": report.timings.resolve.as_millis() as u64,
+            "install_ms": report.timings.install.as_millis() as u64,
+            "autoload_ms": report.timings.autoload.as_millis() as u64,
+            "total_ms": report.timings.total.as_millis() as u64,
+        },
+    })
+}
+
+/// Modification times of composer.json and composer.lock, to notice changes made outside the daemon
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stamp(Option<SystemTime>, Option<SystemTime>);
+
+impl Stamp {
+    fn read(working_dir: &Path) -> Self {
+        let modified = |file: String| std::fs::metadata(working_dir.join(file)).and_then(|m| m.modified()).ok();
+        Self(modified(composer_file_name()), modified(lock_file_name()))
+    }
+}
+
+/// A project kept open between requests
+struct Project {
+    working_dir: PathBuf,
+    composer: Composer,
+    stamp: Stamp,
+    /// An operation started and did not finish, e.g. because it was cancelled
+    stale: bool,
+}
+
+impl Project {
+    fn open(working_dir: &Path) -> Result<Self> {
+        let stamp = Stamp::read(working_dir);
+        let mut composer = Composer::open(working_dir)?;
+        composer.platform_packages = PlatformInfo::detect_for(working_dir).to_packages();
+        Ok(Self { working_dir: working_dir.to_path_buf(), composer, stamp, stale: false })
+    }
+
+    /// Wait for other operations on the project, then reload it if composer.json or
+    /// composer.lock changed or the previous operation was cancelled midway
+    async fn lock(project: &Mutex<Project>) -> Result<MutexGuard<'_, Project>> {
+        let mut guard = project.lock().await;
+        if guard.stale || guard.stamp != Stamp::read(&guard.working_dir) {
+            *guard = Project::open(&guard.working_dir)?;
+        }
+        Ok(guard)
+    }
+}
+
+/// A development server started through the socket
+struct ServerProcess {
+    child: Child,
+    url: String,
+}
+
+struct Daemon {
+    projects: RefCell<HashMap<PathBuf, Rc<Mutex<Project>>>>,
+    servers: RefCell<HashMap<PathBuf, ServerProcess>>,
+    operations: Semaphore,
+    shutdown: Notify,
+}
+
+#[derive(Deserialize)]
+struct ProjectParams {
+    working_dir: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct SolveParams {
+    working_dir: PathBuf,
+    /// Packages to update, all when missing
+    #[serde(default)]
+    packages: Option<Vec<String>>,
+    #[serde(default)]
+    no_dev: bool,
+    #[serde(default)]
+    prefer_lowest: bool,
+    #[serde(default)]
+    prefer_stable: bool,
+}
+
+#[derive(Deserialize)]
+struct InstallParams {
+    working_dir: PathBuf,
+    #[serde(default)]
+    no_dev: bool,
+    #[serde(default)]
+    dry_run: bool,
+    #[serde(default)]
+    no_scripts: bool,
+    #[serde(default)]
+    no_autoloader: bool,
+    #[serde(default)]
+    optimize_autoloader: bool,
+}
+
+#[derive(Deserialize)]
+struct ShowParams {
+    working_dir: PathBuf,
+    #[serde(default)]
+    package: Option<String>,
+    #[serde(default)]
+    no_dev: bool,
+}
+
+#[derive(Deserialize)]
+struct AuditParams {
+    working_dir: PathBuf,
+    #[serde(default)]
+    no_dev: bool,
+}
+
+#[derive(Deserialize)]
+struct ServerStartParams {
+    working_dir: PathBuf,
+    #[serde(default = "default_host")]
+    host: String,
+    #[serde(default = "default_port")]
+    port: u16,
+    /// Further `pox server` arguments, e.g. `["-t", "public"]`
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+fn default_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_port() -> u16 {
+    8000
+}
+
+impl Daemon {
+    fn new(max_operations: usize) -> Self {
+        Self {
+            projects: RefCell::new(HashMap::new()),
+            servers: RefCell::new(HashMap::new()),
+            operations: Semaphore::new(max_operations),
+            shutdown: Notify::new(),
+        }
+    }
+
+    async fn serve(self: &Rc<Self>, listener: UnixListener) -> Result<()> {
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    let (stream, _) = accepted.context("Failed to accept connection")?;
+                    spawn_local(self.clone().handle_connection(stream));
+                }
+                _ = self.shutdown.notified() => break,
+            }
+        }
+
+        // Servers are started with kill_on_drop
+        self.servers.borrow_mut().clear();
+        Ok(())
+    }
+
+    async fn handle_connection(self: Rc<Self>, stream: UnixStream) {
+        let (reader, mut writer) = stream.into_split();
+        let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+
+        // Responses of concurrent requests and their notifications share the socket
+        let writer_task = spawn_local(async move {
+            while let Some(message) = rx.recv().await {
+                let mut line = message.to_string();
+                line.push('\n');
+                if writer.write_all(line.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let in_flight: Rc<RefCell<HashMap<String, CancellationToken>>> = Rc::default();
+        let mut reader = BufReader::new(reader);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match (&mut reader).take(MAX_LINE_LENGTH as u64 + 1).read_until(b'\n', &mut line).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            if line.len() > MAX_LINE_LENGTH {
+                let message = format!("Request exceeds {} bytes", MAX_LINE_LENGTH);
+                tx.send(response(Value::Null, Err(RpcError::new(INVALID_REQUEST, message)))).ok();
+                break;
+            }
+
+            let line = String::from_utf8_lossy(&line);
+            if !line.trim().is_empty() {
+                self.handle_line(&line, &tx, &in_flight);
+            }
+        }
+
+        // Nobody is left to receive the results
+        for cancel in in_flight.borrow().values() {
+            cancel.cancel();
+        }
+        drop(tx);
+        writer_task.await.ok();
+    }
+
+    fn handle_line(
+        self: &Rc<Self>,
+        line: &str,
+        tx: &mpsc::UnboundedSender<Value>,
+        in_flight: &Rc<RefCell<HashMap<String, CancellationToken>>>,
+    ) {
+        let value: Value = match serde_json::from_str(line) {
+            Ok(value) => value,
+            Err(e) => {
+                tx.send(response(Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string())))).ok();
+                return;
+            }
+        };
+        let request: RpcRequest = match serde_json::from_value(value) {
+            Ok(request) => request,
+            Err(e) => {
+                tx.send(response(Value::Null, Err(RpcError::new(INVALID_REQUEST, e.to_string())))).ok();
+                return;
+            }
+        };
+
+        // Notifications have no id and get no response
+        let Some(id) = request.id else {
+            if request.method == "$/cancelRequest" {
+                if let Some(id) = request.params.get("id") {
+                    if let Some(cancel) = in_flight.borrow().get(&id.to_string()) {
+                        cancel.cancel();
+                    }
+                }
+            }
+            return;
+        };
+
+        // Cancellation and progress are addressed by id, so it must be unique among running requests
+        let key = id.to_string();
+        if in_flight.borrow().contains_key(&key) {
+            let message = format!("Request id {} is already in use", key);
+            tx.send(response(id, Err(RpcError::new(INVALID_REQUEST, message)))).ok();
+            return;
+        }
+        let cancel = CancellationToken::new();
+        in_flight.borrow_mut().insert(key.clone(), cancel.clone());
+
+        // The operation is dropped at its next await point once the request is cancelled
+        let io = RpcIo { id: id.clone(), tx: tx.clone() };
+        let operation = self.clone().dispatch(request.method, request.params, io);
+        let task = spawn_local(async move {
+            tokio::select! {
+                result = operation => result,
+                _ = cancel.cancelled() => Err(RpcError::new(REQUEST_CANCELLED, "Request cancelled")),
+            }
+        });
+
+        let tx = tx.clone();
+        let in_flight = in_flight.clone();
+        spawn_local(async move {
+            let result = task.await.unwrap_or_else(|e| Err(RpcError::new(INTERNAL_ERROR, e.to_string())));
+            in_flight.borrow_mut().remove(&key);
+            tx.send(response(id, result)).ok();
+        });
+    }
+
+    async fn dispatch(self: Rc<Self>, method: String, params: Value, io: RpcIo) -> RpcResult {
+        match method.as_str() {
+            "ping" => Ok(json!({"version": env!("CARGO_PKG_VERSION")})),
+            "solve" => self.solve(self::params(params)?, &io).await,
+            "install" => self.install(self::params(params)?, &io).await,
+            "show" => self.show(self::params(params)?).await,
+            "audit" => self.audit(self::params(params)?).await,
+            "server.start" => self.start_server(self::params(params)?),
+            "server.stop" => self.stop_server(self::params(params)?).await,
+            "server.list" => Ok(self.list_servers()),
+            "shutdown" => {
+                self.shutdown.notify_one();
+                Ok(Value::Null)
+            }
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method \"{}\" not found", method))),
+        }
+    }
+
+    /// The project in `working_dir`, opened on first use
+    fn project(&self, working_dir: &Path) -> Result<Rc<Mutex<Project>>> {
+        let working_dir = working_dir
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve working directory {}", working_dir.display()))?;
+
+        let existing = self.projects.borrow().get(&working_dir).cloned();
+        if let Some(project) = existing {
+            return Ok(project);
+        }
+
+        let project = Rc::new(Mutex::new(Project::open(&working_dir)?));
+        self.projects.borrow_mut().insert(working_dir, project.clone());
+        Ok(project)
+    }
+
+    async fn solve(&self, params: SolveParams, io: &RpcIo) -> RpcResult {
+        let _permit = self.operations.acquire().await.map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))?;
+        let project = self.project(&params.working_dir)?;
+        let mut project = Project::lock(&project).await?;
+
+        let options = UpdateOptions {
+            install: InstallOptions {
+                no_dev: params.no_dev,
+                dry_run: true,
+                no_scripts: true,
+                ..Default::default()
+            },
+            prefer_lowest: params.prefer_lowest,
+            prefer_stable: params.prefer_stable,
+            ..Default::default()
+        };
+        // Left set when the update is cancelled midway
+        project.stale = true;
+        let report = project.composer.update(params.packages, &options, io).await;
+        project.stale = false;
+        Ok(report_json(&report?))
+    }
+
+    async fn install(&self, params: InstallParams, io: &RpcIo) -> RpcResult {
+        let _permit = self.operations.acquire().await.map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))?;
+        let project = self.project(&params.working_dir)?;
+        let mut project = Project::lock(&project).await?;
+
+        let options = InstallOptions {
+            no_dev: params.no_dev,
+            dry_run: params.dry_run,
+            no_scripts: params.no_scripts,
+            no_autoloader: params.no_autoloader,
+            optimize_autoloader: params.optimize_autoloader,
+            ..Default::default()
+        };
+        project.stale = true;
+        let report = project.composer.install(&options, io).await;
+        project.stale = false;
+        Ok(report_json(&report?))
+    }
+
+    async fn show(&self, params: ShowParams) -> RpcResult {
+        let project = self.project(&params.working_dir)?;
+        let project = Project::lock(&project).await?;
+        let lock = project
+            .composer
+            .composer_lock
+            .as_ref()
+            .ok_or_else(|| RpcError::new(OPERATION_FAILED, "No composer.lock found. Run 'install' or 'update' first."))?;
+
+        let mut packages: Vec<(&pox_pm::json::LockedPackage, bool)> = lock.packages.iter().map(|p| (p, false)).collect();
+        if !params.no_dev {
+            packages.extend(lock.packages_dev.iter().map(|p| (p, true)));
+        }
+
+        if let Some(name) = params.package {
+            let (package, dev) = packages
+                .into_iter()
+                .find(|(p, _)| p.name.eq_ignore_ascii_case(&name))
+                .ok_or_else(|| RpcError::new(OPERATION_FAILED, format!("Package \"{}\" not found", name)))?;
+
+            let mut value = serde_json::to_value(package).map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))?;
+            value["dev"] = json!(dev);
+            return Ok(value);
+        }
+
+        packages.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+        let packages: Vec<Value> = packages
+            .into_iter()
+            .map(|(p, dev)| json!({"name": p.name, "version": p.version, "description": p.description, "dev": dev}))
+            .collect();
+        Ok(json!({"packages": packages}))
+    }
+
+    async fn audit(&self, params: AuditParams) -> RpcResult {
+        let _permit = self.operations.acquire().await.map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))?;
+        let project = self.project(&params.working_dir)?;
+        let project = Project::lock(&project).await?;
+        let lock = project
+            .composer
+            .composer_lock
+            .as_ref()
+            .ok_or_else(|| RpcError::new(OPERATION_FAILED, "No composer.lock found. Run 'install' or 'update' first."))?;
+
+        let mut packages = lock.packages.clone();
+        if !params.no_dev {
+            packages.extend(lock.packages_dev.iter().cloned());
+        }
+
+        let versions: HashMap<String, String> = packages.iter().map(|p| (p.name.clone(), p.version.clone())).collect();
+        let advisories = if versions.is_empty() {
+            HashMap::new()
+        } else {
+            find_advisories(&project.composer.config, &versions).await?
+        };
+        let abandoned: HashMap<&str, Option<&str>> = packages
+            .iter()
+            .filter(|p| p.is_abandoned())
+            .map(|p| (p.name.as_str(), p.abandoned_replacement()))
+            .collect();
+
+        Ok(json!({"advisories": advisories, "abandoned": abandoned}))
+    }
+
+    fn start_server(&self, params: ServerStartParams) -> RpcResult {
+        let working_dir = params
+            .working_dir
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve working directory {}", params.working_dir.display()))?;
+
+        self.reap_servers();
+        if let Some(server) = self.servers.borrow().get(&working_dir) {
+            return Err(RpcError::new(OPERATION_FAILED, format!("A server is already running at {}", server.url)));
+        }
+
+        let child = Command::new(std::env::current_exe().context("Failed to locate the pox binary")?)
+            .arg("server")
+            .args(["--host", &params.host, "--port", &params.port.to_string()])
+            .args(&params.args)
+            .current_dir(&working_dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to start the server")?;
+
+        let url = format!("http://{}:{}", params.host, params.port);
+        let result = json!({"pid": child.id(), "url": url});
+        self.servers.borrow_mut().insert(working_dir, ServerProcess { child, url });
+        Ok(result)
+    }
+
+    async fn stop_server(&self, params: ProjectParams) -> RpcResult {
+        let working_dir = params.working_dir.canonicalize().unwrap_or(params.working_dir);
+        let Some(mut server) = self.servers.borrow_mut().remove(&working_dir) else {
+            return Err(RpcError::new(OPERATION_FAILED, format!("No server running for {}", working_dir.display())));
+        };
+
+        server.child.kill().await.ok();
+        Ok(json!({"url": server.url}))
+    }
+
+    fn list_servers(&self) -> Value {
+        self.reap_servers();
+        let servers: Vec<Value> = self
+            .servers
+            .borrow()
+            .iter()
+            .map(|(working_dir, server)| json!({"working_dir": working_dir, "url": server.url, "pid": server.child.id()}))
+            .collect();
+        json!({"servers": servers})
+    }
+
+    /// Forget servers that exited on their own, e.g. because the port was taken
+    fn reap_servers(&self) {
+        self.servers
+            .borrow_mut()
+            .retain(|_, server| matches!(server.child.try_wait(), Ok(None)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn connect(daemon: Rc<Daemon>) -> (tempfile::TempDir, tokio::io::Lines<BufReader<tokio::net::unix::OwnedReadHalf>>, tokio::net::unix::OwnedWriteHalf) {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("pox.sock");
+        let listener = bind(&socket).unwrap();
+        spawn_local(async move { daemon.serve(listener).await.unwrap() });
+
+        let (reader, writer) = UnixStream::connect(&socket).await.unwrap().into_split();
+        (dir, BufReader::new(reader).lines(), writer)
+    }
+
+    async fn send(writer: &mut tokio::net::unix::OwnedWriteHalf, message: &str) {
+        writer.write_all(format!("{}\n", message).as_bytes()).await.unwrap();
+    }
+
+    async fn receive(lines: &mut tokio::io::Lines<BufReader<tokio::net::unix::OwnedReadHalf>>) -> Value {
+        serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_requests() {
+        LocalSet::new().run_until(async {
+            let (_dir, mut lines, mut writer) = connect(Rc::new(Daemon::new(1))).await;
+
+            send(&mut writer, r#"{"jsonrpc": "2.0", "id": 1, "method": "ping"}"#).await;
+            let ping = receive(&mut lines).await;
+            assert_eq!(ping["id"], 1);
+            assert_eq!(ping["result"]["version"], env!("CARGO_PKG_VERSION"));
+
+            send(&mut writer, r#"{"jsonrpc": "2.0", "id": 2, "method": "frobnicate"}"#).await;
+            assert_eq!(receive(&mut lines).await["error"]["code"], METHOD_NOT_FOUND);
+
+            send(&mut writer, r#"{"jsonrpc": "2.0", "id": 3, "method": "show", "params": {}}"#).await;
+            assert_eq!(receive(&mut lines).await["error"]["code"], INVALID_PARAMS);
+
+            send(&mut writer, "{not json").await;
+            assert_eq!(receive(&mut lines).await["error"]["code"], PARSE_ERROR);
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn test_show() {
+        LocalSet::new().run_until(async {
+            let project = tempfile::tempdir().unwrap();
+            std::fs::write(project.path().join("composer.json"), r#"{"require": {"acme/lib": "^1.0"}}"#).unwrap();
+            std::fs::write(
+                project.path().join("composer.lock"),
+                r#"{"packages": [{"name": "acme/lib", "version": "1.2.0"}], "packages-dev": [{"name": "acme/test", "version": "2.0.0"}]}"#,
+            ).unwrap();
+
+            let (_dir, mut lines, mut writer) = connect(Rc::new(Daemon::new(1))).await;
+            let request = json!({"jsonrpc": "2.0", "id": "a", "method": "show", "params": {"working_dir": project.path()}});
+            send(&mut writer, &request.to_string()).await;
+
+            let show = receive(&mut lines).await;
+            let names: Vec<&str> = show["result"]["packages"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|p| p["name"].as_str().unwrap())
+                .collect();
+            assert_eq!(names, vec!["acme/lib", "acme/test"]);
+            assert_eq!(show["result"]["packages"][1]["dev"], true);
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn test_cancel_waiting_request() {
+        LocalSet::new().run_until(async {
+            // Without free operation slots the solve waits until it is cancelled
+            let (_dir, mut lines, mut writer) = connect(Rc::new(Daemon::new(0))).await;
+
+            send(&mut writer, r#"{"jsonrpc": "2.0", "id": 7, "method": "solve", "params": {"working_dir": "."}}"#).await;
+            send(&mut writer, r#"{"jsonrpc": "2.0", "id": 8, "method": "ping"}"#).await;
+            assert_eq!(receive(&mut lines).await["id"], 8);
+
+            send(&mut writer, r#"{"jsonrpc": "2.0", "method": "$/cancelRequest", "params": {"id": 7}}"#).await;
+            let cancelled = receive(&mut lines).await;
+            assert_eq!(cancelled["id"], 7);
+            assert_eq!(cancelled["error"]["code"], REQUEST_CANCELLED);
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_request_id() {
+        LocalSet::new().run_until(async {
+            let (_dir, mut lines, mut writer) = connect(Rc::new(Daemon::new(0))).await;
+
+            send(&mut writer, r#"{"jsonrpc": "2.0", "id": 7, "method": "solve", "params": {"working_dir": "."}}"#).await;
+            send(&mut writer, r#"{"jsonrpc": "2.0", "id": 7, "method": "ping"}"#).await;
+            let duplicate = receive(&mut lines).await;
+            assert_eq!(duplicate["id"], 7);
+            assert_eq!(duplicate["error"]["code"], INVALID_REQUEST);
+
+            // The first request is still the one the id refers to
+            send(&mut writer, r#"{"jsonrpc": "2.0", "method": "$/cancelRequest", "params": {"id": 7}}"#).await;
+            assert_eq!(receive(&mut lines).await["error"]["code"], REQUEST_CANCELLED);
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn test_overlong_line_disconnects() {
+        LocalSet::new().run_until(async {
+            let (_dir, mut lines, mut writer) = connect(Rc::new(Daemon::new(1))).await;
+
+            let line = format!(r#"{{"jsonrpc": "2.0", "id": 1, "method": "ping", "params": "{}"}}"#, "x".repeat(MAX_LINE_LENGTH));
+            send(&mut writer, &line).await;
+            assert_eq!(receive(&mut lines).await["error"]["code"], INVALID_REQUEST);
+            assert!(lines.next_line().await.unwrap().is_none());
+        }).await;
+    }
+
+    #[test]
+    fn test_bind_keeps_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pox.sock");
+        std::fs::write(&path, "not a socket").unwrap();
+
+        let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let result = rt.block_on(async { bind(&path).map(|_| ()) });
+        assert!(result.unwrap_err().to_string().contains("is not a socket"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not a socket");
+    }
+}
@@ -0,0 +1,161 @@
+//! Request tracing and Xdebug integration for server mode.
+//!
+//! Every PHP request gets a trace id, passed to PHP and returned in the
+//! `X-Request-Id` response header. With `-vv` the time spent in the Rust
+//! request handling and in PHP is logged per request.
+
+use pox_embed::{HttpResponse, PhpError};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Header carrying the trace id, an incoming id is kept
+pub const TRACE_HEADER: &str = "X-Request-Id";
+
+/// Xdebug session key used as trigger value
+pub const XDEBUG_SESSION: &str = "pox";
+
+/// INI entries that let Xdebug connect to the IDE when triggered
+pub fn xdebug_ini_entries() -> Vec<String> {
+    vec![
+        "xdebug.mode=debug".to_string(),
+        "xdebug.start_with_request=trigger".to_string(),
+    ]
+}
+
+/// Set the Xdebug environment for the PHP runtime, must run before PHP starts
+pub fn set_xdebug_env() {
+    if std::env::var_os("XDEBUG_MODE").is_none() {
+        std::env::set_var("XDEBUG_MODE", "debug");
+    }
+    if std::env::var_os("XDEBUG_SESSION").is_none() {
+        std::env::set_var("XDEBUG_SESSION", XDEBUG_SESSION);
+    }
+}
+
+/// Add the `XDEBUG_SESSION` trigger cookie unless the request sets one
+pub fn inject_xdebug_trigger(headers: &mut Vec<(String, String)>) {
+    let cookie = format!("XDEBUG_SESSION={}", XDEBUG_SESSION);
+
+    match headers.iter_mut().find(|(k, _)| k.eq_ignore_ascii_case("cookie")) {
+        Some((_, value)) if value.contains("XDEBUG_SESSION=") => {}
+        Some((_, value)) => {
+            value.push_str("; ");
+            value.push_str(&cookie);
+        }
+        None => headers.push(("Cookie".to_string(), cookie)),
+    }
+}
+
+/// Timing of a single PHP request
+pub struct RequestTrace {
+    id: String,
+    started: Instant,
+    php: Cell<Duration>,
+}
+
+impl RequestTrace {
+    /// Start tracing a request, adding the trace id to its headers
+    pub fn start(headers: &mut Vec<(String, String)>) -> Self {
+        let id = match headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(TRACE_HEADER)) {
+            Some((_, id)) => id.clone(),
+            None => {
+                let id = generate_id();
+                headers.push((TRACE_HEADER.to_string(), id.clone()));
+                id
+            }
+        };
+
+        Self {
+            id,
+            started: Instant::now(),
+            php: Cell::new(Duration::ZERO),
+        }
+    }
+
+    /// Run PHP, counting the time towards the PHP execution
+    pub fn time_php<T>(&self, execute: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = execute();
+        self.php.set(self.php.get() + started.elapsed());
+        result
+    }
+
+    /// Add the trace id to the response and log the timing breakdown
+    pub fn finish(self, result: &mut Result<HttpResponse, PhpError>, method: &str, url: &str) {
+        let status = match result {
+            Ok(response) => {
+                response.headers.push((TRACE_HEADER.to_string(), self.id.clone()));
+                response.status
+            }
+            Err(e) => crate::metrics::error_status(e),
+        };
+
+        let total = self.started.elapsed();
+        let php = self.php.get();
+        log::debug!(
+            "[{}] {} {} - {} (routing {:.2}ms, php {:.2}ms, total {:.2}ms)",
+            self.id,
+            method,
+            url,
+            status,
+            millis(total.saturating_sub(php)),
+            millis(php),
+            millis(total)
+        );
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// A process unique id: start time of the server plus a request counter
+fn generate_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    static EPOCH: std::sync::OnceLock<u64> = std::sync::OnceLock::new();
+
+    let epoch = *EPOCH.get_or_init(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    });
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    format!("{:x}-{:06x}", epoch, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_keeps_incoming_id() {
+        let mut headers = vec![("x-request-id".to_string(), "abc".to_string())];
+        let trace = RequestTrace::start(&mut headers);
+        assert_eq!(trace.id, "abc");
+        assert_eq!(headers.len(), 1);
+
+        let mut headers = Vec::new();
+        let first = RequestTrace::start(&mut headers);
+        let second = RequestTrace::start(&mut Vec::new());
+        assert_ne!(first.id, second.id);
+        assert_eq!(headers, vec![(TRACE_HEADER.to_string(), first.id.clone())]);
+    }
+
+    #[test]
+    fn test_inject_xdebug_trigger() {
+        let mut headers = Vec::new();
+        inject_xdebug_trigger(&mut headers);
+        assert_eq!(headers, vec![("Cookie".to_string(), "XDEBUG_SESSION=pox".to_string())]);
+
+        let mut headers = vec![("cookie".to_string(), "a=1".to_string())];
+        inject_xdebug_trigger(&mut headers);
+        assert_eq!(headers[0].1, "a=1; XDEBUG_SESSION=pox");
+
+        let mut headers = vec![("Cookie".to_string(), "XDEBUG_SESSION=PHPSTORM".to_string())];
+        inject_xdebug_trigger(&mut headers);
+        assert_eq!(headers[0].1, "XDEBUG_SESSION=PHPSTORM");
+    }
+}
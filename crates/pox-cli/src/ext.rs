@@ -0,0 +1,482 @@
+//! Ext command - manage shared PHP extensions of a project.
+//!
+//! Extensions live in `.pox/extensions` of the project, next to an
+//! `extensions.json` manifest. Enabled extensions are loaded through generated
+//! `extension` INI entries whenever pox runs PHP in the project, and are
+//! reported to the solver as `ext-*` platform packages.
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use console::style;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use pox_pm::downloader::ArchiveExtractor;
+use pox_pm::http::HttpClient;
+use pox_pm::ComposerError;
+
+/// Extension directory, relative to the project root
+pub const EXTENSIONS_DIR: &str = ".pox/extensions";
+
+/// Manifest of the extension directory
+const MANIFEST_FILE: &str = "extensions.json";
+
+/// Where PECL source releases are downloaded from
+const PECL_URL: &str = "https://pecl.php.net/get";
+
+#[derive(Subcommand, Debug)]
+pub enum ExtCommands {
+    /// List the extensions of the project
+    List,
+    /// Download, compile and enable a PECL extension (e.g. redis or redis@6.0.2)
+    Install(InstallArgs),
+    /// Enable an extension of the project, or add and enable an already built .so file
+    Enable(EnableArgs),
+    /// Disable an extension without removing it
+    Disable(NameArgs),
+    /// Remove an extension from the project
+    Remove(NameArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct InstallArgs {
+    /// PECL package, optionally with a version: name[@version]
+    #[arg(value_name = "EXTENSION")]
+    pub extension: String,
+
+    /// php-config of a PHP installation matching the embedded PHP version
+    #[arg(long, value_name = "PATH", default_value = "php-config")]
+    pub php_config: String,
+
+    /// phpize of the same PHP installation
+    #[arg(long, value_name = "PATH", default_value = "phpize")]
+    pub phpize: String,
+
+    /// Extra arguments passed to ./configure
+    #[arg(long = "configure-option", value_name = "OPTION", allow_hyphen_values = true)]
+    pub configure_options: Vec<String>,
+
+    /// Load as zend_extension (e.g. xdebug)
+    #[arg(long)]
+    pub zend: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct EnableArgs {
+    /// Extension name, or path to a shared extension (.so) to add
+    #[arg(value_name = "EXTENSION")]
+    pub extension: String,
+
+    /// Load as zend_extension (e.g. xdebug)
+    #[arg(long)]
+    pub zend: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct NameArgs {
+    /// Extension name
+    #[arg(value_name = "EXTENSION")]
+    pub extension: String,
+}
+
+/// An extension of the project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtensionEntry {
+    /// File name of the shared object inside the extension directory
+    pub file: String,
+    /// Version of the extension, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub enabled: bool,
+    /// Loaded with `zend_extension` instead of `extension`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub zend: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    extensions: IndexMap<String, ExtensionEntry>,
+}
+
+/// The extension directory of a project
+#[derive(Debug)]
+pub struct ProjectExtensions {
+    dir: PathBuf,
+    manifest: Manifest,
+}
+
+impl ProjectExtensions {
+    /// Load the extensions of the project in `project_dir`, empty if it has none
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let dir = project_dir.join(EXTENSIONS_DIR);
+        let manifest_path = dir.join(MANIFEST_FILE);
+
+        let manifest = if manifest_path.exists() {
+            let content = std::fs::read_to_string(&manifest_path)
+                .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", manifest_path.display()))?
+        } else {
+            Manifest::default()
+        };
+
+        Ok(Self { dir, manifest })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut content = serde_json::to_string_pretty(&self.manifest)?;
+        content.push('\n');
+        std::fs::write(self.dir.join(MANIFEST_FILE), content)?;
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ExtensionEntry)> {
+        self.manifest.extensions.iter()
+    }
+
+    /// The enabled extensions
+    pub fn enabled(&self) -> impl Iterator<Item = (&String, &ExtensionEntry)> {
+        self.iter().filter(|(_, entry)| entry.enabled)
+    }
+
+    /// Copy a built shared extension into the extension directory and enable it
+    pub fn add(&mut self, name: &str, source: &Path, version: Option<String>, zend: bool) -> Result<()> {
+        let name = normalize_name(name);
+        let file = format!("{}.so", name);
+
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::copy(source, self.dir.join(&file))
+            .with_context(|| format!("Failed to copy {}", source.display()))?;
+
+        self.manifest.extensions.insert(name, ExtensionEntry { file, version, enabled: true, zend });
+        Ok(())
+    }
+
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<()> {
+        let entry = self.manifest.extensions.get_mut(&normalize_name(name))
+            .with_context(|| format!("Extension \"{}\" is not part of the project, see `pox ext list`", name))?;
+        entry.enabled = enabled;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<()> {
+        let entry = self.manifest.extensions.shift_remove(&normalize_name(name))
+            .with_context(|| format!("Extension \"{}\" is not part of the project, see `pox ext list`", name))?;
+
+        let path = self.dir.join(&entry.file);
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// INI entries loading the enabled extensions
+    pub fn ini_entries(&self) -> Vec<String> {
+        let dir = std::path::absolute(&self.dir).unwrap_or_else(|_| self.dir.clone());
+
+        let mut entries = Vec::new();
+        if self.enabled().any(|(_, entry)| !entry.zend) {
+            entries.push(format!("extension_dir={}", dir.display()));
+        }
+        for (_, entry) in self.enabled() {
+            if entry.zend {
+                entries.push(format!("zend_extension={}", dir.join(&entry.file).display()));
+            } else {
+                entries.push(format!("extension={}", entry.file));
+            }
+        }
+        entries
+    }
+}
+
+/// INI entries loading the enabled extensions of the project in `project_dir`
+pub fn ini_entries(project_dir: &Path) -> Vec<String> {
+    match ProjectExtensions::load(project_dir) {
+        Ok(extensions) => extensions.ini_entries(),
+        Err(e) => {
+            log::warn!("Ignoring project extensions: {:#}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Names and versions of the enabled extensions of the project in `project_dir`
+pub fn enabled_extensions(project_dir: &Path) -> Vec<(String, Option<String>)> {
+    ProjectExtensions::load(project_dir)
+        .map(|extensions| {
+            extensions.enabled()
+                .map(|(name, entry)| (name.clone(), entry.version.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Extension names are matched case-insensitively, with or without `ext-` prefix
+fn normalize_name(name: &str) -> String {
+    let name = name.to_lowercase();
+    name.strip_prefix("ext-").map(str::to_string).unwrap_or(name)
+}
+
+/// Split `name@version` into its parts
+fn parse_extension_spec(spec: &str) -> (String, Option<String>) {
+    match spec.split_once('@') {
+        Some((name, version)) if !version.is_empty() => (normalize_name(name), Some(version.to_string())),
+        _ => (normalize_name(spec.trim_end_matches('@')), None),
+    }
+}
+
+/// Execute an ext command for the project in `working_dir`
+pub async fn execute(command: ExtCommands, working_dir: &Path) -> Result<i32> {
+    let project_dir = working_dir
+        .canonicalize()
+        .context("Failed to resolve working directory")?;
+
+    match command {
+        ExtCommands::List => list(&project_dir),
+        ExtCommands::Install(args) => install(&project_dir, args).await,
+        ExtCommands::Enable(args) => enable(&project_dir, args),
+        ExtCommands::Disable(args) => {
+            let mut extensions = ProjectExtensions::load(&project_dir)?;
+            extensions.set_enabled(&args.extension, false)?;
+            extensions.save()?;
+            println!("{} Disabled {}", style("Success:").green().bold(), normalize_name(&args.extension));
+            Ok(0)
+        }
+        ExtCommands::Remove(args) => {
+            let mut extensions = ProjectExtensions::load(&project_dir)?;
+            extensions.remove(&args.extension)?;
+            extensions.save()?;
+            println!("{} Removed {}", style("Success:").green().bold(), normalize_name(&args.extension));
+            Ok(0)
+        }
+    }
+}
+
+fn list(project_dir: &Path) -> Result<i32> {
+    let extensions = ProjectExtensions::load(project_dir)?;
+    if extensions.iter().next().is_none() {
+        println!("{} No project extensions, add one with `pox ext install <name>`", style("Info:").cyan());
+        return Ok(0);
+    }
+
+    let width = extensions.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, entry) in extensions.iter() {
+        let status = if entry.enabled { style("enabled").green() } else { style("disabled").yellow() };
+        let kind = if entry.zend { " (zend_extension)" } else { "" };
+        println!(
+            "{:width$}  {:10}  {}{}",
+            style(name).white().bold(),
+            entry.version.as_deref().unwrap_or("-"),
+            status,
+            kind,
+            width = width
+        );
+    }
+    Ok(0)
+}
+
+fn enable(project_dir: &Path, args: EnableArgs) -> Result<i32> {
+    let mut extensions = ProjectExtensions::load(project_dir)?;
+
+    let path = Path::new(&args.extension);
+    let name = if path.extension().is_some_and(|ext| ext == "so") {
+        if !path.is_file() {
+            bail!("Shared extension {} does not exist", path.display());
+        }
+        let name = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        extensions.add(&name, path, None, args.zend)?;
+        normalize_name(&name)
+    } else {
+        extensions.set_enabled(&args.extension, true)?;
+        normalize_name(&args.extension)
+    };
+
+    extensions.save()?;
+    println!("{} Enabled {}", style("Success:").green().bold(), name);
+    Ok(0)
+}
+
+async fn install(project_dir: &Path, args: InstallArgs) -> Result<i32> {
+    let (name, version) = parse_extension_spec(&args.extension);
+
+    check_php_config(&args.php_config)?;
+
+    let url = match &version {
+        Some(version) => format!("{}/{}-{}", PECL_URL, name, version),
+        None => format!("{}/{}", PECL_URL, name),
+    };
+
+    println!("{} Downloading {}", style("Info:").cyan(), url);
+    let client = HttpClient::new().context("Failed to create HTTP client")?;
+    let archive = client.download_bytes(&url).await
+        .map_err(|e| ComposerError::from_http(&url, e))?;
+
+    let build_dir = tempfile::tempdir().context("Failed to create build directory")?;
+    let source_dir = extract_source(&archive, build_dir.path(), &name)
+        .with_context(|| format!("Failed to extract {}", url))?;
+    let version = version.or_else(|| {
+        source_dir.file_name()
+            .and_then(|dir| dir.to_string_lossy().strip_prefix(&format!("{}-", name)).map(str::to_string))
+    });
+
+    println!("{} Compiling {} {}", style("Info:").cyan(), name, version.as_deref().unwrap_or(""));
+    run_build_step(Command::new(&args.phpize).current_dir(&source_dir), "phpize")?;
+    run_build_step(
+        Command::new("./configure")
+            .arg(format!("--with-php-config={}", args.php_config))
+            .args(&args.configure_options)
+            .current_dir(&source_dir),
+        "configure",
+    )?;
+    let jobs = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    run_build_step(Command::new("make").arg(format!("-j{}", jobs)).current_dir(&source_dir), "make")?;
+
+    let built = source_dir.join("modules").join(format!("{}.so", name));
+    if !built.is_file() {
+        bail!("Compiling {} did not produce {}", name, built.display());
+    }
+
+    let mut extensions = ProjectExtensions::load(project_dir)?;
+    extensions.add(&name, &built, version.clone(), args.zend)?;
+    extensions.save()?;
+
+    println!(
+        "{} Installed and enabled {} {}",
+        style("Success:").green().bold(),
+        name,
+        version.as_deref().unwrap_or("")
+    );
+    Ok(0)
+}
+
+/// Extensions only load into the PHP version they were compiled against
+fn check_php_config(php_config: &str) -> Result<()> {
+    let output = Command::new(php_config).arg("--version").output()
+        .with_context(|| format!("Failed to run {}, pass --php-config of a PHP installation with development headers", php_config))?;
+    let build_version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let embedded = pox_embed::Php::version();
+    if minor_version(&build_version) != minor_version(embedded.version) {
+        bail!(
+            "{} is PHP {}, but pox embeds PHP {}; pass --php-config of a matching PHP installation",
+            php_config,
+            build_version,
+            embedded.version
+        );
+    }
+    Ok(())
+}
+
+fn minor_version(version: &str) -> Option<&str> {
+    let mut dots = version.match_indices('.');
+    dots.next()?;
+    match dots.next() {
+        Some((pos, _)) => Some(&version[..pos]),
+        None => Some(version),
+    }
+}
+
+/// Unpack a PECL release, `package.xml` next to a `<name>-<version>` source directory
+fn extract_source(archive: &[u8], dir: &Path, name: &str) -> Result<PathBuf> {
+    ArchiveExtractor::extract_tar_with_strip(flate2::read::GzDecoder::new(archive), dir, 0)?;
+    find_source_dir(dir, name).with_context(|| format!("The archive contains no {}-<version> directory", name))
+}
+
+fn find_source_dir(dir: &Path, name: &str) -> Option<PathBuf> {
+    let prefix = format!("{}-", name);
+    std::fs::read_dir(dir).ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.is_dir()
+                && path.file_name().is_some_and(|dir| dir.to_string_lossy().to_lowercase().starts_with(&prefix))
+        })
+}
+
+/// Run one step of an extension build, showing its output with -v
+fn run_build_step(command: &mut Command, step: &str) -> Result<()> {
+    if !crate::io::io().is_verbose() {
+        command.stdout(Stdio::null());
+    }
+
+    let status = command.status().with_context(|| format!("Failed to run {}", step))?;
+    if !status.success() {
+        bail!("{} failed with {}, run with -v to see its output", step, status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_extension_spec() {
+        assert_eq!(parse_extension_spec("redis"), ("redis".to_string(), None));
+        assert_eq!(parse_extension_spec("Redis@6.0.2"), ("redis".to_string(), Some("6.0.2".to_string())));
+        assert_eq!(parse_extension_spec("ext-apcu@"), ("apcu".to_string(), None));
+        assert_eq!(minor_version("8.4.1"), Some("8.4"));
+        assert_eq!(minor_version("8.4.0RC1"), Some("8.4"));
+    }
+
+    #[test]
+    fn test_extract_source() {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+        for (path, contents) in [("package.xml", "<package/>"), ("redis-6.0.2/config.m4", "PHP_ARG_ENABLE(redis)")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, path, contents.as_bytes()).unwrap();
+        }
+        let archive = builder.into_inner().unwrap().finish().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let source_dir = extract_source(&archive, dir.path(), "redis").unwrap();
+        assert_eq!(source_dir, dir.path().join("redis-6.0.2"));
+        assert!(source_dir.join("config.m4").is_file());
+        assert!(extract_source(&archive, dir.path(), "apcu").is_err());
+    }
+
+    #[test]
+    fn test_project_extensions() {
+        let project = tempfile::tempdir().unwrap();
+        let built = project.path().join("redis.so");
+        std::fs::write(&built, b"\x7fELF").unwrap();
+
+        let mut extensions = ProjectExtensions::load(project.path()).unwrap();
+        assert!(extensions.ini_entries().is_empty());
+
+        extensions.add("redis", &built, Some("6.0.2".to_string()), false).unwrap();
+        extensions.add("xdebug", &built, None, true).unwrap();
+        extensions.save().unwrap();
+
+        let mut extensions = ProjectExtensions::load(project.path()).unwrap();
+        let dir = std::path::absolute(project.path().join(EXTENSIONS_DIR)).unwrap();
+        assert_eq!(
+            extensions.ini_entries(),
+            vec![
+                format!("extension_dir={}", dir.display()),
+                "extension=redis.so".to_string(),
+                format!("zend_extension={}", dir.join("xdebug.so").display()),
+            ]
+        );
+        assert_eq!(
+            enabled_extensions(project.path()),
+            vec![("redis".to_string(), Some("6.0.2".to_string())), ("xdebug".to_string(), None)]
+        );
+
+        extensions.set_enabled("ext-xdebug", false).unwrap();
+        assert_eq!(extensions.ini_entries().len(), 2);
+        assert!(extensions.set_enabled("missing", true).is_err());
+
+        extensions.remove("redis").unwrap();
+        assert!(!dir.join("redis.so").exists());
+        assert!(extensions.ini_entries().is_empty());
+        assert!(extensions.iter().any(|(name, _)| name == "xdebug"));
+    }
+}
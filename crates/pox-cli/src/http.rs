@@ -0,0 +1,650 @@
+//! HTTP/1.1 front-end of the dev server.
+//!
+//! Every connection is read on its own thread: connections are kept alive
+//! between requests, pipelined requests are answered in order and request
+//! bodies are decoded from `Content-Length` or chunked transfer encoding.
+//! Responses are written by tiny_http's `Response`, which switches to chunked
+//! encoding when the length is not known up front. The number of open
+//! connections and how long an idle connection is kept open are limited, so
+//! dev proxies holding persistent connections neither pile up threads nor get
+//! a fresh connection for every request. Clients sending their request head or
+//! body too slowly are cut off, so they can't hold a connection forever.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tiny_http::{HTTPVersion, Header, Method, Response, StatusCode};
+
+/// Longest accepted request or header line
+const MAX_LINE_LENGTH: u64 = 16 * 1024;
+
+/// Most headers accepted per request
+const MAX_HEADERS: usize = 128;
+
+/// Unread request body that is skipped to keep the connection, larger leftovers close it
+const MAX_DRAIN: u64 = 64 * 1024;
+
+/// Limits of the connections served at the same time
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionLimits {
+    /// Open connections, further clients get `503 Service Unavailable`
+    pub max_connections: usize,
+    /// How long a connection waits for its next request before it is closed
+    pub keep_alive_timeout: Duration,
+    /// How long a client may take to send the request line and headers
+    pub header_timeout: Duration,
+    /// How long a read of the request body waits for data
+    pub body_read_timeout: Duration,
+}
+
+impl Default for ConnectionLimits {
+    fn default() -> Self {
+        Self {
+            max_connections: 256,
+            keep_alive_timeout: Duration::from_secs(5),
+            header_timeout: Duration::from_secs(10),
+            body_read_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Accepts connections and hands out their requests
+pub struct Server {
+    requests: Mutex<Receiver<Request>>,
+}
+
+impl Server {
+    /// Listen on `addr`, serving connections in the background
+    pub fn http(addr: impl ToSocketAddrs, limits: ConnectionLimits) -> io::Result<Self> {
+        Ok(Self::from_listener(TcpListener::bind(addr)?, limits))
+    }
+
+    fn from_listener(listener: TcpListener, limits: ConnectionLimits) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || accept(listener, sender, limits));
+
+        Self {
+            requests: Mutex::new(receiver),
+        }
+    }
+
+    /// Requests of all connections, in the order they are received
+    ///
+    /// Several threads may take requests from the same server.
+    pub fn incoming_requests(&self) -> impl Iterator<Item = Request> + '_ {
+        std::iter::from_fn(|| self.requests.lock().ok()?.recv().ok())
+    }
+}
+
+fn accept(listener: TcpListener, requests: Sender<Request>, limits: ConnectionLimits) {
+    let open = Arc::new(AtomicUsize::new(0));
+
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+
+        if open.load(Ordering::SeqCst) >= limits.max_connections {
+            let _ = stream.write_all(b"HTTP/1.1 503 Service Unavailable\r\nConnection: close\r\nContent-Length: 0\r\n\r\n");
+            continue;
+        }
+
+        open.fetch_add(1, Ordering::SeqCst);
+        let open = open.clone();
+        let requests = requests.clone();
+        std::thread::spawn(move || {
+            serve_connection(stream, &requests, &limits);
+            open.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+}
+
+/// Read the requests of a connection one after another until it is closed
+fn serve_connection(stream: TcpStream, requests: &Sender<Request>, limits: &ConnectionLimits) {
+    let remote_addr = stream.peer_addr().ok();
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        // An idle connection waits for the next request up to the keep-alive timeout,
+        // once it arrives the whole head must be read within the header timeout
+        let _ = reader.get_ref().set_read_timeout(Some(limits.keep_alive_timeout));
+        match reader.fill_buf() {
+            Ok([]) | Err(_) => return,
+            Ok(_) => {}
+        }
+        let head = match read_head(&mut Deadline::new(&mut reader, limits.header_timeout)) {
+            Ok(Some(head)) => head,
+            Ok(None) => return,
+            Err(status) => {
+                let _ = Response::empty(status).with_header(connection_header("close")).raw_print(
+                    &mut writer,
+                    HTTPVersion(1, 1),
+                    &[],
+                    false,
+                    None,
+                );
+                return;
+            }
+        };
+        let _ = reader.get_ref().set_read_timeout(Some(limits.body_read_timeout));
+
+        let Ok(response_writer) = writer.try_clone() else {
+            return;
+        };
+        let (done, returned) = mpsc::channel();
+        if requests.send(Request::new(head, remote_addr, reader, response_writer, done)).is_err() {
+            return;
+        }
+
+        // The reader comes back once the response is written, unless the connection is done
+        match returned.recv() {
+            Ok(Some(next)) => reader = next,
+            _ => return,
+        }
+    }
+}
+
+/// Request line and headers
+struct Head {
+    method: Method,
+    url: String,
+    version: HTTPVersion,
+    headers: Vec<Header>,
+}
+
+/// Reads of a request head, which must be complete before a deadline
+struct Deadline<'a> {
+    reader: &'a mut BufReader<TcpStream>,
+    deadline: Instant,
+}
+
+impl<'a> Deadline<'a> {
+    fn new(reader: &'a mut BufReader<TcpStream>, timeout: Duration) -> Self {
+        Self {
+            reader,
+            deadline: Instant::now() + timeout,
+        }
+    }
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let read = available.len().min(buf.len());
+        buf[..read].copy_from_slice(&available[..read]);
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+impl BufRead for Deadline<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        // Each read from the socket may only wait for the time left
+        if self.reader.buffer().is_empty() {
+            let remaining = self.deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            self.reader.get_ref().set_read_timeout(Some(remaining))?;
+        }
+
+        self.reader.fill_buf().map_err(|e| match e.kind() {
+            io::ErrorKind::WouldBlock => io::ErrorKind::TimedOut.into(),
+            _ => e,
+        })
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.reader.consume(amount);
+    }
+}
+
+/// Read the head of the next request, `None` if the connection was closed
+fn read_head(reader: &mut impl BufRead) -> Result<Option<Head>, StatusCode> {
+    let request_line = loop {
+        match read_line(reader) {
+            // Empty lines before a request are ignored (RFC 9112, section 2.2)
+            Ok(Some(line)) if line.is_empty() => continue,
+            Ok(Some(line)) => break line,
+            Ok(None) => return Ok(None),
+            Err(e) => return head_error(e),
+        }
+    };
+
+    let mut parts = request_line.split(' ');
+    let (Some(method), Some(url), Some(version), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+        return Err(StatusCode(400));
+    };
+    let method = method.parse().map_err(|_| StatusCode(400))?;
+    let version = parse_version(version).ok_or(StatusCode(400))?;
+    if version > (1, 1) {
+        return Err(StatusCode(505));
+    }
+
+    let mut headers = Vec::new();
+    loop {
+        let line = match read_line(reader) {
+            Ok(Some(line)) => line,
+            Ok(None) => return Ok(None),
+            Err(e) => return head_error(e),
+        };
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err(StatusCode(431));
+        }
+        headers.push(line.parse::<Header>().map_err(|_| StatusCode(400))?);
+    }
+
+    // Only a final chunked coding can be decoded, applying it twice or not
+    // last leaves the body without framing (RFC 9112, section 6.3)
+    let codings = transfer_codings(&headers);
+    if let Some(last) = codings.last() {
+        if last != "chunked" || codings.iter().filter(|coding| *coding == "chunked").count() > 1 {
+            return Err(StatusCode(400));
+        }
+        if codings.len() > 1 {
+            return Err(StatusCode(501));
+        }
+    }
+
+    Ok(Some(Head {
+        method,
+        url: url.to_string(),
+        version,
+        headers,
+    }))
+}
+
+/// Malformed heads are answered with 400, heads not sent in time with 408
+fn head_error(e: io::Error) -> Result<Option<Head>, StatusCode> {
+    match e.kind() {
+        io::ErrorKind::InvalidData => Err(StatusCode(400)),
+        io::ErrorKind::TimedOut => Err(StatusCode(408)),
+        _ => Ok(None),
+    }
+}
+
+/// Read a CRLF terminated line, `None` at the end of the stream
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    if reader.take(MAX_LINE_LENGTH).read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+    if line.pop() != Some(b'\n') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "line too long or incomplete"));
+    }
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn parse_version(version: &str) -> Option<HTTPVersion> {
+    let (major, minor) = version.strip_prefix("HTTP/")?.split_once('.')?;
+    Some(HTTPVersion(major.parse().ok()?, minor.parse().ok()?))
+}
+
+fn header_value<'a>(headers: &'a [Header], name: &'static str) -> Option<&'a str> {
+    headers.iter().find(|h| h.field.equiv(name)).map(|h| h.value.as_str())
+}
+
+/// Comma separated values of all headers called `name`
+fn header_list<'a>(headers: &'a [Header], name: &'static str) -> impl Iterator<Item = &'a str> {
+    headers
+        .iter()
+        .filter(move |h| h.field.equiv(name))
+        .flat_map(|h| h.value.as_str().split(','))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn has_connection_option(headers: &[Header], option: &str) -> bool {
+    header_list(headers, "Connection").any(|value| value.eq_ignore_ascii_case(option))
+}
+
+/// Transfer codings of the request body in the order they were applied
+fn transfer_codings(headers: &[Header]) -> Vec<String> {
+    header_list(headers, "Transfer-Encoding").map(str::to_ascii_lowercase).collect()
+}
+
+fn connection_header(value: &str) -> Header {
+    Header::from_bytes(&b"Connection"[..], value.as_bytes()).expect("valid header")
+}
+
+/// Whether the client keeps the connection open after this request (RFC 9112, section 9.3)
+fn wants_keep_alive(version: &HTTPVersion, headers: &[Header]) -> bool {
+    if has_connection_option(headers, "close") || has_connection_option(headers, "upgrade") {
+        return false;
+    }
+    if *version >= (1, 1) {
+        return true;
+    }
+
+    // HTTP/1.0 connections are closed unless the client asks to keep them, and
+    // never after a transfer encoded body, whose framing can't be trusted
+    has_connection_option(headers, "keep-alive") && transfer_codings(headers).is_empty()
+}
+
+/// Request body, reading from the connection up to the end of the body
+enum Body {
+    Fixed(io::Take<BufReader<TcpStream>>),
+    /// The decoder must not be read past the last chunk, it would wait for the next request
+    Chunked {
+        decoder: chunked_transfer::Decoder<BufReader<TcpStream>>,
+        ended: bool,
+    },
+}
+
+impl Body {
+    fn new(reader: BufReader<TcpStream>, headers: &[Header]) -> Self {
+        // Content-Length is ignored when a transfer encoding is given (RFC 9112, section 6.3)
+        if !transfer_codings(headers).is_empty() {
+            return Self::Chunked {
+                decoder: chunked_transfer::Decoder::new(reader),
+                ended: false,
+            };
+        }
+
+        let length = header_value(headers, "Content-Length")
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(0);
+        Self::Fixed(reader.take(length))
+    }
+
+    /// Skip the unread rest of the body, giving back the connection if the body ended
+    fn finish(mut self) -> Option<BufReader<TcpStream>> {
+        let drained = io::copy(&mut (&mut self).take(MAX_DRAIN + 1), &mut io::sink()).ok()?;
+        if drained > MAX_DRAIN {
+            return None;
+        }
+
+        match self {
+            Self::Fixed(body) => Some(body.into_inner()),
+            Self::Chunked { decoder, .. } => Some(decoder.into_inner()),
+        }
+    }
+}
+
+impl Read for Body {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Fixed(body) => body.read(buf),
+            Self::Chunked { ended: true, .. } => Ok(0),
+            Self::Chunked { decoder, ended } => {
+                let read = decoder.read(buf)?;
+                *ended = read == 0 && !buf.is_empty();
+                Ok(read)
+            }
+        }
+    }
+}
+
+/// A request received on a connection, which waits for the response
+pub struct Request {
+    method: Method,
+    url: String,
+    version: HTTPVersion,
+    headers: Vec<Header>,
+    remote_addr: Option<SocketAddr>,
+    body: Option<Body>,
+    writer: TcpStream,
+    must_send_continue: bool,
+    keep_alive: bool,
+    done: Sender<Option<BufReader<TcpStream>>>,
+}
+
+impl Request {
+    fn new(
+        head: Head,
+        remote_addr: Option<SocketAddr>,
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+        done: Sender<Option<BufReader<TcpStream>>>,
+    ) -> Self {
+        // HTTP/1.0 clients don't know 100 Continue (RFC 9110, section 10.1.1)
+        let must_send_continue = head.version >= (1, 1)
+            && header_value(&head.headers, "Expect").is_some_and(|value| value.eq_ignore_ascii_case("100-continue"));
+        let keep_alive = wants_keep_alive(&head.version, &head.headers);
+
+        Self {
+            body: Some(Body::new(reader, &head.headers)),
+            method: head.method,
+            url: head.url,
+            version: head.version,
+            headers: head.headers,
+            remote_addr,
+            writer,
+            must_send_continue,
+            keep_alive,
+            done,
+        }
+    }
+
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn http_version(&self) -> &HTTPVersion {
+        &self.version
+    }
+
+    pub fn headers(&self) -> &[Header] {
+        &self.headers
+    }
+
+    pub fn remote_addr(&self) -> Option<&SocketAddr> {
+        self.remote_addr.as_ref()
+    }
+
+    /// The request body, answers `Expect: 100-continue` on first use
+    pub fn as_reader(&mut self) -> &mut dyn Read {
+        if self.must_send_continue {
+            self.must_send_continue = false;
+            let _ = write!(self.writer, "HTTP/{} 100 Continue\r\n\r\n", self.version);
+        }
+
+        self.body.as_mut().expect("body is taken when responding")
+    }
+
+    /// Send the response, the connection then moves on to its next request
+    pub fn respond<R: Read>(mut self, mut response: Response<R>) -> io::Result<()> {
+        // A client still waiting for 100 Continue may or may not send its body
+        if self.must_send_continue {
+            self.keep_alive = false;
+        }
+        if !self.keep_alive {
+            response.add_header(connection_header("close"));
+        } else if self.version == (1, 0) {
+            response.add_header(connection_header("keep-alive"));
+        }
+
+        let head_only = self.method == Method::Head;
+        let result = response
+            .raw_print(&mut self.writer, self.version.clone(), &self.headers, head_only, None)
+            .and_then(|_| self.writer.flush());
+        let result = ignore_client_closing_errors(result);
+
+        let reader = match (result.is_ok() && self.keep_alive, self.body.take()) {
+            (true, Some(body)) => body.finish(),
+            _ => None,
+        };
+        let _ = self.done.send(reader);
+
+        result
+    }
+}
+
+impl Drop for Request {
+    fn drop(&mut self) {
+        // Dropped without a response, the connection is closed
+        if self.body.is_some() {
+            let _ = self.done.send(None);
+        }
+    }
+}
+
+fn ignore_client_closing_errors(result: io::Result<()>) -> io::Result<()> {
+    result.or_else(|e| match e.kind() {
+        io::ErrorKind::BrokenPipe | io::ErrorKind::ConnectionAborted | io::ErrorKind::ConnectionReset => Ok(()),
+        _ => Err(e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serve `server` by echoing request bodies
+    fn echo(limits: ConnectionLimits) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = Server::from_listener(listener, limits);
+        std::thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                let mut body = Vec::new();
+                request.as_reader().read_to_end(&mut body).unwrap();
+                let body = format!("{} {}", request.url(), String::from_utf8_lossy(&body));
+                request.respond(Response::from_string(body)).unwrap();
+            }
+        });
+        addr
+    }
+
+    fn read_response(reader: &mut BufReader<TcpStream>) -> (String, String) {
+        let mut status = String::new();
+        reader.read_line(&mut status).unwrap();
+
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                length = value.trim().parse().unwrap();
+            }
+        }
+
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        (status.trim_end().to_string(), String::from_utf8(body).unwrap())
+    }
+
+    #[test]
+    fn test_keep_alive_and_pipelining() {
+        let addr = echo(ConnectionLimits::default());
+        let mut stream = TcpStream::connect(addr).unwrap();
+
+        // Both requests are sent before reading a response
+        stream.write_all(b"POST /first HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\nhello\
+            POST /second HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2\r\nde\r\n0\r\n\r\n").unwrap();
+
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        assert_eq!(read_response(&mut reader), ("HTTP/1.1 200 OK".to_string(), "/first hello".to_string()));
+        assert_eq!(read_response(&mut reader), ("HTTP/1.1 200 OK".to_string(), "/second abcde".to_string()));
+
+        // The connection stays open for further requests
+        stream.write_all(b"GET /third HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        assert_eq!(read_response(&mut reader).1, "/third ");
+    }
+
+    #[test]
+    fn test_connection_limits() {
+        let addr = echo(ConnectionLimits {
+            max_connections: 1,
+            keep_alive_timeout: Duration::from_millis(200),
+            ..ConnectionLimits::default()
+        });
+
+        let mut first = TcpStream::connect(addr).unwrap();
+        first.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut first_reader = BufReader::new(first.try_clone().unwrap());
+        assert_eq!(read_response(&mut first_reader).0, "HTTP/1.1 200 OK");
+
+        let second = TcpStream::connect(addr).unwrap();
+        let mut second_reader = BufReader::new(second);
+        assert_eq!(read_response(&mut second_reader).0, "HTTP/1.1 503 Service Unavailable");
+
+        // The idle connection is closed after the keep-alive timeout, making room again
+        let mut rest = Vec::new();
+        first_reader.read_to_end(&mut rest).unwrap();
+        assert!(rest.is_empty());
+        std::thread::sleep(Duration::from_millis(50));
+
+        let mut third = TcpStream::connect(addr).unwrap();
+        third.write_all(b"GET /again HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+        assert_eq!(read_response(&mut BufReader::new(third)).1, "/again ");
+    }
+
+    #[test]
+    fn test_malformed_request() {
+        let addr = echo(ConnectionLimits::default());
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"NOT A REQUEST LINE\r\n\r\n").unwrap();
+
+        assert_eq!(read_response(&mut BufReader::new(stream)).0, "HTTP/1.1 400 Bad Request");
+    }
+
+    #[test]
+    fn test_slow_request_head() {
+        let addr = echo(ConnectionLimits {
+            header_timeout: Duration::from_millis(200),
+            ..ConnectionLimits::default()
+        });
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n").unwrap();
+
+        assert_eq!(read_response(&mut BufReader::new(stream)).0, "HTTP/1.1 408 Request Timeout");
+    }
+
+    #[test]
+    fn test_unsupported_transfer_encoding() {
+        let addr = echo(ConnectionLimits::default());
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"POST / HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked, gzip\r\n\r\n").unwrap();
+        assert_eq!(read_response(&mut BufReader::new(stream)).0, "HTTP/1.1 400 Bad Request");
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"POST / HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n").unwrap();
+        assert_eq!(read_response(&mut BufReader::new(stream)).0, "HTTP/1.1 501 Not Implemented");
+    }
+
+    #[test]
+    fn test_http_1_0_keep_alive() {
+        let addr = echo(ConnectionLimits::default());
+
+        // Without keep-alive the connection is closed after the response
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"GET /once HTTP/1.0\r\n\r\n").unwrap();
+        let mut reader = BufReader::new(stream);
+        assert_eq!(read_response(&mut reader).1, "/once ");
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert!(rest.is_empty());
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"GET /first HTTP/1.0\r\nConnection: Keep-Alive\r\n\r\n").unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        assert_eq!(read_response(&mut reader).1, "/first ");
+        stream.write_all(b"GET /second HTTP/1.0\r\nConnection: keep-alive\r\n\r\n").unwrap();
+        assert_eq!(read_response(&mut reader).1, "/second ");
+    }
+}
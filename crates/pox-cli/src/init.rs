@@ -1,14 +1,18 @@
 //! Init command - create a new composer.json file with interactive prompts.
 
 use anyhow::{Context, Result};
-use clap::Args;
+use clap::{Args, ValueEnum};
 use console::style;
 use dialoguer::{Confirm, Input};
 use regex::Regex;
 use pox_spdx::SpdxLicenses;
-use std::io::IsTerminal;
-use std::path::PathBuf;
+use pox_semver::VersionParser;
+use std::path::{Path, PathBuf};
+
+use crate::create_project::CreateProjectArgs;
 use std::process::Command;
+use pox_pm::config::{composer_file_name, Config};
+use pox_pm::installer::spdx_licenses;
 
 #[derive(Args, Debug)]
 pub struct InitArgs {
@@ -56,13 +60,247 @@ pub struct InitArgs {
     #[arg(long, action = clap::ArgAction::Append)]
     pub repository: Vec<String>,
 
+    /// Scaffold the project from a template
+    #[arg(long, value_enum)]
+    pub template: Option<Template>,
+
+    /// Create the project from the template's skeleton package (symfony-webapp, laravel)
+    #[arg(long, requires = "template")]
+    pub skeleton: bool,
+
+    /// PHP version constraint (defaults to the embedded PHP version)
+    #[arg(long)]
+    pub php: Option<String>,
+
     /// Working directory
     #[arg(short = 'd', long, default_value = ".")]
     pub working_dir: PathBuf,
+}
+
+/// Project templates for `init --template`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Template {
+    /// Reusable library with src/ and tests/
+    Library,
+    /// Symfony web application
+    SymfonyWebapp,
+    /// Laravel application
+    Laravel,
+    /// Command line tool built on symfony/console
+    CliTool,
+}
+
+impl Template {
+    fn label(&self) -> &'static str {
+        match self {
+            Template::Library => "library",
+            Template::SymfonyWebapp => "symfony-webapp",
+            Template::Laravel => "laravel",
+            Template::CliTool => "cli-tool",
+        }
+    }
+
+    fn package_type(&self) -> &'static str {
+        match self {
+            Template::Library => "library",
+            Template::SymfonyWebapp | Template::Laravel | Template::CliTool => "project",
+        }
+    }
+
+    /// Skeleton package installed with `create-project` when `--skeleton` is given
+    fn skeleton(&self) -> Option<&'static str> {
+        match self {
+            Template::SymfonyWebapp => Some("symfony/skeleton"),
+            Template::Laravel => Some("laravel/laravel"),
+            Template::Library | Template::CliTool => None,
+        }
+    }
+
+    fn require(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Template::Library => &[],
+            Template::SymfonyWebapp => &[
+                ("symfony/console", "^7.0"),
+                ("symfony/dotenv", "^7.0"),
+                ("symfony/flex", "^2"),
+                ("symfony/framework-bundle", "^7.0"),
+                ("symfony/runtime", "^7.0"),
+                ("symfony/yaml", "^7.0"),
+            ],
+            Template::Laravel => &[("laravel/framework", "^11.0")],
+            Template::CliTool => &[("symfony/console", "^7.0")],
+        }
+    }
+
+    fn require_dev(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Template::Library | Template::CliTool | Template::Laravel => &[("phpunit/phpunit", "^11.0")],
+            Template::SymfonyWebapp => &[("symfony/phpunit-bridge", "^7.0")],
+        }
+    }
+
+    /// Directory that the PSR-4 namespace maps to
+    fn autoload_path(&self) -> &'static str {
+        match self {
+            Template::Laravel => "app/",
+            _ => "src/",
+        }
+    }
+
+    fn directories(&self) -> &'static [&'static str] {
+        match self {
+            Template::Library => &["src", "tests"],
+            Template::SymfonyWebapp => &["bin", "config", "public", "src/Controller", "templates", "tests", "var"],
+            Template::Laravel => &["app", "bootstrap", "config", "database", "public", "resources", "routes", "storage", "tests"],
+            Template::CliTool => &["bin", "src/Command", "tests"],
+        }
+    }
+
+    /// Files created by the template, relative to the project directory
+    fn files(&self, package_name: &str) -> Vec<(String, String)> {
+        let binary = package_name.rsplit('/').next().unwrap_or(package_name);
+
+        match self {
+            Template::Library => vec![],
+            Template::SymfonyWebapp => vec![
+                (
+                    "public/index.php".to_string(),
+                    "<?php\n\nuse App\\Kernel;\n\nrequire_once dirname(__DIR__).'/vendor/autoload_runtime.php';\n\n\
+                     return function (array $context) {\n    return new Kernel($context['APP_ENV'], (bool) $context['APP_DEBUG']);\n};\n"
+                        .to_string(),
+                ),
+                ("var/.gitignore".to_string(), "*\n!.gitignore\n".to_string()),
+            ],
+            Template::Laravel => {
+                let mut files = vec![
+                    ("artisan".to_string(), LARAVEL_ARTISAN.to_string()),
+                    ("bootstrap/app.php".to_string(), LARAVEL_BOOTSTRAP_APP.to_string()),
+                    ("bootstrap/providers.php".to_string(), "<?php\n\nreturn [\n];\n".to_string()),
+                    ("public/index.php".to_string(), LARAVEL_PUBLIC_INDEX.to_string()),
+                    (
+                        "routes/web.php".to_string(),
+                        "<?php\n\nuse Illuminate\\Support\\Facades\\Route;\n\nRoute::get('/', function () {\n    return 'Hello World';\n});\n"
+                            .to_string(),
+                    ),
+                    ("routes/console.php".to_string(), "<?php\n".to_string()),
+                    (".gitignore".to_string(), "/.env\n".to_string()),
+                ];
+                // Laravel writes compiled files below these directories, they have to exist
+                for dir in [
+                    "bootstrap/cache",
+                    "storage/app",
+                    "storage/framework/cache",
+                    "storage/framework/sessions",
+                    "storage/framework/views",
+                    "storage/logs",
+                ] {
+                    files.push((format!("{}/.gitignore", dir), "*\n!.gitignore\n".to_string()));
+                }
+                files
+            }
+            Template::CliTool => vec![(
+                format!("bin/{}", binary),
+                format!(
+                    "#!/usr/bin/env php\n<?php\n\nrequire __DIR__.'/../vendor/autoload.php';\n\n\
+                     use Symfony\\Component\\Console\\Application;\n\n\
+                     $application = new Application('{}');\n$application->run();\n",
+                    binary
+                ),
+            )],
+        }
+    }
+
+    /// Root namespace of the project, applications use `App`
+    fn namespace(&self, package_name: &str) -> Option<String> {
+        match self {
+            Template::SymfonyWebapp | Template::Laravel => Some("App".to_string()),
+            Template::Library | Template::CliTool => namespace_from_package_name(package_name),
+        }
+    }
+
+    /// PSR-4 mapping for tests, if the template has a tests directory
+    fn autoload_dev(&self, namespace: &str) -> Option<(String, &'static str)> {
+        if self.directories().contains(&"tests") {
+            Some((format!("{}\\Tests\\", namespace), "tests/"))
+        } else {
+            None
+        }
+    }
+}
+
+const LARAVEL_ARTISAN: &str = r#"#!/usr/bin/env php
+<?php
+
+use Symfony\Component\Console\Input\ArgvInput;
+
+define('LARAVEL_START', microtime(true));
+
+require __DIR__.'/vendor/autoload.php';
+
+$status = (require_once __DIR__.'/bootstrap/app.php')
+    ->handleCommand(new ArgvInput);
+
+exit($status);
+"#;
+
+const LARAVEL_BOOTSTRAP_APP: &str = r#"<?php
+
+use Illuminate\Foundation\Application;
+use Illuminate\Foundation\Configuration\Exceptions;
+use Illuminate\Foundation\Configuration\Middleware;
+
+return Application::configure(basePath: dirname(__DIR__))
+    ->withRouting(
+        web: __DIR__.'/../routes/web.php',
+        commands: __DIR__.'/../routes/console.php',
+        health: '/up',
+    )
+    ->withMiddleware(function (Middleware $middleware) {
+        //
+    })
+    ->withExceptions(function (Exceptions $exceptions) {
+        //
+    })->create();
+"#;
+
+const LARAVEL_PUBLIC_INDEX: &str = r#"<?php
+
+use Illuminate\Http\Request;
+
+define('LARAVEL_START', microtime(true));
+
+require __DIR__.'/../vendor/autoload.php';
+
+(require_once __DIR__.'/../bootstrap/app.php')
+    ->handleRequest(Request::capture());
+"#;
+
+/// `.env` of the Laravel template, Laravel cannot encrypt cookies without `APP_KEY`
+///
+/// Cache and sessions use files, the framework defaults need a database.
+fn laravel_env(package_name: &str, app_key: &str) -> String {
+    format!(
+        "APP_NAME={}\nAPP_ENV=local\nAPP_KEY={}\nAPP_DEBUG=true\nAPP_URL=http://localhost\n\n\
+         CACHE_STORE=file\nSESSION_DRIVER=file\nQUEUE_CONNECTION=sync\n",
+        package_name.rsplit('/').next().unwrap_or(package_name),
+        app_key
+    )
+}
+
+/// Random application key, 32 hex characters are a valid AES-256 key for Laravel
+fn generate_app_key() -> Result<String> {
+    use std::io::Read;
+
+    let mut bytes = [0u8; 16];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut random| random.read_exact(&mut bytes))
+        .context("Failed to generate an application key")?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
 
-    /// Non-interactive mode (use defaults and provided options)
-    #[arg(long, short = 'n')]
-    pub no_interaction: bool,
+/// Default PHP constraint, derived from the embedded PHP runtime
+fn default_php_constraint(major: i32, minor: i32) -> String {
+    format!(">={}.{}", major, minor)
 }
 
 /// Git configuration values
@@ -164,13 +402,20 @@ fn validate_stability(stability: &str) -> Result<(), String> {
     }
 }
 
+/// Validate a PHP version constraint
+fn validate_php_constraint(constraint: &str) -> Result<(), String> {
+    VersionParser::new()
+        .parse_constraints(constraint)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid PHP version constraint '{}': {}", constraint, e))
+}
+
 /// Validate license using SPDX
-fn validate_license(license: &str) -> Result<(), String> {
+fn validate_license(spdx: &SpdxLicenses, license: &str) -> Result<(), String> {
     if license.to_lowercase() == "proprietary" {
         return Ok(());
     }
 
-    let spdx = SpdxLicenses::new();
     if spdx.validate(license) {
         Ok(())
     } else {
@@ -274,7 +519,7 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
         .canonicalize()
         .context("Failed to resolve working directory")?;
 
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
 
     if json_path.exists() {
         eprintln!(
@@ -286,7 +531,8 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
     }
 
     let git_config = GitConfig::load();
-    let is_interactive = !args.no_interaction && std::io::stdin().is_terminal();
+    let is_interactive = crate::io::io().is_interactive();
+    let template = args.template;
 
     println!(
         "\n{}",
@@ -294,6 +540,39 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
     );
     println!("\nThis command will guide you through creating your composer.json config.\n");
 
+    // --- Skeleton ---
+    let skeleton = match template.and_then(|t| t.skeleton()) {
+        Some(skeleton) if args.skeleton => Some(skeleton),
+        Some(skeleton) if is_interactive => {
+            let use_skeleton = Confirm::new()
+                .with_prompt(format!("Create the project from the {} skeleton?", skeleton))
+                .default(false)
+                .interact()?;
+            use_skeleton.then_some(skeleton)
+        }
+        Some(_) => None,
+        None => {
+            if args.skeleton {
+                eprintln!(
+                    "{} The {} template has no skeleton package, scaffolding it instead",
+                    style("Warning:").yellow(),
+                    template.map(|t| t.label()).unwrap_or_default()
+                );
+            }
+            None
+        }
+    };
+
+    if skeleton.is_some() {
+        if let Some(entry) = std::fs::read_dir(&working_dir)?.next() {
+            anyhow::bail!(
+                "Cannot create a project from a skeleton, \"{}\" is not empty (found {}).",
+                working_dir.display(),
+                entry?.file_name().to_string_lossy()
+            );
+        }
+    }
+
     // Collect all configuration values
     let mut json_obj = serde_json::Map::new();
 
@@ -416,6 +695,8 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
     // --- Package Type ---
     let pkg_type = if let Some(t) = args.package_type {
         Some(t)
+    } else if let Some(template) = template {
+        Some(template.package_type().to_string())
     } else if is_interactive {
         let t: String = Input::new()
             .with_prompt("Package Type (e.g. library, project, metapackage, composer-plugin)")
@@ -435,8 +716,10 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
     }
 
     // --- License ---
+    // Includes the data refreshed by `pm licenses --update-spdx-data`
+    let spdx = spdx_licenses(&Config::build(Some(&working_dir), true)?);
     let license = if let Some(lic) = args.license {
-        validate_license(&lic).map_err(|e| anyhow::anyhow!(e))?;
+        validate_license(&spdx, &lic).map_err(|e| anyhow::anyhow!(e))?;
         Some(lic)
     } else if is_interactive {
         let lic: String = Input::new()
@@ -446,7 +729,7 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
                 if input.is_empty() {
                     Ok(())
                 } else {
-                    validate_license(input)
+                    validate_license(&spdx, input)
                 }
             })
             .interact_text()?;
@@ -463,9 +746,37 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
         json_obj.insert("license".to_string(), serde_json::Value::String(lic));
     }
 
+    // --- PHP Version ---
+    let php_constraint = if let Some(php) = args.php {
+        validate_php_constraint(&php).map_err(|e| anyhow::anyhow!(e))?;
+        Some(php)
+    } else if template.is_some() {
+        let version = pox_embed::Php::version();
+        let default = default_php_constraint(version.major, version.minor);
+        if is_interactive {
+            let php: String = Input::new()
+                .with_prompt("PHP version constraint")
+                .default(default)
+                .validate_with(|input: &String| validate_php_constraint(input))
+                .interact_text()?;
+            Some(php)
+        } else {
+            Some(default)
+        }
+    } else {
+        None
+    };
+
     // --- Autoload ---
+    let namespace = match template {
+        Some(template) => template.namespace(&name),
+        None => namespace_from_package_name(&name),
+    };
+
     let autoload_path = if let Some(autoload) = args.autoload {
         Some(autoload)
+    } else if let Some(template) = template {
+        Some(template.autoload_path().to_string())
     } else if is_interactive {
         let namespace = namespace_from_package_name(&name);
         let prompt = if let Some(ref ns) = namespace {
@@ -492,7 +803,7 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
     };
 
     if let Some(ref path) = autoload_path {
-        if let Some(namespace) = namespace_from_package_name(&name) {
+        if let Some(ref namespace) = namespace {
             let mut psr4 = serde_json::Map::new();
             psr4.insert(
                 format!("{}\\", namespace),
@@ -501,6 +812,24 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
             let mut autoload = serde_json::Map::new();
             autoload.insert("psr-4".to_string(), serde_json::Value::Object(psr4));
             json_obj.insert("autoload".to_string(), serde_json::Value::Object(autoload));
+
+            if let Some((dev_namespace, dev_path)) = template.and_then(|t| t.autoload_dev(namespace)) {
+                let mut psr4 = serde_json::Map::new();
+                psr4.insert(dev_namespace, serde_json::Value::String(dev_path.to_string()));
+                let mut autoload_dev = serde_json::Map::new();
+                autoload_dev.insert("psr-4".to_string(), serde_json::Value::Object(psr4));
+                json_obj.insert("autoload-dev".to_string(), serde_json::Value::Object(autoload_dev));
+            }
+        }
+    }
+
+    if let Some(template) = template {
+        if template == Template::CliTool {
+            let binary = name.rsplit('/').next().unwrap_or(&name);
+            json_obj.insert(
+                "bin".to_string(),
+                serde_json::json!([format!("bin/{}", binary)]),
+            );
         }
     }
 
@@ -529,28 +858,30 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
     }
 
     // --- Require ---
-    if !args.require.is_empty() {
-        let mut require_map = serde_json::Map::new();
-        for spec in &args.require {
-            let (pkg_name, constraint) = parse_package_spec(spec);
-            require_map.insert(pkg_name, serde_json::Value::String(constraint));
-        }
-        json_obj.insert("require".to_string(), serde_json::Value::Object(require_map));
-    } else {
-        // Empty require object
-        json_obj.insert(
-            "require".to_string(),
-            serde_json::Value::Object(serde_json::Map::new()),
-        );
+    let mut require_map = serde_json::Map::new();
+    if let Some(php) = php_constraint {
+        require_map.insert("php".to_string(), serde_json::Value::String(php));
     }
+    for (pkg_name, constraint) in template.map(|t| t.require()).unwrap_or_default() {
+        require_map.insert(pkg_name.to_string(), serde_json::Value::String(constraint.to_string()));
+    }
+    for spec in &args.require {
+        let (pkg_name, constraint) = parse_package_spec(spec);
+        require_map.insert(pkg_name, serde_json::Value::String(constraint));
+    }
+    // Always written, an empty require object if nothing is required
+    json_obj.insert("require".to_string(), serde_json::Value::Object(require_map));
 
     // --- Require-dev ---
-    if !args.require_dev.is_empty() {
-        let mut require_dev_map = serde_json::Map::new();
-        for spec in &args.require_dev {
-            let (pkg_name, constraint) = parse_package_spec(spec);
-            require_dev_map.insert(pkg_name, serde_json::Value::String(constraint));
-        }
+    let mut require_dev_map = serde_json::Map::new();
+    for (pkg_name, constraint) in template.map(|t| t.require_dev()).unwrap_or_default() {
+        require_dev_map.insert(pkg_name.to_string(), serde_json::Value::String(constraint.to_string()));
+    }
+    for spec in &args.require_dev {
+        let (pkg_name, constraint) = parse_package_spec(spec);
+        require_dev_map.insert(pkg_name, serde_json::Value::String(constraint));
+    }
+    if !require_dev_map.is_empty() {
         json_obj.insert(
             "require-dev".to_string(),
             serde_json::Value::Object(require_dev_map),
@@ -575,6 +906,10 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
         }
     }
 
+    if let Some(skeleton) = skeleton {
+        return create_from_skeleton(skeleton, &working_dir, &json_obj).await;
+    }
+
     // --- Write composer.json ---
     std::fs::write(&json_path, &json_content).context("Failed to write composer.json")?;
 
@@ -598,6 +933,11 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
         }
     }
 
+    // --- Scaffold template ---
+    if let Some(template) = template {
+        scaffold_template(template, &working_dir, &name)?;
+    }
+
     // --- Handle .gitignore ---
     if is_git_repo(&working_dir) {
         let gitignore_path = working_dir.join(".gitignore");
@@ -624,7 +964,7 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
 
     // --- Show autoload info ---
     if let Some(ref path) = autoload_path {
-        if let Some(namespace) = namespace_from_package_name(&name) {
+        if let Some(namespace) = namespace {
             println!(
                 "\n{} PSR-4 autoloading configured. Use \"{}\" in {}",
                 style("Info:").cyan(),
@@ -641,6 +981,108 @@ pub async fn execute(args: InitArgs) -> Result<i32> {
     Ok(0)
 }
 
+/// Create the template's directories and files, existing files are kept
+fn scaffold_template(template: Template, working_dir: &Path, package_name: &str) -> Result<()> {
+    for dir in template.directories() {
+        let path = working_dir.join(dir);
+        if !path.exists() {
+            std::fs::create_dir_all(&path)
+                .context(format!("Failed to create directory: {}", path.display()))?;
+            println!("  {} Created {}", style("✓").green(), path.display());
+        }
+    }
+
+    for (file, content) in template.files(package_name) {
+        let path = working_dir.join(&file);
+        if path.exists() {
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, content).context(format!("Failed to write {}", path.display()))?;
+
+        #[cfg(unix)]
+        if file.starts_with("bin/") || file == "artisan" {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        }
+
+        println!("  {} Created {}", style("✓").green(), path.display());
+    }
+
+    let env_path = working_dir.join(".env");
+    if template == Template::Laravel && !env_path.exists() {
+        std::fs::write(&env_path, laravel_env(package_name, &generate_app_key()?))
+            .context(format!("Failed to write {}", env_path.display()))?;
+        println!("  {} Created {}", style("✓").green(), env_path.display());
+    }
+
+    Ok(())
+}
+
+/// Install a skeleton package with create-project and apply the collected package metadata to it
+async fn create_from_skeleton(
+    skeleton: &str,
+    working_dir: &Path,
+    json_obj: &serde_json::Map<String, serde_json::Value>,
+) -> Result<i32> {
+    let args = CreateProjectArgs {
+        package: skeleton.to_string(),
+        directory: Some(working_dir.to_string_lossy().to_string()),
+        version: None,
+        stability: None,
+        prefer_source: false,
+        prefer_dist: false,
+        no_verify: false,
+        repository: Vec::new(),
+        no_dev: false,
+        no_scripts: false,
+        no_progress: false,
+        no_install: false,
+        keep_vcs: false,
+        remove_vcs: true,
+        ignore_platform_reqs: false,
+        ignore_platform_req: Vec::new(),
+        no_plugins: false,
+        no_audit: false,
+        audit_format: "summary".to_string(),
+    };
+
+    let code = crate::create_project::execute(args).await?;
+    if code != 0 {
+        return Ok(code);
+    }
+
+    let json_path = working_dir.join(composer_file_name());
+    if !json_path.exists() {
+        return Ok(0);
+    }
+
+    let content = std::fs::read_to_string(&json_path)?;
+    let mut skeleton_json: serde_json::Value =
+        serde_json::from_str(&content).context("Failed to parse the skeleton composer.json")?;
+
+    if let Some(obj) = skeleton_json.as_object_mut() {
+        for key in ["name", "description", "authors", "license", "minimum-stability"] {
+            if let Some(value) = json_obj.get(key) {
+                obj.insert(key.to_string(), value.clone());
+            }
+        }
+    }
+
+    std::fs::write(&json_path, serde_json::to_string_pretty(&skeleton_json)? + "\n")
+        .context("Failed to write composer.json")?;
+
+    println!(
+        "\n{} Updated {} with the package details",
+        style("Success:").green().bold(),
+        json_path.display()
+    );
+
+    Ok(0)
+}
+
 /// Parse a package specification (vendor/package:^1.0 or vendor/package)
 fn parse_package_spec(spec: &str) -> (String, String) {
     // Try different separators: :, =, or space
@@ -687,3 +1129,109 @@ fn parse_author(author: &str) -> Vec<serde_json::Value> {
 
     vec![author_obj]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_args(dir: &Path, template: Template) -> InitArgs {
+        InitArgs {
+            name: Some("acme/app".to_string()),
+            description: None,
+            author: None,
+            package_type: None,
+            homepage: None,
+            require: Vec::new(),
+            require_dev: Vec::new(),
+            stability: None,
+            license: None,
+            autoload: None,
+            repository: Vec::new(),
+            template: Some(template),
+            skeleton: false,
+            php: Some(">=8.2".to_string()),
+            working_dir: dir.to_path_buf(),
+        }
+    }
+
+    async fn init_template(template: Template) -> (tempfile::TempDir, serde_json::Value) {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(execute(init_args(dir.path(), template)).await.unwrap(), 0);
+        let json = std::fs::read_to_string(dir.path().join("composer.json")).unwrap();
+        (dir, serde_json::from_str(&json).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_library_template() {
+        let (dir, json) = init_template(Template::Library).await;
+
+        assert_eq!(json["type"], "library");
+        assert_eq!(json["require"], serde_json::json!({"php": ">=8.2"}));
+        assert_eq!(json["require-dev"]["phpunit/phpunit"], "^11.0");
+        assert_eq!(json["autoload"]["psr-4"]["Acme\\App\\"], "src/");
+        assert_eq!(json["autoload-dev"]["psr-4"]["Acme\\App\\Tests\\"], "tests/");
+        assert!(dir.path().join("src").is_dir());
+        assert!(dir.path().join("tests").is_dir());
+    }
+
+    #[tokio::test]
+    async fn test_cli_tool_template() {
+        let (dir, json) = init_template(Template::CliTool).await;
+
+        assert_eq!(json["type"], "project");
+        assert_eq!(json["bin"], serde_json::json!(["bin/app"]));
+        assert_eq!(json["require"]["symfony/console"], "^7.0");
+
+        let binary = dir.path().join("bin/app");
+        assert!(std::fs::read_to_string(&binary).unwrap().contains("new Application('app')"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&binary).unwrap().permissions().mode() & 0o111, 0o111);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_laravel_template_without_skeleton() {
+        let (dir, json) = init_template(Template::Laravel).await;
+
+        assert_eq!(json["type"], "project");
+        assert_eq!(json["require"]["laravel/framework"], "^11.0");
+        assert_eq!(json["autoload"]["psr-4"]["App\\"], "app/");
+
+        // Everything Laravel needs to boot without the skeleton package
+        for file in ["artisan", "bootstrap/app.php", "bootstrap/providers.php", "public/index.php", "routes/web.php", "routes/console.php"] {
+            assert!(dir.path().join(file).is_file(), "{} is missing", file);
+        }
+        for writable in ["bootstrap/cache", "storage/framework/sessions", "storage/framework/views", "storage/logs"] {
+            assert!(dir.path().join(writable).is_dir(), "{} is missing", writable);
+        }
+        assert!(std::fs::read_to_string(dir.path().join("public/index.php")).unwrap().contains("bootstrap/app.php"));
+
+        let env = std::fs::read_to_string(dir.path().join(".env")).unwrap();
+        let app_key = env.lines().find_map(|line| line.strip_prefix("APP_KEY=")).unwrap();
+        assert_eq!(app_key.len(), 32);
+        assert!(env.contains("SESSION_DRIVER=file"));
+    }
+
+    #[test]
+    fn test_scaffold_keeps_existing_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("routes")).unwrap();
+        std::fs::write(dir.path().join("routes/web.php"), "<?php // mine").unwrap();
+        std::fs::write(dir.path().join(".env"), "APP_KEY=mine").unwrap();
+
+        scaffold_template(Template::Laravel, dir.path(), "acme/app").unwrap();
+
+        assert_eq!(std::fs::read_to_string(dir.path().join("routes/web.php")).unwrap(), "<?php // mine");
+        assert_eq!(std::fs::read_to_string(dir.path().join(".env")).unwrap(), "APP_KEY=mine");
+    }
+
+    #[test]
+    fn test_generate_app_key() {
+        let key = generate_app_key().unwrap();
+        assert_eq!(key.len(), 32);
+        assert!(key.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(key, generate_app_key().unwrap());
+    }
+}
@@ -11,8 +11,9 @@ use pox_pm::{
     installer::Installer,
     json::{ComposerJson, ComposerLock},
 };
+use pox_pm::config::{composer_file_name, lock_file_name};
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Clone)]
 pub struct InstallArgs {
     /// Prefer source installation (git clone)
     #[arg(long)]
@@ -22,6 +23,10 @@ pub struct InstallArgs {
     #[arg(long)]
     pub prefer_dist: bool,
 
+    /// Skip checksum and signature verification of downloaded archives
+    #[arg(long)]
+    pub no_verify: bool,
+
     /// Run in dry-run mode (no actual changes)
     #[arg(long)]
     pub dry_run: bool,
@@ -30,6 +35,10 @@ pub struct InstallArgs {
     #[arg(long)]
     pub no_dev: bool,
 
+    /// Resolve dependencies without reading or writing composer.lock
+    #[arg(long)]
+    pub no_lock: bool,
+
     /// Skip autoloader generation
     #[arg(long)]
     pub no_autoloader: bool,
@@ -71,18 +80,6 @@ pub struct InstallArgs {
     #[arg(long)]
     pub no_ansi: bool,
 
-    /// Do not ask any interactive question
-    #[arg(short = 'n', long)]
-    pub no_interaction: bool,
-
-    /// Do not output any message
-    #[arg(short = 'q', long)]
-    pub quiet: bool,
-
-    /// Increase verbosity (-v, -vv, -vvv)
-    #[arg(short = 'v', long, action = clap::ArgAction::Count)]
-    pub verbose: u8,
-
     /// Skip the audit step after installation (env: COMPOSER_NO_AUDIT)
     #[arg(long)]
     pub no_audit: bool,
@@ -95,13 +92,15 @@ pub struct InstallArgs {
 use crate::pm::platform::PlatformInfo;
 
 pub async fn execute(args: InstallArgs) -> Result<i32> {
+    let io = crate::io::io();
+
     let skip_audit = args.no_audit || std::env::var("COMPOSER_NO_AUDIT").unwrap_or_default() == "1";
 
     let working_dir = args.working_dir.canonicalize()
         .context("Failed to resolve working directory")?;
 
     // Load composer.json
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
     let composer_json: ComposerJson = if json_path.exists() {
         let content = std::fs::read_to_string(&json_path)?;
         serde_json::from_str(&content)?
@@ -109,33 +108,42 @@ pub async fn execute(args: InstallArgs) -> Result<i32> {
         ComposerJson::default()
     };
 
+    // Load config, `--no-lock` behaves like the `lock: false` setting
+    let mut config = Config::build(Some(&working_dir), true)?;
+    if args.no_lock {
+        config.lock = false;
+    }
+
     // Check for composer.lock
-    let lock_path = working_dir.join("composer.lock");
-    let (lock, run_update) = if lock_path.exists() {
+    let lock_path = working_dir.join(lock_file_name());
+    let (lock, run_update) = if !config.lock {
+        io.write(&format!("{} Lock file disabled, resolving dependencies without composer.lock", style("Info:").cyan()));
+        (None, true)
+    } else if lock_path.exists() {
         let lock_content = std::fs::read_to_string(&lock_path)
             .context("Failed to read composer.lock")?;
         let lock: ComposerLock = serde_json::from_str(&lock_content)
             .context("Failed to parse composer.lock")?;
         (Some(lock), false)
     } else {
-        println!("{} No composer.lock file found. Running update to generate one.", style("Info:").cyan());
+        io.write(&format!("{} No composer.lock file found. Running update to generate one.", style("Info:").cyan()));
         (None, true)
     };
 
-    // Load config
-    let config = Config::build(Some(&working_dir), true)?;
-
     // Detect platform
-    let platform = PlatformInfo::detect();
+    let platform = PlatformInfo::detect_for(&working_dir);
 
     // Create Composer using builder
     let mut builder = ComposerBuilder::new(working_dir.clone())
+        .with_io(io.clone())
+        .with_profiler(crate::io::profiler())
         .with_config(config)
         .with_composer_json(composer_json)
         .with_composer_lock(lock)
         .with_platform_packages(platform.to_packages())
         .dry_run(args.dry_run)
-        .no_dev(args.no_dev);
+        .no_dev(args.no_dev)
+        .no_verify(args.no_verify);
 
     // Apply prefer_source/prefer_dist flags
     if args.prefer_source {
@@ -147,7 +155,7 @@ pub async fn execute(args: InstallArgs) -> Result<i32> {
     let composer = builder.build()?;
 
     // Run Installer
-    let installer = Installer::new(composer);
+    let installer = Installer::new(composer).audit(!skip_audit);
 
     let result = if run_update {
         installer.update(
@@ -165,17 +173,11 @@ pub async fn execute(args: InstallArgs) -> Result<i32> {
         ).await
     };
 
-    if result.is_ok() && !skip_audit {
-        let audit_args = crate::pm::audit::AuditArgs {
-            no_dev: args.no_dev,
-            format: args.audit_format.clone(),
-            locked: false,
-            abandoned: Some("report".to_string()),
-            working_dir: working_dir.clone(),
-        };
-
-        if let Err(e) = crate::pm::audit::execute(audit_args).await {
-            eprintln!("Warning: Audit failed: {}", e);
+    if matches!(result, Ok(0)) && !skip_audit {
+        match crate::pm::audit::audit_after_install(&working_dir, args.no_dev, &args.audit_format).await {
+            Ok(0) => {}
+            Ok(exit_code) => return Ok(exit_code),
+            Err(e) => io.write_error(&format!("Warning: Audit failed: {}", e)),
         }
     }
 
@@ -0,0 +1,69 @@
+//! Global output options shared by the package manager commands.
+
+use clap::Args;
+use std::sync::{Arc, OnceLock};
+
+use pox_pm::io::{ConsoleIo, Io, Verbosity};
+use pox_pm::profile::Profiler;
+
+static IO: OnceLock<Arc<dyn Io>> = OnceLock::new();
+static PROFILER: OnceLock<Arc<Profiler>> = OnceLock::new();
+
+/// `-q`, `-v`, `-n` and `--profile`, accepted by the server and every package manager command
+#[derive(Args, Debug, Clone, Default)]
+pub struct IoArgs {
+    /// Do not output any message
+    #[arg(short = 'q', long, global = true, help_heading = "Global options")]
+    pub quiet: bool,
+
+    /// Increase verbosity (-v, -vv, -vvv)
+    #[arg(short = 'v', long, action = clap::ArgAction::Count, global = true, help_heading = "Global options")]
+    pub verbose: u8,
+
+    /// Do not ask any interactive question
+    #[arg(short = 'n', long, global = true, help_heading = "Global options")]
+    pub no_interaction: bool,
+
+    /// Display timing and memory usage information per phase
+    #[arg(long, global = true, help_heading = "Global options")]
+    pub profile: bool,
+}
+
+impl IoArgs {
+    /// Install the console io for this process and set up logging for the verbosity
+    pub fn init(&self) {
+        let io = ConsoleIo::from_flags(self.quiet, self.verbose, self.no_interaction);
+
+        // Only enable verbose logging for pox crates, not dependencies
+        let log_level = match io.verbosity() {
+            Verbosity::Quiet => log::LevelFilter::Error,
+            Verbosity::Normal => log::LevelFilter::Warn,
+            Verbosity::Verbose => log::LevelFilter::Info,
+            Verbosity::VeryVerbose => log::LevelFilter::Debug,
+            Verbosity::Debug => log::LevelFilter::Trace,
+        };
+        let _ = env_logger::Builder::new()
+            .filter_level(log::LevelFilter::Warn)
+            .filter_module("pox_pm", log_level)
+            .filter_module("pox_cli", log_level)
+            .format_timestamp(None)
+            .format_target(false)
+            .try_init();
+
+        let io: Arc<dyn Io> = Arc::new(io);
+        if self.profile {
+            let _ = PROFILER.set(Arc::new(Profiler::new(io.clone())));
+        }
+        let _ = IO.set(io);
+    }
+}
+
+/// The process wide io, a non-interactive console if no command initialized it
+pub fn io() -> Arc<dyn Io> {
+    IO.get_or_init(|| Arc::new(ConsoleIo::default())).clone()
+}
+
+/// The process wide profiler, disabled unless `--profile` was passed
+pub fn profiler() -> Arc<Profiler> {
+    PROFILER.get_or_init(|| Arc::new(Profiler::disabled())).clone()
+}
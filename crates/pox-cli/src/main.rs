@@ -1,27 +1,53 @@
 mod add;
+mod affinity;
+mod completions;
 mod config;
 mod create_project;
+#[cfg(unix)]
+mod daemon;
+mod debug;
+mod ext;
+mod http;
 mod pm;
 mod init;
 mod install;
+mod io;
+mod metrics;
+mod opcache;
+mod phar;
+mod php_errors;
+mod proxy;
 mod remove;
+mod repl;
+mod router;
+mod routes;
+mod runtime;
+mod session;
+mod static_files;
 mod update;
+mod upload;
+mod watch;
+mod workspace;
 
 use config::PoxConfig;
+use metrics::Metrics;
+use router::Script;
+use routes::Route;
+use static_files::Lookup;
+use upload::LimitedBody;
 
 use anyhow::Result;
-use clap::{Parser, Subcommand, CommandFactory};
-use clap_complete::{generate, Shell};
+use clap::{Parser, Subcommand, CommandFactory, FromArgMatches};
+use clap_complete::Shell;
 use pox_embed::{HttpRequest, Php, PhpWeb, PhpWorker};
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
-use tiny_http::{Header, Response, Server, StatusCode};
-use notify::RecursiveMode;
-use notify_debouncer_full::{new_debouncer, DebouncedEvent};
-use globset::{Glob, GlobSetBuilder};
+use http::{ConnectionLimits, Server};
+use tiny_http::{Header, Response, StatusCode};
 
 #[derive(Parser, Debug)]
 #[command(name = "pox")]
@@ -49,6 +75,10 @@ struct Args {
     #[arg(short = 'm', long = "modules", conflicts_with_all = ["script_and_args", "run", "lint", "info", "version_flag"])]
     modules: bool,
 
+    /// Run as interactive shell
+    #[arg(short = 'a', long = "interactive", conflicts_with_all = ["script_and_args", "run", "lint", "info", "modules", "version_flag"])]
+    interactive: bool,
+
     /// Version information
     #[arg(short = 'v', long = "version", conflicts_with_all = ["script_and_args", "run", "lint", "info", "modules"])]
     version_flag: bool,
@@ -90,44 +120,193 @@ enum Commands {
         #[arg(long, default_value = "0")]
         workers: usize,
 
+        /// Route requests of a client to the same worker: header:<name>, cookie:<name> or ip
+        #[arg(long, value_name = "SOURCE")]
+        worker_affinity: Option<String>,
+
         /// Watch for file changes and restart workers (glob patterns, e.g., "**/*.php")
         #[arg(long, action = clap::ArgAction::Append)]
         watch: Vec<String>,
+
+        /// Preload script for opcache (sets opcache.preload)
+        #[arg(long, value_name = "FILE")]
+        preload: Option<PathBuf>,
+
+        /// Expose opcache statistics at /__pox/opcache
+        #[arg(long)]
+        opcache_status: bool,
+
+        /// Number of request threads without worker mode (requires a ZTS build)
+        #[arg(long, default_value = "0")]
+        threads: usize,
+
+        /// Maximum execution time per request in seconds (sets max_execution_time)
+        #[arg(long, value_name = "SECONDS")]
+        request_timeout: Option<u64>,
+
+        /// Memory limit for PHP (sets memory_limit, e.g. 256M)
+        #[arg(long, value_name = "SIZE")]
+        memory_limit: Option<String>,
+
+        /// Replace a worker after it handled this many requests (0 = never)
+        #[arg(long, value_name = "COUNT")]
+        max_requests_per_worker: Option<u64>,
+
+        /// Expose Prometheus metrics at /__pox/metrics
+        #[arg(long)]
+        metrics: bool,
+
+        /// Serve Prometheus metrics at /metrics on a separate port
+        #[arg(long, value_name = "PORT")]
+        metrics_port: Option<u16>,
+
+        /// Largest accepted request body, e.g. 512M (sets upload_max_filesize and post_max_size)
+        #[arg(long, value_name = "SIZE")]
+        upload_max_filesize: Option<String>,
+
+        /// Trigger an Xdebug step-debugging session for every request
+        #[arg(long)]
+        xdebug: bool,
+
+        /// Directory for PHP session files (default: a temp dir of this server, removed on shutdown)
+        #[arg(long, value_name = "DIR")]
+        session_path: Option<PathBuf>,
+
+        /// Maximum number of open client connections (default: 256)
+        #[arg(long, value_name = "COUNT")]
+        max_connections: Option<usize>,
+
+        /// Seconds an idle keep-alive connection is kept open (default: 5)
+        #[arg(long, value_name = "SECONDS")]
+        keep_alive_timeout: Option<u64>,
+
+        /// Set an environment variable for PHP and worker scripts (can be used multiple times)
+        #[arg(long, value_name = "KEY=VALUE", action = clap::ArgAction::Append)]
+        env: Vec<String>,
+
+        /// Proxy addresses or CIDR ranges whose Forwarded/X-Forwarded-* headers are trusted (comma separated)
+        #[arg(long, value_name = "CIDR", value_delimiter = ',', action = clap::ArgAction::Append)]
+        trusted_proxies: Vec<String>,
+
+        #[command(flatten)]
+        io: io::IoArgs,
     },
 
     /// Create a new composer.json in current directory
-    Init(init::InitArgs),
+    Init {
+        #[command(flatten)]
+        io: io::IoArgs,
+        #[command(flatten)]
+        args: init::InitArgs,
+    },
 
     /// Create a new project from a package into a directory
-    CreateProject(create_project::CreateProjectArgs),
+    CreateProject {
+        #[command(flatten)]
+        io: io::IoArgs,
+        #[command(flatten)]
+        args: create_project::CreateProjectArgs,
+    },
 
     /// Install project dependencies from composer.lock
-    Install(install::InstallArgs),
+    Install {
+        #[command(flatten)]
+        io: io::IoArgs,
+        #[command(flatten)]
+        args: install::InstallArgs,
+    },
 
     /// Update dependencies to their latest versions
-    Update(update::UpdateArgs),
+    Update {
+        #[command(flatten)]
+        io: io::IoArgs,
+        #[command(flatten)]
+        args: update::UpdateArgs,
+    },
 
     /// Add a package to the project
-    Add(add::AddArgs),
+    Add {
+        #[command(flatten)]
+        io: io::IoArgs,
+        #[command(flatten)]
+        args: add::AddArgs,
+    },
 
     /// Remove a package from the project
-    Remove(remove::RemoveArgs),
+    Remove {
+        #[command(flatten)]
+        io: io::IoArgs,
+        #[command(flatten)]
+        args: remove::RemoveArgs,
+    },
 
     /// Package manager commands (show, validate, dump-autoload)
     Pm {
+        #[command(flatten)]
+        io: io::IoArgs,
         #[command(subcommand)]
         command: pm::PmCommands,
     },
 
+    /// Work with all packages of a monorepo workspace
+    Workspace {
+        #[command(flatten)]
+        io: io::IoArgs,
+        #[command(subcommand)]
+        command: workspace::WorkspaceCommands,
+    },
+
     /// Run a script defined in composer.json
-    Run(pm::RunArgs),
+    Run {
+        #[command(flatten)]
+        io: io::IoArgs,
+        #[command(flatten)]
+        args: pm::RunArgs,
+    },
+
+    /// Manage shared PHP extensions of the project
+    Ext {
+        #[command(flatten)]
+        io: io::IoArgs,
+        /// Working directory
+        #[arg(short = 'd', long, default_value = ".", global = true)]
+        working_dir: PathBuf,
+        #[command(subcommand)]
+        command: ext::ExtCommands,
+    },
+
+    /// Manage the PHP versions available to projects
+    Php {
+        #[command(flatten)]
+        io: io::IoArgs,
+        #[command(subcommand)]
+        command: runtime::PhpCommands,
+    },
+
+    /// Start an interactive PHP shell
+    Repl(repl::ReplArgs),
+
+    /// Run a daemon answering JSON-RPC requests on a unix socket, for editors and IDE plugins
+    #[cfg(unix)]
+    Daemon(daemon::DaemonArgs),
+
+    /// Inspect and extract phar archives
+    Phar {
+        #[command(subcommand)]
+        command: phar::PharCommands,
+    },
 
     /// Generate shell completion scripts
+    #[command(alias = "completions")]
     Completion {
         /// The shell to generate completions for
         #[arg(value_enum)]
         shell: Shell,
     },
+
+    /// Complete package names for the shell completion scripts
+    #[command(name = "__complete-package", hide = true)]
+    CompletePackage(completions::CompletePackageArgs),
 }
 
 fn print_version() {
@@ -138,8 +317,9 @@ fn print_version() {
 }
 
 /// Build INI entries by merging config file and CLI arguments
-/// CLI arguments take precedence over config file settings
-fn build_ini_entries(config: Option<&PoxConfig>, defines: &[String]) -> Option<String> {
+/// CLI arguments take precedence over config file settings, the enabled
+/// extensions of the project in `project_dir` are loaded as well
+fn build_ini_entries(config: Option<&PoxConfig>, defines: &[String], project_dir: &Path) -> Option<String> {
     use std::collections::HashMap;
 
     let mut ini_map: HashMap<String, String> = HashMap::new();
@@ -162,27 +342,119 @@ fn build_ini_entries(config: Option<&PoxConfig>, defines: &[String]) -> Option<S
         }
     }
 
-    if ini_map.is_empty() {
-        return None;
-    }
-
-    let entries: Vec<String> = ini_map
+    let mut entries: Vec<String> = ini_map
         .iter()
         .map(|(k, v)| format!("{}={}", k, v))
         .collect();
 
+    // Extensions of the project, `extension` may be repeated so they bypass the map
+    entries.extend(ext::ini_entries(project_dir));
+
+    if entries.is_empty() {
+        return None;
+    }
+
     Some(entries.join("\n") + "\n")
 }
 
-fn run_server(host: &str, port: u16, document_root: &Path, router: Option<&Path>, worker: Option<&Path>, num_workers: usize, watch_patterns: Vec<String>, config: Option<&PoxConfig>) -> Result<i32> {
-    // Apply INI entries from config for server mode
-    let ini_entries = build_ini_entries(config, &[]);
+/// Build environment variables by merging config file and CLI arguments
+/// CLI arguments take precedence over config file settings
+fn build_env_vars(config: Option<&PoxConfig>, vars: &[String]) -> Result<Vec<(String, String)>> {
+    let mut env: Vec<(String, String)> = config
+        .map(|c| c.server.env.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default();
+    env.sort();
+
+    for var in vars {
+        let Some((key, value)) = var.split_once('=').filter(|(key, _)| !key.is_empty()) else {
+            anyhow::bail!("Invalid environment variable \"{}\", expected KEY=VALUE", var);
+        };
+        env.retain(|(k, _)| k != key);
+        env.push((key.to_string(), value.to_string()));
+    }
+
+    Ok(env)
+}
+
+/// Runtime tuning options for the development server
+#[derive(Debug, Default)]
+struct ServerOptions {
+    /// Glob patterns of files that restart the workers when changed
+    watch: Vec<String>,
+    /// Opcache preload script
+    preload: Option<PathBuf>,
+    /// Serve opcache statistics at opcache::STATUS_PATH
+    opcache_status: bool,
+    /// Request threads of the standard (non-worker) server
+    threads: usize,
+    /// Maximum execution time of a request
+    request_timeout: Option<Duration>,
+    /// PHP memory_limit
+    memory_limit: Option<String>,
+    /// Requests after which a worker is recycled
+    max_requests_per_worker: Option<u64>,
+    /// Source of the key that pins requests to a worker
+    worker_affinity: Option<affinity::Affinity>,
+    /// Serve metrics at metrics::METRICS_PATH
+    metrics: bool,
+    /// Serve metrics on a separate port
+    metrics_port: Option<u16>,
+    /// Largest accepted request body in bytes
+    upload_max_filesize: Option<u64>,
+    /// Start an Xdebug session for every request
+    xdebug: bool,
+    /// Persistent session.save_path instead of the per instance one
+    session_path: Option<PathBuf>,
+    /// Open connections and keep-alive of the HTTP front-end
+    connections: ConnectionLimits,
+    /// Denied, static-only and PHP paths from pox.toml
+    routes: routes::RouteRules,
+    /// Proxies whose forwarding headers set REMOTE_ADDR and HTTPS
+    trusted_proxies: proxy::TrustedProxies,
+}
+
+/// Extra time a worker gets to report PHP's own timeout before it is replaced
+const WORKER_TIMEOUT_GRACE: Duration = Duration::from_secs(1);
+
+fn run_server(host: &str, port: u16, document_root: &Path, router: Option<&Path>, worker: Option<&Path>, num_workers: usize, config: Option<&PoxConfig>, options: &ServerOptions) -> Result<i32> {
+    // Apply INI entries from config for server mode, the preload settings take precedence
+    let mut defines = match &options.preload {
+        Some(preload) => opcache::preload_ini_entries(preload)?,
+        None => Vec::new(),
+    };
+    if let Some(timeout) = options.request_timeout {
+        defines.push(format!("max_execution_time={}", timeout.as_secs()));
+    }
+    if let Some(memory_limit) = &options.memory_limit {
+        defines.push(format!("memory_limit={}", memory_limit));
+    }
+    if let Some(limit) = options.upload_max_filesize {
+        defines.push(format!("upload_max_filesize={}", limit));
+        defines.push(format!("post_max_size={}", limit));
+    }
+    if options.xdebug {
+        debug::set_xdebug_env();
+        defines.extend(debug::xdebug_ini_entries());
+    }
+
+    // Sessions and uploads of parallel servers must not end up in the same directory,
+    // unless the php.ini settings of pox.toml say otherwise
+    let dirs = session::ServerDirs::create(port, options.session_path.as_deref())?;
+    dirs.remove_on_shutdown();
+    defines.extend(dirs.ini_entries().into_iter().filter(|entry| {
+        let key = entry.split('=').next().unwrap_or_default();
+        !config.is_some_and(|c| c.php.ini.contains_key(key))
+    }));
+
+    let ini_entries = build_ini_entries(config, &defines, &std::env::current_dir()?);
     if ini_entries.is_some() {
         Php::set_ini_entries(ini_entries.as_deref())?;
     }
+    php_errors::install();
+    Php::bridge_log();
 
     let addr = format!("{}:{}", host, port);
-    let server = Server::http(&addr).map_err(|e| anyhow::anyhow!("Failed to start server: {}", e))?;
+    let server = Server::http(&addr, options.connections).map_err(|e| anyhow::anyhow!("Failed to start server: {}", e))?;
 
     let document_root = document_root
         .canonicalize()
@@ -194,9 +466,26 @@ fn run_server(host: &str, port: u16, document_root: &Path, router: Option<&Path>
         addr
     );
     println!("Document root is {}", document_root.display());
+    println!("Sessions are stored in {}", dirs.session_path().display());
     if let Some(router) = router {
         println!("Router script is {}", router.display());
     }
+    if let Some(preload) = &options.preload {
+        println!("Preload script is {}", preload.display());
+    }
+    if options.opcache_status {
+        println!("Opcache status is available at http://{}{}", addr, opcache::STATUS_PATH);
+    }
+    if options.metrics {
+        println!("Metrics are available at http://{}{}", addr, metrics::METRICS_PATH);
+    }
+    if options.xdebug {
+        println!("Xdebug is triggered for every request (session {})", debug::XDEBUG_SESSION);
+    }
+    if !options.trusted_proxies.ranges().is_empty() {
+        let ranges: Vec<String> = options.trusted_proxies.ranges().iter().map(|r| r.to_string()).collect();
+        println!("Trusting forwarding headers of {}", ranges.join(", "));
+    }
     if let Some(worker_script) = worker {
         let num_workers = if num_workers == 0 {
             std::thread::available_parallelism()
@@ -206,92 +495,222 @@ fn run_server(host: &str, port: u16, document_root: &Path, router: Option<&Path>
             num_workers
         };
         println!("Worker script is {} ({} workers)", worker_script.display(), num_workers);
-        if !watch_patterns.is_empty() {
-            println!("Watching for file changes: {:?}", watch_patterns);
+        if !options.watch.is_empty() {
+            println!("Watching for file changes: {:?}", options.watch);
         }
-        return run_worker_server(server, host, port, &document_root, worker_script, num_workers, watch_patterns);
+        return run_worker_server(server, host, port, &document_root, worker_script, num_workers, options);
+    }
+    let threads = if options.threads > 1 && !Php::is_zts() {
+        eprintln!("Warning: PHP is not built with thread safety (ZTS), handling requests on a single thread");
+        1
+    } else {
+        options.threads.max(1)
+    };
+    if threads > 1 {
+        println!("Handling requests on {} threads", threads);
     }
     println!("Press Ctrl-C to quit.");
 
     // Initialize PHP web runtime
     let php = PhpWeb::new().map_err(|e| anyhow::anyhow!("Failed to initialize PHP: {}", e))?;
+    let metrics = start_metrics(host, options)?;
+
+    let site = Site {
+        document_root: &document_root,
+        router,
+        host,
+        port,
+        opcache_status: options.opcache_status,
+        metrics: metrics.as_deref(),
+        metrics_endpoint: options.metrics,
+        upload_limit: options.upload_max_filesize,
+        xdebug: options.xdebug,
+        routes: &options.routes,
+        trusted_proxies: &options.trusted_proxies,
+    };
+
+    // The calling thread serves requests as well, additional threads need a ZTS build
+    std::thread::scope(|scope| {
+        for _ in 1..threads {
+            scope.spawn(|| match php.attach_thread() {
+                Ok(thread) => serve_web_requests(&server, &site, |r, body| thread.execute_with_body(r, body), || thread.opcache_status()),
+                Err(e) => eprintln!("Failed to start request thread: {}", e),
+            });
+        }
+
+        serve_web_requests(&server, &site, |r, body| php.execute_with_body(r, body), || php.opcache_status());
+    });
+
+    Ok(0)
+}
+
+/// Where and how the standard (non-worker) server handles requests
+struct Site<'a> {
+    document_root: &'a Path,
+    router: Option<&'a Path>,
+    host: &'a str,
+    port: u16,
+    opcache_status: bool,
+    metrics: Option<&'a Metrics>,
+    metrics_endpoint: bool,
+    upload_limit: Option<u64>,
+    xdebug: bool,
+    routes: &'a routes::RouteRules,
+    trusted_proxies: &'a proxy::TrustedProxies,
+}
 
+/// Request loop of the standard server, run on every request thread
+fn serve_web_requests(
+    server: &Server,
+    site: &Site,
+    execute: impl Fn(HttpRequest, &mut dyn Read) -> std::result::Result<pox_embed::HttpResponse, pox_embed::PhpError>,
+    opcache_status: impl Fn() -> Option<String>,
+) {
     for mut request in server.incoming_requests() {
         let method = request.method().to_string();
         let url = request.url().to_string();
         let (path, query_string) = parse_url(&url);
 
-        // Try to serve static file first
-        if let Some((content, content_type)) = get_static_file_content(&document_root, &path) {
-            serve_static_file(request, content, &content_type, &method, &url);
+        if site.opcache_status && path == opcache::STATUS_PATH {
+            send_opcache_status(request, opcache_status(), &method, &url);
             continue;
         }
 
-        // Determine the script to execute
-        let script_path = resolve_script_path(&document_root, &path, router);
+        if let Some(metrics) = site.metrics.filter(|_| site.metrics_endpoint && path == metrics::METRICS_PATH) {
+            send_metrics(request, metrics);
+            continue;
+        }
 
-        // Check if the file exists
-        if !script_path.exists() || !script_path.is_file() {
-            send_error_response(request, 404, "The requested URL was not found on this server.", &method, &url);
+        let route = site.routes.route(&path);
+        if route == Route::Deny {
+            send_error_response(request, 403, "Access to the requested URL is not allowed.", &method, &url);
             continue;
         }
 
-        let (headers, body, remote_addr, remote_port) = extract_request_metadata(&mut request);
-        let php_request = build_php_request(
-            method.clone(),
-            url.clone(),
-            query_string,
-            headers,
-            body,
-            &document_root,
-            &script_path,
-            host,
-            port,
-            remote_addr,
-            remote_port,
-        );
+        let (mut headers, remote_addr, remote_port, protocol) = extract_request_metadata(&request);
+        let client = site.trusted_proxies.resolve(&headers, &remote_addr);
+        if site.upload_limit.is_some_and(|limit| upload::exceeds_limit(&headers, limit)) {
+            send_error_response(request, 413, "The request body exceeds the upload limit.", &method, &url);
+            continue;
+        }
+        let trace = debug::RequestTrace::start(&mut headers);
+        if site.xdebug {
+            debug::inject_xdebug_trigger(&mut headers);
+        }
 
-        let result = php.execute(php_request);
-        send_php_response(request, result, &method, &url);
-    }
+        let php_request = |script: &Script| HttpRequest {
+            https: client.https,
+            ..build_php_request(
+                method.clone(),
+                url.clone(),
+                query_string.clone(),
+                headers.clone(),
+                site.document_root,
+                script,
+                site.host,
+                site.port,
+                client.remote_addr.clone(),
+                remote_port,
+                &protocol,
+            )
+        };
 
-    Ok(0)
-}
+        // Like php -S, the router sees every request and returns false to decline it.
+        // The body is buffered as it may be passed to PHP twice.
+        let mut buffered = None;
+        if let Some(router) = site.router.filter(|_| route != Route::Static) {
+            let (body, exceeded) = read_limited_body(&mut request, site.upload_limit);
+            if exceeded {
+                send_error_response(request, 413, "The request body exceeds the upload limit.", &method, &url);
+                continue;
+            }
 
-/// Resolve the PHP script path for a request
-fn resolve_script_path(document_root: &Path, url_path: &str, router: Option<&Path>) -> PathBuf {
-    if let Some(router) = router {
-        return router.to_path_buf();
-    }
+            let mut router_request = php_request(&Script::router(router, &path));
+            router_request.router = true;
 
-    let mut file_path = document_root.to_path_buf();
-    let url_path = url_path.trim_start_matches('/');
-    if url_path.is_empty() {
-        file_path.push("index.php");
-    } else {
-        file_path.push(url_path);
-    }
+            let mut result = run_php(site.metrics, || trace.time_php(|| execute(router_request, &mut body.as_slice())));
+            if !matches!(&result, Ok(response) if response.declined) {
+                trace.finish(&mut result, &method, &url);
+                send_php_response(request, result, &method, &url);
+                continue;
+            }
+            buffered = Some(body);
+        }
 
-    // If it's a directory, look for index.php
-    if file_path.is_dir() {
-        file_path.push("index.php");
-    }
+        // Try to serve static file first
+        match static_files::lookup(site.document_root, &path) {
+            Lookup::File(file) => {
+                static_files::serve(request, &file, &method, &url);
+                continue;
+            }
+            Lookup::Forbidden => {
+                send_error_response(request, 403, "Access to the requested URL is not allowed.", &method, &url);
+                continue;
+            }
+            Lookup::NotFound if route == Route::Static => {
+                send_error_response(request, 404, "The requested URL was not found on this server.", &method, &url);
+                continue;
+            }
+            Lookup::NotFound => {}
+        }
+
+        // Determine the script to execute
+        let Some(script) = router::resolve(site.document_root, &path) else {
+            send_error_response(request, 404, "The requested URL was not found on this server.", &method, &url);
+            continue;
+        };
+        if !site.routes.allows_script(&script.script_name) {
+            send_error_response(request, 403, "Access to the requested URL is not allowed.", &method, &url);
+            continue;
+        }
 
-    // If the file doesn't exist, fall back to index.php (front controller pattern)
-    if !file_path.exists() || !file_path.is_file() {
-        let index_php = document_root.join("index.php");
-        if index_php.exists() && index_php.is_file() {
-            return index_php;
+        let (mut result, exceeded) = match &buffered {
+            Some(body) => (run_php(site.metrics, || trace.time_php(|| execute(php_request(&script), &mut body.as_slice()))), false),
+            None => {
+                let mut body = LimitedBody::new(request.as_reader(), site.upload_limit);
+                let result = run_php(site.metrics, || trace.time_php(|| execute(php_request(&script), &mut body)));
+                (result, body.exceeded())
+            }
+        };
+        if exceeded {
+            send_error_response(request, 413, "The request body exceeds the upload limit.", &method, &url);
+            continue;
         }
+        trace.finish(&mut result, &method, &url);
+        send_php_response(request, result, &method, &url);
     }
+}
 
-    file_path
+/// Execute a PHP request, recording it in the metrics if enabled
+///
+/// Errors of requests executed on the calling thread end up on an error page when fatal.
+fn run_php(
+    metrics: Option<&Metrics>,
+    execute: impl FnOnce() -> std::result::Result<pox_embed::HttpResponse, pox_embed::PhpError>,
+) -> std::result::Result<pox_embed::HttpResponse, pox_embed::PhpError> {
+    let (result, errors) = php_errors::collect(|| match metrics {
+        Some(metrics) => metrics.track(execute),
+        None => execute(),
+    });
+    result.map(|response| php_errors::render_fatal(response, &errors))
 }
 
-fn run_worker_server(server: Server, host: &str, port: u16, document_root: &Path, worker_script: &Path, num_workers: usize, watch_patterns: Vec<String>) -> Result<i32> {
+/// Read the whole request body, returns whether it was cut off at the upload limit
+fn read_limited_body(request: &mut http::Request, limit: Option<u64>) -> (Vec<u8>, bool) {
+    let mut body = Vec::new();
+    let mut reader = LimitedBody::new(request.as_reader(), limit);
+    if let Err(e) = reader.read_to_end(&mut body) {
+        eprintln!("Failed to read request body: {}", e);
+    }
+    let exceeded = reader.exceeded();
+    (body, exceeded)
+}
+
+fn run_worker_server(server: Server, host: &str, port: u16, document_root: &Path, worker_script: &Path, num_workers: usize, options: &ServerOptions) -> Result<i32> {
     let document_root = document_root.to_path_buf();
     let worker_script = worker_script.canonicalize()
         .map_err(|e| anyhow::anyhow!("Worker script not found: {}", e))?;
+    let script = Script::fixed(&document_root, &worker_script);
     let host = host.to_string();
 
     println!("Press Ctrl-C to quit.");
@@ -301,74 +720,35 @@ fn run_worker_server(server: Server, host: &str, port: u16, document_root: &Path
         worker_script.to_string_lossy().as_ref(),
         document_root.to_string_lossy().as_ref(),
         num_workers,
-    ).map_err(|e| anyhow::anyhow!("Failed to initialize PHP worker pool: {}", e))?));
+    ).map_err(|e| anyhow::anyhow!("Failed to initialize PHP worker pool: {}", e))?
+        .with_request_timeout(options.request_timeout.map(|t| t + WORKER_TIMEOUT_GRACE))
+        .with_max_requests(options.max_requests_per_worker)));
+
+    let metrics = start_metrics(&host, options)?;
+    if let Some(metrics) = &metrics {
+        metrics.set_worker_stats(worker_pool.lock().unwrap_or_else(|e| e.into_inner()).stats());
+    }
 
     // Set up file watcher if patterns are provided
     let restart_flag = Arc::new(AtomicBool::new(false));
-    let _watcher = if !watch_patterns.is_empty() {
-        // Build glob set from patterns
-        let mut glob_builder = GlobSetBuilder::new();
-        for pattern in &watch_patterns {
-            match Glob::new(pattern) {
-                Ok(glob) => { glob_builder.add(glob); }
-                Err(e) => eprintln!("Invalid glob pattern '{}': {}", pattern, e),
-            }
-        }
-        let glob_set = glob_builder.build().map_err(|e| anyhow::anyhow!("Failed to build glob set: {}", e))?;
-
-        // Create debounced watcher
+    let _watcher = if !options.watch.is_empty() {
         let restart_flag_clone = restart_flag.clone();
-        let (tx, rx) = std::sync::mpsc::channel();
-
-        let mut debouncer = new_debouncer(
-            Duration::from_millis(150),
-            None,
-            move |result: std::result::Result<Vec<DebouncedEvent>, Vec<notify::Error>>| {
-                if let Ok(events) = result {
-                    let _ = tx.send(events);
-                }
-            },
-        ).map_err(|e| anyhow::anyhow!("Failed to create file watcher: {}", e))?;
-
-        // Watch the document root recursively
-        debouncer.watch(&document_root, RecursiveMode::Recursive)
-            .map_err(|e| anyhow::anyhow!("Failed to watch directory: {}", e))?;
-
-        // Spawn thread to handle file change events
         let worker_pool_clone = worker_pool.clone();
-        let doc_root_clone = document_root.clone();
-        std::thread::spawn(move || {
-            while let Ok(events) = rx.recv() {
-                // Check if any changed file matches our patterns
-                let mut should_restart = false;
-                for event in events {
-                    for path in &event.paths {
-                        // Get relative path from document root
-                        if let Ok(rel_path) = path.strip_prefix(&doc_root_clone) {
-                            let rel_path_str = rel_path.to_string_lossy();
-                            if glob_set.is_match(&*rel_path_str) || glob_set.is_match(path) {
-                                eprintln!("File changed: {}", path.display());
-                                should_restart = true;
-                            }
-                        } else if glob_set.is_match(path) {
-                            eprintln!("File changed: {}", path.display());
-                            should_restart = true;
-                        }
-                    }
-                }
 
-                if should_restart {
-                    restart_flag_clone.store(true, Ordering::SeqCst);
-                    // Restart workers
-                    if let Ok(mut pool) = worker_pool_clone.lock() {
-                        pool.restart();
-                    }
-                    restart_flag_clone.store(false, Ordering::SeqCst);
-                }
+        // Restart the workers when a file matching the patterns changes
+        let watcher = watch::watch(&document_root, &options.watch, move |changed| {
+            for path in &changed {
+                eprintln!("File changed: {}", path.display());
             }
-        });
 
-        Some(debouncer)
+            restart_flag_clone.store(true, Ordering::SeqCst);
+            if let Ok(mut pool) = worker_pool_clone.lock() {
+                pool.restart();
+            }
+            restart_flag_clone.store(false, Ordering::SeqCst);
+        })?;
+
+        Some(watcher)
     } else {
         None
     };
@@ -379,87 +759,168 @@ fn run_worker_server(server: Server, host: &str, port: u16, document_root: &Path
         let url = request.url().to_string();
         let (path, query_string) = parse_url(&url);
 
-        // Try to serve static files first
-        if let Some((content, content_type)) = get_static_file_content(&document_root, &path) {
-            serve_static_file(request, content, &content_type, &method, &url);
+        if options.opcache_status && path == opcache::STATUS_PATH {
+            let status = {
+                let pool = worker_pool.lock().unwrap_or_else(|e| e.into_inner());
+                pool.opcache_status()
+            };
+            send_opcache_status(request, status, &method, &url);
+            continue;
+        }
+
+        if let Some(metrics) = metrics.as_ref().filter(|_| options.metrics && path == metrics::METRICS_PATH) {
+            send_metrics(request, metrics);
+            continue;
+        }
+
+        let route = options.routes.route(&path);
+        if route == Route::Deny {
+            send_error_response(request, 403, "Access to the requested URL is not allowed.", &method, &url);
             continue;
         }
 
+        // Try to serve static files first
+        match static_files::lookup(&document_root, &path) {
+            Lookup::File(file) => {
+                static_files::serve(request, &file, &method, &url);
+                continue;
+            }
+            Lookup::Forbidden => {
+                send_error_response(request, 403, "Access to the requested URL is not allowed.", &method, &url);
+                continue;
+            }
+            Lookup::NotFound if route == Route::Static => {
+                send_error_response(request, 404, "The requested URL was not found on this server.", &method, &url);
+                continue;
+            }
+            Lookup::NotFound => {}
+        }
+
         // Wait if workers are restarting
         while restart_flag.load(Ordering::SeqCst) {
             std::thread::sleep(Duration::from_millis(10));
         }
 
-        let (headers, body, remote_addr, remote_port) = extract_request_metadata(&mut request);
-        let php_request = build_php_request(
-            method.clone(),
-            url.clone(),
-            query_string,
-            headers,
-            body,
-            &document_root,
-            &worker_script,
-            &host,
-            port,
-            remote_addr,
-            remote_port,
-        );
+        let (mut headers, remote_addr, remote_port, protocol) = extract_request_metadata(&request);
+        let client = options.trusted_proxies.resolve(&headers, &remote_addr);
+        if options.upload_max_filesize.is_some_and(|limit| upload::exceeds_limit(&headers, limit)) {
+            send_error_response(request, 413, "The request body exceeds the upload limit.", &method, &url);
+            continue;
+        }
+        let trace = debug::RequestTrace::start(&mut headers);
+        if options.xdebug {
+            debug::inject_xdebug_trigger(&mut headers);
+        }
+        let affinity_key = options.worker_affinity.as_ref().and_then(|a| a.key(&headers, &client.remote_addr));
+
+        let php_request = HttpRequest {
+            https: client.https,
+            ..build_php_request(
+                method.clone(),
+                url.clone(),
+                query_string,
+                headers,
+                &document_root,
+                &script,
+                &host,
+                port,
+                client.remote_addr,
+                remote_port,
+                &protocol,
+            )
+        };
 
-        // Execute through worker pool
-        let result = {
+        // Execute through worker pool, streaming the request body into the worker
+        let (mut result, exceeded) = {
             let pool = worker_pool.lock().unwrap_or_else(|e| e.into_inner());
-            pool.handle_request(php_request)
+            let mut body = LimitedBody::new(request.as_reader(), options.upload_max_filesize);
+            let result = run_php(metrics.as_deref(), || trace.time_php(|| match &affinity_key {
+                Some(key) => pool.handle_request_with_key_and_body(key, php_request, &mut body),
+                None => pool.handle_request_with_body(php_request, &mut body),
+            }));
+            if let Some(metrics) = &metrics {
+                metrics.set_worker_stats(pool.stats());
+            }
+            (result, body.exceeded())
         };
 
+        if exceeded {
+            send_error_response(request, 413, "The request body exceeds the upload limit.", &method, &url);
+            continue;
+        }
+        trace.finish(&mut result, &method, &url);
         send_php_response(request, result, &method, &url);
     }
 
     Ok(0)
 }
 
-fn make_content_type_header(content_type: &str) -> Option<Header> {
-    Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes()).ok()
-}
+/// Create the request metrics if enabled and serve them on the metrics port
+fn start_metrics(host: &str, options: &ServerOptions) -> Result<Option<Arc<Metrics>>> {
+    if !options.metrics && options.metrics_port.is_none() {
+        return Ok(None);
+    }
+    let metrics = Arc::new(Metrics::new());
 
-/// Parse URL into path and query string
-fn parse_url(url: &str) -> (String, String) {
-    if let Some(pos) = url.find('?') {
-        (url[..pos].to_string(), url[pos + 1..].to_string())
-    } else {
-        (url.to_string(), String::new())
+    if let Some(port) = options.metrics_port {
+        let addr = format!("{}:{}", host, port);
+        let server = Server::http(&addr, ConnectionLimits::default())
+            .map_err(|e| anyhow::anyhow!("Failed to start metrics server: {}", e))?;
+        println!("Metrics are available at http://{}{}", addr, metrics::METRICS_PORT_PATH);
+
+        let metrics = metrics.clone();
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                if parse_url(request.url()).0 == metrics::METRICS_PORT_PATH {
+                    send_metrics(request, &metrics);
+                } else {
+                    let _ = request.respond(Response::empty(404));
+                }
+            }
+        });
     }
+
+    Ok(Some(metrics))
 }
 
-/// Check if a static file can be served, returns Some(content) if so
-fn get_static_file_content(document_root: &Path, path: &str) -> Option<(Vec<u8>, String)> {
-    let static_path = document_root.join(path.trim_start_matches('/'));
-    if static_path.exists() && static_path.is_file() && !path.ends_with(".php") {
-        if let Ok(content) = std::fs::read(&static_path) {
-            let content_type = guess_content_type(&static_path);
-            return Some((content, content_type));
-        }
+/// Send the metrics endpoint response
+fn send_metrics(request: http::Request, metrics: &Metrics) {
+    let mut response = Response::from_string(metrics.render());
+    if let Some(header) = make_content_type_header(metrics::CONTENT_TYPE) {
+        response = response.with_header(header);
     }
-    None
+    let _ = request.respond(response);
 }
 
-/// Serve a static file response
-fn serve_static_file(request: tiny_http::Request, content: Vec<u8>, content_type: &str, method: &str, url: &str) {
-    let mut response = Response::from_data(content);
-    if let Some(header) = make_content_type_header(content_type) {
+/// Send the opcache status endpoint response
+fn send_opcache_status(request: http::Request, raw_status: Option<String>, method: &str, url: &str) {
+    let (status, body) = opcache::status_body(raw_status.as_deref());
+    let body = serde_json::to_string_pretty(&body).unwrap_or_default();
+
+    let mut response = Response::from_string(body)
+        .with_status_code(StatusCode(status));
+    if let Some(header) = make_content_type_header("application/json") {
         response = response.with_header(header);
     }
     let _ = request.respond(response);
-    println!("{} {} - 200", method, url);
+    println!("{} {} - {}", method, url, status);
 }
 
-/// Extract request metadata from tiny_http::Request
-fn extract_request_metadata(request: &mut tiny_http::Request) -> (Vec<(String, String)>, Vec<u8>, String, u16) {
-    // Read request body
-    let mut body = Vec::new();
-    if let Err(e) = request.as_reader().read_to_end(&mut body) {
-        eprintln!("Failed to read request body: {}", e);
+fn make_content_type_header(content_type: &str) -> Option<Header> {
+    Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes()).ok()
+}
+
+/// Parse URL into path and query string
+fn parse_url(url: &str) -> (String, String) {
+    if let Some(pos) = url.find('?') {
+        (url[..pos].to_string(), url[pos + 1..].to_string())
+    } else {
+        (url.to_string(), String::new())
     }
+}
 
+/// Extract headers, remote address and port and protocol from a request
+fn extract_request_metadata(request: &http::Request) -> (Vec<(String, String)>, String, u16, String) {
     // Collect headers
     let headers: Vec<(String, String)> = request
         .headers()
@@ -477,7 +938,9 @@ fn extract_request_metadata(request: &mut tiny_http::Request) -> (Vec<(String, S
         .map(|a| a.port())
         .unwrap_or(0);
 
-    (headers, body, remote_addr, remote_port)
+    let protocol = format!("HTTP/{}", request.http_version());
+
+    (headers, remote_addr, remote_port, protocol)
 }
 
 /// Build an HttpRequest for PHP
@@ -486,32 +949,37 @@ fn build_php_request(
     url: String,
     query_string: String,
     headers: Vec<(String, String)>,
-    body: Vec<u8>,
     document_root: &Path,
-    script_filename: &Path,
+    script: &Script,
     host: &str,
     port: u16,
     remote_addr: String,
     remote_port: u16,
+    protocol: &str,
 ) -> HttpRequest {
     HttpRequest {
         method,
         uri: url,
         query_string,
         headers,
-        body,
+        body: Vec::new(),
         document_root: document_root.to_string_lossy().to_string(),
-        script_filename: script_filename.to_string_lossy().to_string(),
+        script_filename: script.filename.to_string_lossy().to_string(),
+        script_name: script.script_name.clone(),
+        path_info: script.path_info.clone(),
+        router: false,
         server_name: host.to_string(),
         server_port: port,
         remote_addr,
         remote_port,
+        protocol: protocol.to_string(),
+        https: false,
     }
 }
 
 /// Send a PHP response back to the client
 fn send_php_response(
-    request: tiny_http::Request,
+    request: http::Request,
     result: std::result::Result<pox_embed::HttpResponse, pox_embed::PhpError>,
     method: &str,
     url: &str,
@@ -532,27 +1000,36 @@ fn send_php_response(
             println!("{} {} - {}", method, url, status);
         }
         Err(e) => {
-            send_error_response(request, 500, &e.to_string(), method, url);
+            let status = metrics::error_status(&e);
+            send_error_response(request, status, &e.to_string(), method, url);
         }
     }
 }
 
 /// Send an error response
 fn send_error_response(
-    request: tiny_http::Request,
+    request: http::Request,
     status_code: u16,
     message: &str,
     method: &str,
     url: &str,
 ) {
     let title = match status_code {
+        403 => "403 Forbidden",
         404 => "404 Not Found",
+        413 => "413 Payload Too Large",
         500 => "500 Internal Server Error",
+        502 => "502 Bad Gateway",
+        504 => "504 Gateway Timeout",
         _ => "Error",
     };
     let heading = match status_code {
+        403 => "Forbidden",
         404 => "Not Found",
+        413 => "Payload Too Large",
         500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        504 => "Gateway Timeout",
         _ => "Error",
     };
     let body = format!(
@@ -572,39 +1049,25 @@ fn send_error_response(
     }
 }
 
-fn guess_content_type(path: &Path) -> String {
-    let extension = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("");
-
-    match extension.to_lowercase().as_str() {
-        "html" | "htm" => "text/html",
-        "css" => "text/css",
-        "js" => "application/javascript",
-        "json" => "application/json",
-        "png" => "image/png",
-        "jpg" | "jpeg" => "image/jpeg",
-        "gif" => "image/gif",
-        "svg" => "image/svg+xml",
-        "ico" => "image/x-icon",
-        "woff" => "font/woff",
-        "woff2" => "font/woff2",
-        "ttf" => "font/ttf",
-        "txt" => "text/plain",
-        "xml" => "application/xml",
-        "pdf" => "application/pdf",
-        _ => "application/octet-stream",
-    }
-    .to_string()
-}
-
 fn run() -> Result<i32> {
-    let args = Args::parse();
+    let matches = pm::with_plugin_commands(Args::command()).get_matches();
 
     // Load pox.toml config if present
     let config = PoxConfig::load_from_cwd()?;
 
+    // Run with the PHP version pinned in pox.toml, unless managing the versions
+    if !matches!(matches.subcommand_name(), Some("php" | "completion" | "__complete-package")) {
+        let project_config = runtime::project_config(&matches)?;
+        if let Some(code) = runtime::delegate(project_config.as_ref())? {
+            return Ok(code);
+        }
+    }
+
+    if let Some(code) = pm::execute_plugin_command(&matches)? {
+        return Ok(code);
+    }
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
     // Handle subcommands first
     if let Some(command) = args.command {
         match command {
@@ -615,8 +1078,33 @@ fn run() -> Result<i32> {
                 router,
                 worker,
                 workers,
+                worker_affinity,
                 watch,
+                preload,
+                opcache_status,
+                threads,
+                request_timeout,
+                memory_limit,
+                max_requests_per_worker,
+                metrics,
+                metrics_port,
+                upload_max_filesize,
+                xdebug,
+                session_path,
+                max_connections,
+                keep_alive_timeout,
+                env,
+                trusted_proxies,
+                io,
             } => {
+                io.init();
+
+                // Set while the process is still single threaded, before PHP or any server
+                // thread starts, so worker scripts see them in getenv() and $_ENV from the beginning
+                for (key, value) in build_env_vars(config.as_ref(), &env)? {
+                    std::env::set_var(key, value);
+                }
+
                 // Merge CLI args with config file settings (CLI takes precedence)
                 let effective_host = config.as_ref()
                     .and_then(|c| c.server.host.clone())
@@ -650,6 +1138,81 @@ fn run() -> Result<i32> {
                     watch
                 };
 
+                let options = ServerOptions {
+                    watch: effective_watch,
+                    preload: preload.or_else(|| {
+                        config.as_ref()
+                            .and_then(|c| c.server.preload.as_ref().map(PathBuf::from))
+                    }),
+                    opcache_status: opcache_status || config.as_ref()
+                        .and_then(|c| c.server.opcache_status)
+                        .unwrap_or(false),
+                    threads: if threads == 0 {
+                        config.as_ref()
+                            .and_then(|c| c.server.threads)
+                            .unwrap_or(1)
+                    } else {
+                        threads
+                    },
+                    request_timeout: request_timeout
+                        .or_else(|| config.as_ref().and_then(|c| c.server.request_timeout))
+                        .filter(|secs| *secs > 0)
+                        .map(Duration::from_secs),
+                    memory_limit: memory_limit.or_else(|| {
+                        config.as_ref().and_then(|c| c.server.memory_limit.clone())
+                    }),
+                    max_requests_per_worker: max_requests_per_worker
+                        .or_else(|| config.as_ref().and_then(|c| c.server.max_requests_per_worker)),
+                    worker_affinity: worker_affinity
+                        .or_else(|| config.as_ref().and_then(|c| c.server.worker_affinity.clone()))
+                        .map(|source| affinity::Affinity::parse(&source))
+                        .transpose()?,
+                    metrics: metrics || config.as_ref()
+                        .and_then(|c| c.server.metrics)
+                        .unwrap_or(false),
+                    metrics_port: metrics_port
+                        .or_else(|| config.as_ref().and_then(|c| c.server.metrics_port)),
+                    upload_max_filesize: match upload_max_filesize
+                        .or_else(|| config.as_ref().and_then(|c| c.server.upload_max_filesize.clone()))
+                    {
+                        Some(size) => Some(upload::parse_size(&size)
+                            .ok_or_else(|| anyhow::anyhow!("Invalid upload size: {}", size))?),
+                        None => None,
+                    },
+                    xdebug: xdebug || config.as_ref()
+                        .and_then(|c| c.server.xdebug)
+                        .unwrap_or(false),
+                    session_path: match (session_path, config.as_ref().and_then(|c| c.server.session_path.as_ref())) {
+                        (Some(path), _) => Some(path),
+                        // Relative to the project of pox.toml, not to where the server is started
+                        (None, Some(path)) => Some(PoxConfig::dir_from_cwd()?.unwrap_or_default().join(path)),
+                        (None, None) => None,
+                    },
+                    routes: match &config {
+                        Some(c) => routes::RouteRules::new(&c.server.routes)?,
+                        None => routes::RouteRules::default(),
+                    },
+                    connections: {
+                        let defaults = ConnectionLimits::default();
+                        ConnectionLimits {
+                            max_connections: max_connections
+                                .or_else(|| config.as_ref().and_then(|c| c.server.max_connections))
+                                .filter(|count| *count > 0)
+                                .unwrap_or(defaults.max_connections),
+                            keep_alive_timeout: keep_alive_timeout
+                                .or_else(|| config.as_ref().and_then(|c| c.server.keep_alive_timeout))
+                                .map(Duration::from_secs)
+                                .unwrap_or(defaults.keep_alive_timeout),
+                            ..defaults
+                        }
+                    },
+                    trusted_proxies: proxy::TrustedProxies::parse(if trusted_proxies.is_empty() {
+                        config.as_ref().map(|c| c.server.trusted_proxies.as_slice()).unwrap_or_default()
+                    } else {
+                        &trusted_proxies
+                    })?,
+                };
+
                 return run_server(
                     &effective_host,
                     effective_port,
@@ -657,64 +1220,116 @@ fn run() -> Result<i32> {
                     effective_router.as_deref(),
                     effective_worker.as_deref(),
                     effective_workers,
-                    effective_watch,
                     config.as_ref(),
+                    &options,
                 );
             }
-            Commands::Init(init_args) => {
+            Commands::Init { io, args: init_args } => {
+                io.init();
                 let rt = tokio::runtime::Runtime::new()
                     .map_err(|e| anyhow::anyhow!("Failed to create async runtime: {}", e))?;
                 return rt.block_on(init::execute(init_args));
             }
-            Commands::CreateProject(create_project_args) => {
+            Commands::CreateProject { io, args: create_project_args } => {
+                io.init();
                 let rt = tokio::runtime::Runtime::new()
                     .map_err(|e| anyhow::anyhow!("Failed to create async runtime: {}", e))?;
                 return rt.block_on(create_project::execute(create_project_args));
             }
-            Commands::Install(install_args) => {
+            Commands::Install { io, args: install_args } => {
+                io.init();
                 let rt = tokio::runtime::Runtime::new()
                     .map_err(|e| anyhow::anyhow!("Failed to create async runtime: {}", e))?;
                 return rt.block_on(install::execute(install_args));
             }
-            Commands::Update(update_args) => {
+            Commands::Update { io, args: update_args } => {
+                io.init();
                 let rt = tokio::runtime::Runtime::new()
                     .map_err(|e| anyhow::anyhow!("Failed to create async runtime: {}", e))?;
                 return rt.block_on(update::execute(update_args));
             }
-            Commands::Add(add_args) => {
+            Commands::Add { io, args: add_args } => {
+                io.init();
                 let rt = tokio::runtime::Runtime::new()
                     .map_err(|e| anyhow::anyhow!("Failed to create async runtime: {}", e))?;
                 return rt.block_on(add::execute(add_args));
             }
-            Commands::Remove(remove_args) => {
+            Commands::Remove { io, args: remove_args } => {
+                io.init();
                 let rt = tokio::runtime::Runtime::new()
                     .map_err(|e| anyhow::anyhow!("Failed to create async runtime: {}", e))?;
                 return rt.block_on(remove::execute(remove_args));
             }
-            Commands::Pm { command } => {
+            Commands::Pm { io, command } => {
+                io.init();
                 let rt = tokio::runtime::Runtime::new()
                     .map_err(|e| anyhow::anyhow!("Failed to create async runtime: {}", e))?;
                 return rt.block_on(pm::execute(command));
             }
-            Commands::Run(run_args) => {
+            Commands::Workspace { io, command } => {
+                io.init();
+                let rt = tokio::runtime::Runtime::new()
+                    .map_err(|e| anyhow::anyhow!("Failed to create async runtime: {}", e))?;
+                return rt.block_on(workspace::execute(command));
+            }
+            Commands::Run { io, args: run_args } => {
+                io.init();
                 let rt = tokio::runtime::Runtime::new()
                     .map_err(|e| anyhow::anyhow!("Failed to create async runtime: {}", e))?;
                 return rt.block_on(pm::run::execute(run_args));
             }
+            Commands::Ext { io, working_dir, command } => {
+                io.init();
+                let rt = tokio::runtime::Runtime::new()
+                    .map_err(|e| anyhow::anyhow!("Failed to create async runtime: {}", e))?;
+                return rt.block_on(ext::execute(command, &working_dir));
+            }
+            Commands::Php { io, command } => {
+                io.init();
+                let rt = tokio::runtime::Runtime::new()
+                    .map_err(|e| anyhow::anyhow!("Failed to create async runtime: {}", e))?;
+                return rt.block_on(runtime::execute(command));
+            }
+            Commands::Repl(repl_args) => {
+                let ini_entries = build_ini_entries(config.as_ref(), &repl_args.define, &std::env::current_dir()?);
+                return repl::execute(repl_args, ini_entries);
+            }
+            Commands::Phar { command } => {
+                return phar::execute(command);
+            }
+            #[cfg(unix)]
+            Commands::Daemon(daemon_args) => {
+                return daemon::execute(daemon_args);
+            }
             Commands::Completion { shell } => {
                 let mut cmd = Args::command();
-                generate(shell, &mut cmd, "pox", &mut std::io::stdout());
+                completions::write_script(shell, &mut cmd, &mut std::io::stdout())?;
                 return Ok(0);
             }
+            Commands::CompletePackage(complete_args) => {
+                return completions::complete_package(complete_args);
+            }
         }
     }
 
     // Set INI entries from config file and CLI args
-    let ini_entries = build_ini_entries(config.as_ref(), &args.define);
+    let ini_entries = build_ini_entries(config.as_ref(), &args.define, &std::env::current_dir()?);
     if ini_entries.is_some() {
         Php::set_ini_entries(ini_entries.as_deref())?;
     }
 
+    // Handle -a/--interactive
+    if args.interactive {
+        return repl::execute(
+            repl::ReplArgs {
+                define: Vec::new(),
+                no_history: false,
+                args: Vec::new(),
+            },
+            None,
+        );
+    }
+
     // Handle -v/--version
     if args.version_flag {
         print_version();
@@ -773,6 +1388,7 @@ fn run() -> Result<i32> {
     eprintln!("Options:");
     eprintln!("  -d key[=value]  Define INI entry");
     eprintln!("  -i              PHP information (phpinfo)");
+    eprintln!("  -a              Run as interactive shell");
     eprintln!("  -l              Syntax check only (lint)");
     eprintln!("  -m              Show compiled in modules");
     eprintln!("  -r <code>       Run PHP <code> without script tags");
@@ -787,6 +1403,10 @@ fn run() -> Result<i32> {
     eprintln!("  remove          Remove a package from the project");
     eprintln!("  run             Run a script defined in composer.json");
     eprintln!("  server          Start a PHP development server");
+    eprintln!("  repl            Interactive PHP shell");
+    eprintln!("  phar            Inspect and extract phar archives");
+    eprintln!("  ext             Manage shared PHP extensions of the project");
+    eprintln!("  php             Manage the PHP versions of projects (list, install, use)");
     eprintln!("  pm              Other package manager commands (dump-autoload, exec, etc.)");
     eprintln!("  completion      Generate shell completion scripts");
     eprintln!();
@@ -796,7 +1416,10 @@ fn run() -> Result<i32> {
 }
 
 fn main() -> ExitCode {
-    match run() {
+    let result = run();
+    io::profiler().finish();
+
+    match result {
         Ok(code) => ExitCode::from(code as u8),
         Err(e) => {
             eprintln!("Error: {}", e);
@@ -804,6 +1427,15 @@ fn main() -> ExitCode {
             for cause in e.chain().skip(1) {
                 eprintln!("  Caused by: {}", cause);
             }
+            // Point the user at a fix when the failure is a known package manager error
+            if let Some(hint) = e
+                .chain()
+                .find_map(|cause| cause.downcast_ref::<pox_pm::ComposerError>())
+                .and_then(|error| error.hint())
+            {
+                eprintln!();
+                eprintln!("{} {}", console::style("Hint:").yellow().bold(), hint);
+            }
             ExitCode::FAILURE
         }
     }
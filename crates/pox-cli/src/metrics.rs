@@ -0,0 +1,273 @@
+//! Prometheus metrics for server mode.
+//!
+//! Requests are recorded around the PHP execution on the Rust side, so the
+//! same counters work for the standard server and the worker pool.
+
+use pox_embed::{HttpResponse, PhpError, WorkerStats};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Path of the opt-in metrics endpoint on the server port
+pub const METRICS_PATH: &str = "/__pox/metrics";
+
+/// Path of the metrics endpoint on a dedicated `--metrics-port`
+pub const METRICS_PORT_PATH: &str = "/metrics";
+
+/// Content type of the Prometheus text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Upper bounds of the request duration buckets in seconds
+const DURATION_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Upper bounds of the peak memory buckets in bytes (1 MiB to 512 MiB)
+const MEMORY_BUCKETS: &[f64] = &[
+    1048576.0, 2097152.0, 4194304.0, 8388608.0, 16777216.0, 33554432.0, 67108864.0,
+    134217728.0, 268435456.0, 536870912.0,
+];
+
+/// Cumulative histogram with fixed buckets
+struct Histogram {
+    bounds: &'static [f64],
+    /// Observations per bucket, the last entry is `+Inf`
+    counts: Vec<AtomicU64>,
+    /// Sum of all observations, stored as f64 bits
+    sum: AtomicU64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            sum: AtomicU64::new(0f64.to_bits()),
+        }
+    }
+
+    fn observe(&self, value: f64) {
+        let bucket = self.bounds.iter().position(|bound| value <= *bound).unwrap_or(self.bounds.len());
+        self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+
+        let _ = self.sum.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+            Some((f64::from_bits(bits) + value).to_bits())
+        });
+    }
+
+    fn render(&self, out: &mut String, name: &str) {
+        let mut cumulative = 0;
+        for (i, count) in self.counts.iter().enumerate() {
+            cumulative += count.load(Ordering::Relaxed);
+            let le = self.bounds.get(i).map_or("+Inf".to_string(), |bound| bound.to_string());
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, le, cumulative);
+        }
+        let _ = writeln!(out, "{}_sum {}", name, f64::from_bits(self.sum.load(Ordering::Relaxed)));
+        let _ = writeln!(out, "{}_count {}", name, cumulative);
+    }
+}
+
+/// Request metrics shared by all request threads
+pub struct Metrics {
+    /// PHP requests by response status code
+    requests: Mutex<BTreeMap<u16, u64>>,
+    in_flight: AtomicI64,
+    duration: Histogram,
+    peak_memory: Histogram,
+    /// Latest worker pool snapshot, taken by the request loop
+    workers: Mutex<Option<WorkerStats>>,
+    started_at: Instant,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            requests: Mutex::new(BTreeMap::new()),
+            in_flight: AtomicI64::new(0),
+            duration: Histogram::new(DURATION_BUCKETS),
+            peak_memory: Histogram::new(MEMORY_BUCKETS),
+            workers: Mutex::new(None),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Run a PHP request and record its status, duration and memory usage
+    pub fn track(
+        &self,
+        execute: impl FnOnce() -> Result<HttpResponse, PhpError>,
+    ) -> Result<HttpResponse, PhpError> {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        let started = Instant::now();
+        let result = execute();
+        self.record(&result, started.elapsed());
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+        result
+    }
+
+    fn record(&self, result: &Result<HttpResponse, PhpError>, duration: Duration) {
+        let status = match result {
+            Ok(response) => {
+                self.peak_memory.observe(response.peak_memory as f64);
+                response.status
+            }
+            Err(e) => error_status(e),
+        };
+
+        *self.requests.lock().unwrap_or_else(|e| e.into_inner()).entry(status).or_insert(0) += 1;
+        self.duration.observe(duration.as_secs_f64());
+    }
+
+    /// Store the current worker pool state for the next scrape
+    pub fn set_worker_stats(&self, stats: WorkerStats) {
+        *self.workers.lock().unwrap_or_else(|e| e.into_inner()) = Some(stats);
+    }
+
+    /// Render all metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        header(&mut out, "pox_uptime_seconds", "gauge", "Seconds since the server started");
+        let _ = writeln!(out, "pox_uptime_seconds {}", self.started_at.elapsed().as_secs_f64());
+
+        header(&mut out, "pox_http_requests_total", "counter", "PHP requests by response status code");
+        for (status, count) in self.requests.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            let _ = writeln!(out, "pox_http_requests_total{{status=\"{}\"}} {}", status, count);
+        }
+
+        header(&mut out, "pox_http_requests_in_flight", "gauge", "PHP requests currently being handled");
+        let _ = writeln!(out, "pox_http_requests_in_flight {}", self.in_flight.load(Ordering::Relaxed));
+
+        header(&mut out, "pox_http_request_duration_seconds", "histogram", "PHP request duration");
+        self.duration.render(&mut out, "pox_http_request_duration_seconds");
+
+        header(&mut out, "pox_php_peak_memory_bytes", "histogram", "Peak PHP memory usage per request");
+        self.peak_memory.render(&mut out, "pox_php_peak_memory_bytes");
+
+        if let Some(stats) = self.workers.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            render_worker_stats(&mut out, stats);
+        }
+
+        out
+    }
+}
+
+/// HTTP status sent to the client when PHP could not produce a response
+pub fn error_status(error: &PhpError) -> u16 {
+    match error {
+        PhpError::Timeout(_) => 504,
+        PhpError::WorkerExited => 502,
+        _ => 500,
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn render_worker_stats(out: &mut String, stats: &WorkerStats) {
+    header(out, "pox_workers", "gauge", "Worker threads in the pool");
+    let _ = writeln!(out, "pox_workers {}", stats.workers.len());
+
+    header(out, "pox_workers_alive", "gauge", "Workers with a running worker script");
+    let _ = writeln!(out, "pox_workers_alive {}", stats.alive());
+
+    header(out, "pox_workers_busy", "gauge", "Workers handling a request");
+    let _ = writeln!(out, "pox_workers_busy {}", stats.busy());
+
+    header(out, "pox_worker_requests", "gauge", "Requests handled by each worker since it was started");
+    for (i, worker) in stats.workers.iter().enumerate() {
+        let _ = writeln!(out, "pox_worker_requests{{worker=\"{}\"}} {}", i, worker.requests);
+    }
+
+    header(out, "pox_worker_uptime_seconds", "gauge", "Seconds since each worker was started");
+    for (i, worker) in stats.workers.iter().enumerate() {
+        let _ = writeln!(out, "pox_worker_uptime_seconds{{worker=\"{}\"}} {}", i, worker.uptime.as_secs_f64());
+    }
+
+    let counters = [
+        ("pox_worker_timeouts_total", "Requests that exceeded the request timeout", stats.timeouts),
+        ("pox_worker_crashes_total", "Worker scripts that exited unexpectedly", stats.crashes),
+        ("pox_worker_restarts_total", "Workers restarted after a crash or timeout", stats.restarts),
+        ("pox_worker_recycled_total", "Workers replaced after reaching the max requests limit", stats.recycled),
+    ];
+    for (name, help, value) in counters {
+        header(out, name, "counter", help);
+        let _ = writeln!(out, "{} {}", name, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pox_embed::WorkerInfo;
+
+    fn response(status: u16, peak_memory: usize) -> Result<HttpResponse, PhpError> {
+        Ok(HttpResponse {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            peak_memory,
+            declined: false,
+        })
+    }
+
+    #[test]
+    fn test_histogram_is_cumulative() {
+        let histogram = Histogram::new(&[1.0, 2.0]);
+        histogram.observe(0.5);
+        histogram.observe(1.5);
+        histogram.observe(5.0);
+
+        let mut out = String::new();
+        histogram.render(&mut out, "h");
+        assert_eq!(
+            out,
+            "h_bucket{le=\"1\"} 1\nh_bucket{le=\"2\"} 2\nh_bucket{le=\"+Inf\"} 3\nh_sum 7\nh_count 3\n"
+        );
+    }
+
+    #[test]
+    fn test_track_records_status_and_memory() {
+        let metrics = Metrics::new();
+        metrics.track(|| response(200, 2 * 1024 * 1024)).unwrap();
+        metrics.track(|| response(200, 1024)).unwrap();
+        let _ = metrics.track(|| Err(PhpError::Timeout(Duration::from_secs(1))));
+
+        let out = metrics.render();
+        assert!(out.contains("pox_http_requests_total{status=\"200\"} 2\n"));
+        assert!(out.contains("pox_http_requests_total{status=\"504\"} 1\n"));
+        assert!(out.contains("pox_http_requests_in_flight 0\n"));
+        assert!(out.contains("pox_http_request_duration_seconds_count 3\n"));
+        assert!(out.contains("pox_php_peak_memory_bytes_bucket{le=\"1048576\"} 1\n"));
+        assert!(out.contains("pox_php_peak_memory_bytes_count 2\n"));
+        assert!(!out.contains("pox_workers"));
+    }
+
+    #[test]
+    fn test_render_worker_stats() {
+        let metrics = Metrics::new();
+        metrics.set_worker_stats(WorkerStats {
+            workers: vec![
+                WorkerInfo { alive: true, busy: true, requests: 3, uptime: Duration::from_secs(2) },
+                WorkerInfo { alive: false, busy: false, requests: 0, uptime: Duration::ZERO },
+            ],
+            restarts: 4,
+            ..Default::default()
+        });
+
+        let out = metrics.render();
+        assert!(out.contains("pox_workers 2\n"));
+        assert!(out.contains("pox_workers_alive 1\n"));
+        assert!(out.contains("pox_workers_busy 1\n"));
+        assert!(out.contains("pox_worker_requests{worker=\"0\"} 3\n"));
+        assert!(out.contains("pox_worker_restarts_total 4\n"));
+        assert!(out.contains("# TYPE pox_worker_crashes_total counter\n"));
+    }
+}
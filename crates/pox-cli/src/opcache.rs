@@ -0,0 +1,155 @@
+//! Opcache helpers for server mode - preloading and the status endpoint.
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Path of the opt-in opcache status endpoint
+pub const STATUS_PATH: &str = "/__pox/opcache";
+
+/// INI entries enabling opcache with `preload` as preload script
+pub fn preload_ini_entries(preload: &Path) -> Result<Vec<String>> {
+    let preload = preload
+        .canonicalize()
+        .with_context(|| format!("Preload script not found: {}", preload.display()))?;
+
+    Ok(vec![
+        "opcache.enable=1".to_string(),
+        format!("opcache.preload={}", preload.display()),
+    ])
+}
+
+/// Build the status endpoint body from the raw `opcache_get_status()` JSON.
+///
+/// Returns the HTTP status code and the JSON document to send.
+pub fn status_body(raw: Option<&str>) -> (u16, Value) {
+    let Some(raw) = raw else {
+        return (503, json!({ "error": "opcache extension is not loaded" }));
+    };
+
+    match serde_json::from_str::<Value>(raw) {
+        Ok(status) => (200, summarize(&status)),
+        Err(e) => (500, json!({ "error": format!("invalid opcache status: {}", e) })),
+    }
+}
+
+/// Reduce the opcache status to the numbers useful for tuning
+fn summarize(status: &Value) -> Value {
+    // opcache_get_status() returns false when opcache is disabled
+    let Some(status) = status.as_object() else {
+        return json!({ "enabled": false });
+    };
+
+    let number = |section: &str, key: &str| -> u64 {
+        status
+            .get(section)
+            .and_then(|s| s.get(key))
+            .and_then(Value::as_u64)
+            .unwrap_or(0)
+    };
+
+    let hits = number("opcache_statistics", "hits");
+    let misses = number("opcache_statistics", "misses");
+    let hit_rate = if hits + misses > 0 {
+        (hits as f64 / (hits + misses) as f64 * 10000.0).round() / 100.0
+    } else {
+        0.0
+    };
+
+    let used = number("memory_usage", "used_memory");
+    let free = number("memory_usage", "free_memory");
+    let wasted = number("memory_usage", "wasted_memory");
+    let total = used + free + wasted;
+    let used_percent = if total > 0 {
+        (used as f64 / total as f64 * 10000.0).round() / 100.0
+    } else {
+        0.0
+    };
+
+    let mut summary = json!({
+        "enabled": status.get("opcache_enabled").and_then(Value::as_bool).unwrap_or(false),
+        "cache_full": status.get("cache_full").and_then(Value::as_bool).unwrap_or(false),
+        "restart_pending": status.get("restart_pending").and_then(Value::as_bool).unwrap_or(false),
+        "hits": hits,
+        "misses": misses,
+        "hit_rate": hit_rate,
+        "cached_scripts": number("opcache_statistics", "num_cached_scripts"),
+        "max_cached_keys": number("opcache_statistics", "max_cached_keys"),
+        "memory": {
+            "used": used,
+            "free": free,
+            "wasted": wasted,
+            "used_percent": used_percent,
+        },
+        "interned_strings": {
+            "used": number("interned_strings_usage", "used_memory"),
+            "free": number("interned_strings_usage", "free_memory"),
+            "strings": number("interned_strings_usage", "number_of_strings"),
+        },
+    });
+
+    if let Some(preload) = status.get("preload_statistics") {
+        summary["preload"] = json!({
+            "memory": preload.get("memory_consumption").cloned().unwrap_or(Value::Null),
+            "functions": preload.get("functions").and_then(Value::as_array).map(|a| a.len()).unwrap_or(0),
+            "classes": preload.get("classes").and_then(Value::as_array).map(|a| a.len()).unwrap_or(0),
+            "scripts": preload.get("scripts").and_then(Value::as_array).map(|a| a.len()).unwrap_or(0),
+        });
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_body_without_opcache() {
+        let (status, body) = status_body(None);
+        assert_eq!(status, 503);
+        assert!(body["error"].is_string());
+
+        let (status, body) = status_body(Some("false"));
+        assert_eq!(status, 200);
+        assert_eq!(body, json!({ "enabled": false }));
+    }
+
+    #[test]
+    fn test_status_body_summarizes_statistics() {
+        let raw = json!({
+            "opcache_enabled": true,
+            "cache_full": false,
+            "restart_pending": false,
+            "memory_usage": { "used_memory": 300, "free_memory": 600, "wasted_memory": 100 },
+            "interned_strings_usage": { "used_memory": 10, "free_memory": 20, "number_of_strings": 3 },
+            "opcache_statistics": { "hits": 3, "misses": 1, "num_cached_scripts": 2, "max_cached_keys": 16229 },
+            "preload_statistics": { "memory_consumption": 1024, "classes": ["App\\Kernel"], "scripts": ["/app/a.php", "/app/b.php"] }
+        })
+        .to_string();
+
+        let (status, body) = status_body(Some(&raw));
+        assert_eq!(status, 200);
+        assert_eq!(body["enabled"], json!(true));
+        assert_eq!(body["hit_rate"], json!(75.0));
+        assert_eq!(body["cached_scripts"], json!(2));
+        assert_eq!(body["memory"]["used_percent"], json!(30.0));
+        assert_eq!(body["interned_strings"]["strings"], json!(3));
+        assert_eq!(body["preload"]["classes"], json!(1));
+        assert_eq!(body["preload"]["scripts"], json!(2));
+        assert_eq!(body["preload"]["functions"], json!(0));
+    }
+
+    #[test]
+    fn test_preload_ini_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let preload = dir.path().join("preload.php");
+        std::fs::write(&preload, "<?php").unwrap();
+
+        let entries = preload_ini_entries(&preload).unwrap();
+        assert_eq!(entries[0], "opcache.enable=1");
+        assert!(entries[1].starts_with("opcache.preload=") && entries[1].ends_with("preload.php"));
+
+        assert!(preload_ini_entries(&dir.path().join("missing.php")).is_err());
+    }
+}
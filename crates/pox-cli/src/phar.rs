@@ -0,0 +1,99 @@
+//! Phar command - inspect and extract PHP archives.
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use console::style;
+use std::path::PathBuf;
+
+use pox_pm::phar::{PharArchive, PharError};
+
+#[derive(Subcommand, Debug)]
+pub enum PharCommands {
+    /// Extract the files of a phar archive
+    Extract(ExtractArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ExtractArgs {
+    /// Phar archive to extract
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Target directory (default: archive name without .phar)
+    #[arg(value_name = "DIR")]
+    pub dir: Option<PathBuf>,
+
+    /// Only list the archive contents
+    #[arg(short = 'l', long)]
+    pub list: bool,
+}
+
+/// Execute a phar command
+pub fn execute(command: PharCommands) -> Result<i32> {
+    match command {
+        PharCommands::Extract(args) => extract(args),
+    }
+}
+
+fn extract(args: ExtractArgs) -> Result<i32> {
+    let phar = match PharArchive::open(&args.file) {
+        Ok(phar) => phar,
+        Err(e) => {
+            eprintln!("{} {}: {}", style("Error:").red().bold(), args.file.display(), e);
+            return Ok(1);
+        }
+    };
+
+    let signature = match phar.verify() {
+        Ok(Some(sig_type)) => format!("{} (verified)", sig_type.as_str()),
+        Ok(None) => "none".to_string(),
+        Err(PharError::Unsupported(_)) => {
+            format!("{} (not verified)", phar.signature_type().map(|t| t.as_str()).unwrap_or("unknown"))
+        }
+        Err(e) => {
+            eprintln!("{} {}: {}", style("Error:").red().bold(), args.file.display(), e);
+            return Ok(1);
+        }
+    };
+
+    println!("{} {}", style("Archive:").cyan().bold(), args.file.display());
+    println!("  API version: {}", phar.api_version);
+    if !phar.alias.is_empty() {
+        println!("  Alias:       {}", phar.alias);
+    }
+    println!("  Signature:   {}", signature);
+    println!("  Files:       {}", phar.entries.iter().filter(|e| !e.is_dir()).count());
+
+    if args.list {
+        println!();
+        for entry in phar.entries.iter().filter(|e| !e.is_dir()) {
+            println!("  {:>10}  {}", entry.size, entry.name);
+        }
+        return Ok(0);
+    }
+
+    let dir = args.dir.unwrap_or_else(|| {
+        let stem = args.file.file_stem().map(PathBuf::from).unwrap_or_else(|| PathBuf::from("phar"));
+        args.file.with_file_name(stem)
+    });
+
+    if dir.exists() && std::fs::read_dir(&dir)?.next().is_some() {
+        eprintln!("{} Directory {} is not empty",
+            style("Error:").red().bold(),
+            dir.display()
+        );
+        return Ok(1);
+    }
+
+    let count = phar.extract_to(&dir)
+        .with_context(|| format!("Failed to extract {}", args.file.display()))?;
+
+    println!();
+    println!("{} Extracted {} files to {}",
+        style("Success:").green().bold(),
+        count,
+        dir.display()
+    );
+
+    Ok(0)
+}
@@ -0,0 +1,133 @@
+//! PHP errors in server mode.
+//!
+//! PHP notices, warnings and fatal errors are logged with their file and
+//! line instead of PHP printing them. A request that ends in a fatal error
+//! gets an error page listing everything PHP reported for it.
+
+use pox_embed::{HttpResponse, Php, PhpErrorEvent};
+use std::cell::RefCell;
+
+thread_local! {
+    /// Errors of the request running on this thread, `None` outside of [`collect`]
+    static REQUEST_ERRORS: RefCell<Option<Vec<PhpErrorEvent>>> = const { RefCell::new(None) };
+}
+
+/// Log PHP errors instead of printing them, must be called before PHP starts
+pub fn install() {
+    Php::set_error_handler(|event| {
+        if event.is_fatal() {
+            log::error!("{}", event);
+        } else {
+            log::warn!("{}", event);
+        }
+
+        REQUEST_ERRORS.with(|errors| {
+            if let Some(errors) = errors.borrow_mut().as_mut() {
+                errors.push(event.clone());
+            }
+        });
+    });
+}
+
+/// Run `execute`, returning the PHP errors it raised on this thread
+pub fn collect<T>(execute: impl FnOnce() -> T) -> (T, Vec<PhpErrorEvent>) {
+    REQUEST_ERRORS.with(|errors| *errors.borrow_mut() = Some(Vec::new()));
+    let result = execute();
+    let errors = REQUEST_ERRORS.with(|errors| errors.borrow_mut().take()).unwrap_or_default();
+    (result, errors)
+}
+
+/// Replace the response of a request that ended in a fatal error with an error page
+pub fn render_fatal(mut response: HttpResponse, errors: &[PhpErrorEvent]) -> HttpResponse {
+    if !errors.iter().any(PhpErrorEvent::is_fatal) {
+        return response;
+    }
+
+    response.status = 500;
+    response.headers.retain(|(key, _)| {
+        !key.eq_ignore_ascii_case("content-type") && !key.eq_ignore_ascii_case("content-length")
+    });
+    response.headers.push(("Content-Type".to_string(), "text/html; charset=UTF-8".to_string()));
+    response.body = error_page(errors).into_bytes();
+    response
+}
+
+fn error_page(errors: &[PhpErrorEvent]) -> String {
+    let items: String = errors
+        .iter()
+        .map(|error| {
+            format!(
+                "<li class=\"{}\"><strong>{}</strong>: {}<br><code>{}:{}</code></li>",
+                if error.is_fatal() { "fatal" } else { "error" },
+                error.severity(),
+                escape(&error.message),
+                escape(&error.file),
+                error.line
+            )
+        })
+        .collect();
+
+    format!(
+        "<!DOCTYPE html><html><head><title>500 Internal Server Error</title><style>\
+         body{{font-family:sans-serif;margin:2em}}li{{margin-bottom:1em;white-space:pre-wrap}}\
+         li.fatal strong{{color:#c00}}code{{color:#666}}</style></head>\
+         <body><h1>PHP Fatal Error</h1><ol>{}</ol></body></html>",
+        items
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(level: i32, message: &str) -> PhpErrorEvent {
+        PhpErrorEvent {
+            level,
+            message: message.to_string(),
+            file: "/app/index.php".to_string(),
+            line: 3,
+        }
+    }
+
+    fn response() -> HttpResponse {
+        HttpResponse {
+            status: 200,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: b"partial".to_vec(),
+            peak_memory: 0,
+            declined: false,
+        }
+    }
+
+    #[test]
+    fn test_fatal_error_renders_page() {
+        let errors = vec![
+            event(PhpErrorEvent::E_WARNING, "Undefined variable $a"),
+            event(PhpErrorEvent::E_ERROR, "Uncaught Exception: <b>boom</b>"),
+        ];
+
+        let response = render_fatal(response(), &errors);
+        let body = String::from_utf8(response.body).unwrap();
+        assert_eq!(response.status, 500);
+        assert_eq!(response.headers, vec![("Content-Type".to_string(), "text/html; charset=UTF-8".to_string())]);
+        assert!(body.contains("<strong>Warning</strong>: Undefined variable $a"));
+        assert!(body.contains("Uncaught Exception: &lt;b&gt;boom&lt;/b&gt;"));
+        assert!(body.contains("<code>/app/index.php:3</code>"));
+    }
+
+    #[test]
+    fn test_non_fatal_errors_keep_response() {
+        let errors = vec![event(PhpErrorEvent::E_DEPRECATED, "Deprecated call")];
+
+        let response = render_fatal(response(), &errors);
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, b"partial");
+    }
+}
@@ -0,0 +1,162 @@
+//! Archive command - create a distributable archive of the project or an installed package.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use console::style;
+use std::path::PathBuf;
+
+use pox_pm::{
+    config::Config,
+    json::{ComposerJson, ComposerLock},
+    package::{
+        archiver::{archive_file_name, create_archive, ArchiveFormat},
+        detect_root_version, ArchiveConfig, Package,
+    },
+    scripts::run_event_script,
+    ComposerBuilder,
+};
+use pox_pm::config::{composer_file_name, lock_file_name};
+
+#[derive(Args, Debug)]
+pub struct ArchiveArgs {
+    /// Installed package to archive, defaults to the current project
+    pub package: Option<String>,
+
+    /// Format of the archive: zip or tar (defaults to the archive-format config)
+    #[arg(short = 'f', long)]
+    pub format: Option<String>,
+
+    /// Directory to write the archive to (defaults to the archive-dir config)
+    #[arg(long)]
+    pub dir: Option<PathBuf>,
+
+    /// File name of the archive without extension
+    #[arg(long)]
+    pub file: Option<String>,
+
+    /// Working directory
+    #[arg(short = 'd', long, default_value = ".")]
+    pub working_dir: PathBuf,
+}
+
+pub async fn execute(args: ArchiveArgs) -> Result<i32> {
+    let working_dir = args
+        .working_dir
+        .canonicalize()
+        .context("Failed to resolve working directory")?;
+
+    let json_path = working_dir.join(composer_file_name());
+    let composer_json: ComposerJson = if json_path.exists() {
+        let content = std::fs::read_to_string(&json_path)?;
+        serde_json::from_str(&content)?
+    } else {
+        eprintln!("Error: composer.json not found in working directory");
+        return Ok(1);
+    };
+
+    let config = Config::build(Some(&working_dir), true)?;
+    let format_name = args.format.as_deref().unwrap_or(&config.archive_format);
+    let Some(format) = ArchiveFormat::from_name(format_name) else {
+        eprintln!("Error: Unsupported archive format '{}', use zip or tar", format_name);
+        return Ok(1);
+    };
+
+    let (package, source) = match &args.package {
+        Some(name) => {
+            let lock_path = working_dir.join(lock_file_name());
+            let lock: Option<ComposerLock> = std::fs::read_to_string(&lock_path)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok());
+            let locked = lock.as_ref().and_then(|lock| {
+                lock.packages
+                    .iter()
+                    .chain(lock.packages_dev.iter())
+                    .find(|p| p.name.eq_ignore_ascii_case(name))
+            });
+
+            let package = match locked {
+                Some(locked) => Package::from(locked),
+                None => {
+                    eprintln!("Error: Package {} is not installed", name);
+                    return Ok(1);
+                }
+            };
+
+            // The installation manager knows about installer-paths
+            let composer = ComposerBuilder::new(working_dir.clone())
+                .with_io(crate::io::io())
+                .with_config(config.clone())
+                .with_composer_json(composer_json.clone())
+                .with_composer_lock(lock.clone())
+                .build()?;
+            let Some(source) = composer
+                .installation_manager
+                .get_install_path(&package)
+                .filter(|path| path.is_dir())
+            else {
+                eprintln!("Error: Package {} is not installed, run `pox install` first", package.name);
+                return Ok(1);
+            };
+            (package, source)
+        }
+        None => {
+            let name = composer_json.name.clone().unwrap_or_else(|| {
+                working_dir
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "archive".to_string())
+            });
+            let root_version = detect_root_version(
+                &working_dir,
+                composer_json.version.as_deref(),
+                &composer_json.get_branch_aliases(),
+            );
+
+            let mut package = Package::new(&name, &root_version.version);
+            package.pretty_version = Some(root_version.pretty_version);
+            package.archive = Some(ArchiveConfig {
+                name: composer_json.archive.name.clone(),
+                exclude: composer_json.archive.exclude.clone(),
+            });
+            (package, working_dir.clone())
+        }
+    };
+
+    let quiet = crate::io::io().is_quiet();
+    if args.package.is_none() {
+        let code = run_event_script("pre-archive-cmd", &composer_json, &working_dir, quiet)?;
+        if code != 0 {
+            return Ok(code);
+        }
+    }
+
+    let dir = working_dir.join(args.dir.unwrap_or_else(|| config.archive_dir.clone()));
+    let file_name = args.file.unwrap_or_else(|| archive_file_name(&package));
+    let target = dir.join(format!("{}.{}", file_name, format.extension()));
+
+    let excludes = package.archive.as_ref().map(|a| a.exclude.clone()).unwrap_or_default();
+
+    if !quiet {
+        println!(
+            "Creating the archive into \"{}\".",
+            style(dir.display()).cyan()
+        );
+    }
+
+    let count = create_archive(&source, &target, format, &excludes)
+        .with_context(|| format!("Failed to create archive {}", target.display()))?;
+
+    if !quiet {
+        println!(
+            "Created: {} ({} files)",
+            style(target.display()).green(),
+            count
+        );
+    }
+
+    if args.package.is_none() {
+        return run_event_script("post-archive-cmd", &composer_json, &working_dir, quiet);
+    }
+
+    Ok(0)
+}
@@ -3,13 +3,13 @@ use clap::Args;
 use colored::Colorize;
 use pox_pm::json::{ComposerLock, LockedPackage};
 use pox_pm::cache::Cache;
-use pox_pm::config::Config;
+use pox_pm::config::{lock_file_name, Config};
 use pox_semver::VersionParser;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 #[derive(Args, Debug)]
@@ -26,7 +26,7 @@ pub struct AuditArgs {
     #[arg(long)]
     pub locked: bool,
 
-    /// Behavior on abandoned packages (ignore, report, or fail)
+    /// Behavior on abandoned packages (ignore, report, or fail), defaults to the `audit.abandoned` config
     #[arg(long, value_parser = ["ignore", "report", "fail"])]
     pub abandoned: Option<String>,
 
@@ -42,7 +42,7 @@ struct SecurityAdvisoriesResponse {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
-struct SecurityAdvisory {
+pub struct SecurityAdvisory {
     #[serde(rename = "advisoryId")]
     advisory_id: String,
     #[serde(rename = "packageName")]
@@ -70,13 +70,41 @@ struct AdvisorySource {
     _remote_id: String,
 }
 
+/// Exit code of install and update when the audit fails, as in Composer
+pub const ERROR_AUDIT_FAILED: i32 = 5;
+
 pub async fn execute(args: AuditArgs) -> Result<i32> {
     let working_dir = args
         .working_dir
         .canonicalize()
         .context("Failed to resolve working directory")?;
 
-    let lock_path = working_dir.join("composer.lock");
+    let config = Config::build(Some(&working_dir), true)?;
+    let abandoned = args.abandoned.clone().unwrap_or_else(|| config.audit.abandoned.clone());
+
+    run_audit(&working_dir, &config, args.no_dev, &args.format, &abandoned).await
+}
+
+/// Audit run after install and update, following the `audit` config.
+///
+/// Vulnerabilities are only reported, abandoned packages fail with
+/// [`ERROR_AUDIT_FAILED`] when `audit.abandoned` is `fail`.
+pub async fn audit_after_install(working_dir: &Path, no_dev: bool, format: &str) -> Result<i32> {
+    let config = Config::build(Some(working_dir), true)?;
+    let exit_code = run_audit(working_dir, &config, no_dev, format, &config.audit.abandoned).await?;
+
+    Ok(after_install_exit_code(exit_code))
+}
+
+/// Exit code of install and update for the exit code of their audit run
+fn after_install_exit_code(audit_exit_code: i32) -> i32 {
+    if audit_exit_code & 2 != 0 { ERROR_AUDIT_FAILED } else { 0 }
+}
+
+/// Audit the locked packages, returns 1 for vulnerabilities and 2 for
+/// abandoned packages when `abandoned_behavior` is `fail`, or both
+async fn run_audit(working_dir: &Path, config: &Config, no_dev: bool, format: &str, abandoned_behavior: &str) -> Result<i32> {
+    let lock_path = working_dir.join(lock_file_name());
     let lock: ComposerLock = if lock_path.exists() {
         let content = std::fs::read_to_string(&lock_path)?;
         serde_json::from_str(&content)
@@ -85,7 +113,7 @@ pub async fn execute(args: AuditArgs) -> Result<i32> {
         return Err(anyhow::anyhow!("No composer.lock found. Run 'install' or 'update' first."));
     };
 
-    let packages_with_versions: HashMap<String, String> = if args.no_dev {
+    let packages_with_versions: HashMap<String, String> = if no_dev {
         lock.packages
             .iter()
             .map(|p| (p.name.clone(), p.version.clone()))
@@ -98,17 +126,77 @@ pub async fn execute(args: AuditArgs) -> Result<i32> {
             .collect()
     };
 
-    let packages: Vec<String> = packages_with_versions.keys().cloned().collect();
-
-    if packages.is_empty() {
+    if packages_with_versions.is_empty() {
         println!("{}", "No packages - skipping audit.".yellow());
         return Ok(0);
     }
 
+    let filtered_advisories = find_advisories(config, &packages_with_versions).await?;
+
+    let advisories_response = SecurityAdvisoriesResponse {
+        advisories: filtered_advisories,
+    };
+
+    let abandoned_packages: Vec<_> = if abandoned_behavior != "ignore" {
+        lock.packages
+            .iter()
+            .chain(if no_dev {
+                [].iter()
+            } else {
+                lock.packages_dev.iter()
+            })
+            .filter(|p| p.is_abandoned())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let has_vulnerabilities = !advisories_response.advisories.is_empty();
+    let has_abandoned = !abandoned_packages.is_empty();
+
+    match format {
+        "json" => {
+            output_json(&advisories_response, &abandoned_packages)?;
+        }
+        "plain" => {
+            output_plain(&advisories_response, &abandoned_packages)?;
+        }
+        "summary" => {
+            output_summary(&advisories_response)?;
+        }
+        _ => {
+            // table format (default)
+            output_table(&advisories_response, &abandoned_packages)?;
+        }
+    }
+
+    Ok(audit_exit_code(has_vulnerabilities, has_abandoned, abandoned_behavior))
+}
+
+/// 1 for vulnerabilities, 2 for abandoned packages when `abandoned_behavior` is `fail`
+fn audit_exit_code(has_vulnerabilities: bool, has_abandoned: bool, abandoned_behavior: &str) -> i32 {
+    let mut exit_code = 0;
+    if has_vulnerabilities {
+        exit_code |= 1;
+    }
+    if has_abandoned && abandoned_behavior == "fail" {
+        exit_code |= 2;
+    }
+    exit_code
+}
+
+/// Advisories affecting the given package versions, without the ones silenced
+/// by the `audit` config. The API response is cached for ten minutes
+pub async fn find_advisories(
+    config: &Config,
+    packages_with_versions: &HashMap<String, String>,
+) -> Result<HashMap<String, Vec<SecurityAdvisory>>> {
+    let packages: Vec<String> = packages_with_versions.keys().cloned().collect();
+
     let version_parser = VersionParser::new();
 
-    let config = Config::build(Some(&working_dir), true)?;
     let cache_dir = config.cache_dir
+        .as_ref()
         .context("Cache directory not configured")?
         .join("audit");
     let cache = Cache::new(cache_dir);
@@ -130,16 +218,16 @@ pub async fn execute(args: AuditArgs) -> Result<i32> {
                     if let Ok(cached) = serde_json::from_slice::<SecurityAdvisoriesResponse>(&data) {
                         cached.advisories
                     } else {
-                        fetch_and_cache_advisories(&cache, &cache_key, &packages).await?
+                        fetch_and_cache_advisories(config, &cache, &cache_key, &packages).await?
                     }
                 } else {
-                    fetch_and_cache_advisories(&cache, &cache_key, &packages).await?
+                    fetch_and_cache_advisories(config, &cache, &cache_key, &packages).await?
                 }
             } else {
-                fetch_and_cache_advisories(&cache, &cache_key, &packages).await?
+                fetch_and_cache_advisories(config, &cache, &cache_key, &packages).await?
             }
         } else {
-            fetch_and_cache_advisories(&cache, &cache_key, &packages).await?
+            fetch_and_cache_advisories(config, &cache, &cache_key, &packages).await?
         };
 
     let mut filtered_advisories: HashMap<String, Vec<SecurityAdvisory>> = HashMap::new();
@@ -161,6 +249,9 @@ pub async fn execute(args: AuditArgs) -> Result<i32> {
 
         let matching_advisories: Vec<SecurityAdvisory> = advisories
             .into_iter()
+            .filter(|advisory| {
+                !config.audit.is_ignored(&advisory.advisory_id, advisory.cve.as_deref(), advisory.severity.as_deref())
+            })
             .filter(|advisory| {
                 match version_parser.parse_constraints_cached(&advisory.affected_versions) {
                     Ok(constraint) => constraint.matches_normalized(&normalized_version),
@@ -174,53 +265,7 @@ pub async fn execute(args: AuditArgs) -> Result<i32> {
         }
     }
 
-    let advisories_response = SecurityAdvisoriesResponse {
-        advisories: filtered_advisories,
-    };
-
-    let abandoned_behavior = args.abandoned.as_deref().unwrap_or("fail");
-    let abandoned_packages: Vec<_> = if abandoned_behavior != "ignore" {
-        lock.packages
-            .iter()
-            .chain(if args.no_dev {
-                [].iter()
-            } else {
-                lock.packages_dev.iter()
-            })
-            .filter(|p| p.is_abandoned())
-            .collect()
-    } else {
-        Vec::new()
-    };
-
-    let has_vulnerabilities = !advisories_response.advisories.is_empty();
-    let has_abandoned = !abandoned_packages.is_empty();
-
-    match args.format.as_str() {
-        "json" => {
-            output_json(&advisories_response, &abandoned_packages)?;
-        }
-        "plain" => {
-            output_plain(&advisories_response, &abandoned_packages)?;
-        }
-        "summary" => {
-            output_summary(&advisories_response)?;
-        }
-        _ => {
-            // table format (default)
-            output_table(&advisories_response, &abandoned_packages)?;
-        }
-    }
-
-    let mut exit_code = 0;
-    if has_vulnerabilities {
-        exit_code |= 1;
-    }
-    if has_abandoned && abandoned_behavior == "fail" {
-        exit_code |= 2;
-    }
-
-    Ok(exit_code)
+    Ok(filtered_advisories)
 }
 
 fn output_json(
@@ -248,6 +293,7 @@ fn output_json(
 }
 
 async fn fetch_and_cache_advisories(
+    config: &Config,
     cache: &Cache,
     cache_key: &str,
     packages: &[String],
@@ -260,7 +306,9 @@ async fn fetch_and_cache_advisories(
         .collect::<Vec<_>>()
         .join("&");
 
-    let client = reqwest::Client::new();
+    let client = pox_pm::http::client_builder(&config.proxy)
+        .build()
+        .context("Failed to create HTTP client")?;
     let response = client
         .post(api_url)
         .header("Content-Type", "application/x-www-form-urlencoded")
@@ -454,3 +502,24 @@ fn colorize_severity(severity: Option<&str>) -> colored::ColoredString {
         _ => "unknown".normal(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        // Abandoned packages are only reported unless the config says otherwise
+        let default = Config::default().audit.abandoned;
+        assert_eq!(audit_exit_code(false, true, &default), 0);
+        assert_eq!(after_install_exit_code(audit_exit_code(false, true, &default)), 0);
+
+        assert_eq!(audit_exit_code(false, true, "fail"), 2);
+        assert_eq!(audit_exit_code(true, true, "fail"), 3);
+        assert_eq!(audit_exit_code(true, true, "ignore"), 1);
+        assert_eq!(after_install_exit_code(audit_exit_code(false, true, "fail")), ERROR_AUDIT_FAILED);
+
+        // Vulnerabilities alone don't fail an install
+        assert_eq!(after_install_exit_code(audit_exit_code(true, false, "fail")), 0);
+    }
+}
@@ -0,0 +1,156 @@
+//! Bench command - run the standardized package manager benchmark.
+
+use anyhow::Result;
+use clap::Args;
+use console::style;
+use std::time::Duration;
+
+use pox_pm::bench::{BenchResult, Workload};
+
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// Number of timed iterations per workload
+    #[arg(short = 'i', long, default_value_t = 5)]
+    pub iterations: usize,
+
+    /// Only run the given workloads: parse-constraints, solve or autoload
+    #[arg(long, value_delimiter = ',')]
+    pub only: Vec<String>,
+
+    /// Output format: text or json
+    #[arg(short = 'f', long, default_value = "text")]
+    pub format: String,
+}
+
+pub async fn execute(args: BenchArgs) -> Result<i32> {
+    if args.format != "text" && args.format != "json" {
+        eprintln!(
+            "Error: Unsupported format '{}'. See help for supported formats.",
+            args.format
+        );
+        return Ok(1);
+    }
+
+    let mut workloads = Vec::new();
+    for name in &args.only {
+        let Some(workload) = Workload::from_name(name) else {
+            eprintln!(
+                "Error: Unknown workload '{}'. Use 'parse-constraints', 'solve' or 'autoload'.",
+                name
+            );
+            return Ok(1);
+        };
+        workloads.push(workload);
+    }
+    if workloads.is_empty() {
+        workloads = Workload::ALL.to_vec();
+    }
+
+    let iterations = args.iterations.max(1);
+    let text = args.format == "text";
+
+    if text {
+        println!(
+            "{} Running {} iterations per workload, nothing is sent anywhere",
+            style("Benchmark").green().bold(),
+            iterations
+        );
+    }
+
+    let mut results = Vec::new();
+    for workload in workloads {
+        if text {
+            println!("  {} {}...", style(workload.name()).cyan(), workload.description());
+        }
+        results.push(workload.run(iterations)?);
+    }
+
+    if text {
+        print_text(&results);
+    } else {
+        println!("{}", serde_json::to_string_pretty(&report_json(&results, iterations))?);
+    }
+
+    Ok(0)
+}
+
+fn print_text(results: &[BenchResult]) {
+    let width = results.iter().map(|r| r.workload.name().len()).max().unwrap_or(0);
+
+    println!();
+    println!(
+        "{:width$}  {:>10}  {:>10}  {:>10}",
+        "workload", "median", "min", "mean",
+        width = width
+    );
+    for result in results {
+        println!(
+            "{:width$}  {:>10}  {:>10}  {:>10}",
+            result.workload.name(),
+            format_duration(result.median()),
+            format_duration(result.min()),
+            format_duration(result.mean()),
+            width = width
+        );
+    }
+    println!();
+    println!(
+        "pox {} on {} {} with {} CPUs",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        available_cpus()
+    );
+}
+
+/// Structured report for comparing machines or attaching to regression reports
+fn report_json(results: &[BenchResult], iterations: usize) -> serde_json::Value {
+    serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "cpus": available_cpus(),
+        "iterations": iterations,
+        "workloads": results.iter().map(|result| serde_json::json!({
+            "name": result.workload.name(),
+            "description": result.workload.description(),
+            "median_ms": millis(result.median()),
+            "min_ms": millis(result.min()),
+            "mean_ms": millis(result.mean()),
+            "samples_ms": result.samples.iter().map(|d| millis(*d)).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+    })
+}
+
+fn available_cpus() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+fn millis(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 1_000_000.0).round() / 1_000.0
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2} ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_json() {
+        let results = vec![BenchResult {
+            workload: Workload::Solve,
+            samples: vec![Duration::from_micros(1500), Duration::from_micros(2500), Duration::from_micros(2000)],
+        }];
+
+        let report = report_json(&results, 3);
+        assert_eq!(report["iterations"], 3);
+        let workload = &report["workloads"][0];
+        assert_eq!(workload["name"], "solve");
+        assert_eq!(workload["median_ms"], 2.0);
+        assert_eq!(workload["min_ms"], 1.5);
+        assert_eq!(workload["samples_ms"].as_array().unwrap().len(), 3);
+    }
+}
@@ -9,6 +9,7 @@ use clap::Args;
 use console::style;
 use std::path::PathBuf;
 use std::process::Command;
+use pox_pm::config::composer_file_name;
 
 /// Configuration for the bin plugin from composer.json extra.bamarni-bin
 #[derive(Debug, Clone)]
@@ -75,7 +76,7 @@ pub async fn execute(args: BinArgs) -> Result<i32> {
         .context("Failed to resolve working directory")?;
 
     // Load composer.json to get config
-    let composer_json_path = working_dir.join("composer.json");
+    let composer_json_path = working_dir.join(composer_file_name());
     let config = if composer_json_path.exists() {
         let content = std::fs::read_to_string(&composer_json_path)?;
         let json: serde_json::Value = serde_json::from_str(&content)?;
@@ -7,6 +7,7 @@ use std::path::PathBuf;
 use pox_pm::json::{ComposerJson, ComposerLock};
 use pox_pm::package::version_bumper::bump_requirement;
 use pox_pm::{compute_content_hash, is_platform_package};
+use pox_pm::config::{composer_file_name, lock_file_name};
 
 #[derive(Args, Debug)]
 pub struct BumpArgs {
@@ -15,7 +16,7 @@ pub struct BumpArgs {
     pub packages: Vec<String>,
 
     /// Only bump requirements in "require-dev"
-    #[arg(short = 'D', long)]
+    #[arg(short = 'D', long, conflicts_with = "no_dev_only")]
     pub dev_only: bool,
 
     /// Only bump requirements in "require"
@@ -31,9 +32,26 @@ pub struct BumpArgs {
     pub working_dir: PathBuf,
 }
 
+/// A single requirement change
+#[derive(Debug, Clone, PartialEq)]
+pub struct BumpChange {
+    pub from: String,
+    pub to: String,
+}
+
 pub struct BumpUpdates {
-    pub require: IndexMap<String, String>,
-    pub require_dev: IndexMap<String, String>,
+    pub require: IndexMap<String, BumpChange>,
+    pub require_dev: IndexMap<String, BumpChange>,
+}
+
+impl BumpUpdates {
+    pub fn len(&self) -> usize {
+        self.require.len() + self.require_dev.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 pub fn calculate_updates(
@@ -77,7 +95,7 @@ pub fn calculate_updates(
             if let Some(pkg) = lock.find_package(name) {
                 let bumped = bump_requirement(constraint, &pkg.version);
                 if bumped != *constraint {
-                    updates.require.insert(name.clone(), bumped);
+                    updates.require.insert(name.clone(), BumpChange { from: constraint.clone(), to: bumped });
                 }
             }
         }
@@ -95,7 +113,7 @@ pub fn calculate_updates(
             if let Some(pkg) = lock.find_package(name) {
                 let bumped = bump_requirement(constraint, &pkg.version);
                 if bumped != *constraint {
-                    updates.require_dev.insert(name.clone(), bumped);
+                    updates.require_dev.insert(name.clone(), BumpChange { from: constraint.clone(), to: bumped });
                 }
             }
         }
@@ -107,12 +125,12 @@ pub fn calculate_updates(
 pub fn apply_updates_to_json(content: &str, updates: &BumpUpdates) -> Result<String> {
     let mut result = content.to_string();
 
-    for (name, new_version) in &updates.require {
-        result = update_dependency_in_json(&result, "require", name, new_version)?;
+    for (name, change) in &updates.require {
+        result = update_dependency_in_json(&result, "require", name, &change.to)?;
     }
 
-    for (name, new_version) in &updates.require_dev {
-        result = update_dependency_in_json(&result, "require-dev", name, new_version)?;
+    for (name, change) in &updates.require_dev {
+        result = update_dependency_in_json(&result, "require-dev", name, &change.to)?;
     }
 
     Ok(result)
@@ -138,7 +156,7 @@ fn update_dependency_in_json(
         let mut brace_count = 0;
         let mut section_end = remaining.len();
 
-        for (i, ch) in remaining.chars().enumerate() {
+        for (i, ch) in remaining.char_indices() {
             match ch {
                 '{' => brace_count += 1,
                 '}' => {
@@ -156,7 +174,7 @@ fn update_dependency_in_json(
 
         if let Some(caps) = re.captures(section_content) {
             let full_match = caps.get(0).unwrap();
-            let replacement = format!(r#"{}": "{}""#, &caps[1], new_version);
+            let replacement = format!(r#"{}: "{}""#, &caps[1], new_version);
 
             let new_section = format!(
                 "{}{}{}",
@@ -176,7 +194,7 @@ fn update_dependency_in_json(
 
     if let Some(caps) = re.captures(content) {
         let full_match = caps.get(0).unwrap();
-        let replacement = format!(r#"{}": "{}""#, &caps[1], new_version);
+        let replacement = format!(r#"{}: "{}""#, &caps[1], new_version);
 
         return Ok(format!(
             "{}{}{}",
@@ -195,8 +213,8 @@ pub async fn execute(args: BumpArgs) -> Result<i32> {
         .canonicalize()
         .context("Failed to resolve working directory")?;
 
-    let json_path = working_dir.join("composer.json");
-    let lock_path = working_dir.join("composer.lock");
+    let json_path = working_dir.join(composer_file_name());
+    let lock_path = working_dir.join(lock_file_name());
 
     if !json_path.exists() {
         eprintln!("./composer.json is not readable.");
@@ -222,7 +240,15 @@ pub async fn execute(args: BumpArgs) -> Result<i32> {
     let lock: ComposerLock = if lock_path.exists() {
         let lock_content =
             std::fs::read_to_string(&lock_path).context("Failed to read composer.lock")?;
-        serde_json::from_str(&lock_content).context("Failed to parse composer.lock")?
+        let lock: ComposerLock =
+            serde_json::from_str(&lock_content).context("Failed to parse composer.lock")?;
+
+        if !lock.content_hash.is_empty() && lock.content_hash != compute_content_hash(&json_content) {
+            eprintln!("The lock file is not up to date with the latest changes in composer.json. Run the appropriate `update` to fix that before you use the `bump` command.");
+            return Ok(1);
+        }
+
+        lock
     } else {
         let installed_path = working_dir.join("vendor/composer/installed.json");
         if installed_path.exists() {
@@ -244,16 +270,13 @@ pub async fn execute(args: BumpArgs) -> Result<i32> {
         args.no_dev_only,
     );
 
-    let change_count = updates.require.len() + updates.require_dev.len();
+    let change_count = updates.len();
 
-    if change_count > 0 {
+    if !updates.is_empty() {
         if args.dry_run {
             println!("./composer.json would be updated with:");
-            for (name, version) in &updates.require {
-                println!("  - require.{}: {}", name, version);
-            }
-            for (name, version) in &updates.require_dev {
-                println!("  - require-dev.{}: {}", name, version);
+            for line in format_changes(&updates) {
+                println!("{}", line);
             }
             return Ok(1);
         }
@@ -283,6 +306,20 @@ pub async fn execute(args: BumpArgs) -> Result<i32> {
     Ok(0)
 }
 
+/// Describe each change as `section.name: from => to`
+fn format_changes(updates: &BumpUpdates) -> Vec<String> {
+    let sections = [("require", &updates.require), ("require-dev", &updates.require_dev)];
+
+    sections
+        .iter()
+        .flat_map(|(section, changes)| {
+            changes
+                .iter()
+                .map(move |(name, change)| format!("  - {}.{}: {} => {}", section, name, change.from, change.to))
+        })
+        .collect()
+}
+
 fn parse_installed_json(content: &str) -> Result<ComposerLock> {
     use pox_pm::json::LockedPackage;
 
@@ -335,3 +372,104 @@ fn update_lock_hash(lock_path: &std::path::Path, json_content: &str) -> Result<(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pox_pm::json::LockedPackage;
+
+    fn locked(name: &str, version: &str) -> LockedPackage {
+        LockedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn fixture() -> (ComposerJson, ComposerLock) {
+        let mut composer_json = ComposerJson::default();
+        composer_json.require.insert("php".to_string(), ">=8.1".to_string());
+        composer_json.require.insert("vendor/caret".to_string(), "^1.0".to_string());
+        composer_json.require.insert("vendor/tilde".to_string(), "~2.1.0".to_string());
+        composer_json.require.insert("vendor/range".to_string(), ">=3.0 <4.0".to_string());
+        composer_json.require_dev.insert("vendor/dev".to_string(), "^5.0".to_string());
+
+        let lock = ComposerLock {
+            packages: vec![
+                locked("vendor/caret", "1.4.2"),
+                locked("vendor/tilde", "2.1.7"),
+                locked("vendor/range", "3.2.0"),
+            ],
+            packages_dev: vec![locked("vendor/dev", "5.3.0")],
+            ..Default::default()
+        };
+
+        (composer_json, lock)
+    }
+
+    #[test]
+    fn test_calculate_updates_preserves_style() {
+        let (composer_json, lock) = fixture();
+        let updates = calculate_updates(&composer_json, &lock, &[], false, false);
+
+        assert_eq!(updates.require["vendor/caret"].to, "^1.4.2");
+        assert_eq!(updates.require["vendor/tilde"].to, "~2.1.7");
+        assert_eq!(updates.require["vendor/range"].to, ">=3.2 <4.0");
+        assert_eq!(updates.require_dev["vendor/dev"].to, "^5.3");
+        assert!(!updates.require.contains_key("php"));
+        assert_eq!(updates.len(), 4);
+    }
+
+    #[test]
+    fn test_calculate_updates_dev_filters() {
+        let (composer_json, lock) = fixture();
+
+        let dev_only = calculate_updates(&composer_json, &lock, &[], true, false);
+        assert!(dev_only.require.is_empty());
+        assert_eq!(dev_only.require_dev.len(), 1);
+
+        let no_dev_only = calculate_updates(&composer_json, &lock, &[], false, true);
+        assert_eq!(no_dev_only.require.len(), 3);
+        assert!(no_dev_only.require_dev.is_empty());
+    }
+
+    #[test]
+    fn test_calculate_updates_package_filter() {
+        let (composer_json, lock) = fixture();
+
+        let updates = calculate_updates(&composer_json, &lock, &["vendor/caret".to_string(), "vendor/d*".to_string()], false, false);
+        assert_eq!(updates.require.keys().collect::<Vec<_>>(), vec!["vendor/caret"]);
+        assert_eq!(updates.require_dev.keys().collect::<Vec<_>>(), vec!["vendor/dev"]);
+    }
+
+    #[test]
+    fn test_format_changes() {
+        let (composer_json, lock) = fixture();
+        let updates = calculate_updates(&composer_json, &lock, &["vendor/caret".to_string(), "vendor/dev".to_string()], false, false);
+
+        assert_eq!(
+            format_changes(&updates),
+            vec![
+                "  - require.vendor/caret: ^1.0 => ^1.4.2",
+                "  - require-dev.vendor/dev: ^5.0 => ^5.3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_updates_to_json_keeps_formatting() {
+        let content = "{\n    \"require\": {\n        \"vendor/caret\": \"^1.0\"\n    },\n    \"require-dev\": {\n        \"vendor/caret\": \"^1.0\"\n    }\n}\n";
+
+        let mut updates = BumpUpdates { require: IndexMap::new(), require_dev: IndexMap::new() };
+        updates.require_dev.insert(
+            "vendor/caret".to_string(),
+            BumpChange { from: "^1.0".to_string(), to: "^1.4.2".to_string() },
+        );
+
+        let result = apply_updates_to_json(content, &updates).unwrap();
+        assert_eq!(
+            result,
+            "{\n    \"require\": {\n        \"vendor/caret\": \"^1.0\"\n    },\n    \"require-dev\": {\n        \"vendor/caret\": \"^1.4.2\"\n    }\n}\n"
+        );
+    }
+}
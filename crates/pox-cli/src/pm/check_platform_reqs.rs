@@ -0,0 +1,291 @@
+//! Check that the platform satisfies the requirements of the locked packages.
+//!
+//! Like `composer check-platform-reqs` the platform is the embedded PHP runtime
+//! with the `config.platform` overrides applied, the same one the solver saw.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use console::style;
+use std::path::PathBuf;
+
+use pox_pm::config::{composer_file_name, lock_file_name, Config};
+use pox_pm::json::{ComposerJson, ComposerLock};
+use pox_pm::repository::apply_platform_overrides;
+use pox_pm::util::is_platform_package;
+use pox_pm::Package;
+use pox_semver::VersionParser;
+
+use super::platform::PlatformInfo;
+
+#[derive(Args, Debug)]
+pub struct CheckPlatformReqsArgs {
+    /// Disables checking of require-dev packages requirements
+    #[arg(long)]
+    pub no_dev: bool,
+
+    /// Output format: text or json
+    #[arg(short = 'f', long, default_value = "text")]
+    pub format: String,
+
+    /// Working directory
+    #[arg(short = 'd', long, default_value = ".")]
+    pub working_dir: PathBuf,
+}
+
+/// Outcome of checking one platform requirement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Success,
+    Failed,
+    Missing,
+}
+
+impl Status {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Status::Success => "success",
+            Status::Failed => "failed",
+            Status::Missing => "missing",
+        }
+    }
+}
+
+/// A platform requirement of a package
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Requirement {
+    name: String,
+    constraint: String,
+    source: String,
+}
+
+#[derive(Debug)]
+struct CheckResult {
+    name: String,
+    version: Option<String>,
+    /// The first requirement that is not met, or the last one checked
+    requirement: Option<Requirement>,
+    status: Status,
+}
+
+pub async fn execute(args: CheckPlatformReqsArgs) -> Result<i32> {
+    let working_dir = args.working_dir.canonicalize()
+        .context("Failed to resolve working directory")?;
+
+    if !matches!(args.format.as_str(), "text" | "json") {
+        eprintln!("Error: Unsupported format '{}'. See help for supported formats.", args.format);
+        return Ok(1);
+    }
+
+    let json_path = working_dir.join(composer_file_name());
+    let composer_json: ComposerJson = if json_path.exists() {
+        let content = std::fs::read_to_string(&json_path)?;
+        serde_json::from_str(&content)?
+    } else {
+        anyhow::bail!("No composer.json found in the current directory");
+    };
+
+    let lock_path = working_dir.join(lock_file_name());
+    let lock: ComposerLock = if lock_path.exists() {
+        let lock_content = std::fs::read_to_string(&lock_path)
+            .context("Failed to read composer.lock")?;
+        serde_json::from_str(&lock_content)
+            .context("Failed to parse composer.lock")?
+    } else {
+        anyhow::bail!("No composer.lock found. Run 'install' or 'update' first.");
+    };
+
+    let config = Config::build(Some(&working_dir), true)?;
+    let platform = apply_platform_overrides(&PlatformInfo::detect_for(&working_dir).to_packages(), &config.platform);
+
+    let requirements = collect_requirements(&composer_json, &lock, args.no_dev);
+    let results = check_requirements(&requirements, &platform);
+
+    if args.format == "json" {
+        let json: Vec<serde_json::Value> = results
+            .iter()
+            .map(|r| {
+                serde_json::json!({
+                    "name": r.name,
+                    "version": r.version,
+                    "status": r.status.as_str(),
+                    "failed_requirement": r.requirement.as_ref().filter(|_| r.status != Status::Success).map(|req| {
+                        serde_json::json!({
+                            "source": req.source,
+                            "type": "requires",
+                            "target": req.name,
+                            "constraint": req.constraint,
+                        })
+                    }),
+                    "provider": null,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        print_text(&results);
+    }
+
+    let exit_code = results
+        .iter()
+        .map(|r| match r.status {
+            Status::Success => 0,
+            Status::Failed => 1,
+            Status::Missing => 2,
+        })
+        .max()
+        .unwrap_or(0);
+
+    Ok(exit_code)
+}
+
+fn print_text(results: &[CheckResult]) {
+    if results.is_empty() {
+        eprintln!("{}", style("No platform requirements to check.").yellow());
+        return;
+    }
+
+    let name_width = results.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    let version_width = results
+        .iter()
+        .map(|r| r.version.as_deref().unwrap_or("n/a").len())
+        .max()
+        .unwrap_or(0);
+
+    for result in results {
+        let version = result.version.as_deref().unwrap_or("n/a");
+        let reason = match (&result.requirement, result.status) {
+            (Some(req), Status::Failed | Status::Missing) => {
+                format!("{} requires {} ({}) ", req.source, req.name, req.constraint)
+            }
+            _ => String::new(),
+        };
+        let status = match result.status {
+            Status::Success => style(result.status.as_str()).green(),
+            Status::Failed => style(result.status.as_str()).red(),
+            Status::Missing => style(result.status.as_str()).red(),
+        };
+
+        println!(
+            "{:name_width$} {:version_width$} {}{}",
+            result.name,
+            version,
+            reason,
+            status,
+        );
+    }
+}
+
+/// Platform requirements of the root package and the locked packages
+fn collect_requirements(composer_json: &ComposerJson, lock: &ComposerLock, no_dev: bool) -> Vec<Requirement> {
+    let root_name = composer_json.name.clone().unwrap_or_else(|| "__root__".to_string());
+    let mut sources: Vec<(&str, &indexmap::IndexMap<String, String>)> = vec![(&root_name, &composer_json.require)];
+    if !no_dev {
+        sources.push((&root_name, &composer_json.require_dev));
+    }
+
+    sources.extend(lock.packages.iter().map(|p| (p.name.as_str(), &p.require)));
+    if !no_dev {
+        sources.extend(lock.packages_dev.iter().map(|p| (p.name.as_str(), &p.require)));
+    }
+
+    sources
+        .into_iter()
+        .flat_map(|(source, requires)| {
+            requires
+                .iter()
+                .filter(|(name, _)| is_platform_package(name))
+                .map(move |(name, constraint)| Requirement {
+                    name: name.to_lowercase(),
+                    constraint: constraint.clone(),
+                    source: source.to_string(),
+                })
+        })
+        .collect()
+}
+
+/// Check each required platform package against `platform`, sorted by package name
+fn check_requirements(requirements: &[Requirement], platform: &[Package]) -> Vec<CheckResult> {
+    let parser = VersionParser::new();
+    let mut results: Vec<CheckResult> = Vec::new();
+
+    for requirement in requirements {
+        let index = match results.iter().position(|r| r.name == requirement.name) {
+            Some(index) => index,
+            None => {
+                let package = platform.iter().find(|p| p.name.eq_ignore_ascii_case(&requirement.name));
+                results.push(CheckResult {
+                    name: requirement.name.clone(),
+                    version: package.map(|p| p.pretty_version.clone().unwrap_or_else(|| p.version.clone())),
+                    requirement: None,
+                    status: if package.is_some() { Status::Success } else { Status::Missing },
+                });
+                results.len() - 1
+            }
+        };
+
+        let result = &mut results[index];
+        if result.status != Status::Success {
+            // Keep reporting the first requirement that is not met
+            if result.requirement.is_none() {
+                result.requirement = Some(requirement.clone());
+            }
+            continue;
+        }
+
+        let version = result.version.as_deref().unwrap_or_default();
+        let matches = match (parser.normalize(version), parser.parse_constraints_cached(&requirement.constraint)) {
+            (Ok(normalized), Ok(constraint)) => constraint.matches_normalized(&normalized),
+            _ => true,
+        };
+        result.requirement = Some(requirement.clone());
+        if !matches {
+            result.status = Status::Failed;
+        }
+    }
+
+    results.sort_by(|a, b| a.name.cmp(&b.name));
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requirement(name: &str, constraint: &str, source: &str) -> Requirement {
+        Requirement {
+            name: name.to_string(),
+            constraint: constraint.to_string(),
+            source: source.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_check_requirements() {
+        let mut php = Package::new("php", "8.1.0.0");
+        php.pretty_version = Some("8.1.0".to_string());
+        let platform = vec![php, Package::new("ext-json", "8.4.2")];
+
+        let requirements = vec![
+            requirement("php", ">=8.0", "__root__"),
+            requirement("ext-json", "*", "acme/lib"),
+            requirement("php", ">=8.2", "acme/lib"),
+            requirement("php", ">=7.4", "acme/util"),
+            requirement("ext-intl", "*", "acme/util"),
+        ];
+
+        let results = check_requirements(&requirements, &platform);
+        let summary: Vec<(&str, Option<&str>, Status)> = results
+            .iter()
+            .map(|r| (r.name.as_str(), r.version.as_deref(), r.status))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("ext-intl", None, Status::Missing),
+                ("ext-json", Some("8.4.2"), Status::Success),
+                ("php", Some("8.1.0"), Status::Failed),
+            ]
+        );
+        assert_eq!(results[2].requirement, Some(requirement("php", ">=8.2", "acme/lib")));
+    }
+}
@@ -3,10 +3,10 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use console::style;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use pox_pm::cache::Cache;
-use pox_pm::config::ConfigLoader;
+use pox_pm::config::{Config, ConfigLoader};
 
 #[derive(Args, Debug)]
 pub struct ClearCacheArgs {
@@ -26,9 +26,9 @@ pub struct ClearCacheArgs {
     #[arg(long)]
     pub gc: bool,
 
-    /// TTL in seconds for garbage collection (default: 6 months)
-    #[arg(long, default_value = "15552000")]
-    pub gc_ttl: u64,
+    /// TTL in seconds for garbage collection (default: cache-ttl / cache-files-ttl config)
+    #[arg(long)]
+    pub gc_ttl: Option<u64>,
 }
 
 pub async fn execute(args: ClearCacheArgs) -> Result<i32> {
@@ -53,15 +53,23 @@ pub async fn execute(args: ClearCacheArgs) -> Result<i32> {
 
     if args.gc {
         // Garbage collection mode
-        let ttl = std::time::Duration::from_secs(args.gc_ttl);
+        let config = Config::build(std::env::current_dir().ok(), true)?;
+        let ttl_secs = args.gc_ttl.unwrap_or(config.cache_ttl);
+        let ttl = std::time::Duration::from_secs(ttl_secs);
+        let files_ttl = std::time::Duration::from_secs(
+            args.gc_ttl.unwrap_or_else(|| config.get_cache_files_ttl()),
+        );
 
-        println!("{} Running garbage collection (TTL: {} days)...",
+        println!("{} Running garbage collection (TTL: {} days, max files cache size: {})...",
             style("Info:").cyan(),
-            args.gc_ttl / 86400
+            ttl_secs / 86400,
+            format_bytes(config.cache_files_maxsize)
         );
 
         if clear_files {
-            let freed = gc_cache_dir(&cache_dir.join("files"), ttl, "files")?;
+            let freed = gc_cache_dir(&cache_dir.join("files"), files_ttl, "files")?;
+            total_freed += freed;
+            let freed = gc_cache_size(&cache_dir.join("files"), config.cache_files_maxsize)?;
             total_freed += freed;
         }
 
@@ -145,6 +153,22 @@ fn gc_cache_dir(path: &PathBuf, ttl: std::time::Duration, name: &str) -> Result<
     Ok(freed)
 }
 
+/// Evict least recently used archives until the cache fits into `max_size`
+fn gc_cache_size(path: &Path, max_size: u64) -> Result<u64> {
+    if !path.exists() {
+        return Ok(0);
+    }
+
+    let cache = Cache::new(path.to_path_buf());
+    let freed = cache.gc_size(max_size).context("Failed to GC files cache")?;
+
+    if freed > 0 {
+        println!("  files cache: evicted {} to stay below {}", format_bytes(freed), format_bytes(max_size));
+    }
+
+    Ok(freed)
+}
+
 /// Run garbage collection on VCS cache (directory-based)
 fn gc_vcs_cache(path: &PathBuf, ttl: std::time::Duration) -> Result<u64> {
     if !path.exists() {
@@ -0,0 +1,351 @@
+//! Config command - read and write Composer configuration.
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use console::style;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use pox_pm::config::{composer_file_name, is_auth_key, Config, ConfigLoader, ConfigSetting, ConfigSource, SettingTarget};
+use pox_pm::json::JsonManipulator;
+
+#[derive(Args, Debug)]
+pub struct ConfigArgs {
+    /// Setting key, e.g. `sort-packages`, `platform.php` or `repositories.foo`
+    pub setting_key: Option<String>,
+
+    /// Setting value(s)
+    pub setting_values: Vec<String>,
+
+    /// Apply to the global config file in COMPOSER_HOME
+    #[arg(short, long)]
+    pub global: bool,
+
+    /// Unset the given setting
+    #[arg(long, conflicts_with_all = ["list", "setting_values"])]
+    pub unset: bool,
+
+    /// List configuration settings with their source
+    #[arg(short, long)]
+    pub list: bool,
+
+    /// Parse the value as JSON
+    #[arg(short, long)]
+    pub json: bool,
+
+    /// Merge a JSON value with the existing one instead of replacing it
+    #[arg(short, long, requires = "json")]
+    pub merge: bool,
+
+    /// Working directory
+    #[arg(short = 'd', long, default_value = ".")]
+    pub working_dir: PathBuf,
+}
+
+pub async fn execute(args: ConfigArgs) -> Result<i32> {
+    let working_dir = args
+        .working_dir
+        .canonicalize()
+        .context("Failed to resolve working directory")?;
+
+    if args.list {
+        return list(&args, &working_dir);
+    }
+
+    let Some(key) = args.setting_key.as_deref() else {
+        bail!("You must pass a setting key or use --list");
+    };
+
+    let setting = ConfigSetting::parse(key)?;
+    if args.global && setting.target == SettingTarget::Root {
+        bail!("--global cannot be combined with {}, it is a composer.json property", key);
+    }
+
+    let path = target_file(&setting, args.global, &working_dir);
+
+    if !args.unset && args.setting_values.is_empty() {
+        return get(&setting, &path, args.global, &working_dir);
+    }
+
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(_) if args.global || setting.target == SettingTarget::Auth => String::new(),
+        Err(_) => bail!("Composer file not found: {}", path.display()),
+    };
+    let mut manipulator = JsonManipulator::new(&contents)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    if args.unset {
+        unset_setting(&mut manipulator, &setting);
+    } else {
+        let value = if args.json {
+            let [value] = args.setting_values.as_slice() else {
+                bail!("You can only pass one JSON value for {}", key);
+            };
+            serde_json::from_str(value).with_context(|| format!("{} is not valid JSON", value))?
+        } else {
+            setting.value(&args.setting_values)?
+        };
+
+        let value = if args.merge {
+            merge_value(manipulator.get(&path_refs(&setting)), value)?
+        } else {
+            value
+        };
+
+        set_setting(&mut manipulator, &setting, &value)?;
+    }
+
+    if manipulator.contents() != contents {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, manipulator.contents())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    Ok(0)
+}
+
+/// The file a setting is read from and written to.
+fn target_file(setting: &ConfigSetting, global: bool, working_dir: &Path) -> PathBuf {
+    let home = ConfigLoader::new(true).get_composer_home();
+    match (&setting.target, global) {
+        (SettingTarget::Auth, true) => home.join("auth.json"),
+        (SettingTarget::Auth, false) => working_dir.join("auth.json"),
+        (_, true) => home.join("config.json"),
+        (_, false) => working_dir.join(composer_file_name()),
+    }
+}
+
+fn path_refs(setting: &ConfigSetting) -> Vec<&str> {
+    setting.path.iter().map(String::as_str).collect()
+}
+
+/// Print the value of a single setting.
+fn get(setting: &ConfigSetting, path: &Path, global: bool, working_dir: &Path) -> Result<i32> {
+    let value = match setting.target {
+        SettingTarget::Config => {
+            let config = Config::build((!global).then_some(working_dir), true)?;
+            config.get_value(&setting.config_key())
+        }
+        _ => std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| JsonManipulator::new(&contents).ok())
+            .and_then(|manipulator| {
+                let name = &setting.path[setting.path.len() - 1];
+                match (setting.target == SettingTarget::Repository, manipulator.get(&["repositories"])) {
+                    (true, Some(Value::Array(repositories))) => find_named_repository(&repositories, name),
+                    _ => manipulator.get(&path_refs(setting)),
+                }
+            }),
+    };
+
+    match value {
+        Some(value) => {
+            println!("{}", format_value(&value));
+            Ok(0)
+        }
+        None => bail!("{} is not defined", setting.path.join(".")),
+    }
+}
+
+/// Print all settings with the source they were loaded from.
+fn list(args: &ConfigArgs, working_dir: &Path) -> Result<i32> {
+    let config = Config::build((!args.global).then_some(working_dir), true)?;
+
+    let mut entries = Vec::new();
+    for (key, value) in config.to_values() {
+        if !is_auth_key(&key) {
+            flatten(&key, &value, &mut entries);
+        }
+    }
+
+    for (key, value) in entries {
+        let source = config
+            .get_source(&key)
+            .or_else(|| config.get_source(key.split('.').next().unwrap_or(&key)))
+            .map(format_source)
+            .unwrap_or_else(|| "default".to_string());
+
+        println!("[{}] {} {}", style(&key).green(), value, style(format!("({})", source)).dim());
+    }
+
+    let repositories_file = if args.global {
+        ConfigLoader::new(true).get_composer_home().join("config.json")
+    } else {
+        working_dir.join(composer_file_name())
+    };
+    let repositories = std::fs::read_to_string(&repositories_file)
+        .ok()
+        .and_then(|contents| JsonManipulator::new(&contents).ok())
+        .and_then(|manipulator| manipulator.get(&["repositories"]));
+
+    if let Some(repositories) = repositories {
+        let source = if args.global { "global" } else { "project" };
+        let mut entries = Vec::new();
+        flatten("repositories", &repositories, &mut entries);
+        for (key, value) in entries {
+            println!("[{}] {} {}", style(&key).green(), value, style(format!("({})", source)).dim());
+        }
+    }
+
+    Ok(0)
+}
+
+fn format_source(source: &ConfigSource) -> String {
+    match source {
+        ConfigSource::Environment(var) => format!("env: {}", var),
+        source => source.as_str().to_string(),
+    }
+}
+
+/// Flatten nested objects into `a.b` keys for display.
+fn flatten(prefix: &str, value: &Value, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                flatten(&format!("{}.{}", prefix, key), value, out);
+            }
+        }
+        Value::Array(items) if items.iter().any(|i| i.is_object()) => {
+            for (index, item) in items.iter().enumerate() {
+                flatten(&format!("{}.{}", prefix, index), item, out);
+            }
+        }
+        value => out.push((prefix.to_string(), format_value(value))),
+    }
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.iter().all(|i| !i.is_object() && !i.is_array()) => {
+            format!("[{}]", items.iter().map(format_value).collect::<Vec<_>>().join(", "))
+        }
+        value => value.to_string(),
+    }
+}
+
+fn find_named_repository(repositories: &[Value], name: &str) -> Option<Value> {
+    repositories.iter().find_map(|repository| {
+        if repository.get("name").and_then(|n| n.as_str()) == Some(name) {
+            Some(repository.clone())
+        } else {
+            repository.get(name).filter(|v| v.is_boolean()).cloned()
+        }
+    })
+}
+
+fn is_named_repository(repository: &Value, name: &str) -> bool {
+    repository.get("name").and_then(|n| n.as_str()) == Some(name)
+        || repository.get(name).is_some_and(|v| v.is_boolean())
+}
+
+/// Write a setting, keeping list-style `repositories` as a list.
+fn set_setting(manipulator: &mut JsonManipulator, setting: &ConfigSetting, value: &Value) -> Result<()> {
+    if setting.target == SettingTarget::Repository {
+        if let Some(Value::Array(_)) = manipulator.get(&["repositories"]) {
+            let name = &setting.path[1];
+            manipulator.remove_array_items(&["repositories"], |r| is_named_repository(r, name));
+
+            let mut repository = serde_json::Map::new();
+            match value {
+                Value::Object(map) => {
+                    repository.insert("name".to_string(), Value::String(name.clone()));
+                    repository.extend(map.iter().filter(|(k, _)| *k != "name").map(|(k, v)| (k.clone(), v.clone())));
+                }
+                value => {
+                    repository.insert(name.clone(), value.clone());
+                }
+            }
+            manipulator.append(&["repositories"], &Value::Object(repository))?;
+            return Ok(());
+        }
+    }
+
+    manipulator.set(&path_refs(setting), value)?;
+    Ok(())
+}
+
+fn unset_setting(manipulator: &mut JsonManipulator, setting: &ConfigSetting) {
+    if setting.target == SettingTarget::Repository {
+        if let Some(Value::Array(_)) = manipulator.get(&["repositories"]) {
+            let name = &setting.path[1];
+            manipulator.remove_array_items(&["repositories"], |r| is_named_repository(r, name));
+            return;
+        }
+    }
+
+    manipulator.remove(&path_refs(setting));
+}
+
+fn merge_value(existing: Option<Value>, value: Value) -> Result<Value> {
+    match (existing, value) {
+        (Some(Value::Object(mut existing)), Value::Object(value)) => {
+            existing.extend(value);
+            Ok(Value::Object(existing))
+        }
+        (None, value) => Ok(value),
+        _ => bail!("--merge can only be used with JSON objects"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_flatten() {
+        let mut entries = Vec::new();
+        flatten("platform", &json!({"php": "8.2", "ext-intl": false}), &mut entries);
+        flatten("github-protocols", &json!(["https", "ssh"]), &mut entries);
+        flatten("allow-plugins", &json!({}), &mut entries);
+
+        assert_eq!(entries, vec![
+            ("platform.php".to_string(), "8.2".to_string()),
+            ("platform.ext-intl".to_string(), "false".to_string()),
+            ("github-protocols".to_string(), "[https, ssh]".to_string()),
+            ("allow-plugins".to_string(), "{}".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_set_repository_in_list() {
+        let mut manipulator = JsonManipulator::new(
+            "{\n    \"repositories\": [\n        {\"name\": \"foo\", \"type\": \"path\", \"url\": \"../foo\"}\n    ]\n}\n",
+        )
+        .unwrap();
+        let setting = ConfigSetting::parse("repositories.foo").unwrap();
+
+        set_setting(&mut manipulator, &setting, &json!({"type": "vcs", "url": "https://example.com/foo.git"})).unwrap();
+        assert_eq!(
+            manipulator.get(&["repositories"]),
+            Some(json!([{"name": "foo", "type": "vcs", "url": "https://example.com/foo.git"}]))
+        );
+
+        unset_setting(&mut manipulator, &setting);
+        assert_eq!(manipulator.get(&["repositories"]), Some(json!([])));
+    }
+
+    #[test]
+    fn test_set_repository_in_object() {
+        let mut manipulator = JsonManipulator::new("{\n    \"name\": \"vendor/project\"\n}\n").unwrap();
+        let setting = ConfigSetting::parse("repositories.packagist.org").unwrap();
+
+        set_setting(&mut manipulator, &setting, &json!(false)).unwrap();
+        assert_eq!(manipulator.get(&["repositories"]), Some(json!({"packagist.org": false})));
+
+        unset_setting(&mut manipulator, &setting);
+        assert_eq!(manipulator.get(&["repositories"]), Some(json!({})));
+    }
+
+    #[test]
+    fn test_merge_value() {
+        let merged = merge_value(Some(json!({"a": 1})), json!({"b": 2})).unwrap();
+        assert_eq!(merged, json!({"a": 1, "b": 2}));
+        assert_eq!(merge_value(None, json!({"b": 2})).unwrap(), json!({"b": 2}));
+        assert!(merge_value(Some(json!("x")), json!({"b": 2})).is_err());
+    }
+}
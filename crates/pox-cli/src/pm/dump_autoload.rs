@@ -10,6 +10,7 @@ use pox_pm::{
     installer::Installer,
     json::{ComposerJson, ComposerLock},
 };
+use pox_pm::config::{composer_file_name, lock_file_name};
 
 #[derive(Args, Debug)]
 pub struct DumpAutoloadArgs {
@@ -39,7 +40,7 @@ pub async fn execute(args: DumpAutoloadArgs) -> Result<i32> {
         .context("Failed to resolve working directory")?;
 
     // Load composer.json
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
     let composer_json: ComposerJson = if json_path.exists() {
         let content = std::fs::read_to_string(&json_path)?;
         serde_json::from_str(&content)?
@@ -49,7 +50,7 @@ pub async fn execute(args: DumpAutoloadArgs) -> Result<i32> {
     };
 
     // Load composer.lock
-    let lock_path = working_dir.join("composer.lock");
+    let lock_path = working_dir.join(lock_file_name());
     let lock: Option<ComposerLock> = if lock_path.exists() {
         let content = std::fs::read_to_string(&lock_path)
             .context("Failed to read composer.lock")?;
@@ -63,6 +64,8 @@ pub async fn execute(args: DumpAutoloadArgs) -> Result<i32> {
 
     // Create Composer using builder
     let composer = ComposerBuilder::new(working_dir.clone())
+        .with_io(crate::io::io())
+        .with_profiler(crate::io::profiler())
         .with_config(config)
         .with_composer_json(composer_json)
         .with_composer_lock(lock)
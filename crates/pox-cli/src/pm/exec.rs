@@ -4,10 +4,17 @@ use anyhow::{Context, Result};
 use clap::Args;
 use console::style;
 use dialoguer::{theme::ColorfulTheme, Select};
-use std::io::IsTerminal;
-use std::path::PathBuf;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use pox_pm::cache::Cache;
+use pox_pm::config::ConfigLoader;
+use pox_pm::downloader::{verify_signature, MinisignPublicKey, MinisignSignature};
+use pox_pm::http::HttpClient;
+use pox_pm::phar::{PharArchive, PharError};
+use sha2::{Digest, Sha256};
+
 #[derive(Args, Debug)]
 pub struct ExecArgs {
     /// Binary name to execute
@@ -18,6 +25,22 @@ pub struct ExecArgs {
     #[arg(short = 'l', long)]
     pub list: bool,
 
+    /// Run a phar tool from a URL or local path instead of vendor/bin
+    #[arg(long, value_name = "URL|PATH", conflicts_with = "list")]
+    pub phar: Option<String>,
+
+    /// Expected SHA-256 checksum of the phar
+    #[arg(long, value_name = "HASH", requires = "phar")]
+    pub sha256: Option<String>,
+
+    /// Minisign public key (or key file) used to verify the `.minisig` published next to the phar
+    #[arg(long, value_name = "KEY", requires = "phar")]
+    pub signing_key: Option<String>,
+
+    /// Download the phar again even if it is cached
+    #[arg(long, requires = "phar")]
+    pub no_cache: bool,
+
     /// Working directory
     #[arg(short = 'd', long, default_value = ".")]
     pub working_dir: PathBuf,
@@ -31,6 +54,19 @@ pub async fn execute(args: ExecArgs) -> Result<i32> {
     let working_dir = args.working_dir.canonicalize()
         .context("Failed to resolve working directory")?;
 
+    if let Some(phar) = args.phar.as_deref() {
+        // Without a vendor binary to pick, the positional argument belongs to the tool
+        let tool_args: Vec<String> = args.binary.iter().chain(args.args.iter()).cloned().collect();
+        let path = match resolve_phar(phar, &args).await {
+            Ok(path) => path,
+            Err(e) => {
+                eprintln!("{} {:#}", style("Error:").red().bold(), e);
+                return Ok(1);
+            }
+        };
+        return execute_binary(&path, &tool_args, &working_dir);
+    }
+
     let vendor_bin = working_dir.join("vendor/bin");
 
     let binaries = get_available_binaries(&vendor_bin)?;
@@ -98,6 +134,133 @@ pub async fn execute(args: ExecArgs) -> Result<i32> {
     }
 }
 
+/// Download (or reuse from cache) and verify a phar, returning the path to run
+async fn resolve_phar(spec: &str, args: &ExecArgs) -> Result<PathBuf> {
+    let is_url = spec.starts_with("https://") || spec.starts_with("http://");
+
+    if !is_url {
+        let path = PathBuf::from(spec);
+        let data = std::fs::read(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let minisig = match args.signing_key {
+            Some(_) => {
+                let sig_path = PathBuf::from(format!("{}.minisig", spec));
+                Some(std::fs::read_to_string(&sig_path)
+                    .with_context(|| format!("Failed to read signature {}", sig_path.display()))?)
+            }
+            None => None,
+        };
+        verify_phar(&data, args, minisig.as_deref())?;
+        return Ok(path);
+    }
+
+    let loader = ConfigLoader::new(true);
+    let cache = Cache::new(loader.get_cache_dir().join("phar"));
+    let key = phar_cache_key(spec);
+    let sig_key = format!("{}.minisig", key);
+
+    if !args.no_cache {
+        if let Some(data) = cache.read(&key)? {
+            // A phar cached without its signature is downloaded again with it
+            let minisig = match args.signing_key {
+                Some(_) => cache.read(&sig_key)?.map(|sig| String::from_utf8_lossy(&sig).into_owned()),
+                None => None,
+            };
+            if args.signing_key.is_none() || minisig.is_some() {
+                verify_phar(&data, args, minisig.as_deref())?;
+                return Ok(cache.root().join(&key));
+            }
+        }
+    }
+
+    println!("{} Downloading {}", style("Exec:").cyan().bold(), spec);
+    let client = HttpClient::new().context("Failed to create HTTP client")?;
+    let data = client.download_bytes(spec).await
+        .with_context(|| format!("Failed to download {}", spec))?;
+
+    let minisig = match args.signing_key {
+        Some(_) => {
+            let url = format!("{}.minisig", spec);
+            let sig = client.download_bytes(&url).await
+                .with_context(|| format!("Failed to download signature {}", url))?;
+            Some(String::from_utf8_lossy(&sig).into_owned())
+        }
+        None => None,
+    };
+    verify_phar(&data, args, minisig.as_deref())?;
+
+    if cache.is_enabled() && !cache.is_read_only() {
+        // The signature goes first, so a cached phar always has the one it was verified with
+        if let Some(minisig) = &minisig {
+            persist(cache.root(), &sig_key, minisig.as_bytes()).context("Failed to write phar signature to cache")?;
+        }
+        return persist(cache.root(), &key, &data).context("Failed to write phar to cache");
+    }
+
+    // Cache disabled or read-only, run from a temporary copy with an unpredictable name instead
+    let mut file = tempfile::Builder::new().suffix(".phar").tempfile()?;
+    file.write_all(&data)?;
+    let (_, path) = file.keep().context("Failed to keep temporary phar")?;
+    Ok(path)
+}
+
+/// Write `data` to `dir/name` through a temporary file in `dir`, replacing it atomically
+fn persist(dir: &Path, name: &str, data: &[u8]) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(data)?;
+    let path = dir.join(name);
+    file.persist(&path)?;
+    Ok(path)
+}
+
+/// Check the phar's own signature, the expected checksum and, with `--signing-key`, the minisign signature
+fn verify_phar(data: &[u8], args: &ExecArgs, minisig: Option<&str>) -> Result<()> {
+    let phar = PharArchive::from_bytes(data.to_vec()).context("Invalid phar archive")?;
+    match phar.verify() {
+        Ok(_) => {}
+        Err(PharError::Unsupported(reason)) => {
+            eprintln!("{} Embedded signature not checked: {} are not supported",
+                style("Warning:").yellow(),
+                reason
+            );
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    if let Some(expected) = &args.sha256 {
+        let actual = format!("{:x}", Sha256::digest(data));
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            anyhow::bail!("SHA-256 checksum mismatch (expected {}, got {})", expected, actual);
+        }
+    }
+
+    if let Some(key) = &args.signing_key {
+        let minisig = minisig.context("Phar signature missing")?;
+        let key = match std::fs::read_to_string(key) {
+            Ok(content) => content,
+            Err(_) => key.clone(),
+        };
+        let key = MinisignPublicKey::parse(&key).context("Invalid signing key")?;
+        let signature = MinisignSignature::parse(minisig).context("Invalid phar signature")?;
+        verify_signature(data, &signature, &[key]).context("Phar signature verification failed")?;
+    }
+
+    Ok(())
+}
+
+/// Cache file name for a phar URL, keeping the original file name readable
+fn phar_cache_key(url: &str) -> String {
+    let hash = format!("{:x}", Sha256::digest(url.as_bytes()));
+    let name = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|u| u.rsplit('/').next())
+        .filter(|n| !n.is_empty())
+        .unwrap_or("tool.phar");
+    format!("{}-{}", &hash[..12], name)
+}
+
 /// Get list of available binaries in vendor/bin
 fn get_available_binaries(vendor_bin: &PathBuf) -> Result<Vec<String>> {
     let mut binaries = Vec::new();
@@ -256,7 +419,7 @@ fn execute_binary(path: &PathBuf, args: &[String], working_dir: &PathBuf) -> Res
 
 fn is_php_file(path: &PathBuf) -> Result<bool> {
     if let Some(ext) = path.extension() {
-        if ext == "php" {
+        if ext == "php" || ext == "phar" {
             return Ok(true);
         }
     }
@@ -285,3 +448,28 @@ fn is_php_file(path: &PathBuf) -> Result<bool> {
 
     Ok(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phar_cache_key() {
+        let key = phar_cache_key("https://example.com/download/php-cs-fixer.phar?v=3");
+        assert!(key.ends_with("-php-cs-fixer.phar"));
+        assert_eq!(key.len(), 12 + 1 + "php-cs-fixer.phar".len());
+        assert_ne!(key, phar_cache_key("https://example.org/download/php-cs-fixer.phar"));
+
+        assert!(phar_cache_key("https://example.com/").ends_with("-tool.phar"));
+    }
+
+    #[test]
+    fn test_persist_replaces_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = persist(dir.path(), "tool.phar", b"first").unwrap();
+        assert_eq!(path, dir.path().join("tool.phar"));
+        persist(dir.path(), "tool.phar", b"second").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"second");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
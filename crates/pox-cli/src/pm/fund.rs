@@ -11,6 +11,7 @@ use pox_pm::{
     config::Config,
     json::{ComposerJson, ComposerLock},
 };
+use pox_pm::config::{composer_file_name, lock_file_name};
 
 #[derive(Args, Debug)]
 pub struct FundArgs {
@@ -37,7 +38,7 @@ pub async fn execute(args: FundArgs) -> Result<i32> {
         return Ok(1);
     }
 
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
     let _composer_json: ComposerJson = if json_path.exists() {
         let content = std::fs::read_to_string(&json_path)?;
         serde_json::from_str(&content)?
@@ -55,7 +56,7 @@ pub async fn execute(args: FundArgs) -> Result<i32> {
     let packages = installed_repo.get_packages().await;
 
     let packages: Vec<Arc<pox_pm::Package>> = if packages.is_empty() {
-        let lock_path = working_dir.join("composer.lock");
+        let lock_path = working_dir.join(lock_file_name());
         if lock_path.exists() {
             let lock_content = std::fs::read_to_string(&lock_path)?;
             let lock: ComposerLock = serde_json::from_str(&lock_content)?;
@@ -0,0 +1,121 @@
+//! Graph command - export the resolved dependency graph.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use pox_pm::{
+    DependencyGraph, Repository,
+    config::Config,
+    json::{ComposerJson, ComposerLock},
+};
+use pox_pm::config::{composer_file_name, lock_file_name};
+
+#[derive(Args, Debug)]
+pub struct GraphArgs {
+    /// Output format: dot, mermaid or json
+    #[arg(short = 'f', long, default_value = "dot")]
+    pub format: String,
+
+    /// Label edges with the version constraint of the requirement
+    #[arg(long)]
+    pub constraints: bool,
+
+    /// Exclude require-dev packages from the graph
+    #[arg(long)]
+    pub no_dev: bool,
+
+    /// Build the graph from the lock file instead of installed packages
+    #[arg(long)]
+    pub locked: bool,
+
+    /// Write the graph to a file instead of stdout
+    #[arg(short = 'o', long)]
+    pub output: Option<PathBuf>,
+
+    /// Working directory
+    #[arg(short = 'd', long, default_value = ".")]
+    pub working_dir: PathBuf,
+}
+
+pub async fn execute(args: GraphArgs) -> Result<i32> {
+    let working_dir = args
+        .working_dir
+        .canonicalize()
+        .context("Failed to resolve working directory")?;
+
+    if !matches!(args.format.as_str(), "dot" | "mermaid" | "json") {
+        eprintln!(
+            "Error: Unsupported format '{}'. Use 'dot', 'mermaid' or 'json'.",
+            args.format
+        );
+        return Ok(1);
+    }
+
+    let json_path = working_dir.join(composer_file_name());
+    let composer_json: ComposerJson = if json_path.exists() {
+        let content = std::fs::read_to_string(&json_path)?;
+        serde_json::from_str(&content)?
+    } else {
+        eprintln!("Error: composer.json not found in working directory");
+        return Ok(1);
+    };
+
+    let packages: Vec<Arc<pox_pm::Package>> = if args.locked {
+        let lock_path = working_dir.join(lock_file_name());
+        if !lock_path.exists() {
+            eprintln!("Error: A valid composer.json and composer.lock is required for --locked");
+            return Ok(1);
+        }
+        let lock_content = std::fs::read_to_string(&lock_path)?;
+        let lock: ComposerLock = serde_json::from_str(&lock_content)?;
+
+        lock.packages
+            .into_iter()
+            .chain(lock.packages_dev)
+            .map(|lp| Arc::new(pox_pm::Package::from(lp)))
+            .collect()
+    } else {
+        let config = Config::build(Some(&working_dir), true)?;
+        let vendor_dir = working_dir.join(&config.vendor_dir);
+        let installed_repo = Arc::new(pox_pm::repository::InstalledRepository::new(vendor_dir));
+        installed_repo.load().await.ok();
+        installed_repo.get_packages().await
+    };
+
+    if packages.is_empty() && (!composer_json.require.is_empty() || !composer_json.require_dev.is_empty()) {
+        eprintln!("Warning: No dependencies installed. Try running install or update, or use --locked.");
+    }
+
+    let root_package = pox_pm::Package {
+        name: composer_json.name.clone().unwrap_or_else(|| "__root__".to_string()),
+        pretty_name: composer_json.name.clone(),
+        version: composer_json.version.clone().unwrap_or_else(|| "dev-main".to_string()),
+        pretty_version: composer_json.version.clone(),
+        package_type: "root-package".to_string(),
+        require: composer_json.require.clone(),
+        require_dev: composer_json.require_dev.clone(),
+        ..Default::default()
+    };
+
+    let graph = DependencyGraph::build(&root_package, &packages, !args.no_dev);
+
+    let rendered = match args.format.as_str() {
+        "dot" => graph.to_dot(args.constraints),
+        "mermaid" => graph.to_mermaid(args.constraints),
+        "json" => serde_json::to_string_pretty(&graph.to_json())? + "\n",
+        _ => unreachable!(),
+    };
+
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, rendered)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("Dependency graph written to {}", path.display());
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(0)
+}
@@ -10,6 +10,7 @@ use pox_pm::{
     config::Config,
     json::ComposerJson,
 };
+use pox_pm::config::composer_file_name;
 
 #[derive(Args, Debug)]
 pub struct HomeArgs {
@@ -36,7 +37,7 @@ pub async fn execute(args: HomeArgs) -> Result<i32> {
         .canonicalize()
         .context("Failed to resolve working directory")?;
 
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
     let composer_json: ComposerJson = if json_path.exists() {
         let content = std::fs::read_to_string(&json_path)?;
         serde_json::from_str(&content)?
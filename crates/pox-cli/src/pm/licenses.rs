@@ -4,17 +4,21 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use pox_spdx::{compare_list_versions, SpdxData, SpdxLicenses, EXCEPTIONS_URL, LICENSES_URL};
 use pox_pm::{
     Repository,
     config::Config,
+    http::{HttpClient, HttpClientConfig},
+    installer::{spdx_data_dir, spdx_licenses},
     json::{ComposerJson, ComposerLock},
     package::detect_root_version,
     repository::RepositoryUtils,
 };
+use pox_pm::config::{composer_file_name, lock_file_name};
 
 #[derive(Args, Debug)]
 pub struct LicensesArgs {
-    /// Output format: text, json or summary
+    /// Output format: text, json, summary, cyclonedx or spdx (SBOM formats)
     #[arg(short = 'f', long, default_value = "text")]
     pub format: String,
 
@@ -26,6 +30,10 @@ pub struct LicensesArgs {
     #[arg(long)]
     pub locked: bool,
 
+    /// Downloads the latest SPDX license list into the cache, used instead of the bundled list
+    #[arg(long)]
+    pub update_spdx_data: bool,
+
     /// Working directory
     #[arg(short = 'd', long, default_value = ".")]
     pub working_dir: PathBuf,
@@ -37,7 +45,15 @@ pub async fn execute(args: LicensesArgs) -> Result<i32> {
         .canonicalize()
         .context("Failed to resolve working directory")?;
 
-    if args.format != "text" && args.format != "json" && args.format != "summary" {
+    if args.update_spdx_data {
+        let config = Config::build(Some(&working_dir), true)?;
+        return update_spdx_data(&config).await;
+    }
+
+    if !matches!(
+        args.format.as_str(),
+        "text" | "json" | "summary" | "cyclonedx" | "spdx" | "spdx-sbom"
+    ) {
         eprintln!(
             "Error: Unsupported format '{}'. See help for supported formats.",
             args.format
@@ -45,7 +61,7 @@ pub async fn execute(args: LicensesArgs) -> Result<i32> {
         return Ok(1);
     }
 
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
     let composer_json: ComposerJson = if json_path.exists() {
         let content = std::fs::read_to_string(&json_path)?;
         serde_json::from_str(&content)?
@@ -56,9 +72,10 @@ pub async fn execute(args: LicensesArgs) -> Result<i32> {
 
     let config = Config::build(Some(&working_dir), true)?;
     let vendor_dir = working_dir.join(&config.vendor_dir);
+    let spdx = spdx_licenses(&config);
 
     let packages: Vec<Arc<pox_pm::Package>> = if args.locked {
-        let lock_path = working_dir.join("composer.lock");
+        let lock_path = working_dir.join(lock_file_name());
         if !lock_path.exists() {
             eprintln!("Error: Valid composer.json and composer.lock files are required to run this command with --locked");
             return Ok(1);
@@ -217,12 +234,306 @@ pub async fn execute(args: LicensesArgs) -> Result<i32> {
 
             println!(" ----------------------- ----------------------- ");
         }
+        "cyclonedx" => {
+            let created = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+            let bom = build_cyclonedx(&spdx, root_name, &root_version, &root_licenses, &packages, &created);
+            println!("{}", serde_json::to_string_pretty(&bom)?);
+        }
+        "spdx" | "spdx-sbom" => {
+            let created = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+            print!(
+                "{}",
+                build_spdx_tag_value(&spdx, root_name, &root_version, &root_licenses, &packages, &created)
+            );
+        }
         _ => unreachable!(),
     }
 
     Ok(0)
 }
 
+/// Build a package URL (purl) for a Composer package.
+fn package_purl(name: &str, version: &str) -> String {
+    format!("pkg:composer/{}@{}", name, urlencoding::encode(version))
+}
+
+/// Combine Composer's license array into a single SPDX expression.
+///
+/// Multiple licenses in composer.json are alternatives, so they are joined with OR.
+/// Returns None when any entry is not a valid SPDX expression.
+fn spdx_expression(spdx: &SpdxLicenses, licenses: &[String]) -> Option<String> {
+    if licenses.is_empty() || !licenses.iter().all(|l| spdx.validate(l)) {
+        return None;
+    }
+
+    if licenses.len() == 1 {
+        return Some(licenses[0].clone());
+    }
+
+    let parts: Vec<String> = licenses
+        .iter()
+        .map(|l| {
+            if l.contains(' ') {
+                format!("({})", l)
+            } else {
+                l.clone()
+            }
+        })
+        .collect();
+    Some(format!("({})", parts.join(" OR ")))
+}
+
+fn non_empty(value: Option<&String>) -> Option<&str> {
+    value.map(|s| s.as_str()).filter(|s| !s.is_empty())
+}
+
+fn cyclonedx_licenses(spdx: &SpdxLicenses, licenses: &[String]) -> Vec<serde_json::Value> {
+    if let Some(expression) = spdx_expression(spdx, licenses) {
+        if licenses.len() == 1 && spdx.get_license_by_identifier(&expression).is_some() {
+            return vec![serde_json::json!({ "license": { "id": expression } })];
+        }
+        return vec![serde_json::json!({ "expression": expression })];
+    }
+
+    licenses
+        .iter()
+        .map(|l| serde_json::json!({ "license": { "name": l } }))
+        .collect()
+}
+
+/// Build a CycloneDX 1.5 JSON SBOM for the root package and its dependencies.
+fn build_cyclonedx(
+    spdx: &SpdxLicenses,
+    root_name: &str,
+    root_version: &str,
+    root_licenses: &[String],
+    packages: &[Arc<pox_pm::Package>],
+    created: &str,
+) -> serde_json::Value {
+    let components: Vec<serde_json::Value> = packages
+        .iter()
+        .map(|package| {
+            let version = package.pretty_version.as_deref().unwrap_or(&package.version);
+            let purl = package_purl(&package.name, version);
+            let (group, name) = package
+                .name
+                .split_once('/')
+                .unwrap_or(("", package.name.as_str()));
+
+            let mut component = serde_json::json!({
+                "type": "library",
+                "bom-ref": purl,
+                "group": group,
+                "name": name,
+                "version": version,
+                "purl": purl,
+            });
+
+            if let Some(description) = &package.description {
+                component["description"] = serde_json::json!(description);
+            }
+
+            let licenses = cyclonedx_licenses(spdx, &package.license);
+            if !licenses.is_empty() {
+                component["licenses"] = serde_json::json!(licenses);
+            }
+
+            if let Some(dist) = &package.dist {
+                let mut hashes = Vec::new();
+                if let Some(sha1) = non_empty(dist.shasum.as_ref()) {
+                    hashes.push(serde_json::json!({ "alg": "SHA-1", "content": sha1 }));
+                }
+                if let Some(sha256) = non_empty(dist.sha256.as_ref()) {
+                    hashes.push(serde_json::json!({ "alg": "SHA-256", "content": sha256 }));
+                }
+                if !hashes.is_empty() {
+                    component["hashes"] = serde_json::json!(hashes);
+                }
+                if !dist.url.is_empty() {
+                    component["externalReferences"] = serde_json::json!([
+                        { "type": "distribution", "url": dist.url }
+                    ]);
+                }
+            }
+
+            if let Some(source) = &package.source {
+                if !source.url.is_empty() {
+                    let refs = component
+                        .as_object_mut()
+                        .unwrap()
+                        .entry("externalReferences")
+                        .or_insert_with(|| serde_json::json!([]));
+                    if let Some(refs) = refs.as_array_mut() {
+                        refs.push(serde_json::json!({ "type": "vcs", "url": source.url }));
+                    }
+                }
+            }
+
+            component
+        })
+        .collect();
+
+    let root_purl = package_purl(root_name, root_version);
+    let mut root_component = serde_json::json!({
+        "type": "application",
+        "bom-ref": root_purl,
+        "name": root_name,
+        "version": root_version,
+        "purl": root_purl,
+    });
+    let root_license_entries = cyclonedx_licenses(spdx, root_licenses);
+    if !root_license_entries.is_empty() {
+        root_component["licenses"] = serde_json::json!(root_license_entries);
+    }
+
+    let depends_on: Vec<&str> = components
+        .iter()
+        .filter_map(|c| c["bom-ref"].as_str())
+        .collect();
+
+    serde_json::json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "timestamp": created,
+            "tools": [{ "vendor": "pox", "name": "pox", "version": env!("CARGO_PKG_VERSION") }],
+            "component": root_component,
+        },
+        "components": components,
+        "dependencies": [{ "ref": root_purl, "dependsOn": depends_on }],
+    })
+}
+
+/// Sanitize a string for use as an SPDX element identifier.
+fn spdx_id(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '-' })
+        .collect();
+    format!("SPDXRef-Package-{}", sanitized)
+}
+
+/// Build an SPDX 2.3 tag-value SBOM for the root package and its dependencies.
+fn build_spdx_tag_value(
+    spdx: &SpdxLicenses,
+    root_name: &str,
+    root_version: &str,
+    root_licenses: &[String],
+    packages: &[Arc<pox_pm::Package>],
+    created: &str,
+) -> String {
+    let mut out = String::new();
+
+    out.push_str("SPDXVersion: SPDX-2.3\n");
+    out.push_str("DataLicense: CC0-1.0\n");
+    out.push_str("SPDXID: SPDXRef-DOCUMENT\n");
+    out.push_str(&format!("DocumentName: {}\n", root_name));
+    out.push_str(&format!(
+        "DocumentNamespace: https://spdx.org/spdxdocs/{}-{}-{}\n",
+        root_name.replace('/', "-"),
+        root_version.replace(' ', "-"),
+        created.replace(':', "")
+    ));
+    out.push_str(&format!("Creator: Tool: pox-{}\n", env!("CARGO_PKG_VERSION")));
+    out.push_str(&format!("Created: {}\n", created));
+
+    let root_id = spdx_id(root_name);
+    let write_package = |out: &mut String, id: &str, name: &str, version: &str, package: Option<&pox_pm::Package>, licenses: &[String]| {
+        out.push('\n');
+        out.push_str(&format!("PackageName: {}\n", name));
+        out.push_str(&format!("SPDXID: {}\n", id));
+        out.push_str(&format!("PackageVersion: {}\n", version));
+
+        let dist = package.and_then(|p| p.dist.as_ref());
+        let location = dist
+            .map(|d| d.url.as_str())
+            .filter(|u| !u.is_empty())
+            .unwrap_or("NOASSERTION");
+        out.push_str(&format!("PackageDownloadLocation: {}\n", location));
+        out.push_str("FilesAnalyzed: false\n");
+
+        if let Some(dist) = dist {
+            if let Some(sha1) = non_empty(dist.shasum.as_ref()) {
+                out.push_str(&format!("PackageChecksum: SHA1: {}\n", sha1));
+            }
+            if let Some(sha256) = non_empty(dist.sha256.as_ref()) {
+                out.push_str(&format!("PackageChecksum: SHA256: {}\n", sha256));
+            }
+        }
+
+        let declared = spdx_expression(spdx, licenses).unwrap_or_else(|| "NOASSERTION".to_string());
+        out.push_str("PackageLicenseConcluded: NOASSERTION\n");
+        out.push_str(&format!("PackageLicenseDeclared: {}\n", declared));
+        out.push_str("PackageCopyrightText: NOASSERTION\n");
+        out.push_str(&format!(
+            "ExternalRef: PACKAGE-MANAGER purl {}\n",
+            package_purl(name, version)
+        ));
+    };
+
+    write_package(&mut out, &root_id, root_name, root_version, None, root_licenses);
+    for package in packages {
+        let version = package.pretty_version.as_deref().unwrap_or(&package.version);
+        write_package(&mut out, &spdx_id(&package.name), &package.name, version, Some(package), &package.license);
+    }
+
+    out.push('\n');
+    out.push_str(&format!("Relationship: SPDXRef-DOCUMENT DESCRIBES {}\n", root_id));
+    for package in packages {
+        out.push_str(&format!(
+            "Relationship: {} DEPENDS_ON {}\n",
+            root_id,
+            spdx_id(&package.name)
+        ));
+    }
+
+    out
+}
+
+/// Download the current SPDX license list into the cache directory.
+async fn update_spdx_data(config: &Config) -> Result<i32> {
+    let dir = spdx_data_dir(config).context("Cache directory not configured")?;
+    let client = HttpClient::with_config(HttpClientConfig::new().with_proxy_config(config.proxy.clone()))
+        .context("Failed to create HTTP client")?;
+
+    let licenses = client.download_bytes(LICENSES_URL).await
+        .with_context(|| format!("Failed to download {}", LICENSES_URL))?;
+    let exceptions = client.download_bytes(EXCEPTIONS_URL).await
+        .with_context(|| format!("Failed to download {}", EXCEPTIONS_URL))?;
+
+    let data = SpdxData::from_spdx_json(
+        &String::from_utf8_lossy(&licenses),
+        &String::from_utf8_lossy(&exceptions),
+    )?;
+    let spdx = data.load()?;
+
+    // A mirror or cache may serve an older list than the one already refreshed
+    if let Some(current) = SpdxData::version_in(&dir) {
+        if compare_list_versions(&current, &data.version) == std::cmp::Ordering::Greater {
+            println!(
+                "Keeping SPDX license list version {} in {}, the downloaded list is older ({})",
+                current,
+                dir.display(),
+                data.version
+            );
+            return Ok(0);
+        }
+    }
+
+    data.write_to(&dir)
+        .with_context(|| format!("Failed to write SPDX data to {}", dir.display()))?;
+
+    println!(
+        "Updated SPDX license list to version {} ({} licenses, {} exceptions) in {}",
+        data.version,
+        spdx.license_count(),
+        spdx.exception_count(),
+        dir.display()
+    );
+    Ok(0)
+}
+
 fn get_short_git_ref(path: &std::path::Path) -> Option<String> {
     let git_dir = path.join(".git");
     if !git_dir.exists() {
@@ -320,4 +631,75 @@ mod tests {
         let result = get_short_git_ref(temp_dir.path());
         assert!(result.is_none());
     }
+
+    fn sbom_package(name: &str, version: &str, licenses: &[&str]) -> Arc<pox_pm::Package> {
+        let mut package = pox_pm::Package::new(name, version);
+        package.pretty_version = Some(version.to_string());
+        package.license = licenses.iter().map(|l| l.to_string()).collect();
+        package.dist = Some(pox_pm::package::Dist {
+            dist_type: "zip".to_string(),
+            url: format!("https://example.org/{}.zip", name),
+            reference: None,
+            shasum: Some("0123456789abcdef0123456789abcdef01234567".to_string()),
+            sha256: None,
+            mirrors: None,
+            signature: None,
+            transport_options: None,
+        });
+        Arc::new(package)
+    }
+
+    #[test]
+    fn test_spdx_expression() {
+        let spdx = SpdxLicenses::new();
+        assert_eq!(spdx_expression(&spdx, &["MIT".to_string()]), Some("MIT".to_string()));
+        assert_eq!(
+            spdx_expression(&spdx, &["MIT".to_string(), "GPL-2.0-or-later".to_string()]),
+            Some("(MIT OR GPL-2.0-or-later)".to_string())
+        );
+        assert_eq!(spdx_expression(&spdx, &["proprietary-ish".to_string()]), None);
+        assert_eq!(spdx_expression(&spdx, &[]), None);
+    }
+
+    #[test]
+    fn test_build_cyclonedx() {
+        let packages = vec![
+            sbom_package("psr/log", "3.0.0", &["MIT"]),
+            sbom_package("vendor/dual", "1.0.0", &["MIT", "Apache-2.0"]),
+        ];
+        let bom = build_cyclonedx(&SpdxLicenses::new(), "acme/app", "1.0.0", &["MIT".to_string()], &packages, "2024-01-01T00:00:00Z");
+
+        assert_eq!(bom["bomFormat"], "CycloneDX");
+        assert_eq!(bom["metadata"]["timestamp"], "2024-01-01T00:00:00Z");
+        assert_eq!(bom["metadata"]["component"]["name"], "acme/app");
+
+        let log = &bom["components"][0];
+        assert_eq!(log["group"], "psr");
+        assert_eq!(log["name"], "log");
+        assert_eq!(log["purl"], "pkg:composer/psr/log@3.0.0");
+        assert_eq!(log["licenses"][0]["license"]["id"], "MIT");
+        assert_eq!(log["hashes"][0]["alg"], "SHA-1");
+        assert_eq!(log["externalReferences"][0]["url"], "https://example.org/psr/log.zip");
+
+        assert_eq!(bom["components"][1]["licenses"][0]["expression"], "(MIT OR Apache-2.0)");
+        assert_eq!(bom["dependencies"][0]["dependsOn"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_build_spdx_tag_value() {
+        let packages = vec![sbom_package("psr/log", "3.0.0", &["MIT"])];
+        let doc = build_spdx_tag_value(&SpdxLicenses::new(), "acme/app", "1.0.0", &[], &packages, "2024-01-01T00:00:00Z");
+
+        assert!(doc.starts_with("SPDXVersion: SPDX-2.3\n"));
+        assert!(doc.contains("PackageName: psr/log\nSPDXID: SPDXRef-Package-psr-log\n"));
+        assert!(doc.contains("PackageDownloadLocation: https://example.org/psr/log.zip\n"));
+        assert!(doc.contains("PackageChecksum: SHA1: 0123456789abcdef0123456789abcdef01234567\n"));
+        assert!(doc.contains("PackageLicenseDeclared: MIT\n"));
+        assert!(doc.contains("ExternalRef: PACKAGE-MANAGER purl pkg:composer/psr/log@3.0.0\n"));
+        assert!(doc.contains("Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-acme-app\n"));
+        assert!(doc.contains("Relationship: SPDXRef-Package-acme-app DEPENDS_ON SPDXRef-Package-psr-log\n"));
+
+        // Root package has no license and no dist
+        assert!(doc.contains("PackageName: acme/app\nSPDXID: SPDXRef-Package-acme-app\nPackageVersion: 1.0.0\nPackageDownloadLocation: NOASSERTION\n"));
+    }
 }
@@ -1,7 +1,9 @@
 //! Package manager subcommands.
 
 pub mod bin;
+mod bench;
 mod bump;
+mod config;
 mod exec;
 mod dump_autoload;
 mod clear_cache;
@@ -16,13 +18,21 @@ mod licenses;
 mod home;
 mod suggests;
 mod fund;
+mod graph;
 mod reinstall;
+mod prune;
+mod archive;
+mod check_platform_reqs;
 
-use clap::Subcommand;
+use clap::{ArgMatches, FromArgMatches, Subcommand};
 use anyhow::Result;
 
+use crate::io::IoArgs;
+
 pub use bin::BinArgs;
+pub use bench::BenchArgs;
 pub use bump::BumpArgs;
+pub use config::ConfigArgs;
 pub use exec::ExecArgs;
 pub use dump_autoload::DumpAutoloadArgs;
 pub use clear_cache::ClearCacheArgs;
@@ -36,7 +46,11 @@ pub use licenses::LicensesArgs;
 pub use home::HomeArgs;
 pub use suggests::SuggestsArgs;
 pub use fund::FundArgs;
+pub use graph::GraphArgs;
 pub use reinstall::ReinstallArgs;
+pub use prune::PruneArgs;
+pub use archive::ArchiveArgs;
+pub use check_platform_reqs::CheckPlatformReqsArgs;
 
 // Re-export args for pm subcommand aliases
 pub use crate::install::InstallArgs;
@@ -51,9 +65,15 @@ pub enum PmCommands {
     /// Run a command in a bin namespace (vendor-bin plugin)
     Bin(BinArgs),
 
+    /// Run a standardized benchmark and print the timings
+    Bench(BenchArgs),
+
     /// Increases the lower limit of your composer.json requirements to the currently installed versions
     Bump(BumpArgs),
 
+    /// Read and write configuration settings
+    Config(ConfigArgs),
+
     /// Execute a vendored binary/script
     Exec(ExecArgs),
 
@@ -100,9 +120,23 @@ pub enum PmCommands {
     #[command(alias = "suggest")]
     Suggests(SuggestsArgs),
 
+    /// Export the dependency graph (dot, mermaid or json)
+    Graph(GraphArgs),
+
     /// Uninstall and reinstall packages
     Reinstall(ReinstallArgs),
 
+    /// Remove installed packages that are no longer required by the project
+    #[command(alias = "autoremove")]
+    Prune(PruneArgs),
+
+    /// Create an archive of the project or an installed package
+    Archive(ArchiveArgs),
+
+    /// Check that the platform requirements of the locked packages are satisfied
+    #[command(name = "check-platform-reqs")]
+    CheckPlatformReqs(CheckPlatformReqsArgs),
+
     /// Install project dependencies from composer.lock (alias for top-level install)
     #[command(alias = "i")]
     Install(InstallArgs),
@@ -126,7 +160,9 @@ pub enum PmCommands {
 pub async fn execute(command: PmCommands) -> Result<i32> {
     match command {
         PmCommands::Bin(args) => bin::execute(args).await,
+        PmCommands::Bench(args) => bench::execute(args).await,
         PmCommands::Bump(args) => bump::execute(args).await,
+        PmCommands::Config(args) => config::execute(args).await,
         PmCommands::Exec(args) => exec::execute(args).await,
         PmCommands::DumpAutoload(args) => dump_autoload::execute(args).await,
         PmCommands::ClearCache(args) => clear_cache::execute(args).await,
@@ -140,7 +176,11 @@ pub async fn execute(command: PmCommands) -> Result<i32> {
         PmCommands::Fund(args) => fund::execute(args).await,
         PmCommands::Browse(args) => home::execute(args).await,
         PmCommands::Suggests(args) => suggests::execute(args).await,
+        PmCommands::Graph(args) => graph::execute(args).await,
         PmCommands::Reinstall(args) => reinstall::execute(args).await,
+        PmCommands::Prune(args) => prune::execute(args).await,
+        PmCommands::Archive(args) => archive::execute(args).await,
+        PmCommands::CheckPlatformReqs(args) => check_platform_reqs::execute(args).await,
         PmCommands::Install(args) => crate::install::execute(args).await,
         PmCommands::Update(args) => crate::update::execute(args).await,
         PmCommands::Add(args) => crate::add::execute(args).await,
@@ -148,3 +188,77 @@ pub async fn execute(command: PmCommands) -> Result<i32> {
         PmCommands::CreateProject(args) => crate::create_project::execute(args).await,
     }
 }
+
+/// Register the commands contributed by ported plugins as `pm` subcommands
+///
+/// Unknown names are still rejected by clap, with its usual suggestions.
+pub fn with_plugin_commands(cmd: clap::Command) -> clap::Command {
+    let registry = pox_pm::plugin_commands();
+    if registry.is_empty() {
+        return cmd;
+    }
+
+    cmd.mut_subcommand("pm", |mut pm| {
+        for command in registry.commands() {
+            pm = pm.subcommand(
+                clap::Command::new(command.name())
+                    .about(command.description())
+                    .visible_aliases(command.aliases())
+                    .disable_help_flag(true)
+                    .arg(
+                        clap::Arg::new("args")
+                            .num_args(0..)
+                            .trailing_var_arg(true)
+                            .allow_hyphen_values(true),
+                    ),
+            );
+        }
+        pm
+    })
+}
+
+/// Run the plugin command selected on the command line, if any
+///
+/// Plugin commands are registered at runtime, so they have no `PmCommands`
+/// variant and must be dispatched before the matches are turned into `Args`.
+pub fn execute_plugin_command(matches: &ArgMatches) -> Result<Option<i32>> {
+    let Some(("pm", pm)) = matches.subcommand() else {
+        return Ok(None);
+    };
+    let Some((name, sub)) = pm.subcommand() else {
+        return Ok(None);
+    };
+    let Some(command) = pox_pm::plugin_commands().find(name) else {
+        return Ok(None);
+    };
+
+    IoArgs::from_arg_matches(pm)?.init();
+    let args: Vec<String> = sub.get_many::<String>("args").into_iter().flatten().cloned().collect();
+    let working_dir = std::env::current_dir()?;
+    command.execute(&args, &working_dir).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::error::ErrorKind;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_plugin_commands_are_subcommands() {
+        let cmd = with_plugin_commands(crate::Args::command());
+
+        let matches = cmd
+            .clone()
+            .try_get_matches_from(["pox", "pm", "symfony:recipes:install", "--force", "symfony/console"])
+            .unwrap();
+        let (_, pm) = matches.subcommand().unwrap();
+        let (name, sub) = pm.subcommand().unwrap();
+        assert_eq!(name, "recipes:install");
+        let args: Vec<&String> = sub.get_many::<String>("args").unwrap().collect();
+        assert_eq!(args, ["--force", "symfony/console"]);
+
+        let err = cmd.try_get_matches_from(["pox", "pm", "recipe"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidSubcommand);
+    }
+}
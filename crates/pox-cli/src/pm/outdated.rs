@@ -71,19 +71,18 @@ pub async fn execute(args: OutdatedArgs) -> Result<i32> {
         latest: true,
         outdated: !args.all,
         direct: args.direct,
+        major_only: args.major_only,
+        minor_only: args.minor_only,
+        patch_only: args.patch_only,
+        ignore: args.ignore,
+        strict: args.strict,
+        why: false,
         format: args.format,
         no_dev: args.no_dev,
         working_dir: args.working_dir,
     };
 
-    let result = show::execute(show_args).await?;
-
-    if args.strict && result == 0 {
-        // TODO: Return non-zero if there were outdated packages
-        // For now, just return the result from show
-    }
-
-    Ok(result)
+    show::execute(show_args).await
 }
 
 #[cfg(test)]
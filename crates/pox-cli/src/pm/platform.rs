@@ -3,7 +3,10 @@
 //! This module detects the installed PHP version and extensions
 //! and creates virtual packages that can be used by the dependency solver.
 
+use pox_pm::repository::library_package;
 use pox_pm::Package;
+use std::collections::HashMap;
+use std::path::Path;
 
 /// Information about the PHP platform
 #[derive(Debug, Clone)]
@@ -15,24 +18,39 @@ pub struct PlatformInfo {
     pub php_version_id: i32,
     /// List of loaded extensions (lowercase)
     pub extensions: Vec<String>,
+    /// Versions of extensions that differ from the PHP version, e.g. PECL extensions of the project
+    pub extension_versions: HashMap<String, String>,
 }
 
 impl PlatformInfo {
-    /// Detect the current PHP platform using the embedded PHP runtime
-    pub fn detect() -> Self {
+    /// Detect the PHP platform of the embedded PHP runtime for the project in
+    /// `project_dir`, including the enabled extensions of the project
+    pub fn detect_for(project_dir: &Path) -> Self {
         let version = pox_embed::Php::version();
 
         // Get loaded extensions
-        let extensions = pox_embed::Php::get_loaded_extensions()
+        let mut extensions: Vec<String> = pox_embed::Php::get_loaded_extensions()
             .unwrap_or_default()
             .into_iter()
             .map(|s| s.to_lowercase())
             .collect();
 
+        // Enabled project extensions are only loaded when pox runs PHP code
+        let mut extension_versions = HashMap::new();
+        for (name, ext_version) in crate::ext::enabled_extensions(project_dir) {
+            if !extensions.contains(&name) {
+                extensions.push(name.clone());
+            }
+            if let Some(ext_version) = ext_version {
+                extension_versions.insert(name, ext_version);
+            }
+        }
+
         Self {
             php_version: version.version.to_string(),
             php_version_id: version.version_id,
             extensions,
+            extension_versions,
         }
     }
 
@@ -89,8 +107,9 @@ impl PlatformInfo {
             }
 
             let ext_name = format!("ext-{}", ext);
-            // Extensions use the PHP version as their version
-            let ext_pkg = Package::new(&ext_name, &self.php_version);
+            // Bundled extensions use the PHP version as their version
+            let ext_version = self.extension_versions.get(ext).unwrap_or(&self.php_version);
+            let ext_pkg = Package::new(&ext_name, ext_version);
             packages.push(ext_pkg);
         }
 
@@ -113,60 +132,30 @@ impl PlatformInfo {
         packages
     }
 
-    /// Add lib-* packages based on loaded extensions
+    /// Add lib-* packages for the libraries PHP is linked against
     fn add_library_packages(&self, packages: &mut Vec<Package>) {
-        // ICU library (from intl extension)
-        if let Some(version) = pox_embed::Php::icu_version() {
-            if !version.is_empty() {
-                packages.push(Package::new("lib-icu", version));
-            }
-        }
-
-        // libxml
-        if let Some(version) = pox_embed::Php::libxml_version() {
-            if !version.is_empty() {
-                packages.push(Package::new("lib-libxml", version));
-            }
-        }
-
-        // OpenSSL - parse version from text like "OpenSSL 3.0.2 15 Mar 2022"
-        if let Some(version_text) = pox_embed::Php::openssl_version() {
-            if let Some(version) = parse_openssl_version(version_text) {
-                packages.push(Package::new("lib-openssl", &version));
-            }
-        }
-
-        // PCRE
-        if let Some(version) = pox_embed::Php::pcre_version() {
-            if !version.is_empty() {
-                packages.push(Package::new("lib-pcre", version));
-            }
-        }
-
-        // zlib
-        if let Some(version) = pox_embed::Php::zlib_version() {
-            if !version.is_empty() {
-                packages.push(Package::new("lib-zlib", version));
-            }
-        }
-
-        // curl
-        if let Some(version) = pox_embed::Php::curl_version() {
-            if !version.is_empty() {
-                packages.push(Package::new("lib-curl", version));
+        for (name, version_text) in Self::library_versions() {
+            if let Some(pkg) = library_package(name, version_text) {
+                packages.push(pkg);
             }
         }
     }
-}
 
-/// Parse OpenSSL version from version text
-fn parse_openssl_version(version_text: &str) -> Option<String> {
-    // Format: "OpenSSL 3.0.2 15 Mar 2022" or "LibreSSL 3.3.6"
-    let parts: Vec<&str> = version_text.split_whitespace().collect();
-    if parts.len() >= 2 {
-        Some(parts[1].to_string())
-    } else {
-        None
+    /// Version texts of the libraries PHP is linked against, by `lib-*` name
+    fn library_versions() -> Vec<(&'static str, &'static str)> {
+        let versions = [
+            ("curl", pox_embed::Php::curl_version()),
+            ("icu", pox_embed::Php::icu_version()),
+            ("libxml", pox_embed::Php::libxml_version()),
+            ("openssl", pox_embed::Php::openssl_version()),
+            ("pcre", pox_embed::Php::pcre_version()),
+            ("zlib", pox_embed::Php::zlib_version()),
+        ];
+
+        versions
+            .into_iter()
+            .filter_map(|(name, version)| version.filter(|v| !v.is_empty()).map(|v| (name, v)))
+            .collect()
     }
 }
 
@@ -176,16 +165,34 @@ mod tests {
 
     #[test]
     fn test_platform_detect() {
-        let platform = PlatformInfo::detect();
+        let platform = PlatformInfo::detect_for(Path::new("."));
         assert!(!platform.php_version.is_empty());
         assert!(platform.php_version_id > 0);
         // Core is always loaded
         assert!(platform.has_extension("core"));
     }
 
+    #[test]
+    fn test_platform_detect_includes_project_extensions() {
+        let project = tempfile::tempdir().unwrap();
+        let built = project.path().join("pecl_test.so");
+        std::fs::write(&built, b"\x7fELF").unwrap();
+        let mut extensions = crate::ext::ProjectExtensions::load(project.path()).unwrap();
+        extensions.add("pecl_test", &built, Some("1.2.3".to_string()), false).unwrap();
+        extensions.save().unwrap();
+
+        let platform = PlatformInfo::detect_for(project.path());
+        assert!(platform.has_extension("pecl_test"));
+        let packages = platform.to_packages();
+        let ext = packages.iter().find(|p| p.name == "ext-pecl_test").unwrap();
+        assert_eq!(ext.pretty_version.as_deref(), Some("1.2.3"));
+
+        assert!(!PlatformInfo::detect_for(Path::new(".")).has_extension("pecl_test"));
+    }
+
     #[test]
     fn test_to_packages() {
-        let platform = PlatformInfo::detect();
+        let platform = PlatformInfo::detect_for(Path::new("."));
         let packages = platform.to_packages();
 
         // Should have at least php package
@@ -0,0 +1,240 @@
+//! Prune command - remove packages no longer required by the project.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use console::style;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use pox_pm::{
+    ComposerBuilder,
+    Repository,
+    compute_content_hash,
+    config::Config,
+    find_orphans,
+    installer::Installer,
+    json::{ComposerJson, ComposerLock},
+    package::Package,
+    repository::InstalledRepository,
+};
+use pox_pm::config::{composer_file_name, lock_file_name};
+
+use crate::pm::platform::PlatformInfo;
+
+#[derive(Args, Debug)]
+pub struct PruneArgs {
+    /// Only list the packages that would be removed
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Skip autoloader generation
+    #[arg(long)]
+    pub no_autoloader: bool,
+
+    /// Optimize autoloader (convert PSR-4/PSR-0 to classmap)
+    #[arg(short = 'o', long)]
+    pub optimize_autoloader: bool,
+
+    /// Working directory
+    #[arg(short = 'd', long, default_value = ".")]
+    pub working_dir: PathBuf,
+}
+
+/// Locked and installed packages, installed packages missing from the lock file included
+fn known_packages(lock: &ComposerLock, installed: &[Arc<Package>]) -> Vec<Arc<Package>> {
+    let mut packages: Vec<Arc<Package>> = lock.packages.iter()
+        .chain(lock.packages_dev.iter())
+        .map(|p| Arc::new(Package::from(p)))
+        .collect();
+
+    let mut seen: HashSet<String> = packages.iter().map(|p| p.name.to_lowercase()).collect();
+    let mut installed: Vec<&Arc<Package>> = installed.iter().collect();
+    installed.sort_by(|a, b| a.name.cmp(&b.name));
+    for package in installed {
+        if seen.insert(package.name.to_lowercase()) {
+            packages.push(package.clone());
+        }
+    }
+
+    packages
+}
+
+/// Drop the pruned packages from both package lists of the lock file
+fn prune_lock(lock: &mut ComposerLock, orphans: &[Arc<Package>]) -> bool {
+    let names: HashSet<String> = orphans.iter().map(|p| p.name.to_lowercase()).collect();
+    let count = lock.packages.len() + lock.packages_dev.len();
+    lock.packages.retain(|p| !names.contains(&p.name.to_lowercase()));
+    lock.packages_dev.retain(|p| !names.contains(&p.name.to_lowercase()));
+    count != lock.packages.len() + lock.packages_dev.len()
+}
+
+pub async fn execute(args: PruneArgs) -> Result<i32> {
+    let working_dir = args.working_dir.canonicalize()
+        .context("Failed to resolve working directory")?;
+
+    let json_path = working_dir.join(composer_file_name());
+    let json_content = if json_path.exists() {
+        std::fs::read_to_string(&json_path)?
+    } else {
+        anyhow::bail!("No composer.json found in the current directory");
+    };
+    let composer_json: ComposerJson = serde_json::from_str(&json_content)?;
+
+    let lock_path = working_dir.join(lock_file_name());
+    let mut lock: ComposerLock = if lock_path.exists() {
+        ComposerLock::from_file(&lock_path).context("Failed to read composer.lock")?
+    } else {
+        anyhow::bail!("No composer.lock found. Run 'install' or 'update' first.");
+    };
+
+    let config = Config::build(Some(&working_dir), true)?;
+    let vendor_dir = working_dir.join(&config.vendor_dir);
+
+    let installed_repo = InstalledRepository::new(vendor_dir);
+    installed_repo.load().await.ok();
+    let dev_mode = installed_repo.is_dev_mode().await;
+
+    // Packages required for development are never orphans, even when installed without them
+    let root_requires = composer_json.require.keys().chain(composer_json.require_dev.keys());
+    let orphans = find_orphans(root_requires, &known_packages(&lock, &installed_repo.get_packages().await));
+
+    if orphans.is_empty() {
+        println!("{} No orphaned packages found, nothing to prune.", style("Info:").cyan());
+        return Ok(0);
+    }
+
+    println!(
+        "{} {} {} orphaned package(s)",
+        style("Composer").green().bold(),
+        if args.dry_run { "Would remove" } else { "Removing" },
+        orphans.len()
+    );
+    for package in &orphans {
+        println!(
+            "  {} {} ({})",
+            style("-").red(),
+            style(&package.name).white().bold(),
+            style(package.pretty_version.as_deref().unwrap_or(&package.version)).yellow()
+        );
+    }
+
+    if args.dry_run {
+        return Ok(0);
+    }
+
+    let lock_changed = prune_lock(&mut lock, &orphans);
+    if lock_changed {
+        lock.content_hash = compute_content_hash(&json_content);
+    }
+    let platform = PlatformInfo::detect_for(&working_dir);
+    let composer = ComposerBuilder::new(working_dir.clone())
+        .with_io(crate::io::io())
+        .with_profiler(crate::io::profiler())
+        .with_config(config)
+        .with_composer_json(composer_json)
+        .with_composer_lock(Some(lock.clone()))
+        .with_platform_packages(platform.to_packages())
+        .build()?;
+
+    let packages: Vec<Package> = orphans.iter().map(|p| p.as_ref().clone()).collect();
+    composer.installation_manager.remove_packages_with_events(&packages, &composer).await
+        .context("Failed to remove orphaned packages")?;
+
+    if lock_changed && composer.config.lock {
+        let mut content = lock.to_json().context("Failed to serialize composer.lock")?;
+        content.push('\n');
+        std::fs::write(&lock_path, content).context("Failed to write composer.lock")?;
+    }
+
+    let installer = Installer::new(composer);
+    installer.write_installed_repository(&lock, dev_mode).await?;
+
+    if !args.no_autoloader {
+        installer.dump_autoload(args.optimize_autoloader, false, false, !dev_mode)?;
+    }
+
+    println!(
+        "{} {} package(s) pruned",
+        style("Success:").green().bold(),
+        orphans.len()
+    );
+
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pox_pm::json::LockedPackage;
+
+    fn locked(name: &str) -> LockedPackage {
+        LockedPackage { name: name.to_string(), version: "1.0.0".to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_known_packages_adds_unlocked_installed_packages() {
+        let mut lock = ComposerLock::default();
+        lock.packages.push(locked("acme/app-kit"));
+        lock.packages_dev.push(locked("acme/test-kit"));
+        let installed = vec![
+            Arc::new(Package::new("ACME/App-Kit", "1.0.0.0")),
+            Arc::new(Package::new("acme/stray", "2.0.0.0")),
+        ];
+
+        let names: Vec<String> = known_packages(&lock, &installed).iter().map(|p| p.name.clone()).collect();
+        assert_eq!(names, vec!["acme/app-kit", "acme/test-kit", "acme/stray"]);
+    }
+
+    #[test]
+    fn test_prune_lock() {
+        let mut lock = ComposerLock::default();
+        lock.packages.push(locked("acme/app-kit"));
+        lock.packages.push(locked("acme/orphan"));
+        lock.packages_dev.push(locked("acme/dev-orphan"));
+
+        let orphans = vec![Arc::new(Package::new("Acme/Orphan", "1.0.0.0")), Arc::new(Package::new("acme/dev-orphan", "1.0.0.0"))];
+        assert!(prune_lock(&mut lock, &orphans));
+        assert_eq!(lock.packages.len(), 1);
+        assert!(lock.packages_dev.is_empty());
+        assert!(!prune_lock(&mut lock, &orphans));
+    }
+
+    #[tokio::test]
+    async fn test_prune_removes_orphan() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let project = dir.path();
+        let json_content = r#"{"name": "acme/app", "require": {"acme/kept": "^1.0"}, "config": {"cache-dir": ".cache"}}"#;
+        std::fs::write(project.join("composer.json"), json_content).unwrap();
+
+        let mut lock = ComposerLock { content_hash: "stale".to_string(), ..Default::default() };
+        lock.packages.push(locked("acme/kept"));
+        lock.packages.push(locked("acme/orphan"));
+        std::fs::write(project.join("composer.lock"), lock.to_json().unwrap()).unwrap();
+        for name in ["acme/kept", "acme/orphan"] {
+            std::fs::create_dir_all(project.join("vendor").join(name)).unwrap();
+            std::fs::write(project.join("vendor").join(name).join("composer.json"), "{}").unwrap();
+        }
+
+        let args = PruneArgs {
+            dry_run: false,
+            no_autoloader: false,
+            optimize_autoloader: false,
+            working_dir: project.to_path_buf(),
+        };
+        assert_eq!(execute(args).await.unwrap(), 0);
+
+        assert!(project.join("vendor/acme/kept").is_dir());
+        assert!(!project.join("vendor/acme/orphan").exists());
+        assert!(project.join("vendor/autoload.php").is_file());
+
+        let lock = ComposerLock::from_file(project.join("composer.lock")).unwrap();
+        let names: Vec<&str> = lock.packages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["acme/kept"]);
+        assert_eq!(lock.content_hash, compute_content_hash(json_content));
+
+        let installed = std::fs::read_to_string(project.join("vendor/composer/installed.json")).unwrap();
+        assert!(installed.contains("acme/kept"));
+        assert!(!installed.contains("acme/orphan"));
+    }
+}
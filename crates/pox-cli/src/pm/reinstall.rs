@@ -9,15 +9,17 @@ use std::path::PathBuf;
 use pox_pm::{
     ComposerBuilder,
     config::Config,
+    installer::{FileChange, FileHashes},
     json::{ComposerJson, ComposerLock},
     package::Package,
 };
+use pox_pm::config::{composer_file_name, lock_file_name};
 
 use crate::pm::platform::PlatformInfo;
 
 #[derive(Args, Debug)]
 pub struct ReinstallArgs {
-    /// Package names to reinstall (supports wildcards like "acme/*")
+    /// Package names to reinstall (supports wildcards like "acme/*"), defaults to locally modified packages
     #[arg(value_name = "PACKAGES")]
     pub packages: Vec<String>,
 
@@ -33,6 +35,10 @@ pub struct ReinstallArgs {
     #[arg(long)]
     pub prefer_dist: bool,
 
+    /// Skip checksum and signature verification of downloaded archives
+    #[arg(long)]
+    pub no_verify: bool,
+
     /// Skip autoloader generation
     #[arg(long)]
     pub no_autoloader: bool,
@@ -69,18 +75,6 @@ pub struct ReinstallArgs {
     #[arg(short = 'd', long, default_value = ".")]
     pub working_dir: PathBuf,
 
-    /// Do not ask any interactive question
-    #[arg(short = 'n', long)]
-    pub no_interaction: bool,
-
-    /// Do not output any message
-    #[arg(short = 'q', long)]
-    pub quiet: bool,
-
-    /// Increase verbosity (-v, -vv, -vvv)
-    #[arg(short = 'v', long, action = clap::ArgAction::Count)]
-    pub verbose: u8,
-
     /// Disable plugins
     #[arg(long)]
     pub no_plugins: bool,
@@ -93,11 +87,53 @@ fn package_name_to_regexp(pattern: &str) -> Result<Regex> {
     Regex::new(&full_pattern).context("Invalid package pattern")
 }
 
+/// Locked packages whose installed files differ from the hashes recorded at install time
+///
+/// `install_path` resolves where a package is installed, honoring installer-paths.
+fn find_modified_packages(
+    lock: &ComposerLock,
+    hashes: &FileHashes,
+    install_path: impl Fn(&Package) -> Option<PathBuf>,
+) -> Vec<(Package, Vec<FileChange>)> {
+    let mut modified = Vec::new();
+
+    for locked_pkg in lock.packages.iter().chain(lock.packages_dev.iter()) {
+        let pkg = Package::from(locked_pkg);
+        let Some(path) = install_path(&pkg) else {
+            continue;
+        };
+        match hashes.changed_files(&pkg, &path) {
+            Ok(Some(changes)) if !changes.is_empty() => modified.push((pkg, changes)),
+            _ => {}
+        }
+    }
+
+    modified
+}
+
+fn print_changes(pkg: &Package, changes: &[FileChange]) {
+    println!(
+        "  {} {} ({} changed file{})",
+        style("~").yellow(),
+        style(&pkg.name).white().bold(),
+        changes.len(),
+        if changes.len() == 1 { "" } else { "s" }
+    );
+    for change in changes {
+        let marker = match change {
+            FileChange::Modified(_) => style("M").yellow(),
+            FileChange::Added(_) => style("A").green(),
+            FileChange::Removed(_) => style("D").red(),
+        };
+        println!("      {} {}", marker, change.path());
+    }
+}
+
 pub async fn execute(args: ReinstallArgs) -> Result<i32> {
     let working_dir = args.working_dir.canonicalize()
         .context("Failed to resolve working directory")?;
 
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
     let composer_json: ComposerJson = if json_path.exists() {
         let content = std::fs::read_to_string(&json_path)?;
         serde_json::from_str(&content)?
@@ -105,7 +141,7 @@ pub async fn execute(args: ReinstallArgs) -> Result<i32> {
         anyhow::bail!("No composer.json found in the current directory");
     };
 
-    let lock_path = working_dir.join("composer.lock");
+    let lock_path = working_dir.join(lock_file_name());
     let lock: ComposerLock = if lock_path.exists() {
         let lock_content = std::fs::read_to_string(&lock_path)
             .context("Failed to read composer.lock")?;
@@ -119,14 +155,48 @@ pub async fn execute(args: ReinstallArgs) -> Result<i32> {
         anyhow::bail!("You cannot specify package names and filter by type at the same time.");
     }
 
-    if args.package_types.is_empty() && args.packages.is_empty() {
-        anyhow::bail!("You must pass one or more package names to be reinstalled, or use --type to reinstall by package type.");
+    let config = Config::build(Some(&working_dir), true)?;
+    let platform = PlatformInfo::detect_for(&working_dir);
+
+    let mut builder = ComposerBuilder::new(working_dir.clone())
+        .with_io(crate::io::io())
+        .with_profiler(crate::io::profiler())
+        .with_config(config)
+        .with_composer_json(composer_json)
+        .with_composer_lock(Some(lock.clone()))
+        .with_platform_packages(platform.to_packages())
+        .no_verify(args.no_verify);
+
+    if args.prefer_source {
+        builder = builder.prefer_source(true);
+    } else if args.prefer_dist {
+        builder = builder.prefer_dist(true);
     }
 
+    let composer = builder.build()?;
+    let manager = &composer.installation_manager;
+    let vendor_dir = manager.config().vendor_dir.clone();
+
     let mut packages_to_reinstall: Vec<Package> = Vec::new();
     let mut package_names_to_reinstall: Vec<String> = Vec::new();
 
-    if !args.package_types.is_empty() {
+    if args.package_types.is_empty() && args.packages.is_empty() {
+        let hashes = FileHashes::load(&vendor_dir);
+        if hashes.is_empty() {
+            anyhow::bail!("No file hashes recorded in {}, enable them with `pox pm config file-hashes true` and reinstall, pass one or more package names to be reinstalled, or use --type to reinstall by package type.", vendor_dir.display());
+        }
+
+        for (pkg, changes) in find_modified_packages(&lock, &hashes, |pkg| manager.get_install_path(pkg)) {
+            print_changes(&pkg, &changes);
+            package_names_to_reinstall.push(pkg.name.clone());
+            packages_to_reinstall.push(pkg);
+        }
+
+        if packages_to_reinstall.is_empty() {
+            println!("{} No locally modified packages found, nothing to reinstall.", style("Info:").cyan());
+            return Ok(0);
+        }
+    } else if !args.package_types.is_empty() {
         for locked_pkg in lock.packages.iter().chain(lock.packages_dev.iter()) {
             if args.package_types.contains(&locked_pkg.package_type) {
                 packages_to_reinstall.push(Package::from(locked_pkg));
@@ -169,28 +239,17 @@ pub async fn execute(args: ReinstallArgs) -> Result<i32> {
         packages_to_reinstall.len()
     );
 
-    let config = Config::build(Some(&working_dir), true)?;
-    let platform = PlatformInfo::detect();
-
-    let mut builder = ComposerBuilder::new(working_dir.clone())
-        .with_config(config)
-        .with_composer_json(composer_json)
-        .with_composer_lock(Some(lock.clone()))
-        .with_platform_packages(platform.to_packages());
-
     if args.prefer_source {
-        builder = builder.prefer_source(true);
-    } else if args.prefer_dist {
-        builder = builder.prefer_dist(true);
+        for pkg in packages_to_reinstall.iter().filter(|p| p.source.is_none()) {
+            eprintln!("{} {} has no source, it is installed from dist.", style("Warning:").yellow(), pkg.name);
+        }
     }
 
-    let composer = builder.build()?;
-    let manager = &composer.installation_manager;
-    let vendor_dir = manager.config().vendor_dir.clone();
-
     println!("{} Removing packages...", style("Info:").cyan());
     for pkg in &packages_to_reinstall {
-        let install_path = vendor_dir.join(&pkg.name);
+        let Some(install_path) = manager.get_install_path(pkg) else {
+            continue;
+        };
         if install_path.exists() {
             tokio::fs::remove_dir_all(&install_path).await
                 .with_context(|| format!("Failed to remove {}", pkg.name))?;
@@ -204,7 +263,7 @@ pub async fn execute(args: ReinstallArgs) -> Result<i32> {
     }
 
     println!("{} Installing packages...", style("Info:").cyan());
-    let result = manager.install_packages(&packages_to_reinstall).await
+    let result = manager.install_packages_with_events(&packages_to_reinstall, &composer).await
         .context("Failed to reinstall packages")?;
 
     for pkg in &result.installed {
@@ -283,4 +342,33 @@ mod tests {
         assert!(re.is_match("vendor/pkg.name"));
         assert!(!re.is_match("vendor/pkgXname")); // . should be literal, not any char
     }
+
+    #[test]
+    fn test_find_modified_packages() {
+        use pox_pm::installer::hash_directory;
+        use pox_pm::json::LockedPackage;
+
+        let vendor = tempfile::TempDir::new().unwrap();
+        let mut lock = ComposerLock::default();
+        let mut hashes = FileHashes::default();
+        for name in ["acme/clean", "acme/edited"] {
+            let install_path = vendor.path().join(name);
+            std::fs::create_dir_all(&install_path).unwrap();
+            std::fs::write(install_path.join("index.php"), "<?php").unwrap();
+
+            let locked = LockedPackage { name: name.to_string(), version: "1.0.0".to_string(), ..Default::default() };
+            hashes.insert(&Package::from(&locked), hash_directory(&install_path).unwrap());
+            lock.packages.push(locked);
+        }
+        lock.packages_dev.push(LockedPackage { name: "acme/untracked".to_string(), version: "1.0.0".to_string(), ..Default::default() });
+
+        let install_path = |pkg: &Package| Some(vendor.path().join(&pkg.name));
+        assert!(find_modified_packages(&lock, &hashes, install_path).is_empty());
+
+        std::fs::write(vendor.path().join("acme/edited/index.php"), "<?php // patched").unwrap();
+        let modified = find_modified_packages(&lock, &hashes, install_path);
+        assert_eq!(modified.len(), 1);
+        assert_eq!(modified[0].0.name, "acme/edited");
+        assert_eq!(modified[0].1, vec![FileChange::Modified("index.php".to_string())]);
+    }
 }
@@ -3,11 +3,14 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use console::style;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use pox_pm::json::ComposerJson;
 
 use pox_pm::scripts;
+use pox_pm::config::composer_file_name;
+
+use crate::watch;
 
 #[derive(Args, Debug)]
 pub struct RunArgs {
@@ -19,6 +22,10 @@ pub struct RunArgs {
     #[arg(short = 'l', long)]
     pub list: bool,
 
+    /// Re-run the script when files matching this glob change (repeatable)
+    #[arg(long, value_name = "GLOB", action = clap::ArgAction::Append)]
+    pub watch: Vec<String>,
+
     /// Working directory
     #[arg(short = 'd', long, default_value = ".")]
     pub working_dir: PathBuf,
@@ -33,7 +40,7 @@ pub async fn execute(args: RunArgs) -> Result<i32> {
         .context("Failed to resolve working directory")?;
 
     // Load composer.json
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
     if !json_path.exists() {
         eprintln!("{} No composer.json found in {}",
             style("Error:").red().bold(),
@@ -52,6 +59,41 @@ pub async fn execute(args: RunArgs) -> Result<i32> {
 
     let script_name = args.script.as_ref().unwrap();
 
+    if !args.watch.is_empty() {
+        return watch_script(script_name, &composer_json, &working_dir, &args);
+    }
+
     // Run the script
     scripts::run_script(script_name, &composer_json, &working_dir, &args.args)
 }
+
+/// Run the script, then again whenever a watched file changes, until interrupted
+fn watch_script(script_name: &str, composer_json: &ComposerJson, working_dir: &Path, args: &RunArgs) -> Result<i32> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let _watcher = watch::watch(working_dir, &args.watch, move |changed| {
+        let _ = tx.send(changed);
+    })?;
+
+    loop {
+        scripts::run_script(script_name, composer_json, working_dir, &args.args)?;
+
+        println!();
+        println!("{} Watching for file changes: {}",
+            style("Info:").cyan(),
+            args.watch.join(", ")
+        );
+
+        let Ok(mut changed) = rx.recv() else {
+            return Ok(0);
+        };
+        // Changes made while the script was running trigger a single re-run
+        while let Ok(more) = rx.try_recv() {
+            changed.extend(more);
+        }
+
+        for path in &changed {
+            let path = path.strip_prefix(working_dir).unwrap_or(path);
+            println!("{} {}", style("File changed:").yellow(), path.display());
+        }
+    }
+}
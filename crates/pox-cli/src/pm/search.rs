@@ -9,6 +9,7 @@ use pox_pm::{
     json::ComposerJson,
     repository::{ComposerRepository, RepositoryManager, SearchMode},
 };
+use pox_pm::config::composer_file_name;
 
 #[derive(Args, Debug)]
 pub struct SearchArgs {
@@ -96,7 +97,7 @@ pub async fn execute(args: SearchArgs) -> Result<i32> {
 
     let mut repo_manager = RepositoryManager::new();
 
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
     if json_path.exists() {
         let content = std::fs::read_to_string(&json_path)?;
         let composer_json: ComposerJson = serde_json::from_str(&content)?;
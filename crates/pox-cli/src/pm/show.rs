@@ -3,18 +3,23 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use console::style;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::collections::{HashMap, HashSet};
 
 use pox_pm::{
+    ComposerBuilder,
     Repository,
     config::Config,
     json::{ComposerJson, ComposerLock},
     is_platform_package,
-    repository::ComposerRepository,
+    package::Stability,
+    repository::{apply_platform_overrides, install_reasons, ComposerRepository, InstallReason, InstalledRepository},
 };
-use pox_semver::VersionParser;
+use pox_pm::config::{composer_file_name, lock_file_name};
+use pox_semver::{Comparator, VersionParser};
+
+use super::platform::PlatformInfo;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum UpdateType {
@@ -62,6 +67,107 @@ struct PackageWithLatest {
     package: Arc<pox_pm::Package>,
     latest_version: Option<String>,
     update_type: UpdateType,
+    /// Whether the latest version is allowed by `^current`
+    semver_safe: bool,
+    /// Why the package is installed, with --why
+    install_reason: Option<InstallReason>,
+}
+
+/// Which updates `--latest` looks for
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UpdateFilter {
+    /// The latest version in any major
+    All,
+    /// Only packages with an update outside of `^current`
+    MajorOnly,
+    /// The latest version allowed by `^current`
+    MinorOnly,
+    /// The latest version allowed by `~current`, keeping the minor version
+    PatchOnly,
+}
+
+impl UpdateFilter {
+    fn from_args(args: &ShowArgs) -> Self {
+        if args.major_only {
+            UpdateFilter::MajorOnly
+        } else if args.minor_only {
+            UpdateFilter::MinorOnly
+        } else if args.patch_only {
+            UpdateFilter::PatchOnly
+        } else {
+            UpdateFilter::All
+        }
+    }
+}
+
+/// How the latest version of an installed package is chosen
+struct LatestOptions {
+    update_filter: UpdateFilter,
+    minimum_stability: Stability,
+    prefer_stable: bool,
+}
+
+impl LatestOptions {
+    fn new(args: &ShowArgs, composer_json: &ComposerJson) -> Self {
+        Self {
+            update_filter: UpdateFilter::from_args(args),
+            minimum_stability: composer_json
+                .minimum_stability
+                .as_deref()
+                .unwrap_or("stable")
+                .parse()
+                .unwrap_or(Stability::Stable),
+            prefer_stable: composer_json.prefer_stable.unwrap_or(false),
+        }
+    }
+
+    /// The installed package with its latest version among `candidates`
+    fn resolve(
+        &self,
+        p: Arc<pox_pm::Package>,
+        candidates: Option<&Vec<Arc<pox_pm::Package>>>,
+        install_reason: Option<InstallReason>,
+    ) -> PackageWithLatest {
+        let Some(candidates) = candidates else {
+            return PackageWithLatest { package: p, latest_version: None, update_type: UpdateType::UpToDate, semver_safe: true, install_reason };
+        };
+
+        // Like Composer, prefer-stable keeps the latest version as stable as the installed one
+        let stability = if self.prefer_stable { p.stability() } else { self.minimum_stability };
+        let latest = match self.update_filter {
+            UpdateFilter::All | UpdateFilter::MajorOnly => find_latest_version(candidates, None, stability),
+            UpdateFilter::MinorOnly => find_latest_version(candidates, Some(&format!("^{}", p.version)), stability),
+            UpdateFilter::PatchOnly => patch_constraint(&p.version)
+                .and_then(|constraint| find_latest_version(candidates, Some(&constraint), stability)),
+        };
+
+        let Some(latest) = latest else {
+            return PackageWithLatest { package: p, latest_version: None, update_type: UpdateType::UpToDate, semver_safe: true, install_reason };
+        };
+
+        let current = p.pretty_version.as_deref().unwrap_or(&p.version);
+        let latest_pretty = latest.pretty_version.clone().unwrap_or_else(|| latest.version.clone());
+        // An installed version newer than the latest one, e.g. a pre-release, is up to date
+        let update_type = if Comparator::ordering(&latest.version, &p.version) == std::cmp::Ordering::Greater {
+            determine_update_type(current, &latest_pretty)
+        } else {
+            UpdateType::UpToDate
+        };
+
+        PackageWithLatest {
+            semver_safe: is_semver_safe(&p.version, &latest.version),
+            package: p,
+            latest_version: Some(latest_pretty),
+            update_type,
+            install_reason,
+        }
+    }
+
+    /// Whether the package is listed, --major-only only lists updates outside of `^current`
+    fn is_listed(&self, package: &PackageWithLatest) -> bool {
+        self.update_filter != UpdateFilter::MajorOnly
+            || (package.update_type != UpdateType::UpToDate && !package.semver_safe)
+    }
 }
 
 #[derive(Args, Debug)]
@@ -72,7 +178,7 @@ pub struct ShowArgs {
     /// Version or version constraint to inspect
     pub version: Option<String>,
 
-    /// List all packages
+    /// List all packages, or all available versions of the given package
     #[arg(long)]
     pub all: bool,
 
@@ -116,6 +222,30 @@ pub struct ShowArgs {
     #[arg(short = 'D', long)]
     pub direct: bool,
 
+    /// Show only packages that have major SemVer-compatible updates (with --latest)
+    #[arg(short = 'M', long)]
+    pub major_only: bool,
+
+    /// Show only packages that have minor SemVer-compatible updates (with --latest)
+    #[arg(short = 'm', long)]
+    pub minor_only: bool,
+
+    /// Show only packages that have patch SemVer-compatible updates (with --latest)
+    #[arg(long)]
+    pub patch_only: bool,
+
+    /// Ignore specified package(s), can contain wildcards (*)
+    #[arg(long)]
+    pub ignore: Vec<String>,
+
+    /// Return a non-zero exit code when there are outdated packages
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Show why each package is installed
+    #[arg(long)]
+    pub why: bool,
+
     /// Output format: text or json
     #[arg(short = 'f', long, default_value = "text")]
     pub format: String,
@@ -160,11 +290,17 @@ pub async fn execute(args: ShowArgs) -> Result<i32> {
         return Ok(1);
     }
 
-    if args.outdated {
-        // --outdated implies --latest
+    if args.why && (args.tree || args.all || args.available || args.platform) {
+        eprintln!("Error: --why is not usable with --tree, --all, --platform, or --available");
+        return Ok(1);
+    }
+
+    if [args.major_only, args.minor_only, args.patch_only].iter().filter(|&&set| set).count() > 1 {
+        eprintln!("Error: Only one of --major-only, --minor-only or --patch-only can be used at once");
+        return Ok(1);
     }
 
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
     let composer_json: ComposerJson = if json_path.exists() {
         let content = std::fs::read_to_string(&json_path)?;
         serde_json::from_str(&content)?
@@ -173,7 +309,7 @@ pub async fn execute(args: ShowArgs) -> Result<i32> {
     };
 
     let lock: Option<ComposerLock> = {
-        let lock_path = working_dir.join("composer.lock");
+        let lock_path = working_dir.join(lock_file_name());
         if lock_path.exists() {
             let content = std::fs::read_to_string(&lock_path).ok();
             content.and_then(|c| serde_json::from_str(&c).ok())
@@ -185,9 +321,14 @@ pub async fn execute(args: ShowArgs) -> Result<i32> {
     let config = Config::build(Some(&working_dir), true)?;
 
     let vendor_dir = working_dir.join(&config.vendor_dir);
-    let installed_repo = Arc::new(pox_pm::repository::InstalledRepository::new(vendor_dir.clone()));
+    let installed_repo = Arc::new(InstalledRepository::new(vendor_dir.clone()));
     installed_repo.load().await.ok();
     let installed_packages = installed_repo.get_packages().await;
+    let reasons = if args.why {
+        load_install_reasons(&installed_repo, &installed_packages, &composer_json).await
+    } else {
+        HashMap::new()
+    };
 
     if args.self_package {
         if args.name_only {
@@ -219,27 +360,87 @@ pub async fn execute(args: ShowArgs) -> Result<i32> {
 
     let show_latest = args.latest || args.outdated;
 
+    if args.platform {
+        let platform: Vec<Arc<pox_pm::Package>> =
+            apply_platform_overrides(&PlatformInfo::detect_for(&working_dir).to_packages(), &config.platform)
+                .into_iter()
+                .map(Arc::new)
+                .collect();
+        let filter = args.package.as_deref().map(str::to_lowercase);
+        list_packages_with_latest(&platform, filter.as_deref(), &composer_json, &reasons, &args, &config, false).await?;
+        return Ok(0);
+    }
+
+    let mut outdated = false;
     if let Some(package_name) = &args.package {
         if !package_name.contains('*') {
-            show_single_package(
+            let name_lower = package_name.to_lowercase();
+            let installed = installed_packages
+                .iter()
+                .find(|p| p.name.to_lowercase() == name_lower);
+
+            if args.all || args.available || installed.is_none() {
+                return show_remote_package(
+                    package_name,
+                    args.version.as_deref(),
+                    installed,
+                    &composer_json,
+                    &args,
+                    &config,
+                )
+                .await;
+            }
+
+            outdated = show_single_package(
                 &installed_packages,
                 package_name,
-                args.version.as_deref(),
+                &composer_json,
                 &args,
                 &vendor_dir,
-            )?;
+                &config,
+                show_latest,
+            )
+            .await?;
         } else {
-            list_packages_with_latest(&installed_packages, Some(package_name), &composer_json, &args, &config, show_latest).await?;
+            outdated = list_packages_with_latest(&installed_packages, Some(package_name), &composer_json, &reasons, &args, &config, show_latest).await?;
         }
     } else {
         if args.tree {
             show_tree_all(&installed_packages, &composer_json)?;
         } else {
-            list_packages_with_latest(&installed_packages, None, &composer_json, &args, &config, show_latest).await?;
+            outdated = list_packages_with_latest(&installed_packages, None, &composer_json, &reasons, &args, &config, show_latest).await?;
         }
     }
 
-    Ok(0)
+    Ok(if args.strict && outdated { 1 } else { 0 })
+}
+
+/// Install reasons determined from composer.json and the installed packages
+///
+/// `required-by` in installed.json goes stale as packages change, so only
+/// whether a package was explicitly requested is taken from the record.
+async fn load_install_reasons(
+    installed_repo: &InstalledRepository,
+    installed_packages: &[Arc<pox_pm::Package>],
+    composer_json: &ComposerJson,
+) -> HashMap<String, InstallReason> {
+    let packages: Vec<pox_pm::Package> = installed_packages.iter().map(|p| p.as_ref().clone()).collect();
+    let mut reasons = install_reasons(
+        composer_json.require.keys(),
+        composer_json.require_dev.keys(),
+        &packages,
+        &installed_repo.dev_package_names().await,
+    );
+    for package in installed_packages {
+        let Some(recorded) = installed_repo.install_reason(&package.name).await else {
+            continue;
+        };
+        let name = package.name.to_lowercase();
+        if recorded.is_direct() && !reasons.get(&name).is_some_and(InstallReason::is_direct) {
+            reasons.insert(name, recorded);
+        }
+    }
+    reasons
 }
 
 fn print_root_package_info(composer_json: &ComposerJson, format: &str) -> Result<()> {
@@ -283,13 +484,16 @@ fn print_root_package_info(composer_json: &ComposerJson, format: &str) -> Result
     Ok(())
 }
 
-fn show_single_package(
+/// Show an installed package, returns whether it is outdated
+async fn show_single_package(
     packages: &[Arc<pox_pm::Package>],
     name: &str,
-    _version: Option<&str>,
+    composer_json: &ComposerJson,
     args: &ShowArgs,
     vendor_dir: &PathBuf,
-) -> Result<()> {
+    config: &Config,
+    show_latest: bool,
+) -> Result<bool> {
     let name_lower = name.to_lowercase();
     let package = packages
         .iter()
@@ -299,7 +503,7 @@ fn show_single_package(
         Some(p) => p,
         None => {
             eprintln!("Error: Package '{}' not found", name);
-            return Ok(());
+            return Ok(false);
         }
     };
 
@@ -310,29 +514,211 @@ fn show_single_package(
         } else {
             println!("{} null", package.name);
         }
-        return Ok(());
+        return Ok(false);
     }
 
     if args.tree {
         show_tree_single(package, packages)?;
-        return Ok(());
+        return Ok(false);
+    }
+
+    // The same latest version `show --latest` lists for the package
+    let mut outdated = false;
+    let mut latest = None;
+    if show_latest && !is_ignored(&package.name, &args.ignore) {
+        let available_versions =
+            fetch_available_versions(std::slice::from_ref(package), composer_json, config, &args.working_dir).await?;
+        let options = LatestOptions::new(args, composer_json);
+        let resolved = options.resolve(package.clone(), available_versions.get(&package.name.to_lowercase()), None);
+        if options.is_listed(&resolved) {
+            outdated = resolved.update_type != UpdateType::UpToDate;
+            latest = resolved.latest_version;
+        }
     }
 
     if args.format == "json" {
-        print_package_json(package)?;
+        let mut json = package_json(package);
+        if let Some(latest) = &latest {
+            json["latest"] = serde_json::json!(latest);
+        }
+        println!("{}", serde_json::to_string_pretty(&json)?);
     } else {
-        print_package_info(package)?;
+        let version = package.pretty_version.as_deref().unwrap_or(&package.version);
+        print_package_info(package, &format!("* {}", version), latest.as_deref())?;
     }
 
-    Ok(())
+    Ok(outdated)
 }
 
-fn print_package_info(package: &pox_pm::Package) -> Result<()> {
+/// Show a package from the remote repository with all of its available versions.
+///
+/// Versions are colored by stability and the ones satisfying the root
+/// requirement are marked. Details are shown for the selected version: the
+/// version argument, the installed version, the best match of the root
+/// requirement or the latest stable release, in that order.
+async fn show_remote_package(
+    name: &str,
+    version: Option<&str>,
+    installed: Option<&Arc<pox_pm::Package>>,
+    composer_json: &ComposerJson,
+    args: &ShowArgs,
+    config: &Config,
+) -> Result<i32> {
+    let packagist = if let Some(cache_dir) = &config.cache_dir {
+        ComposerRepository::packagist_with_cache(cache_dir.join("repo"))
+    } else {
+        ComposerRepository::packagist()
+    };
+
+    let mut versions = packagist.find_packages(name).await;
+    if versions.is_empty() {
+        eprintln!("Error: Package '{}' not found", name);
+        return Ok(1);
+    }
+    sort_versions_descending(&mut versions);
+
+    let parser = VersionParser::new();
+    let name_lower = name.to_lowercase();
+    let root_constraint = composer_json
+        .require
+        .iter()
+        .chain(composer_json.require_dev.iter())
+        .find(|(n, _)| n.to_lowercase() == name_lower)
+        .map(|(_, c)| c.clone());
+    let root_matcher = root_constraint
+        .as_deref()
+        .and_then(|c| parser.parse_constraints_cached(c).ok());
+    let matches_root = |p: &pox_pm::Package| {
+        root_matcher
+            .as_ref()
+            .is_some_and(|m| m.matches_normalized(&p.version))
+    };
+
+    let selected = match version {
+        Some(requested) => {
+            let matcher = match parser.parse_constraints_cached(requested) {
+                Ok(matcher) => matcher,
+                Err(e) => {
+                    eprintln!("Error: Invalid version constraint '{}': {}", requested, e);
+                    return Ok(1);
+                }
+            };
+            match versions.iter().find(|p| matcher.matches_normalized(&p.version)) {
+                Some(p) => p.clone(),
+                None => {
+                    eprintln!("Error: Package '{}' has no version matching '{}'", name, requested);
+                    return Ok(1);
+                }
+            }
+        }
+        None => select_default_version(&versions, installed, &matches_root),
+    };
+
+    let latest = if args.latest || args.outdated {
+        find_latest_stable_version(&versions)
+    } else {
+        None
+    };
+
+    if args.format == "json" {
+        let mut json = package_json(&selected);
+        json["versions"] = serde_json::json!(versions
+            .iter()
+            .map(|p| p.pretty_version.as_deref().unwrap_or(&p.version))
+            .collect::<Vec<_>>());
+        if let Some(constraint) = &root_constraint {
+            json["constraint"] = serde_json::json!(constraint);
+            json["matching-versions"] = serde_json::json!(versions
+                .iter()
+                .filter(|p| matches_root(p))
+                .map(|p| p.pretty_version.as_deref().unwrap_or(&p.version))
+                .collect::<Vec<_>>());
+        }
+        if let Some(installed) = installed {
+            json["installed"] = serde_json::json!(installed
+                .pretty_version
+                .as_deref()
+                .unwrap_or(&installed.version));
+        }
+        if let Some(latest) = &latest {
+            json["latest"] = serde_json::json!(latest);
+        }
+        println!("{}", serde_json::to_string_pretty(&json)?);
+        return Ok(0);
+    }
+
+    let versions_line = versions
+        .iter()
+        .map(|p| {
+            let pretty = p.pretty_version.as_deref().unwrap_or(&p.version);
+            let mut styled = style_by_stability(pretty, Stability::from_version(&p.version));
+            if matches_root(p) {
+                styled = styled.bold().underlined();
+            }
+            if p.version == selected.version {
+                format!("* {}", styled)
+            } else {
+                styled.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    print_package_info(&selected, &versions_line, latest.as_deref())?;
+
+    if let Some(constraint) = &root_constraint {
+        eprintln!();
+        eprintln!(
+            "{} versions matching the root requirement {} are underlined",
+            style("Note:").green(),
+            style(constraint).cyan()
+        );
+    }
+
+    Ok(0)
+}
+
+/// Sort packages from the newest to the oldest version
+fn sort_versions_descending(packages: &mut [Arc<pox_pm::Package>]) {
+    packages.sort_by(|a, b| Comparator::ordering(&b.version, &a.version));
+}
+
+/// Pick the version to show details for when none was requested.
+///
+/// `versions` must be sorted from the newest to the oldest version.
+fn select_default_version(
+    versions: &[Arc<pox_pm::Package>],
+    installed: Option<&Arc<pox_pm::Package>>,
+    matches_root: &dyn Fn(&pox_pm::Package) -> bool,
+) -> Arc<pox_pm::Package> {
+    let is_stable = |p: &&Arc<pox_pm::Package>| Stability::from_version(&p.version) == Stability::Stable;
+
+    installed
+        .and_then(|i| versions.iter().find(|p| p.version == i.version))
+        .or_else(|| versions.iter().filter(is_stable).find(|p| matches_root(p)))
+        .or_else(|| versions.iter().find(|p| matches_root(p)))
+        .or_else(|| versions.iter().find(is_stable))
+        .unwrap_or(&versions[0])
+        .clone()
+}
+
+fn style_by_stability(version: &str, stability: Stability) -> console::StyledObject<&str> {
+    match stability {
+        Stability::Stable => style(version).green(),
+        Stability::RC | Stability::Beta | Stability::Alpha => style(version).yellow(),
+        Stability::Dev => style(version).red(),
+    }
+}
+
+fn print_package_info(package: &pox_pm::Package, versions: &str, latest: Option<&str>) -> Result<()> {
     println!("name     : {}", package.name);
     if let Some(desc) = &package.description {
         println!("descrip. : {}", desc);
     }
-    println!("versions : {}", package.pretty_version.as_deref().unwrap_or(&package.version));
+    println!("versions : {}", versions);
+    if let Some(latest) = latest {
+        println!("latest   : {}", latest);
+    }
     println!("type     : {}", package.package_type);
 
     if let Some(abandoned) = &package.abandoned {
@@ -357,6 +743,13 @@ fn print_package_info(package: &pox_pm::Package) -> Result<()> {
         }
     }
 
+    if !package.suggest.is_empty() {
+        println!("\nsuggests");
+        for (name, reason) in &package.suggest {
+            println!("{} {}", name, reason);
+        }
+    }
+
     if !package.provide.is_empty() {
         println!("\nprovide");
         for (name, constraint) in &package.provide {
@@ -381,7 +774,7 @@ fn print_package_info(package: &pox_pm::Package) -> Result<()> {
     Ok(())
 }
 
-fn print_package_json(package: &pox_pm::Package) -> Result<()> {
+fn package_json(package: &pox_pm::Package) -> serde_json::Value {
     let abandoned_value = package.abandoned.as_ref().map(|a| {
         match a.replacement() {
             Some(pkg) => serde_json::json!(pkg),
@@ -389,7 +782,7 @@ fn print_package_json(package: &pox_pm::Package) -> Result<()> {
         }
     });
 
-    let json = serde_json::json!({
+    serde_json::json!({
         "name": package.name,
         "version": package.pretty_version.as_deref().unwrap_or(&package.version),
         "description": package.description,
@@ -397,38 +790,11 @@ fn print_package_json(package: &pox_pm::Package) -> Result<()> {
         "abandoned": abandoned_value,
         "require": package.require,
         "require-dev": package.require_dev,
+        "suggest": package.suggest,
         "provide": package.provide,
         "conflict": package.conflict,
         "replace": package.replace,
-    });
-    println!("{}", serde_json::to_string_pretty(&json)?);
-    Ok(())
-}
-
-async fn fetch_latest_versions(
-    packages: &[Arc<pox_pm::Package>],
-    config: &Config,
-) -> HashMap<String, String> {
-    let mut latest_versions = HashMap::new();
-
-    let packagist = if let Some(cache_dir) = &config.cache_dir {
-        ComposerRepository::packagist_with_cache(cache_dir.join("repo"))
-    } else {
-        ComposerRepository::packagist()
-    };
-
-    for pkg in packages {
-        if is_platform_package(&pkg.name) {
-            continue;
-        }
-
-        let versions = packagist.find_packages(&pkg.name).await;
-        if let Some(latest) = find_latest_stable_version(&versions) {
-            latest_versions.insert(pkg.name.to_lowercase(), latest);
-        }
-    }
-
-    latest_versions
+    })
 }
 
 fn find_latest_stable_version(packages: &[Arc<pox_pm::Package>]) -> Option<String> {
@@ -475,6 +841,98 @@ fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
     std::cmp::Ordering::Equal
 }
 
+/// Available versions of the packages in the repositories of the project, by lowercase name
+async fn fetch_available_versions(
+    packages: &[Arc<pox_pm::Package>],
+    composer_json: &ComposerJson,
+    config: &Config,
+    working_dir: &Path,
+) -> Result<HashMap<String, Vec<Arc<pox_pm::Package>>>> {
+    let composer = ComposerBuilder::new(working_dir.to_path_buf())
+        .with_config(config.clone())
+        .with_composer_json(composer_json.clone())
+        .build()?;
+
+    let mut versions = HashMap::new();
+    for pkg in packages {
+        if is_platform_package(&pkg.name) {
+            continue;
+        }
+        versions.insert(pkg.name.to_lowercase(), composer.repository_manager.find_packages(&pkg.name).await);
+    }
+
+    Ok(versions)
+}
+
+/// The latest version matching `constraint` that is at least as stable as `stability`
+///
+/// Dev versions are never reported as the latest version.
+fn find_latest_version(
+    candidates: &[Arc<pox_pm::Package>],
+    constraint: Option<&str>,
+    stability: Stability,
+) -> Option<Arc<pox_pm::Package>> {
+    let parser = VersionParser::new();
+    let matcher = match constraint {
+        Some(constraint) => Some(parser.parse_constraints_cached(constraint).ok()?),
+        None => None,
+    };
+
+    candidates
+        .iter()
+        .filter(|p| p.stability() != Stability::Dev && p.stability() >= stability)
+        .filter(|p| matcher.as_ref().is_none_or(|m| m.matches_normalized(&p.version)))
+        .max_by(|a, b| Comparator::ordering(&a.version, &b.version))
+        .cloned()
+}
+
+/// `~major.minor.patch` of a normalized version, allowing patch releases only
+fn patch_constraint(version: &str) -> Option<String> {
+    let parts: Vec<&str> = version.split('-').next()?.split('.').take(3).collect();
+    if parts.len() < 3 || parts.iter().any(|p| p.parse::<u64>().is_err()) {
+        return None;
+    }
+    Some(format!("~{}", parts.join(".")))
+}
+
+/// Whether `version` is allowed by `^current`
+fn is_semver_safe(current: &str, version: &str) -> bool {
+    VersionParser::new()
+        .parse_constraints_cached(&format!("^{}", current))
+        .is_ok_and(|m| m.matches_normalized(version))
+}
+
+/// Whether a package name matches one of the `--ignore` patterns
+fn is_ignored(name: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| {
+        let regex = regex::escape(&pattern.to_lowercase()).replace("\\*", ".*");
+        regex::Regex::new(&format!("^{}$", regex)).is_ok_and(|re| re.is_match(&name.to_lowercase()))
+    })
+}
+
+/// The latest version with the parts that differ from the current version in bold
+fn highlight_version_diff(current: &str, latest: &str, color: console::Color) -> String {
+    let current_parts: Vec<&str> = current.split('.').collect();
+    let latest_parts: Vec<&str> = latest.split('.').collect();
+    let common = current_parts
+        .iter()
+        .zip(&latest_parts)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    if common == latest_parts.len() {
+        return style(latest).fg(color).to_string();
+    }
+
+    let (same, changed) = latest_parts.split_at(common);
+    let mut highlighted = String::new();
+    if !same.is_empty() {
+        highlighted.push_str(&style(format!("{}.", same.join("."))).fg(color).to_string());
+    }
+    highlighted.push_str(&style(changed.join(".")).fg(color).bold().to_string());
+    highlighted
+}
+
 fn strip_version_prefix(version: &str) -> &str {
     version.strip_prefix('v').or_else(|| version.strip_prefix('V')).unwrap_or(version)
 }
@@ -483,10 +941,11 @@ async fn list_packages_with_latest(
     packages: &[Arc<pox_pm::Package>],
     filter: Option<&str>,
     composer_json: &ComposerJson,
+    reasons: &HashMap<String, InstallReason>,
     args: &ShowArgs,
     config: &Config,
     show_latest: bool,
-) -> Result<()> {
+) -> Result<bool> {
     let mut filtered: Vec<_> = packages
         .iter()
         .filter(|p| {
@@ -498,6 +957,7 @@ async fn list_packages_with_latest(
                 true
             }
         })
+        .filter(|p| !is_ignored(&p.name, &args.ignore))
         .cloned()
         .collect();
 
@@ -522,27 +982,18 @@ async fn list_packages_with_latest(
 
     filtered.sort_by(|a, b| a.name.cmp(&b.name));
 
-    let latest_versions = if show_latest {
-        fetch_latest_versions(&filtered, config).await
+    let available_versions = if show_latest {
+        fetch_available_versions(&filtered, composer_json, config, &args.working_dir).await?
     } else {
         HashMap::new()
     };
 
+    let options = LatestOptions::new(args, composer_json);
     let mut packages_with_latest: Vec<PackageWithLatest> = filtered
         .into_iter()
         .map(|p| {
-            let current = p.pretty_version.as_deref().unwrap_or(&p.version);
-            let latest = latest_versions.get(&p.name.to_lowercase()).cloned();
-            let update_type = if let Some(ref lat) = latest {
-                determine_update_type(current, lat)
-            } else {
-                UpdateType::UpToDate
-            };
-            PackageWithLatest {
-                package: p,
-                latest_version: latest,
-                update_type,
-            }
+            let name = p.name.to_lowercase();
+            options.resolve(p, available_versions.get(&name), reasons.get(&name).cloned())
         })
         .collect();
 
@@ -550,8 +1001,12 @@ async fn list_packages_with_latest(
         packages_with_latest.retain(|p| p.update_type != UpdateType::UpToDate);
     }
 
+    packages_with_latest.retain(|p| options.is_listed(p));
+
+    let has_outdated = packages_with_latest.iter().any(|p| p.update_type != UpdateType::UpToDate);
+
     if packages_with_latest.is_empty() {
-        return Ok(());
+        return Ok(false);
     }
 
     if args.format == "json" {
@@ -572,12 +1027,16 @@ async fn list_packages_with_latest(
                     "abandoned": abandoned_value,
                 });
 
+                if let Some(reason) = &p.install_reason {
+                    obj["install-reason"] = serde_json::json!(reason);
+                }
+
                 if let Some(ref latest) = p.latest_version {
                     obj["latest"] = serde_json::json!(latest);
-                    obj["latest-status"] = serde_json::json!(match p.update_type {
-                        UpdateType::UpToDate => "up-to-date",
-                        UpdateType::Patch | UpdateType::Minor => "semver-safe-update",
-                        UpdateType::Major => "update-possible",
+                    obj["latest-status"] = serde_json::json!(match (p.update_type, p.semver_safe) {
+                        (UpdateType::UpToDate, _) => "up-to-date",
+                        (_, true) => "semver-safe-update",
+                        (_, false) => "update-possible",
                     });
                 }
 
@@ -590,6 +1049,7 @@ async fn list_packages_with_latest(
             eprintln!("{}", style("Color legend:").green());
             eprintln!("- {} release available - update recommended", style("patch or minor").red());
             eprintln!("- {} release available - update possible", style("major").yellow());
+            eprintln!("- the parts of the version that change are {}", style("bold").bold());
             eprintln!();
 
             let direct: Vec<_> = packages_with_latest
@@ -619,7 +1079,7 @@ async fn list_packages_with_latest(
         }
     }
 
-    Ok(())
+    Ok(has_outdated)
 }
 
 fn make_packagist_link(name: &str) -> String {
@@ -643,6 +1103,12 @@ fn print_packages_list(packages: &[&PackageWithLatest], args: &ShowArgs) {
         .max()
         .unwrap_or(30)
         .max(30);
+    let why_width = packages
+        .iter()
+        .filter_map(|p| p.install_reason.as_ref())
+        .map(|r| r.to_string().len())
+        .max()
+        .unwrap_or(0);
 
     for pwl in packages {
         let package = &pwl.package;
@@ -659,6 +1125,16 @@ fn print_packages_list(packages: &[&PackageWithLatest], args: &ShowArgs) {
                 .next()
                 .unwrap_or("");
 
+            let why = match &pwl.install_reason {
+                Some(reason) => {
+                    let text = reason.to_string();
+                    let padding = " ".repeat(why_width.saturating_sub(text.len()));
+                    let text = if reason.is_direct() { style(text).green() } else { style(text).dim() };
+                    format!("{}{} ", text, padding)
+                }
+                None => String::new(),
+            };
+
             let link_url = make_packagist_link(&package.name);
             let linked_name = terminal_link(&package.name, &link_url);
             let padding = " ".repeat(name_width.saturating_sub(package.name.len()));
@@ -671,27 +1147,22 @@ fn print_packages_list(packages: &[&PackageWithLatest], args: &ShowArgs) {
                     desc.to_string()
                 };
 
-                let (colored_version, indicator, colored_latest) = match pwl.update_type {
-                    UpdateType::UpToDate => (
-                        style(version).green().to_string(),
-                        style("=").green().to_string(),
-                        style(latest_display).green().to_string(),
-                    ),
-                    UpdateType::Patch | UpdateType::Minor => (
-                        style(version).red().to_string(),
-                        style("!").red().to_string(),
-                        style(latest_display).red().to_string(),
-                    ),
-                    UpdateType::Major => (
-                        style(version).yellow().to_string(),
-                        style("~").yellow().to_string(),
-                        style(latest_display).yellow().to_string(),
-                    ),
+                let (color, indicator) = match (pwl.update_type, pwl.semver_safe) {
+                    (UpdateType::UpToDate, _) => (console::Color::Green, "="),
+                    (_, true) => (console::Color::Red, "!"),
+                    (_, false) => (console::Color::Yellow, "~"),
+                };
+                let colored_version = style(version).fg(color).to_string();
+                let indicator = style(indicator).fg(color).to_string();
+                let colored_latest = if pwl.update_type == UpdateType::UpToDate {
+                    style(latest_display).fg(color).to_string()
+                } else {
+                    highlight_version_diff(version, latest_display, color)
                 };
 
                 println!(
-                    "{}{} {:<7} {} {:<7} {}",
-                    linked_name, padding, colored_version, indicator, colored_latest, truncated_desc
+                    "{}{} {:<7} {} {:<7} {}{}",
+                    linked_name, padding, colored_version, indicator, colored_latest, why, truncated_desc
                 );
             } else {
                 let abandoned_marker = if package.abandoned.is_some() {
@@ -699,7 +1170,7 @@ fn print_packages_list(packages: &[&PackageWithLatest], args: &ShowArgs) {
                 } else {
                     String::new()
                 };
-                println!("{}{} {:<15} {}{}", linked_name, padding, version, desc, abandoned_marker);
+                println!("{}{} {:<15} {}{}{}", linked_name, padding, version, why, desc, abandoned_marker);
             }
         }
     }
@@ -829,6 +1300,112 @@ mod tests {
         assert_eq!(compare_versions("1.0.0-beta", "1.0.0"), std::cmp::Ordering::Equal);
     }
 
+    fn versions(list: &[&str]) -> Vec<Arc<pox_pm::Package>> {
+        list.iter()
+            .map(|v| Arc::new(pox_pm::Package::new("vendor/pkg", *v)))
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_versions_descending() {
+        let mut packages = versions(&["1.0.0.0", "2.0.0.0-beta1", "1.10.0.0", "1.2.0.0"]);
+        sort_versions_descending(&mut packages);
+
+        let sorted: Vec<_> = packages.iter().map(|p| p.version.as_str()).collect();
+        assert_eq!(sorted, vec!["2.0.0.0-beta1", "1.10.0.0", "1.2.0.0", "1.0.0.0"]);
+    }
+
+    #[test]
+    fn test_select_default_version_prefers_installed() {
+        let packages = versions(&["2.0.0.0", "1.1.0.0", "1.0.0.0"]);
+        let installed = Arc::new(pox_pm::Package::new("vendor/pkg", "1.0.0.0"));
+
+        let selected = select_default_version(&packages, Some(&installed), &|_| false);
+        assert_eq!(selected.version, "1.0.0.0");
+    }
+
+    #[test]
+    fn test_select_default_version_prefers_stable_root_match() {
+        let packages = versions(&["2.0.0.0", "1.2.0.0-RC1", "1.1.0.0", "1.0.0.0"]);
+
+        let selected = select_default_version(&packages, None, &|p| p.version.starts_with("1."));
+        assert_eq!(selected.version, "1.1.0.0");
+    }
+
+    #[test]
+    fn test_select_default_version_falls_back_to_latest_stable() {
+        let packages = versions(&["3.0.0.0-beta1", "2.0.0.0", "1.0.0.0"]);
+
+        let selected = select_default_version(&packages, None, &|_| false);
+        assert_eq!(selected.version, "2.0.0.0");
+    }
+
+    #[test]
+    fn test_find_latest_version() {
+        let packages = versions(&["1.2.0.0", "1.3.1.0", "2.0.0.0", "2.1.0.0-beta1", "dev-main", "1.2.5.0"]);
+
+        let latest = |constraint, stability| find_latest_version(&packages, constraint, stability).map(|p| p.version.clone());
+        assert_eq!(latest(None, Stability::Stable).as_deref(), Some("2.0.0.0"));
+        assert_eq!(latest(None, Stability::Beta).as_deref(), Some("2.1.0.0-beta1"));
+        assert_eq!(latest(None, Stability::Dev).as_deref(), Some("2.1.0.0-beta1"));
+        assert_eq!(latest(Some("^1.2.0.0"), Stability::Stable).as_deref(), Some("1.3.1.0"));
+        assert_eq!(latest(Some("~1.2.0"), Stability::Stable).as_deref(), Some("1.2.5.0"));
+        assert_eq!(latest(Some("^3.0"), Stability::Stable), None);
+    }
+
+    #[test]
+    fn test_latest_options_filters() {
+        let candidates = versions(&["1.2.0.0", "1.2.5.0", "1.3.1.0", "2.0.0.0"]);
+        let installed = Arc::new(pox_pm::Package::new("vendor/pkg", "1.2.0.0"));
+        let resolve = |update_filter| {
+            let options = LatestOptions { update_filter, minimum_stability: Stability::Stable, prefer_stable: false };
+            let resolved = options.resolve(installed.clone(), Some(&candidates), None);
+            options.is_listed(&resolved).then_some(resolved.latest_version).flatten()
+        };
+
+        assert_eq!(resolve(UpdateFilter::All).as_deref(), Some("2.0.0.0"));
+        assert_eq!(resolve(UpdateFilter::MajorOnly).as_deref(), Some("2.0.0.0"));
+        assert_eq!(resolve(UpdateFilter::MinorOnly).as_deref(), Some("1.3.1.0"));
+        assert_eq!(resolve(UpdateFilter::PatchOnly).as_deref(), Some("1.2.5.0"));
+
+        // Only updates outside of ^current count for --major-only
+        let options = LatestOptions { update_filter: UpdateFilter::MajorOnly, minimum_stability: Stability::Stable, prefer_stable: false };
+        let minor_only = versions(&["1.2.0.0", "1.3.1.0"]);
+        assert!(!options.is_listed(&options.resolve(installed.clone(), Some(&minor_only), None)));
+    }
+
+    #[test]
+    fn test_patch_constraint() {
+        assert_eq!(patch_constraint("1.2.3.0").as_deref(), Some("~1.2.3"));
+        assert_eq!(patch_constraint("0.4.0.0-beta1").as_deref(), Some("~0.4.0"));
+        assert_eq!(patch_constraint("dev-main"), None);
+    }
+
+    #[test]
+    fn test_is_semver_safe() {
+        assert!(is_semver_safe("1.2.3.0", "1.9.0.0"));
+        assert!(!is_semver_safe("1.2.3.0", "2.0.0.0"));
+        assert!(is_semver_safe("0.1.2.0", "0.1.9.0"));
+        assert!(!is_semver_safe("0.1.2.0", "0.2.0.0"));
+    }
+
+    #[test]
+    fn test_is_ignored() {
+        let patterns = vec!["symfony/*".to_string(), "vendor/exact".to_string()];
+        assert!(is_ignored("symfony/console", &patterns));
+        assert!(is_ignored("Vendor/Exact", &patterns));
+        assert!(!is_ignored("vendor/exact-other", &patterns));
+        assert!(!is_ignored("monolog/monolog", &patterns));
+    }
+
+    #[test]
+    fn test_highlight_version_diff() {
+        let plain = |current, latest| console::strip_ansi_codes(&highlight_version_diff(current, latest, console::Color::Red)).to_string();
+        assert_eq!(plain("1.2.3", "1.3.0"), "1.3.0");
+        assert_eq!(plain("1.2.3", "1.2.3"), "1.2.3");
+        assert_eq!(plain("1.2", "1.2.1"), "1.2.1");
+    }
+
     #[test]
     fn test_strip_version_prefix() {
         assert_eq!(strip_version_prefix("v1.0.0"), "1.0.0");
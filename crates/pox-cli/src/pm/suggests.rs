@@ -2,6 +2,8 @@ use anyhow::{Context, Result};
 use clap::Args;
 use colored::Colorize;
 use pox_pm::json::{ComposerJson, ComposerLock};
+use pox_pm::config::{composer_file_name, lock_file_name};
+use pox_pm::installer::SuggestedPackage;
 use std::collections::{BTreeMap, HashSet};
 use std::path::PathBuf;
 
@@ -36,13 +38,6 @@ pub struct SuggestsArgs {
     pub working_dir: PathBuf,
 }
 
-#[derive(Debug, Clone)]
-struct Suggestion {
-    source: String,
-    target: String,
-    reason: String,
-}
-
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum OutputMode {
     List,
@@ -57,7 +52,7 @@ pub async fn execute(args: SuggestsArgs) -> Result<i32> {
         .canonicalize()
         .context("Failed to resolve working directory")?;
 
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
     let composer_json: Option<ComposerJson> = if json_path.exists() {
         let content = std::fs::read_to_string(&json_path)?;
         Some(serde_json::from_str(&content).context("Failed to parse composer.json")?)
@@ -65,7 +60,7 @@ pub async fn execute(args: SuggestsArgs) -> Result<i32> {
         None
     };
 
-    let lock_path = working_dir.join("composer.lock");
+    let lock_path = working_dir.join(lock_file_name());
     let lock: ComposerLock = if lock_path.exists() {
         let content = std::fs::read_to_string(&lock_path)?;
         serde_json::from_str(&content).context("Failed to parse composer.lock")?
@@ -93,13 +88,13 @@ pub async fn execute(args: SuggestsArgs) -> Result<i32> {
         })
         .unwrap_or_default();
 
-    let mut all_suggestions: Vec<Suggestion> = Vec::new();
+    let mut all_suggestions: Vec<SuggestedPackage> = Vec::new();
 
     if let Some(ref json) = composer_json {
         if let Some(ref name) = json.name {
             if args.packages.is_empty() || args.packages.iter().any(|p| p.eq_ignore_ascii_case(name)) {
                 for (target, reason) in &json.suggest {
-                    all_suggestions.push(Suggestion {
+                    all_suggestions.push(SuggestedPackage {
                         source: name.clone(),
                         target: target.clone(),
                         reason: reason.clone(),
@@ -129,7 +124,7 @@ pub async fn execute(args: SuggestsArgs) -> Result<i32> {
         }
 
         for (target, reason) in &pkg.suggest {
-            all_suggestions.push(Suggestion {
+            all_suggestions.push(SuggestedPackage {
                 source: pkg.name.clone(),
                 target: target.clone(),
                 reason: reason.clone(),
@@ -137,7 +132,7 @@ pub async fn execute(args: SuggestsArgs) -> Result<i32> {
         }
     }
 
-    let suggestions: Vec<Suggestion> = all_suggestions
+    let suggestions: Vec<SuggestedPackage> = all_suggestions
         .into_iter()
         .filter(|s| !installed_names.contains(&s.target.to_lowercase()))
         .collect();
@@ -148,7 +143,7 @@ pub async fn execute(args: SuggestsArgs) -> Result<i32> {
             .and_then(|j| j.name.as_ref())
             .map(|n| n.to_lowercase());
 
-        let filtered: Vec<Suggestion> = suggestions
+        let filtered: Vec<SuggestedPackage> = suggestions
             .iter()
             .filter(|s| {
                 let source_lower = s.source.to_lowercase();
@@ -179,7 +174,7 @@ pub async fn execute(args: SuggestsArgs) -> Result<i32> {
     Ok(0)
 }
 
-fn output_suggestions(suggestions: &[Suggestion], mode: OutputMode, transitive_count: usize) {
+fn output_suggestions(suggestions: &[SuggestedPackage], mode: OutputMode, transitive_count: usize) {
     if suggestions.is_empty() && transitive_count == 0 {
         return;
     }
@@ -211,7 +206,7 @@ fn output_suggestions(suggestions: &[Suggestion], mode: OutputMode, transitive_c
     }
 }
 
-fn output_by_package(suggestions: &[Suggestion]) {
+fn output_by_package(suggestions: &[SuggestedPackage]) {
     let mut by_source: BTreeMap<&str, Vec<(&str, &str)>> = BTreeMap::new();
 
     for suggestion in suggestions {
@@ -234,7 +229,7 @@ fn output_by_package(suggestions: &[Suggestion]) {
     }
 }
 
-fn output_by_suggestion(suggestions: &[Suggestion]) {
+fn output_by_suggestion(suggestions: &[SuggestedPackage]) {
     let mut by_target: BTreeMap<&str, Vec<(&str, &str)>> = BTreeMap::new();
 
     for suggestion in suggestions {
@@ -8,10 +8,11 @@ use std::sync::Arc;
 use pox_pm::{
     ComposerBuilder, DependencyResult, Repository,
     config::Config,
-    find_packages_with_replacers_and_providers, get_dependents,
+    dependency_chains, find_packages_with_replacers_and_providers, get_dependents, get_provider_dependents,
     is_platform_package,
     json::{ComposerJson, ComposerLock},
 };
+use pox_pm::config::{composer_file_name, lock_file_name};
 
 #[derive(Args, Debug)]
 pub struct WhyArgs {
@@ -29,6 +30,10 @@ pub struct WhyArgs {
     #[arg(short = 'r', long)]
     pub recursive: bool,
 
+    /// Output format: text or json (json always follows the chains up to the root)
+    #[arg(short = 'f', long, default_value = "text")]
+    pub format: String,
+
     /// Working directory
     #[arg(short = 'd', long, default_value = ".")]
     pub working_dir: PathBuf,
@@ -40,7 +45,16 @@ pub async fn execute(args: WhyArgs, inverted: bool) -> Result<i32> {
         .canonicalize()
         .context("Failed to resolve working directory")?;
 
-    let json_path = working_dir.join("composer.json");
+    if args.format != "text" && args.format != "json" {
+        eprintln!(
+            "Error: Unsupported format '{}'. See help for supported formats.",
+            args.format
+        );
+        return Ok(1);
+    }
+    let json = args.format == "json";
+
+    let json_path = working_dir.join(composer_file_name());
     let composer_json: ComposerJson = if json_path.exists() {
         let content = std::fs::read_to_string(&json_path)?;
         serde_json::from_str(&content)?
@@ -49,7 +63,7 @@ pub async fn execute(args: WhyArgs, inverted: bool) -> Result<i32> {
     };
 
     let lock: Option<ComposerLock> = {
-        let lock_path = working_dir.join("composer.lock");
+        let lock_path = working_dir.join(lock_file_name());
         if lock_path.exists() {
             let content = std::fs::read_to_string(&lock_path).ok();
             content.and_then(|c| serde_json::from_str(&c).ok())
@@ -123,6 +137,10 @@ pub async fn execute(args: WhyArgs, inverted: bool) -> Result<i32> {
 
     if matched_package.is_some() && inverted {
         if let Some(pkg) = matched_package {
+            if json {
+                print_json(needle, args.constraint.as_deref(), Some(pkg), inverted, &[])?;
+                return Ok(0);
+            }
             println!(
                 "Package \"{}\" {} is already installed! To find out why, run `pox pm why {}`",
                 needle,
@@ -135,15 +153,15 @@ pub async fn execute(args: WhyArgs, inverted: bool) -> Result<i32> {
 
     let mut needles = vec![needle.to_string()];
     if inverted {
-        for package in &matching_packages {
-            for (target, _constraint) in &package.replace {
+        for found in &matching_packages {
+            for target in found.package.replace.keys() {
                 needles.push(target.clone());
             }
         }
     }
 
-    let recursive = args.tree || args.recursive;
-    let results = get_dependents(
+    let recursive = args.tree || args.recursive || json;
+    let mut results = get_dependents(
         &installed_packages,
         &needles,
         constraint.as_ref().map(|v| &**v),
@@ -152,6 +170,18 @@ pub async fn execute(args: WhyArgs, inverted: bool) -> Result<i32> {
         None,
     );
 
+    // A virtual or replaced package is installed because of whoever provides it
+    if !inverted && matched_package.is_none() {
+        let mut providers = get_provider_dependents(&installed_packages, &matching_packages, recursive);
+        providers.append(&mut results);
+        results = providers;
+    }
+
+    if json {
+        print_json(needle, args.constraint.as_deref(), matched_package.map(|p| p.as_ref()), inverted, &results)?;
+        return Ok(i32::from(results.is_empty() != inverted));
+    }
+
     if results.is_empty() {
         let extra = if constraint.is_some() {
             format!(
@@ -170,7 +200,11 @@ pub async fn execute(args: WhyArgs, inverted: bool) -> Result<i32> {
     }
 
     if args.tree {
-        print_tree(&results, &matching_packages[0]);
+        let root_label = match matched_package {
+            Some(package) => package_label(package),
+            None => format!("{} (virtual package)", needle),
+        };
+        print_tree(&results, &root_label);
     } else {
         print_table(&results);
     }
@@ -201,6 +235,35 @@ pub async fn execute(args: WhyArgs, inverted: bool) -> Result<i32> {
     Ok(if inverted { 1 } else { 0 })
 }
 
+fn print_json(
+    needle: &str,
+    constraint: Option<&str>,
+    installed: Option<&pox_pm::Package>,
+    inverted: bool,
+    results: &[DependencyResult],
+) -> Result<()> {
+    let output = why_json(needle, constraint, installed, inverted, results);
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// Machine-readable why/why-not result: every chain of links from the package up to the root.
+fn why_json(
+    needle: &str,
+    constraint: Option<&str>,
+    installed: Option<&pox_pm::Package>,
+    inverted: bool,
+    results: &[DependencyResult],
+) -> serde_json::Value {
+    serde_json::json!({
+        "command": if inverted { "why-not" } else { "why" },
+        "package": needle,
+        "constraint": constraint,
+        "installed": installed.map(|p| p.pretty_version.clone().unwrap_or_else(|| p.version.clone())),
+        "chains": dependency_chains(results),
+    })
+}
+
 fn print_table(results: &[DependencyResult]) {
     println!("{:<40} {:<15} {:<15} {}", "Package", "Version", "Dependency", "Constraint");
     println!("{}", "-".repeat(100));
@@ -258,16 +321,37 @@ fn print_table(results: &[DependencyResult]) {
     }
 }
 
-fn print_tree(results: &[DependencyResult], root: &Arc<pox_pm::Package>) {
-    println!(
+fn print_tree(results: &[DependencyResult], root_label: &str) {
+    print!("{}", render_tree(results, root_label));
+}
+
+/// Name, version and description of the package at the top of the tree.
+fn package_label(package: &pox_pm::Package) -> String {
+    let mut label = format!(
         "{} {}",
-        root.name,
-        root.pretty_version.as_deref().unwrap_or(&root.version)
+        package.pretty_name.as_deref().unwrap_or(&package.name),
+        package.pretty_version.as_deref().unwrap_or(&package.version)
     );
-    print_tree_recursive(results, "", 0);
+    if let Some(description) = package.description.as_deref().filter(|d| !d.is_empty()) {
+        label.push(' ');
+        label.push_str(description);
+    }
+    label
 }
 
-fn print_tree_recursive(results: &[DependencyResult], prefix: &str, _level: usize) {
+/// Render reverse dependencies as an indented tree, like `composer depends --tree`.
+///
+/// Branches that would loop back into a package already on the current path are
+/// cut off and marked instead of being expanded again.
+fn render_tree(results: &[DependencyResult], root_label: &str) -> String {
+    let mut output = String::new();
+    output.push_str(root_label);
+    output.push('\n');
+    render_tree_recursive(results, "", &mut output);
+    output
+}
+
+fn render_tree_recursive(results: &[DependencyResult], prefix: &str, output: &mut String) {
     let count = results.len();
 
     for (idx, result) in results.iter().enumerate() {
@@ -280,7 +364,7 @@ fn print_tree_recursive(results: &[DependencyResult], prefix: &str, _level: usiz
             .as_deref()
             .unwrap_or(&result.package.version);
 
-        let circular_warn = if result.children.is_none() {
+        let circular_warn = if result.circular {
             " (circular dependency aborted here)"
         } else {
             ""
@@ -288,8 +372,8 @@ fn print_tree_recursive(results: &[DependencyResult], prefix: &str, _level: usiz
 
         let link_desc = result.link.link_type.description();
 
-        println!(
-            "{}{}{} {} ({} {} {}){}",
+        output.push_str(&format!(
+            "{}{}{} {} ({} {} {}){}\n",
             prefix,
             branch,
             result.package.name,
@@ -298,11 +382,126 @@ fn print_tree_recursive(results: &[DependencyResult], prefix: &str, _level: usiz
             result.link.target,
             result.link.constraint,
             circular_warn
-        );
+        ));
 
         if let Some(ref children) = result.children {
             let new_prefix = format!("{}{}   ", prefix, if is_last { " " } else { "│" });
-            print_tree_recursive(children, &new_prefix, _level + 1);
+            render_tree_recursive(children, &new_prefix, output);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pox_pm::Package;
+
+    fn package(name: &str, version: &str, requires: &[(&str, &str)]) -> Arc<Package> {
+        let mut pkg = Package::new(name, version);
+        pkg.pretty_version = Some(version.to_string());
+        for (target, constraint) in requires {
+            pkg.require.insert(target.to_string(), constraint.to_string());
         }
+        Arc::new(pkg)
+    }
+
+    #[test]
+    fn test_render_tree_recurses_to_root() {
+        let mut root = Package::new("acme/app", "dev-main");
+        root.package_type = "root-package".to_string();
+        root.require.insert("a/a".to_string(), "^1.0".to_string());
+
+        let packages = vec![
+            Arc::new(root),
+            package("a/a", "1.0.0", &[("c/c", "^2.0")]),
+            package("c/c", "2.0.0", &[]),
+        ];
+
+        let results = get_dependents(&packages, &["c/c".to_string()], None, false, true, None);
+        let tree = render_tree(&results, &package_label(&packages[2]));
+
+        assert_eq!(
+            tree,
+            concat!(
+                "c/c 2.0.0\n",
+                "└── a/a 1.0.0 (requires c/c ^2.0)\n",
+                "    └── acme/app dev-main (requires a/a ^1.0)\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_tree_for_virtual_package() {
+        let mut root = Package::new("acme/app", "dev-main");
+        root.package_type = "root-package".to_string();
+        root.require.insert("monolog/monolog".to_string(), "^3.0".to_string());
+
+        let mut monolog = Package::new("monolog/monolog", "3.5.0");
+        monolog.pretty_version = Some("3.5.0".to_string());
+        monolog.provide.insert("psr/log-implementation".to_string(), "3.0.0".to_string());
+
+        let packages = vec![Arc::new(root), Arc::new(monolog)];
+        let matches = find_packages_with_replacers_and_providers(&packages, "psr/log-implementation", None);
+        let results = get_provider_dependents(&packages, &matches, true);
+        let tree = render_tree(&results, "psr/log-implementation (virtual package)");
+
+        assert_eq!(
+            tree,
+            concat!(
+                "psr/log-implementation (virtual package)\n",
+                "└── monolog/monolog 3.5.0 (provides psr/log-implementation 3.0.0)\n",
+                "    └── acme/app dev-main (requires monolog/monolog ^3.0)\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_why_json_lists_chains_to_root() {
+        let mut root = Package::new("acme/app", "dev-main");
+        root.package_type = "root-package".to_string();
+        root.require.insert("a/a".to_string(), "^1.0".to_string());
+
+        let packages = vec![
+            Arc::new(root),
+            package("a/a", "1.0.0", &[("c/c", "^2.0")]),
+            package("c/c", "2.0.0", &[]),
+        ];
+
+        let results = get_dependents(&packages, &["c/c".to_string()], None, false, true, None);
+        let output = why_json("c/c", None, Some(&packages[2]), false, &results);
+
+        assert_eq!(
+            output,
+            serde_json::json!({
+                "command": "why",
+                "package": "c/c",
+                "constraint": null,
+                "installed": "2.0.0",
+                "chains": [{
+                    "links": [
+                        {"package": "a/a", "version": "1.0.0", "type": "requires", "target": "c/c", "constraint": "^2.0"},
+                        {"package": "acme/app", "version": "dev-main", "type": "requires", "target": "a/a", "constraint": "^1.0"},
+                    ],
+                    "root": true,
+                    "circular": false,
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_render_tree_marks_cycles() {
+        let packages = vec![
+            package("a/a", "1.0.0", &[("b/b", "^1.0")]),
+            package("b/b", "1.0.0", &[("a/a", "^1.0"), ("c/c", "^1.0")]),
+            package("c/c", "1.0.0", &[]),
+        ];
+
+        let results = get_dependents(&packages, &["c/c".to_string()], None, false, true, None);
+        let tree = render_tree(&results, &package_label(&packages[2]));
+
+        assert!(tree.contains("└── b/b 1.0.0 (requires c/c ^1.0)\n"));
+        assert!(tree.contains("    └── a/a 1.0.0 (requires b/b ^1.0)\n"));
+        assert!(tree.contains("        └── b/b 1.0.0 (requires a/a ^1.0) (circular dependency aborted here)\n"));
     }
 }
@@ -0,0 +1,306 @@
+//! Client address and scheme of requests passing a reverse proxy.
+//!
+//! Behind a proxy the peer of every connection is the proxy itself. When the
+//! peer is in one of the trusted proxy ranges, `REMOTE_ADDR` is taken from the
+//! `Forwarded` header (RFC 7239) or, without it, from `X-Forwarded-For`, and
+//! `HTTPS` from its `proto` parameter or `X-Forwarded-Proto`. Headers of
+//! other peers are ignored, as any client can send them, and so are the parts
+//! the client sent along to the trusted proxies.
+
+use std::fmt;
+use std::net::IpAddr;
+
+use anyhow::{anyhow, Result};
+
+/// An IP address range in CIDR notation, e.g. `10.0.0.0/8`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// Parse `<ip>/<prefix>` or a single IP address
+    pub fn parse(range: &str) -> Result<Self> {
+        let range = range.trim();
+        let invalid = || anyhow!("Invalid trusted proxy \"{}\", expected an IP address or CIDR range", range);
+
+        let (addr, prefix) = match range.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (range, None),
+        };
+        let addr = addr.parse::<IpAddr>().map_err(|_| invalid())?.to_canonical();
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse::<u8>().ok().filter(|p| *p <= max).ok_or_else(invalid)?,
+            None => max,
+        };
+
+        Ok(Self { addr, prefix })
+    }
+
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        match (self.addr, addr.to_canonical()) {
+            (IpAddr::V4(range), IpAddr::V4(addr)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(self.prefix)).unwrap_or(0);
+                u32::from(range) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(range), IpAddr::V6(addr)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(self.prefix)).unwrap_or(0);
+                u128::from(range) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// Address ranges of the proxies whose forwarding headers are honored
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustedProxies {
+    ranges: Vec<Cidr>,
+}
+
+/// Client of a request as seen by PHP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Client {
+    pub remote_addr: String,
+    pub https: bool,
+}
+
+/// A proxy hop taken from the forwarding headers
+struct Hop {
+    addr: Option<IpAddr>,
+    proto: Option<String>,
+}
+
+impl TrustedProxies {
+    /// Parse a list of IP addresses and CIDR ranges
+    pub fn parse(ranges: &[String]) -> Result<Self> {
+        let ranges = ranges.iter().map(|range| Cidr::parse(range)).collect::<Result<_>>()?;
+        Ok(Self { ranges })
+    }
+
+    pub fn ranges(&self) -> &[Cidr] {
+        &self.ranges
+    }
+
+    pub fn is_trusted(&self, addr: &str) -> bool {
+        addr.parse::<IpAddr>().is_ok_and(|addr| self.contains(&addr))
+    }
+
+    fn contains(&self, addr: &IpAddr) -> bool {
+        self.ranges.iter().any(|range| range.contains(addr))
+    }
+
+    /// Resolve the client of a request received from `remote_addr`
+    ///
+    /// The forwarded hops are read from the right, skipping the trusted
+    /// proxies the request passed, so hops the client prepended are never
+    /// used. The scheme is the one of the hop the walk stops at, the one the
+    /// outermost trusted proxy added for the client.
+    pub fn resolve(&self, headers: &[(String, String)], remote_addr: &str) -> Client {
+        let mut client = Client {
+            remote_addr: remote_addr.to_string(),
+            https: false,
+        };
+        if !self.is_trusted(remote_addr) {
+            return client;
+        }
+
+        let hops = forwarded_hops(headers).unwrap_or_else(|| x_forwarded_hops(headers));
+        let mut proto = None;
+        for hop in hops.iter().rev() {
+            proto = hop.proto.as_deref();
+            let Some(addr) = hop.addr else {
+                break;
+            };
+            client.remote_addr = addr.to_string();
+            if !self.contains(&addr) {
+                break;
+            }
+        }
+
+        client.https = proto.is_some_and(|proto| proto.eq_ignore_ascii_case("https"));
+        client
+    }
+}
+
+/// Comma separated items of all headers with the given name
+fn list_items<'a>(headers: &'a [(String, String)], name: &'a str) -> impl Iterator<Item = &'a str> {
+    headers
+        .iter()
+        .filter(move |(k, _)| k.eq_ignore_ascii_case(name))
+        .flat_map(|(_, v)| v.split(','))
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+/// Hops of the `Forwarded` header, `None` if the request has none
+fn forwarded_hops(headers: &[(String, String)]) -> Option<Vec<Hop>> {
+    let hops: Vec<Hop> = list_items(headers, "forwarded")
+        .map(|element| {
+            let mut hop = Hop { addr: None, proto: None };
+            for (key, value) in element.split(';').filter_map(|pair| pair.split_once('=')) {
+                let value = value.trim().trim_matches('"');
+                match key.trim().to_ascii_lowercase().as_str() {
+                    "for" => hop.addr = parse_node(value),
+                    "proto" => hop.proto = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+            hop
+        })
+        .collect();
+
+    (!hops.is_empty()).then_some(hops)
+}
+
+/// Hops of the `X-Forwarded-For` header
+///
+/// Every proxy appends to both headers, so the `X-Forwarded-Proto` items
+/// belong to the hops at the same position counted from the right.
+fn x_forwarded_hops(headers: &[(String, String)]) -> Vec<Hop> {
+    let mut hops: Vec<Hop> = list_items(headers, "x-forwarded-for")
+        .map(|addr| Hop { addr: parse_node(addr), proto: None })
+        .collect();
+
+    let protos: Vec<&str> = list_items(headers, "x-forwarded-proto").collect();
+    if hops.is_empty() && !protos.is_empty() {
+        hops.push(Hop { addr: None, proto: None });
+    }
+    for (hop, proto) in hops.iter_mut().rev().zip(protos.iter().rev()) {
+        hop.proto = Some(proto.to_string());
+    }
+
+    hops
+}
+
+/// Parse a node of a forwarding header, an IP address with an optional port
+///
+/// IPv6 addresses with a port are in brackets. `unknown` and obfuscated
+/// identifiers like `_hidden` are `None`.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let host = match node.strip_prefix('[') {
+        Some(rest) => rest.split_once(']')?.0,
+        None if node.parse::<IpAddr>().is_ok() => node,
+        None => node.rsplit_once(':')?.0,
+    };
+    host.parse::<IpAddr>().ok().map(|addr| addr.to_canonical())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn proxies(ranges: &[&str]) -> TrustedProxies {
+        TrustedProxies::parse(&ranges.iter().map(|r| r.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(proxies(&["10.0.0.1", "::1"]).is_trusted("::1"));
+        assert!(!proxies(&["10.0.0.1"]).is_trusted("10.0.0.2"));
+        assert!(TrustedProxies::parse(&["proxy".to_string()]).is_err());
+        assert!(TrustedProxies::parse(&["10.0.0.0/33".to_string()]).is_err());
+        assert!(TrustedProxies::parse(&["10.0.0.0/".to_string()]).is_err());
+        assert_eq!(Cidr::parse("172.16.0.0/12").unwrap().to_string(), "172.16.0.0/12");
+        assert_eq!(Cidr::parse("fd00::1").unwrap().to_string(), "fd00::1/128");
+    }
+
+    #[test]
+    fn test_cidr_contains() {
+        let trusted = proxies(&["172.16.0.0/12", "fd00::/8", "0.0.0.0/0"]);
+        assert!(trusted.ranges()[0].contains(&"172.31.255.1".parse().unwrap()));
+        assert!(!trusted.ranges()[0].contains(&"172.32.0.1".parse().unwrap()));
+        assert!(trusted.ranges()[0].contains(&"::ffff:172.17.0.1".parse().unwrap()));
+        assert!(trusted.ranges()[1].contains(&"fd12::3".parse().unwrap()));
+        assert!(!trusted.ranges()[1].contains(&"fe80::1".parse().unwrap()));
+        assert!(trusted.ranges()[2].contains(&"8.8.8.8".parse().unwrap()));
+        assert!(!trusted.ranges()[2].contains(&"2001:db8::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_resolve_x_forwarded() {
+        let trusted = proxies(&["10.0.0.0/8"]);
+        let request = headers(&[
+            ("X-Forwarded-For", "1.1.1.1, 203.0.113.7"),
+            ("x-forwarded-for", "10.0.0.2"),
+            ("X-Forwarded-Proto", "https, http"),
+        ]);
+
+        assert_eq!(
+            trusted.resolve(&request, "10.0.0.1"),
+            Client { remote_addr: "203.0.113.7".to_string(), https: true }
+        );
+
+        // The scheme 10.0.0.2 was reached with, not the one of the client
+        let request = headers(&[("X-Forwarded-For", "203.0.113.7, 10.0.0.2"), ("X-Forwarded-Proto", "https")]);
+        assert_eq!(
+            trusted.resolve(&request, "10.0.0.1"),
+            Client { remote_addr: "203.0.113.7".to_string(), https: false }
+        );
+        assert_eq!(
+            trusted.resolve(&headers(&[("X-Forwarded-Proto", "https")]), "10.0.0.1"),
+            Client { remote_addr: "10.0.0.1".to_string(), https: true }
+        );
+    }
+
+    #[test]
+    fn test_resolve_forwarded() {
+        let trusted = proxies(&["10.0.0.0/8"]);
+        let request = headers(&[
+            ("Forwarded", r#"for=198.51.100.1;proto=http, for="[2001:db8:cafe::17]:4711";proto=https"#),
+            ("Forwarded", "for=10.1.2.3:8080;proto=http;by=10.0.0.1"),
+            ("X-Forwarded-For", "192.0.2.1"),
+        ]);
+
+        assert_eq!(
+            trusted.resolve(&request, "10.0.0.1"),
+            Client { remote_addr: "2001:db8:cafe::17".to_string(), https: true }
+        );
+        assert_eq!(
+            trusted.resolve(&headers(&[("Forwarded", "for=_hidden, for=10.0.0.5")]), "10.0.0.1"),
+            Client { remote_addr: "10.0.0.5".to_string(), https: false }
+        );
+    }
+
+    #[test]
+    fn test_resolve_ignores_proto_sent_by_client() {
+        let trusted = proxies(&["10.0.0.1"]);
+
+        // The client claims https, the trusted proxy only appends the address it saw
+        let request = headers(&[("Forwarded", "for=198.51.100.1;proto=https, for=203.0.113.7")]);
+        assert_eq!(
+            trusted.resolve(&request, "10.0.0.1"),
+            Client { remote_addr: "203.0.113.7".to_string(), https: false }
+        );
+
+        let request = headers(&[("Forwarded", "for=x;proto=https, for=203.0.113.7")]);
+        assert!(!trusted.resolve(&request, "10.0.0.1").https);
+    }
+
+    #[test]
+    fn test_resolve_untrusted_peer() {
+        let trusted = proxies(&["10.0.0.1"]);
+        let request = headers(&[("X-Forwarded-For", "203.0.113.7"), ("X-Forwarded-Proto", "https")]);
+
+        assert_eq!(
+            trusted.resolve(&request, "192.168.1.5"),
+            Client { remote_addr: "192.168.1.5".to_string(), https: false }
+        );
+        assert_eq!(
+            trusted.resolve(&headers(&[("X-Forwarded-For", "unknown")]), "10.0.0.1"),
+            Client { remote_addr: "10.0.0.1".to_string(), https: false }
+        );
+    }
+}
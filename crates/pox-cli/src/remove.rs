@@ -11,6 +11,8 @@ use pox_pm::{
     installer::Installer,
     json::{ComposerJson, ComposerLock},
 };
+use pox_pm::config::{composer_file_name, lock_file_name};
+use pox_pm::io::Verbosity;
 use crate::pm::platform::PlatformInfo;
 
 #[derive(Args, Debug)]
@@ -49,14 +51,16 @@ pub struct RemoveArgs {
 }
 
 pub async fn execute(args: RemoveArgs) -> Result<i32> {
+    let io = crate::io::io();
+
     let working_dir = args.working_dir.canonicalize()
         .context("Failed to resolve working directory")?;
 
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
     if !json_path.exists() {
-        eprintln!("{} No composer.json found in {}",
-            style("Error:").red().bold(),
-            working_dir.display()
+        io.write_error_at(
+            &format!("{} No composer.json found in {}", style("Error:").red().bold(), working_dir.display()),
+            Verbosity::Quiet,
         );
         return Ok(1);
     }
@@ -66,7 +70,7 @@ pub async fn execute(args: RemoveArgs) -> Result<i32> {
     let composer_json: ComposerJson = serde_json::from_str(&content)?;
 
     // Load composer.lock
-    let lock_path = working_dir.join("composer.lock");
+    let lock_path = working_dir.join(lock_file_name());
     let lock: Option<ComposerLock> = if lock_path.exists() {
         let content = std::fs::read_to_string(&lock_path)
             .context("Failed to read composer.lock")?;
@@ -79,10 +83,12 @@ pub async fn execute(args: RemoveArgs) -> Result<i32> {
     let config = Config::build(Some(&working_dir), true)?;
 
     // Detect platform
-    let platform = PlatformInfo::detect();
+    let platform = PlatformInfo::detect_for(&working_dir);
 
     // Create Composer using builder
     let mut composer = ComposerBuilder::new(working_dir.clone())
+        .with_io(io.clone())
+        .with_profiler(crate::io::profiler())
         .with_config(config)
         .with_composer_json(composer_json)
         .with_composer_lock(lock)
@@ -90,9 +96,9 @@ pub async fn execute(args: RemoveArgs) -> Result<i32> {
         .dry_run(args.dry_run)
         .build()?;
 
-    println!("{} Removing packages", style("Composer").green().bold());
+    io.write(&format!("{} Removing packages", style("Composer").green().bold()));
     if args.dry_run {
-        println!("{} Running in dry-run mode", style("Info:").cyan());
+        io.write(&format!("{} Running in dry-run mode", style("Info:").cyan()));
     }
 
     let mut removed = Vec::new();
@@ -103,21 +109,21 @@ pub async fn execute(args: RemoveArgs) -> Result<i32> {
         let was_in_dev = composer.composer_json.require_dev.shift_remove(name).is_some();
 
         if was_in_require || was_in_dev {
-            println!("  {} {}",
+            io.write(&format!("  {} {}",
                 style("-").red(),
                 style(name).white().bold()
-            );
+            ));
             removed.push(name.clone());
         } else {
-            println!("  {} {} is not installed",
+            io.write(&format!("  {} {} is not installed",
                 style("!").yellow(),
                 style(name).white()
-            );
+            ));
         }
     }
 
     if removed.is_empty() {
-        println!("{} Nothing to remove", style("Info:").cyan());
+        io.write(&format!("{} Nothing to remove", style("Info:").cyan()));
         return Ok(0);
     }
 
@@ -139,10 +145,10 @@ pub async fn execute(args: RemoveArgs) -> Result<i32> {
             None,
         ).await
     } else {
-        println!("{} {} packages removed from composer.json",
+        io.write(&format!("{} {} packages removed from composer.json",
             style("Success:").green().bold(),
             removed.len()
-        );
+        ));
         Ok(0)
     }
 }
@@ -0,0 +1,337 @@
+//! Repl command - interactive PHP shell, like `php -a`.
+//!
+//! A single PHP request is kept alive for the whole session, so variables,
+//! functions and classes defined in one input remain available in the next.
+
+use anyhow::Result;
+use clap::Args;
+use console::style;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Editor, Helper};
+use std::path::PathBuf;
+
+use pox_embed::{Php, PhpRepl, ReplOutcome};
+
+/// Keywords that start a statement and can therefore not be returned as a value
+const STATEMENT_KEYWORDS: &[&str] = &[
+    "abstract", "break", "class", "const", "continue", "declare", "do", "echo", "enum", "final",
+    "for", "foreach", "function", "global", "goto", "if", "interface", "namespace", "readonly",
+    "return", "static", "switch", "throw", "trait", "try", "unset", "use", "while",
+];
+
+#[derive(Args, Debug)]
+pub struct ReplArgs {
+    /// Define INI entry (can be used multiple times)
+    #[arg(short = 'd', value_name = "KEY=VALUE", action = clap::ArgAction::Append)]
+    pub define: Vec<String>,
+
+    /// Do not read or write the history file
+    #[arg(long)]
+    pub no_history: bool,
+
+    /// Arguments available in $argv
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub args: Vec<String>,
+}
+
+pub fn execute(args: ReplArgs, ini_entries: Option<String>) -> Result<i32> {
+    if ini_entries.is_some() {
+        Php::set_ini_entries(ini_entries.as_deref())?;
+    }
+
+    let mut repl = PhpRepl::start(&args.args)?;
+
+    let mut editor: Editor<ReplHelper, DefaultHistory> = Editor::new()?;
+    let history = if args.no_history { None } else { history_path() };
+    if let Some(path) = &history {
+        let _ = editor.load_history(path);
+    }
+
+    let v = Php::version();
+    println!("Interactive shell - PHP {}", v.version);
+    println!("Type {} or press Ctrl-D to leave.", style("exit").cyan());
+    println!();
+
+    let mut exit_code = 0;
+    loop {
+        editor.set_helper(Some(ReplHelper::new(&repl)));
+
+        let input = match editor.readline("php > ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(trimmed);
+
+        if matches!(trimmed.trim_end_matches(';'), "exit" | "quit") {
+            break;
+        }
+
+        let outcome = match as_expression(trimmed) {
+            Some(expr) => repl.eval(&expr, true)?,
+            None => repl.eval(&as_statements(trimmed), false)?,
+        };
+
+        if let ReplOutcome::Exit(status) = outcome {
+            exit_code = status;
+            break;
+        }
+    }
+
+    if let Some(path) = &history {
+        let _ = editor.save_history(path);
+    }
+
+    Ok(exit_code)
+}
+
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".pox_history"))
+}
+
+/// Editor helper providing completion from the running request and multi-line input
+struct ReplHelper {
+    /// Snapshot of the names known to PHP when the prompt was shown
+    names: Vec<String>,
+}
+
+impl ReplHelper {
+    fn new(repl: &PhpRepl) -> Self {
+        let mut names = repl.complete("");
+        names.extend(repl.complete("$"));
+        Self { names }
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = word_start(line, pos);
+        let word = &line[start..pos];
+        if word.is_empty() {
+            return Ok((pos, Vec::new()));
+        }
+
+        let lower = word.to_lowercase();
+        let mut candidates: Vec<Pair> = self
+            .names
+            .iter()
+            .filter(|name| name.to_lowercase().starts_with(&lower))
+            .map(|name| Pair {
+                display: name.clone(),
+                replacement: name.clone(),
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.display.cmp(&b.display));
+        candidates.dedup_by(|a, b| a.display == b.display);
+
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        if is_complete(ctx.input()) {
+            Ok(ValidationResult::Valid(None))
+        } else {
+            Ok(ValidationResult::Incomplete)
+        }
+    }
+}
+
+impl Helper for ReplHelper {}
+
+/// Start of the identifier (or `$variable`) ending at `pos`
+fn word_start(line: &str, pos: usize) -> usize {
+    line[..pos]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphanumeric() || matches!(c, '_' | '\\' | '$'))
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(pos)
+}
+
+/// Lexical state while scanning PHP input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scan {
+    Code,
+    SingleQuoted,
+    DoubleQuoted,
+    LineComment,
+    BlockComment,
+}
+
+/// Walk over the input, calling `on_code` for every character outside strings and comments.
+///
+/// Returns the state at the end of the input.
+fn scan(input: &str, mut on_code: impl FnMut(usize, char)) -> Scan {
+    let mut state = Scan::Code;
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match state {
+            Scan::Code => match c {
+                '\'' => state = Scan::SingleQuoted,
+                '"' => state = Scan::DoubleQuoted,
+                '#' if chars.peek().map(|(_, n)| *n) != Some('[') => state = Scan::LineComment,
+                '/' if chars.peek().map(|(_, n)| *n) == Some('/') => state = Scan::LineComment,
+                '/' if chars.peek().map(|(_, n)| *n) == Some('*') => {
+                    chars.next();
+                    state = Scan::BlockComment;
+                }
+                _ => on_code(i, c),
+            },
+            Scan::SingleQuoted | Scan::DoubleQuoted => match c {
+                '\\' => {
+                    chars.next();
+                }
+                '\'' if state == Scan::SingleQuoted => state = Scan::Code,
+                '"' if state == Scan::DoubleQuoted => state = Scan::Code,
+                _ => {}
+            },
+            Scan::LineComment => {
+                if c == '\n' {
+                    state = Scan::Code;
+                }
+            }
+            Scan::BlockComment => {
+                if c == '*' && chars.peek().map(|(_, n)| *n) == Some('/') {
+                    chars.next();
+                    state = Scan::Code;
+                }
+            }
+        }
+    }
+
+    state
+}
+
+/// Whether the input is complete or more lines are needed.
+///
+/// Input is incomplete while brackets are unbalanced or a string or block
+/// comment is still open. Unbalanced closing brackets are left for PHP to report.
+pub fn is_complete(input: &str) -> bool {
+    let mut depth: i32 = 0;
+    let state = scan(input, |_, c| match c {
+        '(' | '[' | '{' => depth += 1,
+        ')' | ']' | '}' => depth -= 1,
+        _ => {}
+    });
+
+    matches!(state, Scan::Code | Scan::LineComment) && depth <= 0
+}
+
+/// Return the input as a single expression whose value can be printed.
+///
+/// A trailing `;` is removed. Input containing several statements or starting
+/// with a statement keyword is not an expression and returns `None`.
+pub fn as_expression(input: &str) -> Option<String> {
+    let code = input.trim().trim_end_matches(';').trim_end();
+    if code.is_empty() {
+        return None;
+    }
+
+    let first_word: String = code
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect::<String>()
+        .to_lowercase();
+    if STATEMENT_KEYWORDS.contains(&first_word.as_str()) {
+        return None;
+    }
+
+    let mut depth: i32 = 0;
+    let mut has_statement_separator = false;
+    let state = scan(code, |_, c| match c {
+        '(' | '[' | '{' => depth += 1,
+        ')' | ']' | '}' => depth -= 1,
+        ';' if depth == 0 => has_statement_separator = true,
+        _ => {}
+    });
+
+    if has_statement_separator || state == Scan::LineComment {
+        return None;
+    }
+
+    Some(code.to_string())
+}
+
+/// Terminate statements with `;` when the user left it out
+fn as_statements(input: &str) -> String {
+    let code = input.trim_end();
+    if code.ends_with(';') || code.ends_with('}') {
+        code.to_string()
+    } else {
+        format!("{};", code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_complete() {
+        assert!(is_complete("1 + 1"));
+        assert!(is_complete("function foo() { return 1; }"));
+        assert!(is_complete("$a = ['{', \"(\"];"));
+        assert!(is_complete("$a = 1; // trailing comment {"));
+
+        assert!(!is_complete("function foo() {"));
+        assert!(!is_complete("$a = [1,\n2"));
+        assert!(!is_complete("$s = 'unterminated"));
+        assert!(!is_complete("$s = \"escaped \\\" quote"));
+        assert!(!is_complete("/* open comment"));
+    }
+
+    #[test]
+    fn test_as_expression() {
+        assert_eq!(as_expression("1 + 1"), Some("1 + 1".to_string()));
+        assert_eq!(as_expression("$a = [1, 2];"), Some("$a = [1, 2]".to_string()));
+        assert_eq!(as_expression("strlen('a;b')"), Some("strlen('a;b')".to_string()));
+        assert_eq!(
+            as_expression("array_map(function ($x) { return $x; }, [1])"),
+            Some("array_map(function ($x) { return $x; }, [1])".to_string())
+        );
+
+        assert_eq!(as_expression("echo 'hi';"), None);
+        assert_eq!(as_expression("function foo() {}"), None);
+        assert_eq!(as_expression("foreach ($a as $b) {}"), None);
+        assert_eq!(as_expression("$a = 1; $b = 2;"), None);
+        assert_eq!(as_expression("   "), None);
+    }
+
+    #[test]
+    fn test_as_statements() {
+        assert_eq!(as_statements("echo 1"), "echo 1;");
+        assert_eq!(as_statements("echo 1;"), "echo 1;");
+        assert_eq!(as_statements("if (true) {}"), "if (true) {}");
+    }
+
+    #[test]
+    fn test_word_start() {
+        assert_eq!(word_start("echo $fo", 8), 5);
+        assert_eq!(word_start("str_rep", 7), 0);
+        assert_eq!(word_start("new App\\Mo", 10), 4);
+        assert_eq!(word_start("foo(", 4), 4);
+    }
+}
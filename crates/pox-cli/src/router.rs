@@ -0,0 +1,196 @@
+//! Script resolution for the standard server, following `php -S`.
+//!
+//! A request path is mapped to a PHP script in the document root together
+//! with the `SCRIPT_NAME` and `PATH_INFO` server variables. Paths that do not
+//! match a script fall back to the `index.php` front controller.
+
+use std::path::{Path, PathBuf};
+
+/// A PHP script handling a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Script {
+    /// Absolute path of the script
+    pub filename: PathBuf,
+    /// URI path of the script (`$_SERVER['SCRIPT_NAME']`)
+    pub script_name: String,
+    /// URI path following the script name (`$_SERVER['PATH_INFO']`)
+    pub path_info: Option<String>,
+}
+
+impl Script {
+    /// A router script, which handles every request path itself
+    pub fn router(filename: &Path, url_path: &str) -> Self {
+        Self {
+            filename: filename.to_path_buf(),
+            script_name: decode(url_path),
+            path_info: None,
+        }
+    }
+
+    /// A script that handles all requests regardless of their path, like a worker script
+    pub fn fixed(document_root: &Path, filename: &Path) -> Self {
+        let script_name = match filename.strip_prefix(document_root) {
+            Ok(relative) => relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .fold(String::new(), |name, c| name + "/" + &c),
+            Err(_) => format!("/{}", filename.file_name().unwrap_or_default().to_string_lossy()),
+        };
+
+        Self {
+            filename: filename.to_path_buf(),
+            script_name,
+            path_info: None,
+        }
+    }
+}
+
+/// Find the script for a request path
+///
+/// `/blog/index.php/post/1` runs `blog/index.php` with path info `/post/1`,
+/// a directory runs its `index.php` and anything else is handled by the
+/// `index.php` in the document root. Returns `None` if no script matches.
+pub fn resolve(document_root: &Path, url_path: &str) -> Option<Script> {
+    let decoded = decode(url_path);
+    let segments: Vec<&str> = decoded
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != ".")
+        .collect();
+    if segments.contains(&"..") {
+        return None;
+    }
+
+    let mut filename = document_root.to_path_buf();
+    for (i, segment) in segments.iter().enumerate() {
+        filename.push(segment);
+
+        if filename.is_file() {
+            if !is_php(&filename) {
+                break;
+            }
+            let rest = &segments[i + 1..];
+            return Some(Script {
+                script_name: join(&segments[..=i]),
+                path_info: (!rest.is_empty()).then(|| join(rest)),
+                filename,
+            });
+        }
+
+        if !filename.is_dir() {
+            break;
+        }
+    }
+
+    // A directory index
+    if filename.is_dir() && filename.join("index.php").is_file() {
+        let mut segments = segments;
+        segments.push("index.php");
+        return Some(Script {
+            filename: filename.join("index.php"),
+            script_name: join(&segments),
+            path_info: None,
+        });
+    }
+
+    // Front controller
+    let index = document_root.join("index.php");
+    if index.is_file() {
+        return Some(Script {
+            filename: index,
+            script_name: "/index.php".to_string(),
+            path_info: (!segments.is_empty()).then(|| join(&segments)),
+        });
+    }
+
+    None
+}
+
+fn decode(url_path: &str) -> String {
+    urlencoding::decode(url_path)
+        .map(|p| p.into_owned())
+        .unwrap_or_else(|_| url_path.to_string())
+}
+
+fn join(segments: &[&str]) -> String {
+    segments.iter().fold(String::new(), |path, s| path + "/" + s)
+}
+
+fn is_php(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("php"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document_root() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("blog")).unwrap();
+        std::fs::create_dir_all(root.join("assets")).unwrap();
+        std::fs::write(root.join("index.php"), "<?php").unwrap();
+        std::fs::write(root.join("info.php"), "<?php").unwrap();
+        std::fs::write(root.join("blog/index.php"), "<?php").unwrap();
+        std::fs::write(root.join("assets/app.css"), "").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_resolve_script_with_path_info() {
+        let dir = document_root();
+        let root = dir.path();
+
+        let script = resolve(root, "/info.php/foo/bar").unwrap();
+        assert_eq!(script.filename, root.join("info.php"));
+        assert_eq!(script.script_name, "/info.php");
+        assert_eq!(script.path_info.as_deref(), Some("/foo/bar"));
+
+        let script = resolve(root, "/info.php").unwrap();
+        assert_eq!(script.script_name, "/info.php");
+        assert_eq!(script.path_info, None);
+
+        let script = resolve(root, "/blog/index.php/post%201").unwrap();
+        assert_eq!(script.script_name, "/blog/index.php");
+        assert_eq!(script.path_info.as_deref(), Some("/post 1"));
+    }
+
+    #[test]
+    fn test_resolve_directory_index_and_front_controller() {
+        let dir = document_root();
+        let root = dir.path();
+
+        let script = resolve(root, "/blog/").unwrap();
+        assert_eq!(script.filename, root.join("blog/index.php"));
+        assert_eq!(script.script_name, "/blog/index.php");
+
+        let script = resolve(root, "/").unwrap();
+        assert_eq!(script.filename, root.join("index.php"));
+        assert_eq!(script.script_name, "/index.php");
+        assert_eq!(script.path_info, None);
+
+        let script = resolve(root, "/products/42").unwrap();
+        assert_eq!(script.filename, root.join("index.php"));
+        assert_eq!(script.script_name, "/index.php");
+        assert_eq!(script.path_info.as_deref(), Some("/products/42"));
+
+        // No script without a front controller, and no traversal
+        std::fs::remove_file(root.join("index.php")).unwrap();
+        assert_eq!(resolve(root, "/products/42"), None);
+        assert_eq!(resolve(root, "/blog/../../etc/passwd"), None);
+    }
+
+    #[test]
+    fn test_fixed_and_router_scripts() {
+        let root = Path::new("/srv/app/public");
+
+        let script = Script::fixed(root, Path::new("/srv/app/public/worker.php"));
+        assert_eq!(script.script_name, "/worker.php");
+
+        let script = Script::fixed(root, Path::new("/srv/app/bin/worker.php"));
+        assert_eq!(script.script_name, "/worker.php");
+
+        let script = Script::router(Path::new("/srv/app/router.php"), "/hello%20world");
+        assert_eq!(script.script_name, "/hello world");
+        assert_eq!(script.path_info, None);
+    }
+}
@@ -0,0 +1,150 @@
+//! Route rules of the server, from `[server.routes]` in pox.toml.
+//!
+//! Rules are glob patterns matched against the decoded request path. Denied
+//! paths are answered with `403 Forbidden` before a static file or PHP is
+//! looked at, static paths are only ever served as files and, when a `php`
+//! list is given, only the scripts on it are executed.
+
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::config::RoutesConfig;
+
+/// How the server may answer a request path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// Answered with `403 Forbidden`
+    Deny,
+    /// Served from the document root, never by PHP
+    Static,
+    /// Served as a static file or by PHP
+    Any,
+}
+
+/// Compiled route rules
+#[derive(Debug, Default)]
+pub struct RouteRules {
+    deny: GlobSet,
+    static_paths: GlobSet,
+    /// Scripts PHP may execute, all when not set
+    php: Option<GlobSet>,
+}
+
+impl RouteRules {
+    pub fn new(config: &RoutesConfig) -> Result<Self> {
+        Ok(Self {
+            // Case-insensitive file systems serve /.GIT/config as well
+            deny: build(&config.deny, true)?,
+            static_paths: build(&config.static_paths, false)?,
+            php: if config.php.is_empty() {
+                None
+            } else {
+                Some(build(&config.php, false)?)
+            },
+        })
+    }
+
+    /// The rule for a request path, as sent by the client
+    pub fn route(&self, url_path: &str) -> Route {
+        let path = normalize(url_path);
+        let matches = |set: &GlobSet| set.is_match(&path) || set.is_match(format!("{}/", path.trim_end_matches('/')));
+
+        if matches(&self.deny) {
+            Route::Deny
+        } else if matches(&self.static_paths) {
+            Route::Static
+        } else {
+            Route::Any
+        }
+    }
+
+    /// Whether PHP may execute the script with this `SCRIPT_NAME`
+    pub fn allows_script(&self, script_name: &str) -> bool {
+        self.php.as_ref().is_none_or(|php| php.is_match(normalize(script_name)))
+    }
+}
+
+/// Build a glob set, unlike the watch patterns an invalid rule is an error
+fn build(patterns: &[String], case_insensitive: bool) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .case_insensitive(case_insensitive)
+            .build()
+            .with_context(|| format!("Invalid route pattern '{}'", pattern))?;
+        builder.add(glob);
+    }
+    Ok(builder.build()?)
+}
+
+/// Decode a request path and drop empty and `.` segments, so encoded or
+/// doubled slashes can't sidestep a rule
+fn normalize(url_path: &str) -> String {
+    let decoded = urlencoding::decode(url_path)
+        .map(|p| p.into_owned())
+        .unwrap_or_else(|_| url_path.to_string());
+
+    let path: String = decoded
+        .split(['/', '\\'])
+        .filter(|s| !s.is_empty() && *s != ".")
+        .fold(String::new(), |path, s| path + "/" + s);
+
+    if path.is_empty() {
+        "/".to_string()
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(deny: &[&str], static_paths: &[&str], php: &[&str]) -> RouteRules {
+        let strings = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect();
+        RouteRules::new(&RoutesConfig {
+            deny: strings(deny),
+            static_paths: strings(static_paths),
+            php: strings(php),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_route() {
+        let rules = rules(&["/.git/**", "/vendor/**", "/*.env"], &["/assets/**"], &[]);
+
+        assert_eq!(rules.route("/.git/config"), Route::Deny);
+        assert_eq!(rules.route("/.git"), Route::Deny);
+        assert_eq!(rules.route("//.git//config"), Route::Deny);
+        assert_eq!(rules.route("/%2Egit/config"), Route::Deny);
+        assert_eq!(rules.route("/./vendor/autoload.php"), Route::Deny);
+        assert_eq!(rules.route("/.GIT/HEAD"), Route::Deny);
+        assert_eq!(rules.route("/.env"), Route::Deny);
+        assert_eq!(rules.route("/assets/app.css"), Route::Static);
+        assert_eq!(rules.route("/assets/upload.php"), Route::Static);
+        assert_eq!(rules.route("/index.php"), Route::Any);
+        assert_eq!(rules.route("/"), Route::Any);
+    }
+
+    #[test]
+    fn test_allows_script() {
+        assert!(RouteRules::default().allows_script("/anything.php"));
+
+        let rules = rules(&[], &[], &["/index.php", "/admin.php"]);
+        assert!(rules.allows_script("/index.php"));
+        assert!(rules.allows_script("/admin.php"));
+        assert!(!rules.allows_script("/phpinfo.php"));
+        assert!(!rules.allows_script("/sub/index.php"));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let config = RoutesConfig {
+            deny: vec!["/[".to_string()],
+            ..Default::default()
+        };
+        assert!(RouteRules::new(&config).is_err());
+    }
+}
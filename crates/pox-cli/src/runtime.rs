@@ -0,0 +1,452 @@
+//! PHP runtime management - select the PHP version of a project.
+//!
+//! PHP is linked into the pox binary, so every PHP version is a pox build of
+//! its own. `pox php install` registers such builds in `~/.pox/php/<version>`,
+//! and `pox php use` pins a version in the project's pox.toml. When the
+//! embedded PHP does not match the pinned version, pox hands the command over
+//! to the matching runtime, so PHP code, the server and the solver's platform
+//! packages all see the selected version.
+
+use anyhow::{bail, Context, Result};
+use clap::{ArgGroup, ArgMatches, Args, Subcommand};
+use console::style;
+use sha2::{Digest, Sha256};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use pox_pm::downloader::{verify_signature, MinisignPublicKey, MinisignSignature};
+use pox_pm::http::HttpClient;
+use pox_pm::ComposerError;
+
+use crate::config::PoxConfig;
+
+/// Set for runtimes started by another pox, they never delegate again
+const DELEGATED_ENV: &str = "POX_PHP_DELEGATED";
+
+/// File name of a runtime inside its version directory
+const RUNTIME_BINARY: &str = if cfg!(windows) { "pox.exe" } else { "pox" };
+
+#[derive(Subcommand, Debug)]
+pub enum PhpCommands {
+    /// List the embedded and installed PHP versions
+    List,
+    /// Install a pox build embedding another PHP version from a URL or file
+    Install(InstallArgs),
+    /// Use a PHP version for the current project (writes pox.toml)
+    Use(UseArgs),
+}
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("verification").required(true).multiple(true).args(["sha256", "signing_key"])))]
+pub struct InstallArgs {
+    /// HTTPS URL or path of a pox binary built against the wanted PHP version
+    #[arg(value_name = "SOURCE")]
+    pub source: String,
+
+    /// Expected SHA-256 checksum of the binary
+    #[arg(long, value_name = "HASH")]
+    pub sha256: Option<String>,
+
+    /// Minisign public key (or key file) used to verify the `.minisig` published next to the binary
+    #[arg(long, value_name = "KEY")]
+    pub signing_key: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct UseArgs {
+    /// PHP version, e.g. 8.2 or 8.2.27
+    #[arg(value_name = "VERSION")]
+    pub version: String,
+}
+
+/// An installed PHP runtime
+#[derive(Debug, Clone, PartialEq)]
+pub struct Runtime {
+    pub version: String,
+    pub binary: PathBuf,
+}
+
+/// Directory holding the installed runtimes
+pub fn runtimes_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".pox").join("php"))
+}
+
+/// The runtimes installed in `dir`, newest version first
+pub fn installed_runtimes(dir: &Path) -> Vec<Runtime> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut runtimes: Vec<Runtime> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let binary = entry.path().join(RUNTIME_BINARY);
+            binary.is_file().then(|| Runtime {
+                version: entry.file_name().to_string_lossy().to_string(),
+                binary,
+            })
+        })
+        .collect();
+    runtimes.sort_by(|a, b| compare_versions(&b.version, &a.version));
+    runtimes
+}
+
+/// Whether `version` satisfies a requested version: "8.2" matches "8.2.27"
+pub fn version_matches(requested: &str, version: &str) -> bool {
+    let requested = requested.trim();
+    version == requested || version.starts_with(&format!("{}.", requested))
+}
+
+/// The newest runtime satisfying the requested version
+pub fn find_runtime<'a>(runtimes: &'a [Runtime], requested: &str) -> Option<&'a Runtime> {
+    runtimes.iter().find(|runtime| version_matches(requested, &runtime.version))
+}
+
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let parts = |v: &str| -> Vec<u64> {
+        v.split('.')
+            .map(|part| part.chars().take_while(|c| c.is_ascii_digit()).collect::<String>().parse().unwrap_or(0))
+            .collect()
+    };
+    parts(a).cmp(&parts(b))
+}
+
+/// The pox.toml of the project a command runs in, found from the
+/// `--working-dir` of the invoked subcommand rather than the process directory
+pub fn project_config(matches: &ArgMatches) -> Result<Option<PoxConfig>> {
+    let cwd = std::env::current_dir()?;
+    let dir = match working_dir(matches) {
+        Some(dir) => dir.canonicalize().unwrap_or_else(|_| cwd.join(dir)),
+        None => cwd,
+    };
+    PoxConfig::load(&dir)
+}
+
+/// `--working-dir` of the innermost subcommand that has one
+fn working_dir(matches: &ArgMatches) -> Option<PathBuf> {
+    let (_, sub) = matches.subcommand()?;
+    working_dir(sub).or_else(|| sub.try_get_one::<PathBuf>("working_dir").ok().flatten().cloned())
+}
+
+/// Hand the current command over to the runtime of the project's PHP version.
+///
+/// Returns the exit code of the runtime, or `None` if the embedded PHP is the
+/// one to use.
+pub fn delegate(config: Option<&PoxConfig>) -> Result<Option<i32>> {
+    let Some(requested) = config.and_then(|c| c.php.version.as_deref()) else {
+        return Ok(None);
+    };
+    if std::env::var_os(DELEGATED_ENV).is_some() {
+        return Ok(None);
+    }
+
+    let embedded = pox_embed::Php::version();
+    if version_matches(requested, embedded.version) {
+        return Ok(None);
+    }
+
+    let runtimes = runtimes_dir().map(|dir| installed_runtimes(&dir)).unwrap_or_default();
+    let Some(runtime) = find_runtime(&runtimes, requested) else {
+        eprintln!(
+            "{} pox.toml requires PHP {}, which is not installed (see `pox php install`); using the embedded PHP {}",
+            style("Warning:").yellow(),
+            requested,
+            embedded.version
+        );
+        return Ok(None);
+    };
+
+    log::debug!("Delegating to PHP {} at {}", runtime.version, runtime.binary.display());
+
+    let args: Vec<OsString> = std::env::args_os().skip(1).collect();
+    let mut command = Command::new(&runtime.binary);
+    command.args(&args).env(DELEGATED_ENV, "1");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        let error = command.exec();
+        Err(error).with_context(|| format!("Failed to start {}", runtime.binary.display()))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let status = command.status()
+            .with_context(|| format!("Failed to start {}", runtime.binary.display()))?;
+        Ok(Some(status.code().unwrap_or(1)))
+    }
+}
+
+/// Execute a php command
+pub async fn execute(command: PhpCommands) -> Result<i32> {
+    let dir = runtimes_dir().context("Cannot locate the home directory to store PHP runtimes in")?;
+
+    match command {
+        PhpCommands::List => list(&dir),
+        PhpCommands::Install(args) => install(&dir, args).await,
+        PhpCommands::Use(args) => use_version(&dir, args),
+    }
+}
+
+fn list(dir: &Path) -> Result<i32> {
+    let config = PoxConfig::load_from_cwd()?;
+    let requested = config.as_ref().and_then(|c| c.php.version.clone());
+    let runtimes = installed_runtimes(dir);
+    let embedded = pox_embed::Php::version().version.to_string();
+
+    // The runtime a command in this directory runs with
+    let active = match &requested {
+        Some(requested) if !version_matches(requested, &embedded) => {
+            find_runtime(&runtimes, requested).map(|r| r.version.clone())
+        }
+        _ => None,
+    }
+    .unwrap_or_else(|| embedded.clone());
+
+    let marker = |version: &str| if version == active { style("*").green().bold().to_string() } else { " ".to_string() };
+
+    println!("{} {:10} {}", marker(&embedded), embedded, style("embedded").dim());
+    for runtime in &runtimes {
+        println!("{} {:10} {}", marker(&runtime.version), runtime.version, style(runtime.binary.display()).dim());
+    }
+
+    if let Some(requested) = requested {
+        println!();
+        println!("pox.toml requires PHP {}", requested);
+    }
+    Ok(0)
+}
+
+async fn install(dir: &Path, args: InstallArgs) -> Result<i32> {
+    if args.source.starts_with("http://") {
+        bail!("Refusing to download a runtime over plain http, use an https URL");
+    }
+
+    let (data, minisig) = if args.source.starts_with("https://") {
+        println!("{} Downloading {}", style("Info:").cyan(), args.source);
+        let client = HttpClient::new().context("Failed to create HTTP client")?;
+        let data = client.download_bytes(&args.source).await
+            .map_err(|e| ComposerError::from_http(&args.source, e))?;
+        let minisig = match args.signing_key {
+            Some(_) => {
+                let url = format!("{}.minisig", args.source);
+                let sig = client.download_bytes(&url).await.map_err(|e| ComposerError::from_http(&url, e))?;
+                Some(String::from_utf8_lossy(&sig).into_owned())
+            }
+            None => None,
+        };
+        (data, minisig)
+    } else {
+        let data = std::fs::read(&args.source).with_context(|| format!("Failed to read {}", args.source))?;
+        let minisig = match args.signing_key {
+            Some(_) => {
+                let sig_path = format!("{}.minisig", args.source);
+                Some(std::fs::read_to_string(&sig_path).with_context(|| format!("Failed to read {}", sig_path))?)
+            }
+            None => None,
+        };
+        (data, minisig)
+    };
+
+    // Nothing is made executable, let alone run, before it is verified
+    verify_runtime(&data, &args, minisig.as_deref())?;
+
+    std::fs::create_dir_all(dir)?;
+    let download = tempfile::NamedTempFile::new_in(dir).context("Failed to create temporary file")?;
+    std::fs::write(download.path(), &data)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(download.path(), std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    let version = runtime_version(download.path())
+        .with_context(|| format!("{} is not a pox binary", args.source))?;
+
+    let target_dir = dir.join(&version);
+    std::fs::create_dir_all(&target_dir)?;
+    let binary = target_dir.join(RUNTIME_BINARY);
+    download.persist(&binary)
+        .with_context(|| format!("Failed to write {}", binary.display()))?;
+
+    println!("{} Installed PHP {} to {}", style("Success:").green().bold(), version, binary.display());
+    println!("Use it in a project with `pox php use {}`", version);
+    Ok(0)
+}
+
+/// Check a runtime against `--sha256` and, with `--signing-key`, its minisign signature
+fn verify_runtime(data: &[u8], args: &InstallArgs, minisig: Option<&str>) -> Result<()> {
+    if let Some(expected) = &args.sha256 {
+        let actual = format!("{:x}", Sha256::digest(data));
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            bail!("SHA-256 checksum mismatch (expected {}, got {})", expected, actual);
+        }
+    }
+
+    if let Some(key) = &args.signing_key {
+        let minisig = minisig.context("Runtime signature missing")?;
+        let key = std::fs::read_to_string(key).unwrap_or_else(|_| key.clone());
+        let key = MinisignPublicKey::parse(&key).context("Invalid signing key")?;
+        let signature = MinisignSignature::parse(minisig).context("Invalid runtime signature")?;
+        verify_signature(data, &signature, &[key]).context("Runtime signature verification failed")?;
+    }
+
+    Ok(())
+}
+
+/// PHP version embedded in a pox binary
+fn runtime_version(binary: &Path) -> Result<String> {
+    let output = Command::new(binary)
+        .args(["-r", "echo PHP_VERSION;"])
+        .env(DELEGATED_ENV, "1")
+        .output()?;
+    if !output.status.success() {
+        bail!("it exited with {}", output.status);
+    }
+
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if version.is_empty() || !version.starts_with(|c: char| c.is_ascii_digit()) {
+        bail!("it reported no PHP version");
+    }
+    Ok(version)
+}
+
+fn use_version(dir: &Path, args: UseArgs) -> Result<i32> {
+    let embedded = pox_embed::Php::version().version;
+    let runtimes = installed_runtimes(dir);
+
+    let resolved = if version_matches(&args.version, embedded) {
+        embedded.to_string()
+    } else if let Some(runtime) = find_runtime(&runtimes, &args.version) {
+        runtime.version.clone()
+    } else {
+        eprintln!(
+            "{} PHP {} is not installed, install a pox build for it with `pox php install <url> --sha256 <hash>`",
+            style("Error:").red().bold(),
+            args.version
+        );
+        return Ok(1);
+    };
+
+    let cwd = std::env::current_dir()?;
+    let config_path = PoxConfig::find(&cwd).unwrap_or_else(|| cwd.join("pox.toml"));
+    let content = if config_path.exists() { std::fs::read_to_string(&config_path)? } else { String::new() };
+    std::fs::write(&config_path, set_php_version(&content, &args.version))
+        .with_context(|| format!("Failed to write {}", config_path.display()))?;
+
+    println!(
+        "{} Using PHP {} ({}) in {}",
+        style("Success:").green().bold(),
+        args.version,
+        resolved,
+        config_path.display()
+    );
+    Ok(0)
+}
+
+/// Set `version` in the `[php]` table of a pox.toml, keeping everything else as is
+fn set_php_version(content: &str, version: &str) -> String {
+    let entry = format!("version = \"{}\"", version);
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+
+    match lines.iter().position(|line| line.trim() == "[php]") {
+        Some(table) => {
+            let end = lines[table + 1..]
+                .iter()
+                .position(|line| line.trim_start().starts_with('['))
+                .map_or(lines.len(), |pos| table + 1 + pos);
+            let existing = (table + 1..end).find(|&i| {
+                lines[i].split_once('=').is_some_and(|(key, _)| key.trim() == "version")
+            });
+            match existing {
+                Some(i) => lines[i] = entry,
+                None => lines.insert(table + 1, entry),
+            }
+        }
+        None => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push("[php]".to_string());
+            lines.push(entry);
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_matches() {
+        assert!(version_matches("8.2", "8.2.27"));
+        assert!(version_matches("8.2.27", "8.2.27"));
+        assert!(!version_matches("8.2", "8.20.1"));
+        assert!(!version_matches("8.3", "8.2.27"));
+    }
+
+    #[test]
+    fn test_installed_runtimes() {
+        let dir = tempfile::tempdir().unwrap();
+        for version in ["8.2.9", "8.2.27", "8.3.1"] {
+            std::fs::create_dir_all(dir.path().join(version)).unwrap();
+            std::fs::write(dir.path().join(version).join(RUNTIME_BINARY), "").unwrap();
+        }
+        std::fs::create_dir_all(dir.path().join("8.4.0")).unwrap();
+
+        let runtimes = installed_runtimes(dir.path());
+        let versions: Vec<_> = runtimes.iter().map(|r| r.version.as_str()).collect();
+        assert_eq!(versions, vec!["8.3.1", "8.2.27", "8.2.9"]);
+        assert_eq!(find_runtime(&runtimes, "8.2").unwrap().version, "8.2.27");
+        assert!(find_runtime(&runtimes, "8.4").is_none());
+    }
+
+    #[test]
+    fn test_working_dir_of_subcommand() {
+        let command = clap::Command::new("pox")
+            .subcommand(clap::Command::new("install").arg(
+                clap::Arg::new("working_dir").short('d').long("working-dir").value_parser(clap::value_parser!(PathBuf)),
+            ))
+            .subcommand(clap::Command::new("server"));
+
+        let matches = command.clone().get_matches_from(["pox", "install", "-d", "/srv/app"]);
+        assert_eq!(working_dir(&matches), Some(PathBuf::from("/srv/app")));
+
+        let matches = command.get_matches_from(["pox", "server"]);
+        assert_eq!(working_dir(&matches), None);
+    }
+
+    #[test]
+    fn test_verify_runtime_checksum() {
+        let args = |sha256: &str| InstallArgs {
+            source: "https://example.com/pox".to_string(),
+            sha256: Some(sha256.to_string()),
+            signing_key: None,
+        };
+        let digest = format!("{:x}", Sha256::digest(b"binary"));
+
+        verify_runtime(b"binary", &args(&digest.to_uppercase()), None).unwrap();
+        assert!(verify_runtime(b"tampered", &args(&digest), None).is_err());
+    }
+
+    #[test]
+    fn test_set_php_version() {
+        assert_eq!(set_php_version("", "8.2"), "[php]\nversion = \"8.2\"\n");
+
+        let config = "[server]\nport = 9000\n";
+        assert_eq!(set_php_version(config, "8.2"), "[server]\nport = 9000\n\n[php]\nversion = \"8.2\"\n");
+
+        let config = "[php]\nversion = \"8.1\"\n\n[php.ini]\nmemory_limit = \"1G\"\n";
+        let updated = set_php_version(config, "8.3");
+        assert_eq!(updated, "[php]\nversion = \"8.3\"\n\n[php.ini]\nmemory_limit = \"1G\"\n");
+
+        let config = "[php.ini]\nversion = \"1\"\n\n[php]\n";
+        let parsed: PoxConfig = toml::from_str(&set_php_version(config, "8.2")).unwrap();
+        assert_eq!(parsed.php.version.as_deref(), Some("8.2"));
+        assert_eq!(parsed.php.ini.get("version").map(String::as_str), Some("1"));
+    }
+}
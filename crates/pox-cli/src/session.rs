@@ -0,0 +1,138 @@
+//! Per instance session and temp directories for server mode.
+//!
+//! Every `pox server` gets its own `session.save_path`, `upload_tmp_dir` and
+//! `sys_temp_dir`, so dev servers running side by side don't share session
+//! files or uploaded temp files. The directories of an instance are removed
+//! when the server shuts down, a `--session-path` given by the user is kept.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// Session and temp directories of one server instance
+#[derive(Debug)]
+pub struct ServerDirs {
+    /// Directory owned by this instance, removed on shutdown
+    root: PathBuf,
+    session_path: PathBuf,
+    temp_dir: PathBuf,
+}
+
+impl ServerDirs {
+    /// Create the directories of a server listening on `port` in the system temp dir
+    pub fn create(port: u16, session_path: Option<&Path>) -> Result<Self> {
+        let root = std::env::temp_dir()
+            .join("pox-server")
+            .join(format!("{}-{}", port, std::process::id()));
+        Self::create_in(root, session_path)
+    }
+
+    fn create_in(root: PathBuf, session_path: Option<&Path>) -> Result<Self> {
+        let temp_dir = root.join("tmp");
+        let session_path = match session_path {
+            Some(path) => std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+            None => root.join("sessions"),
+        };
+
+        for dir in [&temp_dir, &session_path] {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+
+        Ok(Self { root, session_path, temp_dir })
+    }
+
+    pub fn session_path(&self) -> &Path {
+        &self.session_path
+    }
+
+    /// INI entries pointing PHP at the directories of this instance
+    pub fn ini_entries(&self) -> Vec<String> {
+        vec![
+            format!("session.save_path={}", self.session_path.display()),
+            format!("upload_tmp_dir={}", self.temp_dir.display()),
+            format!("sys_temp_dir={}", self.temp_dir.display()),
+        ]
+    }
+
+    /// Remove the directories when the server is stopped with Ctrl-C or SIGTERM
+    pub fn remove_on_shutdown(&self) {
+        let root = self.root.clone();
+        std::thread::spawn(move || {
+            let Ok(rt) = tokio::runtime::Builder::new_current_thread().enable_all().build() else {
+                return;
+            };
+            let code = rt.block_on(shutdown_signal());
+            let _ = std::fs::remove_dir_all(&root);
+            std::process::exit(code);
+        });
+    }
+}
+
+impl Drop for ServerDirs {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Wait for Ctrl-C or SIGTERM, returning the shell's exit code for the signal
+async fn shutdown_signal() -> i32 {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => 130,
+                    _ = terminate.recv() => 143,
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+                130
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        130
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance_dirs_are_removed() {
+        let base = tempfile::tempdir().unwrap();
+        let root = base.path().join("8000-1");
+
+        let dirs = ServerDirs::create_in(root.clone(), None).unwrap();
+        assert!(dirs.session_path().starts_with(&root));
+        assert!(dirs.session_path().is_dir());
+
+        let entries = dirs.ini_entries();
+        assert_eq!(entries[0], format!("session.save_path={}", root.join("sessions").display()));
+        assert_eq!(entries[1], format!("upload_tmp_dir={}", root.join("tmp").display()));
+
+        drop(dirs);
+        assert!(!root.exists());
+    }
+
+    #[test]
+    fn test_custom_session_path_is_kept() {
+        let base = tempfile::tempdir().unwrap();
+        let sessions = base.path().join("sessions");
+
+        let dirs = ServerDirs::create_in(base.path().join("8000-1"), Some(&sessions)).unwrap();
+        assert_eq!(dirs.session_path(), sessions);
+
+        drop(dirs);
+        assert!(sessions.is_dir());
+        assert!(!base.path().join("8000-1").exists());
+    }
+}
@@ -0,0 +1,544 @@
+//! Static file serving for server mode.
+//!
+//! Handles conditional requests (`ETag`, `Last-Modified`), single byte ranges
+//! and gzip/brotli compression of text assets. Files are streamed from disk
+//! unless they are compressed on the fly.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tiny_http::{Header, Response, StatusCode};
+
+/// Files smaller than this are not worth compressing
+const MIN_COMPRESS_SIZE: u64 = 1024;
+
+/// Larger files are streamed as they are instead of being compressed in memory
+const MAX_COMPRESS_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Result of looking up a request path in the document root
+#[derive(Debug)]
+pub enum Lookup {
+    /// A static file to serve
+    File(StaticFile),
+    /// The path escapes the document root
+    Forbidden,
+    /// No static file, the request goes to PHP
+    NotFound,
+}
+
+/// A file in the document root together with its metadata
+#[derive(Debug)]
+pub struct StaticFile {
+    pub path: PathBuf,
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+    pub content_type: &'static str,
+}
+
+impl StaticFile {
+    /// Strong validator derived from size and modification time
+    pub fn etag(&self) -> String {
+        let modified = self
+            .modified
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos());
+        format!("\"{:x}-{:x}\"", self.len, modified)
+    }
+
+    /// ETag of the representation sent with `encoding`
+    fn encoded_etag(&self, encoding: Option<Encoding>) -> String {
+        let etag = self.etag();
+        match encoding {
+            Some(encoding) => format!("{}-{}\"", etag.trim_end_matches('"'), encoding.name()),
+            None => etag,
+        }
+    }
+}
+
+/// Content codings supported for compression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        }
+    }
+}
+
+/// Request headers relevant for static files
+#[derive(Debug, Default)]
+pub struct Conditions<'a> {
+    pub range: Option<&'a str>,
+    pub if_range: Option<&'a str>,
+    pub if_none_match: Option<&'a str>,
+    pub if_modified_since: Option<&'a str>,
+    pub accept_encoding: Option<&'a str>,
+}
+
+impl<'a> Conditions<'a> {
+    pub fn from_request(request: &'a crate::http::Request) -> Self {
+        let header = |name: &'static str| {
+            request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv(name))
+                .map(|h| h.value.as_str())
+        };
+
+        Self {
+            range: header("Range"),
+            if_range: header("If-Range"),
+            if_none_match: header("If-None-Match"),
+            if_modified_since: header("If-Modified-Since"),
+            accept_encoding: header("Accept-Encoding"),
+        }
+    }
+}
+
+/// How a static file request is answered
+#[derive(Debug, PartialEq, Eq)]
+pub enum Plan {
+    /// The client's cached copy is still valid
+    NotModified,
+    /// The full file, optionally compressed
+    Full(Option<Encoding>),
+    /// Bytes `start..=end` of the file
+    Partial(u64, u64),
+    /// The requested range lies outside the file
+    RangeNotSatisfiable,
+}
+
+/// Look up a request path in the document root
+///
+/// `url_path` is the percent-encoded path of the request. PHP files are never
+/// served as static files.
+pub fn lookup(document_root: &Path, url_path: &str) -> Lookup {
+    let Ok(decoded) = urlencoding::decode(url_path) else {
+        return Lookup::NotFound;
+    };
+
+    let relative = Path::new(decoded.trim_start_matches('/'));
+    if decoded.contains('\0')
+        || relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Lookup::Forbidden;
+    }
+
+    let path = document_root.join(relative);
+    if !path.is_file() || path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("php")) {
+        return Lookup::NotFound;
+    }
+
+    // Symlinks may still point outside of the document root
+    let (Ok(root), Ok(path)) = (document_root.canonicalize(), path.canonicalize()) else {
+        return Lookup::NotFound;
+    };
+    if !path.starts_with(&root) {
+        return Lookup::Forbidden;
+    }
+
+    let Ok(metadata) = path.metadata() else {
+        return Lookup::NotFound;
+    };
+
+    Lookup::File(StaticFile {
+        content_type: content_type(&path),
+        len: metadata.len(),
+        modified: metadata.modified().ok(),
+        path,
+    })
+}
+
+/// Decide how to answer a request for `file`
+pub fn plan(file: &StaticFile, conditions: &Conditions) -> Plan {
+    if is_not_modified(file, conditions) {
+        return Plan::NotModified;
+    }
+
+    // A range only applies if the client's copy is still current
+    let range_applies = conditions
+        .if_range
+        .is_none_or(|validator| validator.trim() == file.etag() || is_unchanged_since(file, validator));
+    if let Some(range) = conditions.range.filter(|_| range_applies) {
+        match parse_range(range, file.len) {
+            Some(Ok((start, end))) => return Plan::Partial(start, end),
+            Some(Err(())) => return Plan::RangeNotSatisfiable,
+            None => {}
+        }
+    }
+
+    let compress = is_compressible(file.content_type)
+        && (MIN_COMPRESS_SIZE..=MAX_COMPRESS_SIZE).contains(&file.len);
+    let encoding = conditions
+        .accept_encoding
+        .filter(|_| compress)
+        .and_then(preferred_encoding);
+
+    Plan::Full(encoding)
+}
+
+/// Serve a static file, streaming it from disk where possible
+pub fn serve(request: crate::http::Request, file: &StaticFile, method: &str, url: &str) {
+    let plan = plan(file, &Conditions::from_request(&request));
+
+    let mut headers = vec![header("Accept-Ranges", "bytes")];
+    if let Some(modified) = file.modified {
+        headers.push(header("Last-Modified", &httpdate::fmt_http_date(modified)));
+    }
+    if is_compressible(file.content_type) {
+        headers.push(header("Vary", "Accept-Encoding"));
+    }
+
+    let status = match send(request, file, &plan, headers) {
+        Ok(status) => status,
+        Err(e) => {
+            eprintln!("Failed to serve {}: {}", file.path.display(), e);
+            return;
+        }
+    };
+    println!("{} {} - {}", method, url, status);
+}
+
+fn send(request: crate::http::Request, file: &StaticFile, plan: &Plan, mut headers: Vec<Header>) -> std::io::Result<u16> {
+    match *plan {
+        Plan::NotModified => {
+            headers.push(header("ETag", &file.etag()));
+            respond(request, Response::empty(304), headers)?;
+            Ok(304)
+        }
+        Plan::RangeNotSatisfiable => {
+            headers.push(header("Content-Range", &format!("bytes */{}", file.len)));
+            respond(request, Response::empty(416), headers)?;
+            Ok(416)
+        }
+        Plan::Partial(start, end) => {
+            let mut reader = File::open(&file.path)?;
+            reader.seek(SeekFrom::Start(start))?;
+            let len = end - start + 1;
+
+            headers.push(header("ETag", &file.etag()));
+            headers.push(header("Content-Type", file.content_type));
+            headers.push(header("Content-Range", &format!("bytes {}-{}/{}", start, end, file.len)));
+            let response = Response::new(StatusCode(206), headers, reader.take(len), Some(len as usize), None);
+            request.respond(response)?;
+            Ok(206)
+        }
+        Plan::Full(Some(encoding)) => {
+            let body = compress(&std::fs::read(&file.path)?, encoding)?;
+
+            headers.push(header("ETag", &file.encoded_etag(Some(encoding))));
+            headers.push(header("Content-Type", file.content_type));
+            headers.push(header("Content-Encoding", encoding.name()));
+            respond(request, Response::from_data(body), headers)?;
+            Ok(200)
+        }
+        Plan::Full(None) => {
+            headers.push(header("ETag", &file.etag()));
+            headers.push(header("Content-Type", file.content_type));
+            respond(request, Response::from_file(File::open(&file.path)?), headers)?;
+            Ok(200)
+        }
+    }
+}
+
+fn respond<R: Read>(request: crate::http::Request, mut response: Response<R>, headers: Vec<Header>) -> std::io::Result<()> {
+    for header in headers {
+        response.add_header(header);
+    }
+    request.respond(response)
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("valid static file header")
+}
+
+/// `If-None-Match` takes precedence over `If-Modified-Since`
+fn is_not_modified(file: &StaticFile, conditions: &Conditions) -> bool {
+    if let Some(if_none_match) = conditions.if_none_match {
+        let etag = file.etag();
+        return if_none_match.split(',').map(str::trim).any(|tag| {
+            let tag = tag.trim_start_matches("W/");
+            tag == "*"
+                || tag == etag
+                || [Encoding::Brotli, Encoding::Gzip]
+                    .iter()
+                    .any(|encoding| tag == file.encoded_etag(Some(*encoding)))
+        });
+    }
+
+    conditions
+        .if_modified_since
+        .is_some_and(|since| is_unchanged_since(file, since))
+}
+
+fn is_unchanged_since(file: &StaticFile, date: &str) -> bool {
+    let (Some(modified), Ok(since)) = (file.modified, httpdate::parse_http_date(date.trim())) else {
+        return false;
+    };
+    // HTTP dates have a resolution of one second
+    let truncated = modified
+        .duration_since(UNIX_EPOCH)
+        .map(|d| UNIX_EPOCH + std::time::Duration::from_secs(d.as_secs()))
+        .unwrap_or(modified);
+    truncated <= since
+}
+
+/// Parse a `Range` header for a file of `len` bytes
+///
+/// Returns `None` when the header should be ignored (unsupported unit or
+/// multiple ranges), `Some(Err(()))` when no requested byte exists and
+/// otherwise the inclusive byte range.
+pub fn parse_range(range: &str, len: u64) -> Option<Result<(u64, u64), ()>> {
+    let spec = range.trim().strip_prefix("bytes=")?.trim();
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    if start.is_empty() {
+        // Suffix range: the last `end` bytes
+        let suffix: u64 = end.parse().ok()?;
+        if suffix == 0 || len == 0 {
+            return Some(Err(()));
+        }
+        return Some(Ok((len.saturating_sub(suffix), len - 1)));
+    }
+
+    let start: u64 = start.parse().ok()?;
+    let end: u64 = if end.is_empty() { u64::MAX } else { end.parse().ok()? };
+    if end < start {
+        return None;
+    }
+    if start >= len {
+        return Some(Err(()));
+    }
+    Some(Ok((start, end.min(len - 1))))
+}
+
+/// Pick the best supported encoding from an `Accept-Encoding` header
+pub fn preferred_encoding(accept_encoding: &str) -> Option<Encoding> {
+    let mut best: Option<(Encoding, f32)> = None;
+
+    for item in accept_encoding.split(',') {
+        let mut parts = item.split(';');
+        let coding = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        let quality = parts
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        let encoding = match coding.as_str() {
+            "br" => Encoding::Brotli,
+            "gzip" | "x-gzip" => Encoding::Gzip,
+            _ => continue,
+        };
+        if quality <= 0.0 {
+            continue;
+        }
+
+        // Brotli wins ties, it compresses text assets better
+        let better = match best {
+            None => true,
+            Some((current, q)) => quality > q || (quality == q && encoding == Encoding::Brotli && current != encoding),
+        };
+        if better {
+            best = Some((encoding, quality));
+        }
+    }
+
+    best.map(|(encoding, _)| encoding)
+}
+
+fn compress(data: &[u8], encoding: Encoding) -> std::io::Result<Vec<u8>> {
+    match encoding {
+        Encoding::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(data)?;
+            encoder.finish()
+        }
+        Encoding::Brotli => {
+            let mut output = Vec::new();
+            {
+                let mut encoder = brotli::CompressorWriter::new(&mut output, 4096, 5, 22);
+                encoder.write_all(data)?;
+            }
+            Ok(output)
+        }
+    }
+}
+
+fn is_compressible(content_type: &str) -> bool {
+    content_type.starts_with("text/")
+        || matches!(
+            content_type,
+            "application/javascript" | "application/json" | "application/xml" | "image/svg+xml"
+        )
+}
+
+/// Guess the content type from the file extension
+pub fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+
+    match extension.to_lowercase().as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "application/javascript",
+        "json" | "map" => "application/json",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "txt" => "text/plain",
+        "xml" => "application/xml",
+        "pdf" => "application/pdf",
+        "wasm" => "application/wasm",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mp3" => "audio/mpeg",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn file(len: u64, content_type: &'static str) -> StaticFile {
+        StaticFile {
+            path: PathBuf::from("/srv/app.js"),
+            len,
+            modified: Some(UNIX_EPOCH + Duration::from_millis(1_700_000_000_500)),
+            content_type,
+        }
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some(Ok((0, 99))));
+        assert_eq!(parse_range("bytes=900-", 1000), Some(Ok((900, 999))));
+        assert_eq!(parse_range("bytes=-100", 1000), Some(Ok((900, 999))));
+        assert_eq!(parse_range("bytes=-5000", 1000), Some(Ok((0, 999))));
+        assert_eq!(parse_range("bytes=500-5000", 1000), Some(Ok((500, 999))));
+        assert_eq!(parse_range("bytes=1000-", 1000), Some(Err(())));
+        assert_eq!(parse_range("bytes=-0", 1000), Some(Err(())));
+
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+        assert_eq!(parse_range("bytes=9-1", 1000), None);
+        assert_eq!(parse_range("bytes=a-b", 1000), None);
+    }
+
+    #[test]
+    fn test_preferred_encoding() {
+        assert_eq!(preferred_encoding("gzip, deflate, br"), Some(Encoding::Brotli));
+        assert_eq!(preferred_encoding("gzip"), Some(Encoding::Gzip));
+        assert_eq!(preferred_encoding("br;q=0.5, gzip;q=0.8"), Some(Encoding::Gzip));
+        assert_eq!(preferred_encoding("br;q=0, gzip;q=0"), None);
+        assert_eq!(preferred_encoding("identity"), None);
+    }
+
+    #[test]
+    fn test_plan_conditional_requests() {
+        let file = file(4096, "application/javascript");
+        let etag = file.etag();
+
+        let conditions = Conditions { if_none_match: Some(&etag), ..Default::default() };
+        assert_eq!(plan(&file, &conditions), Plan::NotModified);
+
+        let gzip_etag = file.encoded_etag(Some(Encoding::Gzip));
+        let conditions = Conditions { if_none_match: Some(&gzip_etag), ..Default::default() };
+        assert_eq!(plan(&file, &conditions), Plan::NotModified);
+
+        let conditions = Conditions { if_none_match: Some("\"other\""), ..Default::default() };
+        assert_eq!(plan(&file, &conditions), Plan::Full(None));
+
+        let since = httpdate::fmt_http_date(file.modified.unwrap());
+        let conditions = Conditions { if_modified_since: Some(&since), ..Default::default() };
+        assert_eq!(plan(&file, &conditions), Plan::NotModified);
+
+        let conditions = Conditions {
+            if_modified_since: Some("Sun, 06 Nov 1994 08:49:37 GMT"),
+            ..Default::default()
+        };
+        assert_eq!(plan(&file, &conditions), Plan::Full(None));
+    }
+
+    #[test]
+    fn test_plan_ranges_and_compression() {
+        let js = file(4096, "application/javascript");
+
+        let conditions = Conditions { range: Some("bytes=0-9"), accept_encoding: Some("gzip"), ..Default::default() };
+        assert_eq!(plan(&js, &conditions), Plan::Partial(0, 9));
+
+        let conditions = Conditions { range: Some("bytes=0-9"), if_range: Some("\"stale\""), ..Default::default() };
+        assert_eq!(plan(&js, &conditions), Plan::Full(None));
+
+        let conditions = Conditions { range: Some("bytes=5000-"), ..Default::default() };
+        assert_eq!(plan(&js, &conditions), Plan::RangeNotSatisfiable);
+
+        let conditions = Conditions { accept_encoding: Some("gzip, br"), ..Default::default() };
+        assert_eq!(plan(&js, &conditions), Plan::Full(Some(Encoding::Brotli)));
+        assert_eq!(plan(&file(4096, "image/png"), &conditions), Plan::Full(None));
+        assert_eq!(plan(&file(100, "text/css"), &conditions), Plan::Full(None));
+    }
+
+    #[test]
+    fn test_lookup_rejects_traversal() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("public");
+        std::fs::create_dir_all(root.join("css")).unwrap();
+        std::fs::write(root.join("css/app.css"), "body {}").unwrap();
+        std::fs::write(root.join("index.php"), "<?php").unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+
+        match lookup(&root, "/css/app%2Ecss") {
+            Lookup::File(file) => {
+                assert_eq!(file.len, 7);
+                assert_eq!(file.content_type, "text/css");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        assert!(matches!(lookup(&root, "/../secret.txt"), Lookup::Forbidden));
+        assert!(matches!(lookup(&root, "/css/%2e%2e/%2e%2e/secret.txt"), Lookup::Forbidden));
+        assert!(matches!(lookup(&root, "/index.php"), Lookup::NotFound));
+        assert!(matches!(lookup(&root, "/missing.js"), Lookup::NotFound));
+        assert!(matches!(lookup(&root, "/css"), Lookup::NotFound));
+    }
+
+    #[test]
+    fn test_compress_round_trip() {
+        let data = "body { color: red; }\n".repeat(100);
+
+        let gzip = compress(data.as_bytes(), Encoding::Gzip).unwrap();
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(&gzip[..]).read_to_string(&mut decoded).unwrap();
+        assert_eq!(decoded, data);
+
+        let br = compress(data.as_bytes(), Encoding::Brotli).unwrap();
+        let mut decoded = String::new();
+        brotli::Decompressor::new(&br[..], 4096).read_to_string(&mut decoded).unwrap();
+        assert_eq!(decoded, data);
+        assert!(br.len() < data.len());
+    }
+}
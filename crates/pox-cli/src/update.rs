@@ -3,20 +3,28 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use console::style;
+use dialoguer::{theme::ColorfulTheme, MultiSelect};
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use pox_pm::{
-    ComposerBuilder,
+    Composer, ComposerBuilder, Package,
     config::Config,
     installer::Installer,
+    is_platform_package,
     json::{ComposerJson, ComposerLock},
+    package::Stability,
+    solver::UpdateAllowTransitiveDeps,
 };
+use pox_pm::config::{composer_file_name, lock_file_name};
+use pox_pm::io::Verbosity;
+use pox_semver::Comparator;
 
 use crate::pm::platform::PlatformInfo;
 
 #[derive(Args, Debug)]
 pub struct UpdateArgs {
-    /// Packages to update (all if not specified)
+    /// Packages to update (all if not specified), may contain wildcards like vendor/*
     #[arg(value_name = "PACKAGES")]
     pub packages: Vec<String>,
 
@@ -28,6 +36,10 @@ pub struct UpdateArgs {
     #[arg(long)]
     pub prefer_dist: bool,
 
+    /// Skip checksum and signature verification of downloaded archives
+    #[arg(long)]
+    pub no_verify: bool,
+
     /// Run in dry-run mode
     #[arg(long)]
     pub dry_run: bool,
@@ -64,6 +76,10 @@ pub struct UpdateArgs {
     #[arg(long)]
     pub prefer_lowest: bool,
 
+    /// Break ties between equally preferred packages with this seed (env: POX_SOLVER_SEED)
+    #[arg(long, value_name = "SEED")]
+    pub seed: Option<u64>,
+
     /// Only update the lock file
     #[arg(long)]
     pub lock: bool,
@@ -72,6 +88,10 @@ pub struct UpdateArgs {
     #[arg(short = 'o', long)]
     pub optimize_autoloader: bool,
 
+    /// Interactively select the packages to update
+    #[arg(short = 'i', long)]
+    pub interactive: bool,
+
     /// Working directory
     #[arg(short = 'd', long, default_value = ".")]
     pub working_dir: PathBuf,
@@ -85,18 +105,6 @@ pub struct UpdateArgs {
     #[arg(long)]
     pub no_ansi: bool,
 
-    /// Do not ask any interactive question
-    #[arg(short = 'n', long)]
-    pub no_interaction: bool,
-
-    /// Do not output any message
-    #[arg(short = 'q', long)]
-    pub quiet: bool,
-
-    /// Increase verbosity (-v, -vv, -vvv)
-    #[arg(short = 'v', long, action = clap::ArgAction::Count)]
-    pub verbose: u8,
-
     /// Skip the audit step after update (env: COMPOSER_NO_AUDIT)
     #[arg(long)]
     pub no_audit: bool,
@@ -107,33 +115,19 @@ pub struct UpdateArgs {
 }
 
 pub async fn execute(args: UpdateArgs) -> Result<i32> {
-    let skip_audit = args.no_audit || std::env::var("COMPOSER_NO_AUDIT").unwrap_or_default() == "1";
+    let io = crate::io::io();
 
-    // Initialize logger based on verbosity level
-    // Only enable verbose logging for pox crates, not dependencies
-    let log_level = match args.verbose {
-        0 => log::LevelFilter::Warn,
-        1 => log::LevelFilter::Info,
-        2 => log::LevelFilter::Debug,
-        _ => log::LevelFilter::Trace,
-    };
-    env_logger::Builder::new()
-        .filter_level(log::LevelFilter::Warn)
-        .filter_module("pox_pm", log_level)
-        .filter_module("pox_cli", log_level)
-        .format_timestamp(None)
-        .format_target(false)
-        .init();
+    let skip_audit = args.no_audit || std::env::var("COMPOSER_NO_AUDIT").unwrap_or_default() == "1";
 
     let working_dir = args.working_dir.canonicalize()
         .context("Failed to resolve working directory")?;
 
     // Check for composer.json
-    let json_path = working_dir.join("composer.json");
+    let json_path = working_dir.join(composer_file_name());
     if !json_path.exists() {
-        eprintln!("{} No composer.json found in {}",
-            style("Error:").red().bold(),
-            working_dir.display()
+        io.write_error_at(
+            &format!("{} No composer.json found in {}", style("Error:").red().bold(), working_dir.display()),
+            Verbosity::Quiet,
         );
         return Ok(1);
     }
@@ -145,7 +139,7 @@ pub async fn execute(args: UpdateArgs) -> Result<i32> {
         .context("Failed to parse composer.json")?;
 
     // Load composer.lock if it exists (to determine what's already installed)
-    let lock_path = working_dir.join("composer.lock");
+    let lock_path = working_dir.join(lock_file_name());
     let lock = if lock_path.exists() {
         let lock_content = std::fs::read_to_string(&lock_path)
             .context("Failed to read composer.lock")?;
@@ -160,17 +154,29 @@ pub async fn execute(args: UpdateArgs) -> Result<i32> {
     let config = Config::build(Some(&working_dir), true)?;
 
     // Detect platform
-    let platform = PlatformInfo::detect();
+    let platform = PlatformInfo::detect_for(&working_dir);
 
     // Create Composer using builder
     let mut builder = ComposerBuilder::new(working_dir.clone())
+        .with_io(io.clone())
+        .with_profiler(crate::io::profiler())
         .with_config(config)
         .with_composer_json(composer_json)
         .with_composer_lock(lock)
         .with_platform_packages(platform.to_packages())
         .dry_run(args.dry_run)
         .no_dev(args.no_dev)
-        .prefer_lowest(args.prefer_lowest);
+        .no_verify(args.no_verify)
+        .prefer_lowest(args.prefer_lowest)
+        .prefer_stable(args.prefer_stable)
+        .solver_seed(args.seed)
+        .update_with_dependencies(if args.with_all_dependencies {
+            UpdateAllowTransitiveDeps::All
+        } else if args.with_dependencies {
+            UpdateAllowTransitiveDeps::NoRootRequire
+        } else {
+            UpdateAllowTransitiveDeps::OnlyListed
+        });
 
     // Apply prefer_source/prefer_dist flags
     if args.prefer_source {
@@ -181,34 +187,210 @@ pub async fn execute(args: UpdateArgs) -> Result<i32> {
 
     let composer = builder.build()?;
 
-    // Run Installer
-    let installer = Installer::new(composer);
+    let update_packages = if args.interactive {
+        if !io.is_interactive() {
+            io.write_error_at(
+                &format!("{} --interactive cannot be used in non-interactive terminals.", style("Error:").red().bold()),
+                Verbosity::Quiet,
+            );
+            return Ok(1);
+        }
 
-    let update_packages = if args.packages.is_empty() {
+        match select_packages_interactively(&composer, &args).await? {
+            Some(selected) => Some(selected),
+            None => return Ok(0),
+        }
+    } else if args.packages.is_empty() {
         None
     } else {
         Some(args.packages.clone())
     };
 
+    // Run Installer
+    let installer = Installer::new(composer).audit(!skip_audit);
+
     let result = installer.update(
         args.optimize_autoloader,
         args.lock,
         update_packages,
     ).await;
 
-    if result.is_ok() && !skip_audit {
-        let audit_args = crate::pm::audit::AuditArgs {
-            no_dev: args.no_dev,
-            format: args.audit_format.clone(),
-            locked: false,
-            abandoned: Some("report".to_string()),
-            working_dir: working_dir.clone(),
+    if matches!(result, Ok(0)) && !skip_audit {
+        match crate::pm::audit::audit_after_install(&working_dir, args.no_dev, &args.audit_format).await {
+            Ok(0) => {}
+            Ok(exit_code) => return Ok(exit_code),
+            Err(e) => io.write_error(&format!("Warning: Audit failed: {}", e)),
+        }
+    }
+
+    result
+}
+
+/// A direct requirement with a newer version available within its constraint.
+#[derive(Debug, Clone, PartialEq)]
+struct OutdatedRequirement {
+    name: String,
+    current: Option<String>,
+    latest: String,
+}
+
+/// Lists outdated direct requirements and lets the user pick which ones to update.
+///
+/// Returns `None` if there is nothing to update or nothing was selected.
+async fn select_packages_interactively(composer: &Composer, args: &UpdateArgs) -> Result<Option<Vec<String>>> {
+    let io = &composer.io;
+    io.write(&format!("{} Checking for available updates", style("Info:").cyan()));
+
+    let outdated = find_outdated_requirements(composer, args).await;
+    if outdated.is_empty() {
+        io.write(&format!("{} All direct requirements are up to date.", style("Info:").cyan()));
+        return Ok(None);
+    }
+
+    let items: Vec<String> = outdated.iter().map(format_outdated).collect();
+    let selection = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Select packages to update (space to toggle, enter to confirm)")
+        .items(&items)
+        .interact_opt()
+        .context("Failed to show selection prompt")?;
+
+    match selection {
+        Some(indices) if !indices.is_empty() => {
+            Ok(Some(indices.into_iter().map(|i| outdated[i].name.clone()).collect()))
+        }
+        _ => {
+            io.write(&format!("{} No packages selected, nothing to update.", style("Info:").cyan()));
+            Ok(None)
+        }
+    }
+}
+
+/// Compares the locked version of each direct requirement with the newest version
+/// its constraint allows.
+async fn find_outdated_requirements(composer: &Composer, args: &UpdateArgs) -> Vec<OutdatedRequirement> {
+    let composer_json = &composer.composer_json;
+    let minimum_stability: Stability = composer_json.minimum_stability
+        .as_deref()
+        .unwrap_or("stable")
+        .parse()
+        .unwrap_or(Stability::Stable);
+
+    let locked: Vec<&pox_pm::json::LockedPackage> = composer.composer_lock
+        .as_ref()
+        .map(|lock| lock.packages.iter().chain(lock.packages_dev.iter()).collect())
+        .unwrap_or_default();
+
+    let mut requires: Vec<(&String, &String)> = composer_json.require.iter().collect();
+    if !args.no_dev {
+        requires.extend(composer_json.require_dev.iter());
+    }
+    requires.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut outdated = Vec::new();
+    for (name, constraint) in requires {
+        if is_platform_package(name) {
+            continue;
+        }
+        if !args.packages.is_empty() && !args.packages.iter().any(|p| p.eq_ignore_ascii_case(name)) {
+            continue;
+        }
+
+        let current = locked.iter()
+            .find(|lp| lp.name.eq_ignore_ascii_case(name))
+            .map(|lp| lp.version.clone());
+
+        let candidates = composer.repository_manager.find_packages_with_constraint(name, constraint).await;
+        let Some(latest) = pick_latest_version(&candidates, minimum_stability) else {
+            continue;
         };
+        let latest_version = latest.pretty_version.clone().unwrap_or_else(|| latest.version.clone());
 
-        if let Err(e) = crate::pm::audit::execute(audit_args).await {
-            eprintln!("Warning: Audit failed: {}", e);
+        let is_newer = match &current {
+            Some(current) => Comparator::greater_than(&latest.version, current),
+            None => true,
+        };
+        if is_newer {
+            outdated.push(OutdatedRequirement {
+                name: name.clone(),
+                current,
+                latest: latest_version,
+            });
         }
     }
 
-    result
+    outdated
+}
+
+/// Picks the highest version that satisfies the minimum stability.
+fn pick_latest_version(candidates: &[Arc<Package>], minimum_stability: Stability) -> Option<&Arc<Package>> {
+    candidates
+        .iter()
+        .filter(|p| {
+            let version = p.pretty_version.as_deref().unwrap_or(&p.version);
+            Stability::from_version(version).priority() <= minimum_stability.priority()
+        })
+        .fold(None, |best: Option<&Arc<Package>>, p| match best {
+            Some(b) if !Comparator::greater_than(&p.version, &b.version) => Some(b),
+            _ => Some(p),
+        })
+}
+
+fn format_outdated(req: &OutdatedRequirement) -> String {
+    format!(
+        "{} {} => {}",
+        req.name,
+        req.current.as_deref().unwrap_or("(not installed)"),
+        req.latest
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize_version(version: &str) -> String {
+        pox_semver::VersionParser::new()
+            .normalize(version)
+            .unwrap_or_else(|_| version.to_string())
+    }
+
+    fn candidate(pretty: &str) -> Arc<Package> {
+        let mut pkg = Package::new("vendor/pkg", normalize_version(pretty));
+        pkg.pretty_version = Some(pretty.to_string());
+        Arc::new(pkg)
+    }
+
+    #[test]
+    fn test_pick_latest_version_respects_stability() {
+        let candidates = vec![
+            candidate("1.0.0"),
+            candidate("1.2.0"),
+            candidate("1.10.0"),
+            candidate("2.0.0-beta1"),
+        ];
+
+        let latest = pick_latest_version(&candidates, Stability::Stable).unwrap();
+        assert_eq!(latest.pretty_version.as_deref(), Some("1.10.0"));
+
+        let latest = pick_latest_version(&candidates, Stability::Beta).unwrap();
+        assert_eq!(latest.pretty_version.as_deref(), Some("2.0.0-beta1"));
+    }
+
+    #[test]
+    fn test_pick_latest_version_empty() {
+        assert!(pick_latest_version(&[], Stability::Stable).is_none());
+    }
+
+    #[test]
+    fn test_format_outdated() {
+        let req = OutdatedRequirement {
+            name: "vendor/pkg".to_string(),
+            current: Some("1.0.0".to_string()),
+            latest: "1.2.0".to_string(),
+        };
+        assert_eq!(format_outdated(&req), "vendor/pkg 1.0.0 => 1.2.0");
+
+        let req = OutdatedRequirement { current: None, ..req };
+        assert_eq!(format_outdated(&req), "vendor/pkg (not installed) => 1.2.0");
+    }
 }
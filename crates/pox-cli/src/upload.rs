@@ -0,0 +1,124 @@
+//! Request body limits for server mode.
+//!
+//! Request bodies are streamed into PHP, so the upload limit is enforced while
+//! reading instead of after buffering the whole body.
+
+use std::io::Read;
+
+/// Parse a size in PHP's shorthand notation (`512`, `64K`, `256M`, `1G`)
+pub fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let (number, unit) = match size.char_indices().last()? {
+        (i, c) if c.is_ascii_alphabetic() => (&size[..i], Some(c.to_ascii_lowercase())),
+        _ => (size, None),
+    };
+
+    let number: u64 = number.trim().parse().ok()?;
+    let multiplier: u64 = match unit {
+        None => 1,
+        Some('k') => 1024,
+        Some('m') => 1024 * 1024,
+        Some('g') => 1024 * 1024 * 1024,
+        Some(_) => return None,
+    };
+    number.checked_mul(multiplier)
+}
+
+/// Whether a request announcing `content_length` bytes exceeds `limit`
+pub fn exceeds_limit(headers: &[(String, String)], limit: u64) -> bool {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.trim().parse::<u64>().ok())
+        .is_some_and(|length| length > limit)
+}
+
+/// Reader that ends a request body after `limit` bytes
+///
+/// Used for bodies without `Content-Length` (chunked transfer encoding), whose
+/// size is only known once they are read.
+pub struct LimitedBody<R> {
+    inner: R,
+    remaining: u64,
+    exceeded: bool,
+}
+
+impl<R: Read> LimitedBody<R> {
+    pub fn new(inner: R, limit: Option<u64>) -> Self {
+        Self {
+            inner,
+            remaining: limit.unwrap_or(u64::MAX),
+            exceeded: false,
+        }
+    }
+
+    /// Whether the body was cut off because it is larger than the limit
+    pub fn exceeded(&self) -> bool {
+        self.exceeded
+    }
+}
+
+impl<R: Read> Read for LimitedBody<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.exceeded || buf.is_empty() {
+            return Ok(0);
+        }
+
+        // Read one byte more than allowed to detect bodies exceeding the limit
+        let max = (buf.len() as u64).min(self.remaining.saturating_add(1)) as usize;
+        let n = self.inner.read(&mut buf[..max])?;
+        if n as u64 > self.remaining {
+            self.exceeded = true;
+            let allowed = self.remaining as usize;
+            self.remaining = 0;
+            return Ok(allowed);
+        }
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size("64K"), Some(64 * 1024));
+        assert_eq!(parse_size("256M"), Some(256 * 1024 * 1024));
+        assert_eq!(parse_size("1g"), Some(1024 * 1024 * 1024));
+        assert_eq!(parse_size(" 2M "), Some(2 * 1024 * 1024));
+        assert_eq!(parse_size("M"), None);
+        assert_eq!(parse_size("10X"), None);
+        assert_eq!(parse_size(""), None);
+    }
+
+    #[test]
+    fn test_exceeds_limit() {
+        let headers = vec![("Content-Length".to_string(), "2048".to_string())];
+        assert!(exceeds_limit(&headers, 1024));
+        assert!(!exceeds_limit(&headers, 2048));
+        assert!(!exceeds_limit(&[], 1024));
+    }
+
+    #[test]
+    fn test_limited_body() {
+        let mut body = LimitedBody::new(&b"hello world"[..], Some(5));
+        let mut out = Vec::new();
+        body.read_to_end(&mut out).unwrap();
+        assert!(body.exceeded());
+        assert_eq!(out, b"hello");
+
+        let mut body = LimitedBody::new(&b"hello"[..], Some(5));
+        let mut out = Vec::new();
+        body.read_to_end(&mut out).unwrap();
+        assert!(!body.exceeded());
+        assert_eq!(out, b"hello");
+
+        let mut body = LimitedBody::new(&b"hello world"[..], None);
+        let mut out = Vec::new();
+        body.read_to_end(&mut out).unwrap();
+        assert_eq!(out, b"hello world");
+    }
+}
@@ -0,0 +1,97 @@
+//! Debounced file watching, shared by the worker server and `run --watch`.
+
+use anyhow::Result;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebouncedEvent, Debouncer, RecommendedCache};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Delay after the last change before the callback runs
+const DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Watches a directory recursively as long as it is alive
+pub struct FileWatcher {
+    _debouncer: Debouncer<RecommendedWatcher, RecommendedCache>,
+}
+
+/// Build a glob set, invalid patterns are reported and skipped
+pub fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut glob_builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        match Glob::new(pattern) {
+            Ok(glob) => { glob_builder.add(glob); }
+            Err(e) => eprintln!("Invalid glob pattern '{}': {}", pattern, e),
+        }
+    }
+    glob_builder.build().map_err(|e| anyhow::anyhow!("Failed to build glob set: {}", e))
+}
+
+/// Whether `path` matches, relative to `root` or as an absolute path
+fn matches(glob_set: &GlobSet, root: &Path, path: &Path) -> bool {
+    match path.strip_prefix(root) {
+        Ok(rel_path) => glob_set.is_match(rel_path) || glob_set.is_match(path),
+        Err(_) => glob_set.is_match(path),
+    }
+}
+
+/// Watch `root` and call `on_change` with the changed files matching `patterns`.
+///
+/// The callback runs on a background thread once changes settled, so a burst
+/// of writes (e.g. a git checkout) triggers it only once.
+pub fn watch<F>(root: &Path, patterns: &[String], mut on_change: F) -> Result<FileWatcher>
+where
+    F: FnMut(Vec<PathBuf>) + Send + 'static,
+{
+    let glob_set = build_glob_set(patterns)?;
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    let mut debouncer = new_debouncer(
+        DEBOUNCE,
+        None,
+        move |result: std::result::Result<Vec<DebouncedEvent>, Vec<notify::Error>>| {
+            if let Ok(events) = result {
+                let _ = tx.send(events);
+            }
+        },
+    ).map_err(|e| anyhow::anyhow!("Failed to create file watcher: {}", e))?;
+
+    debouncer.watch(root, RecursiveMode::Recursive)
+        .map_err(|e| anyhow::anyhow!("Failed to watch directory: {}", e))?;
+
+    let root = root.to_path_buf();
+    std::thread::spawn(move || {
+        while let Ok(events) = rx.recv() {
+            let mut changed: Vec<PathBuf> = Vec::new();
+            for event in events {
+                for path in event.event.paths {
+                    if matches(&glob_set, &root, &path) && !changed.contains(&path) {
+                        changed.push(path);
+                    }
+                }
+            }
+
+            if !changed.is_empty() {
+                on_change(changed);
+            }
+        }
+    });
+
+    Ok(FileWatcher { _debouncer: debouncer })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_relative_and_absolute() {
+        let glob_set = build_glob_set(&["src/**/*.php".to_string(), "*.json".to_string()]).unwrap();
+        let root = Path::new("/project");
+
+        assert!(matches(&glob_set, root, Path::new("/project/src/Controller/Home.php")));
+        assert!(matches(&glob_set, root, Path::new("/project/composer.json")));
+        assert!(!matches(&glob_set, root, Path::new("/project/tests/HomeTest.php")));
+        assert!(!matches(&glob_set, root, Path::new("/project/src/readme.md")));
+    }
+}
@@ -0,0 +1,179 @@
+//! Workspace command - operate on all packages of a monorepo.
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use console::style;
+use std::path::{Path, PathBuf};
+
+use pox_pm::io::Verbosity;
+use pox_pm::scripts;
+use pox_pm::workspace::Workspace;
+
+use crate::install::InstallArgs;
+
+/// Workspace subcommands
+#[derive(Subcommand, Debug)]
+pub enum WorkspaceCommands {
+    /// List the packages of the workspace
+    List(WorkspaceListArgs),
+
+    /// Install dependencies of the workspace root and every member
+    Install(InstallArgs),
+
+    /// Run a script in every member that defines it
+    Run(WorkspaceRunArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct WorkspaceListArgs {
+    /// Working directory
+    #[arg(short = 'd', long, default_value = ".")]
+    pub working_dir: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct WorkspaceRunArgs {
+    /// Script name to run
+    #[arg(value_name = "SCRIPT")]
+    pub script: String,
+
+    /// Stop at the first member whose script fails
+    #[arg(long)]
+    pub fail_fast: bool,
+
+    /// Working directory
+    #[arg(short = 'd', long, default_value = ".")]
+    pub working_dir: PathBuf,
+
+    /// Arguments passed to the script
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub args: Vec<String>,
+}
+
+/// Execute a workspace command
+pub async fn execute(command: WorkspaceCommands) -> Result<i32> {
+    match command {
+        WorkspaceCommands::List(args) => list(args),
+        WorkspaceCommands::Install(args) => install(args).await,
+        WorkspaceCommands::Run(args) => run(args),
+    }
+}
+
+fn find_workspace(working_dir: &Path) -> Result<Workspace> {
+    let working_dir = working_dir
+        .canonicalize()
+        .context("Failed to resolve working directory")?;
+
+    match Workspace::find(&working_dir)? {
+        Some(workspace) => Ok(workspace),
+        None => bail!(
+            "No workspace found in {}, add \"workspace\" globs to the root composer.json",
+            working_dir.display()
+        ),
+    }
+}
+
+fn relative_path<'a>(workspace: &Workspace, path: &'a Path) -> &'a Path {
+    path.strip_prefix(&workspace.root).unwrap_or(path)
+}
+
+fn list(args: WorkspaceListArgs) -> Result<i32> {
+    let workspace = find_workspace(&args.working_dir)?;
+    let io = crate::io::io();
+
+    for member in &workspace.members {
+        let version = member.composer_json.version.as_deref().unwrap_or("");
+        io.write(&format!(
+            "{} {} {}",
+            style(member.display_name()).green(),
+            style(version).yellow(),
+            style(relative_path(&workspace, &member.path).display()).dim()
+        ));
+    }
+
+    Ok(0)
+}
+
+async fn install(args: InstallArgs) -> Result<i32> {
+    let workspace = find_workspace(&args.working_dir)?;
+    let io = crate::io::io();
+
+    let root = workspace.root_package();
+
+    let mut failed = Vec::new();
+    for member in root.iter().chain(&workspace.members) {
+        io.write(&format!(
+            "{} Installing {} ({})",
+            style("Workspace").green().bold(),
+            style(member.display_name()).white().bold(),
+            relative_path(&workspace, &member.path).display()
+        ));
+
+        let member_args = InstallArgs {
+            working_dir: member.path.clone(),
+            ..args.clone()
+        };
+        match crate::install::execute(member_args).await {
+            Ok(0) => {}
+            Ok(_) => failed.push(member.display_name()),
+            Err(e) => {
+                io.write_error_at(&format!("{} {:#}", style("Error:").red().bold(), e), Verbosity::Quiet);
+                failed.push(member.display_name());
+            }
+        }
+    }
+
+    report_failures(&failed)
+}
+
+fn run(args: WorkspaceRunArgs) -> Result<i32> {
+    let workspace = find_workspace(&args.working_dir)?;
+    let io = crate::io::io();
+
+    let members: Vec<_> = workspace
+        .members
+        .iter()
+        .filter(|m| scripts::collect_scripts(&m.composer_json).contains_key(args.script.as_str()))
+        .collect();
+
+    if members.is_empty() {
+        bail!("No workspace member defines the script \"{}\"", args.script);
+    }
+
+    let mut failed = Vec::new();
+    for member in members {
+        io.write(&format!(
+            "{} {} in {}",
+            style("Workspace").green().bold(),
+            style(&args.script).cyan(),
+            style(member.display_name()).white().bold()
+        ));
+
+        let code = scripts::run_script(&args.script, &member.composer_json, &member.path, &args.args)
+            .unwrap_or_else(|e| {
+                io.write_error_at(&format!("{} {:#}", style("Error:").red().bold(), e), Verbosity::Quiet);
+                1
+            });
+
+        if code != 0 {
+            failed.push(member.display_name());
+            if args.fail_fast {
+                break;
+            }
+        }
+    }
+
+    report_failures(&failed)
+}
+
+fn report_failures(failed: &[String]) -> Result<i32> {
+    if failed.is_empty() {
+        return Ok(0);
+    }
+
+    crate::io::io().write_error_at(
+        &format!("{} Failed in {}", style("Error:").red().bold(), failed.join(", ")),
+        Verbosity::Quiet,
+    );
+    Ok(1)
+}
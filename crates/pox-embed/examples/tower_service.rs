@@ -0,0 +1,81 @@
+//! A tower `Service` that runs `http::Request`s through a PHP worker pool.
+//!
+//! The same service can be mounted in an axum router or served with hyper.
+//! This example sends a single request to it:
+//!
+//! ```bash
+//! cargo run -p pox-embed --features http --example tower_service -- /var/www/worker.php /var/www
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use pox_embed::{HttpRequestBuilder, PhpError, PhpWorker};
+use tower::{Service, ServiceExt};
+
+/// Runs each request on a worker of the pool, off the async runtime
+#[derive(Clone)]
+struct PhpService {
+    worker: Arc<PhpWorker>,
+    document_root: String,
+    script_filename: String,
+}
+
+impl Service<http::Request<Bytes>> for PhpService {
+    type Response = http::Response<Bytes>;
+    type Error = PhpError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // The pool queues requests while all workers are busy
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Bytes>) -> Self::Future {
+        let worker = self.worker.clone();
+        let request = HttpRequestBuilder::from(request)
+            .document_root(self.document_root.as_str())
+            .script_filename(self.script_filename.as_str())
+            .build();
+
+        Box::pin(async move {
+            let response = tokio::task::spawn_blocking(move || worker.handle_request(request))
+                .await
+                .expect("PHP worker task panicked")?;
+            Ok(response.into())
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let (Some(script_filename), Some(document_root)) = (args.next(), args.next()) else {
+        eprintln!("Usage: tower_service <worker-script> <document-root>");
+        std::process::exit(1);
+    };
+
+    let service = PhpService {
+        worker: Arc::new(PhpWorker::new(&script_filename, &document_root, 2)?),
+        document_root,
+        script_filename,
+    };
+
+    let request = http::Request::builder()
+        .uri("/?name=tower")
+        .header("Host", "localhost:8080")
+        .body(Bytes::new())?;
+    let response = service.oneshot(request).await?;
+
+    println!("{}", response.status());
+    for (name, value) in response.headers() {
+        println!("{}: {}", name, value.to_str().unwrap_or_default());
+    }
+    println!();
+    println!("{}", String::from_utf8_lossy(response.body()));
+
+    Ok(())
+}
@@ -8,11 +8,13 @@
 #include <stdio.h>
 #include <stdlib.h>
 #include <string.h>
+#include <strings.h>
 #include <signal.h>
 
 #include <sapi/embed/php_embed.h>
 #include <php.h>
 #include <php_main.h>
+#include <php_ini.h>
 #include <php_variables.h>
 #include <php_output.h>
 #include <SAPI.h>
@@ -21,7 +23,11 @@
 #include <Zend/zend_modules.h>
 #include <Zend/zend_compile.h>
 #include <Zend/zend_extensions.h>
+#include <Zend/zend_smart_str.h>
 #include <ext/standard/info.h>
+#include <ext/standard/base64.h>
+#include <ext/standard/php_var.h>
+#include <ext/json/php_json.h>
 #include <ext/spl/spl_exceptions.h>
 
 /* ============================================================================
@@ -32,9 +38,54 @@
 static char *pox_script_filename = NULL;
 static char *pox_ini_entries = NULL;
 
+/* Variables injected into a CLI execution, as key, value pairs */
+static const char *const *pox_extra_env = NULL;
+static int pox_extra_env_count = 0;
+static const char *const *pox_extra_server = NULL;
+static int pox_extra_server_count = 0;
+
+static void pox_register_pairs(const char *const *pairs, int count, zval *track_vars_array) {
+    for (int i = 0; i < count; i++) {
+        const char *value = pairs[2 * i + 1];
+        php_register_variable_safe((char *)pairs[2 * i], (char *)value, strlen(value), track_vars_array);
+    }
+}
+
+/* getenv() sees the injected environment before the process environment */
+static char *pox_getenv(const char *name, size_t name_len) {
+    for (int i = 0; i < pox_extra_env_count; i++) {
+        const char *key = pox_extra_env[2 * i];
+        if (strlen(key) == name_len && strncmp(key, name, name_len) == 0) {
+            return (char *)pox_extra_env[2 * i + 1];
+        }
+    }
+    return NULL;
+}
+
+/* Add the injected environment to $_ENV */
+static void pox_populate_env(void) {
+    if (pox_extra_env_count == 0) {
+        return;
+    }
+
+    zend_is_auto_global_str(ZEND_STRL("_ENV"));
+    zval *env = zend_hash_str_find(&EG(symbol_table), ZEND_STRL("_ENV"));
+    if (env == NULL) {
+        return;
+    }
+    ZVAL_DEREF(env);
+    if (Z_TYPE_P(env) != IS_ARRAY) {
+        return;
+    }
+
+    SEPARATE_ARRAY(env);
+    pox_register_pairs(pox_extra_env, pox_extra_env_count, env);
+}
+
 static void pox_register_variables(zval *track_vars_array) {
     /* Import environment variables */
     php_import_environment_variables(track_vars_array);
+    pox_register_pairs(pox_extra_env, pox_extra_env_count, track_vars_array);
 
     if (pox_script_filename != NULL) {
         size_t len = strlen(pox_script_filename);
@@ -45,6 +96,9 @@ static void pox_register_variables(zval *track_vars_array) {
     }
 
     php_register_variable_safe("DOCUMENT_ROOT", "", 0, track_vars_array);
+
+    /* Injected server variables override the defaults */
+    pox_register_pairs(pox_extra_server, pox_extra_server_count, track_vars_array);
 }
 
 /* Register STDIN, STDOUT, STDERR constants */
@@ -124,22 +178,195 @@ static void pox_apply_ini_entries(void) {
     free(entries);
 }
 
+/* Error hook, hands PHP errors to the embedder instead of displaying them */
+typedef void (*pox_error_handler_fn)(void *data, int type, const char *message, size_t message_len,
+                                     const char *file, size_t file_len, uint32_t line);
+
+static pox_error_handler_fn pox_error_handler = NULL;
+static void *pox_error_handler_data = NULL;
+
+#if PHP_VERSION_ID >= 80100
+typedef zend_string *pox_error_filename;
+#else
+typedef const char *pox_error_filename;
+#endif
+
+static void (*pox_original_error_cb)(int type, pox_error_filename error_filename,
+                                     const uint32_t error_lineno, zend_string *message) = NULL;
+
+static void pox_error_cb(int type, pox_error_filename error_filename, const uint32_t error_lineno, zend_string *message) {
+    if (pox_error_handler == NULL || !(EG(error_reporting) & (type & E_ALL))) {
+        pox_original_error_cb(type, error_filename, error_lineno, message);
+        return;
+    }
+
+#if PHP_VERSION_ID >= 80100
+    const char *file = error_filename ? ZSTR_VAL(error_filename) : "";
+    size_t file_len = error_filename ? ZSTR_LEN(error_filename) : 0;
+#else
+    const char *file = error_filename ? error_filename : "";
+    size_t file_len = strlen(file);
+#endif
+    pox_error_handler(pox_error_handler_data, type & E_ALL, ZSTR_VAL(message), ZSTR_LEN(message),
+                      file, file_len, error_lineno);
+
+    /* Run PHP's handler without output, it still sets error_get_last() and bails out on fatal errors */
+    uint8_t display_errors = PG(display_errors);
+    bool log_errors = PG(log_errors);
+    bool bailout = false;
+    PG(display_errors) = 0;
+    PG(log_errors) = 0;
+
+    zend_try {
+        pox_original_error_cb(type, error_filename, error_lineno, message);
+    } zend_catch {
+        bailout = true;
+    } zend_end_try();
+
+    PG(display_errors) = display_errors;
+    PG(log_errors) = log_errors;
+
+    if (bailout) {
+        zend_bailout();
+    }
+}
+
+/* Route PHP errors through pox_error_cb, called after every module startup */
+static void pox_install_error_cb(void) {
+    if (zend_error_cb != pox_error_cb) {
+        pox_original_error_cb = zend_error_cb;
+        zend_error_cb = pox_error_cb;
+    }
+}
+
+/*
+ * Set the handler receiving PHP errors, NULL restores PHP's own error output.
+ */
+void pox_set_error_handler(pox_error_handler_fn handler, void *data) {
+    pox_error_handler = handler;
+    pox_error_handler_data = data;
+}
+
+/* Log hook, hands messages PHP logs to the embedder: error_log() calls, logged errors and startup errors */
+typedef void (*pox_log_handler_fn)(void *data, int level, const char *message, size_t message_len);
+
+static pox_log_handler_fn pox_log_handler = NULL;
+static void *pox_log_handler_data = NULL;
+
+/* pox.error_log_file: write to the error_log file as well while a log handler is set */
+static int pox_log_keep_file = 0;
+
+static ZEND_INI_MH((*pox_original_error_log_mh)) = NULL;
+
+/* Whether an INI value is On, Yes, True or a non-zero number */
+static int pox_parse_bool(const char *value, size_t len) {
+    if ((len == 2 && strncasecmp(value, "on", 2) == 0)
+        || (len == 3 && strncasecmp(value, "yes", 3) == 0)
+        || (len == 4 && strncasecmp(value, "true", 4) == 0)) {
+        return 1;
+    }
+    return len > 0 && atoi(value) != 0;
+}
+
+/* pox.* settings are not registered INI entries, read them from php.ini or the entries given to pox */
+static int pox_ini_flag(const char *name) {
+    size_t name_len = strlen(name);
+
+    zval *value = cfg_get_entry(name, name_len);
+    if (value != NULL && Z_TYPE_P(value) == IS_STRING) {
+        return pox_parse_bool(Z_STRVAL_P(value), Z_STRLEN_P(value));
+    }
+
+    const char *line = pox_ini_entries;
+    while (line != NULL && *line != '\0') {
+        const char *eol = strchr(line, '\n');
+        size_t len = eol != NULL ? (size_t)(eol - line) : strlen(line);
+        if (len > name_len && strncmp(line, name, name_len) == 0 && line[name_len] == '=') {
+            return pox_parse_bool(line + name_len + 1, len - name_len - 1);
+        }
+        line = eol != NULL ? eol + 1 : NULL;
+    }
+
+    return 0;
+}
+
+/* SAPI log_message handler, PHP calls it for everything it logs while no error_log file is set */
+static void pox_log_message(const char *message, int syslog_type_int) {
+    if (pox_log_handler == NULL) {
+        fprintf(stderr, "%s\n", message);
+        fflush(stderr);
+        return;
+    }
+
+    pox_log_handler(pox_log_handler_data, syslog_type_int, message, strlen(message));
+
+    /* Let PHP write the configured error_log file as well */
+    char *error_log = INI_STR("error_log");
+    if (pox_log_keep_file && error_log != NULL && *error_log != '\0') {
+        PG(error_log) = error_log;
+        PG(in_error_log) = 0;
+        php_log_err_with_severity(message, syslog_type_int);
+        PG(in_error_log) = 1;
+        PG(error_log) = NULL;
+    }
+}
+
+/* Keep PG(error_log) unset while a log handler is set, so everything PHP logs reaches pox_log_message */
+static ZEND_INI_MH(pox_on_update_error_log) {
+    int result = pox_original_error_log_mh(entry, new_value, mh_arg1, mh_arg2, mh_arg3, stage);
+    if (result == SUCCESS && pox_log_handler != NULL) {
+        PG(error_log) = NULL;
+    }
+    return result;
+}
+
+/* Route the error_log setting through pox_on_update_error_log, called after every module startup */
+static void pox_install_log_hooks(void) {
+    zend_ini_entry *entry = zend_hash_str_find_ptr(EG(ini_directives), ZEND_STRL("error_log"));
+    if (entry == NULL || entry->on_modify == pox_on_update_error_log) {
+        return;
+    }
+
+    pox_original_error_log_mh = entry->on_modify;
+    entry->on_modify = pox_on_update_error_log;
+    pox_log_keep_file = pox_ini_flag("pox.error_log_file");
+
+    if (pox_log_handler != NULL) {
+        PG(error_log) = NULL;
+    }
+}
+
+/*
+ * Set the handler receiving PHP's log messages, NULL restores logging to
+ * the error_log file or stderr. Set it before PHP starts to receive startup errors.
+ */
+void pox_set_log_handler(pox_log_handler_fn handler, void *data) {
+    pox_log_handler = handler;
+    pox_log_handler_data = data;
+}
+
 /* Internal initialization helper */
 static int pox_init(int argc, char **argv) {
     php_embed_module.name = "cli";
     php_embed_module.pretty_name = "PHP CLI embedded";
     php_embed_module.register_server_variables = pox_register_variables;
+    php_embed_module.getenv = pox_getenv;
     php_embed_module.phpinfo_as_text = 1;  /* Output phpinfo as plain text, not HTML */
+    php_embed_module.log_message = pox_log_message;
 
     if (php_embed_init(argc, argv) != SUCCESS) {
         return 1;
     }
 
     pox_register_file_handles();
+    pox_install_error_cb();
+    pox_install_log_hooks();
 
     /* Apply INI entries after startup */
     pox_apply_ini_entries();
 
+    pox_populate_env();
+
     return 0;
 }
 
@@ -175,6 +402,29 @@ int pox_execute_script(const char *script_path, int argc, char **argv) {
     return exit_status;
 }
 
+/*
+ * Execute a PHP script file with additional $_ENV and $_SERVER variables,
+ * given as key, value pairs, without touching the process environment.
+ * Returns the exit status code.
+ */
+int pox_execute_script_with_vars(const char *script_path, int argc, char **argv,
+                                 const char *const *env, int env_count,
+                                 const char *const *server, int server_count) {
+    pox_extra_env = env;
+    pox_extra_env_count = env_count;
+    pox_extra_server = server;
+    pox_extra_server_count = server_count;
+
+    int exit_status = pox_execute_script(script_path, argc, argv);
+
+    pox_extra_env = NULL;
+    pox_extra_env_count = 0;
+    pox_extra_server = NULL;
+    pox_extra_server_count = 0;
+
+    return exit_status;
+}
+
 /*
  * Execute PHP code passed as a string (like php -r).
  * Returns the exit status code.
@@ -468,6 +718,209 @@ void pox_free_string(char *str) {
     }
 }
 
+/* ============================================================================
+ * Interactive Shell (REPL) - one request kept alive across evaluations
+ * ============================================================================ */
+
+#define POX_REPL_OK    0
+#define POX_REPL_ERROR 1
+#define POX_REPL_EXIT  2
+
+static int pox_repl_active = 0;
+static char pox_repl_last_char = '\0';
+
+/* Output writer that remembers the last written character, so the shell
+ * can start the prompt on a fresh line like `php -a` does. */
+static size_t pox_repl_ub_write(const char *str, size_t str_length) {
+    size_t written = fwrite(str, 1, str_length, stdout);
+    if (str_length > 0) {
+        pox_repl_last_char = str[str_length - 1];
+    }
+    fflush(stdout);
+    return written;
+}
+
+/*
+ * Start the shell request.
+ * Returns 0 on success.
+ */
+int pox_repl_start(int argc, char **argv) {
+    if (pox_repl_active) {
+        return 0;
+    }
+
+    pox_script_filename = "php shell code";
+    php_embed_module.ub_write = pox_repl_ub_write;
+
+    if (pox_init(argc, argv) != 0) {
+        return 1;
+    }
+
+    pox_repl_active = 1;
+    return 0;
+}
+
+/* Print the value of an evaluated expression */
+static void pox_repl_print_result(zval *value) {
+    php_printf("= ");
+    if (Z_TYPE_P(value) == IS_OBJECT || Z_TYPE_P(value) == IS_RESOURCE) {
+        php_var_dump(value, 1);
+    } else {
+        php_var_export(value, 1);
+        php_printf("\n");
+    }
+}
+
+/*
+ * Evaluate code in the shell request.
+ * With print_result set, the code is first evaluated as an expression and its
+ * value printed; if it does not parse as an expression it runs as statements.
+ * Returns POX_REPL_OK, POX_REPL_ERROR or POX_REPL_EXIT (after exit()).
+ */
+int pox_repl_eval(const char *code, int print_result) {
+    int status = POX_REPL_OK;
+    size_t len = strlen(code);
+
+    if (!pox_repl_active) {
+        return POX_REPL_ERROR;
+    }
+
+    pox_repl_last_char = '\0';
+
+    zend_try {
+        zval retval;
+        ZVAL_UNDEF(&retval);
+
+        int evaluated = 0;
+        if (print_result) {
+            if (zend_eval_stringl((char *)code, len, &retval, "php shell code") == SUCCESS) {
+                evaluated = 1;
+                if (!EG(exception)) {
+                    pox_repl_print_result(&retval);
+                }
+                zval_ptr_dtor(&retval);
+            } else if (EG(exception) && instanceof_function(EG(exception)->ce, zend_ce_parse_error)) {
+                /* Not an expression, retry as statements */
+                zend_clear_exception();
+            } else {
+                evaluated = 1;
+            }
+        }
+
+        if (!evaluated) {
+            zend_eval_stringl((char *)code, len, NULL, "php shell code");
+        }
+    } zend_catch {
+        status = POX_REPL_ERROR;
+    } zend_end_try();
+
+    if (EG(exception)) {
+        if (zend_is_unwind_exit(EG(exception))) {
+            zend_clear_exception();
+            status = POX_REPL_EXIT;
+        } else {
+            /* Report like `php -a`: uncaught exceptions must not end the shell */
+            zend_exception_error(EG(exception), E_WARNING);
+            status = POX_REPL_ERROR;
+        }
+    }
+
+    if (pox_repl_last_char != '\0' && pox_repl_last_char != '\n') {
+        php_write("\n", 1);
+    }
+
+    return status;
+}
+
+/*
+ * Exit status set by exit() during the shell request.
+ */
+int pox_repl_exit_status(void) {
+    return EG(exit_status);
+}
+
+static int pox_repl_starts_with(const char *name, size_t name_len, const char *prefix, size_t prefix_len) {
+    return name_len >= prefix_len && strncasecmp(name, prefix, prefix_len) == 0;
+}
+
+static void pox_repl_append(smart_str *out, const char *name, size_t len) {
+    smart_str_appendl(out, name, len);
+    smart_str_appendc(out, '\n');
+}
+
+/*
+ * Completion candidates for a prefix as a newline-separated string.
+ * A `$` prefix completes variables, anything else functions, classes and constants.
+ * Caller must free the returned string.
+ */
+char *pox_repl_complete(const char *prefix) {
+    smart_str out = {0};
+    size_t prefix_len = strlen(prefix);
+
+    if (!pox_repl_active) {
+        return NULL;
+    }
+
+    if (prefix[0] == '$') {
+        zend_string *name;
+        ZEND_HASH_FOREACH_STR_KEY(&EG(symbol_table), name) {
+            if (name && pox_repl_starts_with(ZSTR_VAL(name), ZSTR_LEN(name), prefix + 1, prefix_len - 1)) {
+                smart_str_appendc(&out, '$');
+                pox_repl_append(&out, ZSTR_VAL(name), ZSTR_LEN(name));
+            }
+        } ZEND_HASH_FOREACH_END();
+    } else {
+        zend_function *func;
+        zend_class_entry *ce;
+        zend_constant *constant;
+        zend_string *key;
+
+        ZEND_HASH_MAP_FOREACH_PTR(EG(function_table), func) {
+            zend_string *name = func->common.function_name;
+            if (name && pox_repl_starts_with(ZSTR_VAL(name), ZSTR_LEN(name), prefix, prefix_len)) {
+                pox_repl_append(&out, ZSTR_VAL(name), ZSTR_LEN(name));
+            }
+        } ZEND_HASH_FOREACH_END();
+
+        ZEND_HASH_MAP_FOREACH_STR_KEY_PTR(EG(class_table), key, ce) {
+            /* Runtime declaration keys start with a NUL byte */
+            if (key && ZSTR_VAL(key)[0] != '\0'
+                && pox_repl_starts_with(ZSTR_VAL(ce->name), ZSTR_LEN(ce->name), prefix, prefix_len)) {
+                pox_repl_append(&out, ZSTR_VAL(ce->name), ZSTR_LEN(ce->name));
+            }
+        } ZEND_HASH_FOREACH_END();
+
+        ZEND_HASH_MAP_FOREACH_PTR(EG(zend_constants), constant) {
+            zend_string *name = constant->name;
+            if (name && pox_repl_starts_with(ZSTR_VAL(name), ZSTR_LEN(name), prefix, prefix_len)) {
+                pox_repl_append(&out, ZSTR_VAL(name), ZSTR_LEN(name));
+            }
+        } ZEND_HASH_FOREACH_END();
+    }
+
+    smart_str_0(&out);
+    if (out.s == NULL) {
+        return NULL;
+    }
+
+    char *result = strdup(ZSTR_VAL(out.s));
+    smart_str_free(&out);
+    return result;
+}
+
+/*
+ * End the shell request and shut PHP down.
+ */
+void pox_repl_shutdown(void) {
+    if (!pox_repl_active) {
+        return;
+    }
+
+    php_embed_shutdown();
+    pox_repl_active = 0;
+    pox_script_filename = NULL;
+}
+
 /* ============================================================================
  * Web/Server Mode - Custom SAPI for handling HTTP requests
  * ============================================================================ */
@@ -490,12 +943,17 @@ typedef struct {
     /* Document root and script */
     const char *document_root;
     const char *script_filename;
+    const char *script_name;
+    const char *path_info;        /* NULL if the URI has no path info */
+    int is_router;                /* Script is a router that may return false */
 
     /* Server info */
     const char *server_name;
     int server_port;
     const char *remote_addr;
     int remote_port;
+    const char *server_protocol;  /* NULL for HTTP/1.1 */
+    int https;                    /* Request was received over TLS */
 
     /* Response output buffer */
     char *response_body;
@@ -509,6 +967,16 @@ typedef struct {
 
     /* Response status */
     int response_status;
+
+    /* Peak memory usage of the request in bytes */
+    size_t peak_memory;
+
+    /* Router script returned false */
+    int declined;
+
+    /* Streamed request body, read through read_body instead of request_body */
+    void *body_stream;
+    size_t (*read_body)(void *body_stream, char *buffer, size_t len);
 } pox_request_context;
 
 /* Thread-local request context for the web SAPI */
@@ -595,7 +1063,18 @@ static int pox_web_send_headers(sapi_headers_struct *sapi_headers) {
 
 /* SAPI: Read POST data */
 static size_t pox_web_read_post(char *buffer, size_t count_bytes) {
-    if (current_request == NULL || current_request->request_body == NULL) {
+    if (current_request == NULL) {
+        return 0;
+    }
+
+    /* Large uploads are fed from the socket as PHP consumes them */
+    if (current_request->read_body) {
+        size_t read = current_request->read_body(current_request->body_stream, buffer, count_bytes);
+        current_request->request_body_read += read;
+        return read;
+    }
+
+    if (current_request->request_body == NULL) {
         return 0;
     }
 
@@ -650,7 +1129,37 @@ static char *pox_web_read_cookies(void) {
     return NULL;
 }
 
-/* SAPI: Register server variables ($_SERVER) */
+/* Register PHP_AUTH_* and REMOTE_USER from an Authorization header value, like Apache */
+static void pox_register_auth_variables(const char *value, size_t value_len, zval *track_vars_array) {
+    if (value_len > 6 && strncasecmp(value, "Basic ", 6) == 0) {
+        const char *encoded = value + 6;
+        size_t encoded_len = value_len - 6;
+        while (encoded_len > 0 && *encoded == ' ') { encoded++; encoded_len--; }
+
+        zend_string *decoded = php_base64_decode((const unsigned char *)encoded, encoded_len);
+        if (decoded == NULL) return;
+
+        char *colon = memchr(ZSTR_VAL(decoded), ':', ZSTR_LEN(decoded));
+        if (colon) {
+            size_t user_len = colon - ZSTR_VAL(decoded);
+            size_t pw_len = ZSTR_LEN(decoded) - user_len - 1;
+            php_register_variable_safe("PHP_AUTH_USER", ZSTR_VAL(decoded), user_len, track_vars_array);
+            php_register_variable_safe("PHP_AUTH_PW", colon + 1, pw_len, track_vars_array);
+            php_register_variable_safe("REMOTE_USER", ZSTR_VAL(decoded), user_len, track_vars_array);
+            php_register_variable_safe("AUTH_TYPE", "Basic", 5, track_vars_array);
+        }
+        zend_string_free(decoded);
+    } else if (value_len > 7 && strncasecmp(value, "Digest ", 7) == 0) {
+        php_register_variable_safe("PHP_AUTH_DIGEST", (char *)value + 7, value_len - 7, track_vars_array);
+        php_register_variable_safe("AUTH_TYPE", "Digest", 6, track_vars_array);
+    }
+}
+
+/* SAPI: Register server variables ($_SERVER)
+ *
+ * Mirrors php -S: CGI variables, every header as HTTP_* (Content-Type and
+ * Content-Length also without the prefix) and the PHP_AUTH_* variables.
+ * REQUEST_TIME and REQUEST_TIME_FLOAT are added by PHP itself. */
 static void pox_web_register_variables(zval *track_vars_array) {
     if (current_request == NULL) return;
 
@@ -674,13 +1183,24 @@ static void pox_web_register_variables(zval *track_vars_array) {
         (char *)(current_request->script_filename ? current_request->script_filename : ""),
         current_request->script_filename ? strlen(current_request->script_filename) : 0, track_vars_array);
 
-    php_register_variable_safe("SCRIPT_NAME",
-        (char *)(current_request->uri ? current_request->uri : "/"),
-        current_request->uri ? strlen(current_request->uri) : 1, track_vars_array);
-
-    php_register_variable_safe("PHP_SELF",
-        (char *)(current_request->uri ? current_request->uri : "/"),
-        current_request->uri ? strlen(current_request->uri) : 1, track_vars_array);
+    const char *script_name = current_request->script_name ? current_request->script_name : "/";
+    php_register_variable_safe("SCRIPT_NAME", (char *)script_name, strlen(script_name), track_vars_array);
+
+    /* PHP_SELF is the script name followed by the path info, like in php -S */
+    if (current_request->path_info) {
+        size_t path_info_len = strlen(current_request->path_info);
+        php_register_variable_safe("PATH_INFO",
+            (char *)current_request->path_info, path_info_len, track_vars_array);
+
+        size_t self_len = strlen(script_name) + path_info_len;
+        char *php_self = emalloc(self_len + 1);
+        memcpy(php_self, script_name, strlen(script_name));
+        memcpy(php_self + strlen(script_name), current_request->path_info, path_info_len + 1);
+        php_register_variable_safe("PHP_SELF", php_self, self_len, track_vars_array);
+        efree(php_self);
+    } else {
+        php_register_variable_safe("PHP_SELF", (char *)script_name, strlen(script_name), track_vars_array);
+    }
 
     php_register_variable_safe("DOCUMENT_ROOT",
         (char *)(current_request->document_root ? current_request->document_root : ""),
@@ -702,10 +1222,16 @@ static void pox_web_register_variables(zval *track_vars_array) {
     snprintf(remote_port_str, sizeof(remote_port_str), "%d", current_request->remote_port);
     php_register_variable_safe("REMOTE_PORT", remote_port_str, strlen(remote_port_str), track_vars_array);
 
-    php_register_variable_safe("SERVER_SOFTWARE", "pox", 4, track_vars_array);
-    php_register_variable_safe("SERVER_PROTOCOL", "HTTP/1.1", 8, track_vars_array);
+    php_register_variable_safe("SERVER_SOFTWARE", "pox", 3, track_vars_array);
+
+    const char *protocol = current_request->server_protocol ? current_request->server_protocol : "HTTP/1.1";
+    php_register_variable_safe("SERVER_PROTOCOL", (char *)protocol, strlen(protocol), track_vars_array);
     php_register_variable_safe("GATEWAY_INTERFACE", "CGI/1.1", 7, track_vars_array);
 
+    if (current_request->https) {
+        php_register_variable_safe("HTTPS", "on", 2, track_vars_array);
+    }
+
     if (current_request->content_type) {
         php_register_variable_safe("CONTENT_TYPE",
             (char *)current_request->content_type,
@@ -749,10 +1275,10 @@ static void pox_web_register_variables(zval *track_vars_array) {
                     while (*value == ' ' || *value == '\t') value++;
                     size_t value_len = line_len - (value - line);
 
-                    /* Skip Content-Type and Content-Length (already handled) */
-                    if (strcmp(var_name, "HTTP_CONTENT_TYPE") != 0 &&
-                        strcmp(var_name, "HTTP_CONTENT_LENGTH") != 0) {
-                        php_register_variable_safe(var_name, (char *)value, value_len, track_vars_array);
+                    php_register_variable_safe(var_name, (char *)value, value_len, track_vars_array);
+
+                    if (strcmp(var_name, "HTTP_AUTHORIZATION") == 0) {
+                        pox_register_auth_variables(value, value_len, track_vars_array);
                     }
 
                     free(var_name);
@@ -796,7 +1322,7 @@ static sapi_module_struct pox_web_sapi_module = {
     pox_web_read_cookies,          /* read Cookies */
 
     pox_web_register_variables,    /* register server variables */
-    NULL,                           /* Log message */
+    pox_log_message,                /* Log message */
     NULL,                           /* Get request time */
     NULL,                           /* Child terminate */
 
@@ -826,6 +1352,8 @@ int pox_web_init(void) {
     if (pox_web_sapi_module.startup(&pox_web_sapi_module) == FAILURE) {
         return 1;
     }
+    pox_install_error_cb();
+    pox_install_log_hooks();
 
     pox_web_initialized = 1;
     return 0;
@@ -844,6 +1372,68 @@ void pox_web_shutdown(void) {
     pox_web_initialized = 0;
 }
 
+/*
+ * Prepare the calling thread for executing web requests.
+ * Needed on every thread except the one that called pox_web_init().
+ * Returns 1 if PHP is not built with ZTS and can only run on one thread.
+ */
+int pox_web_thread_init(void) {
+#ifdef ZTS
+    (void)ts_resource(0);
+    ZEND_TSRMLS_CACHE_UPDATE();
+    return 0;
+#else
+    return 1;
+#endif
+}
+
+/*
+ * Release the PHP resources of a thread set up with pox_web_thread_init().
+ */
+void pox_web_thread_shutdown(void) {
+#ifdef ZTS
+    ts_free_thread();
+#endif
+}
+
+/*
+ * Execute a router script like php -S does: from the script's directory and
+ * between auto_prepend_file and auto_append_file, keeping its return value.
+ */
+static bool pox_execute_router(zend_file_handle *file_handle, zval *retval) {
+#if PHP_VERSION_ID >= 80300
+    return php_execute_script_ex(file_handle, retval);
+#else
+    zend_file_handle prepend_file, append_file;
+    zend_file_handle *prepend_file_p = NULL, *append_file_p = NULL;
+    bool result;
+
+    if (!(SG(options) & SAPI_OPTION_NO_CHDIR)) {
+        VCWD_CHDIR_FILE(ZSTR_VAL(file_handle->filename));
+    }
+
+    if (PG(auto_prepend_file) && PG(auto_prepend_file)[0]) {
+        zend_stream_init_filename(&prepend_file, PG(auto_prepend_file));
+        prepend_file_p = &prepend_file;
+    }
+    if (PG(auto_append_file) && PG(auto_append_file)[0]) {
+        zend_stream_init_filename(&append_file, PG(auto_append_file));
+        append_file_p = &append_file;
+    }
+
+    result = zend_execute_scripts(ZEND_REQUIRE, retval, 3, prepend_file_p, file_handle, append_file_p) == SUCCESS;
+
+    if (prepend_file_p) {
+        zend_destroy_file_handle(prepend_file_p);
+    }
+    if (append_file_p) {
+        zend_destroy_file_handle(append_file_p);
+    }
+
+    return result;
+#endif
+}
+
 /*
  * Execute a web request.
  * Takes a request context and populates response fields.
@@ -866,6 +1456,8 @@ int pox_web_execute(pox_request_context *ctx) {
     ctx->response_headers_cap = 0;
     ctx->response_status = 200;
     ctx->request_body_read = 0;
+    ctx->peak_memory = 0;
+    ctx->declined = 0;
 
     /* Setup request info */
     SG(request_info).request_method = ctx->method;
@@ -891,13 +1483,25 @@ int pox_web_execute(pox_request_context *ctx) {
             zend_file_handle file_handle;
             zend_stream_init_filename(&file_handle, ctx->script_filename);
 
-            php_execute_script(&file_handle);
+            if (ctx->is_router) {
+                /* Like php -S, a router returning false declines the request */
+                zval retval;
+                ZVAL_UNDEF(&retval);
+                if (pox_execute_router(&file_handle, &retval) && Z_TYPE(retval) != IS_UNDEF) {
+                    ctx->declined = Z_TYPE(retval) == IS_FALSE;
+                    zval_ptr_dtor(&retval);
+                }
+            } else {
+                php_execute_script(&file_handle);
+            }
             result = EG(exit_status);
         }
     } zend_catch {
         result = EG(exit_status);
     } zend_end_try();
 
+    ctx->peak_memory = zend_memory_peak_usage(0);
+
     zend_try {
         php_request_shutdown(NULL);
     } zend_end_try();
@@ -928,6 +1532,52 @@ void pox_free_response(pox_request_context *ctx) {
     }
 }
 
+/*
+ * Get opcache_get_status(false) as a JSON string.
+ * Runs a short request on the calling thread, so PHP must already be
+ * initialized there (pox_web_init() or pox_worker_global_init()).
+ * Returns NULL if opcache is not loaded. Caller must free the returned string.
+ */
+char *pox_opcache_status(void) {
+    char *result = NULL;
+    pox_request_context *saved_request = current_request;
+
+    current_request = NULL;
+
+    zend_try {
+        if (php_request_startup() == SUCCESS) {
+            zval fname, retval, params[1];
+
+            ZVAL_STRING(&fname, "opcache_get_status");
+            ZVAL_FALSE(&params[0]);
+            ZVAL_UNDEF(&retval);
+
+            if (zend_hash_str_exists(EG(function_table), "opcache_get_status", sizeof("opcache_get_status") - 1)
+                && call_user_function(NULL, NULL, &fname, &retval, 1, params) == SUCCESS
+                && Z_TYPE(retval) == IS_ARRAY) {
+                smart_str buf = {0};
+
+                if (php_json_encode(&buf, &retval, 0) == SUCCESS && buf.s) {
+                    smart_str_0(&buf);
+                    result = strdup(ZSTR_VAL(buf.s));
+                }
+                smart_str_free(&buf);
+            }
+
+            zval_ptr_dtor(&retval);
+            zval_ptr_dtor(&fname);
+        }
+    } zend_end_try();
+
+    zend_try {
+        php_request_shutdown(NULL);
+    } zend_end_try();
+
+    current_request = saved_request;
+
+    return result;
+}
+
 /* ============================================================================
  * Worker Mode - Long-running PHP processes like FrankenPHP
  * ============================================================================ */
@@ -951,6 +1601,10 @@ extern void pox_worker_request_done(void);
  * This function is called from the worker script in a loop.
  * It waits for an incoming HTTP request, sets up the request context,
  * calls the callback function, and then signals completion.
+ * Returns false when the worker should stop, ending the loop.
+ *
+ * Also available as frankenphp_handle_request(), so worker scripts written
+ * for FrankenPHP (e.g. with runtime/frankenphp-symfony) run unmodified.
  */
 ZEND_BEGIN_ARG_WITH_RETURN_TYPE_INFO_EX(arginfo_pox_handle_request, 0, 1, _IS_BOOL, 0)
     ZEND_ARG_TYPE_INFO(0, callback, IS_CALLABLE, 0)
@@ -1000,6 +1654,7 @@ PHP_FUNCTION(pox_handle_request) {
     current_request->response_headers_cap = 0;
     current_request->response_status = 200;
     current_request->request_body_read = 0;
+    current_request->peak_memory = 0;
 
     /* Re-initialize request info from the new request */
     SG(request_info).request_method = current_request->method;
@@ -1054,9 +1709,19 @@ PHP_FUNCTION(pox_handle_request) {
     }
     php_output_activate();
 
-    /* Disable timeout in worker mode (we're in a Rust-managed thread) */
+    /* max_execution_time applies to each request instead of the whole worker
+     * script. Only per-thread timers can be used here, elsewhere the Rust side
+     * enforces the request timeout. */
 #ifdef ZEND_MAX_EXECUTION_TIMERS
     zend_unset_timeout();
+    if (EG(timeout_seconds) > 0) {
+        zend_set_timeout(EG(timeout_seconds), 0);
+    }
+#endif
+
+#if PHP_VERSION_ID >= 80200
+    /* Report the peak of this request rather than of the whole worker */
+    zend_memory_reset_peak_usage();
 #endif
 
     /* Call the callback function */
@@ -1079,6 +1744,11 @@ PHP_FUNCTION(pox_handle_request) {
 
     zval_ptr_dtor(&retval);
 
+#ifdef ZEND_MAX_EXECUTION_TIMERS
+    /* No time limit while waiting for the next request */
+    zend_unset_timeout();
+#endif
+
     /* Flush output */
     php_output_end_all();
 
@@ -1087,6 +1757,8 @@ PHP_FUNCTION(pox_handle_request) {
         sapi_send_headers();
     }
 
+    current_request->peak_memory = zend_memory_peak_usage(0);
+
     /* Signal that the response is ready */
     pox_worker_request_done();
 
@@ -1099,6 +1771,7 @@ PHP_FUNCTION(pox_handle_request) {
 /* Module entry for the pox extension */
 static const zend_function_entry pox_functions[] = {
     PHP_FE(pox_handle_request, arginfo_pox_handle_request)
+    PHP_FALIAS(frankenphp_handle_request, pox_handle_request, arginfo_pox_handle_request)
     PHP_FE_END
 };
 
@@ -1115,6 +1788,12 @@ static zend_module_entry pox_module_entry = {
     STANDARD_MODULE_PROPERTIES
 };
 
+/* Worker scripts see FRANKENPHP_WORKER like under FrankenPHP, runtimes use it to detect worker mode */
+static void pox_worker_register_variables(zval *track_vars_array) {
+    pox_web_register_variables(track_vars_array);
+    php_register_variable_safe("FRANKENPHP_WORKER", "1", 1, track_vars_array);
+}
+
 /* Modified startup to register our extension */
 static int pox_worker_startup(sapi_module_struct *sapi_module) {
     if (php_module_startup(sapi_module, &pox_module_entry) == FAILURE) {
@@ -1148,8 +1827,8 @@ static sapi_module_struct pox_worker_sapi_module = {
     pox_web_read_post,             /* read POST data */
     pox_web_read_cookies,          /* read Cookies */
 
-    pox_web_register_variables,    /* register server variables */
-    NULL,                           /* Log message */
+    pox_worker_register_variables, /* register server variables */
+    pox_log_message,                /* Log message */
     NULL,                           /* Get request time */
     NULL,                           /* Child terminate */
 
@@ -1179,6 +1858,8 @@ int pox_worker_global_init(void) {
     if (pox_worker_sapi_module.startup(&pox_worker_sapi_module) == FAILURE) {
         return 1;
     }
+    pox_install_error_cb();
+    pox_install_log_hooks();
 
     pox_worker_global_initialized = 1;
     return 0;
@@ -6,6 +6,9 @@
 #ifndef POX_H
 #define POX_H
 
+#include <stddef.h>
+#include <stdint.h>
+
 #ifdef __cplusplus
 extern "C" {
 #endif
@@ -22,6 +25,68 @@ int pox_execute_script(const char *script_path, int argc, char **argv);
  */
 int pox_execute_code(const char *code, int argc, char **argv);
 
+/*
+ * Execute a PHP script file with additional $_ENV and $_SERVER variables,
+ * given as key, value pairs, without touching the process environment.
+ * Returns the exit status code.
+ */
+int pox_execute_script_with_vars(const char *script_path, int argc, char **argv,
+                                 const char *const *env, int env_count,
+                                 const char *const *server, int server_count);
+
+/*
+ * Receives PHP errors (E_* type, message, file and line) instead of
+ * PHP displaying or logging them.
+ */
+typedef void (*pox_error_handler_fn)(void *data, int type, const char *message, size_t message_len,
+                                     const char *file, size_t file_len, uint32_t line);
+
+/*
+ * Set the handler receiving PHP errors, NULL restores PHP's own error output.
+ */
+void pox_set_error_handler(pox_error_handler_fn handler, void *data);
+
+/*
+ * Receives the messages PHP logs (syslog level and message): error_log()
+ * calls, logged errors and startup errors.
+ */
+typedef void (*pox_log_handler_fn)(void *data, int level, const char *message, size_t message_len);
+
+/*
+ * Set the handler receiving PHP's log messages, NULL restores logging to
+ * the error_log file or stderr.
+ */
+void pox_set_log_handler(pox_log_handler_fn handler, void *data);
+
+/*
+ * Start an interactive shell request kept alive across evaluations.
+ * Returns 0 on success.
+ */
+int pox_repl_start(int argc, char **argv);
+
+/*
+ * Evaluate code in the shell request, printing the value of expressions
+ * when print_result is set.
+ * Returns 0 on success, 1 on error, 2 after exit() was called.
+ */
+int pox_repl_eval(const char *code, int print_result);
+
+/*
+ * Exit status set by exit() in the shell request.
+ */
+int pox_repl_exit_status(void);
+
+/*
+ * Completion candidates for a prefix, newline-separated.
+ * Caller must free the returned string with pox_free_string().
+ */
+char *pox_repl_complete(const char *prefix);
+
+/*
+ * End the shell request and shut PHP down.
+ */
+void pox_repl_shutdown(void);
+
 /*
  * Get PHP version string.
  */
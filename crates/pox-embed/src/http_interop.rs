@@ -0,0 +1,194 @@
+//! Conversions between [`HttpRequest`]/[`HttpResponse`] and the `http` crate
+//! types used by hyper, axum and tower, enabled with the `http` feature.
+//!
+//! The document root and script filename are not part of an `http::Request`,
+//! convert into an [`HttpRequestBuilder`] to set them:
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use pox_embed::{HttpRequest, HttpRequestBuilder, PhpWeb};
+//!
+//! fn handle(web: &PhpWeb, request: http::Request<Bytes>) -> http::Response<Bytes> {
+//!     let request = HttpRequestBuilder::from(request)
+//!         .document_root("/var/www")
+//!         .script_filename("/var/www/index.php")
+//!         .build();
+//!
+//!     match web.execute(request) {
+//!         Ok(response) => response.into(),
+//!         Err(_) => http::Response::builder().status(502).body(Bytes::new()).unwrap(),
+//!     }
+//! }
+//! ```
+
+use bytes::Bytes;
+use http::header::HOST;
+use http::{HeaderName, HeaderValue, Version};
+
+use crate::{HttpRequest, HttpRequestBuilder, HttpResponse};
+
+impl From<http::Request<Bytes>> for HttpRequestBuilder {
+    /// Method, URI, headers, body and protocol of the request. The server name
+    /// and port come from the URI authority or the `Host` header, `https` from
+    /// the URI scheme.
+    fn from(request: http::Request<Bytes>) -> Self {
+        let (parts, body) = request.into_parts();
+
+        let https = parts.uri.scheme() == Some(&http::uri::Scheme::HTTPS);
+        let default_port = if https { 443 } else { 80 };
+        let authority = parts
+            .uri
+            .authority()
+            .map(|a| a.as_str().to_string())
+            .or_else(|| parts.headers.get(HOST).and_then(|h| h.to_str().ok()).map(String::from));
+
+        let mut builder = HttpRequest::builder()
+            .method(parts.method.as_str())
+            .uri(parts.uri.path_and_query().map_or("/", |p| p.as_str()))
+            .body(Vec::from(body))
+            .protocol(protocol(parts.version))
+            .https(https);
+
+        if let Some(authority) = authority {
+            let (name, port) = split_authority(&authority, default_port);
+            builder = builder.server(name, port);
+        } else {
+            builder = builder.server("localhost", default_port);
+        }
+
+        for (name, value) in &parts.headers {
+            builder = builder.header(name.as_str(), String::from_utf8_lossy(value.as_bytes()));
+        }
+
+        builder
+    }
+}
+
+impl From<http::Request<Bytes>> for HttpRequest {
+    fn from(request: http::Request<Bytes>) -> Self {
+        HttpRequestBuilder::from(request).build()
+    }
+}
+
+impl From<HttpResponse> for http::Response<Bytes> {
+    /// Headers that are not valid HTTP headers are dropped, an invalid status becomes 500
+    fn from(response: HttpResponse) -> Self {
+        let mut http_response = http::Response::new(Bytes::from(response.body));
+        *http_response.status_mut() =
+            http::StatusCode::from_u16(response.status).unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+
+        let headers = http_response.headers_mut();
+        for (name, value) in response.headers {
+            if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+                headers.append(name, value);
+            }
+        }
+
+        http_response
+    }
+}
+
+/// `$_SERVER['SERVER_PROTOCOL']` for an HTTP version
+fn protocol(version: Version) -> &'static str {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_2 => "HTTP/2",
+        Version::HTTP_3 => "HTTP/3",
+        _ => "HTTP/1.1",
+    }
+}
+
+/// Host and port of an authority like `example.com:8080` or `[::1]:80`
+fn split_authority(authority: &str, default_port: u16) -> (String, u16) {
+    let host_end = if authority.starts_with('[') {
+        authority.find(']').map_or(authority.len(), |i| i + 1)
+    } else {
+        authority.rfind(':').unwrap_or(authority.len())
+    };
+    let (host, port) = authority.split_at(host_end);
+    let port = port.strip_prefix(':').and_then(|p| p.parse().ok()).unwrap_or(default_port);
+    (host.to_string(), port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_from_http() {
+        let request = http::Request::builder()
+            .method("PUT")
+            .uri("/users/1?verbose=1")
+            .version(Version::HTTP_2)
+            .header("Host", "example.test:8443")
+            .header("Accept", "text/html")
+            .header("Accept", "application/json")
+            .body(Bytes::from_static(b"{}"))
+            .unwrap();
+
+        let request = HttpRequestBuilder::from(request)
+            .document_root("/app/public")
+            .script_filename("/app/public/index.php")
+            .build();
+
+        assert_eq!(request.method, "PUT");
+        assert_eq!(request.uri, "/users/1?verbose=1");
+        assert_eq!(request.query_string, "verbose=1");
+        assert_eq!(request.protocol, "HTTP/2");
+        assert_eq!(request.server_name, "example.test");
+        assert_eq!(request.server_port, 8443);
+        assert_eq!(request.body, b"{}");
+        assert_eq!(request.script_name, "/index.php");
+        assert_eq!(
+            request.headers.iter().filter(|(name, _)| name == "accept").count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_request_from_absolute_https_uri() {
+        let request = http::Request::builder()
+            .uri("https://[::1]/")
+            .body(Bytes::new())
+            .unwrap();
+
+        let request = HttpRequest::from(request);
+        assert!(request.https);
+        assert_eq!(request.server_name, "[::1]");
+        assert_eq!(request.server_port, 443);
+    }
+
+    #[test]
+    fn test_request_uri_keeps_query() {
+        let request = http::Request::builder()
+            .uri("http://example.test/search?a=b")
+            .body(Bytes::new())
+            .unwrap();
+
+        let request = HttpRequest::from(request);
+        assert_eq!(request.uri, "/search?a=b");
+        assert_eq!(request.query_string, "a=b");
+    }
+
+    #[test]
+    fn test_response_into_http() {
+        let response = HttpResponse {
+            status: 201,
+            headers: vec![
+                ("Set-Cookie".to_string(), "a=1".to_string()),
+                ("Set-Cookie".to_string(), "b=2".to_string()),
+                ("Bad Header".to_string(), "dropped".to_string()),
+            ],
+            body: b"created".to_vec(),
+            peak_memory: 0,
+            declined: false,
+        };
+
+        let response: http::Response<Bytes> = response.into();
+        assert_eq!(response.status(), http::StatusCode::CREATED);
+        assert_eq!(response.headers().get_all("set-cookie").iter().count(), 2);
+        assert_eq!(response.headers().len(), 2);
+        assert_eq!(response.body(), &Bytes::from_static(b"created"));
+    }
+}
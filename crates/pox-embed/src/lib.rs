@@ -52,8 +52,16 @@
 //! Static linking will automatically include all PHP dependencies (libxml2, openssl,
 //! zlib, etc.) that PHP was compiled with.
 
+#[cfg(feature = "http")]
+mod http_interop;
+#[cfg(feature = "log")]
+mod log_bridge;
+
 use std::ffi::{CStr, CString, NulError};
+use std::io::Read;
 use std::os::raw::{c_char, c_int, c_void};
+use std::path::PathBuf;
+use std::sync::RwLock;
 use thiserror::Error;
 
 // FFI bindings to our C code - CLI mode
@@ -63,11 +71,22 @@ extern "C" {
         argc: c_int,
         argv: *mut *mut c_char,
     ) -> c_int;
+    fn pox_execute_script_with_vars(
+        script_path: *const c_char,
+        argc: c_int,
+        argv: *mut *mut c_char,
+        env: *const *const c_char,
+        env_count: c_int,
+        server: *const *const c_char,
+        server_count: c_int,
+    ) -> c_int;
     fn pox_execute_code(code: *const c_char, argc: c_int, argv: *mut *mut c_char) -> c_int;
     fn pox_lint_file(script_path: *const c_char, argc: c_int, argv: *mut *mut c_char) -> c_int;
     fn pox_info(flag: c_int, argc: c_int, argv: *mut *mut c_char) -> c_int;
     fn pox_print_modules(argc: c_int, argv: *mut *mut c_char) -> c_int;
     fn pox_set_ini_entries(entries: *const c_char);
+    fn pox_set_error_handler(handler: Option<ErrorHandlerFn>, data: *mut c_void);
+    fn pox_set_log_handler(handler: Option<LogHandlerFn>, data: *mut c_void);
     fn pox_get_version() -> *const c_char;
     fn pox_get_version_id() -> c_int;
     fn pox_get_zend_version() -> *const c_char;
@@ -85,12 +104,24 @@ extern "C" {
     fn pox_get_curl_version() -> *const c_char;
 }
 
+// FFI bindings to our C code - interactive shell
+extern "C" {
+    fn pox_repl_start(argc: c_int, argv: *mut *mut c_char) -> c_int;
+    fn pox_repl_eval(code: *const c_char, print_result: c_int) -> c_int;
+    fn pox_repl_exit_status() -> c_int;
+    fn pox_repl_complete(prefix: *const c_char) -> *mut c_char;
+    fn pox_repl_shutdown();
+}
+
 // FFI bindings to our C code - Web mode
 extern "C" {
     fn pox_web_init() -> c_int;
     fn pox_web_shutdown();
     fn pox_web_execute(ctx: *mut c_void) -> c_int;
+    fn pox_web_thread_init() -> c_int;
+    fn pox_web_thread_shutdown();
     fn pox_free_response(ctx: *mut c_void);
+    fn pox_opcache_status() -> *mut c_char;
 }
 
 /// Errors that can occur when executing PHP
@@ -104,6 +135,15 @@ pub enum PhpError {
 
     #[error("PHP execution failed with exit code {0}")]
     ExecutionFailed(i32),
+
+    #[error("Request timed out after {0:?}")]
+    Timeout(std::time::Duration),
+
+    #[error("Worker exited before sending a response")]
+    WorkerExited,
+
+    #[error("Failed to change into working directory {0}: {1}")]
+    WorkingDir(PathBuf, std::io::Error),
 }
 
 /// Result type for PHP operations
@@ -170,6 +210,178 @@ fn build_argv<A: AsRef<str>>(program: &str, args: &[A]) -> Result<(Vec<CString>,
     Ok((c_args, c_argv))
 }
 
+/// Flatten key, value pairs into C strings, the pointers are only valid while the strings live
+fn build_pairs(pairs: &[(String, String)]) -> Result<(Vec<CString>, Vec<*const c_char>)> {
+    let strings = pairs
+        .iter()
+        .flat_map(|(key, value)| [key, value])
+        .map(|s| CString::new(s.as_str()))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let pointers = strings.iter().map(|s| s.as_ptr()).collect();
+
+    Ok((strings, pointers))
+}
+
+/// Environment of a script run with [`Php::execute_with_superglobals`]
+#[derive(Debug, Clone, Default)]
+pub struct ScriptContext {
+    /// Variables added to `$_ENV`, `$_SERVER` and `getenv()`, the process environment is untouched
+    pub env: Vec<(String, String)>,
+    /// Variables added to `$_SERVER`, overriding the defaults
+    pub server: Vec<(String, String)>,
+    /// Working directory of the script, restored afterwards
+    pub working_dir: Option<PathBuf>,
+}
+
+type ErrorHandlerFn = unsafe extern "C" fn(
+    data: *mut c_void,
+    level: c_int,
+    message: *const c_char,
+    message_len: usize,
+    file: *const c_char,
+    file_len: usize,
+    line: u32,
+);
+
+/// A notice, warning or error raised by PHP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhpErrorEvent {
+    /// The `E_*` constant of the error
+    pub level: i32,
+    pub message: String,
+    pub file: String,
+    pub line: u32,
+}
+
+impl PhpErrorEvent {
+    pub const E_ERROR: i32 = 1;
+    pub const E_WARNING: i32 = 2;
+    pub const E_PARSE: i32 = 4;
+    pub const E_NOTICE: i32 = 8;
+    pub const E_CORE_ERROR: i32 = 16;
+    pub const E_CORE_WARNING: i32 = 32;
+    pub const E_COMPILE_ERROR: i32 = 64;
+    pub const E_COMPILE_WARNING: i32 = 128;
+    pub const E_USER_ERROR: i32 = 256;
+    pub const E_USER_WARNING: i32 = 512;
+    pub const E_USER_NOTICE: i32 = 1024;
+    pub const E_STRICT: i32 = 2048;
+    pub const E_RECOVERABLE_ERROR: i32 = 4096;
+    pub const E_DEPRECATED: i32 = 8192;
+    pub const E_USER_DEPRECATED: i32 = 16384;
+
+    /// Severity as PHP displays it, e.g. `Fatal error` or `Warning`
+    pub fn severity(&self) -> &'static str {
+        match self.level {
+            Self::E_ERROR | Self::E_CORE_ERROR | Self::E_COMPILE_ERROR | Self::E_USER_ERROR => "Fatal error",
+            Self::E_RECOVERABLE_ERROR => "Recoverable fatal error",
+            Self::E_WARNING | Self::E_CORE_WARNING | Self::E_COMPILE_WARNING | Self::E_USER_WARNING => "Warning",
+            Self::E_PARSE => "Parse error",
+            Self::E_NOTICE | Self::E_USER_NOTICE => "Notice",
+            Self::E_STRICT => "Strict Standards",
+            Self::E_DEPRECATED | Self::E_USER_DEPRECATED => "Deprecated",
+            _ => "Unknown error",
+        }
+    }
+
+    /// The error ended the script
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self.level,
+            Self::E_ERROR | Self::E_CORE_ERROR | Self::E_COMPILE_ERROR | Self::E_USER_ERROR
+                | Self::E_RECOVERABLE_ERROR | Self::E_PARSE
+        )
+    }
+}
+
+impl std::fmt::Display for PhpErrorEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PHP {}:  {} in {} on line {}", self.severity(), self.message, self.file, self.line)
+    }
+}
+
+/// Held while [`Php::execute_with_superglobals`] runs, as it changes the process
+/// working directory and hands its variables to PHP through C statics
+static SUPERGLOBALS_LOCK: Mutex<()> = Mutex::new(());
+
+type ErrorHandler = Box<dyn Fn(&PhpErrorEvent) + Send + Sync>;
+
+static ERROR_HANDLER: RwLock<Option<ErrorHandler>> = RwLock::new(None);
+
+/// Called by PHP for every reported error, on the thread running the script
+unsafe extern "C" fn forward_error(
+    _data: *mut c_void,
+    level: c_int,
+    message: *const c_char,
+    message_len: usize,
+    file: *const c_char,
+    file_len: usize,
+    line: u32,
+) {
+    let text = |ptr: *const c_char, len: usize| {
+        String::from_utf8_lossy(std::slice::from_raw_parts(ptr as *const u8, len)).into_owned()
+    };
+    let event = PhpErrorEvent {
+        level,
+        message: text(message, message_len),
+        file: text(file, file_len),
+        line,
+    };
+
+    if let Ok(handler) = ERROR_HANDLER.read() {
+        if let Some(handler) = handler.as_ref() {
+            handler(&event);
+        }
+    }
+}
+
+type LogHandlerFn = unsafe extern "C" fn(data: *mut c_void, level: c_int, message: *const c_char, message_len: usize);
+
+/// A message PHP writes to its log: an `error_log()` call, a logged error or a startup error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhpLogEvent {
+    /// The syslog priority PHP logged the message with, e.g. [`PhpLogEvent::LOG_WARNING`]
+    pub level: i32,
+    pub message: String,
+}
+
+impl PhpLogEvent {
+    pub const LOG_EMERG: i32 = 0;
+    pub const LOG_ALERT: i32 = 1;
+    pub const LOG_CRIT: i32 = 2;
+    pub const LOG_ERR: i32 = 3;
+    pub const LOG_WARNING: i32 = 4;
+    /// `error_log()` calls and notices
+    pub const LOG_NOTICE: i32 = 5;
+    /// Deprecations
+    pub const LOG_INFO: i32 = 6;
+    pub const LOG_DEBUG: i32 = 7;
+}
+
+impl std::fmt::Display for PhpLogEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+type LogHandler = Box<dyn Fn(&PhpLogEvent) + Send + Sync>;
+
+static LOG_HANDLER: RwLock<Option<LogHandler>> = RwLock::new(None);
+
+/// Called by PHP for every message it logs, on the thread running the script
+unsafe extern "C" fn forward_log(_data: *mut c_void, level: c_int, message: *const c_char, message_len: usize) {
+    let event = PhpLogEvent {
+        level,
+        message: String::from_utf8_lossy(std::slice::from_raw_parts(message as *const u8, message_len)).into_owned(),
+    };
+
+    if let Ok(handler) = LOG_HANDLER.read() {
+        if let Some(handler) = handler.as_ref() {
+            handler(&event);
+        }
+    }
+}
+
 /// Main interface for executing PHP code
 pub struct Php;
 
@@ -195,6 +407,53 @@ impl Php {
         Ok(())
     }
 
+    /// Receive PHP errors in `handler` instead of PHP displaying or logging them
+    ///
+    /// The handler is process wide and applies to CLI, web and worker execution. It is
+    /// called on the thread running the script, for errors included in `error_reporting`
+    /// and not handled by a userland `set_error_handler()`. Fatal errors still end the script.
+    pub fn set_error_handler<F>(handler: F)
+    where
+        F: Fn(&PhpErrorEvent) + Send + Sync + 'static,
+    {
+        if let Ok(mut current) = ERROR_HANDLER.write() {
+            *current = Some(Box::new(handler));
+        }
+        unsafe { pox_set_error_handler(Some(forward_error), std::ptr::null_mut()) };
+    }
+
+    /// Remove the error handler, PHP displays and logs errors again
+    pub fn clear_error_handler() {
+        unsafe { pox_set_error_handler(None, std::ptr::null_mut()) };
+        if let Ok(mut current) = ERROR_HANDLER.write() {
+            *current = None;
+        }
+    }
+
+    /// Receive the messages PHP logs in `handler` instead of the `error_log` file or stderr
+    ///
+    /// These are `error_log()` calls, errors logged with `log_errors` and errors raised
+    /// while PHP starts, so the handler must be set before PHP starts. Errors passed to
+    /// the handler of [`Php::set_error_handler`] are not logged. Set the INI entry
+    /// `pox.error_log_file=On` to keep writing to the configured `error_log` file.
+    pub fn set_log_handler<F>(handler: F)
+    where
+        F: Fn(&PhpLogEvent) + Send + Sync + 'static,
+    {
+        if let Ok(mut current) = LOG_HANDLER.write() {
+            *current = Some(Box::new(handler));
+        }
+        unsafe { pox_set_log_handler(Some(forward_log), std::ptr::null_mut()) };
+    }
+
+    /// Remove the log handler, PHP logs to the `error_log` file or stderr again
+    pub fn clear_log_handler() {
+        unsafe { pox_set_log_handler(None, std::ptr::null_mut()) };
+        if let Ok(mut current) = LOG_HANDLER.write() {
+            *current = None;
+        }
+    }
+
     /// Execute a PHP script file
     ///
     /// # Arguments
@@ -225,6 +484,57 @@ impl Php {
         Ok(exit_status)
     }
 
+    /// Execute a PHP script file with injected superglobals
+    ///
+    /// Like [`Php::execute_script`], but the variables of `context` are visible to the
+    /// script without changing the environment of the host process. A relative
+    /// `script_path` is resolved before changing into the working directory.
+    ///
+    /// The working directory is process-wide and the variables are passed to PHP
+    /// through C statics, so calls are serialized by a global lock. Other threads
+    /// still see the changed working directory while a script runs.
+    pub fn execute_with_superglobals<S, A>(script_path: S, args: &[A], context: &ScriptContext) -> Result<i32>
+    where
+        S: AsRef<str>,
+        A: AsRef<str>,
+    {
+        let script_path = std::path::absolute(script_path.as_ref())
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| script_path.as_ref().to_string());
+        let c_script = CString::new(script_path.as_str())?;
+        let (_c_args, mut c_argv) = build_argv(&script_path, args)?;
+        let (_c_env, c_env_ptrs) = build_pairs(&context.env)?;
+        let (_c_server, c_server_ptrs) = build_pairs(&context.server)?;
+
+        let _guard = SUPERGLOBALS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let previous_dir = match &context.working_dir {
+            Some(dir) => {
+                let previous = std::env::current_dir().ok();
+                std::env::set_current_dir(dir).map_err(|e| PhpError::WorkingDir(dir.clone(), e))?;
+                previous
+            }
+            None => None,
+        };
+
+        let exit_status = unsafe {
+            pox_execute_script_with_vars(
+                c_script.as_ptr(),
+                c_argv.len() as c_int - 1,
+                c_argv.as_mut_ptr(),
+                c_env_ptrs.as_ptr(),
+                context.env.len() as c_int,
+                c_server_ptrs.as_ptr(),
+                context.server.len() as c_int,
+            )
+        };
+
+        if let Some(previous) = previous_dir {
+            let _ = std::env::set_current_dir(previous);
+        }
+
+        Ok(exit_status)
+    }
+
     /// Execute PHP code directly
     ///
     /// # Arguments
@@ -417,6 +727,85 @@ impl Php {
     }
 }
 
+// ============================================================================
+// Interactive Shell Support
+// ============================================================================
+
+/// Outcome of evaluating a snippet in the interactive shell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplOutcome {
+    /// Code ran without uncaught errors
+    Ok,
+    /// An uncaught exception or fatal error was reported
+    Error,
+    /// `exit()` was called with the given status
+    Exit(i32),
+}
+
+/// A PHP request kept alive across evaluations, like `php -a`
+///
+/// Variables, functions and classes defined by one evaluation stay available
+/// to the next. Only one shell can be active per process.
+pub struct PhpRepl {
+    _args: Vec<CString>,
+}
+
+impl PhpRepl {
+    /// Start the shell request with `$argv` set to `args`
+    pub fn start<A: AsRef<str>>(args: &[A]) -> Result<Self> {
+        let (c_args, mut c_argv) = build_argv("php", args)?;
+
+        let result = unsafe { pox_repl_start(c_argv.len() as c_int - 1, c_argv.as_mut_ptr()) };
+        if result != 0 {
+            return Err(PhpError::InitFailed);
+        }
+
+        Ok(Self { _args: c_args })
+    }
+
+    /// Evaluate code, printing the value when it is an expression and `print_result` is set
+    pub fn eval(&mut self, code: &str, print_result: bool) -> Result<ReplOutcome> {
+        let c_code = CString::new(code)?;
+
+        let outcome = match unsafe { pox_repl_eval(c_code.as_ptr(), print_result as c_int) } {
+            0 => ReplOutcome::Ok,
+            2 => ReplOutcome::Exit(unsafe { pox_repl_exit_status() }),
+            _ => ReplOutcome::Error,
+        };
+
+        Ok(outcome)
+    }
+
+    /// Names of variables (for a `$` prefix), functions, classes and constants starting with `prefix`
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        let Ok(c_prefix) = CString::new(prefix) else {
+            return Vec::new();
+        };
+
+        let ptr = unsafe { pox_repl_complete(c_prefix.as_ptr()) };
+        if ptr.is_null() {
+            return Vec::new();
+        }
+
+        unsafe {
+            let candidates = CStr::from_ptr(ptr)
+                .to_string_lossy()
+                .lines()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect();
+            pox_free_string(ptr);
+            candidates
+        }
+    }
+}
+
+impl Drop for PhpRepl {
+    fn drop(&mut self) {
+        unsafe { pox_repl_shutdown() };
+    }
+}
+
 // ============================================================================
 // Web Server Support
 // ============================================================================
@@ -440,12 +829,17 @@ pub struct PhpRequestContext {
     // Document root and script
     document_root: *const c_char,
     script_filename: *const c_char,
+    script_name: *const c_char,
+    path_info: *const c_char,
+    is_router: c_int,
 
     // Server info
     server_name: *const c_char,
     server_port: c_int,
     remote_addr: *const c_char,
     remote_port: c_int,
+    server_protocol: *const c_char,
+    https: c_int,
 
     // Response output buffer (filled by C code)
     response_body: *mut c_char,
@@ -459,21 +853,251 @@ pub struct PhpRequestContext {
 
     // Response status
     response_status: c_int,
+
+    // Peak memory usage of the request (filled by C code)
+    peak_memory: usize,
+
+    // Router script returned false (filled by C code)
+    declined: c_int,
+
+    // Streamed request body, read through `read_body` instead of `request_body`
+    body_stream: *mut c_void,
+    read_body: Option<ReadBodyFn>,
+}
+
+/// Reads the next chunk of a streamed request body into `buffer`, returns 0 at the end
+type ReadBodyFn = unsafe extern "C" fn(stream: *mut c_void, buffer: *mut c_char, len: usize) -> usize;
+
+/// Largest chunk of a streamed request body handed to PHP at once
+const BODY_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Read from a request body stream, treating errors as the end of the body
+fn read_chunk(reader: &mut dyn Read, buffer: &mut [u8]) -> usize {
+    loop {
+        match reader.read(buffer) {
+            Ok(n) => return n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                eprintln!("Failed to read request body: {}", e);
+                return 0;
+            }
+        }
+    }
+}
+
+/// `Content-Length` of a request whose body is streamed, 0 if unknown
+fn streamed_content_length(headers: &[(String, String)]) -> usize {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Format headers as "Key: Value\n" lines for the C side
+///
+/// Repeated headers are folded into one line like php -S does, cookies joined
+/// with `; ` and everything else with `, `.
+fn format_headers(headers: &[(String, String)]) -> String {
+    let mut folded: Vec<(&str, String)> = Vec::new();
+    for (name, value) in headers {
+        match folded.iter_mut().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            Some((_, existing)) => {
+                existing.push_str(if name.eq_ignore_ascii_case("cookie") { "; " } else { ", " });
+                existing.push_str(value);
+            }
+            None => folded.push((name, value.clone())),
+        }
+    }
+
+    folded
+        .iter()
+        .map(|(k, v)| format!("{}: {}\n", k, v))
+        .collect()
+}
+
+/// [`ReadBodyFn`] for a body read on the executing thread, `stream` is a `*mut &mut dyn Read`
+unsafe extern "C" fn read_body_stream(stream: *mut c_void, buffer: *mut c_char, len: usize) -> usize {
+    let reader = &mut *(stream as *mut &mut dyn Read);
+    let buffer = std::slice::from_raw_parts_mut(buffer as *mut u8, len.min(BODY_CHUNK_SIZE));
+    read_chunk(*reader, buffer)
 }
 
 /// HTTP request to execute
 pub struct HttpRequest {
     pub method: String,
+    /// `$_SERVER['REQUEST_URI']`, the path including the query string
     pub uri: String,
     pub query_string: String,
     pub headers: Vec<(String, String)>,
     pub body: Vec<u8>,
     pub document_root: String,
     pub script_filename: String,
+    /// `$_SERVER['SCRIPT_NAME']`, the script's URI path
+    pub script_name: String,
+    /// `$_SERVER['PATH_INFO']`, the URI path following the script name
+    pub path_info: Option<String>,
+    /// Run the script as a `php -S` style router, see [`HttpResponse::declined`]
+    pub router: bool,
     pub server_name: String,
     pub server_port: u16,
     pub remote_addr: String,
     pub remote_port: u16,
+    /// `$_SERVER['SERVER_PROTOCOL']`, e.g. `HTTP/1.1`
+    pub protocol: String,
+    /// The request was received over TLS, sets `$_SERVER['HTTPS']`
+    pub https: bool,
+}
+
+impl HttpRequest {
+    /// Start building a request, see [`HttpRequestBuilder`] for the defaults
+    pub fn builder() -> HttpRequestBuilder {
+        HttpRequestBuilder::new()
+    }
+}
+
+/// Builder for [`HttpRequest`]
+///
+/// Defaults to `GET /` over `HTTP/1.1` for `localhost:80` from `127.0.0.1`.
+/// The script name is derived from the script filename and document root
+/// unless it is set explicitly.
+///
+/// ```no_run
+/// use pox_embed::HttpRequest;
+///
+/// let request = HttpRequest::builder()
+///     .method("POST")
+///     .uri("/index.php?page=2")
+///     .header("Content-Type", "application/json")
+///     .body(r#"{"name":"pox"}"#)
+///     .document_root("/var/www")
+///     .script_filename("/var/www/index.php")
+///     .build();
+/// assert_eq!(request.query_string, "page=2");
+/// assert_eq!(request.script_name, "/index.php");
+/// ```
+pub struct HttpRequestBuilder {
+    request: HttpRequest,
+}
+
+impl HttpRequestBuilder {
+    fn new() -> Self {
+        Self {
+            request: HttpRequest {
+                method: "GET".to_string(),
+                uri: "/".to_string(),
+                query_string: String::new(),
+                headers: Vec::new(),
+                body: Vec::new(),
+                document_root: String::new(),
+                script_filename: String::new(),
+                script_name: String::new(),
+                path_info: None,
+                router: false,
+                server_name: "localhost".to_string(),
+                server_port: 80,
+                remote_addr: "127.0.0.1".to_string(),
+                remote_port: 0,
+                protocol: "HTTP/1.1".to_string(),
+                https: false,
+            },
+        }
+    }
+
+    pub fn method(mut self, method: impl Into<String>) -> Self {
+        self.request.method = method.into();
+        self
+    }
+
+    /// Request URI, `$_SERVER['REQUEST_URI']`, a query string following `?` also sets the query string
+    pub fn uri(mut self, uri: impl Into<String>) -> Self {
+        self.request.uri = uri.into();
+        if let Some((_, query)) = self.request.uri.split_once('?') {
+            self.request.query_string = query.to_string();
+        }
+        self
+    }
+
+    pub fn query_string(mut self, query_string: impl Into<String>) -> Self {
+        self.request.query_string = query_string.into();
+        self
+    }
+
+    /// Add a header, repeated headers are kept in order
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.request.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.request.body = body.into();
+        self
+    }
+
+    pub fn document_root(mut self, document_root: impl Into<String>) -> Self {
+        self.request.document_root = document_root.into();
+        self
+    }
+
+    pub fn script_filename(mut self, script_filename: impl Into<String>) -> Self {
+        self.request.script_filename = script_filename.into();
+        self
+    }
+
+    pub fn script_name(mut self, script_name: impl Into<String>) -> Self {
+        self.request.script_name = script_name.into();
+        self
+    }
+
+    pub fn path_info(mut self, path_info: impl Into<String>) -> Self {
+        self.request.path_info = Some(path_info.into());
+        self
+    }
+
+    /// Run the script as a `php -S` style router
+    pub fn router(mut self, router: bool) -> Self {
+        self.request.router = router;
+        self
+    }
+
+    /// Server name and port, `$_SERVER['SERVER_NAME']` and `$_SERVER['SERVER_PORT']`
+    pub fn server(mut self, name: impl Into<String>, port: u16) -> Self {
+        self.request.server_name = name.into();
+        self.request.server_port = port;
+        self
+    }
+
+    /// Address of the client, `$_SERVER['REMOTE_ADDR']` and `$_SERVER['REMOTE_PORT']`
+    pub fn remote_addr(mut self, addr: std::net::SocketAddr) -> Self {
+        self.request.remote_addr = addr.ip().to_string();
+        self.request.remote_port = addr.port();
+        self
+    }
+
+    pub fn protocol(mut self, protocol: impl Into<String>) -> Self {
+        self.request.protocol = protocol.into();
+        self
+    }
+
+    pub fn https(mut self, https: bool) -> Self {
+        self.request.https = https;
+        self
+    }
+
+    pub fn build(mut self) -> HttpRequest {
+        if self.request.script_name.is_empty() {
+            self.request.script_name = default_script_name(&self.request.document_root, &self.request.script_filename);
+        }
+        self.request
+    }
+}
+
+/// The script's URI path, its filename relative to the document root
+fn default_script_name(document_root: &str, script_filename: &str) -> String {
+    let relative = script_filename
+        .strip_prefix(document_root.trim_end_matches('/'))
+        .unwrap_or(script_filename);
+    format!("/{}", relative.trim_start_matches('/'))
 }
 
 /// HTTP response from PHP execution
@@ -481,6 +1105,10 @@ pub struct HttpResponse {
     pub status: u16,
     pub headers: Vec<(String, String)>,
     pub body: Vec<u8>,
+    /// Peak PHP memory usage of the request in bytes
+    pub peak_memory: usize,
+    /// The router script returned `false`, the request should be served as if there was no router
+    pub declined: bool,
 }
 
 /// PHP web server runtime
@@ -500,99 +1128,199 @@ impl PhpWeb {
 
     /// Execute an HTTP request and return the response
     pub fn execute(&self, request: HttpRequest) -> Result<HttpResponse> {
-        // Convert strings to CStrings, keeping them alive
-        let method = CString::new(request.method)?;
-        let uri = CString::new(request.uri)?;
-        let query_string = CString::new(request.query_string)?;
-        let document_root = CString::new(request.document_root)?;
-        let script_filename = CString::new(request.script_filename)?;
-        let server_name = CString::new(request.server_name)?;
-        let remote_addr = CString::new(request.remote_addr)?;
+        execute_web_request(request, None)
+    }
 
-        // Format headers as "Key: Value\n" string
-        let headers_str: String = request
-            .headers
-            .iter()
-            .map(|(k, v)| format!("{}: {}\n", k, v))
-            .collect();
-        let headers = CString::new(headers_str)?;
+    /// Execute an HTTP request whose body is read from `body` as PHP consumes it
+    ///
+    /// `request.body` is ignored. Uploads are streamed into `php://input` and
+    /// `$_FILES` without buffering the whole body in memory first.
+    pub fn execute_with_body(&self, request: HttpRequest, body: &mut dyn Read) -> Result<HttpResponse> {
+        execute_web_request(request, Some(body))
+    }
 
-        // Get content type from headers
-        let content_type = request
-            .headers
-            .iter()
-            .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
-            .map(|(_, v)| v.clone())
-            .unwrap_or_default();
-        let content_type_c = CString::new(content_type)?;
-
-        // Create the request context
-        let mut ctx = PhpRequestContext {
-            method: method.as_ptr(),
-            uri: uri.as_ptr(),
-            query_string: query_string.as_ptr(),
-            content_type: content_type_c.as_ptr(),
-            content_length: request.body.len(),
-            request_body: request.body.as_ptr() as *const c_char,
-            request_body_len: request.body.len(),
-            request_body_read: 0,
-            headers: headers.as_ptr(),
-            document_root: document_root.as_ptr(),
-            script_filename: script_filename.as_ptr(),
-            server_name: server_name.as_ptr(),
-            server_port: request.server_port as c_int,
-            remote_addr: remote_addr.as_ptr(),
-            remote_port: request.remote_port as c_int,
-            response_body: std::ptr::null_mut(),
-            response_body_len: 0,
-            response_body_cap: 0,
-            response_headers: std::ptr::null_mut(),
-            response_headers_len: 0,
-            response_headers_cap: 0,
-            response_status: 200,
-        };
+    /// Set up the calling thread to execute requests concurrently with this runtime
+    ///
+    /// Only supported when PHP is built with ZTS (see [`Php::is_zts`]).
+    pub fn attach_thread(&self) -> Result<PhpWebThread> {
+        let result = unsafe { pox_web_thread_init() };
+        if result != 0 {
+            return Err(PhpError::InitFailed);
+        }
+        Ok(PhpWebThread {
+            _not_send: std::marker::PhantomData,
+        })
+    }
 
-        // Execute the request
-        let _exit_code = unsafe { pox_web_execute(&mut ctx as *mut _ as *mut c_void) };
+    /// `opcache_get_status(false)` as JSON, or `None` if opcache is not loaded or disabled
+    ///
+    /// Must be called from the thread that created the runtime.
+    pub fn opcache_status(&self) -> Option<String> {
+        opcache_status()
+    }
+}
 
-        // Extract response body
-        let body = if !ctx.response_body.is_null() && ctx.response_body_len > 0 {
-            unsafe {
-                std::slice::from_raw_parts(ctx.response_body as *const u8, ctx.response_body_len)
-                    .to_vec()
-            }
-        } else {
-            Vec::new()
-        };
+/// Request execution context of an additional thread of a [`PhpWeb`] runtime
+///
+/// Bound to the thread that created it; PHP's per-thread state is released on drop.
+pub struct PhpWebThread {
+    _not_send: std::marker::PhantomData<*const ()>,
+}
 
-        // Parse response headers
-        let mut response_headers = Vec::new();
-        if !ctx.response_headers.is_null() && ctx.response_headers_len > 0 {
-            let headers_bytes = unsafe {
-                std::slice::from_raw_parts(
-                    ctx.response_headers as *const u8,
-                    ctx.response_headers_len,
-                )
-            };
-            if let Ok(headers_str) = std::str::from_utf8(headers_bytes) {
-                for line in headers_str.lines() {
-                    if let Some(colon_pos) = line.find(':') {
-                        let key = line[..colon_pos].trim().to_string();
-                        let value = line[colon_pos + 1..].trim().to_string();
-                        response_headers.push((key, value));
-                    }
+impl PhpWebThread {
+    /// Execute an HTTP request and return the response
+    pub fn execute(&self, request: HttpRequest) -> Result<HttpResponse> {
+        execute_web_request(request, None)
+    }
+
+    /// Execute an HTTP request whose body is read from `body`, see [`PhpWeb::execute_with_body`]
+    pub fn execute_with_body(&self, request: HttpRequest, body: &mut dyn Read) -> Result<HttpResponse> {
+        execute_web_request(request, Some(body))
+    }
+
+    /// `opcache_get_status(false)` as JSON, or `None` if opcache is not loaded or disabled
+    ///
+    /// Runs on this thread's own PHP context.
+    pub fn opcache_status(&self) -> Option<String> {
+        opcache_status()
+    }
+}
+
+impl Drop for PhpWebThread {
+    fn drop(&mut self) {
+        unsafe { pox_web_thread_shutdown() };
+    }
+}
+
+/// Execute an HTTP request on the calling thread
+fn execute_web_request(request: HttpRequest, mut body: Option<&mut dyn Read>) -> Result<HttpResponse> {
+    // Convert strings to CStrings, keeping them alive
+    let method = CString::new(request.method)?;
+    let uri = CString::new(request.uri)?;
+    let query_string = CString::new(request.query_string)?;
+    let document_root = CString::new(request.document_root)?;
+    let script_filename = CString::new(request.script_filename)?;
+    let script_name = CString::new(request.script_name)?;
+    let path_info = request.path_info.map(CString::new).transpose()?;
+    let server_name = CString::new(request.server_name)?;
+    let remote_addr = CString::new(request.remote_addr)?;
+
+    let protocol = CString::new(request.protocol)?;
+    let headers = CString::new(format_headers(&request.headers))?;
+
+    // Get content type from headers
+    let content_type = request
+        .headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+        .map(|(_, v)| v.clone())
+        .unwrap_or_default();
+    let content_type_c = CString::new(content_type)?;
+
+    let content_length = match body {
+        Some(_) => streamed_content_length(&request.headers),
+        None => request.body.len(),
+    };
+    let (body_stream, read_body) = match body.as_mut() {
+        Some(reader) => (reader as *mut &mut dyn Read as *mut c_void, Some(read_body_stream as ReadBodyFn)),
+        None => (std::ptr::null_mut(), None),
+    };
+
+    // Create the request context
+    let mut ctx = PhpRequestContext {
+        method: method.as_ptr(),
+        uri: uri.as_ptr(),
+        query_string: query_string.as_ptr(),
+        content_type: content_type_c.as_ptr(),
+        content_length,
+        request_body: request.body.as_ptr() as *const c_char,
+        request_body_len: request.body.len(),
+        request_body_read: 0,
+        headers: headers.as_ptr(),
+        document_root: document_root.as_ptr(),
+        script_filename: script_filename.as_ptr(),
+        script_name: script_name.as_ptr(),
+        path_info: path_info.as_ref().map_or(std::ptr::null(), |p| p.as_ptr()),
+        is_router: request.router as c_int,
+        server_name: server_name.as_ptr(),
+        server_port: request.server_port as c_int,
+        remote_addr: remote_addr.as_ptr(),
+        remote_port: request.remote_port as c_int,
+        server_protocol: protocol.as_ptr(),
+        https: request.https as c_int,
+        response_body: std::ptr::null_mut(),
+        response_body_len: 0,
+        response_body_cap: 0,
+        response_headers: std::ptr::null_mut(),
+        response_headers_len: 0,
+        response_headers_cap: 0,
+        response_status: 200,
+        peak_memory: 0,
+        declined: 0,
+        body_stream,
+        read_body,
+    };
+
+    // Execute the request
+    let _exit_code = unsafe { pox_web_execute(&mut ctx as *mut _ as *mut c_void) };
+
+    // Extract response body
+    let body = if !ctx.response_body.is_null() && ctx.response_body_len > 0 {
+        unsafe {
+            std::slice::from_raw_parts(ctx.response_body as *const u8, ctx.response_body_len)
+                .to_vec()
+        }
+    } else {
+        Vec::new()
+    };
+
+    // Parse response headers
+    let mut response_headers = Vec::new();
+    if !ctx.response_headers.is_null() && ctx.response_headers_len > 0 {
+        let headers_bytes = unsafe {
+            std::slice::from_raw_parts(
+                ctx.response_headers as *const u8,
+                ctx.response_headers_len,
+            )
+        };
+        if let Ok(headers_str) = std::str::from_utf8(headers_bytes) {
+            for line in headers_str.lines() {
+                if let Some(colon_pos) = line.find(':') {
+                    let key = line[..colon_pos].trim().to_string();
+                    let value = line[colon_pos + 1..].trim().to_string();
+                    response_headers.push((key, value));
                 }
             }
         }
+    }
 
-        // Free C-allocated response buffers
-        unsafe { pox_free_response(&mut ctx as *mut _ as *mut c_void) };
+    // Free C-allocated response buffers
+    unsafe { pox_free_response(&mut ctx as *mut _ as *mut c_void) };
 
-        Ok(HttpResponse {
-            status: ctx.response_status as u16,
-            headers: response_headers,
-            body,
-        })
+    Ok(HttpResponse {
+        status: ctx.response_status as u16,
+        headers: response_headers,
+        body,
+        peak_memory: ctx.peak_memory,
+        declined: ctx.declined != 0,
+    })
+}
+
+/// Current `opcache_get_status(false)` as JSON, or `None` if opcache is not
+/// loaded or disabled, in which case `opcache_get_status` returns `false`
+///
+/// Runs a short request on the calling thread, which must have a PHP context:
+/// the thread that initialized PHP or one holding a [`PhpWebThread`].
+fn opcache_status() -> Option<String> {
+    let ptr = unsafe { pox_opcache_status() };
+    if ptr.is_null() {
+        return None;
+    }
+
+    unsafe {
+        let status = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+        pox_free_string(ptr);
+        Some(status)
     }
 }
 
@@ -612,9 +1340,14 @@ impl Drop for PhpWeb {
 // Worker Mode Support
 // ============================================================================
 
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
 use std::thread::{self, JoinHandle};
+use std::time::Instant;
 
 // FFI bindings for worker mode
 extern "C" {
@@ -630,8 +1363,11 @@ struct RequestStrings {
     query_string: CString,
     document_root: CString,
     script_filename: CString,
+    script_name: CString,
+    path_info: Option<CString>,
     server_name: CString,
     remote_addr: CString,
+    protocol: CString,
     headers: CString,
     content_type: CString,
     body: Vec<u8>,
@@ -665,6 +1401,18 @@ struct WorkerThreadState {
     has_response: AtomicBool,
     /// Whether Rust has finished reading the response
     response_read: AtomicBool,
+    /// Whether the worker script has returned (or bailed out)
+    exited: AtomicBool,
+    /// Bytes of the streamed request body the worker is waiting for, 0 if none
+    body_wanted: AtomicUsize,
+    /// Next chunk of the streamed request body, empty at the end of the body
+    body_chunk: Mutex<Option<Vec<u8>>>,
+    /// Condition variable to signal the worker that a body chunk is available
+    body_ready: Condvar,
+    /// Whether an async request owns the worker, it does not hold the worker's lock while waiting
+    claimed: AtomicBool,
+    /// Task of an async request to wake once the response is ready or the worker exited
+    response_waker: Mutex<Option<Waker>>,
 }
 
 impl WorkerThreadState {
@@ -678,6 +1426,24 @@ impl WorkerThreadState {
             processing: AtomicBool::new(false),
             has_response: AtomicBool::new(false),
             response_read: AtomicBool::new(false),
+            exited: AtomicBool::new(false),
+            body_wanted: AtomicUsize::new(0),
+            body_chunk: Mutex::new(None),
+            body_ready: Condvar::new(),
+            claimed: AtomicBool::new(false),
+            response_waker: Mutex::new(None),
+        }
+    }
+
+    /// Whether waiting for the response is over, successfully or not
+    fn is_finished(&self) -> bool {
+        self.has_response.load(Ordering::SeqCst) || self.exited.load(Ordering::SeqCst)
+    }
+
+    /// Wake the async request waiting for this worker, the counterpart of `response_ready`
+    fn wake_response(&self) {
+        if let Some(waker) = self.response_waker.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            waker.wake();
         }
     }
 }
@@ -732,6 +1498,7 @@ pub extern "C" fn pox_worker_request_done() {
             worker_state.has_response.store(true, Ordering::SeqCst);
             worker_state.processing.store(false, Ordering::SeqCst);
             worker_state.response_ready.notify_all();
+            worker_state.wake_response();
 
             // Wait for Rust to finish reading the response
             let req = worker_state.request.lock().unwrap_or_else(|e| e.into_inner());
@@ -746,6 +1513,32 @@ pub extern "C" fn pox_worker_request_done() {
     });
 }
 
+/// [`ReadBodyFn`] for worker threads
+///
+/// The body is read by the thread that submitted the request, which serves
+/// chunks while it waits for the response.
+unsafe extern "C" fn read_worker_body(_stream: *mut c_void, buffer: *mut c_char, len: usize) -> usize {
+    WORKER_STATE.with(|state| {
+        let state_ref = state.borrow();
+        let Some(ref worker_state) = *state_ref else {
+            return 0;
+        };
+
+        let req = worker_state.request.lock().unwrap_or_else(|e| e.into_inner());
+        worker_state.body_wanted.store(len.clamp(1, BODY_CHUNK_SIZE), Ordering::SeqCst);
+        worker_state.response_ready.notify_all();
+        let _req = worker_state.body_ready.wait_while(req, |_| {
+            worker_state.body_wanted.load(Ordering::SeqCst) != 0
+                && !worker_state.shutdown.load(Ordering::SeqCst)
+        }).unwrap_or_else(|e| e.into_inner());
+
+        let chunk = worker_state.body_chunk.lock().unwrap_or_else(|e| e.into_inner()).take().unwrap_or_default();
+        let n = chunk.len().min(len);
+        std::ptr::copy_nonoverlapping(chunk.as_ptr(), buffer as *mut u8, n);
+        n
+    })
+}
+
 /// A worker thread that runs a long-lived PHP script
 struct WorkerThread {
     handle: Option<JoinHandle<()>>,
@@ -769,6 +1562,16 @@ impl WorkerThread {
             unsafe {
                 pox_worker_run(c_script.as_ptr(), c_docroot.as_ptr());
             }
+
+            // Wake up a request that will never get a response, e.g. after a fatal error
+            let worker_state = WORKER_STATE.with(|s| s.borrow_mut().take());
+            if let Some(worker_state) = worker_state {
+                let _req = worker_state.request.lock().unwrap_or_else(|e| e.into_inner());
+                worker_state.exited.store(true, Ordering::SeqCst);
+                worker_state.response_ready.notify_all();
+                drop(_req);
+                worker_state.wake_response();
+            }
         });
 
         Self {
@@ -780,25 +1583,87 @@ impl WorkerThread {
     fn is_available(&self) -> bool {
         !self.state.processing.load(Ordering::SeqCst)
             && !self.state.shutdown.load(Ordering::SeqCst)
+            && !self.state.exited.load(Ordering::SeqCst)
+            && !self.is_claimed()
     }
 
-    fn submit_request(&self, request: HttpRequest) -> Result<HttpResponse> {
+    fn has_exited(&self) -> bool {
+        self.state.exited.load(Ordering::SeqCst)
+    }
+
+    fn is_claimed(&self) -> bool {
+        self.state.claimed.load(Ordering::SeqCst)
+    }
+
+    fn submit_request(&self, request: HttpRequest, mut body: Option<&mut dyn Read>, timeout: Option<std::time::Duration>) -> Result<HttpResponse> {
+        if self.has_exited() {
+            return Err(PhpError::WorkerExited);
+        }
+
+        self.send_request(request, body.is_some())?;
+
+        // Wait for the response, serving request body chunks the worker asks for
+        let started = std::time::Instant::now();
+        {
+            let mut req = self.state.request.lock().unwrap_or_else(|e| e.into_inner());
+            loop {
+                if self.state.is_finished() {
+                    break;
+                }
+
+                let wanted = self.state.body_wanted.load(Ordering::SeqCst);
+                if wanted > 0 {
+                    let mut chunk = vec![0; wanted];
+                    let n = body.as_mut().map_or(0, |reader| read_chunk(*reader, &mut chunk));
+                    chunk.truncate(n);
+                    *self.state.body_chunk.lock().unwrap_or_else(|e| e.into_inner()) = Some(chunk);
+                    self.state.body_wanted.store(0, Ordering::SeqCst);
+                    self.state.body_ready.notify_all();
+                    continue;
+                }
+
+                req = match timeout {
+                    Some(timeout) => {
+                        let Some(remaining) = timeout.checked_sub(started.elapsed()) else {
+                            // The stuck worker keeps the request data alive until it is abandoned
+                            return Err(PhpError::Timeout(timeout));
+                        };
+                        self.state.response_ready
+                            .wait_timeout(req, remaining)
+                            .unwrap_or_else(|e| e.into_inner())
+                            .0
+                    }
+                    None => self.state.response_ready.wait(req).unwrap_or_else(|e| e.into_inner()),
+                };
+            }
+        }
+
+        if !self.state.has_response.load(Ordering::SeqCst) {
+            // PHP bailed out (fatal error, exceeded max_execution_time or memory_limit)
+            return match timeout {
+                Some(timeout) if started.elapsed() >= timeout => Err(PhpError::Timeout(timeout)),
+                _ => Err(PhpError::WorkerExited),
+            };
+        }
+
+        self.take_response()
+    }
+
+    /// Hand a request to the worker script, `streamed` bodies are read through [`read_worker_body`]
+    fn send_request(&self, request: HttpRequest, streamed: bool) -> Result<()> {
         // Convert the request to CStrings that will be stored alongside the context
         let method = CString::new(request.method)?;
         let uri = CString::new(request.uri)?;
         let query_string = CString::new(request.query_string)?;
         let document_root = CString::new(request.document_root)?;
         let script_filename = CString::new(request.script_filename)?;
+        let script_name = CString::new(request.script_name)?;
+        let path_info = request.path_info.map(CString::new).transpose()?;
         let server_name = CString::new(request.server_name)?;
         let remote_addr = CString::new(request.remote_addr)?;
 
-        // Format headers
-        let headers_str: String = request
-            .headers
-            .iter()
-            .map(|(k, v)| format!("{}: {}\n", k, v))
-            .collect();
-        let headers = CString::new(headers_str)?;
+        let protocol = CString::new(request.protocol)?;
+        let headers = CString::new(format_headers(&request.headers))?;
 
         // Get content type
         let content_type_str = request
@@ -809,10 +1674,16 @@ impl WorkerThread {
             .unwrap_or_default();
         let content_type = CString::new(content_type_str)?;
 
-        let body = request.body;
-        let body_len = body.len();
+        let content_length = if streamed {
+            streamed_content_length(&request.headers)
+        } else {
+            request.body.len()
+        };
+        let body_bytes = request.body;
+        let body_len = body_bytes.len();
         let server_port = request.server_port;
         let remote_port = request.remote_port;
+        let https = request.https;
 
         // Store strings that need to live as long as the context
         // We Box it so it has a stable address
@@ -822,11 +1693,14 @@ impl WorkerThread {
             query_string,
             document_root,
             script_filename,
+            script_name,
+            path_info,
             server_name,
             remote_addr,
+            protocol,
             headers,
             content_type,
-            body,
+            body: body_bytes,
         });
 
         // Create the request context pointing to the boxed strings
@@ -835,17 +1709,22 @@ impl WorkerThread {
             uri: strings.uri.as_ptr(),
             query_string: strings.query_string.as_ptr(),
             content_type: strings.content_type.as_ptr(),
-            content_length: body_len,
+            content_length,
             request_body: strings.body.as_ptr() as *const c_char,
             request_body_len: body_len,
             request_body_read: 0,
             headers: strings.headers.as_ptr(),
             document_root: strings.document_root.as_ptr(),
             script_filename: strings.script_filename.as_ptr(),
+            script_name: strings.script_name.as_ptr(),
+            path_info: strings.path_info.as_ref().map_or(std::ptr::null(), |p| p.as_ptr()),
+            is_router: 0,
             server_name: strings.server_name.as_ptr(),
             server_port: server_port as c_int,
             remote_addr: strings.remote_addr.as_ptr(),
             remote_port: remote_port as c_int,
+            server_protocol: strings.protocol.as_ptr(),
+            https: https as c_int,
             response_body: std::ptr::null_mut(),
             response_body_len: 0,
             response_body_cap: 0,
@@ -853,6 +1732,10 @@ impl WorkerThread {
             response_headers_len: 0,
             response_headers_cap: 0,
             response_status: 200,
+            peak_memory: 0,
+            declined: 0,
+            body_stream: std::ptr::null_mut(),
+            read_body: streamed.then_some(read_worker_body as ReadBodyFn),
         });
 
         // Store the request and strings together, then signal the worker
@@ -863,14 +1746,11 @@ impl WorkerThread {
             self.state.request_available.notify_one();
         }
 
-        // Wait for the response
-        {
-            let req = self.state.request.lock().unwrap_or_else(|e| e.into_inner());
-            let _guard = self.state.response_ready.wait_while(req, |_| {
-                !self.state.has_response.load(Ordering::SeqCst)
-            }).unwrap_or_else(|e| e.into_inner());
-        }
+        Ok(())
+    }
 
+    /// Read the response of a finished request and let the worker script continue
+    fn take_response(&self) -> Result<HttpResponse> {
         // Extract response
         let mut req = self.state.request.lock().unwrap_or_else(|e| e.into_inner());
         if let Some((ref ctx, _)) = *req {
@@ -905,6 +1785,7 @@ impl WorkerThread {
             }
 
             let status = ctx.response_status as u16;
+            let peak_memory = ctx.peak_memory;
 
             // Free response buffers
             unsafe { pox_free_response(ctx.as_ref() as *const PhpRequestContext as *mut c_void) };
@@ -920,6 +1801,8 @@ impl WorkerThread {
                 status,
                 headers: response_headers,
                 body,
+                peak_memory,
+                declined: false,
             })
         } else {
             // Still signal even on error so C doesn't block forever
@@ -933,6 +1816,16 @@ impl WorkerThread {
         self.state.shutdown.store(true, Ordering::SeqCst);
         self.state.request_available.notify_all();
         self.state.response_consumed.notify_all();
+        self.state.body_ready.notify_all();
+    }
+
+    /// Give up on a stuck worker without waiting for its thread
+    ///
+    /// PHP threads cannot be killed safely, so the thread is detached and exits
+    /// on its own once the script returns to `pox_handle_request()`.
+    fn abandon(mut self) {
+        self.shutdown();
+        self.handle.take();
     }
 
     fn shutdown_and_join(mut self) {
@@ -952,13 +1845,111 @@ impl Drop for WorkerThread {
     }
 }
 
+/// Delay before respawning a worker that crashed `failures` times in a row
+///
+/// The first crash is recovered immediately, repeated crashes (e.g. a syntax error
+/// in the worker script) back off exponentially up to 30 seconds.
+fn restart_backoff(failures: u32) -> std::time::Duration {
+    const BASE: std::time::Duration = std::time::Duration::from_millis(100);
+    const MAX: std::time::Duration = std::time::Duration::from_secs(30);
+
+    if failures <= 1 {
+        return std::time::Duration::ZERO;
+    }
+    BASE.checked_mul(1 << (failures - 2).min(16)).map_or(MAX, |d| d.min(MAX))
+}
+
+/// A worker thread together with its liveness and recovery state
+struct WorkerSlot {
+    worker: Mutex<WorkerThread>,
+    /// Requests handled by the current worker thread
+    requests: AtomicU64,
+    /// Crashes since the last successfully handled request
+    failures: AtomicU32,
+    /// When a crashed worker may be respawned, `None` while it is alive
+    retry_at: Mutex<Option<Instant>>,
+    /// When the current worker thread was started
+    started_at: Mutex<Instant>,
+}
+
+impl WorkerSlot {
+    fn new(worker: WorkerThread) -> Self {
+        Self {
+            worker: Mutex::new(worker),
+            requests: AtomicU64::new(0),
+            failures: AtomicU32::new(0),
+            retry_at: Mutex::new(None),
+            started_at: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+/// Pool wide counters
+#[derive(Default)]
+struct PoolCounters {
+    requests: AtomicU64,
+    timeouts: AtomicU64,
+    crashes: AtomicU64,
+    restarts: AtomicU64,
+    recycled: AtomicU64,
+}
+
+/// Snapshot of a single worker thread
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerInfo {
+    /// Whether the worker script is running
+    pub alive: bool,
+    /// Whether the worker is handling a request right now
+    pub busy: bool,
+    /// Requests handled since the worker was (re)started
+    pub requests: u64,
+    /// Time since the worker was (re)started
+    pub uptime: std::time::Duration,
+}
+
+/// Snapshot of the worker pool counters
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkerStats {
+    pub workers: Vec<WorkerInfo>,
+    /// Requests handled by the pool
+    pub requests: u64,
+    /// Requests that exceeded the request timeout
+    pub timeouts: u64,
+    /// Worker scripts that exited unexpectedly (fatal errors, crashes)
+    pub crashes: u64,
+    /// Workers respawned after a crash or timeout
+    pub restarts: u64,
+    /// Workers replaced after reaching the max requests limit
+    pub recycled: u64,
+}
+
+impl WorkerStats {
+    /// Number of workers currently handling a request
+    pub fn busy(&self) -> usize {
+        self.workers.iter().filter(|w| w.busy).count()
+    }
+
+    /// Number of workers with a running worker script
+    pub fn alive(&self) -> usize {
+        self.workers.iter().filter(|w| w.alive).count()
+    }
+}
+
 /// PHP Worker pool for handling requests with long-lived PHP processes
 pub struct PhpWorker {
-    workers: Vec<WorkerThread>,
+    workers: Vec<WorkerSlot>,
     next_worker: AtomicUsize,
     script_filename: String,
     document_root: String,
     num_workers: usize,
+    request_timeout: Option<std::time::Duration>,
+    max_requests: Option<u64>,
+    counters: PoolCounters,
+    /// Signalled when a worker is released, for requests waiting for a free worker
+    released: Condvar,
+    released_lock: Mutex<()>,
+    /// Async requests waiting for a free worker
+    waiters: Mutex<Vec<Waker>>,
 }
 
 impl PhpWorker {
@@ -984,7 +1975,7 @@ impl PhpWorker {
                 script_filename.to_string(),
                 document_root.to_string(),
             );
-            workers.push(worker);
+            workers.push(WorkerSlot::new(worker));
         }
 
         // Give workers time to start up
@@ -996,16 +1987,39 @@ impl PhpWorker {
             script_filename: script_filename.to_string(),
             document_root: document_root.to_string(),
             num_workers,
+            request_timeout: None,
+            max_requests: None,
+            counters: PoolCounters::default(),
+            released: Condvar::new(),
+            released_lock: Mutex::new(()),
+            waiters: Mutex::new(Vec::new()),
         })
     }
 
+    /// Give up on requests that take longer than `timeout`
+    ///
+    /// The client gets [`PhpError::Timeout`] and the stuck worker is replaced by a
+    /// fresh one, so a hanging script cannot block the pool.
+    pub fn with_request_timeout(mut self, timeout: Option<std::time::Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Replace a worker after it handled `max_requests` requests
+    ///
+    /// Recycling workers bounds the impact of memory leaks in long-running scripts.
+    pub fn with_max_requests(mut self, max_requests: Option<u64>) -> Self {
+        self.max_requests = max_requests.filter(|max| *max > 0);
+        self
+    }
+
     /// Restart all workers (used for hot reloading on file changes)
     pub fn restart(&mut self) {
         eprintln!("Restarting {} workers...", self.num_workers);
 
         // Shutdown existing workers
-        for worker in self.workers.drain(..) {
-            worker.shutdown_and_join();
+        for slot in self.workers.drain(..) {
+            slot.worker.into_inner().unwrap_or_else(|e| e.into_inner()).shutdown_and_join();
         }
 
         // Create new workers
@@ -1014,7 +2028,7 @@ impl PhpWorker {
                 self.script_filename.clone(),
                 self.document_root.clone(),
             );
-            self.workers.push(worker);
+            self.workers.push(WorkerSlot::new(worker));
         }
 
         // Give workers time to start up
@@ -1025,19 +2039,281 @@ impl PhpWorker {
 
     /// Handle an HTTP request using an available worker
     pub fn handle_request(&self, request: HttpRequest) -> Result<HttpResponse> {
-        // Simple round-robin selection
+        self.dispatch(request, None)
+    }
+
+    /// Handle an HTTP request whose body is read from `body` as the worker consumes it
+    ///
+    /// `request.body` is ignored, see [`PhpWeb::execute_with_body`].
+    pub fn handle_request_with_body(&self, request: HttpRequest, body: &mut dyn Read) -> Result<HttpResponse> {
+        self.dispatch(request, Some(body))
+    }
+
+    /// Handle an HTTP request without blocking the calling thread
+    ///
+    /// The returned future resolves once a worker has handled the request, so an
+    /// async server can keep many connections waiting on a fixed pool without a
+    /// hop to a blocking thread pool. It works with any async runtime.
+    ///
+    /// The request timeout of the pool is not applied, use a timeout of the
+    /// runtime instead: dropping the future before it resolves replaces the
+    /// worker handling the request, like a timed out request.
+    pub fn handle_request_async(&self, request: HttpRequest) -> WorkerRequest<'_> {
+        WorkerRequest {
+            pool: self,
+            request: Some(request),
+            in_flight: None,
+        }
+    }
+
+    /// Handle an HTTP request on the worker assigned to `key`
+    ///
+    /// Requests with the same key (a session ID, user ID or client IP) always
+    /// reach the same worker thread while it is alive, so state the worker script
+    /// keeps in memory between requests stays warm. A request waits for its
+    /// worker when it is busy and falls back to any worker when it is down.
+    pub fn handle_request_with_key(&self, key: &str, request: HttpRequest) -> Result<HttpResponse> {
+        self.dispatch_to(Some(self.worker_for_key(key)), request, None)
+    }
+
+    /// [`PhpWorker::handle_request_with_key`] reading the request body from `body`
+    pub fn handle_request_with_key_and_body(&self, key: &str, request: HttpRequest, body: &mut dyn Read) -> Result<HttpResponse> {
+        self.dispatch_to(Some(self.worker_for_key(key)), request, Some(body))
+    }
+
+    /// Index of the worker that handles the requests of an affinity key
+    fn worker_for_key(&self, key: &str) -> usize {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        key.hash(&mut hasher);
+        (hasher.finish() % self.workers.len() as u64) as usize
+    }
+
+    fn dispatch(&self, request: HttpRequest, body: Option<&mut dyn Read>) -> Result<HttpResponse> {
+        self.dispatch_to(None, request, body)
+    }
+
+    fn dispatch_to(&self, preferred: Option<usize>, request: HttpRequest, body: Option<&mut dyn Read>) -> Result<HttpResponse> {
+        self.check_health();
+
+        let mut released = self.released_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let (idx, worker) = loop {
+            match self.try_claim(preferred)? {
+                Some(claimed) => break claimed,
+                None => released = self.released.wait(released).unwrap_or_else(|e| e.into_inner()),
+            }
+        };
+        drop(released);
+
+        let result = self.submit(&self.workers[idx], worker, request, body);
+        self.notify_released();
+        result
+    }
+
+    /// Lock an idle worker, trying the workers from the round-robin position
+    ///
+    /// `None` if all live workers are busy. If none is alive, all are waiting for
+    /// their restart backoff to pass. A `preferred` worker is waited for while it
+    /// is busy, the other workers are only tried while it is down.
+    fn try_claim(&self, preferred: Option<usize>) -> Result<Option<(usize, MutexGuard<'_, WorkerThread>)>> {
+        if let Some(idx) = preferred {
+            match self.workers[idx].worker.try_lock() {
+                Ok(worker) if worker.is_available() => return Ok(Some((idx, worker))),
+                Ok(worker) if worker.has_exited() => {}
+                _ => return Ok(None),
+            }
+        }
+
         let start = self.next_worker.fetch_add(1, Ordering::SeqCst) % self.workers.len();
 
-        // Try to find an available worker, starting from the round-robin position
+        let mut busy = false;
         for i in 0..self.workers.len() {
             let idx = (start + i) % self.workers.len();
-            if self.workers[idx].is_available() {
-                return self.workers[idx].submit_request(request);
+            match self.workers[idx].worker.try_lock() {
+                Ok(worker) if worker.is_available() => return Ok(Some((idx, worker))),
+                Ok(worker) => busy |= worker.is_claimed(),
+                Err(_) => busy = true,
+            }
+        }
+
+        if busy {
+            Ok(None)
+        } else {
+            Err(PhpError::WorkerExited)
+        }
+    }
+
+    /// [`PhpWorker::try_claim`] for async requests, `cx` is woken when a worker is released
+    fn poll_claim(&self, cx: &mut Context<'_>) -> Poll<Result<(usize, MutexGuard<'_, WorkerThread>)>> {
+        self.check_health();
+        match self.try_claim(None) {
+            Ok(Some(claimed)) => return Poll::Ready(Ok(claimed)),
+            Ok(None) => {}
+            Err(e) => return Poll::Ready(Err(e)),
+        }
+
+        {
+            let mut waiters = self.waiters.lock().unwrap_or_else(|e| e.into_inner());
+            if !waiters.iter().any(|w| w.will_wake(cx.waker())) {
+                waiters.push(cx.waker().clone());
+            }
+        }
+
+        // A worker may have been released before the waker was registered
+        match self.try_claim(None) {
+            Ok(Some(claimed)) => Poll::Ready(Ok(claimed)),
+            Ok(None) => Poll::Pending,
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Wake the requests waiting for a free worker
+    fn notify_released(&self) {
+        {
+            let _released = self.released_lock.lock().unwrap_or_else(|e| e.into_inner());
+            self.released.notify_all();
+        }
+        let waiters = std::mem::take(&mut *self.waiters.lock().unwrap_or_else(|e| e.into_inner()));
+        for waker in waiters {
+            waker.wake();
+        }
+    }
+
+    /// Respawn crashed workers whose restart backoff has passed
+    pub fn check_health(&self) {
+        for slot in &self.workers {
+            if let Ok(mut worker) = slot.worker.try_lock() {
+                // An exited worker claimed by an async request is recovered by that request
+                if worker.has_exited() && !worker.is_claimed() {
+                    self.recover(slot, &mut worker);
+                }
+            }
+        }
+    }
+
+    /// Current pool counters and the state of every worker
+    pub fn stats(&self) -> WorkerStats {
+        let workers = self
+            .workers
+            .iter()
+            .map(|slot| {
+                let (alive, busy) = match slot.worker.try_lock() {
+                    Ok(worker) => (!worker.has_exited(), worker.is_claimed()),
+                    Err(_) => (true, true),
+                };
+                let started_at = *slot.started_at.lock().unwrap_or_else(|e| e.into_inner());
+                WorkerInfo {
+                    alive,
+                    busy,
+                    requests: slot.requests.load(Ordering::Relaxed),
+                    uptime: started_at.elapsed(),
+                }
+            })
+            .collect();
+
+        WorkerStats {
+            workers,
+            requests: self.counters.requests.load(Ordering::Relaxed),
+            timeouts: self.counters.timeouts.load(Ordering::Relaxed),
+            crashes: self.counters.crashes.load(Ordering::Relaxed),
+            restarts: self.counters.restarts.load(Ordering::Relaxed),
+            recycled: self.counters.recycled.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Run a request on a worker, replacing the worker if it hung, died or is due for recycling
+    fn submit(&self, slot: &WorkerSlot, mut worker: MutexGuard<'_, WorkerThread>, request: HttpRequest, body: Option<&mut dyn Read>) -> Result<HttpResponse> {
+        let result = worker.submit_request(request, body, self.request_timeout);
+        self.complete(slot, &mut worker, &result);
+        result
+    }
+
+    /// Read the response of an async request once its worker finished and release the worker
+    fn finish_async(&self, idx: usize) -> Result<HttpResponse> {
+        let slot = &self.workers[idx];
+        let mut worker = slot.worker.lock().unwrap_or_else(|e| e.into_inner());
+
+        let result = if worker.state.has_response.load(Ordering::SeqCst) {
+            worker.take_response()
+        } else {
+            Err(PhpError::WorkerExited)
+        };
+        worker.state.claimed.store(false, Ordering::SeqCst);
+        self.complete(slot, &mut worker, &result);
+
+        drop(worker);
+        self.notify_released();
+        result
+    }
+
+    /// Count a handled request, replacing the worker if it hung, died or is due for recycling
+    fn complete(&self, slot: &WorkerSlot, worker: &mut WorkerThread, result: &Result<HttpResponse>) {
+        self.counters.requests.fetch_add(1, Ordering::Relaxed);
+
+        match result {
+            Err(PhpError::Timeout(_)) => {
+                // A stuck worker is replaced right away, it is not crash looping
+                eprintln!("Worker did not respond in time, replacing it");
+                self.counters.timeouts.fetch_add(1, Ordering::Relaxed);
+                self.counters.restarts.fetch_add(1, Ordering::Relaxed);
+                self.respawn(slot, worker);
+            }
+            _ if worker.has_exited() => self.recover(slot, worker),
+            Ok(_) => {
+                slot.failures.store(0, Ordering::Relaxed);
+                let handled = slot.requests.fetch_add(1, Ordering::Relaxed) + 1;
+                if self.max_requests.is_some_and(|max| handled >= max) {
+                    self.counters.recycled.fetch_add(1, Ordering::Relaxed);
+                    self.respawn(slot, worker);
+                }
             }
+            Err(_) => {}
         }
+    }
 
-        // All workers busy, use the round-robin one anyway (it will block)
-        self.workers[start % self.workers.len()].submit_request(request)
+    /// Handle a worker whose script exited: respawn it now or once its backoff passed
+    fn recover(&self, slot: &WorkerSlot, worker: &mut WorkerThread) {
+        let mut retry_at = slot.retry_at.lock().unwrap_or_else(|e| e.into_inner());
+
+        match *retry_at {
+            // Crash already recorded, waiting for the backoff
+            Some(at) if Instant::now() < at => {}
+            Some(_) => {
+                *retry_at = None;
+                self.counters.restarts.fetch_add(1, Ordering::Relaxed);
+                self.respawn(slot, worker);
+            }
+            None => {
+                let failures = slot.failures.fetch_add(1, Ordering::Relaxed) + 1;
+                self.counters.crashes.fetch_add(1, Ordering::Relaxed);
+
+                let backoff = restart_backoff(failures);
+                if backoff.is_zero() {
+                    eprintln!("Worker exited unexpectedly, restarting it");
+                    self.counters.restarts.fetch_add(1, Ordering::Relaxed);
+                    self.respawn(slot, worker);
+                } else {
+                    eprintln!("Worker exited unexpectedly ({} times in a row), restarting in {:?}", failures, backoff);
+                    *retry_at = Some(Instant::now() + backoff);
+                }
+            }
+        }
+    }
+
+    /// Replace the worker thread of a slot with a fresh one
+    fn respawn(&self, slot: &WorkerSlot, worker: &mut WorkerThread) {
+        let fresh = WorkerThread::new(self.script_filename.clone(), self.document_root.clone());
+        let old = std::mem::replace(worker, fresh);
+        old.abandon();
+
+        slot.requests.store(0, Ordering::Relaxed);
+        *slot.started_at.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    /// `opcache_get_status(false)` as JSON, or `None` if opcache is not loaded or disabled
+    ///
+    /// Must be called from the thread that created the pool.
+    pub fn opcache_status(&self) -> Option<String> {
+        opcache_status()
     }
 }
 
@@ -1047,6 +2323,79 @@ impl Drop for PhpWorker {
     }
 }
 
+/// Future of [`PhpWorker::handle_request_async`]
+pub struct WorkerRequest<'a> {
+    pool: &'a PhpWorker,
+    /// The request until a worker was claimed for it
+    request: Option<HttpRequest>,
+    /// Slot index and state of the worker running the request
+    in_flight: Option<(usize, Arc<WorkerThreadState>)>,
+}
+
+impl Future for WorkerRequest<'_> {
+    type Output = Result<HttpResponse>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        if this.in_flight.is_none() {
+            let (idx, worker) = match this.pool.poll_claim(cx) {
+                Poll::Ready(Ok(claimed)) => claimed,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            // The worker lock cannot be held across polls, the claim keeps other requests away
+            let request = this.request.take().expect("WorkerRequest polled after completion");
+            worker.state.claimed.store(true, Ordering::SeqCst);
+            if let Err(e) = worker.send_request(request, false) {
+                worker.state.claimed.store(false, Ordering::SeqCst);
+                drop(worker);
+                this.pool.notify_released();
+                return Poll::Ready(Err(e));
+            }
+            this.in_flight = Some((idx, worker.state.clone()));
+        }
+
+        let Some((idx, state)) = &this.in_flight else {
+            unreachable!("request is in flight");
+        };
+        if !state.is_finished() {
+            *state.response_waker.lock().unwrap_or_else(|e| e.into_inner()) = Some(cx.waker().clone());
+            // The worker may have finished before the waker was registered
+            if !state.is_finished() {
+                return Poll::Pending;
+            }
+        }
+
+        let idx = *idx;
+        this.in_flight = None;
+        Poll::Ready(this.pool.finish_async(idx))
+    }
+}
+
+impl Drop for WorkerRequest<'_> {
+    fn drop(&mut self) {
+        let Some((idx, state)) = self.in_flight.take() else {
+            return;
+        };
+
+        if state.is_finished() {
+            let _ = self.pool.finish_async(idx);
+            return;
+        }
+
+        // Dropped while the worker runs the request, e.g. by a timeout. Nobody will
+        // read the response, so the worker is replaced like a timed out one.
+        let slot = &self.pool.workers[idx];
+        let mut worker = slot.worker.lock().unwrap_or_else(|e| e.into_inner());
+        self.pool.counters.restarts.fetch_add(1, Ordering::Relaxed);
+        self.pool.respawn(slot, &mut worker);
+        drop(worker);
+        self.pool.notify_released();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1057,4 +2406,55 @@ mod tests {
         assert!(version.version_id > 0);
         assert!(version.major >= 8);
     }
+
+    #[test]
+    fn test_restart_backoff() {
+        use std::time::Duration;
+
+        assert_eq!(restart_backoff(0), Duration::ZERO);
+        assert_eq!(restart_backoff(1), Duration::ZERO);
+        assert_eq!(restart_backoff(2), Duration::from_millis(100));
+        assert_eq!(restart_backoff(4), Duration::from_millis(400));
+        assert_eq!(restart_backoff(20), Duration::from_secs(30));
+        assert_eq!(restart_backoff(u32::MAX), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_request_builder() {
+        let request = HttpRequest::builder()
+            .method("POST")
+            .uri("/api/users?page=2")
+            .header("Host", "example.test:8080")
+            .header("Cookie", "a=1")
+            .body("name=pox")
+            .document_root("/var/www/")
+            .script_filename("/var/www/index.php")
+            .server("example.test", 8080)
+            .remote_addr("[::1]:4000".parse().unwrap())
+            .build();
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.uri, "/api/users?page=2");
+        assert_eq!(request.query_string, "page=2");
+        assert_eq!(request.headers.len(), 2);
+        assert_eq!(request.body, b"name=pox");
+        assert_eq!(request.script_name, "/index.php");
+        assert_eq!(request.remote_addr, "::1");
+        assert_eq!(request.remote_port, 4000);
+        assert_eq!(request.protocol, "HTTP/1.1");
+    }
+
+    #[test]
+    fn test_format_headers_folds_repeated_headers() {
+        let headers = vec![
+            ("Accept".to_string(), "text/html".to_string()),
+            ("Cookie".to_string(), "a=1".to_string()),
+            ("accept".to_string(), "application/json".to_string()),
+            ("cookie".to_string(), "b=2".to_string()),
+        ];
+        assert_eq!(
+            format_headers(&headers),
+            "Accept: text/html, application/json\nCookie: a=1; b=2\n"
+        );
+    }
 }
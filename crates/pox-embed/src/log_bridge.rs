@@ -0,0 +1,63 @@
+//! Forwarding of PHP's log to the [`log`] crate, enabled with the `log` feature.
+//!
+//! Messages are logged with the `php` target, so they can be filtered like
+//! the logs of any other crate:
+//!
+//! ```no_run
+//! use pox_embed::Php;
+//!
+//! // Before PHP starts, to receive startup errors as well
+//! Php::bridge_log();
+//! Php::execute_code("error_log('hello');", &[] as &[&str]).unwrap();
+//! ```
+
+use crate::{Php, PhpLogEvent};
+
+/// Log target of PHP's messages
+const TARGET: &str = "php";
+
+impl PhpLogEvent {
+    /// The `log` level of the syslog priority: errors, warnings and notices (which
+    /// include `error_log()` calls) are warnings, deprecations are info
+    pub fn log_level(&self) -> log::Level {
+        match self.level {
+            ..=Self::LOG_ERR => log::Level::Error,
+            Self::LOG_WARNING | Self::LOG_NOTICE => log::Level::Warn,
+            Self::LOG_INFO => log::Level::Info,
+            _ => log::Level::Debug,
+        }
+    }
+}
+
+impl Php {
+    /// Send everything PHP logs to the `log` facade with the `php` target
+    ///
+    /// See [`Php::set_log_handler`] for what is logged, this replaces its handler.
+    pub fn bridge_log() {
+        Php::set_log_handler(|event| {
+            log::log!(target: TARGET, event.log_level(), "{}", event.message);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(level: i32) -> PhpLogEvent {
+        PhpLogEvent {
+            level,
+            message: "PHP Deprecated:  Creation of dynamic property in /app/index.php on line 3".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_log_level() {
+        assert_eq!(event(PhpLogEvent::LOG_CRIT).log_level(), log::Level::Error);
+        assert_eq!(event(PhpLogEvent::LOG_ERR).log_level(), log::Level::Error);
+        assert_eq!(event(PhpLogEvent::LOG_WARNING).log_level(), log::Level::Warn);
+        assert_eq!(event(PhpLogEvent::LOG_NOTICE).log_level(), log::Level::Warn);
+        assert_eq!(event(PhpLogEvent::LOG_INFO).log_level(), log::Level::Info);
+        assert_eq!(event(PhpLogEvent::LOG_DEBUG).log_level(), log::Level::Debug);
+    }
+}
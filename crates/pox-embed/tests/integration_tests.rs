@@ -6,7 +6,8 @@
 //! cargo test -- --test-threads=1
 //! ```
 
-use pox_embed::{Php, PhpVersion};
+use pox_embed::{Php, PhpErrorEvent, PhpVersion, ScriptContext};
+use std::sync::{Arc, Mutex};
 use std::io::Write;
 use tempfile::NamedTempFile;
 
@@ -304,6 +305,59 @@ fn test_server_variables() {
     assert_eq!(result.unwrap(), 0, "Server variables should be set");
 }
 
+#[test]
+fn test_execute_with_superglobals() {
+    let file = create_php_file(
+        r#"<?php
+        if (getenv('POX_TEST_VAR') !== 'env') exit(1);
+        if (($_ENV['POX_TEST_VAR'] ?? null) !== 'env') exit(2);
+        if (($_SERVER['POX_SERVER_VAR'] ?? null) !== 'server') exit(3);
+        if (realpath(getcwd()) !== realpath($argv[1])) exit(4);
+        exit(0);
+        "#,
+    );
+    let path = file.path().to_str().unwrap();
+    let work_dir = tempfile::tempdir().unwrap();
+    let cwd = std::env::current_dir().unwrap();
+
+    let context = ScriptContext {
+        env: vec![("POX_TEST_VAR".to_string(), "env".to_string())],
+        server: vec![("POX_SERVER_VAR".to_string(), "server".to_string())],
+        working_dir: Some(work_dir.path().to_path_buf()),
+    };
+    let result = Php::execute_with_superglobals(path, &[work_dir.path().to_str().unwrap()], &context);
+    assert_eq!(result.unwrap(), 0, "Injected variables and working dir should be visible");
+
+    // The host process is left untouched
+    assert!(std::env::var("POX_TEST_VAR").is_err());
+    assert_eq!(std::env::current_dir().unwrap(), cwd);
+}
+
+#[test]
+fn test_error_handler() {
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let collected = errors.clone();
+    Php::set_error_handler(move |event| collected.lock().unwrap().push(event.clone()));
+
+    let result = Php::execute_code(
+        r#"
+        trigger_error('custom notice', E_USER_NOTICE);
+        @trigger_error('silenced', E_USER_WARNING);
+        exit(error_get_last()['message'] === 'silenced' ? 0 : 1);
+        "#,
+        &[] as &[&str],
+    );
+    Php::clear_error_handler();
+
+    assert_eq!(result.unwrap(), 0, "error_get_last() should still be set");
+    let errors = errors.lock().unwrap();
+    assert_eq!(errors.len(), 1, "Silenced errors should not be reported");
+    assert_eq!(errors[0].level, PhpErrorEvent::E_USER_NOTICE);
+    assert_eq!(errors[0].message, "custom notice");
+    assert_eq!(errors[0].severity(), "Notice");
+    assert!(!errors[0].is_fatal());
+}
+
 #[test]
 fn test_stdin_stdout_stderr_constants() {
     let result = Php::execute_code(
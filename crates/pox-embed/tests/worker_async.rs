@@ -0,0 +1,72 @@
+//! Round trips through [`PhpWorker::handle_request_async`].
+//!
+//! The worker runtime is process wide, so all requests run in one test.
+
+use pox_embed::{HttpRequest, PhpWorker};
+use tempfile::TempDir;
+
+const WORKER: &str = r#"<?php
+$handled = 0;
+while (pox_handle_request(function () use (&$handled) {
+    $handled++;
+    header('X-Handled: ' . $handled);
+    echo $_SERVER['REQUEST_METHOD'], ' ', $_SERVER['REQUEST_URI'], ' ', file_get_contents('php://input');
+})) {
+}
+"#;
+
+fn request(document_root: &TempDir, method: &str, uri: &str, body: &str) -> HttpRequest {
+    let root = document_root.path().to_string_lossy().to_string();
+    HttpRequest::builder()
+        .method(method)
+        .uri(uri)
+        .header("Host", "localhost:8000")
+        .body(body)
+        .script_filename(format!("{}/worker.php", root))
+        .document_root(root)
+        .build()
+}
+
+#[tokio::test]
+async fn test_handle_request_async_round_trip() {
+    let document_root = TempDir::new().unwrap();
+    let script = document_root.path().join("worker.php");
+    std::fs::write(&script, WORKER).unwrap();
+
+    let worker = PhpWorker::new(
+        &script.to_string_lossy(),
+        &document_root.path().to_string_lossy(),
+        1,
+    )
+    .unwrap();
+
+    let response = worker
+        .handle_request_async(request(&document_root, "POST", "/users", "name=pox"))
+        .await
+        .unwrap();
+    assert_eq!(response.status, 200);
+    assert_eq!(String::from_utf8_lossy(&response.body), "POST /users name=pox");
+
+    // More requests than workers wait for the single worker, which keeps its state
+    let (first, second) = tokio::join!(
+        worker.handle_request_async(request(&document_root, "GET", "/a", "")),
+        worker.handle_request_async(request(&document_root, "GET", "/b", "")),
+    );
+    let mut bodies = vec![
+        String::from_utf8_lossy(&first.unwrap().body).into_owned(),
+        String::from_utf8_lossy(&second.unwrap().body).into_owned(),
+    ];
+    bodies.sort();
+    assert_eq!(bodies, vec!["GET /a ", "GET /b "]);
+
+    let response = worker
+        .handle_request_async(request(&document_root, "GET", "/", ""))
+        .await
+        .unwrap();
+    let handled = response
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("X-Handled"))
+        .map(|(_, value)| value.as_str());
+    assert_eq!(handled, Some("4"));
+}
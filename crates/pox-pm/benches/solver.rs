@@ -0,0 +1,27 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pox_pm::bench::solver_fixture;
+use pox_pm::solver::{Policy, RuleGenerator, Solver};
+
+fn bench_solve(c: &mut Criterion) {
+    // The same fixture `pox pm bench` solves
+    let (pool, request) = solver_fixture();
+    let policy = Policy::new();
+
+    c.bench_function("solve_symfony_sized_graph", |b| {
+        b.iter(|| {
+            let solver = Solver::new(&pool, &policy).with_optimization(false);
+            black_box(solver.solve(black_box(&request)).is_ok())
+        })
+    });
+}
+
+fn bench_generate_rules(c: &mut Criterion) {
+    let (pool, request) = solver_fixture();
+
+    c.bench_function("generate_rules_symfony_sized_graph", |b| {
+        b.iter(|| black_box(RuleGenerator::new(&pool).generate(black_box(&request)).len()))
+    });
+}
+
+criterion_group!(benches, bench_solve, bench_generate_rules);
+criterion_main!(benches);
@@ -12,46 +12,113 @@ use crate::Result;
 
 use super::classmap::ClassMapGenerator;
 
-/// Sort packages by dependency weight (topological sort).
-/// Packages that are dependencies come first, alphabetical by name as tie-breaker.
+/// Sort packages like Composer's `PackageSorter`.
+///
+/// Every package starts with weight 0 and loses `1 - importance(user)` for each
+/// package requiring it, so deep dependencies sink to the front. Ties are broken
+/// with a case-insensitive natural compare of the names.
 fn sort_packages_by_dependency(packages: &[PackageAutoload]) -> Vec<PackageAutoload> {
-    if packages.is_empty() {
-        return Vec::new();
+    let mut usage: HashMap<&str, Vec<&str>> = HashMap::new();
+    for pkg in packages {
+        for dep in &pkg.requires {
+            usage.entry(dep.as_str()).or_default().push(pkg.name.as_str());
+        }
     }
 
-    // Build a map of package names for quick lookup
-    let package_names: HashSet<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+    fn importance<'a>(
+        name: &'a str,
+        usage: &HashMap<&'a str, Vec<&'a str>>,
+        computing: &mut HashSet<&'a str>,
+        computed: &mut HashMap<&'a str, i64>,
+    ) -> i64 {
+        if let Some(weight) = computed.get(name) {
+            return *weight;
+        }
+        // Circular reference
+        if !computing.insert(name) {
+            return 0;
+        }
 
-    // Calculate weight for each package (number of packages that depend on it)
-    let mut weights: HashMap<&str, usize> = HashMap::new();
-    for pkg in packages {
-        weights.entry(&pkg.name).or_insert(0);
+        let mut weight = 0;
+        for user in usage.get(name).into_iter().flatten() {
+            weight -= 1 - importance(user, usage, computing, computed);
+        }
+
+        computing.remove(name);
+        computed.insert(name, weight);
+        weight
     }
 
-    // For each package, increase weight of its dependencies
-    for pkg in packages {
-        for dep in &pkg.requires {
-            // Only count dependencies that are in our package list
-            if package_names.contains(dep.as_str()) {
-                *weights.entry(dep.as_str()).or_insert(0) += 1;
+    let mut computing = HashSet::new();
+    let mut computed = HashMap::new();
+    let mut weighted: Vec<(i64, &PackageAutoload)> = packages
+        .iter()
+        .map(|pkg| (importance(&pkg.name, &usage, &mut computing, &mut computed), pkg))
+        .collect();
+
+    weighted.sort_by(|(weight_a, a), (weight_b, b)| {
+        weight_a.cmp(weight_b).then_with(|| natural_cmp_ignore_case(&a.name, &b.name))
+    });
+
+    weighted.into_iter().map(|(_, pkg)| pkg.clone()).collect()
+}
+
+/// Case-insensitive natural order compare, equivalent to PHP's `strnatcasecmp`.
+fn natural_cmp_ignore_case(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    let a = a.to_ascii_lowercase();
+    let b = b.to_ascii_lowercase();
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+
+    loop {
+        match (a.first(), b.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let a_len = a.iter().take_while(|c| c.is_ascii_digit()).count();
+                let b_len = b.iter().take_while(|c| c.is_ascii_digit()).count();
+                let a_num = trim_leading_zeros(&a[..a_len]);
+                let b_num = trim_leading_zeros(&b[..b_len]);
+                let ordering = a_num.len().cmp(&b_num.len()).then_with(|| a_num.cmp(b_num));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a = &a[a_len..];
+                b = &b[b_len..];
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(y);
+                }
+                a = &a[1..];
+                b = &b[1..];
             }
         }
     }
+}
 
-    // Sort by weight (descending - most depended-on first), then by name (ascending)
-    let mut sorted: Vec<_> = packages.to_vec();
-    sorted.sort_by(|a, b| {
-        let weight_a = weights.get(a.name.as_str()).unwrap_or(&0);
-        let weight_b = weights.get(b.name.as_str()).unwrap_or(&0);
+fn trim_leading_zeros(digits: &[u8]) -> &[u8] {
+    let zeros = digits.iter().take_while(|c| **c == b'0').count();
+    &digits[zeros.min(digits.len().saturating_sub(1))..]
+}
 
-        // Higher weight comes first
-        match weight_b.cmp(weight_a) {
-            std::cmp::Ordering::Equal => a.name.cmp(&b.name), // Alphabetical tie-breaker
-            other => other,
+/// Normalize a relative autoload path the way Composer does: drop `.` segments,
+/// resolve `..` where possible and collapse duplicate slashes.
+fn normalize_autoload_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut parts: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|last| *last != "..") => {
+                parts.pop();
+            }
+            segment => parts.push(segment),
         }
-    });
-
-    sorted
+    }
+    parts.join("/")
 }
 
 /// Configuration for autoload generation
@@ -249,6 +316,19 @@ impl AutoloadGenerator {
         // Sort packages by dependency weight for reproducible output
         let sorted_packages = sort_packages_by_dependency(packages);
 
+        // Dev packages are left out of the autoloader when dumping with --no-dev,
+        // metapackages have no files to autoload
+        let dev_mode = root_package.is_none_or(|root| root.dev_mode);
+        let autoload_packages: Vec<&PackageAutoload> = sorted_packages
+            .iter()
+            .filter(|pkg| !pkg.is_metapackage() && (dev_mode || !pkg.dev_requirement))
+            .collect();
+
+        let root_name = root_package
+            .map(|root| root.name.as_str())
+            .filter(|name| !name.is_empty())
+            .unwrap_or("__root__");
+
         // Collect exclude-from-classmap patterns from all packages
         let exclude_patterns = self.collect_exclude_patterns(&sorted_packages, root_autoload);
 
@@ -260,18 +340,22 @@ impl AutoloadGenerator {
         // Files are stored as (identifier, path) pairs - order matters!
         let mut files: Vec<(String, String)> = Vec::new();
 
-        // Process package autoloads in sorted order (dependencies first)
-        // Skip metapackages as they have no files to autoload
-        for pkg in &sorted_packages {
-            if pkg.is_metapackage() {
-                continue;
-            }
-            self.process_autoload(&pkg.autoload, &pkg.install_path, &pkg.name, &mut psr4, &mut psr0, &mut classmap, &mut files, &exclude_patterns)?;
+        // Namespaces and classmaps are collected root first, then packages in
+        // reverse dependency order, so dependents are tried before their dependencies
+        if let Some(autoload) = root_autoload {
+            self.process_autoload(autoload, "", &mut psr4, &mut psr0, &mut classmap, &exclude_patterns)?;
+        }
+        for pkg in autoload_packages.iter().rev() {
+            self.process_autoload(&pkg.autoload, &pkg.install_path, &mut psr4, &mut psr0, &mut classmap, &exclude_patterns)?;
         }
 
-        // Process root autoload last (root overrides)
+        // Files are required dependencies first; the root package requires
+        // everything that is installed, so its files come last
+        for pkg in &autoload_packages {
+            self.process_files(&pkg.autoload, &pkg.install_path, &pkg.name, &mut files);
+        }
         if let Some(autoload) = root_autoload {
-            self.process_autoload(autoload, "", "__root__", &mut psr4, &mut psr0, &mut classmap, &mut files, &exclude_patterns)?;
+            self.process_files(autoload, "", root_name, &mut files);
         }
 
         // Generate authoritative classmap if optimizing
@@ -303,16 +387,15 @@ impl AutoloadGenerator {
         Ok(())
     }
 
-    /// Process a package's autoload configuration
+    /// Process a package's PSR-4, PSR-0 and classmap configuration.
+    /// The first package to define a class wins, like in Composer.
     fn process_autoload(
         &self,
         autoload: &Autoload,
         install_path: &str,
-        package_name: &str,
         psr4: &mut BTreeMap<String, Vec<String>>,
         psr0: &mut BTreeMap<String, Vec<String>>,
         classmap: &mut BTreeMap<String, String>,
-        files: &mut Vec<(String, String)>,
         exclude_patterns: &[Regex],
     ) -> Result<()> {
         let is_root = install_path.is_empty();
@@ -348,36 +431,39 @@ impl AutoloadGenerator {
             let classes = self.classmap_generator.generate_with_excludes(&full_path, exclude_patterns)?;
             for (class_name, file_path) in classes {
                 let path_code = self.path_to_code(&file_path);
-                classmap.insert(class_name, path_code);
+                classmap.entry(class_name).or_insert(path_code);
             }
         }
 
-        // Files - compute identifier as md5(package_name:path)
+        Ok(())
+    }
+
+    /// Collect a package's files, keyed by md5(package_name:path) as in Composer
+    fn process_files(&self, autoload: &Autoload, install_path: &str, package_name: &str, files: &mut Vec<(String, String)>) {
+        let is_root = install_path.is_empty();
+
         for path in &autoload.files {
             let file_identifier = Self::compute_file_identifier(package_name, path);
             let full_path = self.get_path_code(install_path, path, is_root);
-            files.push((file_identifier, full_path));
+            match files.iter_mut().find(|(identifier, _)| *identifier == file_identifier) {
+                Some(existing) => existing.1 = full_path,
+                None => files.push((file_identifier, full_path)),
+            }
         }
-
-        Ok(())
     }
 
     /// Convert a path to PHP code reference ($vendorDir or $baseDir)
     /// This format is used for autoload_psr4.php, autoload_namespaces.php, etc.
     fn get_path_code(&self, install_path: &str, path: &str, is_root: bool) -> String {
-        let path = path.trim_end_matches('/');
         if is_root {
-            if path.is_empty() || path == "." {
+            let path = normalize_autoload_path(path);
+            if path.is_empty() {
                 "$baseDir . '/'".to_string()
             } else {
                 format!("$baseDir . '/{}'", path)
             }
         } else {
-            let full_path = if path.is_empty() {
-                install_path.to_string()
-            } else {
-                format!("{}/{}", install_path, path)
-            };
+            let full_path = normalize_autoload_path(&format!("{}/{}", install_path, path));
             format!("$vendorDir . '/{}'", full_path)
         }
     }
@@ -419,28 +505,16 @@ impl AutoloadGenerator {
         classmap: &mut BTreeMap<String, String>,
         exclude_patterns: &[Regex],
     ) -> Result<()> {
-        // Scan PSR-4 directories
-        for paths in psr4.values() {
+        // Scan PSR-4 then PSR-0 directories in krsort order, keeping the first
+        // definition of a class
+        for paths in psr4.values().rev().chain(psr0.values().rev()) {
             for path_code in paths {
                 // Extract actual path from code like "$vendorDir . '/symfony/console'"
                 if let Some(path) = self.extract_path_from_code(path_code) {
                     let classes = self.classmap_generator.generate_with_excludes(Path::new(&path), exclude_patterns)?;
                     for (class_name, file_path) in classes {
                         let code = self.path_to_code(&file_path);
-                        classmap.insert(class_name, code);
-                    }
-                }
-            }
-        }
-
-        // Scan PSR-0 directories
-        for paths in psr0.values() {
-            for path_code in paths {
-                if let Some(path) = self.extract_path_from_code(path_code) {
-                    let classes = self.classmap_generator.generate_with_excludes(Path::new(&path), exclude_patterns)?;
-                    for (class_name, file_path) in classes {
-                        let code = self.path_to_code(&file_path);
-                        classmap.insert(class_name, code);
+                        classmap.entry(class_name).or_insert(code);
                     }
                 }
             }
@@ -588,6 +662,15 @@ class ComposerAutoloaderInit{suffix}
         }
     }
 
+    /// Render a var_export style list of static paths
+    fn static_path_list(paths: &[String], indent: &str) -> String {
+        paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| format!("{}{} => {},\n", indent, i, Self::to_static_path(path)))
+            .collect()
+    }
+
     /// Generate vendor/composer/autoload_static.php
     fn generate_autoload_static(
         &self,
@@ -617,14 +700,21 @@ class ComposerStaticInit{suffix}
             content.push_str("    );\n\n");
         }
 
-        // Generate PSR-4 prefix lengths grouped by first character
-        // Sorted in descending order by namespace (krsort equivalent)
+        // Sorted in descending order by namespace (krsort equivalent), the
+        // empty prefix becomes the fallback directories
         let mut psr4_vec: Vec<_> = psr4.iter().collect();
-        psr4_vec.sort_by(|a, b| b.0.cmp(a.0)); // Reverse sort
+        psr4_vec.sort_by(|a, b| b.0.cmp(a.0));
+        let (psr4_fallback, psr4_vec): (Vec<_>, Vec<_>) = psr4_vec.into_iter().partition(|(ns, _)| ns.is_empty());
 
-        if !psr4.is_empty() {
-            // Group by first character
-            let mut by_first_char: BTreeMap<char, Vec<(&String, usize)>> = BTreeMap::new();
+        let mut psr0_vec: Vec<_> = psr0.iter().collect();
+        psr0_vec.sort_by(|a, b| b.0.cmp(a.0));
+        let (psr0_fallback, psr0_vec): (Vec<_>, Vec<_>) = psr0_vec.into_iter().partition(|(ns, _)| ns.is_empty());
+
+        let mut properties = Vec::new();
+
+        if !psr4_vec.is_empty() {
+            // Group by first character, in order of first appearance
+            let mut by_first_char: IndexMap<char, Vec<(&String, usize)>> = IndexMap::new();
             for (namespace, _) in &psr4_vec {
                 let first_char = namespace.chars().next().unwrap_or('_');
                 by_first_char.entry(first_char)
@@ -633,12 +723,8 @@ class ComposerStaticInit{suffix}
             }
 
             content.push_str("    public static $prefixLengthsPsr4 = array (\n");
-            // Sort by first char descending
-            let mut char_entries: Vec<_> = by_first_char.iter().collect();
-            char_entries.sort_by(|a, b| b.0.cmp(a.0));
-
-            for (first_char, namespaces) in char_entries {
-                content.push_str(&format!("        '{}' =>\n        array (\n", first_char));
+            for (first_char, namespaces) in &by_first_char {
+                content.push_str(&format!("        '{}' => \n        array (\n", first_char));
                 for (ns, len) in namespaces {
                     let ns_escaped = ns.replace('\\', "\\\\");
                     content.push_str(&format!("            '{}' => {},\n", ns_escaped, len));
@@ -651,22 +737,27 @@ class ComposerStaticInit{suffix}
             content.push_str("    public static $prefixDirsPsr4 = array (\n");
             for (namespace, paths) in &psr4_vec {
                 let ns_escaped = namespace.replace('\\', "\\\\");
-                content.push_str(&format!("        '{}' =>\n        array (\n", ns_escaped));
-                for (i, path) in paths.iter().enumerate() {
-                    content.push_str(&format!("            {} => {},\n", i, Self::to_static_path(path)));
-                }
+                content.push_str(&format!("        '{}' => \n        array (\n", ns_escaped));
+                content.push_str(&Self::static_path_list(paths, "            "));
                 content.push_str("        ),\n");
             }
             content.push_str("    );\n\n");
+
+            properties.push("prefixLengthsPsr4");
+            properties.push("prefixDirsPsr4");
         }
 
-        // Generate PSR-0 prefixes if present
-        if !psr0.is_empty() {
-            let mut psr0_vec: Vec<_> = psr0.iter().collect();
-            psr0_vec.sort_by(|a, b| b.0.cmp(a.0));
+        if let Some((_, paths)) = psr4_fallback.first() {
+            content.push_str("    public static $fallbackDirsPsr4 = array (\n");
+            content.push_str(&Self::static_path_list(paths, "        "));
+            content.push_str("    );\n\n");
+            properties.push("fallbackDirsPsr4");
+        }
 
-            // Group by first character
-            let mut by_first_char: BTreeMap<char, Vec<(&String, &Vec<String>)>> = BTreeMap::new();
+        // Generate PSR-0 prefixes if present
+        if !psr0_vec.is_empty() {
+            // Group by first character, in order of first appearance
+            let mut by_first_char: IndexMap<char, Vec<(&String, &Vec<String>)>> = IndexMap::new();
             for (namespace, paths) in &psr0_vec {
                 let first_char = namespace.chars().next().unwrap_or('_');
                 by_first_char.entry(first_char)
@@ -675,22 +766,25 @@ class ComposerStaticInit{suffix}
             }
 
             content.push_str("    public static $prefixesPsr0 = array (\n");
-            let mut char_entries: Vec<_> = by_first_char.iter().collect();
-            char_entries.sort_by(|a, b| b.0.cmp(a.0));
-
-            for (first_char, namespaces) in char_entries {
-                content.push_str(&format!("        '{}' =>\n        array (\n", first_char));
+            for (first_char, namespaces) in &by_first_char {
+                content.push_str(&format!("        '{}' => \n        array (\n", first_char));
                 for (ns, paths) in namespaces {
                     let ns_escaped = ns.replace('\\', "\\\\");
-                    content.push_str(&format!("            '{}' =>\n            array (\n", ns_escaped));
-                    for (i, path) in paths.iter().enumerate() {
-                        content.push_str(&format!("                {} => {},\n", i, Self::to_static_path(path)));
-                    }
+                    content.push_str(&format!("            '{}' => \n            array (\n", ns_escaped));
+                    content.push_str(&Self::static_path_list(paths, "                "));
                     content.push_str("            ),\n");
                 }
                 content.push_str("        ),\n");
             }
             content.push_str("    );\n\n");
+            properties.push("prefixesPsr0");
+        }
+
+        if let Some((_, paths)) = psr0_fallback.first() {
+            content.push_str("    public static $fallbackDirsPsr0 = array (\n");
+            content.push_str(&Self::static_path_list(paths, "        "));
+            content.push_str("    );\n\n");
+            properties.push("fallbackDirsPsr0");
         }
 
         // Generate classmap
@@ -700,29 +794,13 @@ class ComposerStaticInit{suffix}
             content.push_str(&format!("        '{}' => {},\n", class_escaped, Self::to_static_path(path)));
         }
         content.push_str("    );\n\n");
+        properties.push("classMap");
 
-        // Generate initializer
-        let mut initializer_content = String::new();
-        if !psr4.is_empty() {
-            initializer_content.push_str(&format!(
-                "            $loader->prefixLengthsPsr4 = ComposerStaticInit{}::$prefixLengthsPsr4;\n",
-                suffix
-            ));
-            initializer_content.push_str(&format!(
-                "            $loader->prefixDirsPsr4 = ComposerStaticInit{}::$prefixDirsPsr4;\n",
-                suffix
-            ));
-        }
-        if !psr0.is_empty() {
-            initializer_content.push_str(&format!(
-                "            $loader->prefixesPsr0 = ComposerStaticInit{}::$prefixesPsr0;\n",
-                suffix
-            ));
-        }
-        initializer_content.push_str(&format!(
-            "            $loader->classMap = ComposerStaticInit{}::$classMap;\n",
-            suffix
-        ));
+        // Generate initializer, assigning the properties in ClassLoader order
+        let initializer_content: String = properties
+            .iter()
+            .map(|property| format!("            $loader->{property} = ComposerStaticInit{suffix}::${property};\n"))
+            .collect();
 
         content.push_str(&format!(r#"    public static function getInitializer(ClassLoader $loader)
     {{
@@ -757,6 +835,12 @@ class ComposerStaticInit{suffix}
             ));
         }
 
+        let entries_str = if entries.is_empty() {
+            String::new()
+        } else {
+            format!("{},\n", entries.join(",\n"))
+        };
+
         let content = format!(r#"<?php
 
 // autoload_psr4.php @generated by Composer
@@ -765,9 +849,8 @@ $vendorDir = dirname(__DIR__);
 $baseDir = dirname($vendorDir);
 
 return array(
-{},
-);
-"#, entries.join(",\n"));
+{});
+"#, entries_str);
 
         std::fs::write(composer_dir.join("autoload_psr4.php"), content)?;
         Ok(())
@@ -1145,7 +1228,7 @@ if ($issues) {
     fn php_value_or_null(opt: &Option<String>) -> String {
         match opt {
             Some(s) => Self::php_string(s),
-            None => "NULL".to_string(),
+            None => "null".to_string(),
         }
     }
 
@@ -1313,4 +1396,31 @@ mod tests {
         assert!(content.contains("'old/package'"));
         assert!(content.contains("'replaced'"));
     }
+
+    #[test]
+    fn test_sort_packages_by_dependency() {
+        let package = |name: &str, requires: &[&str]| PackageAutoload {
+            name: name.to_string(),
+            requires: requires.iter().map(|r| r.to_string()).collect(),
+            ..Default::default()
+        };
+        let packages = vec![
+            package("vendor/app-10", &["vendor/b"]),
+            package("vendor/app-9", &["vendor/b"]),
+            package("vendor/b", &["vendor/c"]),
+            package("vendor/c", &["vendor/b"]),
+            package("Vendor/A", &[]),
+        ];
+
+        let names: Vec<String> = sort_packages_by_dependency(&packages).into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["vendor/b", "vendor/c", "Vendor/A", "vendor/app-9", "vendor/app-10"]);
+    }
+
+    #[test]
+    fn test_normalize_autoload_path() {
+        assert_eq!(normalize_autoload_path("./src/"), "src");
+        assert_eq!(normalize_autoload_path("src/../lib//Foo"), "lib/Foo");
+        assert_eq!(normalize_autoload_path("../shared"), "../shared");
+        assert_eq!(normalize_autoload_path(""), "");
+    }
 }
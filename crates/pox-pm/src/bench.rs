@@ -0,0 +1,298 @@
+//! Standardized benchmark workloads behind `pox pm bench`.
+//!
+//! Every fixture is generated deterministically, so timings are comparable
+//! between machines and releases. Nothing is downloaded or reported anywhere.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use indexmap::IndexMap;
+use pox_semver::VersionParser;
+
+use crate::autoload::{AutoloadConfig, AutoloadGenerator, PackageAutoload};
+use crate::package::{Autoload, AutoloadPath, Package};
+use crate::solver::{Policy, Pool, Request, Solver};
+use crate::{ComposerError, Result};
+
+/// Number of constraints parsed by the constraint workload
+pub const CONSTRAINT_COUNT: usize = 10_000;
+
+/// Shape of the solver fixture: roughly a Symfony skeleton with its dev tools,
+/// 120 packages with 40 versions each, every version requiring 6 other packages
+const SOLVER_PACKAGES: usize = 120;
+const SOLVER_MAJORS: usize = 4;
+const SOLVER_MINORS: usize = 10;
+const SOLVER_REQUIRES: usize = 6;
+
+/// Shape of the autoload fixture: 150 packages with 20 PSR-4 classes each
+const AUTOLOAD_PACKAGES: usize = 150;
+const AUTOLOAD_CLASSES: usize = 20;
+
+/// A benchmark workload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    /// Parse the constraint fixture
+    Constraints,
+    /// Solve the dependency fixture
+    Solve,
+    /// Generate an optimized autoloader for the fixture vendor tree
+    Autoload,
+}
+
+impl Workload {
+    /// All workloads in the order they run
+    pub const ALL: [Workload; 3] = [Workload::Constraints, Workload::Solve, Workload::Autoload];
+
+    /// Stable identifier used in reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            Workload::Constraints => "parse-constraints",
+            Workload::Solve => "solve",
+            Workload::Autoload => "autoload",
+        }
+    }
+
+    /// What one iteration of the workload does
+    pub fn description(&self) -> String {
+        match self {
+            Workload::Constraints => format!("Parse {} version constraints", CONSTRAINT_COUNT),
+            Workload::Solve => format!(
+                "Solve {} packages with {} versions each",
+                SOLVER_PACKAGES,
+                SOLVER_MAJORS * SOLVER_MINORS
+            ),
+            Workload::Autoload => format!(
+                "Dump an optimized autoloader for {} packages with {} classes each",
+                AUTOLOAD_PACKAGES, AUTOLOAD_CLASSES
+            ),
+        }
+    }
+
+    /// Look up a workload by its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|w| w.name() == name)
+    }
+
+    /// Run the workload `iterations` times; fixtures are set up once and not timed
+    pub fn run(&self, iterations: usize) -> Result<BenchResult> {
+        let iterations = iterations.max(1);
+        let samples = match self {
+            Workload::Constraints => {
+                let constraints = constraint_fixture();
+                let parser = VersionParser::new();
+                time(iterations, || {
+                    for constraint in &constraints {
+                        parser.parse_constraints(constraint).map_err(|e| {
+                            ComposerError::InvalidManifest { message: format!("{}: {}", constraint, e) }
+                        })?;
+                    }
+                    Ok(())
+                })?
+            }
+            Workload::Solve => {
+                let (pool, request) = solver_fixture();
+                let policy = Policy::new();
+                time(iterations, || {
+                    Solver::new(&pool, &policy)
+                        .with_optimization(false)
+                        .solve(&request)
+                        .map(|_| ())
+                        .map_err(|_| ComposerError::DependencyResolution("benchmark fixture".to_string()))
+                })?
+            }
+            Workload::Autoload => {
+                let dir = tempfile::tempdir()?;
+                let packages = autoload_fixture(dir.path())?;
+                let generator = AutoloadGenerator::new(AutoloadConfig {
+                    vendor_dir: dir.path().join("vendor"),
+                    base_dir: dir.path().to_path_buf(),
+                    optimize: true,
+                    suffix: Some("Bench".to_string()),
+                    ..Default::default()
+                });
+                time(iterations, || generator.generate(&packages, None, None))?
+            }
+        };
+
+        Ok(BenchResult { workload: *self, samples })
+    }
+}
+
+/// Timings of one workload
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    pub workload: Workload,
+    pub samples: Vec<Duration>,
+}
+
+impl BenchResult {
+    /// Fastest iteration
+    pub fn min(&self) -> Duration {
+        self.samples.iter().min().copied().unwrap_or_default()
+    }
+
+    /// Median iteration, the number to compare
+    pub fn median(&self) -> Duration {
+        let mut sorted = self.samples.clone();
+        sorted.sort();
+        match sorted.len() {
+            0 => Duration::ZERO,
+            len if len % 2 == 0 => (sorted[len / 2 - 1] + sorted[len / 2]) / 2,
+            len => sorted[len / 2],
+        }
+    }
+
+    /// Mean of all iterations
+    pub fn mean(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+}
+
+fn time(iterations: usize, mut f: impl FnMut() -> Result<()>) -> Result<Vec<Duration>> {
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        f()?;
+        samples.push(start.elapsed());
+    }
+    Ok(samples)
+}
+
+/// The constraints parsed by the constraint workload, a mix of the forms found on Packagist
+pub fn constraint_fixture() -> Vec<String> {
+    (0..CONSTRAINT_COUNT)
+        .map(|i| {
+            let (major, minor, patch) = (i % 13, (i / 13) % 20, (i / 260) % 10);
+            match i % 10 {
+                0 => format!("^{}.{}", major, minor),
+                1 => format!("~{}.{}.{}", major, minor, patch),
+                2 => format!(">={}.{} <{}.0", major, minor, major + 1),
+                3 => format!("{}.{}.*", major, minor),
+                4 => format!("^{}.{} || ^{}.0", major, minor, major + 1),
+                5 => format!("{}.{}.{}", major, minor, patch),
+                6 => format!("{}.{}.x-dev", major, minor),
+                7 => format!(">={}.{}.{}-beta{}", major, minor, patch, i % 5 + 1),
+                8 => format!("~{}.{} | ~{}.0@dev", major, minor, major + 1),
+                _ => format!("v{}.{}.{} - {}.0", major, minor, patch, major + 2),
+            }
+        })
+        .collect()
+}
+
+fn solver_package_name(i: usize) -> String {
+    format!("vendor{}/package-{}", i % 12, i)
+}
+
+/// The pool and request solved by the solver workload
+pub fn solver_fixture() -> (Pool, Request) {
+    let mut pool = Pool::new();
+
+    for i in 0..SOLVER_PACKAGES {
+        for major in 1..=SOLVER_MAJORS {
+            for minor in 0..SOLVER_MINORS {
+                let version = format!("{}.{}.0", major, minor);
+                let mut package = Package::new(solver_package_name(i), &version);
+                package.pretty_version = Some(version.clone());
+
+                for offset in 1..=SOLVER_REQUIRES {
+                    let dep = (i + offset * 7) % SOLVER_PACKAGES;
+                    let constraint = format!("^{}.{} || ^{}.0", (dep + major) % SOLVER_MAJORS + 1, minor % 3, major);
+                    package.require.insert(solver_package_name(dep), constraint);
+                }
+                if i % 20 == 0 {
+                    package.provide.insert("psr/log-implementation".to_string(), "1.0|2.0|3.0".to_string());
+                }
+
+                pool.add_package(package);
+            }
+        }
+    }
+
+    let mut request = Request::new();
+    for i in (0..SOLVER_PACKAGES).step_by(6) {
+        request.require(solver_package_name(i), "*");
+    }
+
+    (pool, request)
+}
+
+/// Write the vendor tree of the autoload workload below `dir` and return its packages
+pub fn autoload_fixture(dir: &Path) -> Result<Vec<PackageAutoload>> {
+    let mut packages = Vec::with_capacity(AUTOLOAD_PACKAGES);
+
+    for i in 0..AUTOLOAD_PACKAGES {
+        let name = format!("bench{}/package-{}", i % 10, i);
+        let namespace = format!("Bench{}\\Package{}\\", i % 10, i);
+        let src = dir.join("vendor").join(&name).join("src");
+
+        for class in 0..AUTOLOAD_CLASSES {
+            let sub = format!("Sub{}", class % 4);
+            std::fs::create_dir_all(src.join(&sub))?;
+            std::fs::write(
+                src.join(&sub).join(format!("Class{}.php", class)),
+                format!(
+                    "<?php\n\nnamespace {}{};\n\nfinal class Class{}\n{{\n    public function run(): int\n    {{\n        return {};\n    }}\n}}\n",
+                    namespace, sub, class, class
+                ),
+            )?;
+        }
+
+        let mut psr4 = IndexMap::new();
+        psr4.insert(namespace, AutoloadPath::Single("src/".to_string()));
+        let requires = if i > 0 { vec![format!("bench{}/package-{}", (i - 1) % 10, i - 1)] } else { Vec::new() };
+
+        packages.push(PackageAutoload {
+            name: name.clone(),
+            autoload: Autoload { psr4, ..Default::default() },
+            install_path: name,
+            requires,
+            pretty_version: Some("1.0.0".to_string()),
+            version: Some("1.0.0.0".to_string()),
+            reference: None,
+            package_type: "library".to_string(),
+            dev_requirement: false,
+            aliases: Vec::new(),
+            replaces: IndexMap::new(),
+            provides: IndexMap::new(),
+        });
+    }
+
+    Ok(packages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constraint_fixture_parses() {
+        let constraints = constraint_fixture();
+        assert_eq!(constraints.len(), CONSTRAINT_COUNT);
+
+        let parser = VersionParser::new();
+        for constraint in &constraints {
+            assert!(parser.parse_constraints(constraint).is_ok(), "{}", constraint);
+        }
+    }
+
+    #[test]
+    fn test_workloads_run() {
+        // Rule generation recurses through the solver fixture's dependency
+        // chains, which needs more than the test thread stack in debug builds
+        let handle = std::thread::Builder::new()
+            .stack_size(64 * 1024 * 1024)
+            .spawn(|| {
+                for workload in Workload::ALL {
+                    let result = workload.run(2).unwrap();
+                    assert_eq!(result.samples.len(), 2);
+                    assert!(result.min() <= result.median());
+                    assert_eq!(Workload::from_name(workload.name()), Some(workload));
+                }
+            })
+            .unwrap();
+        handle.join().unwrap();
+    }
+}
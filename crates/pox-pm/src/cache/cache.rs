@@ -1,12 +1,18 @@
 use regex::Regex;
 use sha2::{Digest, Sha256};
+use std::collections::hash_map::RandomState;
 use std::fs::{self, File};
+use std::hash::{BuildHasher, Hasher};
 use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::OnceLock;
 use std::time::{Duration, SystemTime};
 use walkdir::WalkDir;
 
+/// Set once a garbage collection was scheduled in this process
+static GC_SCHEDULED: AtomicBool = AtomicBool::new(false);
+
 fn sanitize_regex() -> &'static Regex {
     static REGEX: OnceLock<Regex> = OnceLock::new();
     REGEX.get_or_init(|| Regex::new("[^a-z0-9._]").unwrap())
@@ -154,8 +160,14 @@ impl Cache {
             return Ok(None);
         }
 
-        match fs::read(self.get_path(key)) {
-            Ok(data) => Ok(Some(data)),
+        let path = self.get_path(key);
+        match fs::read(&path) {
+            Ok(data) => {
+                if !self.read_only {
+                    Self::touch(&path);
+                }
+                Ok(Some(data))
+            }
             Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
             Err(e) => Err(e),
         }
@@ -202,7 +214,12 @@ impl Cache {
         }
 
         match fs::copy(&path, dest) {
-            Ok(_) => Ok(true),
+            Ok(_) => {
+                if !self.read_only {
+                    Self::touch(&path);
+                }
+                Ok(true)
+            }
             Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
             Err(e) => Err(e),
         }
@@ -314,6 +331,68 @@ impl Cache {
         Ok(freed)
     }
 
+    /// Evict least recently used files until the cache fits into `max_size` bytes
+    ///
+    /// Files are ordered by modification time, which is refreshed whenever an
+    /// entry is read from the cache.
+    ///
+    /// # Returns
+    /// Number of bytes freed
+    pub fn gc_size(&self, max_size: u64) -> io::Result<u64> {
+        if !self.enabled || self.read_only || !self.root.is_dir() {
+            return Ok(0);
+        }
+
+        let mut files: Vec<(PathBuf, SystemTime, u64)> = WalkDir::new(&self.root)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                if !metadata.is_file() {
+                    return None;
+                }
+                let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                Some((entry.into_path(), modified, metadata.len()))
+            })
+            .collect();
+
+        let mut total: u64 = files.iter().map(|(_, _, size)| size).sum();
+        if total <= max_size {
+            return Ok(0);
+        }
+
+        files.sort_by_key(|(_, modified, _)| *modified);
+
+        let mut freed = 0u64;
+        for (path, _, size) in files {
+            if total <= max_size {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total -= size;
+                freed += size;
+            }
+        }
+
+        Ok(freed)
+    }
+
+    /// Whether an automatic garbage collection should run now
+    ///
+    /// Like Composer, collection runs at most once per process and only on
+    /// roughly one out of 50 runs, so regular installs stay fast.
+    pub fn gc_is_necessary() -> bool {
+        if GC_SCHEDULED.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+
+        // The keys of RandomState come from the OS, which makes its hash a random draw
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        hasher.finish().is_multiple_of(50)
+    }
+
     /// Garbage collect VCS cache directories
     ///
     /// Removes VCS cache directories older than the specified TTL
@@ -428,6 +507,16 @@ impl Cache {
         Ok(SystemTime::now().duration_since(modified).ok())
     }
 
+    /// Mark a cache entry as recently used by refreshing its modification time
+    ///
+    /// The GC evicts least recently used entries first. Failures are ignored,
+    /// they only make the entry look older than it is.
+    pub fn touch(path: &Path) {
+        if let Ok(file) = File::options().write(true).open(path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+    }
+
     /// Calculate the total size of a directory
     fn dir_size(&self, path: &Path) -> io::Result<u64> {
         let mut total = 0u64;
@@ -581,6 +670,49 @@ mod tests {
         assert!(cache.has("new.txt"));
     }
 
+    #[test]
+    fn test_cache_gc_size_evicts_least_recently_used() {
+        let temp = TempDir::new().unwrap();
+        let cache = Cache::new(temp.path().to_path_buf());
+
+        cache.write("a.zip", b"aaaa").unwrap();
+        thread::sleep(StdDuration::from_millis(20));
+        cache.write("b.zip", b"bbbb").unwrap();
+        thread::sleep(StdDuration::from_millis(20));
+        cache.write("c.zip", b"cccc").unwrap();
+        thread::sleep(StdDuration::from_millis(20));
+
+        // Reading "a.zip" makes it the most recently used entry
+        cache.read("a.zip").unwrap();
+
+        assert_eq!(cache.gc_size(12).unwrap(), 0);
+
+        let freed = cache.gc_size(8).unwrap();
+        assert_eq!(freed, 4);
+        assert!(cache.has("a.zip"));
+        assert!(!cache.has("b.zip"));
+        assert!(cache.has("c.zip"));
+        assert_eq!(cache.size().unwrap(), 8);
+    }
+
+    #[test]
+    fn test_cache_touch() {
+        let temp = TempDir::new().unwrap();
+        let cache = Cache::new(temp.path().to_path_buf());
+
+        cache.write("a.zip", b"aaaa").unwrap();
+        thread::sleep(StdDuration::from_millis(20));
+        cache.write("b.zip", b"bbbb").unwrap();
+        thread::sleep(StdDuration::from_millis(20));
+
+        // Touching "a.zip" by path makes it the most recently used entry
+        Cache::touch(&temp.path().join("a.zip"));
+
+        assert_eq!(cache.gc_size(4).unwrap(), 4);
+        assert!(cache.has("a.zip"));
+        assert!(!cache.has("b.zip"));
+    }
+
     #[test]
     fn test_cache_size() {
         let temp = TempDir::new().unwrap();
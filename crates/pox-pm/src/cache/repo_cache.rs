@@ -19,6 +19,13 @@ pub struct CacheMetadata {
     pub etag: Option<String>,
 }
 
+impl CacheMetadata {
+    /// Whether the entry can be revalidated with a conditional request
+    pub fn has_validators(&self) -> bool {
+        self.last_modified.is_some() || self.etag.is_some()
+    }
+}
+
 impl Default for CacheMetadata {
     fn default() -> Self {
         Self {
@@ -215,6 +222,23 @@ mod tests {
         assert_eq!(read_metadata.etag, metadata.etag);
     }
 
+    #[test]
+    fn test_cache_metadata_has_validators() {
+        assert!(!CacheMetadata::default().has_validators());
+
+        let etag_only = CacheMetadata {
+            last_modified: None,
+            etag: Some("\"abc123\"".to_string()),
+        };
+        assert!(etag_only.has_validators());
+
+        let last_modified_only = CacheMetadata {
+            last_modified: Some("Wed, 24 Dec 2025 10:00:00 GMT".to_string()),
+            etag: None,
+        };
+        assert!(last_modified_only.has_validators());
+    }
+
     #[test]
     fn test_sanitize_url() {
         assert_eq!(
@@ -2,14 +2,20 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use anyhow::{Context, Result};
 
-use crate::config::{Config, PreferredInstall};
+use crate::config::{AuthConfig, Config, PreferredInstall};
+use crate::downloader::RepositorySigningKeys;
 use crate::event::EventDispatcher;
-use crate::http::HttpClient;
+use crate::http::{HttpClient, HttpClientConfig};
 use crate::json::{ComposerJson, ComposerLock, Repository as JsonRepository, Repositories};
 use crate::plugin::register_plugins;
 use crate::repository::{ComposerRepository, RepositoryManager, Repository};
-use crate::installer::InstallationManager;
+use crate::installer::{BinCompat, InstallationManager, InstallerPaths};
 use crate::installer::InstallConfig;
+use crate::io::{Io, NullIo};
+use crate::profile::Profiler;
+use crate::progress::ProgressReporter;
+use crate::solver::UpdateAllowTransitiveDeps;
+use crate::workspace::Workspace;
 
 /// The central Composer application object.
 pub struct Composer {
@@ -22,6 +28,10 @@ pub struct Composer {
     pub working_dir: PathBuf,
     pub platform_packages: Vec<crate::package::Package>,
     pub event_dispatcher: EventDispatcher,
+    pub io: Arc<dyn Io>,
+    pub profiler: Arc<Profiler>,
+    /// Progress of metadata loading, solving and downloads
+    pub progress: Arc<ProgressReporter>,
 }
 
 impl Composer {
@@ -46,7 +56,10 @@ impl Composer {
 
     /// Dispatch a typed event and return the exit code.
     pub fn dispatch<E: crate::event::ComposerEvent>(&self, event: &E) -> anyhow::Result<i32> {
-        self.event_dispatcher.dispatch(event, self)
+        let start = std::time::Instant::now();
+        let result = self.event_dispatcher.dispatch(event, self);
+        self.profiler.record("scripts", start.elapsed());
+        result
     }
 
     /// Get the vendor directory path.
@@ -64,13 +77,19 @@ pub struct ComposerBuilder {
     http_client: Option<Arc<HttpClient>>,
     repository_manager: Option<RepositoryManager>,
     additional_repositories: Vec<Arc<dyn Repository>>,
+    io: Option<Arc<dyn Io>>,
+    profiler: Option<Arc<Profiler>>,
 
     // Installation options (override config)
     prefer_source: Option<bool>,
     prefer_dist: Option<bool>,
     dry_run: bool,
     no_dev: bool,
+    no_verify: bool,
     prefer_lowest: bool,
+    prefer_stable: bool,
+    solver_seed: Option<u64>,
+    update_with_dependencies: UpdateAllowTransitiveDeps,
 
     // Platform packages (php, ext-*, lib-*)
     platform_packages: Vec<crate::package::Package>,
@@ -90,11 +109,17 @@ impl ComposerBuilder {
             http_client: None,
             repository_manager: None,
             additional_repositories: Vec::new(),
+            io: None,
+            profiler: None,
             prefer_source: None,
             prefer_dist: None,
             dry_run: false,
             no_dev: false,
+            no_verify: false,
             prefer_lowest: false,
+            prefer_stable: false,
+            solver_seed: None,
+            update_with_dependencies: UpdateAllowTransitiveDeps::OnlyListed,
             platform_packages: Vec::new(),
             disable_packagist: None,
         }
@@ -125,6 +150,18 @@ impl ComposerBuilder {
         self
     }
 
+    /// Io used for output and questions, defaults to [`NullIo`] which shows nothing
+    pub fn with_io(mut self, io: Arc<dyn Io>) -> Self {
+        self.io = Some(io);
+        self
+    }
+
+    /// Profiler timing the phases of installs and updates, disabled by default
+    pub fn with_profiler(mut self, profiler: Arc<Profiler>) -> Self {
+        self.profiler = Some(profiler);
+        self
+    }
+
     pub fn add_repository(mut self, repo: Arc<dyn Repository>) -> Self {
         self.additional_repositories.push(repo);
         self
@@ -156,11 +193,38 @@ impl ComposerBuilder {
         self
     }
 
+    /// Skip checksum and signature verification of dist archives
+    pub fn no_verify(mut self, no_verify: bool) -> Self {
+        self.no_verify = no_verify;
+        self
+    }
+
     pub fn prefer_lowest(mut self, prefer: bool) -> Self {
         self.prefer_lowest = prefer;
         self
     }
 
+    /// Prefer stable versions even if `prefer-stable` is not set in composer.json
+    pub fn prefer_stable(mut self, prefer: bool) -> Self {
+        self.prefer_stable = prefer;
+        self
+    }
+
+    /// Seed breaking ties between equally preferred packages, see [`Policy::seed`]
+    ///
+    /// Defaults to the `POX_SOLVER_SEED` environment variable.
+    ///
+    /// [`Policy::seed`]: crate::solver::Policy::seed
+    pub fn solver_seed(mut self, seed: Option<u64>) -> Self {
+        self.solver_seed = seed;
+        self
+    }
+
+    pub fn update_with_dependencies(mut self, mode: UpdateAllowTransitiveDeps) -> Self {
+        self.update_with_dependencies = mode;
+        self
+    }
+
     pub fn with_platform_packages(mut self, packages: Vec<crate::package::Package>) -> Self {
         self.platform_packages = packages;
         self
@@ -175,21 +239,48 @@ impl ComposerBuilder {
         let composer_json = self.composer_json.take()
             .ok_or_else(|| anyhow::anyhow!("composer.json is required"))?;
 
-        let config = self.config.take().unwrap_or_else(|| {
+        let mut config = self.config.take().unwrap_or_else(|| {
             Config::with_base_dir(&self.working_dir)
         });
 
+        let workspace = Workspace::find(&self.working_dir).context("Failed to load workspace")?;
+
+        // Workspace members share the cache of the workspace root
+        if let Some(workspace) = &workspace {
+            if workspace.is_member(&self.canonical_working_dir()) {
+                if let Ok(root_config) = Config::build(Some(&workspace.root), true) {
+                    config.cache_dir = root_config.cache_dir;
+                    config.cache_files_dir = root_config.cache_files_dir;
+                    config.cache_repo_dir = root_config.cache_repo_dir;
+                    config.cache_vcs_dir = root_config.cache_vcs_dir;
+                }
+            }
+        }
+
+        let auth = Arc::new(self.build_auth(&config, &composer_json));
+
         let http_client = match self.http_client.take() {
             Some(client) => client,
-            None => Arc::new(HttpClient::new().context("Failed to create HTTP client")?),
+            None => Arc::new(
+                HttpClient::with_config(HttpClientConfig::new().with_proxy_config(config.proxy.clone()))
+                    .context("Failed to create HTTP client")?
+                    .with_auth_shared(auth.clone()),
+            ),
         };
 
-        let repository_manager = self.build_repository_manager(&config, &composer_json)?;
-        let install_config = self.build_install_config(&config);
+        let io = self.io.take().unwrap_or_else(|| Arc::new(NullIo));
 
-        let installation_manager = Arc::new(InstallationManager::new(
+        let repository_manager = self.build_repository_manager(&config, &composer_json, workspace.as_ref(), auth)?;
+        let install_config = self.build_install_config(&config, &composer_json);
+
+        let profiler = self.profiler.take().unwrap_or_default();
+        let progress = Arc::new(ProgressReporter::new());
+        let installation_manager = Arc::new(InstallationManager::with_progress(
             http_client.clone(),
             install_config,
+            io.clone(),
+            profiler.clone(),
+            progress.clone(),
         ));
 
         // Create event dispatcher with script listeners and plugins
@@ -206,6 +297,9 @@ impl ComposerBuilder {
             working_dir: self.working_dir.clone(),
             platform_packages: std::mem::take(&mut self.platform_packages),
             event_dispatcher,
+            io,
+            profiler,
+            progress,
         })
     }
 
@@ -213,17 +307,31 @@ impl ComposerBuilder {
         &mut self,
         config: &Config,
         composer_json: &ComposerJson,
+        workspace: Option<&Workspace>,
+        auth: Arc<AuthConfig>,
     ) -> Result<RepositoryManager> {
         if let Some(manager) = self.repository_manager.take() {
             return Ok(manager);
         }
 
         let mut repository_manager = RepositoryManager::new();
+        repository_manager.set_auth(auth);
+        repository_manager.set_proxy(config.proxy.clone());
+        if let Some(cache_dir) = config.cache_dir.clone() {
+            repository_manager.set_cache_dir(cache_dir);
+        }
 
         for repo in composer_json.repositories.as_vec() {
             repository_manager.add_from_json_repository(&repo);
         }
 
+        // Sibling packages of a monorepo are installed from the working tree
+        if let Some(workspace) = workspace {
+            for repo in workspace.path_repositories(&self.canonical_working_dir()) {
+                repository_manager.add_repository(Arc::new(repo));
+            }
+        }
+
         for repo in &self.additional_repositories {
             repository_manager.add_repository(repo.clone());
         }
@@ -233,18 +341,41 @@ impl ComposerBuilder {
         });
 
         if !packagist_disabled {
-            let packagist = if let Some(cache_dir) = config.cache_dir.clone() {
+            let mut packagist = if let Some(cache_dir) = config.cache_dir.clone() {
                 ComposerRepository::packagist_with_cache(cache_dir)
             } else {
                 ComposerRepository::packagist()
             };
+            packagist.set_proxy(&config.proxy);
             repository_manager.add_repository(Arc::new(packagist));
         }
 
         Ok(repository_manager)
     }
 
-    fn build_install_config(&self, config: &Config) -> InstallConfig {
+    /// Credentials from auth.json, COMPOSER_AUTH, the composer config and
+    /// repository URLs (e.g. Private Packagist vendor URLs)
+    fn build_auth(&self, config: &Config, composer_json: &ComposerJson) -> AuthConfig {
+        let mut auth = AuthConfig::build(Some(&self.working_dir)).unwrap_or_else(|e| {
+            log::warn!("Failed to load auth.json: {}", e);
+            AuthConfig::default()
+        });
+        auth.merge_config(config);
+
+        for repo in composer_json.repositories.as_vec() {
+            if let JsonRepository::Composer { url, .. } = &repo {
+                auth.add_url_credentials(url);
+            }
+        }
+
+        auth
+    }
+
+    fn canonical_working_dir(&self) -> PathBuf {
+        self.working_dir.canonicalize().unwrap_or_else(|_| self.working_dir.clone())
+    }
+
+    pub(crate) fn build_install_config(&self, config: &Config, composer_json: &ComposerJson) -> InstallConfig {
         let (prefer_source, prefer_dist) = match (self.prefer_source, self.prefer_dist) {
             (Some(src), Some(dst)) => (src, dst),
             (Some(src), None) => (src, !src),
@@ -258,16 +389,64 @@ impl ComposerBuilder {
             }
         };
 
+        let cache_dir = config.cache_dir.clone()
+            .unwrap_or_else(|| self.working_dir.join(".pox/cache"));
+
         InstallConfig {
             vendor_dir: self.working_dir.join(&config.vendor_dir),
             bin_dir: self.working_dir.join(&config.bin_dir),
-            cache_dir: config.cache_dir.clone()
-                .unwrap_or_else(|| self.working_dir.join(".pox/cache")),
+            // A read-only cache can't keep its mirrors up to date
+            cache_vcs_dir: (!config.cache_read_only)
+                .then(|| config.cache_vcs_dir.clone().unwrap_or_else(|| cache_dir.join("vcs"))),
+            cache_dir,
+            cache_files_ttl: config.get_cache_files_ttl(),
+            cache_files_maxsize: config.cache_files_maxsize,
             prefer_source,
             prefer_dist,
+            // --prefer-source/--prefer-dist override per-package config
+            preferred_install: if self.prefer_source.is_some() || self.prefer_dist.is_some() {
+                Vec::new()
+            } else {
+                config.preferred_install_patterns.clone()
+            },
+            verify: !self.no_verify,
+            signing_keys: composer_json
+                .repositories
+                .as_vec()
+                .into_iter()
+                .filter_map(|repo| match repo {
+                    crate::json::Repository::Composer { url, signing_keys, .. } if !signing_keys.is_empty() => {
+                        Some(RepositorySigningKeys { url, keys: signing_keys })
+                    }
+                    _ => None,
+                })
+                .collect(),
+            secure_http: config.secure_http,
             dry_run: self.dry_run,
             no_dev: self.no_dev,
             prefer_lowest: self.prefer_lowest,
+            prefer_stable: self.prefer_stable,
+            solver_seed: self.solver_seed.or_else(|| {
+                std::env::var("POX_SOLVER_SEED").ok().and_then(|seed| seed.trim().parse().ok())
+            }),
+            update_with_dependencies: self.update_with_dependencies,
+            max_parallel_extractions: config.get_max_parallel_extractions(),
+            download_retries: config.download_retries,
+            bin_compat: BinCompat::parse(&config.bin_compat),
+            installer_paths: InstallerPaths::from_extra(&self.working_dir, &composer_json.extra),
+            notify_on_install: config.notify_on_install,
+            record_file_hashes: config.file_hashes,
+            notify_hosts: composer_json
+                .repositories
+                .as_vec()
+                .into_iter()
+                .filter_map(|repo| match repo {
+                    crate::json::Repository::Composer { url, notify_on_install: true, .. } => {
+                        url::Url::parse(&url).ok()?.host_str().map(str::to_lowercase)
+                    }
+                    _ => None,
+                })
+                .collect(),
         }
     }
 }
@@ -282,11 +461,17 @@ impl Clone for ComposerBuilder {
             http_client: self.http_client.clone(),
             repository_manager: None, // RepositoryManager doesn't implement Clone
             additional_repositories: self.additional_repositories.clone(),
+            io: self.io.clone(),
+            profiler: self.profiler.clone(),
             prefer_source: self.prefer_source,
             prefer_dist: self.prefer_dist,
             dry_run: self.dry_run,
             no_dev: self.no_dev,
+            no_verify: self.no_verify,
             prefer_lowest: self.prefer_lowest,
+            prefer_stable: self.prefer_stable,
+            solver_seed: self.solver_seed,
+            update_with_dependencies: self.update_with_dependencies,
             platform_packages: self.platform_packages.clone(),
             disable_packagist: self.disable_packagist,
         }
@@ -336,6 +521,9 @@ mod tests {
         assert!(result.is_ok());
         let composer = result.unwrap();
         assert_eq!(composer.working_dir, working_dir);
+        // Embedders get no console output unless they ask for it
+        assert_eq!(composer.io.verbosity(), crate::io::Verbosity::Quiet);
+        assert!(!composer.io.is_interactive());
     }
 
     #[test]
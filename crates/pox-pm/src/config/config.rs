@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
+use pox_spdx::LicenseUsage;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use crate::error::Result;
+use crate::http::ProxyConfig;
 use super::source::{ConfigLoader, ConfigSource, RawConfig};
 
 /// Preferred installation method
@@ -113,6 +115,27 @@ impl PlatformCheck {
     }
 }
 
+/// What to do when an installed package violates the license policy
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LicensePolicyMode {
+    Off,
+    #[default]
+    Warn,
+    Fail,
+}
+
+impl LicensePolicyMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "off" => Some(LicensePolicyMode::Off),
+            "warn" => Some(LicensePolicyMode::Warn),
+            "fail" => Some(LicensePolicyMode::Fail),
+            _ => None,
+        }
+    }
+}
+
 /// Plugin allowlist configuration
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -130,15 +153,21 @@ impl Default for AllowPlugins {
 /// Audit configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditConfig {
+    /// Advisory IDs or CVEs that are not reported
     #[serde(default)]
     pub ignore: Vec<String>,
 
+    /// Handling of abandoned packages: `ignore`, `report` or `fail`
     #[serde(default = "default_audit_abandoned")]
     pub abandoned: String,
+
+    /// Advisory severities that are not reported, e.g. `low`
+    #[serde(rename = "ignore-severity", default)]
+    pub ignore_severity: Vec<String>,
 }
 
 fn default_audit_abandoned() -> String {
-    "fail".to_string()
+    "report".to_string()
 }
 
 impl Default for AuditConfig {
@@ -146,10 +175,19 @@ impl Default for AuditConfig {
         AuditConfig {
             ignore: Vec::new(),
             abandoned: default_audit_abandoned(),
+            ignore_severity: Vec::new(),
         }
     }
 }
 
+impl AuditConfig {
+    /// Whether an advisory is silenced by `ignore` or `ignore-severity`
+    pub fn is_ignored(&self, advisory_id: &str, cve: Option<&str>, severity: Option<&str>) -> bool {
+        self.ignore.iter().any(|id| id == advisory_id || Some(id.as_str()) == cve)
+            || severity.is_some_and(|s| self.ignore_severity.iter().any(|i| i.eq_ignore_ascii_case(s)))
+    }
+}
+
 /// HTTP Basic authentication credentials
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpBasicAuth {
@@ -223,6 +261,12 @@ pub struct Config {
     #[serde(rename = "process-timeout", default = "default_process_timeout")]
     pub process_timeout: u64,
 
+    #[serde(rename = "max-parallel-extractions", skip_serializing_if = "Option::is_none")]
+    pub max_parallel_extractions: Option<u64>,
+
+    #[serde(rename = "download-retries", default = "default_download_retries")]
+    pub download_retries: u32,
+
     #[serde(rename = "use-include-path", default)]
     pub use_include_path: bool,
 
@@ -232,6 +276,10 @@ pub struct Config {
     #[serde(rename = "preferred-install", default)]
     pub preferred_install: PreferredInstall,
 
+    /// Per-package `preferred-install` overrides, in declaration order (`{"vendor/*": "source"}`)
+    #[serde(skip)]
+    pub preferred_install_patterns: Vec<(String, PreferredInstall)>,
+
     #[serde(rename = "store-auths", default)]
     pub store_auths: StoreAuths,
 
@@ -241,6 +289,10 @@ pub struct Config {
     #[serde(rename = "discard-changes", default)]
     pub discard_changes: DiscardChanges,
 
+    /// Record file hashes of installed packages so `reinstall` can detect local modifications
+    #[serde(rename = "file-hashes", default)]
+    pub file_hashes: bool,
+
     #[serde(rename = "optimize-autoloader", default)]
     pub optimize_autoloader: bool,
 
@@ -271,6 +323,23 @@ pub struct Config {
     #[serde(default)]
     pub audit: AuditConfig,
 
+    // License policy
+    #[serde(rename = "allowed-licenses", default)]
+    pub allowed_licenses: Vec<String>,
+
+    #[serde(rename = "denied-licenses", default)]
+    pub denied_licenses: Vec<String>,
+
+    #[serde(rename = "license-policy", default)]
+    pub license_policy: LicensePolicyMode,
+
+    /// Check dependency licenses against the root license, off unless configured
+    #[serde(rename = "license-compatibility", default = "default_license_compatibility")]
+    pub license_compatibility: LicensePolicyMode,
+
+    #[serde(rename = "license-usage", default)]
+    pub license_usage: LicenseUsage,
+
     // Network - Security
     #[serde(rename = "secure-http", default = "default_true")]
     pub secure_http: bool,
@@ -287,6 +356,10 @@ pub struct Config {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub capath: Option<PathBuf>,
 
+    /// Proxy settings, `http_proxy`/`https_proxy`/`no_proxy` take precedence
+    #[serde(default, skip_serializing_if = "ProxyConfig::is_empty")]
+    pub proxy: ProxyConfig,
+
     // Network - Protocols
     #[serde(rename = "github-protocols", default = "default_github_protocols")]
     pub github_protocols: Vec<String>,
@@ -384,6 +457,10 @@ fn default_process_timeout() -> u64 {
     300
 }
 
+fn default_download_retries() -> u32 {
+    crate::downloader::DEFAULT_DOWNLOAD_RETRIES
+}
+
 fn default_cache_ttl() -> u64 {
     15552000 // 6 months in seconds
 }
@@ -392,6 +469,24 @@ fn default_cache_files_maxsize() -> u64 {
     300 * 1024 * 1024 // 300 MiB
 }
 
+/// Parse a size like `300MiB`, `1G` or `512k` into bytes
+fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim().to_lowercase();
+    let split = value.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+
+    let multiplier = match unit.trim().trim_end_matches('b').trim_end_matches('i') {
+        "" => 1,
+        "k" => 1024,
+        "m" => 1024 * 1024,
+        "g" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+
+    Some((number * multiplier as f64) as u64)
+}
+
 fn default_github_protocols() -> Vec<String> {
     vec!["https".to_string(), "ssh".to_string(), "git".to_string()]
 }
@@ -424,6 +519,10 @@ fn default_true() -> bool {
     true
 }
 
+fn default_license_compatibility() -> LicensePolicyMode {
+    LicensePolicyMode::Off
+}
+
 impl Default for Config {
     fn default() -> Self {
         Config {
@@ -443,12 +542,16 @@ impl Default for Config {
 
             // Behavior
             process_timeout: default_process_timeout(),
+            max_parallel_extractions: None,
+            download_retries: default_download_retries(),
             use_include_path: false,
             use_parent_dir: Some("prompt".to_string()),
             preferred_install: PreferredInstall::default(),
+            preferred_install_patterns: Vec::new(),
             store_auths: StoreAuths::default(),
             notify_on_install: true,
             discard_changes: DiscardChanges::default(),
+            file_hashes: false,
             optimize_autoloader: false,
             sort_packages: false,
             classmap_authoritative: false,
@@ -460,12 +563,20 @@ impl Default for Config {
             allow_plugins: AllowPlugins::default(),
             audit: AuditConfig::default(),
 
+            // License policy
+            allowed_licenses: Vec::new(),
+            denied_licenses: Vec::new(),
+            license_policy: LicensePolicyMode::default(),
+            license_compatibility: default_license_compatibility(),
+            license_usage: LicenseUsage::default(),
+
             // Network - Security
             secure_http: true,
             disable_tls: false,
             secure_svn_domains: Vec::new(),
             cafile: None,
             capath: None,
+            proxy: ProxyConfig::default(),
 
             // Network - Protocols
             github_protocols: default_github_protocols(),
@@ -573,6 +684,40 @@ impl Config {
         self.sources.get(key)
     }
 
+    /// Get all configuration values keyed by their composer.json name
+    pub fn to_values(&self) -> serde_json::Map<String, serde_json::Value> {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(values)) => values,
+            _ => serde_json::Map::new(),
+        }
+    }
+
+    /// Get a configuration value by key, nested values are addressed as `platform.php`
+    pub fn get_value(&self, key: &str) -> Option<serde_json::Value> {
+        let (head, rest) = match key.split_once('.') {
+            Some((head, rest)) => (head, Some(rest)),
+            None => (key, None),
+        };
+
+        let value = self.to_values().remove(head)?;
+        match rest {
+            Some(rest) => value.get(rest).cloned(),
+            None => Some(value),
+        }
+    }
+
+    /// TTL in seconds for cached dist archives, falls back to `cache-ttl`
+    pub fn get_cache_files_ttl(&self) -> u64 {
+        self.cache_files_ttl.unwrap_or(self.cache_ttl)
+    }
+
+    /// Number of archives extracted at once, defaults to the number of CPUs
+    pub fn get_max_parallel_extractions(&self) -> usize {
+        self.max_parallel_extractions
+            .map(|n| n as usize)
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(4, |n| n.get()))
+    }
+
     /// Get vendor directory (resolved as absolute path)
     pub fn get_vendor_dir(&self) -> PathBuf {
         self.resolve_path(&self.vendor_dir)
@@ -648,6 +793,25 @@ impl Config {
                     self.sources.insert(key.to_string(), source);
                 }
             }
+            "cache-ttl" => {
+                if let Some(n) = value.as_u64() {
+                    self.cache_ttl = n;
+                    self.sources.insert(key.to_string(), source);
+                }
+            }
+            "cache-files-ttl" => {
+                if let Some(n) = value.as_u64() {
+                    self.cache_files_ttl = Some(n);
+                    self.sources.insert(key.to_string(), source);
+                }
+            }
+            "cache-files-maxsize" => {
+                let size = value.as_u64().or_else(|| value.as_str().and_then(parse_size));
+                if let Some(n) = size {
+                    self.cache_files_maxsize = n;
+                    self.sources.insert(key.to_string(), source);
+                }
+            }
             "data-dir" => {
                 if let Some(s) = value.as_str() {
                     self.data_dir = Some(PathBuf::from(s));
@@ -660,6 +824,18 @@ impl Config {
                     self.sources.insert(key.to_string(), source);
                 }
             }
+            "max-parallel-extractions" => {
+                if let Some(n) = value.as_u64().filter(|n| *n > 0) {
+                    self.max_parallel_extractions = Some(n);
+                    self.sources.insert(key.to_string(), source);
+                }
+            }
+            "download-retries" => {
+                if let Some(n) = value.as_u64().and_then(|n| u32::try_from(n).ok()) {
+                    self.download_retries = n;
+                    self.sources.insert(key.to_string(), source);
+                }
+            }
             "use-include-path" => {
                 if let Some(b) = value.as_bool() {
                     self.use_include_path = b;
@@ -670,8 +846,21 @@ impl Config {
                 if let Some(s) = value.as_str() {
                     if let Some(pi) = PreferredInstall::from_str(s) {
                         self.preferred_install = pi;
+                        self.preferred_install_patterns.clear();
                         self.sources.insert(key.to_string(), source);
                     }
+                } else if let Some(obj) = value.as_object() {
+                    let mut patterns = Vec::new();
+                    for (pattern, v) in obj {
+                        if let Some(pi) = v.as_str().and_then(PreferredInstall::from_str) {
+                            if pattern == "*" {
+                                self.preferred_install = pi.clone();
+                            }
+                            patterns.push((pattern.clone(), pi));
+                        }
+                    }
+                    self.preferred_install_patterns = patterns;
+                    self.sources.insert(key.to_string(), source);
                 }
             }
             "store-auths" => {
@@ -688,6 +877,12 @@ impl Config {
                     self.sources.insert(key.to_string(), source);
                 }
             }
+            "file-hashes" => {
+                if let Some(b) = value.as_bool() {
+                    self.file_hashes = b;
+                    self.sources.insert(key.to_string(), source);
+                }
+            }
             "discard-changes" => {
                 if let Some(s) = value.as_str() {
                     if let Some(dc) = DiscardChanges::from_str(s) {
@@ -729,6 +924,12 @@ impl Config {
                     self.sources.insert(key.to_string(), source);
                 }
             }
+            "proxy" => {
+                if let Some(proxy) = ProxyConfig::from_config_value(&value) {
+                    self.proxy = proxy;
+                    self.sources.insert(key.to_string(), source);
+                }
+            }
             "disable-tls" => {
                 if let Some(b) = value.as_bool() {
                     self.disable_tls = b;
@@ -752,6 +953,48 @@ impl Config {
                     self.sources.insert(key.to_string(), source);
                 }
             }
+            "allowed-licenses" => {
+                if let Some(arr) = value.as_array() {
+                    self.allowed_licenses = arr
+                        .iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect();
+                    self.sources.insert(key.to_string(), source);
+                }
+            }
+            "denied-licenses" => {
+                if let Some(arr) = value.as_array() {
+                    self.denied_licenses = arr
+                        .iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect();
+                    self.sources.insert(key.to_string(), source);
+                }
+            }
+            "license-policy" => {
+                if let Some(s) = value.as_str() {
+                    if let Some(mode) = LicensePolicyMode::parse(s) {
+                        self.license_policy = mode;
+                        self.sources.insert(key.to_string(), source);
+                    }
+                }
+            }
+            "license-compatibility" => {
+                if let Some(s) = value.as_str() {
+                    if let Some(mode) = LicensePolicyMode::parse(s) {
+                        self.license_compatibility = mode;
+                        self.sources.insert(key.to_string(), source);
+                    }
+                }
+            }
+            "license-usage" => {
+                if let Some(s) = value.as_str() {
+                    if let Some(usage) = LicenseUsage::parse(s) {
+                        self.license_usage = usage;
+                        self.sources.insert(key.to_string(), source);
+                    }
+                }
+            }
             "github-protocols" => {
                 if let Some(arr) = value.as_array() {
                     self.github_protocols = arr
@@ -794,13 +1037,43 @@ impl Config {
             "platform" => {
                 if let Some(obj) = value.as_object() {
                     for (k, v) in obj {
-                        if let Some(s) = v.as_str() {
-                            self.platform.insert(k.clone(), s.to_string());
+                        // `false` hides a detected platform package
+                        match v {
+                            serde_json::Value::String(s) => {
+                                self.platform.insert(k.clone(), s.clone());
+                            }
+                            serde_json::Value::Bool(false) => {
+                                self.platform.insert(k.clone(), "false".to_string());
+                            }
+                            _ => {}
                         }
                     }
                     self.sources.insert(key.to_string(), source);
                 }
             }
+            "audit" => {
+                if let Some(obj) = value.as_object() {
+                    // `ignore` is a list of IDs or a map of IDs to the reason they are ignored
+                    match obj.get("ignore") {
+                        Some(serde_json::Value::Array(ids)) => {
+                            self.audit.ignore = ids.iter().filter_map(|v| v.as_str().map(String::from)).collect();
+                        }
+                        Some(serde_json::Value::Object(ids)) => {
+                            self.audit.ignore = ids.keys().cloned().collect();
+                        }
+                        _ => {}
+                    }
+                    if let Some(abandoned) = obj.get("abandoned").and_then(|v| v.as_str()) {
+                        if matches!(abandoned, "ignore" | "report" | "fail") {
+                            self.audit.abandoned = abandoned.to_string();
+                        }
+                    }
+                    if let Some(severities) = obj.get("ignore-severity").and_then(|v| v.as_array()) {
+                        self.audit.ignore_severity = severities.iter().filter_map(|v| v.as_str().map(str::to_lowercase)).collect();
+                    }
+                    self.sources.insert(key.to_string(), source);
+                }
+            }
             "github-oauth" => {
                 if let Some(obj) = value.as_object() {
                     for (k, v) in obj {
@@ -832,6 +1105,16 @@ impl Config {
 
     /// Apply environment variable overrides
     fn apply_env_overrides(&mut self, loader: &ConfigLoader) {
+        // Proxy
+        let env_proxy = ProxyConfig::from_env();
+        if env_proxy != ProxyConfig::default() {
+            self.proxy = env_proxy.or(std::mem::take(&mut self.proxy));
+            self.sources.insert(
+                "proxy".to_string(),
+                ConfigSource::Environment("HTTP_PROXY".to_string()),
+            );
+        }
+
         // Process timeout
         if let Some(timeout) = loader.get_env_u64("process-timeout") {
             self.process_timeout = timeout;
@@ -868,6 +1151,45 @@ impl Config {
             );
         }
 
+        // Remaining directories and certificate paths
+        for key in ["data-dir", "cache-files-dir", "cache-repo-dir", "cache-vcs-dir", "cafile", "capath"] {
+            let Some(path) = loader.get_env_path(key) else {
+                continue;
+            };
+            match key {
+                "data-dir" => self.data_dir = Some(path),
+                "cache-files-dir" => self.cache_files_dir = Some(path),
+                "cache-repo-dir" => self.cache_repo_dir = Some(path),
+                "cache-vcs-dir" => self.cache_vcs_dir = Some(path),
+                "cafile" => self.cafile = Some(path),
+                _ => self.capath = Some(path),
+            }
+            self.sources.insert(
+                key.to_string(),
+                ConfigSource::Environment(ConfigLoader::env_var_name(key)),
+            );
+        }
+
+        // Composer home
+        if loader.get_composer_env("COMPOSER_HOME").is_some() {
+            self.home_dir = Some(loader.get_composer_home());
+            self.sources.insert(
+                "home".to_string(),
+                ConfigSource::Environment("COMPOSER_HOME".to_string()),
+            );
+        }
+
+        // Audit abandoned packages
+        if let Some(abandoned) = loader.get_composer_env("COMPOSER_AUDIT_ABANDONED") {
+            if matches!(abandoned.as_str(), "ignore" | "report" | "fail") {
+                self.audit.abandoned = abandoned;
+                self.sources.insert(
+                    "audit".to_string(),
+                    ConfigSource::Environment("COMPOSER_AUDIT_ABANDONED".to_string()),
+                );
+            }
+        }
+
         // Discard changes
         if let Some(discard) = loader.get_env_config("discard-changes") {
             if let Some(dc) = DiscardChanges::from_str(&discard) {
@@ -936,21 +1258,33 @@ impl Config {
             "vendor-dir".to_string(),
             "bin-dir".to_string(),
             "cache-dir".to_string(),
+            "cache-ttl".to_string(),
+            "cache-files-ttl".to_string(),
+            "cache-files-maxsize".to_string(),
             "data-dir".to_string(),
             "process-timeout".to_string(),
+            "max-parallel-extractions".to_string(),
+            "download-retries".to_string(),
             "use-include-path".to_string(),
             "preferred-install".to_string(),
             "store-auths".to_string(),
             "notify-on-install".to_string(),
             "discard-changes".to_string(),
+            "file-hashes".to_string(),
             "optimize-autoloader".to_string(),
             "sort-packages".to_string(),
             "classmap-authoritative".to_string(),
             "apcu-autoloader".to_string(),
             "secure-http".to_string(),
             "disable-tls".to_string(),
+            "proxy".to_string(),
             "lock".to_string(),
             "platform-check".to_string(),
+            "allowed-licenses".to_string(),
+            "denied-licenses".to_string(),
+            "license-policy".to_string(),
+            "license-compatibility".to_string(),
+            "license-usage".to_string(),
             "github-protocols".to_string(),
             "github-domains".to_string(),
             "gitlab-domains".to_string(),
@@ -969,6 +1303,7 @@ mod tests {
         assert_eq!(config.bin_dir, PathBuf::from("vendor/bin"));
         assert_eq!(config.process_timeout, 300);
         assert!(config.secure_http);
+        assert!(!config.file_hashes);
         assert!(!config.disable_tls);
         assert_eq!(config.preferred_install, PreferredInstall::Dist);
         assert_eq!(config.store_auths, StoreAuths::Prompt);
@@ -1006,6 +1341,182 @@ mod tests {
         assert_eq!(PlatformCheck::from_str("invalid"), None);
     }
 
+    #[test]
+    fn test_license_policy_mode_from_str() {
+        assert_eq!(LicensePolicyMode::parse("warn"), Some(LicensePolicyMode::Warn));
+        assert_eq!(LicensePolicyMode::parse("FAIL"), Some(LicensePolicyMode::Fail));
+        assert_eq!(LicensePolicyMode::parse("off"), Some(LicensePolicyMode::Off));
+        assert_eq!(LicensePolicyMode::parse("invalid"), None);
+    }
+
+    #[test]
+    fn test_merge_license_policy() {
+        let mut config = Config::default();
+        config
+            .merge_config_value("allowed-licenses", serde_json::json!(["MIT", "BSD-3-Clause"]), ConfigSource::Project)
+            .unwrap();
+        config
+            .merge_config_value("license-policy", serde_json::json!("fail"), ConfigSource::Project)
+            .unwrap();
+
+        assert_eq!(config.allowed_licenses, vec!["MIT".to_string(), "BSD-3-Clause".to_string()]);
+        assert!(config.denied_licenses.is_empty());
+        assert_eq!(config.license_policy, LicensePolicyMode::Fail);
+    }
+
+    #[test]
+    fn test_merge_license_compatibility() {
+        let mut config = Config::default();
+        assert_eq!(config.license_compatibility, LicensePolicyMode::Off);
+        assert_eq!(config.license_usage, LicenseUsage::Distribute);
+
+        config
+            .merge_config_value("license-compatibility", serde_json::json!("warn"), ConfigSource::Project)
+            .unwrap();
+        config
+            .merge_config_value("license-usage", serde_json::json!("network"), ConfigSource::Project)
+            .unwrap();
+
+        assert_eq!(config.license_compatibility, LicensePolicyMode::Warn);
+        assert_eq!(config.license_usage, LicenseUsage::Network);
+    }
+
+    #[test]
+    fn test_merge_audit() {
+        let mut config = Config::default();
+        config
+            .merge_config_value(
+                "audit",
+                serde_json::json!({
+                    "ignore": {"PKSA-1234": "not exploitable", "CVE-2024-0001": "mitigated"},
+                    "abandoned": "report",
+                    "ignore-severity": ["Low"]
+                }),
+                ConfigSource::Project,
+            )
+            .unwrap();
+
+        assert_eq!(config.audit.abandoned, "report");
+        assert!(config.audit.is_ignored("PKSA-1234", None, Some("high")));
+        assert!(config.audit.is_ignored("PKSA-9999", Some("CVE-2024-0001"), None));
+        assert!(config.audit.is_ignored("PKSA-9999", None, Some("low")));
+        assert!(!config.audit.is_ignored("PKSA-9999", None, Some("critical")));
+
+        assert_eq!(Config::default().audit.abandoned, "report");
+
+        // Unknown policies keep the previous value
+        config
+            .merge_config_value("audit", serde_json::json!({"abandoned": "maybe"}), ConfigSource::Project)
+            .unwrap();
+        assert_eq!(config.audit.abandoned, "report");
+    }
+
+    #[test]
+    fn test_merge_proxy() {
+        let mut config = Config::default();
+        config
+            .merge_config_value(
+                "proxy",
+                serde_json::json!({"https": "http://proxy:3128", "no-proxy": "localhost,.internal"}),
+                ConfigSource::Global,
+            )
+            .unwrap();
+
+        assert_eq!(config.proxy.https.as_deref(), Some("http://proxy:3128"));
+        assert_eq!(config.proxy.no_proxy, vec!["localhost".to_string(), ".internal".to_string()]);
+        assert_eq!(config.get_source("proxy"), Some(&ConfigSource::Global));
+    }
+
+    #[test]
+    fn test_merge_preferred_install_patterns() {
+        let mut config = Config::default();
+        config
+            .merge_config_value(
+                "preferred-install",
+                serde_json::json!({"my-org/*": "source", "*": "dist"}),
+                ConfigSource::Project,
+            )
+            .unwrap();
+
+        assert_eq!(
+            config.preferred_install_patterns,
+            vec![
+                ("my-org/*".to_string(), PreferredInstall::Source),
+                ("*".to_string(), PreferredInstall::Dist),
+            ]
+        );
+        assert_eq!(config.preferred_install, PreferredInstall::Dist);
+
+        config
+            .merge_config_value("preferred-install", serde_json::json!("source"), ConfigSource::Project)
+            .unwrap();
+        assert!(config.preferred_install_patterns.is_empty());
+        assert_eq!(config.preferred_install, PreferredInstall::Source);
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Some(1024));
+        assert_eq!(parse_size("512k"), Some(512 * 1024));
+        assert_eq!(parse_size("300MiB"), Some(300 * 1024 * 1024));
+        assert_eq!(parse_size("1.5G"), Some(1536 * 1024 * 1024));
+        assert_eq!(parse_size("10 MB"), Some(10 * 1024 * 1024));
+        assert_eq!(parse_size("lots"), None);
+        assert_eq!(parse_size("5T"), None);
+    }
+
+    #[test]
+    fn test_merge_cache_settings() {
+        let mut config = Config::default();
+        assert_eq!(config.get_cache_files_ttl(), 15552000);
+
+        config
+            .merge_config_value("cache-ttl", serde_json::json!(86400), ConfigSource::Project)
+            .unwrap();
+        assert_eq!(config.get_cache_files_ttl(), 86400);
+
+        config
+            .merge_config_value("cache-files-ttl", serde_json::json!(3600), ConfigSource::Project)
+            .unwrap();
+        config
+            .merge_config_value("cache-files-maxsize", serde_json::json!("1GiB"), ConfigSource::Project)
+            .unwrap();
+        assert_eq!(config.get_cache_files_ttl(), 3600);
+        assert_eq!(config.cache_files_maxsize, 1024 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_max_parallel_extractions() {
+        let mut config = Config::default();
+        assert!(config.get_max_parallel_extractions() >= 1);
+
+        config
+            .merge_config_value("max-parallel-extractions", serde_json::json!(0), ConfigSource::Project)
+            .unwrap();
+        assert_eq!(config.max_parallel_extractions, None);
+
+        config
+            .merge_config_value("max-parallel-extractions", serde_json::json!(2), ConfigSource::Project)
+            .unwrap();
+        assert_eq!(config.get_max_parallel_extractions(), 2);
+    }
+
+    #[test]
+    fn test_download_retries() {
+        let mut config = Config::default();
+        assert_eq!(config.download_retries, 3);
+
+        config
+            .merge_config_value("download-retries", serde_json::json!(0), ConfigSource::Project)
+            .unwrap();
+        assert_eq!(config.download_retries, 0);
+
+        config
+            .merge_config_value("download-retries", serde_json::json!(-1), ConfigSource::Project)
+            .unwrap();
+        assert_eq!(config.download_retries, 0);
+    }
+
     #[test]
     fn test_config_with_base_dir() {
         let config = Config::with_base_dir("/path/to/project");
@@ -1023,4 +1534,18 @@ mod tests {
         let resolved = config.resolve_path(&PathBuf::from("/absolute/path"));
         assert_eq!(resolved, PathBuf::from("/absolute/path"));
     }
+    #[test]
+    fn test_get_value() {
+        let mut config = Config::default();
+        config
+            .merge_config_value("platform", serde_json::json!({"php": "8.2.0"}), ConfigSource::Project)
+            .unwrap();
+
+        assert_eq!(config.get_value("sort-packages"), Some(serde_json::json!(false)));
+        assert_eq!(config.get_value("vendor-dir"), Some(serde_json::json!("vendor")));
+        assert_eq!(config.get_value("platform.php"), Some(serde_json::json!("8.2.0")));
+        assert_eq!(config.get_value("platform.ext-intl"), None);
+        assert_eq!(config.get_value("does-not-exist"), None);
+        assert!(config.to_values().contains_key("process-timeout"));
+    }
 }
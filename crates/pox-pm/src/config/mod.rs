@@ -11,6 +11,14 @@
 //! 3. Global `~/.composer/config.json`
 //! 4. Built-in defaults
 //!
+//! Environment variables follow Composer: `COMPOSER_VENDOR_DIR`, `COMPOSER_BIN_DIR`,
+//! `COMPOSER_CACHE_DIR`, `COMPOSER_PROCESS_TIMEOUT` and friends override the matching
+//! config keys, `COMPOSER_HOME` moves the global config and `COMPOSER` selects another
+//! manifest (`composer-other.json` with `composer-other.lock`). `COMPOSER_MEMORY_LIMIT`
+//! is passed to `@php` scripts. The xdebug variables (`COMPOSER_ALLOW_XDEBUG`,
+//! `COMPOSER_DISABLE_XDEBUG_WARN`) need no equivalent as the embedded PHP never
+//! loads xdebug on its own.
+//!
 //! # Authentication
 //!
 //! Authentication credentials are loaded from `auth.json` files:
@@ -42,11 +50,16 @@
 
 mod auth;
 mod config;
+mod setting;
 mod source;
 
-pub use auth::{AuthConfig, AuthMatch, BitbucketOAuthCredentials, GitLabAuth, HttpBasicCredentials};
+pub use auth::{
+    split_url_credentials, AuthConfig, AuthMatch, BitbucketOAuthCredentials, GitLabAuth,
+    HttpBasicCredentials,
+};
 pub use config::{
     AllowPlugins, AuditConfig, BitbucketOAuth, Config, DiscardChanges, GitLabToken,
-    HttpBasicAuth, PlatformCheck, PreferredInstall, StoreAuths,
+    HttpBasicAuth, LicensePolicyMode, PlatformCheck, PreferredInstall, StoreAuths,
 };
-pub use source::{ConfigLoader, ConfigSource, RawConfig};
+pub use setting::{is_auth_key, ConfigSetting, SettingTarget};
+pub use source::{composer_file_name, lock_file_name, ConfigLoader, ConfigSource, RawConfig};
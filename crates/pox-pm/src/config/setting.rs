@@ -0,0 +1,468 @@
+//! Parsing of `config` command settings.
+//!
+//! Turns a setting key and its command line values into the JSON path and
+//! value that get written to composer.json, config.json or auth.json.
+
+use serde_json::{json, Value};
+
+use crate::error::{ComposerError, Result};
+
+const BOOLEAN_KEYS: &[&str] = &[
+    "use-include-path",
+    "notify-on-install",
+    "file-hashes",
+    "optimize-autoloader",
+    "sort-packages",
+    "classmap-authoritative",
+    "apcu-autoloader",
+    "prepend-autoloader",
+    "secure-http",
+    "disable-tls",
+    "lock",
+    "cache-read-only",
+    "use-github-api",
+    "github-expose-hostname",
+    "bitbucket-expose-hostname",
+    "htaccess-protect",
+];
+
+const INTEGER_KEYS: &[&str] = &[
+    "cache-ttl",
+    "cache-files-ttl",
+    "process-timeout",
+    "max-parallel-extractions",
+    "download-retries",
+];
+
+const STRING_KEYS: &[&str] = &[
+    "vendor-dir",
+    "bin-dir",
+    "cache-dir",
+    "cache-files-dir",
+    "cache-repo-dir",
+    "cache-vcs-dir",
+    "data-dir",
+    "archive-dir",
+    "archive-format",
+    "autoloader-suffix",
+    "cafile",
+    "capath",
+    "gitlab-protocol",
+    "use-parent-dir",
+];
+
+const LIST_KEYS: &[&str] = &[
+    "github-protocols",
+    "github-domains",
+    "gitlab-domains",
+    "bitbucket-domains",
+    "forgejo-domains",
+    "secure-svn-domains",
+    "allowed-licenses",
+    "denied-licenses",
+];
+
+/// Settings that are stored in auth.json rather than the config section.
+const AUTH_KEYS: &[&str] = &[
+    "github-oauth",
+    "gitlab-oauth",
+    "gitlab-token",
+    "bearer",
+    "http-basic",
+    "bitbucket-oauth",
+    "forgejo-token",
+];
+
+const ROOT_KEYS: &[&str] = &[
+    "name",
+    "type",
+    "description",
+    "homepage",
+    "version",
+    "license",
+    "minimum-stability",
+    "prefer-stable",
+];
+
+/// Where a setting is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingTarget {
+    /// The `config` section of composer.json or the global config.json
+    Config,
+    /// A named entry of the `repositories` section
+    Repository,
+    /// auth.json
+    Auth,
+    /// A root property of composer.json such as `name` or `extra.*`
+    Root,
+}
+
+/// A parsed setting key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigSetting {
+    pub target: SettingTarget,
+    /// JSON path of the setting inside its file, e.g. `["config", "platform", "php"]`
+    pub path: Vec<String>,
+}
+
+impl ConfigSetting {
+    /// Parse a setting key like `sort-packages`, `platform.php`,
+    /// `repositories.foo` or `github-oauth.github.com`.
+    pub fn parse(key: &str) -> Result<Self> {
+        let (head, rest) = match key.split_once('.') {
+            Some((head, rest)) => (head, Some(rest)),
+            None => (key, None),
+        };
+
+        let setting = match (head, rest) {
+            ("repositories" | "repos", Some(name)) if !name.is_empty() => Self {
+                target: SettingTarget::Repository,
+                path: vec!["repositories".to_string(), name.to_string()],
+            },
+            (head, Some(domain)) if AUTH_KEYS.contains(&head) && !domain.is_empty() => Self {
+                target: SettingTarget::Auth,
+                path: vec![head.to_string(), domain.to_string()],
+            },
+            ("extra" | "suggest", Some(rest)) if !rest.is_empty() => Self {
+                target: SettingTarget::Root,
+                path: std::iter::once(head)
+                    .chain(if head == "extra" { rest.split('.').collect() } else { vec![rest] })
+                    .map(String::from)
+                    .collect(),
+            },
+            (head, None) if ROOT_KEYS.contains(&head) => Self {
+                target: SettingTarget::Root,
+                path: vec![head.to_string()],
+            },
+            ("platform" | "preferred-install" | "allow-plugins" | "audit", Some(rest)) if !rest.is_empty() => Self {
+                target: SettingTarget::Config,
+                path: vec!["config".to_string(), head.to_string(), rest.to_string()],
+            },
+            (head, None) if is_config_key(head) => Self {
+                target: SettingTarget::Config,
+                path: vec!["config".to_string(), head.to_string()],
+            },
+            _ => {
+                return Err(ComposerError::Config(format!(
+                    "Setting {} does not exist or is not supported by this command",
+                    key
+                )))
+            }
+        };
+
+        Ok(setting)
+    }
+
+    /// The key as used in the config section, e.g. `platform.php`.
+    pub fn config_key(&self) -> String {
+        let start = usize::from(self.target == SettingTarget::Config);
+        self.path[start..].join(".")
+    }
+
+    /// Validate command line values and turn them into the JSON value to store.
+    pub fn value(&self, values: &[String]) -> Result<Value> {
+        if values.is_empty() {
+            return Err(ComposerError::Config(format!(
+                "You must pass a value for {}",
+                self.config_key()
+            )));
+        }
+
+        match self.target {
+            SettingTarget::Repository => repository_value(values),
+            SettingTarget::Auth => auth_value(&self.path[0], &self.path[1], values),
+            SettingTarget::Root => root_value(&self.path, values),
+            SettingTarget::Config => config_value(&self.path[1..], values),
+        }
+    }
+}
+
+/// Whether a config key holds credentials that belong in auth.json.
+pub fn is_auth_key(key: &str) -> bool {
+    let head = key.split('.').next().unwrap_or(key);
+    AUTH_KEYS.contains(&head)
+}
+
+fn is_config_key(key: &str) -> bool {
+    BOOLEAN_KEYS.contains(&key)
+        || INTEGER_KEYS.contains(&key)
+        || STRING_KEYS.contains(&key)
+        || LIST_KEYS.contains(&key)
+        || matches!(
+            key,
+            "cache-files-maxsize"
+                | "preferred-install"
+                | "store-auths"
+                | "discard-changes"
+                | "platform-check"
+                | "license-policy"
+                | "license-compatibility"
+                | "license-usage"
+                | "bin-compat"
+                | "allow-plugins"
+                | "proxy"
+        )
+}
+
+fn single<'a>(key: &str, values: &'a [String]) -> Result<&'a str> {
+    match values {
+        [value] => Ok(value),
+        _ => Err(ComposerError::Config(format!(
+            "You can only pass one value for {}. Example: pox pm config {} {}",
+            key, key, values[0]
+        ))),
+    }
+}
+
+fn invalid(key: &str, value: &str) -> ComposerError {
+    ComposerError::Config(format!("\"{}\" is an invalid value for {}", value, key))
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool> {
+    match value {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err(invalid(key, value)),
+    }
+}
+
+fn one_of(key: &str, value: &str, allowed: &[&str]) -> Result<Value> {
+    if allowed.contains(&value) {
+        Ok(bool_or_string(value))
+    } else {
+        Err(invalid(key, value))
+    }
+}
+
+/// `true`/`false` become booleans, anything else stays a string.
+fn bool_or_string(value: &str) -> Value {
+    match value {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        other => Value::String(other.to_string()),
+    }
+}
+
+fn config_value(path: &[String], values: &[String]) -> Result<Value> {
+    let key = path.join(".");
+
+    if let [head, _] = path {
+        let value = single(&key, values)?;
+        return match head.as_str() {
+            // `false` removes a platform package, anything else is a version
+            "platform" if value == "false" => Ok(Value::Bool(false)),
+            "platform" => Ok(json!(value)),
+            "preferred-install" => one_of(&key, value, &["auto", "source", "dist"]),
+            "allow-plugins" => Ok(Value::Bool(parse_bool(&key, value)?)),
+            "audit" => Ok(bool_or_string(value)),
+            _ => Err(invalid(&key, value)),
+        };
+    }
+
+    let key = key.as_str();
+    if LIST_KEYS.contains(&key) {
+        return Ok(json!(values));
+    }
+
+    let value = single(key, values)?;
+    match key {
+        _ if BOOLEAN_KEYS.contains(&key) => Ok(Value::Bool(parse_bool(key, value)?)),
+        _ if INTEGER_KEYS.contains(&key) => value
+            .parse::<u64>()
+            .map(Value::from)
+            .map_err(|_| invalid(key, value)),
+        _ if STRING_KEYS.contains(&key) => Ok(json!(value)),
+        "cache-files-maxsize" => {
+            let valid = value
+                .trim_end_matches(['b', 'B', 'i'])
+                .trim_end_matches(['k', 'K', 'm', 'M', 'g', 'G'])
+                .parse::<f64>()
+                .is_ok();
+            if valid {
+                Ok(json!(value))
+            } else {
+                Err(invalid(key, value))
+            }
+        }
+        "preferred-install" => one_of(key, value, &["auto", "source", "dist"]),
+        "store-auths" => one_of(key, value, &["true", "false", "prompt"]),
+        "discard-changes" => one_of(key, value, &["true", "false", "stash"]),
+        "platform-check" => one_of(key, value, &["true", "false", "php-only"]),
+        "license-policy" | "license-compatibility" => one_of(key, value, &["off", "warn", "fail"]),
+        "license-usage" => one_of(key, value, &["distribute", "network", "internal"]),
+        "bin-compat" => one_of(key, value, &["auto", "full", "proxy", "symlink"]),
+        "allow-plugins" => Ok(Value::Bool(parse_bool(key, value)?)),
+        "proxy" => Ok(json!(value)),
+        _ => Err(invalid(key, value)),
+    }
+}
+
+fn repository_value(values: &[String]) -> Result<Value> {
+    match values {
+        [value] if value == "false" => Ok(Value::Bool(false)),
+        [value] if value.trim_start().starts_with('{') => {
+            let repository: Value = serde_json::from_str(value)?;
+            if repository.get("type").and_then(|t| t.as_str()).is_none() {
+                return Err(ComposerError::Config("A repository must have a type".to_string()));
+            }
+            Ok(repository)
+        }
+        [kind, url] => Ok(json!({"type": kind, "url": url})),
+        _ => Err(ComposerError::Config(
+            "You must pass the type and a url. Example: pox pm config repositories.foo vcs https://bar.com"
+                .to_string(),
+        )),
+    }
+}
+
+fn auth_value(kind: &str, domain: &str, values: &[String]) -> Result<Value> {
+    let key = format!("{}.{}", kind, domain);
+    match (kind, values) {
+        ("http-basic", [username, password]) => Ok(json!({"username": username, "password": password})),
+        ("bitbucket-oauth", [consumer_key, consumer_secret]) => Ok(json!({
+            "consumer-key": consumer_key,
+            "consumer-secret": consumer_secret,
+        })),
+        ("http-basic", _) => Err(ComposerError::Config(format!(
+            "Expected two arguments (username, password) for {}",
+            key
+        ))),
+        ("bitbucket-oauth", _) => Err(ComposerError::Config(format!(
+            "Expected two arguments (consumer-key, consumer-secret) for {}",
+            key
+        ))),
+        (_, [token]) => Ok(json!(token)),
+        _ => Err(ComposerError::Config(format!("Expected one argument (token) for {}", key))),
+    }
+}
+
+fn root_value(path: &[String], values: &[String]) -> Result<Value> {
+    let key = path.join(".");
+    let value = single(&key, values)?;
+
+    match path[0].as_str() {
+        "prefer-stable" => Ok(Value::Bool(parse_bool(&key, value)?)),
+        "minimum-stability" => one_of(&key, value, &["stable", "RC", "beta", "alpha", "dev"]),
+        "name" if !value.contains('/') => Err(invalid(&key, value)),
+        _ => Ok(json!(value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_config_keys() {
+        let setting = ConfigSetting::parse("sort-packages").unwrap();
+        assert_eq!(setting.target, SettingTarget::Config);
+        assert_eq!(setting.path, vec!["config", "sort-packages"]);
+        assert_eq!(setting.config_key(), "sort-packages");
+
+        let setting = ConfigSetting::parse("platform.ext-intl").unwrap();
+        assert_eq!(setting.path, vec!["config", "platform", "ext-intl"]);
+        assert_eq!(setting.config_key(), "platform.ext-intl");
+
+        assert!(ConfigSetting::parse("does-not-exist").is_err());
+        assert!(ConfigSetting::parse("sort-packages.nested").is_err());
+    }
+
+    #[test]
+    fn test_parse_other_targets() {
+        let setting = ConfigSetting::parse("repositories.foo").unwrap();
+        assert_eq!(setting.target, SettingTarget::Repository);
+        assert_eq!(setting.path, vec!["repositories", "foo"]);
+
+        let setting = ConfigSetting::parse("github-oauth.github.com").unwrap();
+        assert_eq!(setting.target, SettingTarget::Auth);
+        assert_eq!(setting.path, vec!["github-oauth", "github.com"]);
+
+        let setting = ConfigSetting::parse("extra.symfony.allow-contrib").unwrap();
+        assert_eq!(setting.target, SettingTarget::Root);
+        assert_eq!(setting.path, vec!["extra", "symfony", "allow-contrib"]);
+
+        let setting = ConfigSetting::parse("description").unwrap();
+        assert_eq!(setting.target, SettingTarget::Root);
+
+        assert!(is_auth_key("http-basic"));
+        assert!(is_auth_key("gitlab-token.gitlab.com"));
+        assert!(!is_auth_key("platform"));
+    }
+
+    #[test]
+    fn test_config_values() {
+        let setting = ConfigSetting::parse("sort-packages").unwrap();
+        assert_eq!(setting.value(&values(&["true"])).unwrap(), json!(true));
+        assert!(setting.value(&values(&["yes"])).is_err());
+
+        let setting = ConfigSetting::parse("process-timeout").unwrap();
+        assert_eq!(setting.value(&values(&["600"])).unwrap(), json!(600));
+        assert!(setting.value(&values(&["ten"])).is_err());
+
+        let setting = ConfigSetting::parse("github-protocols").unwrap();
+        assert_eq!(setting.value(&values(&["https", "ssh"])).unwrap(), json!(["https", "ssh"]));
+
+        let setting = ConfigSetting::parse("discard-changes").unwrap();
+        assert_eq!(setting.value(&values(&["stash"])).unwrap(), json!("stash"));
+        assert_eq!(setting.value(&values(&["true"])).unwrap(), json!(true));
+
+        let setting = ConfigSetting::parse("cache-files-maxsize").unwrap();
+        assert_eq!(setting.value(&values(&["1GiB"])).unwrap(), json!("1GiB"));
+        assert!(setting.value(&values(&["lots"])).is_err());
+
+        let setting = ConfigSetting::parse("vendor-dir").unwrap();
+        assert!(setting.value(&values(&["a", "b"])).is_err());
+        assert!(setting.value(&[]).is_err());
+    }
+
+    #[test]
+    fn test_platform_values() {
+        let setting = ConfigSetting::parse("platform.php").unwrap();
+        assert_eq!(setting.value(&values(&["8.2.0"])).unwrap(), json!("8.2.0"));
+        assert_eq!(setting.value(&values(&["false"])).unwrap(), json!(false));
+    }
+
+    #[test]
+    fn test_repository_values() {
+        let setting = ConfigSetting::parse("repositories.foo").unwrap();
+        assert_eq!(
+            setting.value(&values(&["vcs", "https://bar.com"])).unwrap(),
+            json!({"type": "vcs", "url": "https://bar.com"})
+        );
+        assert_eq!(
+            setting.value(&values(&[r#"{"type": "path", "url": "../lib"}"#])).unwrap(),
+            json!({"type": "path", "url": "../lib"})
+        );
+        assert_eq!(setting.value(&values(&["false"])).unwrap(), json!(false));
+        assert!(setting.value(&values(&[r#"{"url": "../lib"}"#])).is_err());
+        assert!(setting.value(&values(&["vcs"])).is_err());
+    }
+
+    #[test]
+    fn test_auth_values() {
+        let setting = ConfigSetting::parse("http-basic.repo.example.com").unwrap();
+        assert_eq!(
+            setting.value(&values(&["user", "secret"])).unwrap(),
+            json!({"username": "user", "password": "secret"})
+        );
+        assert!(setting.value(&values(&["user"])).is_err());
+
+        let setting = ConfigSetting::parse("github-oauth.github.com").unwrap();
+        assert_eq!(setting.value(&values(&["token"])).unwrap(), json!("token"));
+    }
+
+    #[test]
+    fn test_root_values() {
+        let setting = ConfigSetting::parse("minimum-stability").unwrap();
+        assert_eq!(setting.value(&values(&["dev"])).unwrap(), json!("dev"));
+        assert!(setting.value(&values(&["nightly"])).is_err());
+
+        let setting = ConfigSetting::parse("name").unwrap();
+        assert!(setting.value(&values(&["invalid"])).is_err());
+        assert_eq!(setting.value(&values(&["vendor/name"])).unwrap(), json!("vendor/name"));
+    }
+}
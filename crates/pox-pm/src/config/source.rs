@@ -102,6 +102,29 @@ impl ConfigLoader {
         }
     }
 
+    /// File name of the project manifest, `COMPOSER` overrides `composer.json`
+    pub fn get_composer_file(&self) -> String {
+        self.get_composer_env("COMPOSER")
+            .map(|file| file.trim().to_string())
+            .filter(|file| !file.is_empty())
+            .unwrap_or_else(|| "composer.json".to_string())
+    }
+
+    /// File name of the lock file belonging to the manifest
+    /// (`composer-other.json` uses `composer-other.lock`)
+    pub fn get_lock_file(&self) -> String {
+        let composer_file = self.get_composer_file();
+        match composer_file.strip_suffix(".json") {
+            Some(stem) => format!("{}.lock", stem),
+            None => format!("{}.lock", composer_file),
+        }
+    }
+
+    /// PHP memory limit for scripts from `COMPOSER_MEMORY_LIMIT`
+    pub fn get_memory_limit(&self) -> Option<String> {
+        self.get_composer_env("COMPOSER_MEMORY_LIMIT")
+    }
+
     /// Load configuration from a JSON file
     pub fn load_config_file<P: AsRef<Path>>(&self, path: P) -> Result<RawConfig> {
         let path = path.as_ref();
@@ -129,7 +152,7 @@ impl ConfigLoader {
     /// Load project configuration from composer.json
     pub fn load_project_config<P: AsRef<Path>>(&self, project_dir: P) -> Result<RawConfig> {
         let project_dir = project_dir.as_ref();
-        let composer_json = project_dir.join("composer.json");
+        let composer_json = project_dir.join(self.get_composer_file());
 
         if !composer_json.exists() {
             return Ok(RawConfig::default());
@@ -141,8 +164,12 @@ impl ConfigLoader {
     /// Get a configuration value from environment variable
     /// Converts "foo-bar" to "COMPOSER_FOO_BAR"
     pub fn get_env_config(&self, key: &str) -> Option<String> {
-        let env_var = format!("COMPOSER_{}", key.replace('-', "_").to_uppercase());
-        self.get_composer_env(&env_var)
+        self.get_composer_env(&Self::env_var_name(key))
+    }
+
+    /// Environment variable overriding a config key, e.g. `COMPOSER_VENDOR_DIR`
+    pub fn env_var_name(key: &str) -> String {
+        format!("COMPOSER_{}", key.replace('-', "_").to_uppercase())
     }
 
     /// Get boolean value from environment variable
@@ -171,6 +198,16 @@ impl ConfigLoader {
     }
 }
 
+/// File name of the project manifest, honoring the `COMPOSER` environment variable
+pub fn composer_file_name() -> String {
+    ConfigLoader::new(true).get_composer_file()
+}
+
+/// File name of the project lock file, honoring the `COMPOSER` environment variable
+pub fn lock_file_name() -> String {
+    ConfigLoader::new(true).get_lock_file()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,6 +238,20 @@ mod tests {
         assert!(home.is_absolute() || home.starts_with(".composer"));
     }
 
+    #[test]
+    fn test_env_var_name() {
+        assert_eq!(ConfigLoader::env_var_name("vendor-dir"), "COMPOSER_VENDOR_DIR");
+        assert_eq!(ConfigLoader::env_var_name("cafile"), "COMPOSER_CAFILE");
+    }
+
+    #[test]
+    fn test_composer_file_without_environment() {
+        let loader = ConfigLoader::new(false);
+        assert_eq!(loader.get_composer_file(), "composer.json");
+        assert_eq!(loader.get_lock_file(), "composer.lock");
+        assert_eq!(loader.get_memory_limit(), None);
+    }
+
     #[test]
     fn test_get_cache_dir() {
         let loader = ConfigLoader::new(false);
@@ -1,9 +1,12 @@
 //! Dependency graph analysis for installed packages.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 
+use serde::Serialize;
+
 use crate::package::{Link, LinkType, Package};
+use crate::util::is_platform_package;
 use pox_semver::ConstraintInterface;
 
 #[derive(Debug, Clone)]
@@ -11,6 +14,8 @@ pub struct DependencyResult {
     pub package: Arc<Package>,
     pub link: Link,
     pub children: Option<Vec<DependencyResult>>,
+    /// Whether the lookup stopped here because the package is already in the tree
+    pub circular: bool,
 }
 
 pub fn get_dependents(
@@ -51,6 +56,7 @@ pub fn get_dependents(
                                     package: package.clone(),
                                     link: replace_link.clone(),
                                     children: None,
+                                    circular: true,
                                 });
                                 continue;
                             }
@@ -73,6 +79,7 @@ pub fn get_dependents(
                                 package: package.clone(),
                                 link: replace_link.clone(),
                                 children: Some(dependents),
+                                circular: false,
                             });
                         }
                     }
@@ -95,6 +102,7 @@ pub fn get_dependents(
                                 package: package.clone(),
                                 link: link.clone(),
                                 children: None,
+                                circular: true,
                             });
                             continue;
                         }
@@ -117,6 +125,7 @@ pub fn get_dependents(
                             package: package.clone(),
                             link: link.clone(),
                             children: Some(dependents),
+                            circular: false,
                         });
                     }
                 }
@@ -133,6 +142,7 @@ pub fn get_dependents(
                                 package: package.clone(),
                                 link: conflict_link.clone(),
                                 children: None,
+                                circular: false,
                             });
                         }
                     }
@@ -150,6 +160,7 @@ pub fn get_dependents(
                                 package: package.clone(),
                                 link: conflict_link.clone(),
                                 children: None,
+                                circular: false,
                             });
                         }
                     }
@@ -161,28 +172,48 @@ pub fn get_dependents(
     results
 }
 
+/// An installed package matching a looked up name.
+#[derive(Debug, Clone)]
+pub struct PackageMatch {
+    pub package: Arc<Package>,
+    /// The `replace` or `provide` link through which the package satisfies the name,
+    /// `None` if the package has the name itself
+    pub link: Option<Link>,
+}
+
+impl PackageMatch {
+    /// Whether the name is satisfied through `replace` or `provide`
+    pub fn is_provider(&self) -> bool {
+        self.link.is_some()
+    }
+}
+
+/// Find the installed packages that are, replace or provide `name`.
 pub fn find_packages_with_replacers_and_providers(
     packages: &[Arc<Package>],
     name: &str,
     constraint: Option<&dyn ConstraintInterface>,
-) -> Vec<Arc<Package>> {
+) -> Vec<PackageMatch> {
     let name_lower = name.to_lowercase();
     let mut matches = Vec::new();
 
     for package in packages {
         if package.name.to_lowercase() == name_lower {
             if constraint.is_none() || constraint_matches_version(&constraint.unwrap().to_string(), &package.version) {
-                matches.push(package.clone());
+                matches.push(PackageMatch { package: package.clone(), link: None });
             }
             continue;
         }
 
-        for (target, target_constraint) in package.provide.iter().chain(package.replace.iter()) {
-            if target.to_lowercase() == name_lower {
-                if constraint.is_none() || matches_constraint(target_constraint, constraint, false) {
-                    matches.push(package.clone());
-                    break;
-                }
+        let links = hashmap_to_links(&package.provide, &package.name, LinkType::Provide)
+            .into_iter()
+            .chain(hashmap_to_links(&package.replace, &package.name, LinkType::Replace));
+        for link in links {
+            if link.target.to_lowercase() == name_lower
+                && (constraint.is_none() || matches_constraint(&link.constraint, constraint, false))
+            {
+                matches.push(PackageMatch { package: package.clone(), link: Some(link) });
+                break;
             }
         }
     }
@@ -190,6 +221,358 @@ pub fn find_packages_with_replacers_and_providers(
     matches
 }
 
+/// Explain how a replaced or virtual package is satisfied.
+///
+/// Every providing or replacing package becomes a result linked through its
+/// `provide`/`replace` link, with the packages that depend on the provider as children,
+/// so the output shows the whole chain from the virtual package up to the root.
+pub fn get_provider_dependents(
+    packages: &[Arc<Package>],
+    matches: &[PackageMatch],
+    recurse: bool,
+) -> Vec<DependencyResult> {
+    matches
+        .iter()
+        .filter_map(|m| {
+            let link = m.link.clone()?;
+            let provider = m.package.name.to_lowercase();
+            let in_tree: HashSet<String> = [link.target.to_lowercase(), provider.clone()].into_iter().collect();
+            let dependents = get_dependents(packages, &[provider], None, false, recurse, Some(in_tree));
+
+            Some(DependencyResult {
+                package: m.package.clone(),
+                link,
+                children: Some(dependents),
+                circular: false,
+            })
+        })
+        .collect()
+}
+
+/// One link of a dependency chain, e.g. `a/a 1.0.0 requires c/c ^2.0`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChainLink {
+    pub package: String,
+    pub version: String,
+    #[serde(rename = "type")]
+    pub link_type: String,
+    pub target: String,
+    pub constraint: String,
+}
+
+/// The links leading from a looked up package to a package nothing depends on.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DependencyChain {
+    /// Ordered from the package linking to the looked up package towards the root
+    pub links: Vec<ChainLink>,
+    /// Whether the chain ends at the root package
+    pub root: bool,
+    /// Whether the chain was cut off where it loops back into itself
+    pub circular: bool,
+}
+
+/// Flatten recursive dependency results into one chain per path up the tree.
+///
+/// Results of a non-recursive lookup produce chains of a single link.
+pub fn dependency_chains(results: &[DependencyResult]) -> Vec<DependencyChain> {
+    fn walk(results: &[DependencyResult], path: &mut Vec<ChainLink>, chains: &mut Vec<DependencyChain>) {
+        for result in results {
+            path.push(ChainLink {
+                package: result.package.name.clone(),
+                version: result.package.pretty_version.clone().unwrap_or_else(|| result.package.version.clone()),
+                link_type: result.link.link_type.description().to_string(),
+                target: result.link.target.clone(),
+                constraint: result.link.pretty_constraint().to_string(),
+            });
+
+            match &result.children {
+                Some(children) if !children.is_empty() => walk(children, path, chains),
+                _ => chains.push(DependencyChain {
+                    links: path.clone(),
+                    root: result.package.package_type == "root-package",
+                    circular: result.circular,
+                }),
+            }
+
+            path.pop();
+        }
+    }
+
+    let mut chains = Vec::new();
+    walk(results, &mut Vec::new(), &mut chains);
+    chains
+}
+
+/// Packages that are not reachable from the root requirements.
+///
+/// Requirements are followed through the names packages provide or replace,
+/// so an implementation of a required virtual package is not an orphan.
+/// Platform requirements are ignored.
+pub fn find_orphans<'a>(root_requires: impl IntoIterator<Item = &'a String>, packages: &[Arc<Package>]) -> Vec<Arc<Package>> {
+    let mut satisfies: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, package) in packages.iter().enumerate() {
+        let names = std::iter::once(&package.name)
+            .chain(package.provide.keys())
+            .chain(package.replace.keys());
+        for name in names {
+            satisfies.entry(name.to_lowercase()).or_default().push(index);
+        }
+    }
+
+    let mut reachable = vec![false; packages.len()];
+    let mut queue: VecDeque<String> = root_requires.into_iter().map(|name| name.to_lowercase()).collect();
+    while let Some(name) = queue.pop_front() {
+        if is_platform_package(&name) {
+            continue;
+        }
+        for &index in satisfies.get(&name).into_iter().flatten() {
+            if !reachable[index] {
+                reachable[index] = true;
+                queue.extend(packages[index].require.keys().map(|name| name.to_lowercase()));
+            }
+        }
+    }
+
+    packages
+        .iter()
+        .zip(reachable)
+        .filter(|(_, reachable)| !reachable)
+        .map(|(package, _)| package.clone())
+        .collect()
+}
+
+/// A package node in an exported dependency graph.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphNode {
+    pub name: String,
+    pub version: String,
+    /// Whether the package is only reachable through require-dev links
+    pub dev: bool,
+    /// Whether this node is the root package
+    pub root: bool,
+}
+
+/// A require edge in an exported dependency graph.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    pub constraint: String,
+    /// Whether the edge is a require-dev link or originates from a dev-only package
+    pub dev: bool,
+}
+
+/// Resolved dependency graph of the root package and its installed dependencies.
+///
+/// Nodes are ordered root first, then alphabetically, so exports are stable and diffable.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DependencyGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl DependencyGraph {
+    /// Build the graph from the root package and the installed packages.
+    ///
+    /// Requirements on platform packages are skipped, and requirements satisfied through
+    /// `replace`/`provide` point at the replacing or providing package.
+    pub fn build(root: &Package, packages: &[Arc<Package>], include_dev: bool) -> Self {
+        let mut by_name: HashMap<String, &Package> = HashMap::new();
+        for package in packages {
+            by_name.insert(package.name.to_lowercase(), package.as_ref());
+        }
+        for package in packages {
+            for target in package.replace.keys().chain(package.provide.keys()) {
+                by_name.entry(target.to_lowercase()).or_insert(package.as_ref());
+            }
+        }
+
+        let root_name = root.name.to_lowercase();
+        let mut edges = Vec::new();
+        let mut adjacency: HashMap<String, Vec<(String, bool)>> = HashMap::new();
+
+        let mut add_links = |source: &Package, requires: &indexmap::IndexMap<String, String>, dev: bool| {
+            for (target, constraint) in requires {
+                if is_platform_package(target) {
+                    continue;
+                }
+                let Some(resolved) = by_name.get(&target.to_lowercase()) else {
+                    continue;
+                };
+                let source_name = source.name.to_lowercase();
+                let target_name = resolved.name.to_lowercase();
+                if source_name == target_name {
+                    continue;
+                }
+                adjacency
+                    .entry(source_name.clone())
+                    .or_default()
+                    .push((target_name.clone(), dev));
+                edges.push(GraphEdge {
+                    source: source_name,
+                    target: target_name,
+                    constraint: constraint.clone(),
+                    dev,
+                });
+            }
+        };
+
+        add_links(root, &root.require, false);
+        if include_dev {
+            add_links(root, &root.require_dev, true);
+        }
+        for package in packages {
+            if package.name.to_lowercase() != root_name {
+                add_links(package, &package.require, false);
+            }
+        }
+
+        // Walk prod edges from the root; everything reachable is a prod package
+        let mut prod: HashSet<String> = HashSet::new();
+        let mut queue = VecDeque::from([root_name.clone()]);
+        while let Some(name) = queue.pop_front() {
+            if !prod.insert(name.clone()) {
+                continue;
+            }
+            for (target, dev) in adjacency.get(&name).into_iter().flatten() {
+                if !dev {
+                    queue.push_back(target.clone());
+                }
+            }
+        }
+
+        // Walk all edges to find which packages belong to the graph at all
+        let mut reachable: HashSet<String> = HashSet::new();
+        let mut queue = VecDeque::from([root_name.clone()]);
+        while let Some(name) = queue.pop_front() {
+            if !reachable.insert(name.clone()) {
+                continue;
+            }
+            for (target, _) in adjacency.get(&name).into_iter().flatten() {
+                queue.push_back(target.clone());
+            }
+        }
+
+        let mut nodes: Vec<GraphNode> = packages
+            .iter()
+            .filter(|p| {
+                let name = p.name.to_lowercase();
+                name != root_name && reachable.contains(&name)
+            })
+            .map(|p| GraphNode {
+                name: p.name.to_lowercase(),
+                version: p.pretty_version.clone().unwrap_or_else(|| p.version.clone()),
+                dev: !prod.contains(&p.name.to_lowercase()),
+                root: false,
+            })
+            .collect();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+        nodes.insert(0, GraphNode {
+            name: root_name,
+            version: root.pretty_version.clone().unwrap_or_else(|| root.version.clone()),
+            dev: false,
+            root: true,
+        });
+
+        for edge in &mut edges {
+            if !prod.contains(&edge.source) {
+                edge.dev = true;
+            }
+        }
+        edges.retain(|e| reachable.contains(&e.source));
+        edges.sort_by(|a, b| (&a.source, &a.target).cmp(&(&b.source, &b.target)));
+
+        Self { nodes, edges }
+    }
+
+    /// Render the graph in Graphviz DOT format.
+    pub fn to_dot(&self, with_constraints: bool) -> String {
+        let mut out = String::from("digraph dependencies {\n    rankdir=LR;\n    node [shape=box];\n");
+        for node in &self.nodes {
+            let mut attrs = vec![format!("label=\"{}\\n{}\"", escape_dot(&node.name), escape_dot(&node.version))];
+            if node.root {
+                attrs.push("style=bold".to_string());
+            } else if node.dev {
+                attrs.push("style=dashed".to_string());
+            }
+            out.push_str(&format!("    \"{}\" [{}];\n", escape_dot(&node.name), attrs.join(", ")));
+        }
+        for edge in &self.edges {
+            let mut attrs = Vec::new();
+            if with_constraints {
+                attrs.push(format!("label=\"{}\"", escape_dot(&edge.constraint)));
+            }
+            if edge.dev {
+                attrs.push("style=dashed".to_string());
+            }
+            let attrs = if attrs.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attrs.join(", "))
+            };
+            out.push_str(&format!(
+                "    \"{}\" -> \"{}\"{};\n",
+                escape_dot(&edge.source),
+                escape_dot(&edge.target),
+                attrs
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render the graph as a Mermaid flowchart.
+    pub fn to_mermaid(&self, with_constraints: bool) -> String {
+        let ids: HashMap<&str, String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.name.as_str(), format!("n{}", i)))
+            .collect();
+
+        let mut out = String::from("graph LR\n");
+        for node in &self.nodes {
+            out.push_str(&format!(
+                "    {}[\"{}<br/>{}\"]\n",
+                ids[node.name.as_str()],
+                escape_mermaid(&node.name),
+                escape_mermaid(&node.version)
+            ));
+        }
+        for edge in &self.edges {
+            let (Some(source), Some(target)) = (ids.get(edge.source.as_str()), ids.get(edge.target.as_str())) else {
+                continue;
+            };
+            let arrow = if edge.dev { "-.->" } else { "-->" };
+            if with_constraints {
+                out.push_str(&format!(
+                    "    {} {}|\"{}\"| {}\n",
+                    source,
+                    arrow,
+                    escape_mermaid(&edge.constraint),
+                    target
+                ));
+            } else {
+                out.push_str(&format!("    {} {} {}\n", source, arrow, target));
+            }
+        }
+        out
+    }
+
+    /// Render the graph as JSON (`{"nodes": [...], "edges": [...]}`).
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(s: &str) -> String {
+    s.replace('"', "#quot;")
+}
+
 fn matches_constraint(link_constraint: &str, filter_constraint: Option<&dyn ConstraintInterface>, invert: bool) -> bool {
     if let Some(filter) = filter_constraint {
         let parser = pox_semver::VersionParser;
@@ -357,7 +740,7 @@ mod tests {
         let results = find_packages_with_replacers_and_providers(&packages, "vendor/old-package", None);
 
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "vendor/package1");
+        assert_eq!(results[0].package.name, "vendor/package1");
     }
 
     #[test]
@@ -368,7 +751,111 @@ mod tests {
         let results = find_packages_with_replacers_and_providers(&packages, "vendor/virtual-package", None);
 
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "vendor/package1");
+        assert_eq!(results[0].package.name, "vendor/package1");
+    }
+
+    #[test]
+    fn test_find_records_provenance() {
+        let logger = Arc::new(pkg_with_provides("monolog/monolog", "3.5.0", vec![("psr/log-implementation", "3.0.0")]));
+        let polyfill = Arc::new(pkg_with_replaces("symfony/polyfill", "1.0.0", vec![("symfony/polyfill-ctype", "self.version")]));
+        let packages = vec![logger.clone(), polyfill.clone(), Arc::new(pkg("psr/log", "3.0.0"))];
+
+        let results = find_packages_with_replacers_and_providers(&packages, "psr/log-implementation", None);
+        assert_eq!(results.len(), 1);
+        let link = results[0].link.as_ref().unwrap();
+        assert_eq!(link.link_type, LinkType::Provide);
+        assert_eq!(link.constraint, "3.0.0");
+
+        let results = find_packages_with_replacers_and_providers(&packages, "symfony/polyfill-ctype", None);
+        assert_eq!(results[0].link.as_ref().unwrap().link_type, LinkType::Replace);
+
+        let results = find_packages_with_replacers_and_providers(&packages, "psr/log", None);
+        assert!(!results[0].is_provider());
+    }
+
+    #[test]
+    fn test_provider_dependents_explain_chain() {
+        let mut root = pkg_with_requires("__root__", "1.0.0", vec![("symfony/monolog-bundle", "^3.0")]);
+        root.package_type = "project".to_string();
+        let packages = vec![
+            Arc::new(root),
+            Arc::new(pkg_with_requires("symfony/monolog-bundle", "3.10.0", vec![("monolog/monolog", "^3.0")])),
+            Arc::new(pkg_with_provides("monolog/monolog", "3.5.0", vec![("psr/log-implementation", "3.0.0")])),
+        ];
+
+        let matches = find_packages_with_replacers_and_providers(&packages, "psr/log-implementation", None);
+        let results = get_provider_dependents(&packages, &matches, true);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].package.name, "monolog/monolog");
+        assert_eq!(results[0].link.link_type, LinkType::Provide);
+
+        let children = results[0].children.as_ref().unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].package.name, "symfony/monolog-bundle");
+        assert_eq!(children[0].children.as_ref().unwrap()[0].package.name, "__root__");
+    }
+
+    #[test]
+    fn test_dependency_chains_lead_to_root() {
+        let mut root = pkg_with_requires("acme/app", "dev-main", vec![("a/a", "^1.0"), ("b/b", "^2.0")]);
+        root.package_type = "root-package".to_string();
+        let packages = vec![
+            Arc::new(root),
+            Arc::new(pkg_with_requires("a/a", "1.0.0", vec![("c/c", "^2.0")])),
+            Arc::new(pkg_with_requires("b/b", "2.0.0", vec![("c/c", ">=2.1")])),
+            Arc::new(pkg("c/c", "2.1.0")),
+        ];
+
+        let results = get_dependents(&packages, &["c/c".to_string()], None, false, true, None);
+        let chains = dependency_chains(&results);
+        assert_eq!(chains.len(), 2);
+
+        let chain = &chains[0];
+        assert!(chain.root && !chain.circular);
+        let links: Vec<String> = chain
+            .links
+            .iter()
+            .map(|l| format!("{} {} {} {}", l.package, l.link_type, l.target, l.constraint))
+            .collect();
+        assert_eq!(links, vec!["a/a requires c/c ^2.0", "acme/app requires a/a ^1.0"]);
+        assert_eq!(chains[1].links[0].constraint, ">=2.1");
+
+        let value = serde_json::to_value(&chains[1].links[1]).unwrap();
+        assert_eq!(value["type"], "requires");
+        assert_eq!(value["version"], "dev-main");
+    }
+
+    #[test]
+    fn test_dependency_chains_mark_cycles() {
+        let packages = vec![
+            Arc::new(pkg_with_requires("a/a", "1.0.0", vec![("b/b", "^1.0")])),
+            Arc::new(pkg_with_requires("b/b", "1.0.0", vec![("a/a", "^1.0"), ("c/c", "^1.0")])),
+            Arc::new(pkg("c/c", "1.0.0")),
+        ];
+
+        let results = get_dependents(&packages, &["c/c".to_string()], None, false, true, None);
+        let chains = dependency_chains(&results);
+        assert_eq!(chains.len(), 1);
+        assert!(chains[0].circular);
+        assert!(!chains[0].root);
+        assert_eq!(chains[0].links.len(), 3);
+    }
+
+    #[test]
+    fn test_dependency_chains_only_end_at_root_package() {
+        let mut app = pkg_with_requires("acme/app", "1.0.0", vec![("c/c", "^1.0")]);
+        app.package_type = "project".to_string();
+        let mut conflicting = pkg("x/x", "1.0.0");
+        conflicting.conflict.insert("c/c".to_string(), "<1.0".to_string());
+        let packages = vec![Arc::new(app), Arc::new(conflicting), Arc::new(pkg("c/c", "1.0.0"))];
+
+        let results = get_dependents(&packages, &["c/c".to_string()], None, false, true, None);
+        let chains = dependency_chains(&results);
+        assert_eq!(chains.len(), 2);
+        assert_eq!(chains[0].links[0].package, "acme/app");
+        assert_eq!(chains[1].links[0].link_type, "conflicts");
+        assert!(chains.iter().all(|chain| !chain.root && !chain.circular));
     }
 
     #[test]
@@ -402,4 +889,107 @@ mod tests {
         assert_eq!(results[0].package.name.to_lowercase(), "vendor/package1");
         assert_eq!(results[0].link.target.to_lowercase(), "vendor/dependency");
     }
+
+    fn graph_fixture() -> (Package, Vec<Arc<Package>>) {
+        let mut root = pkg_with_requires("acme/app", "dev-main", vec![("php", ">=8.1"), ("vendor/a", "^1.0")]);
+        root.package_type = "root-package".to_string();
+        root.require_dev.insert("vendor/test".to_string(), "^2.0".to_string());
+
+        let a = Arc::new(pkg_with_requires("vendor/a", "1.2.0", vec![("vendor/b", "^1.1"), ("ext-json", "*")]));
+        let b = Arc::new(pkg("vendor/b", "1.1.0"));
+        let test = Arc::new(pkg_with_requires("vendor/test", "2.0.0", vec![("vendor/mock", "^3.0"), ("vendor/b", "^1.0")]));
+        let mock = Arc::new(pkg("vendor/mock", "3.0.0"));
+        let orphan = Arc::new(pkg("vendor/orphan", "1.0.0"));
+
+        (root, vec![a, b, test, mock, orphan])
+    }
+
+    #[test]
+    fn test_graph_build_marks_dev_nodes_and_edges() {
+        let (root, packages) = graph_fixture();
+        let graph = DependencyGraph::build(&root, &packages, true);
+
+        let names: Vec<&str> = graph.nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["acme/app", "vendor/a", "vendor/b", "vendor/mock", "vendor/test"]);
+        assert!(graph.nodes[0].root);
+
+        let dev_nodes: Vec<&str> = graph.nodes.iter().filter(|n| n.dev).map(|n| n.name.as_str()).collect();
+        assert_eq!(dev_nodes, vec!["vendor/mock", "vendor/test"]);
+
+        assert_eq!(graph.edges.len(), 5);
+        let edge = |s: &str, t: &str| graph.edges.iter().find(|e| e.source == s && e.target == t).unwrap();
+        assert!(!edge("acme/app", "vendor/a").dev);
+        assert!(edge("acme/app", "vendor/test").dev);
+        assert!(edge("vendor/test", "vendor/b").dev);
+        assert_eq!(edge("vendor/a", "vendor/b").constraint, "^1.1");
+    }
+
+    #[test]
+    fn test_graph_build_without_dev() {
+        let (root, packages) = graph_fixture();
+        let graph = DependencyGraph::build(&root, &packages, false);
+
+        let names: Vec<&str> = graph.nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["acme/app", "vendor/a", "vendor/b"]);
+        assert!(graph.edges.iter().all(|e| !e.dev));
+    }
+
+    #[test]
+    fn test_graph_resolves_replaced_targets() {
+        let mut root = pkg_with_requires("acme/app", "dev-main", vec![("vendor/old", "^1.0")]);
+        root.package_type = "root-package".to_string();
+        let packages = vec![Arc::new(pkg_with_replaces("vendor/new", "2.0.0", vec![("vendor/old", "self.version")]))];
+
+        let graph = DependencyGraph::build(&root, &packages, true);
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].target, "vendor/new");
+    }
+
+    #[test]
+    fn test_find_orphans() {
+        let mut implementation = pkg_with_requires("vendor/logger", "1.0.0", vec![("psr/log", "^3.0")]);
+        implementation.provide.insert("psr/log-implementation".to_string(), "3.0.0".to_string());
+        let packages: Vec<Arc<Package>> = vec![
+            Arc::new(pkg_with_requires("vendor/app-kit", "1.0.0", vec![("vendor/util", "^1.0"), ("php", ">=8.1")])),
+            Arc::new(pkg("vendor/util", "1.0.0")),
+            Arc::new(implementation),
+            Arc::new(pkg("psr/log", "3.0.0")),
+            Arc::new(pkg_with_requires("vendor/leftover", "1.0.0", vec![("vendor/leftover-dep", "^1.0")])),
+            Arc::new(pkg_with_requires("vendor/leftover-dep", "1.0.0", vec![("vendor/leftover", "^1.0")])),
+        ];
+        let root_requires = ["vendor/App-Kit".to_string(), "psr/log-implementation".to_string(), "php".to_string()];
+
+        let orphans = find_orphans(&root_requires, &packages);
+        let names: Vec<&str> = orphans.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["vendor/leftover", "vendor/leftover-dep"]);
+    }
+
+    #[test]
+    fn test_find_orphans_without_requirements() {
+        let packages = vec![Arc::new(pkg("vendor/a", "1.0.0"))];
+        assert_eq!(find_orphans(&[], &packages).len(), 1);
+        assert!(find_orphans(&["vendor/a".to_string()], &packages).is_empty());
+    }
+
+    #[test]
+    fn test_graph_renderers() {
+        let (root, packages) = graph_fixture();
+        let graph = DependencyGraph::build(&root, &packages, true);
+
+        let dot = graph.to_dot(true);
+        assert!(dot.starts_with("digraph dependencies {"));
+        assert!(dot.contains("\"vendor/a\" -> \"vendor/b\" [label=\"^1.1\"];"));
+        assert!(dot.contains("\"acme/app\" -> \"vendor/test\" [label=\"^2.0\", style=dashed];"));
+        assert!(!graph.to_dot(false).contains("label=\"^1.1\""));
+
+        let mermaid = graph.to_mermaid(true);
+        assert!(mermaid.starts_with("graph LR\n"));
+        assert!(mermaid.contains("n0[\"acme/app<br/>dev-main\"]"));
+        assert!(mermaid.contains("n1 -->|\"^1.1\"| n2"));
+        assert!(mermaid.contains("n0 -.->|\"^2.0\"| n4"));
+
+        let json = graph.to_json();
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 5);
+        assert_eq!(json["edges"][0]["source"], "acme/app");
+    }
 }
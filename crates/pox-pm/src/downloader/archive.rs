@@ -1,8 +1,8 @@
-//! Archive extraction (zip, tar, tar.gz, tar.bz2).
+//! Archive extraction (zip, tar, tar.gz, tar.bz2, tar.xz).
 
-use std::fs::File;
-use std::io::{BufReader, Read};
-use std::path::Path;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, ErrorKind, Read};
+use std::path::{Component, Path, PathBuf};
 use flate2::read::GzDecoder;
 
 use crate::{ComposerError, Result};
@@ -108,72 +108,34 @@ impl ArchiveExtractor {
             let mut file = archive.by_index(i)
                 .map_err(|e| ComposerError::InstallationFailed(format!("Failed to read zip entry: {}", e)))?;
 
-            let mut outpath = dest_dir.to_path_buf();
-
             // Get the file path and strip common prefix if present
-            let name = file.name();
+            let name = file.name().to_string();
             let relative_path = if let Some(ref prefix) = common_prefix {
-                name.strip_prefix(prefix).unwrap_or(name)
+                name.strip_prefix(prefix.as_str()).unwrap_or(&name)
             } else {
-                name
+                &name
             };
 
-            // Skip empty paths
-            if relative_path.is_empty() {
+            let Some(relative) = sanitize_entry_path(Path::new(relative_path))? else {
                 continue;
-            }
-
-            // Validate path doesn't contain traversal sequences
-            if relative_path.contains("..") {
-                return Err(ComposerError::InstallationFailed(
-                    format!("Path traversal detected in archive: {}", relative_path)
-                ));
-            }
-
-            outpath.push(relative_path);
+            };
 
-            // Verify the path stays within destination directory
-            // Create parent dirs first so we can canonicalize
             if file.is_dir() {
-                std::fs::create_dir_all(&outpath)?;
-            } else if let Some(parent) = outpath.parent() {
-                std::fs::create_dir_all(parent)?;
+                create_dirs(dest_dir, &relative)?;
+                continue;
             }
+            let outpath = prepare_file(dest_dir, &relative)?;
 
-            // After creating directories, verify the path is safe
-            let outpath_canonical = outpath.canonicalize().unwrap_or_else(|_| {
-                // For new files, canonicalize the parent and append filename
-                if let Some(parent) = outpath.parent() {
-                    if let Ok(parent_canonical) = parent.canonicalize() {
-                        if let Some(filename) = outpath.file_name() {
-                            return parent_canonical.join(filename);
-                        }
-                    }
-                }
-                outpath.clone()
-            });
-
-            if !outpath_canonical.starts_with(&dest_dir_canonical) {
-                return Err(ComposerError::InstallationFailed(
-                    format!("Path traversal detected: {} escapes destination directory", relative_path)
-                ));
+            if file.is_symlink() {
+                let mut target = String::new();
+                file.read_to_string(&mut target)?;
+                check_link_target(&dest_dir_canonical, &outpath, Path::new(&target))?;
+                create_symlink(Path::new(&target), &outpath)?;
+                continue;
             }
 
-            if file.is_dir() {
-                // Already created above for canonicalization
-            } else {
-                let mut outfile = File::create(&outpath)?;
-                std::io::copy(&mut file, &mut outfile)?;
-
-                // Set permissions on Unix
-                #[cfg(unix)]
-                {
-                    use std::os::unix::fs::PermissionsExt;
-                    if let Some(mode) = file.unix_mode() {
-                        std::fs::set_permissions(&outpath, std::fs::Permissions::from_mode(mode))?;
-                    }
-                }
-            }
+            let mode = file.unix_mode();
+            write_file(&mut file, &outpath, mode)?;
         }
 
         Ok(())
@@ -263,65 +225,205 @@ impl ArchiveExtractor {
                 .map_err(|e| ComposerError::InstallationFailed(format!("Failed to read tar entry: {}", e)))?;
 
             let path = entry.path()
-                .map_err(|e| ComposerError::InstallationFailed(format!("Invalid path in tar: {}", e)))?;
+                .map_err(|e| ComposerError::InstallationFailed(format!("Invalid path in tar: {}", e)))?
+                .into_owned();
 
-            // Strip leading components
-            let components: Vec<_> = path.components().collect();
-            if components.len() <= strip_components {
+            let Some(stripped) = strip_path(&path, strip_components)? else {
                 continue;
-            }
+            };
 
-            let stripped: std::path::PathBuf = components[strip_components..].iter().collect();
-            if stripped.as_os_str().is_empty() {
-                continue;
+            match entry.header().entry_type() {
+                tar::EntryType::Directory => {
+                    create_dirs(dest_dir, &stripped)?;
+                }
+                tar::EntryType::Regular | tar::EntryType::Continuous | tar::EntryType::GNUSparse => {
+                    let outpath = prepare_file(dest_dir, &stripped)?;
+                    let mode = entry.header().mode().ok();
+                    write_file(&mut entry, &outpath, mode)?;
+                }
+                tar::EntryType::Symlink => {
+                    let target = link_name(&entry)?;
+                    let outpath = prepare_file(dest_dir, &stripped)?;
+                    check_link_target(&dest_dir_canonical, &outpath, &target)?;
+                    create_symlink(&target, &outpath)?;
+                }
+                tar::EntryType::Link => {
+                    // Hard link targets are archive paths, so they get the same prefix stripping
+                    let target = link_name(&entry)?;
+                    let source = strip_path(&target, strip_components)?
+                        .and_then(|target| extracted_file(dest_dir, &target))
+                        .ok_or_else(|| ComposerError::InstallationFailed(
+                            format!("Invalid hard link in archive: {}", stripped.display())
+                        ))?;
+                    let outpath = prepare_file(dest_dir, &stripped)?;
+                    let mode = source.metadata()?.permissions();
+                    write_file(&mut File::open(&source)?, &outpath, None)?;
+                    std::fs::set_permissions(&outpath, mode)?;
+                }
+                // Devices, fifos and metadata entries have no place in a package
+                _ => {}
             }
+        }
+
+        Ok(())
+    }
+}
+
+/// Strip leading components from an archive path and validate the rest,
+/// `None` if nothing is left
+fn strip_path(path: &Path, strip_components: usize) -> Result<Option<PathBuf>> {
+    let components: Vec<_> = path.components().collect();
+    if components.len() <= strip_components {
+        return Ok(None);
+    }
+
+    let stripped: PathBuf = components[strip_components..].iter().collect();
+    sanitize_entry_path(&stripped)
+}
 
-            // Validate path doesn't contain traversal sequences
-            let stripped_str = stripped.to_string_lossy();
-            if stripped_str.contains("..") {
+/// Normalize an archive entry path, rejecting absolute paths and `..` components
+fn sanitize_entry_path(path: &Path) -> Result<Option<PathBuf>> {
+    let mut sanitized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => sanitized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
                 return Err(ComposerError::InstallationFailed(
-                    format!("Path traversal detected in archive: {}", stripped_str)
+                    format!("Path traversal detected in archive: {}", path.display())
                 ));
             }
+        }
+    }
 
-            let outpath = dest_dir.join(&stripped);
-
-            // Create parent directories first so we can verify the path
-            if entry.header().entry_type().is_dir() {
-                std::fs::create_dir_all(&outpath)?;
-            } else if let Some(parent) = outpath.parent() {
-                std::fs::create_dir_all(parent)?;
-            }
+    Ok(if sanitized.as_os_str().is_empty() { None } else { Some(sanitized) })
+}
 
-            // Verify the path stays within destination directory
-            let outpath_canonical = outpath.canonicalize().unwrap_or_else(|_| {
-                // For new files, canonicalize the parent and append filename
-                if let Some(parent) = outpath.parent() {
-                    if let Ok(parent_canonical) = parent.canonicalize() {
-                        if let Some(filename) = outpath.file_name() {
-                            return parent_canonical.join(filename);
-                        }
-                    }
-                }
-                outpath.clone()
-            });
+/// Create the sanitized `relative` directory below `dest_dir` one component at a
+/// time, refusing to descend through symlinks or anything else that is not a directory
+fn create_dirs(dest_dir: &Path, relative: &Path) -> Result<PathBuf> {
+    let mut path = dest_dir.to_path_buf();
 
-            if !outpath_canonical.starts_with(&dest_dir_canonical) {
+    for component in relative.components() {
+        path.push(component);
+        match path.symlink_metadata() {
+            Ok(metadata) if metadata.is_dir() => {}
+            Ok(_) => {
                 return Err(ComposerError::InstallationFailed(
-                    format!("Path traversal detected: {} escapes destination directory", stripped_str)
+                    format!("Archive entry {} would be written through {}", relative.display(), path.display())
                 ));
             }
+            Err(e) if e.kind() == ErrorKind::NotFound => std::fs::create_dir(&path)?,
+            Err(e) => return Err(e.into()),
+        }
+    }
 
-            if entry.header().entry_type().is_dir() {
-                // Already created above
-            } else {
-                entry.unpack(&outpath)
-                    .map_err(|e| ComposerError::InstallationFailed(format!("Failed to extract: {}", e)))?;
-            }
+    Ok(path)
+}
+
+/// Create the parent directories of a file entry and remove what an earlier entry
+/// left at its path, so the file is always created new instead of written through a link
+fn prepare_file(dest_dir: &Path, relative: &Path) -> Result<PathBuf> {
+    let parent = create_dirs(dest_dir, relative.parent().unwrap_or(Path::new("")))?;
+    let outpath = match relative.file_name() {
+        Some(name) => parent.join(name),
+        None => return Err(ComposerError::InstallationFailed(format!("Invalid path in archive: {}", relative.display()))),
+    };
+
+    match outpath.symlink_metadata() {
+        Ok(metadata) if metadata.is_dir() => {
+            return Err(ComposerError::InstallationFailed(
+                format!("Archive entry {} would replace a directory", relative.display())
+            ));
         }
+        Ok(_) => std::fs::remove_file(&outpath)?,
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
 
-        Ok(())
+    Ok(outpath)
+}
+
+/// Path of an already extracted regular file, `None` if it is missing or
+/// reached through a symlink
+fn extracted_file(dest_dir: &Path, relative: &Path) -> Option<PathBuf> {
+    let mut path = dest_dir.to_path_buf();
+    let count = relative.components().count();
+
+    for (i, component) in relative.components().enumerate() {
+        path.push(component);
+        let metadata = path.symlink_metadata().ok()?;
+        let expected = if i + 1 == count { metadata.is_file() } else { metadata.is_dir() };
+        if !expected {
+            return None;
+        }
+    }
+
+    Some(path)
+}
+
+/// Copy an entry into a file that must not exist yet
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn write_file<R: Read>(reader: &mut R, outpath: &Path, mode: Option<u32>) -> Result<()> {
+    let mut outfile = OpenOptions::new().write(true).create_new(true).open(outpath)?;
+    std::io::copy(reader, &mut outfile)?;
+
+    // Keep permission bits such as the executable flag, never file type or setuid bits
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Some(mode) = mode.map(|m| m & 0o777).filter(|m| *m != 0) {
+            outfile.set_permissions(std::fs::Permissions::from_mode(mode | 0o600))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Verify that a symlink at `link` pointing to `target` resolves inside the
+/// destination directory, starting from the canonicalized directory holding the link
+fn check_link_target(dest_dir_canonical: &Path, link: &Path, target: &Path) -> Result<()> {
+    let escapes = || ComposerError::InstallationFailed(
+        format!("Symlink escapes destination directory: {} -> {}", link.display(), target.display())
+    );
+
+    let mut resolved = link.parent().ok_or_else(escapes)?.canonicalize()?;
+    for component in target.components() {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::RootDir | Component::Prefix(_) => return Err(escapes()),
+        }
+        if !resolved.starts_with(dest_dir_canonical) {
+            return Err(escapes());
+        }
     }
+
+    Ok(())
+}
+
+fn link_name<R: Read>(entry: &tar::Entry<R>) -> Result<PathBuf> {
+    entry.link_name()
+        .map_err(|e| ComposerError::InstallationFailed(format!("Invalid link in tar: {}", e)))?
+        .map(|name| name.into_owned())
+        .ok_or_else(|| ComposerError::InstallationFailed("Link without target in tar".to_string()))
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(target, link)?;
+    Ok(())
+}
+
+/// Symlinks need extra privileges on Windows, the link target is written as the file content
+#[cfg(not(unix))]
+fn create_symlink(target: &Path, link: &Path) -> Result<()> {
+    std::fs::write(link, target.to_string_lossy().as_bytes())?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -371,4 +473,184 @@ mod tests {
             Some(ArchiveType::Tar)
         );
     }
+
+    /// Build a tar archive with a `pkg/` prefix; `(path, target)` entries with a target are symlinks
+    fn tar_bytes(entries: &[(&str, Option<&str>, &[u8], u32)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, link, content, mode) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_mode(*mode);
+            let path = format!("pkg/{}", path);
+            match link {
+                Some(target) => {
+                    header.set_entry_type(tar::EntryType::Symlink);
+                    header.set_size(0);
+                    builder.append_link(&mut header, &path, target).unwrap();
+                }
+                None => {
+                    header.set_size(content.len() as u64);
+                    // `append_data` rejects `..`, so the path is written to the header directly
+                    header.as_gnu_mut().unwrap().name[..path.len()].copy_from_slice(path.as_bytes());
+                    header.set_cksum();
+                    builder.append(&header, *content).unwrap();
+                }
+            }
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn extract_tar_bytes(bytes: &[u8]) -> (tempfile::TempDir, Result<()>) {
+        let dest = tempfile::TempDir::new().unwrap();
+        let result = ArchiveExtractor::extract_tar_with_strip(bytes, dest.path(), 1);
+        (dest, result)
+    }
+
+    #[test]
+    fn test_tar_rejects_path_traversal() {
+        let (dest, result) = extract_tar_bytes(&tar_bytes(&[("../escape.txt", None, b"x", 0o644)]));
+        assert!(result.is_err());
+        assert!(!dest.path().parent().unwrap().join("escape.txt").exists());
+
+        // Dots inside a file name are not traversal
+        let (dest, result) = extract_tar_bytes(&tar_bytes(&[("docs/a..b.md", None, b"x", 0o644)]));
+        result.unwrap();
+        assert!(dest.path().join("docs/a..b.md").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_tar_symlinks() {
+        let (dest, result) = extract_tar_bytes(&tar_bytes(&[
+            ("bin/tool", None, b"#!/bin/sh", 0o755),
+            ("tool", Some("bin/tool"), b"", 0o777),
+        ]));
+        result.unwrap();
+        assert_eq!(std::fs::read_link(dest.path().join("tool")).unwrap(), Path::new("bin/tool"));
+
+        let (_, result) = extract_tar_bytes(&tar_bytes(&[("etc", Some("/etc"), b"", 0o777)]));
+        assert!(result.is_err());
+        let (_, result) = extract_tar_bytes(&tar_bytes(&[("sub/up", Some("../../outside"), b"", 0o777)]));
+        assert!(result.is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_tar_never_writes_through_symlinks() {
+        let outside = tempfile::TempDir::new().unwrap();
+
+        // A symlink that stays inside cannot be used as a directory of later entries
+        let (dest, result) = extract_tar_bytes(&tar_bytes(&[
+            ("real/.keep", None, b"", 0o644),
+            ("dir", Some("real"), b"", 0o777),
+            ("dir/file.php", None, b"<?php", 0o644),
+        ]));
+        assert!(result.is_err());
+        assert!(!dest.path().join("real/file.php").exists());
+
+        // A file entry replaces an earlier symlink instead of writing to its target
+        std::fs::write(outside.path().join("target.txt"), "untouched").unwrap();
+        let dest = tempfile::TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path().join("target.txt"), dest.path().join("file.txt")).unwrap();
+        ArchiveExtractor::extract_tar_with_strip(
+            tar_bytes(&[("file.txt", None, b"new", 0o644)]).as_slice(),
+            dest.path(),
+            1,
+        ).unwrap();
+        assert_eq!(std::fs::read_to_string(outside.path().join("target.txt")).unwrap(), "untouched");
+        assert!(!dest.path().join("file.txt").symlink_metadata().unwrap().file_type().is_symlink());
+        assert_eq!(std::fs::read_to_string(dest.path().join("file.txt")).unwrap(), "new");
+
+        // Link targets are resolved from the directory holding the link
+        let (_, result) = extract_tar_bytes(&tar_bytes(&[("a/b/up", Some("../../.."), b"", 0o777)]));
+        assert!(result.is_err());
+        let (_, result) = extract_tar_bytes(&tar_bytes(&[("a/b/up", Some("../.."), b"", 0o777)]));
+        result.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_tar_preserves_executable_bit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (dest, result) = extract_tar_bytes(&tar_bytes(&[
+            ("bin/tool", None, b"#!/bin/sh", 0o755),
+            ("README.md", None, b"readme", 0o644),
+        ]));
+        result.unwrap();
+        let mode = |p: &str| std::fs::metadata(dest.path().join(p)).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode("bin/tool"), 0o755);
+        assert_eq!(mode("README.md"), 0o644);
+    }
+
+    #[test]
+    fn test_compressed_tar_formats() {
+        use std::io::Write;
+
+        let tar = tar_bytes(&[("src/lib.php", None, b"<?php", 0o644)]);
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let mut bz = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        bz.write_all(&tar).unwrap();
+        std::fs::write(dir.path().join("package.tar.bz2"), bz.finish().unwrap()).unwrap();
+
+        let mut xz = xz2::write::XzEncoder::new(Vec::new(), 6);
+        xz.write_all(&tar).unwrap();
+        std::fs::write(dir.path().join("package.tar.xz"), xz.finish().unwrap()).unwrap();
+
+        for name in ["package.tar.bz2", "package.tar.xz"] {
+            let dest = dir.path().join(name.replace('.', "-"));
+            ArchiveExtractor::extract(&dir.path().join(name), &dest).unwrap();
+            assert_eq!(std::fs::read_to_string(dest.join("src/lib.php")).unwrap(), "<?php");
+        }
+    }
+
+    fn zip_archive(dir: &Path, build: impl FnOnce(&mut zip::ZipWriter<File>)) -> PathBuf {
+        let path = dir.join("package.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&path).unwrap());
+        build(&mut writer);
+        writer.finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_zip_rejects_path_traversal() {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let archive = zip_archive(dir.path(), |zip| {
+            zip.start_file("pkg/../../escape.txt", SimpleFileOptions::default()).unwrap();
+            zip.write_all(b"x").unwrap();
+        });
+
+        let dest = dir.path().join("dest");
+        assert!(ArchiveExtractor::extract(&archive, &dest).is_err());
+        assert!(!dir.path().join("escape.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_zip_symlinks_and_permissions() {
+        use std::io::Write;
+        use std::os::unix::fs::PermissionsExt;
+        use zip::write::SimpleFileOptions;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let archive = zip_archive(dir.path(), |zip| {
+            zip.start_file("pkg/bin/tool", SimpleFileOptions::default().unix_permissions(0o755)).unwrap();
+            zip.write_all(b"#!/bin/sh").unwrap();
+            zip.add_symlink("pkg/tool", "bin/tool", SimpleFileOptions::default()).unwrap();
+        });
+
+        let dest = dir.path().join("dest");
+        ArchiveExtractor::extract(&archive, &dest).unwrap();
+        assert_eq!(std::fs::read_link(dest.join("tool")).unwrap(), Path::new("bin/tool"));
+        let mode = std::fs::metadata(dest.join("bin/tool")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+
+        let archive = zip_archive(dir.path(), |zip| {
+            zip.add_symlink("pkg/passwd", "/etc/passwd", SimpleFileOptions::default()).unwrap();
+        });
+        assert!(ArchiveExtractor::extract(&archive, &dir.path().join("escaped")).is_err());
+    }
 }
@@ -1,42 +1,92 @@
 //! File downloader for HTTP/HTTPS archives.
+//!
+//! Downloads are staged in a `.part` file next to the destination. When the
+//! connection fails midway, the download is retried with a `Range` request
+//! continuing after the bytes already received, so large dist archives don't
+//! restart from zero. A partial file left by a failed run is resumed the
+//! same way by the next one, as long as the server confirms through
+//! `If-Range` that the file did not change in between.
+//!
+//! The `.part` file is guarded by an OS lock on `<dest>.part.lock`, so two
+//! processes downloading into the same cache file wait for each other
+//! instead of appending to the same partial file.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use crate::http::HttpClient;
+use crate::http::{HttpClient, HttpError};
 use crate::{ComposerError, Result};
 
 use super::archive::{ArchiveExtractor, ArchiveType};
 use super::checksum::{verify_checksum, ChecksumType};
 
+/// Retries of a failed download when not configured
+pub const DEFAULT_DOWNLOAD_RETRIES: u32 = 3;
+
 /// File downloader for HTTP archives
 pub struct FileDownloader {
     http_client: Arc<HttpClient>,
+    retries: u32,
 }
 
 impl FileDownloader {
     /// Create a new file downloader
     pub fn new(http_client: Arc<HttpClient>) -> Self {
-        Self { http_client }
+        Self {
+            http_client,
+            retries: DEFAULT_DOWNLOAD_RETRIES,
+        }
+    }
+
+    /// Retry a failed download this many times, continuing where it stopped
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
     }
 
     /// Download a file to the specified path
+    ///
+    /// Returns whether bytes of an earlier, interrupted attempt were kept, in
+    /// which case a checksum mismatch may be fixed by downloading again.
     pub async fn download<F>(
         &self,
         url: &str,
         dest: &Path,
         progress: Option<F>,
-    ) -> Result<()>
+    ) -> Result<bool>
     where
         F: Fn(u64, u64),
     {
-        self.http_client
-            .download(url, dest, progress)
-            .await
-            .map_err(|e| ComposerError::DownloadFailed {
-                package: url.to_string(),
-                reason: e.to_string(),
-            })
+        let partial = partial_path(dest);
+        let _lock = PartialLock::acquire(&partial).await?;
+        let mut attempt = 0;
+
+        loop {
+            match self.http_client.download_resume(url, &partial, progress.as_ref()).await {
+                Ok(resumed) => {
+                    tokio::fs::rename(&partial, dest).await?;
+                    return Ok(resumed);
+                }
+                Err(e) if attempt < self.retries && is_retryable(&e) => {
+                    log::debug!("Download of {} failed, retrying ({}/{}): {}", url, attempt + 1, self.retries, e);
+
+                    if matches!(e, HttpError::HttpStatus { status: 206 | 416, .. }) {
+                        // The server can't continue the partial file, start over
+                        let _ = tokio::fs::remove_file(&partial).await;
+                    }
+
+                    tokio::time::sleep(self.http_client.retry_delay() * 2_u32.pow(attempt)).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    // Keep a partially received file for the next run, unless the server refused it
+                    if matches!(e, HttpError::HttpStatus { .. }) {
+                        let _ = tokio::fs::remove_file(&partial).await;
+                    }
+                    return Err(ComposerError::from_http(url, e));
+                }
+            }
+        }
     }
 
     /// Download and verify checksum
@@ -51,7 +101,7 @@ impl FileDownloader {
         F: Fn(u64, u64),
     {
         // Download the file
-        self.download(url, dest, progress).await?;
+        let resumed = self.download(url, dest, progress.as_ref()).await?;
 
         // Verify checksum
         let checksum_type = ChecksumType::from_hex_length(expected_checksum.len())
@@ -59,7 +109,14 @@ impl FileDownloader {
                 package: url.to_string(),
             })?;
 
-        let valid = verify_checksum(dest, expected_checksum, checksum_type).await?;
+        let mut valid = verify_checksum(dest, expected_checksum, checksum_type).await?;
+
+        if !valid && resumed {
+            // The continued download doesn't match, fetch it in one piece
+            let _ = tokio::fs::remove_file(dest).await;
+            self.download(url, dest, progress.as_ref()).await?;
+            valid = verify_checksum(dest, expected_checksum, checksum_type).await?;
+        }
 
         if !valid {
             // Remove the downloaded file
@@ -107,16 +164,236 @@ impl FileDownloader {
             self.download(url, &temp_file, progress).await?;
         }
 
-        // Extract
-        ArchiveExtractor::extract(&temp_file, dest_dir)?;
+        // Extract without blocking the runtime
+        let dest_dir = dest_dir.to_path_buf();
+        tokio::task::spawn_blocking(move || ArchiveExtractor::extract(&temp_file, &dest_dir))
+            .await
+            .map_err(|e| ComposerError::InstallationFailed(format!("Extraction task failed: {}", e)))??;
 
         Ok(())
     }
 }
 
+/// Staging file of a download, e.g. `package.zip.part`
+fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+/// Exclusive lock on the staging file of a download
+///
+/// An OS file lock on `<part>.lock`, released by the OS when the process
+/// exits, so a crash never leaves the download locked.
+struct PartialLock {
+    _file: std::fs::File,
+}
+
+impl PartialLock {
+    async fn acquire(partial: &Path) -> Result<Self> {
+        let mut path = partial.as_os_str().to_owned();
+        path.push(".lock");
+        let path = PathBuf::from(path);
+
+        let file = tokio::task::spawn_blocking(move || -> std::io::Result<std::fs::File> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let file = std::fs::OpenOptions::new().create(true).truncate(false).write(true).open(&path)?;
+            file.lock()?;
+            Ok(file)
+        })
+        .await
+        .map_err(|e| ComposerError::InstallationFailed(format!("Lock task failed: {}", e)))??;
+
+        Ok(Self { _file: file })
+    }
+}
+
+/// Whether a failed download may succeed when tried again
+fn is_retryable(error: &HttpError) -> bool {
+    match error {
+        HttpError::HttpStatus { status, .. } => {
+            matches!(status, 206 | 408 | 416 | 429) || *status >= 500
+        }
+        HttpError::Request(_) | HttpError::Io(_) | HttpError::MaxRetries { .. } => true,
+        HttpError::JsonParse(_) => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::http::HttpClientConfig;
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve `body` with the ETag `"v1"`, cutting the first response off after
+    /// `cut` bytes; returns the URL and the `Range` header of each request
+    async fn flaky_server(body: Vec<u8>, cut: usize, ranges: bool) -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/dist.zip", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut head = Vec::new();
+                let mut chunk = [0u8; 1024];
+                while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    head.extend_from_slice(&chunk[..n]);
+                }
+
+                let head = String::from_utf8_lossy(&head).to_lowercase();
+                let range = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("range: bytes="))
+                    .map(|r| r.trim_end_matches('-').to_string());
+                let current = head.lines().any(|l| l == "if-range: \"v1\"");
+                let first = {
+                    let mut seen = seen.lock().unwrap();
+                    seen.push(range.clone());
+                    seen.len() == 1
+                };
+
+                let response = match range.and_then(|r| r.parse::<usize>().ok()).filter(|_| ranges && current) {
+                    Some(start) => {
+                        let mut response = format!(
+                            "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                            start,
+                            body.len() - 1,
+                            body.len(),
+                            body.len() - start
+                        )
+                        .into_bytes();
+                        response.extend_from_slice(&body[start..]);
+                        response
+                    }
+                    None => {
+                        let mut response =
+                            format!("HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len())
+                                .into_bytes();
+                        response.extend_from_slice(if first { &body[..cut] } else { &body });
+                        response
+                    }
+                };
+
+                let _ = stream.write_all(&response).await;
+                let _ = stream.shutdown().await;
+            }
+        });
+
+        (url, requests)
+    }
+
+    fn downloader() -> FileDownloader {
+        let config = HttpClientConfig::new().with_retry_delay(Duration::ZERO);
+        FileDownloader::new(Arc::new(HttpClient::with_config(config).unwrap()))
+    }
+
+    #[tokio::test]
+    async fn test_download_resumes_after_interruption() {
+        let body: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        let (url, requests) = flaky_server(body.clone(), 8_000, true).await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("dist.zip");
+
+        let resumed = downloader().download(&url, &dest, None::<fn(u64, u64)>).await.unwrap();
+        assert!(resumed);
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+        assert!(!partial_path(&dest).exists());
+        assert_eq!(*requests.lock().unwrap(), vec![None, Some("8000".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_download_restarts_without_range_support() {
+        let body: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        let (url, requests) = flaky_server(body.clone(), 8_000, false).await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("dist.zip");
+
+        let resumed = downloader().download(&url, &dest, None::<fn(u64, u64)>).await.unwrap();
+        assert!(!resumed);
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+        assert_eq!(*requests.lock().unwrap(), vec![None, Some("8000".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_download_restarts_when_file_changed() {
+        let body: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        let (url, requests) = flaky_server(body.clone(), body.len(), true).await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("dist.zip");
+        // Left by an earlier run against an older version of the file
+        let partial = partial_path(&dest);
+        std::fs::write(&partial, vec![0u8; 5_000]).unwrap();
+        std::fs::write(temp_dir.path().join("dist.zip.part.validator"), "\"v0\"").unwrap();
+
+        let resumed = downloader().download(&url, &dest, None::<fn(u64, u64)>).await.unwrap();
+        assert!(!resumed);
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+        assert!(!temp_dir.path().join("dist.zip.part.validator").exists());
+        assert_eq!(*requests.lock().unwrap(), vec![Some("5000".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_download_without_validator_starts_over() {
+        let body: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        let (url, requests) = flaky_server(body.clone(), body.len(), true).await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("dist.zip");
+        std::fs::write(partial_path(&dest), vec![0u8; 5_000]).unwrap();
+
+        let resumed = downloader().download(&url, &dest, None::<fn(u64, u64)>).await.unwrap();
+        assert!(!resumed);
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+        assert_eq!(*requests.lock().unwrap(), vec![None]);
+    }
+
+    #[tokio::test]
+    async fn test_partial_file_is_locked_while_downloading() {
+        let temp_dir = TempDir::new().unwrap();
+        let partial = partial_path(&temp_dir.path().join("dist.zip"));
+
+        let lock = PartialLock::acquire(&partial).await.unwrap();
+        let other = std::fs::OpenOptions::new()
+            .write(true)
+            .open(temp_dir.path().join("dist.zip.part.lock"))
+            .unwrap();
+        assert!(other.try_lock().is_err());
+        drop(lock);
+        assert!(other.try_lock().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_download_gives_up_after_retries() {
+        let body = vec![7u8; 10_000];
+        let (url, requests) = flaky_server(body, 4_000, true).await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("dist.zip");
+
+        let result = downloader()
+            .with_retries(0)
+            .download(&url, &dest, None::<fn(u64, u64)>)
+            .await;
+        assert!(result.is_err());
+        assert!(!dest.exists());
+        // The partial file is kept for the next run
+        assert_eq!(std::fs::metadata(partial_path(&dest)).unwrap().len(), 4_000);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
 
     #[tokio::test]
     async fn test_file_downloader_creation() {
@@ -3,15 +3,26 @@
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use tokio::sync::Semaphore;
+
+use crate::cache::Cache;
+use crate::config::PreferredInstall;
 use crate::http::HttpClient;
+use crate::installer::InstallerPaths;
+use crate::io::{ConsoleIo, Io};
+use crate::package::archiver::{create_archive, ArchiveFormat};
 use crate::package::{Dist, Source};
+use crate::profile::Profiler;
+use crate::progress::{ProgressEvent, ProgressReporter};
+use crate::solver::matches_update_pattern;
 use crate::{ComposerError, Package, Result};
 
 use super::archive::ArchiveExtractor;
 use super::checksum::{verify_checksum, ChecksumType};
-use super::file::FileDownloader;
+use super::file::{FileDownloader, DEFAULT_DOWNLOAD_RETRIES};
 use super::git::GitDownloader;
 use super::path::{PathDownloader, PathStrategy};
+use super::signature::{verify_signature, MinisignPublicKey, MinisignSignature, RepositorySigningKeys};
 
 /// Result of a download operation
 #[derive(Debug)]
@@ -31,10 +42,26 @@ pub struct DownloadConfig {
     pub prefer_source: bool,
     /// Prefer dist over source
     pub prefer_dist: bool,
+    /// Per-package install method overrides; the first matching pattern wins
+    pub preferred_install: Vec<(String, PreferredInstall)>,
+    /// Verify checksums and signatures of dist archives
+    pub verify: bool,
+    /// Minisign public keys trusted for dist signatures, per repository
+    pub signing_keys: Vec<RepositorySigningKeys>,
+    /// Reject plain http and git:// URLs (`secure-http` config)
+    pub secure_http: bool,
     /// Cache directory for downloaded archives
     pub cache_dir: PathBuf,
+    /// Directory of the bare git mirrors sources are cloned from (`cache-vcs-dir`)
+    pub cache_vcs_dir: Option<PathBuf>,
     /// Vendor directory for extracted packages
     pub vendor_dir: PathBuf,
+    /// Number of archives extracted at once on the blocking thread pool, 0 is treated as 1
+    pub max_parallel_extractions: usize,
+    /// Retries of an interrupted dist download, resumed where it stopped
+    pub download_retries: u32,
+    /// Custom install directories that take precedence over the vendor directory
+    pub installer_paths: InstallerPaths,
 }
 
 impl Default for DownloadConfig {
@@ -42,31 +69,72 @@ impl Default for DownloadConfig {
         Self {
             prefer_source: false,
             prefer_dist: true,
+            preferred_install: Vec::new(),
+            verify: true,
+            signing_keys: Vec::new(),
+            secure_http: true,
             cache_dir: PathBuf::from(".composer/cache"),
+            cache_vcs_dir: None,
             vendor_dir: PathBuf::from("vendor"),
+            max_parallel_extractions: std::thread::available_parallelism().map_or(4, |n| n.get()),
+            download_retries: DEFAULT_DOWNLOAD_RETRIES,
+            installer_paths: InstallerPaths::default(),
         }
     }
 }
 
 /// Download manager for package installation
 pub struct DownloadManager {
+    http_client: Arc<HttpClient>,
     file_downloader: FileDownloader,
-    git_downloader: GitDownloader,
+    git_downloader: Arc<GitDownloader>,
     path_downloader: PathDownloader,
+    extract_permits: Arc<Semaphore>,
     config: DownloadConfig,
+    io: Arc<dyn Io>,
+    profiler: Arc<Profiler>,
+    progress: Arc<ProgressReporter>,
 }
 
 impl DownloadManager {
     /// Create a new download manager
     pub fn new(http_client: Arc<HttpClient>, config: DownloadConfig) -> Self {
+        let mut git_downloader = GitDownloader::new();
+        if let Some(cache_vcs_dir) = &config.cache_vcs_dir {
+            git_downloader = git_downloader.with_cache_dir(cache_vcs_dir);
+        }
+
         Self {
-            file_downloader: FileDownloader::new(http_client),
-            git_downloader: GitDownloader::new(),
-            path_downloader: PathDownloader::new(),
+            file_downloader: FileDownloader::new(http_client.clone()).with_retries(config.download_retries),
+            http_client,
+            git_downloader: Arc::new(git_downloader),
+            path_downloader: PathDownloader::from_env(),
+            extract_permits: Arc::new(Semaphore::new(config.max_parallel_extractions.max(1))),
             config,
+            io: Arc::new(ConsoleIo::default()),
+            profiler: Arc::new(Profiler::disabled()),
+            progress: Arc::new(ProgressReporter::new()),
         }
     }
 
+    /// Report download warnings through the given io
+    pub fn with_io(mut self, io: Arc<dyn Io>) -> Self {
+        self.io = io;
+        self
+    }
+
+    /// Record download and extraction time in the given profiler
+    pub fn with_profiler(mut self, profiler: Arc<Profiler>) -> Self {
+        self.profiler = profiler;
+        self
+    }
+
+    /// Report download and extraction progress to `progress`
+    pub fn with_progress(mut self, progress: Arc<ProgressReporter>) -> Self {
+        self.progress = progress;
+        self
+    }
+
     /// Download and install a package
     pub async fn download(&self, package: &Package) -> Result<DownloadResult> {
         let dest_dir = self.package_path(package);
@@ -112,10 +180,15 @@ impl DownloadManager {
         if let Some(source) = &package.source {
             log::debug!("Installing {} ({}) from source ({})",
                 package.name, package.version, source.source_type);
-            self.download_from_source(package, source, &dest_dir).await?;
+            let from_cache = if source.source_type == "git" {
+                self.download_from_source_archive(package, source, &dest_dir).await?
+            } else {
+                self.download_from_source(package, source, &dest_dir).await?;
+                false
+            };
             return Ok(DownloadResult {
                 path: dest_dir,
-                from_cache: false,
+                from_cache,
                 skipped: false,
             });
         }
@@ -152,65 +225,149 @@ impl DownloadManager {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        // Try URLs in order (primary + mirrors)
+        // Try URLs in order (preferred mirrors, primary, other mirrors)
         let urls = dist.urls();
-
-        let checksum = dist.sha256.as_ref()
-            .filter(|s| !s.is_empty())
-            .or_else(|| dist.shasum.as_ref().filter(|s| !s.is_empty()));
+        let mut last_error = None;
 
         for url in &urls {
+            if let Err(e) = self.check_secure_url(url) {
+                self.io.write_error(&format!("Warning: {}", e));
+                last_error = Some(e);
+                continue;
+            }
+
             if cache_file.exists() {
-                // Verify checksum if available
-                if let Some(checksum) = checksum {
-                    let checksum_type = ChecksumType::from_hex_length(checksum.len())
-                        .unwrap_or(ChecksumType::Sha256);
-
-                    if verify_checksum(&cache_file, checksum, checksum_type).await? {
-                        self.extract_archive(&cache_file, dest_dir)?;
-                        return Ok(true);
-                    }
-                    let _ = tokio::fs::remove_file(&cache_file).await;
-                } else {
-                    self.extract_archive(&cache_file, dest_dir)?;
+                // A cached archive that fails verification is downloaded again
+                if self.verify_archive(package, dist, &cache_file).await.is_ok() {
+                    Cache::touch(&cache_file);
+                    self.progress.report(ProgressEvent::Extracting { package: package.name.clone() });
+                    self.extract_archive(&cache_file, dest_dir).await?;
                     return Ok(true);
                 }
+                let _ = tokio::fs::remove_file(&cache_file).await;
             }
 
+            let start = std::time::Instant::now();
             let result = self
                 .file_downloader
-                .download(url, &cache_file, None::<fn(u64, u64)>)
+                .download(url, &cache_file, Some(|bytes, total| self.report_download(package, bytes, total)))
                 .await;
+            self.profiler.record("downloading", start.elapsed());
 
-            if let Err(e) = result {
-                eprintln!("Warning: Failed to download from {}: {}", url, e);
-                continue;
+            let resumed = match result {
+                Ok(resumed) => resumed,
+                Err(e) => {
+                    if urls.len() > 1 {
+                        self.io.write_error(&format!("Warning: {}", e));
+                    }
+                    last_error = Some(e);
+                    continue;
+                }
+            };
+
+            let mut verified = self.verify_archive(package, dist, &cache_file).await;
+            if verified.is_err() && resumed {
+                // The continued download doesn't match, fetch it in one piece
+                let _ = tokio::fs::remove_file(&cache_file).await;
+                let progress = |bytes, total| self.report_download(package, bytes, total);
+                verified = match self.file_downloader.download(url, &cache_file, Some(progress)).await {
+                    Ok(_) => self.verify_archive(package, dist, &cache_file).await,
+                    Err(e) => Err(e),
+                };
             }
 
-            // Verify checksum if available
-            if let Some(checksum) = checksum {
-                let checksum_type = ChecksumType::from_hex_length(checksum.len())
-                    .unwrap_or(ChecksumType::Sha256);
-
-                if !verify_checksum(&cache_file, checksum, checksum_type).await? {
-                    let _ = tokio::fs::remove_file(&cache_file).await;
-                    return Err(ComposerError::ChecksumMismatch {
-                        package: package.name.clone(),
-                    });
+            // A corrupt archive from one mirror may still be intact on the next
+            if let Err(e) = verified {
+                let _ = tokio::fs::remove_file(&cache_file).await;
+                if urls.len() > 1 {
+                    self.io.write_error(&format!("Warning: {} downloaded from {}", e, url));
                 }
+                last_error = Some(e);
+                continue;
             }
 
             // Extract the archive
-            self.extract_archive(&cache_file, dest_dir)?;
+            self.progress.report(ProgressEvent::Extracting { package: package.name.clone() });
+            self.extract_archive(&cache_file, dest_dir).await?;
             return Ok(false);
         }
 
-        Err(ComposerError::DownloadFailed {
-            package: package.name.clone(),
-            reason: "All download URLs failed".to_string(),
+        Err(match last_error {
+            Some(e) if urls.len() == 1 => e,
+            _ => ComposerError::DownloadFailed {
+                package: package.name.clone(),
+                reason: "All download URLs failed".to_string(),
+            },
         })
     }
 
+    /// Reject insecure URLs unless `secure-http` is disabled
+    fn check_secure_url(&self, url: &str) -> Result<()> {
+        if self.config.secure_http && is_insecure_url(url) {
+            return Err(ComposerError::InsecureUrl { url: url.to_string() });
+        }
+        Ok(())
+    }
+
+    /// Verify the checksums and signature of a downloaded archive
+    async fn verify_archive(&self, package: &Package, dist: &Dist, file: &Path) -> Result<()> {
+        if !self.config.verify {
+            return Ok(());
+        }
+
+        let checksums = [dist.shasum.as_ref(), dist.sha256.as_ref()];
+        for checksum in checksums.into_iter().flatten().filter(|s| !s.is_empty()) {
+            let checksum_type = ChecksumType::from_hex_length(checksum.len()).ok_or_else(|| {
+                ComposerError::DownloadFailed {
+                    package: package.name.clone(),
+                    reason: format!("Unsupported checksum \"{}\"", checksum),
+                }
+            })?;
+
+            if !verify_checksum(file, checksum, checksum_type).await? {
+                return Err(ComposerError::ChecksumMismatch {
+                    package: package.name.clone(),
+                });
+            }
+        }
+
+        let Some(signature) = &dist.signature else {
+            return Ok(());
+        };
+        // Only the keys of the repository serving the package may vouch for it
+        let Some(repository) = self.config.signing_keys.iter().find(|r| r.serves(package)) else {
+            log::debug!("No signing keys configured for the repository of {}, skipping signature check", package.name);
+            return Ok(());
+        };
+
+        let signature_error = |reason: String| ComposerError::SignatureMismatch {
+            package: package.name.clone(),
+            reason,
+        };
+
+        let keys = repository
+            .keys
+            .iter()
+            .map(|k| MinisignPublicKey::parse(k))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| signature_error(e.to_string()))?;
+
+        let content = if signature.starts_with("https://") || signature.starts_with("http://") {
+            let bytes = self
+                .http_client
+                .download_bytes(signature)
+                .await
+                .map_err(|e| signature_error(format!("failed to fetch {}: {}", signature, e)))?;
+            String::from_utf8_lossy(&bytes).into_owned()
+        } else {
+            signature.clone()
+        };
+
+        let signature = MinisignSignature::parse(&content).map_err(|e| signature_error(e.to_string()))?;
+        let data = tokio::fs::read(file).await?;
+        verify_signature(&data, &signature, &keys).map_err(|e| signature_error(e.to_string()))
+    }
+
     /// Download from source (git)
     async fn download_from_source(
         &self,
@@ -225,13 +382,26 @@ impl DownloadManager {
 
         match source.source_type.as_str() {
             "git" => {
+                // A clone needs an empty target, clear leftovers of a previous dist install
+                if dest_dir.exists() {
+                    tokio::fs::remove_dir_all(dest_dir).await?;
+                }
+
                 // Try URLs in order
                 for url in source.urls() {
-                    let result = self.git_downloader.clone(
-                        &url,
-                        dest_dir,
-                        Some(&source.reference),
-                    );
+                    if let Err(e) = self.check_secure_url(&url) {
+                        self.io.write_error(&format!("Warning: {}", e));
+                        continue;
+                    }
+
+                    let start = std::time::Instant::now();
+                    // libgit2 and the mirror lock block, keep them off the async workers
+                    let git = Arc::clone(&self.git_downloader);
+                    let (dest, reference) = (dest_dir.to_path_buf(), source.reference.clone());
+                    let result = tokio::task::spawn_blocking(move || GitDownloader::clone(&git, &url, &dest, Some(&reference)))
+                        .await
+                        .map_err(|e| ComposerError::InstallationFailed(format!("Git task failed: {}", e)))?;
+                    self.profiler.record("downloading", start.elapsed());
 
                     if result.is_ok() {
                         return Ok(());
@@ -250,6 +420,58 @@ impl DownloadManager {
         }
     }
 
+    /// Install a package without dist from a zip archive of its source
+    ///
+    /// The archive is built from a checkout of the reference once and cached
+    /// by source URL and reference, so later installs skip the clone like a
+    /// regular dist. Returns true if the archive was taken from the cache.
+    async fn download_from_source_archive(
+        &self,
+        package: &Package,
+        source: &Source,
+        dest_dir: &Path,
+    ) -> Result<bool> {
+        let cache_file = self.source_archive_path(source);
+        if cache_file.exists() {
+            Cache::touch(&cache_file);
+            self.progress.report(ProgressEvent::Extracting { package: package.name.clone() });
+            self.extract_archive(&cache_file, dest_dir).await?;
+            return Ok(true);
+        }
+
+        let checkout = tempfile::TempDir::new()?;
+        let checkout_dir = checkout.path().join("source");
+        self.download_from_source(package, source, &checkout_dir).await?;
+
+        // Without a writable cache the package is installed from a clone as before
+        if let Err(e) = self.write_source_archive(&checkout_dir, &cache_file).await {
+            log::debug!("Failed to cache archive of {} ({}): {}", package.name, source.reference, e);
+            self.download_from_source(package, source, dest_dir).await?;
+            return Ok(false);
+        }
+
+        self.progress.report(ProgressEvent::Extracting { package: package.name.clone() });
+        self.extract_archive(&cache_file, dest_dir).await?;
+        Ok(false)
+    }
+
+    /// Archive a checkout to `cache_file`, written next to it first so
+    /// concurrent installs never see a partial archive
+    async fn write_source_archive(&self, checkout_dir: &Path, cache_file: &Path) -> Result<()> {
+        let parent = cache_file.parent().unwrap_or(Path::new("."));
+        tokio::fs::create_dir_all(parent).await?;
+        let partial = tempfile::NamedTempFile::new_in(parent)?;
+
+        let source = checkout_dir.to_path_buf();
+        let target = partial.path().to_path_buf();
+        tokio::task::spawn_blocking(move || create_archive(&source, &target, ArchiveFormat::Zip, &[]))
+            .await
+            .map_err(|e| ComposerError::InstallationFailed(format!("Archive task failed: {}", e)))??;
+
+        partial.persist(cache_file).map_err(|e| ComposerError::Io(e.error))?;
+        Ok(())
+    }
+
     /// Download from path (local directory)
     async fn download_from_path(
         &self,
@@ -258,23 +480,7 @@ impl DownloadManager {
         dest_dir: &Path,
     ) -> Result<DownloadResult> {
         let source_path = PathBuf::from(&dist.url);
-
-        // Determine strategy from transport options
-        let strategy = dist.transport_options.as_ref()
-            .and_then(|opts| opts.get("symlink"))
-            .and_then(|v| v.as_bool())
-            .map(|symlink| {
-                if symlink {
-                    PathStrategy::Symlink
-                } else {
-                    PathStrategy::Mirror
-                }
-            });
-
-        let relative = dist.transport_options.as_ref()
-            .and_then(|opts| opts.get("relative"))
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
+        let (strategy, relative) = path_options(dist);
 
         // Create parent directory if needed
         if let Some(parent) = dest_dir.parent() {
@@ -290,20 +496,56 @@ impl DownloadManager {
         })
     }
 
-    /// Extract an archive to destination
-    fn extract_archive(&self, archive_path: &Path, dest_dir: &Path) -> Result<()> {
-        // Clean destination if it exists
-        if dest_dir.exists() {
-            std::fs::remove_dir_all(dest_dir)?;
+    /// Check whether an installed package still matches what would be installed
+    ///
+    /// Only packages from path repositories can go stale without a version change:
+    /// a mirror when its source changes, and either kind when the strategy changes.
+    pub fn is_up_to_date(&self, package: &Package) -> bool {
+        match &package.dist {
+            Some(dist) if dist.dist_type == "path" => {
+                let (strategy, _) = path_options(dist);
+                self.path_downloader.is_up_to_date(Path::new(&dist.url), &self.package_path(package), strategy)
+            }
+            _ => true,
         }
-        std::fs::create_dir_all(dest_dir)?;
+    }
 
-        ArchiveExtractor::extract(archive_path, dest_dir)
+    /// Report the bytes of a package archive downloaded so far
+    fn report_download(&self, package: &Package, bytes: u64, total: u64) {
+        self.progress.report(ProgressEvent::Downloading {
+            package: package.name.clone(),
+            bytes,
+            total: (total > 0).then_some(total),
+        });
+    }
+
+    /// Extract an archive to destination on the blocking thread pool,
+    /// at most `max_parallel_extractions` at a time
+    async fn extract_archive(&self, archive_path: &Path, dest_dir: &Path) -> Result<()> {
+        let _permit = self.extract_permits.acquire().await
+            .map_err(|e| ComposerError::InstallationFailed(format!("Failed to schedule extraction: {}", e)))?;
+
+        let archive_path = archive_path.to_path_buf();
+        let dest_dir = dest_dir.to_path_buf();
+        let start = std::time::Instant::now();
+        let result = tokio::task::spawn_blocking(move || {
+            // Clean destination if it exists
+            if dest_dir.exists() {
+                std::fs::remove_dir_all(&dest_dir)?;
+            }
+            std::fs::create_dir_all(&dest_dir)?;
+
+            ArchiveExtractor::extract(&archive_path, &dest_dir)
+        })
+        .await
+        .map_err(|e| ComposerError::InstallationFailed(format!("Extraction task failed: {}", e)))?;
+        self.profiler.record("extracting", start.elapsed());
+        result
     }
 
     /// Get the path where a package should be installed
     fn package_path(&self, package: &Package) -> PathBuf {
-        self.config.vendor_dir.join(&package.name)
+        self.config.installer_paths.resolve(package, &self.config.vendor_dir)
     }
 
     /// Get the cache path for a package archive
@@ -313,8 +555,27 @@ impl DownloadManager {
         self.config.cache_dir.join("files").join(&package.name).join(filename)
     }
 
+    /// Get the cache path for the archive built from a source reference
+    fn source_archive_path(&self, source: &Source) -> PathBuf {
+        use sha1::{Digest, Sha1};
+
+        let url = format!("{:x}", Sha1::digest(source.url.as_bytes()));
+        let reference: String = source.reference.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '_' { c } else { '-' })
+            .collect();
+        self.config.cache_dir.join("files").join("_vcs").join(url).join(format!("{}.zip", reference))
+    }
+
     /// Determine if source should be used for a package
     fn should_use_source(&self, package: &Package) -> bool {
+        if let Some(preferred) = self.preferred_install_for(&package.name) {
+            return match preferred {
+                PreferredInstall::Source => package.source.is_some(),
+                PreferredInstall::Dist => false,
+                PreferredInstall::Auto => package.is_dev(),
+            };
+        }
+
         // Always use source for dev packages
         if package.is_dev() {
             return true;
@@ -328,6 +589,15 @@ impl DownloadManager {
         false
     }
 
+    /// Find the `preferred-install` override matching a package name
+    fn preferred_install_for(&self, name: &str) -> Option<&PreferredInstall> {
+        self.config
+            .preferred_install
+            .iter()
+            .find(|(pattern, _)| matches_update_pattern(pattern, name))
+            .map(|(_, preferred)| preferred)
+    }
+
     /// Remove a package
     pub async fn remove(&self, package: &Package) -> Result<()> {
         let dest_dir = self.package_path(package);
@@ -340,7 +610,34 @@ impl DownloadManager {
     }
 
     /// Update a package (remove old, install new)
+    ///
+    /// Packages installed from git are fetched and checked out at the new reference
+    /// in place, so the `.git` directory and local history are kept.
     pub async fn update(&self, old: &Package, new: &Package) -> Result<DownloadResult> {
+        let dest_dir = self.package_path(new);
+        if let Some(source) = new.source.as_ref().filter(|s| s.source_type == "git") {
+            let same_repo = old.source.as_ref().is_some_and(|s| s.url == source.url);
+            if same_repo
+                && self.should_use_source(new)
+                && dest_dir == self.package_path(old)
+                && GitDownloader::is_git_repo(&dest_dir)
+            {
+                log::debug!("Updating {} to {} from source", new.name, source.reference);
+                let git = Arc::clone(&self.git_downloader);
+                let (dest, reference) = (dest_dir.clone(), source.reference.clone());
+                let updated = tokio::task::spawn_blocking(move || git.update(&dest, Some(&reference)))
+                    .await
+                    .is_ok_and(|result| result.is_ok());
+                if updated {
+                    return Ok(DownloadResult {
+                        path: dest_dir,
+                        from_cache: false,
+                        skipped: false,
+                    });
+                }
+            }
+        }
+
         // Remove old package
         self.remove(old).await?;
 
@@ -349,6 +646,29 @@ impl DownloadManager {
     }
 }
 
+/// Plain http and unauthenticated git protocol URLs
+fn is_insecure_url(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("git://")
+}
+
+/// Strategy required by the `symlink` option and the `relative` option of a path dist
+fn path_options(dist: &Dist) -> (Option<PathStrategy>, bool) {
+    let option = |name: &str| dist.transport_options.as_ref()
+        .and_then(|opts| opts.get(name))
+        .and_then(|v| v.as_bool());
+
+    let strategy = option("symlink").map(|symlink| {
+        if symlink {
+            PathStrategy::Symlink
+        } else {
+            PathStrategy::Mirror
+        }
+    });
+
+    (strategy, option("relative").unwrap_or(false))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -360,6 +680,34 @@ mod tests {
         assert!(!config.prefer_source);
     }
 
+    #[test]
+    fn test_is_insecure_url() {
+        assert!(is_insecure_url("http://example.com/package.zip"));
+        assert!(is_insecure_url("HTTP://example.com/package.zip"));
+        assert!(is_insecure_url("git://github.com/vendor/package.git"));
+        assert!(!is_insecure_url("https://example.com/package.zip"));
+        assert!(!is_insecure_url("git@github.com:vendor/package.git"));
+        assert!(!is_insecure_url("/local/path/package.zip"));
+    }
+
+    #[test]
+    fn test_check_secure_url() {
+        let client = Arc::new(HttpClient::new().unwrap());
+        let manager = DownloadManager::new(client.clone(), DownloadConfig::default());
+        assert!(matches!(
+            manager.check_secure_url("http://example.com/package.zip"),
+            Err(ComposerError::InsecureUrl { .. })
+        ));
+        assert!(manager.check_secure_url("https://example.com/package.zip").is_ok());
+
+        let config = DownloadConfig {
+            secure_http: false,
+            ..Default::default()
+        };
+        let manager = DownloadManager::new(client, config);
+        assert!(manager.check_secure_url("http://example.com/package.zip").is_ok());
+    }
+
     #[test]
     fn test_package_path() {
         let client = Arc::new(HttpClient::new().unwrap());
@@ -426,4 +774,213 @@ mod tests {
 
         assert!(manager.should_use_source(&package));
     }
+
+    #[test]
+    fn test_should_use_source_preferred_install_patterns() {
+        let client = Arc::new(HttpClient::new().unwrap());
+        let config = DownloadConfig {
+            preferred_install: vec![
+                ("my-org/*".to_string(), PreferredInstall::Source),
+                ("*".to_string(), PreferredInstall::Dist),
+            ],
+            ..Default::default()
+        };
+        let manager = DownloadManager::new(client, config);
+
+        let mut own = Package::new("my-org/library", "1.0.0");
+        own.source = Some(Source::git("https://github.com/my-org/library.git", "abc123"));
+        assert!(manager.should_use_source(&own));
+
+        // Dist pattern wins even for dev versions
+        let mut other = Package::new("vendor/package", "dev-main");
+        other.source = Some(Source::git("https://github.com/vendor/package.git", "abc123"));
+        assert!(!manager.should_use_source(&other));
+
+        // Source preference without a source falls back to dist
+        let no_source = Package::new("my-org/other", "1.0.0");
+        assert!(!manager.should_use_source(&no_source));
+    }
+
+    fn commit_file(repo: &git2::Repository, name: &str, content: &str) -> String {
+        let workdir = repo.workdir().unwrap();
+        std::fs::write(workdir.join(name), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        let parents: Vec<git2::Commit> = repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, "commit", &tree, &parents)
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_source_install_checks_out_reference_and_updates_in_place() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let origin = temp_dir.path().join("origin");
+        let repo = git2::Repository::init(&origin).unwrap();
+        let first = commit_file(&repo, "README", "first");
+        let second = commit_file(&repo, "README", "second");
+        let url = origin.to_string_lossy().to_string();
+
+        let client = Arc::new(HttpClient::new().unwrap());
+        let config = DownloadConfig {
+            prefer_source: true,
+            prefer_dist: false,
+            vendor_dir: temp_dir.path().join("vendor"),
+            ..Default::default()
+        };
+        let manager = DownloadManager::new(client, config);
+
+        let mut old = Package::new("vendor/package", "1.0.0");
+        old.source = Some(Source::git(url.clone(), first.clone()));
+        let result = manager.download(&old).await.unwrap();
+
+        assert!(result.path.join(".git").exists());
+        assert_eq!(GitDownloader::get_head_commit(&result.path).unwrap(), first);
+        assert_eq!(std::fs::read_to_string(result.path.join("README")).unwrap(), "first");
+
+        std::fs::write(result.path.join("local.txt"), "untracked").unwrap();
+
+        let mut new = Package::new("vendor/package", "1.1.0");
+        new.source = Some(Source::git(url, second.clone()));
+        let result = manager.update(&old, &new).await.unwrap();
+
+        assert_eq!(GitDownloader::get_head_commit(&result.path).unwrap(), second);
+        assert_eq!(std::fs::read_to_string(result.path.join("README")).unwrap(), "second");
+        assert!(result.path.join("local.txt").exists());
+    }
+
+    #[test]
+    fn test_source_archive_path() {
+        let client = Arc::new(HttpClient::new().unwrap());
+        let config = DownloadConfig { cache_dir: PathBuf::from("/cache"), ..Default::default() };
+        let manager = DownloadManager::new(client, config);
+
+        let source = Source::git("https://github.com/vendor/package.git", "feature/x");
+        let path = manager.source_archive_path(&source);
+        assert_eq!(path.file_name().unwrap(), "feature-x.zip");
+        assert!(path.starts_with("/cache/files/_vcs"));
+
+        let other = Source::git("https://gitlab.com/vendor/package.git", "feature/x");
+        assert_ne!(manager.source_archive_path(&other), path);
+    }
+
+    #[tokio::test]
+    async fn test_source_without_dist_is_cached_as_archive() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let origin = temp_dir.path().join("origin");
+        let repo = git2::Repository::init(&origin).unwrap();
+        let reference = commit_file(&repo, "README", "hello");
+        let source = Source::git(origin.to_string_lossy().to_string(), reference);
+
+        let client = Arc::new(HttpClient::new().unwrap());
+        let config = DownloadConfig {
+            cache_dir: temp_dir.path().join("cache"),
+            vendor_dir: temp_dir.path().join("vendor"),
+            ..Default::default()
+        };
+        let manager = DownloadManager::new(client.clone(), config.clone());
+
+        let mut package = Package::new("vendor/package", "1.0.0");
+        package.source = Some(source.clone());
+        let result = manager.download(&package).await.unwrap();
+
+        assert!(!result.from_cache);
+        assert!(!result.path.join(".git").exists());
+        assert_eq!(std::fs::read_to_string(result.path.join("README")).unwrap(), "hello");
+        assert!(manager.source_archive_path(&source).exists());
+
+        // Another project installs the same reference without cloning
+        std::fs::remove_dir_all(&origin).unwrap();
+        let config = DownloadConfig { vendor_dir: temp_dir.path().join("other"), ..config };
+        let manager = DownloadManager::new(client, config);
+        let result = manager.download(&package).await.unwrap();
+
+        assert!(result.from_cache);
+        assert_eq!(std::fs::read_to_string(result.path.join("README")).unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_verify_archive_checksums() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive = temp_dir.path().join("package.zip");
+        std::fs::write(&archive, b"hello").unwrap();
+        let package = Package::new("vendor/package", "1.0.0");
+
+        let client = Arc::new(HttpClient::new().unwrap());
+        let manager = DownloadManager::new(client.clone(), DownloadConfig::default());
+
+        let valid = Dist::zip("https://example.com/a.zip")
+            .with_shasum("aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d");
+        assert!(manager.verify_archive(&package, &valid, &archive).await.is_ok());
+
+        let mismatch = Dist::zip("https://example.com/a.zip")
+            .with_shasum("aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d")
+            .with_sha256("0000000000000000000000000000000000000000000000000000000000000000");
+        assert!(matches!(
+            manager.verify_archive(&package, &mismatch, &archive).await,
+            Err(ComposerError::ChecksumMismatch { .. })
+        ));
+
+        let unsupported = Dist::zip("https://example.com/a.zip").with_shasum("abc");
+        assert!(matches!(
+            manager.verify_archive(&package, &unsupported, &archive).await,
+            Err(ComposerError::DownloadFailed { .. })
+        ));
+
+        let config = DownloadConfig { verify: false, ..Default::default() };
+        let manager = DownloadManager::new(client, config);
+        assert!(manager.verify_archive(&package, &mismatch, &archive).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verify_archive_signature() {
+        use crate::downloader::signature::tests::{public_key, sign, signing_key};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive = temp_dir.path().join("package.zip");
+        std::fs::write(&archive, b"archive").unwrap();
+        let mut package = Package::new("vendor/package", "1.0.0");
+        package.notification_url = Some("https://repo.example.com/downloads/".to_string());
+
+        let signing = signing_key();
+        let client = Arc::new(HttpClient::new().unwrap());
+        let config = DownloadConfig {
+            signing_keys: vec![RepositorySigningKeys {
+                url: "https://repo.example.com".to_string(),
+                keys: vec![public_key(&signing)],
+            }],
+            ..Default::default()
+        };
+        let manager = DownloadManager::new(client.clone(), config);
+
+        let signed = Dist::zip("https://example.com/a.zip")
+            .with_signature(sign(&signing, b"archive", true, "file:package.zip"));
+        assert!(manager.verify_archive(&package, &signed, &archive).await.is_ok());
+
+        let wrong = Dist::zip("https://example.com/a.zip")
+            .with_signature(sign(&signing, b"other", true, "file:package.zip"));
+        assert!(matches!(
+            manager.verify_archive(&package, &wrong, &archive).await,
+            Err(ComposerError::SignatureMismatch { .. })
+        ));
+
+        // A key of another repository can't verify the package, so its signature is ignored
+        let mut foreign = Package::new("vendor/foreign", "1.0.0");
+        foreign.notification_url = Some("https://other.example.com/downloads/".to_string());
+        assert!(manager.verify_archive(&foreign, &signed, &archive).await.is_ok());
+        assert!(manager.verify_archive(&foreign, &wrong, &archive).await.is_ok());
+
+        // Without configured keys signatures cannot be checked and are ignored
+        let manager = DownloadManager::new(client, DownloadConfig::default());
+        assert!(manager.verify_archive(&package, &wrong, &archive).await.is_ok());
+    }
 }
@@ -9,10 +9,14 @@ mod git;
 mod manager;
 mod checksum;
 mod path;
+mod signature;
 
 pub use archive::{ArchiveExtractor, ArchiveType};
-pub use file::FileDownloader;
+pub use file::{FileDownloader, DEFAULT_DOWNLOAD_RETRIES};
 pub use git::GitDownloader;
 pub use manager::{DownloadManager, DownloadResult, DownloadConfig};
 pub use checksum::{verify_checksum, ChecksumType};
 pub use path::{PathDownloader, PathStrategy, PathInstallResult};
+pub use signature::{
+    verify_signature, MinisignPublicKey, MinisignSignature, RepositorySigningKeys, SignatureAlgorithm, SignatureError,
+};
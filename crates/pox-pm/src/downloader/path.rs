@@ -1,4 +1,10 @@
 //! Path downloader - installs packages from local paths using symlinks or mirroring.
+//!
+//! Like Composer, the `symlink` option of a path repository selects the strategy:
+//! `true` only symlinks, `false` only mirrors, and without the option packages are
+//! symlinked with a fallback to mirroring. Setting `COMPOSER_MIRROR_PATH_REPOS=1`
+//! mirrors packages without the option. On Windows directories are linked with
+//! junctions, which don't need special privileges.
 
 use std::path::{Path, PathBuf};
 
@@ -36,6 +42,17 @@ impl PathDownloader {
         }
     }
 
+    /// Create a path downloader that mirrors by default when `COMPOSER_MIRROR_PATH_REPOS` is set
+    pub fn from_env() -> Self {
+        let mirror = std::env::var("COMPOSER_MIRROR_PATH_REPOS")
+            .is_ok_and(|v| !v.is_empty() && v != "0");
+        if mirror {
+            Self::with_strategy(PathStrategy::Mirror)
+        } else {
+            Self::new()
+        }
+    }
+
     /// Create a path downloader with a specific default strategy
     pub fn with_strategy(strategy: PathStrategy) -> Self {
         Self {
@@ -48,7 +65,8 @@ impl PathDownloader {
     /// # Arguments
     /// * `source` - Source path (the package location)
     /// * `dest` - Destination path (where to install)
-    /// * `strategy` - Optional strategy override
+    /// * `strategy` - Strategy required by the repository, without one the default
+    ///   strategy is used and a failing symlink falls back to mirroring
     /// * `relative` - Whether to use relative symlinks
     pub fn install(
         &self,
@@ -57,6 +75,7 @@ impl PathDownloader {
         strategy: Option<PathStrategy>,
         relative: bool,
     ) -> Result<PathInstallResult> {
+        let fallback = strategy.is_none();
         let strategy = strategy.unwrap_or(self.default_strategy);
 
         // Ensure source exists
@@ -82,14 +101,18 @@ impl PathDownloader {
         }
 
         match strategy {
-            PathStrategy::Symlink => {
-                self.create_symlink(source, dest, relative)?;
-                Ok(PathInstallResult {
+            PathStrategy::Symlink => match self.create_symlink(source, dest, relative) {
+                Ok(()) => Ok(PathInstallResult {
                     path: dest.to_path_buf(),
                     strategy: PathStrategy::Symlink,
                     relative,
-                })
-            }
+                }),
+                Err(e) if fallback => {
+                    log::debug!("Symlinking {} failed, mirroring instead: {}", source.display(), e);
+                    self.install(source, dest, Some(PathStrategy::Mirror), false)
+                }
+                Err(e) => Err(e),
+            },
             PathStrategy::Mirror => {
                 self.mirror_directory(source, dest)?;
                 Ok(PathInstallResult {
@@ -118,9 +141,12 @@ impl PathDownloader {
 
         #[cfg(windows)]
         {
-            // On Windows, use directory junction for better compatibility
+            // Junctions don't need the symlink privilege, but only take absolute targets
             if source.is_dir() {
-                std::os::windows::fs::symlink_dir(&link_target, dest)?;
+                let absolute = source.canonicalize()?;
+                if Self::create_junction(&absolute, dest).is_err() {
+                    std::os::windows::fs::symlink_dir(&link_target, dest)?;
+                }
             } else {
                 std::os::windows::fs::symlink_file(&link_target, dest)?;
             }
@@ -129,6 +155,32 @@ impl PathDownloader {
         Ok(())
     }
 
+    /// Create a directory junction with `mklink /J`
+    #[cfg(windows)]
+    fn create_junction(target: &Path, dest: &Path) -> Result<()> {
+        // canonicalize() returns a verbatim path, which mklink doesn't understand
+        let target = target.to_string_lossy();
+        let target = target.strip_prefix(r"\\?\").unwrap_or(&target);
+
+        let status = std::process::Command::new("cmd")
+            .arg("/C")
+            .arg("mklink")
+            .arg("/J")
+            .arg(dest)
+            .arg(target)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()?;
+
+        if !status.success() {
+            return Err(ComposerError::DownloadFailed {
+                package: dest.to_string_lossy().to_string(),
+                reason: "Failed to create junction".to_string(),
+            });
+        }
+        Ok(())
+    }
+
     /// Calculate relative path from `from` to `to`
     fn relative_path(from: &Path, to: &Path) -> Result<PathBuf> {
         let from_abs = from.parent()
@@ -210,6 +262,52 @@ impl PathDownloader {
         self.install(source, dest, strategy, relative)
     }
 
+    /// Check whether an installed package still matches its source
+    ///
+    /// A symlink is current as long as symlinking is the wanted strategy. A mirror
+    /// is outdated when the repository requires a symlink, or when a file was added,
+    /// removed or modified in the source since it was copied. Without a required
+    /// strategy a mirror may be the fallback of a failed symlink, so it is kept.
+    pub fn is_up_to_date(&self, source: &Path, dest: &Path, strategy: Option<PathStrategy>) -> bool {
+        if !dest.exists() {
+            return false;
+        }
+
+        if dest.is_symlink() {
+            return strategy.unwrap_or(self.default_strategy) == PathStrategy::Symlink;
+        }
+        if strategy == Some(PathStrategy::Symlink) {
+            return false;
+        }
+
+        let files = |root: &Path| {
+            WalkDir::new(root)
+                .follow_links(false)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .count()
+        };
+        if files(source) != files(dest) {
+            return false;
+        }
+
+        WalkDir::new(source)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .all(|entry| {
+                let relative = entry.path().strip_prefix(source).unwrap_or(entry.path());
+                let (Ok(source_meta), Ok(dest_meta)) = (entry.metadata(), std::fs::metadata(dest.join(relative))) else {
+                    return false;
+                };
+                // Copies are newer than their source until the source is modified
+                source_meta.len() == dest_meta.len()
+                    && matches!((source_meta.modified(), dest_meta.modified()), (Ok(s), Ok(d)) if s <= d)
+            })
+    }
+
     /// Remove an installed package
     pub fn remove(&self, path: &Path) -> Result<()> {
         if !path.exists() {
@@ -313,6 +411,56 @@ mod tests {
         assert!(dest.is_symlink());
     }
 
+    #[test]
+    fn test_default_strategy() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("source");
+        let dest = temp.path().join("dest");
+
+        create_test_package(&source);
+
+        let downloader = PathDownloader::with_strategy(PathStrategy::Mirror);
+        let result = downloader.install(&source, &dest, None, false).unwrap();
+        assert_eq!(result.strategy, PathStrategy::Mirror);
+        assert!(!dest.is_symlink());
+
+        // A strategy required by the repository wins over the default
+        let result = downloader.install(&source, &dest, Some(PathStrategy::Symlink), false).unwrap();
+        assert_eq!(result.strategy, PathStrategy::Symlink);
+        assert!(dest.is_symlink());
+    }
+
+    #[test]
+    fn test_is_up_to_date() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("source");
+        let dest = temp.path().join("dest");
+
+        create_test_package(&source);
+
+        let downloader = PathDownloader::new();
+        assert!(!downloader.is_up_to_date(&source, &dest, None));
+
+        downloader.install(&source, &dest, Some(PathStrategy::Symlink), false).unwrap();
+        assert!(downloader.is_up_to_date(&source, &dest, None));
+        assert!(!downloader.is_up_to_date(&source, &dest, Some(PathStrategy::Mirror)));
+
+        downloader.install(&source, &dest, Some(PathStrategy::Mirror), false).unwrap();
+        assert!(downloader.is_up_to_date(&source, &dest, Some(PathStrategy::Mirror)));
+        assert!(!downloader.is_up_to_date(&source, &dest, Some(PathStrategy::Symlink)));
+        // A mirror is what the symlink fallback of the default strategy leaves behind
+        assert!(downloader.is_up_to_date(&source, &dest, None));
+
+        std::fs::write(source.join("src/New.php"), "<?php class NewClass {}").unwrap();
+        assert!(!downloader.is_up_to_date(&source, &dest, Some(PathStrategy::Mirror)));
+
+        downloader.install(&source, &dest, Some(PathStrategy::Mirror), false).unwrap();
+        assert!(downloader.is_up_to_date(&source, &dest, Some(PathStrategy::Mirror)));
+
+        std::fs::write(source.join("src/Test.php"), "<?php class Test { const CHANGED = true; }").unwrap();
+        assert!(!downloader.is_up_to_date(&source, &dest, Some(PathStrategy::Mirror)));
+    }
+
     #[test]
     fn test_remove() {
         let temp = TempDir::new().unwrap();
@@ -0,0 +1,333 @@
+//! Minisign signature verification for dist archives.
+//!
+//! Repositories can sign their archives with [minisign](https://jedisct1.github.io/minisign/).
+//! The dist entry then carries a `signature` (inline or as URL to a `.minisig` file)
+//! that is checked against the signing keys configured for the repository.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use blake2::{Blake2b512, Digest};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use thiserror::Error;
+
+use crate::package::Package;
+
+/// Errors raised while parsing or verifying minisign data
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SignatureError {
+    #[error("invalid public key: {0}")]
+    InvalidPublicKey(String),
+
+    #[error("invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("signed with unknown key {0}")]
+    UnknownKey(String),
+
+    #[error("signature does not match the archive")]
+    Mismatch,
+
+    #[error("trusted comment signature does not match")]
+    TrustedCommentMismatch,
+}
+
+/// Signature algorithm identifier of a minisign signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureAlgorithm {
+    /// `Ed` - the message is signed directly
+    Pure,
+    /// `ED` - the BLAKE2b-512 hash of the message is signed
+    Prehashed,
+}
+
+/// A minisign public key
+#[derive(Debug, Clone)]
+pub struct MinisignPublicKey {
+    key_id: [u8; 8],
+    key: VerifyingKey,
+}
+
+impl MinisignPublicKey {
+    /// Parse a public key, either the bare base64 line or the full `.pub` file.
+    pub fn parse(input: &str) -> Result<Self, SignatureError> {
+        let line = payload_lines(input)
+            .next()
+            .ok_or_else(|| SignatureError::InvalidPublicKey("empty key".to_string()))?;
+        let bytes = STANDARD
+            .decode(line)
+            .map_err(|e| SignatureError::InvalidPublicKey(e.to_string()))?;
+
+        if bytes.len() != 42 || &bytes[..2] != b"Ed" {
+            return Err(SignatureError::InvalidPublicKey(
+                "unsupported key format".to_string(),
+            ));
+        }
+
+        let mut key_id = [0u8; 8];
+        key_id.copy_from_slice(&bytes[2..10]);
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&bytes[10..42]);
+        let key = VerifyingKey::from_bytes(&key)
+            .map_err(|e| SignatureError::InvalidPublicKey(e.to_string()))?;
+
+        Ok(Self { key_id, key })
+    }
+
+    /// Key id as printed by minisign (uppercase hex)
+    pub fn key_id(&self) -> String {
+        format_key_id(&self.key_id)
+    }
+}
+
+/// A parsed `.minisig` signature
+#[derive(Debug, Clone)]
+pub struct MinisignSignature {
+    pub algorithm: SignatureAlgorithm,
+    key_id: [u8; 8],
+    signature: Signature,
+    pub trusted_comment: String,
+    global_signature: Signature,
+}
+
+impl MinisignSignature {
+    /// Parse the content of a `.minisig` file.
+    pub fn parse(input: &str) -> Result<Self, SignatureError> {
+        let mut lines = input.lines().map(str::trim).filter(|l| !l.is_empty());
+        let invalid = |reason: &str| SignatureError::InvalidSignature(reason.to_string());
+
+        let mut line = lines.next().ok_or_else(|| invalid("empty signature"))?;
+        if line.starts_with("untrusted comment:") {
+            line = lines.next().ok_or_else(|| invalid("missing signature line"))?;
+        }
+
+        let bytes = STANDARD
+            .decode(line)
+            .map_err(|e| SignatureError::InvalidSignature(e.to_string()))?;
+        if bytes.len() != 74 {
+            return Err(invalid("unexpected signature length"));
+        }
+
+        let algorithm = match &bytes[..2] {
+            b"Ed" => SignatureAlgorithm::Pure,
+            b"ED" => SignatureAlgorithm::Prehashed,
+            _ => return Err(invalid("unsupported signature algorithm")),
+        };
+        let mut key_id = [0u8; 8];
+        key_id.copy_from_slice(&bytes[2..10]);
+        let signature = Signature::from_slice(&bytes[10..74])
+            .map_err(|e| SignatureError::InvalidSignature(e.to_string()))?;
+
+        let trusted_comment = lines
+            .next()
+            .and_then(|l| l.strip_prefix("trusted comment:"))
+            .map(|c| c.trim_start().to_string())
+            .ok_or_else(|| invalid("missing trusted comment"))?;
+
+        let global = lines.next().ok_or_else(|| invalid("missing global signature"))?;
+        let global = STANDARD
+            .decode(global)
+            .map_err(|e| SignatureError::InvalidSignature(e.to_string()))?;
+        let global_signature = Signature::from_slice(&global)
+            .map_err(|e| SignatureError::InvalidSignature(e.to_string()))?;
+
+        Ok(Self {
+            algorithm,
+            key_id,
+            signature,
+            trusted_comment,
+            global_signature,
+        })
+    }
+
+    /// Key id of the key that created the signature
+    pub fn key_id(&self) -> String {
+        format_key_id(&self.key_id)
+    }
+}
+
+/// Minisign public keys configured for one repository
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepositorySigningKeys {
+    /// URL of the repository
+    pub url: String,
+    /// Public keys trusted for the archives of this repository
+    pub keys: Vec<String>,
+}
+
+impl RepositorySigningKeys {
+    /// Whether the package was served by this repository.
+    ///
+    /// Locked packages don't record their repository, so it is recognized by
+    /// the notification or dist URL of the package lying below the repository URL.
+    pub fn serves(&self, package: &Package) -> bool {
+        let base = self.url.trim_end_matches('/');
+        let below_base = |url: &str| {
+            url.strip_prefix(base)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        };
+
+        !base.is_empty()
+            && (package.notification_url.as_deref().is_some_and(below_base)
+                || package.dist.as_ref().is_some_and(|d| below_base(&d.url)))
+    }
+}
+
+/// Verify `data` against a signature made by one of the trusted keys.
+pub fn verify_signature(
+    data: &[u8],
+    signature: &MinisignSignature,
+    keys: &[MinisignPublicKey],
+) -> Result<(), SignatureError> {
+    let key = keys
+        .iter()
+        .find(|k| k.key_id == signature.key_id)
+        .ok_or_else(|| SignatureError::UnknownKey(signature.key_id()))?;
+
+    let verified = match signature.algorithm {
+        SignatureAlgorithm::Pure => key.key.verify(data, &signature.signature),
+        SignatureAlgorithm::Prehashed => {
+            key.key.verify(&Blake2b512::digest(data), &signature.signature)
+        }
+    };
+    verified.map_err(|_| SignatureError::Mismatch)?;
+
+    // The trusted comment is bound to the signature by a second, global signature
+    let mut global = signature.signature.to_bytes().to_vec();
+    global.extend_from_slice(signature.trusted_comment.as_bytes());
+    key.key
+        .verify(&global, &signature.global_signature)
+        .map_err(|_| SignatureError::TrustedCommentMismatch)
+}
+
+fn payload_lines(input: &str) -> impl Iterator<Item = &str> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with("untrusted comment:"))
+}
+
+fn format_key_id(key_id: &[u8; 8]) -> String {
+    // minisign stores the key id little-endian and prints it as a number
+    key_id.iter().rev().map(|b| format!("{:02X}", b)).collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    const KEY_ID: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    pub(crate) fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7u8; 32])
+    }
+
+    pub(crate) fn public_key(signing: &SigningKey) -> String {
+        let mut bytes = b"Ed".to_vec();
+        bytes.extend_from_slice(&KEY_ID);
+        bytes.extend_from_slice(signing.verifying_key().as_bytes());
+        format!("untrusted comment: minisign public key\n{}\n", STANDARD.encode(bytes))
+    }
+
+    pub(crate) fn sign(signing: &SigningKey, data: &[u8], prehashed: bool, comment: &str) -> String {
+        let signature = if prehashed {
+            signing.sign(&Blake2b512::digest(data))
+        } else {
+            signing.sign(data)
+        };
+
+        let mut bytes = if prehashed { b"ED".to_vec() } else { b"Ed".to_vec() };
+        bytes.extend_from_slice(&KEY_ID);
+        bytes.extend_from_slice(&signature.to_bytes());
+
+        let mut global = signature.to_bytes().to_vec();
+        global.extend_from_slice(comment.as_bytes());
+        let global = signing.sign(&global);
+
+        format!(
+            "untrusted comment: signature from minisign secret key\n{}\ntrusted comment: {}\n{}\n",
+            STANDARD.encode(bytes),
+            comment,
+            STANDARD.encode(global.to_bytes())
+        )
+    }
+
+    #[test]
+    fn test_parse_public_key() {
+        let signing = signing_key();
+        let key = MinisignPublicKey::parse(&public_key(&signing)).unwrap();
+        assert_eq!(key.key_id(), "0807060504030201");
+
+        assert!(MinisignPublicKey::parse("not base64!").is_err());
+        assert!(MinisignPublicKey::parse(&STANDARD.encode(b"Ed1234")).is_err());
+    }
+
+    #[test]
+    fn test_verify_pure_and_prehashed() {
+        let signing = signing_key();
+        let keys = vec![MinisignPublicKey::parse(&public_key(&signing)).unwrap()];
+        let data = b"archive contents";
+
+        for prehashed in [false, true] {
+            let signature = MinisignSignature::parse(&sign(&signing, data, prehashed, "file:a.zip")).unwrap();
+            assert_eq!(signature.trusted_comment, "file:a.zip");
+            assert_eq!(verify_signature(data, &signature, &keys), Ok(()));
+            assert_eq!(
+                verify_signature(b"tampered", &signature, &keys),
+                Err(SignatureError::Mismatch)
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_rejects_unknown_key() {
+        let signing = signing_key();
+        let other = SigningKey::from_bytes(&[9u8; 32]);
+        let mut other_key = MinisignPublicKey::parse(&public_key(&other)).unwrap();
+        other_key.key_id = [0; 8];
+
+        let signature = MinisignSignature::parse(&sign(&signing, b"data", false, "c")).unwrap();
+        assert_eq!(
+            verify_signature(b"data", &signature, &[other_key]),
+            Err(SignatureError::UnknownKey("0807060504030201".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_repository_serves_its_own_packages() {
+        let repository = RepositorySigningKeys {
+            url: "https://repo.example.com/org-a/".to_string(),
+            keys: Vec::new(),
+        };
+
+        let mut notified = Package::new("vendor/a", "1.0.0");
+        notified.notification_url = Some("https://repo.example.com/org-a/downloads/".to_string());
+        assert!(repository.serves(&notified));
+
+        let mut dist = Package::new("vendor/b", "1.0.0");
+        dist.dist = Some(crate::package::Dist::zip("https://repo.example.com/org-a/dists/b.zip"));
+        assert!(repository.serves(&dist));
+
+        // Other repositories on the same host and lookalike paths are not served by it
+        let mut other = Package::new("vendor/c", "1.0.0");
+        other.notification_url = Some("https://repo.example.com/org-ab/downloads/".to_string());
+        other.dist = Some(crate::package::Dist::zip("https://repo.example.com/org-b/dists/c.zip"));
+        assert!(!repository.serves(&other));
+        assert!(!repository.serves(&Package::new("vendor/d", "1.0.0")));
+    }
+
+    #[test]
+    fn test_verify_rejects_modified_trusted_comment() {
+        let signing = signing_key();
+        let keys = vec![MinisignPublicKey::parse(&public_key(&signing)).unwrap()];
+
+        let signed = sign(&signing, b"data", true, "original");
+        let tampered = signed.replace("trusted comment: original", "trusted comment: changed");
+        let signature = MinisignSignature::parse(&tampered).unwrap();
+
+        assert_eq!(
+            verify_signature(b"data", &signature, &keys),
+            Err(SignatureError::TrustedCommentMismatch)
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::http::HttpError;
+
 #[derive(Error, Debug)]
 pub enum ComposerError {
     // JSON/parsing errors
@@ -20,14 +22,29 @@ pub enum ComposerError {
     #[error("Repository error: {0}")]
     Repository(String),
 
+    #[error("Invalid {repository} repository: {reason}")]
+    InvalidRepository { repository: String, reason: String },
+
     // Network errors
     #[error("Network error: {0}")]
     Network(#[from] reqwest::Error),
 
+    #[error("The \"{url}\" file could not be downloaded (HTTP {status})")]
+    HttpStatus { url: String, status: u16 },
+
+    #[error("The \"{url}\" file could not be downloaded: {reason}")]
+    TransportFailed { url: String, reason: String },
+
+    #[error("Invalid response from \"{url}\": {reason}")]
+    InvalidResponse { url: String, reason: String },
+
     // IO errors
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("Failed to parse {path}: {reason}")]
+    InvalidFile { path: String, reason: String },
+
     // Solver errors
     #[error("Could not resolve dependencies: {0}")]
     DependencyResolution(String),
@@ -39,6 +56,12 @@ pub enum ComposerError {
     #[error("Checksum mismatch for {package}")]
     ChecksumMismatch { package: String },
 
+    #[error("Signature verification failed for {package}: {reason}")]
+    SignatureMismatch { package: String, reason: String },
+
+    #[error("Your configuration does not allow connections to {url}. See https://getcomposer.org/doc/06-config.md#secure-http for details.")]
+    InsecureUrl { url: String },
+
     // Installation errors
     #[error("Installation failed: {0}")]
     InstallationFailed(String),
@@ -60,4 +83,91 @@ pub enum ComposerError {
     Git(#[from] git2::Error),
 }
 
+impl ComposerError {
+    /// Convert an HTTP client error for a request to `url`
+    pub fn from_http(url: &str, error: HttpError) -> Self {
+        match error {
+            HttpError::HttpStatus { status, url } => ComposerError::HttpStatus { url, status },
+            HttpError::Request(e) => ComposerError::TransportFailed { url: url.to_string(), reason: e.to_string() },
+            HttpError::MaxRetries { url } => ComposerError::TransportFailed {
+                url,
+                reason: "the maximum number of retries was exceeded".to_string(),
+            },
+            HttpError::JsonParse(reason) => ComposerError::InvalidResponse { url: url.to_string(), reason },
+            HttpError::Io(e) => ComposerError::Io(e),
+        }
+    }
+
+    /// What the user can do about the error, shown below it by the CLI
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            ComposerError::HttpStatus { url, status: 401 | 403 } => {
+                let host = url::Url::parse(url).ok()?.host_str()?.to_string();
+                if host == "packagist.com" || host.ends_with(".packagist.com") {
+                    return Some(format!(
+                        "Private Packagist needs an authentication token, e.g. `pox pm config --global http-basic.{host} token <token>` or `pox pm config --global bearer.{host} <token>`"
+                    ));
+                }
+                Some(format!(
+                    "Check the credentials for {host} in auth.json, e.g. `pox pm config --global http-basic.{host} <username> <password>` or `pox pm config --global bearer.{host} <token>`"
+                ))
+            }
+            ComposerError::HttpStatus { status: 404, .. } => {
+                Some("Check that the repository URL is correct and the package still exists".to_string())
+            }
+            ComposerError::HttpStatus { status: 500.., .. } => {
+                Some("The server failed to respond, try again later or run with -vvv for details".to_string())
+            }
+            ComposerError::Network(_) | ComposerError::TransportFailed { .. } => {
+                Some("Check your network connection and proxy settings, run with -vvv for details".to_string())
+            }
+            ComposerError::InvalidResponse { .. }
+            | ComposerError::DownloadFailed { .. }
+            | ComposerError::ChecksumMismatch { .. } => {
+                Some("A cached file may be corrupt, try clearing the cache with `pox pm clear-cache`".to_string())
+            }
+            ComposerError::InvalidRepository { .. } => {
+                Some("Check the \"repositories\" section of composer.json".to_string())
+            }
+            ComposerError::Git(_) => {
+                Some("Check that git can access the repository with your credentials, run with -vvv for details".to_string())
+            }
+            ComposerError::LockFileOutOfSync => {
+                Some("Run `pox update` or `pox update <package>` to update the lock file".to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, ComposerError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hints() {
+        let error = ComposerError::from_http(
+            "https://repo.example.com/p2/acme/lib.json",
+            HttpError::HttpStatus { status: 401, url: "https://repo.example.com/p2/acme/lib.json".to_string() },
+        );
+        assert_eq!(
+            error.to_string(),
+            "The \"https://repo.example.com/p2/acme/lib.json\" file could not be downloaded (HTTP 401)"
+        );
+        assert!(error.hint().unwrap().contains("http-basic.repo.example.com"));
+        assert!(error.hint().unwrap().contains("bearer.repo.example.com"));
+
+        let error = ComposerError::HttpStatus { url: "https://repo.packagist.com/acme/packages.json".to_string(), status: 401 };
+        assert!(error.hint().unwrap().contains("http-basic.repo.packagist.com token <token>"));
+
+        let error = ComposerError::InvalidResponse {
+            url: "https://repo.example.com/packages.json".to_string(),
+            reason: "expected value".to_string(),
+        };
+        assert!(error.hint().unwrap().contains("pox pm clear-cache"));
+
+        assert!(ComposerError::InvalidConstraint("^1.0 ||".to_string()).hint().is_none());
+    }
+}
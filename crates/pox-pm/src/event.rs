@@ -6,7 +6,8 @@
 use std::any::Any;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use crate::package::Package;
 
@@ -44,6 +45,11 @@ pub enum EventType {
     PostRootPackageInstall,
     PostCreateProject,
     PreOperationsExec,
+    PreFileDownload,
+    PrePackageInstall,
+    PostPackageInstall,
+    PrePackageUninstall,
+    PostPackageUninstall,
 }
 
 impl EventType {
@@ -62,6 +68,11 @@ impl EventType {
             EventType::PostRootPackageInstall => "post-root-package-install",
             EventType::PostCreateProject => "post-create-project-cmd",
             EventType::PreOperationsExec => "pre-operations-exec",
+            EventType::PreFileDownload => "pre-file-download",
+            EventType::PrePackageInstall => "pre-package-install",
+            EventType::PostPackageInstall => "post-package-install",
+            EventType::PrePackageUninstall => "pre-package-uninstall",
+            EventType::PostPackageUninstall => "post-package-uninstall",
         }
     }
 
@@ -80,6 +91,11 @@ impl EventType {
             EventType::PostRootPackageInstall,
             EventType::PostCreateProject,
             EventType::PreOperationsExec,
+            EventType::PreFileDownload,
+            EventType::PrePackageInstall,
+            EventType::PostPackageInstall,
+            EventType::PrePackageUninstall,
+            EventType::PostPackageUninstall,
         ]
     }
 }
@@ -405,6 +421,133 @@ impl ComposerEvent for PostCreateProjectEvent {
     }
 }
 
+/// Event fired before a package archive is downloaded.
+///
+/// Listeners can point the download to another URL, e.g. a mirror or CDN,
+/// with [`set_processed_url`](Self::set_processed_url).
+#[derive(Debug)]
+pub struct PreFileDownloadEvent {
+    pub package: Package,
+    /// The URL from the package metadata.
+    pub url: String,
+    processed_url: Mutex<String>,
+}
+
+impl PreFileDownloadEvent {
+    pub fn new(package: Package, url: impl Into<String>) -> Self {
+        let url = url.into();
+        Self { package, processed_url: Mutex::new(url.clone()), url }
+    }
+
+    /// The URL the archive is downloaded from.
+    pub fn processed_url(&self) -> String {
+        self.processed_url.lock().unwrap().clone()
+    }
+
+    /// Download the archive from another URL.
+    pub fn set_processed_url(&self, url: impl Into<String>) {
+        *self.processed_url.lock().unwrap() = url.into();
+    }
+}
+
+impl ComposerEvent for PreFileDownloadEvent {
+    fn event_type(&self) -> EventType {
+        EventType::PreFileDownload
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Event fired before a package is installed or updated to a new version.
+#[derive(Debug, Clone)]
+pub struct PrePackageInstallEvent {
+    pub package: Package,
+}
+
+impl PrePackageInstallEvent {
+    pub fn new(package: Package) -> Self {
+        Self { package }
+    }
+}
+
+impl ComposerEvent for PrePackageInstallEvent {
+    fn event_type(&self) -> EventType {
+        EventType::PrePackageInstall
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Event fired after a package is installed or updated to a new version.
+#[derive(Debug, Clone)]
+pub struct PostPackageInstallEvent {
+    pub package: Package,
+}
+
+impl PostPackageInstallEvent {
+    pub fn new(package: Package) -> Self {
+        Self { package }
+    }
+}
+
+impl ComposerEvent for PostPackageInstallEvent {
+    fn event_type(&self) -> EventType {
+        EventType::PostPackageInstall
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Event fired before a package is removed from vendor.
+#[derive(Debug, Clone)]
+pub struct PrePackageUninstallEvent {
+    pub package: Package,
+}
+
+impl PrePackageUninstallEvent {
+    pub fn new(package: Package) -> Self {
+        Self { package }
+    }
+}
+
+impl ComposerEvent for PrePackageUninstallEvent {
+    fn event_type(&self) -> EventType {
+        EventType::PrePackageUninstall
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Event fired after a package is removed from vendor.
+#[derive(Debug, Clone)]
+pub struct PostPackageUninstallEvent {
+    pub package: Package,
+}
+
+impl PostPackageUninstallEvent {
+    pub fn new(package: Package) -> Self {
+        Self { package }
+    }
+}
+
+impl ComposerEvent for PostPackageUninstallEvent {
+    fn event_type(&self) -> EventType {
+        EventType::PostPackageUninstall
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
 /// Trait for event listeners.
 ///
 /// Listeners receive the event, a reference to the Composer instance,
@@ -455,9 +598,23 @@ impl EventListener for ScriptEventListener {
     }
 }
 
+/// A listener registered with the dispatcher.
+struct RegisteredListener {
+    listener: Arc<dyn EventListener>,
+    /// Priority used for ordering (higher = earlier execution).
+    priority: i32,
+    /// Whether the listener is removed after its first invocation.
+    once: bool,
+    /// Set once a one-shot listener has been invoked.
+    fired: AtomicBool,
+}
+
 /// Event dispatcher that manages listeners and dispatches events.
+///
+/// Listeners run in descending priority order. Listeners with the same
+/// priority run in the order they were registered.
 pub struct EventDispatcher {
-    listeners: HashMap<EventType, Vec<Arc<dyn EventListener>>>,
+    listeners: HashMap<EventType, Vec<RegisteredListener>>,
 }
 
 impl EventDispatcher {
@@ -479,8 +636,48 @@ impl EventDispatcher {
     }
 
     /// Add a listener for a specific event type.
+    ///
+    /// The listener's own `priority()` determines its position.
     pub fn add_listener(&mut self, event_type: EventType, listener: Arc<dyn EventListener>) {
-        self.listeners.entry(event_type).or_default().push(listener);
+        let priority = listener.priority();
+        self.register(event_type, listener, priority, false);
+    }
+
+    /// Add a listener with an explicit priority, overriding the listener's own.
+    pub fn add_listener_with_priority(
+        &mut self,
+        event_type: EventType,
+        listener: Arc<dyn EventListener>,
+        priority: i32,
+    ) {
+        self.register(event_type, listener, priority, false);
+    }
+
+    /// Add a listener that is only invoked for the first dispatch of the event.
+    pub fn add_listener_once(&mut self, event_type: EventType, listener: Arc<dyn EventListener>) {
+        let priority = listener.priority();
+        self.register(event_type, listener, priority, true);
+    }
+
+    fn register(&mut self, event_type: EventType, listener: Arc<dyn EventListener>, priority: i32, once: bool) {
+        self.listeners.entry(event_type).or_default().push(RegisteredListener {
+            listener,
+            priority,
+            once,
+            fired: AtomicBool::new(false),
+        });
+    }
+
+    /// Check whether any listener is still pending for the event type.
+    pub fn has_listeners(&self, event_type: EventType) -> bool {
+        self.listeners
+            .get(&event_type)
+            .is_some_and(|listeners| listeners.iter().any(|l| !(l.once && l.fired.load(Ordering::SeqCst))))
+    }
+
+    /// Remove all listeners for an event type.
+    pub fn remove_listeners(&mut self, event_type: EventType) {
+        self.listeners.remove(&event_type);
     }
 
     /// Dispatch a typed event to all registered listeners.
@@ -497,11 +694,16 @@ impl EventDispatcher {
             return Ok(0);
         }
 
+        // Stable sort keeps registration order for equal priorities
         let mut sorted_listeners: Vec<_> = listeners.iter().collect();
-        sorted_listeners.sort_by(|a, b| b.priority().cmp(&a.priority()));
+        sorted_listeners.sort_by_key(|l| std::cmp::Reverse(l.priority));
 
-        for listener in sorted_listeners {
-            let exit_code = listener.handle(event, composer)?;
+        for registered in sorted_listeners {
+            if registered.once && registered.fired.swap(true, Ordering::SeqCst) {
+                continue;
+            }
+
+            let exit_code = registered.listener.handle(event, composer)?;
             if exit_code != 0 {
                 return Ok(exit_code);
             }
@@ -555,6 +757,32 @@ mod tests {
         assert_eq!(post.event_type(), EventType::PostArchive);
     }
 
+    #[test]
+    fn test_pre_file_download_rewrites_url() {
+        struct MirrorListener;
+        impl EventListener for MirrorListener {
+            fn handle(&self, event: &dyn ComposerEvent, _: &crate::composer::Composer) -> anyhow::Result<i32> {
+                if let Some(event) = event.as_any().downcast_ref::<PreFileDownloadEvent>() {
+                    let url = event.processed_url().replace("https://api.github.com", "https://mirror.test");
+                    event.set_processed_url(url);
+                }
+                Ok(0)
+            }
+        }
+
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher.add_listener(EventType::PreFileDownload, Arc::new(MirrorListener));
+
+        let url = "https://api.github.com/repos/vendor/package/zipball/abc";
+        let event = PreFileDownloadEvent::new(Package::new("vendor/package", "1.0.0"), url);
+        assert_eq!(event.processed_url(), url);
+        assert_eq!(event.script_name(), "pre-file-download");
+
+        dispatcher.dispatch(&event, &test_composer()).unwrap();
+        assert_eq!(event.url, url);
+        assert_eq!(event.processed_url(), "https://mirror.test/repos/vendor/package/zipball/abc");
+    }
+
     #[test]
     fn test_event_dispatcher_new() {
         let dispatcher = EventDispatcher::new();
@@ -574,4 +802,93 @@ mod tests {
         dispatcher.add_listener(EventType::PreInstall, Arc::new(DummyListener));
         assert_eq!(dispatcher.listeners.get(&EventType::PreInstall).unwrap().len(), 1);
     }
+
+    /// Listener that records its label into a shared log.
+    struct RecordingListener {
+        label: &'static str,
+        priority: i32,
+        log: Arc<std::sync::Mutex<Vec<&'static str>>>,
+    }
+
+    impl EventListener for RecordingListener {
+        fn handle(&self, _: &dyn ComposerEvent, _: &crate::composer::Composer) -> anyhow::Result<i32> {
+            self.log.lock().unwrap().push(self.label);
+            Ok(0)
+        }
+
+        fn priority(&self) -> i32 {
+            self.priority
+        }
+    }
+
+    fn test_composer() -> crate::composer::Composer {
+        crate::composer::Composer::builder(PathBuf::from("/tmp/test"))
+            .with_composer_json(crate::json::ComposerJson::default())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_event_dispatcher_priority_order() {
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let listener = |label, priority| {
+            Arc::new(RecordingListener { label, priority, log: log.clone() })
+        };
+
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher.add_listener(EventType::PreInstall, listener("default", 0));
+        dispatcher.add_listener(EventType::PreInstall, listener("high", 10));
+        dispatcher.add_listener(EventType::PreInstall, listener("default-second", 0));
+        dispatcher.add_listener_with_priority(EventType::PreInstall, listener("override", 0), -5);
+        dispatcher.add_listener_with_priority(EventType::PreInstall, listener("highest", 0), 100);
+
+        let composer = test_composer();
+        assert_eq!(dispatcher.dispatch(&PreInstallEvent::new(true), &composer).unwrap(), 0);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["highest", "high", "default", "default-second", "override"]
+        );
+    }
+
+    #[test]
+    fn test_event_dispatcher_once_listener() {
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher.add_listener_once(
+            EventType::PostInstall,
+            Arc::new(RecordingListener { label: "once", priority: 0, log: log.clone() }),
+        );
+        dispatcher.add_listener(
+            EventType::PostInstall,
+            Arc::new(RecordingListener { label: "always", priority: 0, log: log.clone() }),
+        );
+
+        let composer = test_composer();
+        let event = PostInstallEvent::new(true);
+        dispatcher.dispatch(&event, &composer).unwrap();
+        dispatcher.dispatch(&event, &composer).unwrap();
+
+        assert_eq!(*log.lock().unwrap(), vec!["once", "always", "always"]);
+    }
+
+    #[test]
+    fn test_event_dispatcher_has_listeners() {
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let mut dispatcher = EventDispatcher::new();
+        assert!(!dispatcher.has_listeners(EventType::PreUpdate));
+
+        dispatcher.add_listener_once(
+            EventType::PreUpdate,
+            Arc::new(RecordingListener { label: "once", priority: 0, log }),
+        );
+        assert!(dispatcher.has_listeners(EventType::PreUpdate));
+
+        dispatcher.dispatch(&PreUpdateEvent::new(true), &test_composer()).unwrap();
+        assert!(!dispatcher.has_listeners(EventType::PreUpdate));
+
+        dispatcher.remove_listeners(EventType::PreUpdate);
+        assert!(dispatcher.listeners.get(&EventType::PreUpdate).is_none());
+    }
 }
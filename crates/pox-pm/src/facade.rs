@@ -0,0 +1,646 @@
+//! High-level API for driving pox-pm from other Rust tools.
+//!
+//! IDEs, CI tools and build systems can install, update and add packages
+//! without shelling out to the CLI. Operations report progress and warnings
+//! through an [`Io`] implementation, see [`Io::on_progress`], and return an
+//! [`OperationReport`] describing what changed.
+//!
+//! ```no_run
+//! use pox_pm::Composer;
+//! use pox_pm::facade::{InstallOptions, NullIo};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let mut composer = Composer::open("path/to/project")?;
+//!
+//! let report = composer.install(&InstallOptions::default(), &NullIo).await?;
+//!
+//! println!("{} installed in {:?}", report.installed.len(), report.timings.total);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Platform packages (`php`, `ext-*`) are not detected by the library; set
+//! [`Composer::platform_packages`] or the `platform` config to describe the
+//! PHP runtime the project targets.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+
+use crate::composer::{Composer, ComposerBuilder};
+use crate::config::{composer_file_name, lock_file_name, Config};
+use crate::event::{
+    PostAutoloadDumpEvent, PostInstallEvent, PostUpdateEvent,
+    PreAutoloadDumpEvent, PreInstallEvent, PreUpdateEvent,
+};
+use crate::io::Io;
+use crate::installer::{new_suggestions, InstallationManager, Installer, SuggestedPackage, UpdatePlan};
+use crate::json::{ComposerJson, ComposerLock, JsonManipulator};
+use crate::package::{detect_root_version, Package};
+use crate::progress::ProgressReporter;
+use crate::repository::{InstalledRepository, Repository};
+use crate::solver::{Transaction, UpdateAllowTransitiveDeps};
+use crate::util::compute_content_hash;
+
+pub use crate::io::NullIo;
+pub use crate::progress::ProgressEvent;
+
+/// Options for [`Composer::install`].
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// Skip packages from `require-dev` and `autoload-dev` rules
+    pub no_dev: bool,
+    /// Report what would change without touching the vendor directory
+    pub dry_run: bool,
+    /// Install packages from source
+    pub prefer_source: bool,
+    /// Install packages from dist archives
+    pub prefer_dist: bool,
+    /// Skip checksum and signature verification of dist archives
+    pub no_verify: bool,
+    /// Skip autoloader generation
+    pub no_autoloader: bool,
+    /// Generate a classmap for PSR-0/PSR-4 packages
+    pub optimize_autoloader: bool,
+    /// Skip script execution
+    pub no_scripts: bool,
+}
+
+/// Options for [`Composer::update`].
+#[derive(Debug, Clone, Default)]
+pub struct UpdateOptions {
+    /// Options used when installing the resolved packages
+    pub install: InstallOptions,
+    /// Only write the lock file, do not install packages
+    pub lock_only: bool,
+    /// Prefer the lowest versions satisfying the constraints
+    pub prefer_lowest: bool,
+    /// Prefer stable versions, like `prefer-stable` in composer.json
+    pub prefer_stable: bool,
+    /// Which dependencies of the listed packages may be updated too
+    pub with_dependencies: UpdateAllowTransitiveDeps,
+}
+
+/// Options for [`Composer::add`].
+#[derive(Debug, Clone, Default)]
+pub struct AddOptions {
+    /// Add the package to `require-dev`
+    pub dev: bool,
+    /// Only change composer.json, do not update the lock file
+    pub no_update: bool,
+    /// Options for the update that follows
+    pub update: UpdateOptions,
+}
+
+/// Time spent in each phase of an operation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Timings {
+    /// Resolving dependencies (zero when installing from the lock file)
+    pub resolve: Duration,
+    /// Downloading and installing packages
+    pub install: Duration,
+    /// Generating the autoloader
+    pub autoload: Duration,
+    /// The whole operation
+    pub total: Duration,
+}
+
+/// Outcome of an install, update or add.
+#[derive(Debug, Clone, Default)]
+pub struct OperationReport {
+    /// Operations needed to get the vendor directory to the desired state
+    pub transaction: Transaction,
+    /// Packages that were installed
+    pub installed: Vec<Package>,
+    /// Packages that were updated (from, to)
+    pub updated: Vec<(Package, Package)>,
+    /// Packages that were removed
+    pub removed: Vec<Package>,
+    /// Whether the lock file changed (or would change in dry-run mode)
+    pub lock_file_changed: bool,
+    /// Packages suggested by the freshly installed direct dependencies
+    pub suggestions: Vec<SuggestedPackage>,
+    /// Warnings such as abandoned packages or a stale lock file
+    pub warnings: Vec<String>,
+    /// Time spent in each phase
+    pub timings: Timings,
+}
+
+impl OperationReport {
+    fn warn(&mut self, io: &dyn Io, message: String) {
+        io.write_error(&message);
+        self.warnings.push(message);
+    }
+}
+
+impl Composer {
+    /// Load the project in `working_dir`: its configuration, composer.json and
+    /// lock file, honoring the `COMPOSER` environment variable.
+    pub fn open(working_dir: impl Into<PathBuf>) -> Result<Self> {
+        let working_dir = working_dir.into();
+
+        let json_path = working_dir.join(composer_file_name());
+        let content = std::fs::read_to_string(&json_path)
+            .with_context(|| format!("Failed to read {}", json_path.display()))?;
+        let composer_json: ComposerJson = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", json_path.display()))?;
+
+        let lock_path = working_dir.join(lock_file_name());
+        let composer_lock: Option<ComposerLock> = if lock_path.exists() {
+            let content = std::fs::read_to_string(&lock_path)
+                .with_context(|| format!("Failed to read {}", lock_path.display()))?;
+            Some(serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", lock_path.display()))?)
+        } else {
+            None
+        };
+
+        let config = Config::build(Some(&working_dir), true)?;
+
+        ComposerBuilder::new(working_dir)
+            .with_config(config)
+            .with_composer_json(composer_json)
+            .with_composer_lock(composer_lock)
+            .build()
+    }
+
+    /// Install the packages from the lock file.
+    pub async fn install(&mut self, options: &InstallOptions, io: &dyn Io) -> Result<OperationReport> {
+        let started = Instant::now();
+        let lock = self.composer_lock.clone().context("No composer.lock file found")?;
+
+        let mut report = OperationReport::default();
+        if let Ok(content) = std::fs::read_to_string(self.working_dir.join(composer_file_name())) {
+            if !lock.content_hash.is_empty() && lock.content_hash != compute_content_hash(&content) {
+                report.warn(io, "The lock file is not up to date with the latest changes in composer.json.".to_string());
+            }
+        }
+
+        let installer = Installer::new(self.scoped(options, false, false, UpdateAllowTransitiveDeps::default()));
+        let result = forward_progress(&self.progress, io, run_install(&installer, &lock, options, io, &mut report)).await;
+        self.event_dispatcher = installer.into_composer().event_dispatcher;
+        result?;
+
+        report.timings.total = started.elapsed();
+        io.on_progress(&ProgressEvent::Finished);
+        Ok(report)
+    }
+
+    /// Resolve dependencies, write the lock file and install the result.
+    ///
+    /// `packages` limits the update to the listed packages (wildcards allowed),
+    /// `None` updates everything.
+    pub async fn update(&mut self, packages: Option<Vec<String>>, options: &UpdateOptions, io: &dyn Io) -> Result<OperationReport> {
+        let started = Instant::now();
+        let mut report = OperationReport::default();
+
+        let installer = Installer::new(self.scoped(&options.install, options.prefer_lowest, options.prefer_stable, options.with_dependencies));
+        let result = forward_progress(&self.progress, io, run_update(&installer, packages, options, io, &mut report)).await;
+        self.event_dispatcher = installer.into_composer().event_dispatcher;
+
+        if let Some(lock) = result? {
+            self.composer_lock = Some(lock);
+        }
+
+        report.timings.total = started.elapsed();
+        io.on_progress(&ProgressEvent::Finished);
+        Ok(report)
+    }
+
+    /// Require a package in composer.json and update it.
+    ///
+    /// composer.json is edited in place, keeping its formatting. A package
+    /// moving between `require` and `require-dev` is removed from the other one.
+    /// If the update fails, composer.json is restored.
+    pub async fn add(&mut self, name: &str, constraint: &str, options: &AddOptions, io: &dyn Io) -> Result<OperationReport> {
+        let (section, other) = if options.dev { ("require-dev", "require") } else { ("require", "require-dev") };
+        let json_path = self.working_dir.join(composer_file_name());
+        let original_json = self.composer_json.clone();
+
+        let mut original_content = None;
+        if !options.update.install.dry_run {
+            let content = std::fs::read_to_string(&json_path).unwrap_or_default();
+            let mut manipulator = JsonManipulator::new(&content)
+                .with_context(|| format!("Failed to parse {}", json_path.display()))?;
+            manipulator.set(&[section, name], &constraint)?;
+            manipulator.remove(&[other, name]);
+            std::fs::write(&json_path, manipulator.contents())
+                .with_context(|| format!("Failed to write {}", json_path.display()))?;
+            original_content = Some(content);
+        }
+
+        let (require, other_require) = if options.dev {
+            (&mut self.composer_json.require_dev, &mut self.composer_json.require)
+        } else {
+            (&mut self.composer_json.require, &mut self.composer_json.require_dev)
+        };
+        require.insert(name.to_string(), constraint.to_string());
+        other_require.shift_remove(name);
+
+        if options.no_update {
+            return Ok(OperationReport::default());
+        }
+
+        let result = self.update(Some(vec![name.to_string()]), &options.update, io).await;
+        if result.is_err() {
+            // Like `composer require`, don't leave a requirement behind that can't be installed
+            if let Some(content) = original_content {
+                std::fs::write(&json_path, content)
+                    .with_context(|| format!("Failed to restore {}", json_path.display()))?;
+            }
+            self.composer_json = original_json;
+        }
+        result
+    }
+
+    /// A Composer sharing this one's repositories and HTTP client, with an
+    /// installation manager configured for a single operation. The event
+    /// dispatcher is moved over and must be put back afterwards.
+    fn scoped(&mut self, options: &InstallOptions, prefer_lowest: bool, prefer_stable: bool, with_dependencies: UpdateAllowTransitiveDeps) -> Composer {
+        let mut builder = ComposerBuilder::new(self.working_dir.clone())
+            .dry_run(options.dry_run)
+            .no_dev(options.no_dev)
+            .no_verify(options.no_verify)
+            .prefer_lowest(prefer_lowest)
+            .prefer_stable(prefer_stable)
+            .update_with_dependencies(with_dependencies);
+        if options.prefer_source {
+            builder = builder.prefer_source(true);
+        } else if options.prefer_dist {
+            builder = builder.prefer_dist(true);
+        }
+
+        let install_config = builder.build_install_config(&self.config, &self.composer_json);
+
+        Composer {
+            config: self.config.clone(),
+            composer_json: self.composer_json.clone(),
+            composer_lock: self.composer_lock.clone(),
+            repository_manager: self.repository_manager.clone(),
+            installation_manager: Arc::new(InstallationManager::with_progress(
+                self.http_client.clone(),
+                install_config,
+                self.io.clone(),
+                self.profiler.clone(),
+                self.progress.clone(),
+            )),
+            http_client: self.http_client.clone(),
+            working_dir: self.working_dir.clone(),
+            platform_packages: self.platform_packages.clone(),
+            event_dispatcher: std::mem::take(&mut self.event_dispatcher),
+            io: self.io.clone(),
+            profiler: self.profiler.clone(),
+            progress: self.progress.clone(),
+        }
+    }
+}
+
+/// Run an operation, passing the progress reported by the library layers on to `io`
+async fn forward_progress<T>(progress: &ProgressReporter, io: &dyn Io, operation: impl Future<Output = T>) -> T {
+    let mut events = progress.subscribe();
+    tokio::pin!(operation);
+
+    let result = loop {
+        tokio::select! {
+            result = &mut operation => break result,
+            Some(event) = events.recv() => io.on_progress(&event),
+        }
+    };
+
+    // Dropping the receiver afterwards unsubscribes, other subscribers keep theirs
+    while let Ok(event) = events.try_recv() {
+        io.on_progress(&event);
+    }
+    result
+}
+
+async fn run_install(
+    installer: &Installer,
+    lock: &ComposerLock,
+    options: &InstallOptions,
+    io: &dyn Io,
+    report: &mut OperationReport,
+) -> Result<()> {
+    let composer = installer.composer();
+    let dev_mode = !options.no_dev;
+
+    if !options.no_scripts {
+        check_exit_code("pre-install-cmd", composer.dispatch(&PreInstallEvent::new(dev_mode))?)?;
+    }
+
+    let mut packages: Vec<Package> = lock.packages.iter().map(Package::from).collect();
+    if dev_mode {
+        packages.extend(lock.packages_dev.iter().map(Package::from));
+    }
+
+    if !installer.check_locked_packages(&packages) {
+        bail!("The locked packages violate the license policy");
+    }
+
+    let vendor_repo = InstalledRepository::new(composer.installation_manager.config().vendor_dir.clone());
+    vendor_repo.load().await.ok();
+    report.transaction = Transaction::from_packages(
+        vendor_repo.get_packages().await,
+        packages.iter().map(|p| Arc::new(p.clone())).collect(),
+        Vec::new(),
+    );
+
+    install_packages(installer, lock, &packages, options, io, report).await?;
+
+    if !options.dry_run && !options.no_autoloader {
+        generate_autoloader(installer, lock, &packages, options, report)?;
+    }
+
+    if !options.no_scripts && !options.dry_run {
+        check_exit_code("post-install-cmd", composer.dispatch(&PostInstallEvent::new(dev_mode))?)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the new lock file if it was written.
+async fn run_update(
+    installer: &Installer,
+    packages: Option<Vec<String>>,
+    options: &UpdateOptions,
+    io: &dyn Io,
+    report: &mut OperationReport,
+) -> Result<Option<ComposerLock>> {
+    let composer = installer.composer();
+    let progress = &composer.progress;
+    let install_options = &options.install;
+    let dev_mode = !install_options.no_dev;
+
+    if !install_options.no_scripts {
+        check_exit_code("pre-update-cmd", composer.dispatch(&PreUpdateEvent::new(dev_mode))?)?;
+    }
+
+    progress.report(ProgressEvent::Resolving);
+    let resolve_started = Instant::now();
+    let plan = installer.plan_update(packages).await??;
+    report.timings.resolve = resolve_started.elapsed();
+
+    let UpdatePlan { transaction, packages, lock, lock_file_changed, .. } = plan;
+    progress.report(ProgressEvent::Resolved { operations: transaction.operations.len() });
+    report.transaction = transaction;
+    report.lock_file_changed = lock_file_changed;
+
+    if install_options.dry_run {
+        return Ok(None);
+    }
+
+    let written = if lock_file_changed && composer.config.lock {
+        let path = composer.working_dir.join(lock_file_name());
+        let mut content = serde_json::to_string_pretty(&lock).context("Failed to serialize composer.lock")?;
+        content.push('\n');
+        std::fs::write(&path, content).context("Failed to write composer.lock")?;
+        progress.report(ProgressEvent::LockFileWritten { path });
+        Some(lock.clone())
+    } else {
+        None
+    };
+
+    if options.lock_only {
+        return Ok(written);
+    }
+
+    install_packages(installer, &lock, &packages, install_options, io, report).await?;
+
+    if !install_options.no_autoloader {
+        generate_autoloader(installer, &lock, &packages, install_options, report)?;
+    }
+
+    if !install_options.no_scripts {
+        check_exit_code("post-update-cmd", composer.dispatch(&PostUpdateEvent::new(dev_mode))?)?;
+    }
+
+    Ok(written)
+}
+
+async fn install_packages(
+    installer: &Installer,
+    lock: &ComposerLock,
+    packages: &[Package],
+    options: &InstallOptions,
+    io: &dyn Io,
+    report: &mut OperationReport,
+) -> Result<()> {
+    let progress = &installer.composer().progress;
+    progress.report(ProgressEvent::Installing { packages: packages.len() });
+
+    let install_started = Instant::now();
+    let result = installer.install_locked_packages(lock, packages).await?;
+    report.timings.install = install_started.elapsed();
+
+    for pkg in &result.installed {
+        progress.report(ProgressEvent::PackageInstalled { name: pkg.name.clone(), version: pkg.version.clone() });
+    }
+    for (from, to) in &result.updated {
+        progress.report(ProgressEvent::PackageUpdated { name: to.name.clone(), from: from.version.clone(), to: to.version.clone() });
+    }
+    for pkg in &result.removed {
+        progress.report(ProgressEvent::PackageRemoved { name: pkg.name.clone(), version: pkg.version.clone() });
+    }
+
+    if !options.dry_run {
+        let composer_json = &installer.composer().composer_json;
+        report.suggestions = new_suggestions(&result.installed, packages, composer_json, !options.no_dev);
+    }
+
+    report.installed = result.installed;
+    report.updated = result.updated;
+    report.removed = result.removed;
+
+    if !options.dry_run && installer.composer().config.audit.abandoned != "ignore" {
+        for warning in crate::installer::abandoned_warnings(packages) {
+            report.warn(io, warning);
+        }
+    }
+
+    Ok(())
+}
+
+fn generate_autoloader(
+    installer: &Installer,
+    lock: &ComposerLock,
+    packages: &[Package],
+    options: &InstallOptions,
+    report: &mut OperationReport,
+) -> Result<()> {
+    let composer = installer.composer();
+    let dev_mode = !options.no_dev;
+    let optimize = options.optimize_autoloader;
+
+    if !options.no_scripts {
+        check_exit_code("pre-autoload-dump", composer.dispatch(&PreAutoloadDumpEvent::new(dev_mode, optimize))?)?;
+    }
+
+    composer.progress.report(ProgressEvent::GeneratingAutoload);
+    let autoload_started = Instant::now();
+    let root_version = detect_root_version(
+        &composer.working_dir,
+        composer.composer_json.version.as_deref(),
+        &composer.composer_json.get_branch_aliases(),
+    );
+    installer.generate_autoloader(lock, &root_version, optimize, dev_mode)?;
+    report.timings.autoload = autoload_started.elapsed();
+
+    if !options.no_scripts {
+        let arc_packages: Vec<Arc<Package>> = packages.iter().map(|p| Arc::new(p.clone())).collect();
+        let event = PostAutoloadDumpEvent::new(arc_packages, dev_mode, optimize);
+        check_exit_code("post-autoload-dump", composer.dispatch(&event)?)?;
+    }
+
+    Ok(())
+}
+
+fn check_exit_code(event: &str, exit_code: i32) -> Result<()> {
+    if exit_code != 0 {
+        bail!("{} script returned with error code {}", event, exit_code);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::Verbosity;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    /// Collects the progress events of an operation
+    #[derive(Default)]
+    struct EventIo(Mutex<Vec<ProgressEvent>>);
+
+    impl Io for EventIo {
+        fn verbosity(&self) -> Verbosity {
+            Verbosity::Quiet
+        }
+
+        fn is_interactive(&self) -> bool {
+            false
+        }
+
+        fn write_at(&self, _message: &str, _verbosity: Verbosity) {}
+
+        fn write_error_at(&self, _message: &str, _verbosity: Verbosity) {}
+
+        fn ask(&self, _question: &str, default: Option<&str>) -> Option<String> {
+            default.map(String::from)
+        }
+
+        fn on_progress(&self, event: &ProgressEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    fn open_project(composer_json: &str) -> (TempDir, Composer) {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("composer.json"), composer_json).unwrap();
+        let mut composer = Composer::open(temp_dir.path()).unwrap();
+        composer.repository_manager = Arc::new(crate::repository::RepositoryManager::new());
+        (temp_dir, composer)
+    }
+
+    #[test]
+    fn test_open_missing_composer_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let err = Composer::open(temp_dir.path()).err().unwrap();
+        assert!(err.to_string().contains("Failed to read"));
+    }
+
+    #[tokio::test]
+    async fn test_install_without_lock() {
+        let (_temp_dir, mut composer) = open_project("{}");
+        let err = composer.install(&InstallOptions::default(), &NullIo).await.err().unwrap();
+        assert!(err.to_string().contains("No composer.lock"));
+    }
+
+    #[tokio::test]
+    async fn test_update_empty_project() {
+        let (temp_dir, mut composer) = open_project("{\n    \"name\": \"acme/app\"\n}\n");
+
+        let io = EventIo::default();
+        let options = UpdateOptions {
+            install: InstallOptions { no_scripts: true, ..Default::default() },
+            ..Default::default()
+        };
+
+        let report = composer.update(None, &options, &io).await.unwrap();
+
+        // Nothing to resolve, so the lock file is left alone
+        assert!(report.transaction.is_empty());
+        assert!(!report.lock_file_changed);
+        assert!(composer.composer_lock.is_none());
+        assert!(report.installed.is_empty());
+        assert!(temp_dir.path().join("vendor/autoload.php").exists());
+
+        let events = io.0.into_inner().unwrap();
+        assert_eq!(events.first(), Some(&ProgressEvent::Resolving));
+        // Events of the solver arrive in order with the ones of the facade
+        let solving = events.iter().position(|e| matches!(e, ProgressEvent::Solving { .. })).unwrap();
+        let resolved = events.iter().position(|e| matches!(e, ProgressEvent::Resolved { .. })).unwrap();
+        assert!(solving < resolved);
+        assert!(events.contains(&ProgressEvent::GeneratingAutoload));
+        assert_eq!(events.last(), Some(&ProgressEvent::Finished));
+    }
+
+    #[tokio::test]
+    async fn test_add_dry_run_keeps_composer_json() {
+        let json = "{\n    \"require\": {\n        \"php\": \">=8.1\"\n    }\n}\n";
+        let (temp_dir, mut composer) = open_project(json);
+
+        let options = AddOptions {
+            no_update: true,
+            update: UpdateOptions {
+                install: InstallOptions { dry_run: true, ..Default::default() },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        composer.add("vendor/package", "^1.0", &options, &NullIo).await.unwrap();
+
+        assert_eq!(composer.composer_json.require.get("vendor/package").map(String::as_str), Some("^1.0"));
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("composer.json")).unwrap(), json);
+    }
+
+    #[tokio::test]
+    async fn test_add_moves_between_sections() {
+        let (temp_dir, mut composer) = open_project(
+            "{\n    \"require\": {\n        \"vendor/package\": \"^1.0\"\n    }\n}\n",
+        );
+
+        let options = AddOptions { dev: true, no_update: true, ..Default::default() };
+        composer.add("vendor/package", "^2.0", &options, &NullIo).await.unwrap();
+
+        assert!(!composer.composer_json.require.contains_key("vendor/package"));
+        assert_eq!(composer.composer_json.require_dev.get("vendor/package").map(String::as_str), Some("^2.0"));
+
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(temp_dir.path().join("composer.json")).unwrap()).unwrap();
+        assert_eq!(written["require"], serde_json::json!({}));
+        assert_eq!(written["require-dev"]["vendor/package"], "^2.0");
+    }
+
+    #[tokio::test]
+    async fn test_failed_add_restores_composer_json() {
+        let json = "{\n    \"require\": {\n        \"php\": \">=8.1\"\n    }\n}\n";
+        let (temp_dir, mut composer) = open_project(json);
+
+        let options = AddOptions {
+            update: UpdateOptions {
+                install: InstallOptions { no_scripts: true, ..Default::default() },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let err = composer.add("vendor/missing", "^1.0", &options, &NullIo).await.err().unwrap();
+
+        assert_eq!(err.to_string(), "Could not resolve dependencies");
+        assert!(!composer.composer_json.require.contains_key("vendor/missing"));
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("composer.json")).unwrap(), json);
+    }
+}
@@ -63,6 +63,7 @@ use thiserror::Error;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 
+use super::ProxyConfig;
 use crate::config::{AuthConfig, AuthMatch};
 
 const DEFAULT_USER_AGENT: &str = "Composer/2.0 (pox-pm)";
@@ -109,10 +110,12 @@ impl HttpClient {
             .gzip(true)
             .user_agent(&config.user_agent);
 
-        // Add proxy if configured
+        // Add proxy if configured, otherwise use the proxy settings
         if let Some(proxy_url) = &config.proxy {
             let proxy = reqwest::Proxy::all(proxy_url)?;
             builder = builder.proxy(proxy);
+        } else {
+            builder = config.proxy_config.apply(builder);
         }
 
         // Add custom CA certificate if configured
@@ -243,6 +246,28 @@ impl HttpClient {
         serde_json::from_str(&text).map_err(|e| HttpError::JsonParse(e.to_string()))
     }
 
+    /// POST a JSON body once, without retries
+    pub async fn post_json(&self, url: &str, body: &serde_json::Value) -> Result<Response, HttpError> {
+        let mut request = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .body(body.to_string());
+
+        if let Some(ref auth) = self.auth {
+            request = self.apply_auth(request, url, auth);
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(HttpError::HttpStatus {
+                status: response.status().as_u16(),
+                url: url.to_string(),
+            });
+        }
+        Ok(response)
+    }
+
     /// Download file with progress callback
     pub async fn download<F>(
         &self,
@@ -287,6 +312,103 @@ impl HttpClient {
         Ok(())
     }
 
+    /// Continue a partial download in `dest` with a `Range` request, without retries
+    ///
+    /// The bytes already in `dest` are kept when the server answers with
+    /// `206 Partial Content` and replaced when it sends the whole file. The
+    /// `ETag` or `Last-Modified` of the response that started the file is kept
+    /// in `<dest>.validator` and sent as `If-Range`, so a file that changed on
+    /// the server is downloaded again instead of being stitched together from
+    /// two versions; without a validator the download restarts. A transfer
+    /// that fails midway leaves what was received in `dest`. Returns whether
+    /// earlier bytes were kept.
+    pub async fn download_resume<F>(
+        &self,
+        url: &str,
+        dest: &Path,
+        progress: Option<F>,
+    ) -> Result<bool, HttpError>
+    where
+        F: Fn(u64, u64),
+    {
+        let validator_file = validator_path(dest);
+        let validator = tokio::fs::read_to_string(&validator_file).await.ok();
+        let offset = match validator {
+            Some(_) => tokio::fs::metadata(dest).await.map(|m| m.len()).unwrap_or(0),
+            None => 0,
+        };
+
+        // Ranges refer to the unencoded file, which is what earlier attempts stored
+        let mut request = self
+            .client
+            .get(url)
+            .header(reqwest::header::ACCEPT_ENCODING, "identity");
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+            if let Some(ref validator) = validator {
+                request = request.header(reqwest::header::IF_RANGE, validator.trim());
+            }
+        }
+        if let Some(ref auth) = self.auth {
+            request = self.apply_auth(request, url, auth);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let resumed = status == StatusCode::PARTIAL_CONTENT;
+        if !status.is_success() || (resumed && content_range_start(&response) != Some(offset)) {
+            return Err(HttpError::HttpStatus {
+                status: status.as_u16(),
+                url: url.to_string(),
+            });
+        }
+
+        let mut file = if resumed {
+            tokio::fs::OpenOptions::new().append(true).open(dest).await?
+        } else {
+            if let Some(parent) = dest.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            match response_validator(&response) {
+                Some(validator) => tokio::fs::write(&validator_file, validator).await?,
+                None => {
+                    let _ = tokio::fs::remove_file(&validator_file).await;
+                }
+            }
+            File::create(dest).await?
+        };
+
+        let start = if resumed { offset } else { 0 };
+        let total_size = response.content_length().map_or(0, |len| start + len);
+        let mut downloaded = start;
+
+        let mut stream = response.bytes_stream();
+
+        use futures_util::StreamExt;
+        while let Some(chunk) = stream.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    // The next attempt resumes from the size of the file, so the
+                    // bytes received so far must be written out first
+                    file.flush().await?;
+                    return Err(e.into());
+                }
+            };
+            file.write_all(&chunk).await?;
+            downloaded += chunk.len() as u64;
+
+            if let Some(ref callback) = progress {
+                callback(downloaded, total_size);
+            }
+        }
+
+        file.flush().await?;
+        let _ = tokio::fs::remove_file(&validator_file).await;
+
+        Ok(resumed && offset > 0)
+    }
+
     /// Download to memory
     pub async fn download_bytes(&self, url: &str) -> Result<Vec<u8>, HttpError> {
         let response = self.get(url).await?;
@@ -303,6 +425,32 @@ impl HttpClient {
     pub fn max_retries(&self) -> u32 {
         self.max_retries
     }
+
+    /// Get the delay before the first retry, doubled for each further one
+    pub fn retry_delay(&self) -> Duration {
+        self.retry_delay
+    }
+}
+
+/// Sidecar of a partial download holding the validator sent as `If-Range`
+fn validator_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_owned();
+    name.push(".validator");
+    PathBuf::from(name)
+}
+
+/// Validator usable in `If-Range`: a strong `ETag`, else `Last-Modified`
+fn response_validator(response: &Response) -> Option<String> {
+    let header = |name| response.headers().get(name)?.to_str().ok().map(str::to_string);
+    header(reqwest::header::ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(reqwest::header::LAST_MODIFIED))
+}
+
+/// First byte of a `Content-Range: bytes 100-199/200` response header
+fn content_range_start(response: &Response) -> Option<u64> {
+    let range = response.headers().get(reqwest::header::CONTENT_RANGE)?.to_str().ok()?;
+    range.strip_prefix("bytes ")?.split('-').next()?.trim().parse().ok()
 }
 
 impl Default for HttpClient {
@@ -318,6 +466,8 @@ pub struct HttpClientConfig {
     pub max_retries: u32,
     pub retry_delay: Duration,
     pub proxy: Option<String>,
+    /// Proxy settings used unless `proxy` is set
+    pub proxy_config: ProxyConfig,
     pub cafile: Option<PathBuf>,
     pub user_agent: String,
     pub auth: Option<AuthConfig>,
@@ -331,6 +481,7 @@ impl Default for HttpClientConfig {
             max_retries: DEFAULT_MAX_RETRIES,
             retry_delay: DEFAULT_RETRY_DELAY,
             proxy: None,
+            proxy_config: ProxyConfig::default(),
             cafile: None,
             user_agent: DEFAULT_USER_AGENT.to_string(),
             auth: None,
@@ -368,6 +519,11 @@ impl HttpClientConfig {
         self
     }
 
+    pub fn with_proxy_config(mut self, proxy_config: ProxyConfig) -> Self {
+        self.proxy_config = proxy_config;
+        self
+    }
+
     pub fn with_cafile(mut self, cafile: PathBuf) -> Self {
         self.cafile = Some(cafile);
         self
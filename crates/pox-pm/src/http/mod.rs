@@ -1,3 +1,5 @@
 mod client;
+mod proxy;
 
 pub use client::{HttpClient, HttpClientConfig, HttpError};
+pub use proxy::{blocking_client, client_builder, ProxyConfig};
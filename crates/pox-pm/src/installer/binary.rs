@@ -1,16 +1,65 @@
-//! Binary installer - creates executable links for package binaries.
+//! Binary installer - creates vendor/bin entries for package binaries.
+//!
+//! Like Composer, binaries are installed as proxies by default: PHP scripts
+//! get a PHP proxy that includes them and tells them where the autoloader is
+//! (`$_composer_autoload_path`), other files a shell proxy. `bin-compat`
+//! selects `proxy`, `full` (proxies plus `.bat` files for Windows) or
+//! `symlink` (plain symlinks, where the platform supports them).
 
 use std::path::{Path, PathBuf};
 
 use crate::package::Package;
 use crate::Result;
 
+use super::installer_paths::InstallerPaths;
+
+/// How binaries are exposed in the bin directory (`bin-compat` config)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BinCompat {
+    /// `full` on Windows, `proxy` elsewhere
+    #[default]
+    Auto,
+    /// Proxy scripts
+    Proxy,
+    /// Proxy scripts plus `.bat` proxies
+    Full,
+    /// Symlinks to the binaries
+    Symlink,
+}
+
+impl BinCompat {
+    /// Parse a `bin-compat` value, unknown values fall back to `auto`
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "proxy" => BinCompat::Proxy,
+            "full" => BinCompat::Full,
+            "symlink" => BinCompat::Symlink,
+            _ => BinCompat::Auto,
+        }
+    }
+
+    /// The mode used on the current platform
+    fn resolve(self) -> Self {
+        match self {
+            BinCompat::Auto if cfg!(windows) => BinCompat::Full,
+            BinCompat::Auto => BinCompat::Proxy,
+            // Symlinks need privileges on Windows
+            BinCompat::Symlink if cfg!(windows) => BinCompat::Full,
+            compat => compat,
+        }
+    }
+}
+
 /// Binary installer for creating executable links
 pub struct BinaryInstaller {
     /// Directory where binaries are linked
     bin_dir: PathBuf,
     /// Vendor directory where packages are installed
     vendor_dir: PathBuf,
+    /// How binaries are exposed
+    compat: BinCompat,
+    /// Custom install directories of packages outside the vendor directory
+    installer_paths: InstallerPaths,
 }
 
 impl BinaryInstaller {
@@ -19,9 +68,23 @@ impl BinaryInstaller {
         Self {
             bin_dir: bin_dir.into(),
             vendor_dir: vendor_dir.into(),
+            compat: BinCompat::default(),
+            installer_paths: InstallerPaths::default(),
         }
     }
 
+    /// Set how binaries are exposed
+    pub fn with_compat(mut self, compat: BinCompat) -> Self {
+        self.compat = compat;
+        self
+    }
+
+    /// Look up binaries of packages installed into `extra.installer-paths`
+    pub fn with_installer_paths(mut self, installer_paths: InstallerPaths) -> Self {
+        self.installer_paths = installer_paths;
+        self
+    }
+
     /// Install binaries for a package
     pub async fn install(&self, package: &Package) -> Result<Vec<PathBuf>> {
         if package.bin.is_empty() {
@@ -31,17 +94,11 @@ impl BinaryInstaller {
         tokio::fs::create_dir_all(&self.bin_dir).await?;
 
         let mut installed = Vec::new();
-        let package_dir = self.vendor_dir.join(&package.name);
+        let package_dir = self.installer_paths.resolve(package, &self.vendor_dir);
 
         for bin_path in &package.bin {
             let source = package_dir.join(bin_path);
-            let bin_name = Path::new(bin_path)
-                .file_name()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_else(|| bin_path.clone());
-
-            let link_name = bin_name.strip_suffix(".php").unwrap_or(&bin_name);
-            let link_path = self.bin_dir.join(link_name);
+            let link_path = self.link_path(bin_path);
 
             if source.exists() {
                 self.create_bin_link(&source, &link_path).await?;
@@ -55,55 +112,58 @@ impl BinaryInstaller {
     /// Remove binaries for a package
     pub async fn uninstall(&self, package: &Package) -> Result<()> {
         for bin_path in &package.bin {
-            let bin_name = Path::new(bin_path)
-                .file_name()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_else(|| bin_path.clone());
+            let link_path = self.link_path(bin_path);
 
-            let link_name = bin_name.strip_suffix(".php").unwrap_or(&bin_name);
-            let link_path = self.bin_dir.join(link_name);
-
-            if link_path.exists() {
-                tokio::fs::remove_file(&link_path).await?;
+            for path in [link_path.clone(), bat_path(&link_path)] {
+                if path.symlink_metadata().is_ok() {
+                    tokio::fs::remove_file(&path).await?;
+                }
             }
         }
 
         Ok(())
     }
 
-    /// Create a binary link (symlink on Unix, batch file on Windows)
-    #[cfg(unix)]
-    async fn create_bin_link(&self, source: &Path, link: &Path) -> Result<()> {
-        if link.exists() {
-            tokio::fs::remove_file(link).await?;
-        }
-
-        tokio::fs::symlink(source, link).await?;
-
-        use std::os::unix::fs::PermissionsExt;
-        let metadata = tokio::fs::metadata(source).await?;
-        let mut perms = metadata.permissions();
-        perms.set_mode(perms.mode() | 0o111);
-        tokio::fs::set_permissions(source, perms).await?;
+    fn link_path(&self, bin_path: &str) -> PathBuf {
+        let bin_name = Path::new(bin_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| bin_path.to_string());
 
-        Ok(())
+        let link_name = bin_name.strip_suffix(".php").unwrap_or(&bin_name);
+        self.bin_dir.join(link_name)
     }
 
-    /// Create a binary link (batch file on Windows)
-    #[cfg(windows)]
+    /// Create the bin directory entries for `source`
     async fn create_bin_link(&self, source: &Path, link: &Path) -> Result<()> {
-        let bat_path = link.with_extension("bat");
-        if bat_path.exists() {
-            tokio::fs::remove_file(&bat_path).await?;
+        for path in [link.to_path_buf(), bat_path(link)] {
+            if path.symlink_metadata().is_ok() {
+                tokio::fs::remove_file(&path).await?;
+            }
         }
 
-        let source_str = source.to_string_lossy();
-        let content = format!(
-            "@ECHO OFF\r\nphp \"{}\" %*\r\n",
-            source_str.replace('/', "\\")
-        );
+        set_executable(source).await?;
+
+        let compat = self.compat.resolve();
+        if compat == BinCompat::Symlink {
+            return symlink(source, link).await;
+        }
 
-        tokio::fs::write(&bat_path, content).await?;
+        let target = relative_path(&self.bin_dir, source);
+        let is_php = is_php_script(source);
+
+        let proxy = if is_php {
+            let autoload = relative_path(&self.bin_dir, &self.vendor_dir.join("autoload.php"));
+            php_proxy(&target, &autoload)
+        } else {
+            shell_proxy(&target)
+        };
+        tokio::fs::write(link, proxy).await?;
+        set_executable(link).await?;
+
+        if compat == BinCompat::Full {
+            tokio::fs::write(bat_path(link), bat_proxy(&target, is_php)).await?;
+        }
 
         Ok(())
     }
@@ -114,6 +174,121 @@ impl BinaryInstaller {
     }
 }
 
+fn bat_path(link: &Path) -> PathBuf {
+    let mut path = link.as_os_str().to_owned();
+    path.push(".bat");
+    PathBuf::from(path)
+}
+
+/// Path of `target` relative to `dir`, with forward slashes
+fn relative_path(dir: &Path, target: &Path) -> String {
+    let relative = pathdiff::diff_paths(target, dir).unwrap_or_else(|| target.to_path_buf());
+    relative.to_string_lossy().replace('\\', "/")
+}
+
+/// Whether a binary is a PHP script: `<?php` or a shebang running php
+fn is_php_script(path: &Path) -> bool {
+    use std::io::Read;
+
+    let mut head = [0u8; 500];
+    let Ok(len) = std::fs::File::open(path).and_then(|mut file| file.read(&mut head)) else {
+        return false;
+    };
+    let head = String::from_utf8_lossy(&head[..len]);
+
+    if head.starts_with("<?php") {
+        return true;
+    }
+    match head.strip_prefix("#!") {
+        Some(rest) => {
+            let shebang = rest.lines().next().unwrap_or_default();
+            shebang.split(|c: char| c == '/' || c.is_whitespace()).any(|part| part.starts_with("php"))
+        }
+        None => false,
+    }
+}
+
+fn php_proxy(target: &str, autoload: &str) -> String {
+    format!(
+        r#"#!/usr/bin/env php
+<?php
+
+/**
+ * Proxy PHP file generated by pox
+ *
+ * This file includes the referenced bin path ({target})
+ *
+ * @generated
+ */
+
+namespace Composer;
+
+$GLOBALS['_composer_bin_dir'] = __DIR__;
+$GLOBALS['_composer_autoload_path'] = __DIR__ . '/{autoload}';
+
+return include __DIR__ . '/{target}';
+"#
+    )
+}
+
+fn shell_proxy(target: &str) -> String {
+    let (dir, file) = match target.rsplit_once('/') {
+        Some((dir, file)) => (dir, file),
+        None => (".", target),
+    };
+
+    format!(
+        r#"#!/usr/bin/env sh
+
+# Proxy shell script generated by pox, runs {target}
+
+self_dir=$(cd "${{0%[/\\]*}}" > /dev/null && pwd)
+dir=$(cd "$self_dir" > /dev/null && cd '{dir}' && pwd)
+
+export COMPOSER_RUNTIME_BIN_DIR="$self_dir"
+
+exec "$dir/{file}" "$@"
+"#
+    )
+}
+
+fn bat_proxy(target: &str, is_php: bool) -> String {
+    let target = target.replace('/', "\\");
+    let run = if is_php { "php \"%BIN_TARGET%\" %*" } else { "\"%BIN_TARGET%\" %*" };
+
+    format!(
+        "@ECHO OFF\r\nsetlocal DISABLEDELAYEDEXPANSION\r\nSET BIN_TARGET=%~dp0\\{}\r\nSET COMPOSER_RUNTIME_BIN_DIR=%~dp0\r\n{}\r\n",
+        target, run
+    )
+}
+
+#[cfg(unix)]
+async fn set_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let metadata = tokio::fs::metadata(path).await?;
+    let mut perms = metadata.permissions();
+    perms.set_mode(perms.mode() | 0o111);
+    tokio::fs::set_permissions(path, perms).await?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn set_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn symlink(source: &Path, link: &Path) -> Result<()> {
+    tokio::fs::symlink(source, link).await?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn symlink(_source: &Path, _link: &Path) -> Result<()> {
+    unreachable!("symlinks are replaced by proxies on this platform")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,4 +328,66 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    async fn install_bin(compat: BinCompat, name: &str, contents: &str) -> (TempDir, BinaryInstaller, Package) {
+        let temp_dir = TempDir::new().unwrap();
+        let vendor = temp_dir.path().join("vendor");
+        std::fs::create_dir_all(vendor.join("acme/tool/bin")).unwrap();
+        std::fs::write(vendor.join("acme/tool/bin").join(name), contents).unwrap();
+
+        let installer = BinaryInstaller::new(vendor.join("bin"), &vendor).with_compat(compat);
+        let mut package = Package::new("acme/tool", "1.0.0");
+        package.bin = vec![format!("bin/{}", name)];
+        installer.install(&package).await.unwrap();
+
+        (temp_dir, installer, package)
+    }
+
+    #[tokio::test]
+    async fn test_php_proxy() {
+        let (temp_dir, installer, package) = install_bin(BinCompat::Proxy, "tool", "#!/usr/bin/env php\n<?php echo 1;\n").await;
+
+        let proxy = std::fs::read_to_string(temp_dir.path().join("vendor/bin/tool")).unwrap();
+        assert!(proxy.starts_with("#!/usr/bin/env php\n<?php"));
+        assert!(proxy.contains("$GLOBALS['_composer_autoload_path'] = __DIR__ . '/../autoload.php';"));
+        assert!(proxy.contains("return include __DIR__ . '/../acme/tool/bin/tool';"));
+        assert!(!temp_dir.path().join("vendor/bin/tool.bat").exists());
+
+        installer.uninstall(&package).await.unwrap();
+        assert!(!temp_dir.path().join("vendor/bin/tool").exists());
+    }
+
+    #[tokio::test]
+    async fn test_shell_proxy_with_bat() {
+        let (temp_dir, installer, package) = install_bin(BinCompat::Full, "run.sh", "#!/bin/sh\necho 1\n").await;
+
+        let proxy = std::fs::read_to_string(temp_dir.path().join("vendor/bin/run.sh")).unwrap();
+        assert!(proxy.starts_with("#!/usr/bin/env sh"));
+        assert!(proxy.contains("cd '../acme/tool/bin'"));
+        assert!(proxy.contains("exec \"$dir/run.sh\" \"$@\""));
+
+        let bat = std::fs::read_to_string(temp_dir.path().join("vendor/bin/run.sh.bat")).unwrap();
+        assert!(bat.contains("SET BIN_TARGET=%~dp0\\..\\acme\\tool\\bin\\run.sh"));
+        assert!(!bat.contains("php "));
+
+        installer.uninstall(&package).await.unwrap();
+        assert!(!temp_dir.path().join("vendor/bin/run.sh.bat").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink() {
+        let (temp_dir, _, _) = install_bin(BinCompat::Symlink, "tool.php", "<?php echo 1;\n").await;
+
+        let link = temp_dir.path().join("vendor/bin/tool");
+        assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+    }
+
+    #[test]
+    fn test_bin_compat_parse() {
+        assert_eq!(BinCompat::parse("full"), BinCompat::Full);
+        assert_eq!(BinCompat::parse("symlink"), BinCompat::Symlink);
+        assert_eq!(BinCompat::parse("auto"), BinCompat::Auto);
+        assert_eq!(BinCompat::parse("unknown"), BinCompat::Auto);
+    }
 }
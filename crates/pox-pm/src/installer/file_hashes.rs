@@ -0,0 +1,213 @@
+//! File hashes of installed packages, used to detect local modifications.
+//!
+//! After a package is installed the SHA-256 of every file is recorded in
+//! `vendor/composer/pox-file-hashes.json`. Comparing the recorded hashes
+//! with the files on disk tells which packages were edited in place.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::package::Package;
+
+/// Name of the hashes file inside `vendor/composer`
+pub const FILE_HASHES_FILE: &str = "pox-file-hashes.json";
+
+/// Recorded file hashes of all installed packages
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileHashes {
+    packages: BTreeMap<String, PackageFileHashes>,
+}
+
+/// Recorded file hashes of one package, keyed by path relative to the install path
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageFileHashes {
+    pub version: String,
+    pub files: BTreeMap<String, String>,
+}
+
+/// A difference between the recorded and the current files of a package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileChange {
+    Modified(String),
+    Added(String),
+    Removed(String),
+}
+
+impl FileChange {
+    /// Path of the changed file relative to the install path
+    pub fn path(&self) -> &str {
+        match self {
+            FileChange::Modified(path) | FileChange::Added(path) | FileChange::Removed(path) => path,
+        }
+    }
+}
+
+impl FileHashes {
+    fn path(vendor_dir: &Path) -> PathBuf {
+        vendor_dir.join("composer").join(FILE_HASHES_FILE)
+    }
+
+    /// Load the recorded hashes, empty if none were recorded or the file is unreadable
+    pub fn load(vendor_dir: &Path) -> Self {
+        std::fs::read_to_string(Self::path(vendor_dir))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the hashes to `vendor/composer`
+    pub fn save(&self, vendor_dir: &Path) -> std::io::Result<()> {
+        let path = Self::path(vendor_dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, content)
+    }
+
+    /// Recorded hashes of a package
+    pub fn get(&self, name: &str) -> Option<&PackageFileHashes> {
+        self.packages.get(&name.to_lowercase())
+    }
+
+    /// Record the hashes of an installed package
+    pub fn insert(&mut self, package: &Package, files: BTreeMap<String, String>) {
+        self.packages.insert(
+            package.name.to_lowercase(),
+            PackageFileHashes { version: package.version.clone(), files },
+        );
+    }
+
+    /// Forget a removed package
+    pub fn remove(&mut self, name: &str) {
+        self.packages.remove(&name.to_lowercase());
+    }
+
+    /// Whether no package has recorded hashes
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+
+    /// Files of an installed package that differ from the recorded hashes
+    ///
+    /// Returns `None` if no hashes were recorded for this version of the package.
+    pub fn changed_files(&self, package: &Package, install_path: &Path) -> std::io::Result<Option<Vec<FileChange>>> {
+        let Some(recorded) = self.get(&package.name).filter(|r| r.version == package.version) else {
+            return Ok(None);
+        };
+
+        let current = hash_directory(install_path)?;
+        let mut changes = Vec::new();
+
+        for (path, hash) in &recorded.files {
+            match current.get(path) {
+                Some(current_hash) if current_hash == hash => {}
+                Some(_) => changes.push(FileChange::Modified(path.clone())),
+                None => changes.push(FileChange::Removed(path.clone())),
+            }
+        }
+        for path in current.keys() {
+            if !recorded.files.contains_key(path) {
+                changes.push(FileChange::Added(path.clone()));
+            }
+        }
+
+        Ok(Some(changes))
+    }
+}
+
+/// SHA-256 of every file below `dir`, keyed by `/` separated relative path
+///
+/// VCS metadata directories of source installs are skipped, symlinks are
+/// hashed by their target.
+pub fn hash_directory(dir: &Path) -> std::io::Result<BTreeMap<String, String>> {
+    let mut hashes = BTreeMap::new();
+    if !dir.is_dir() {
+        return Ok(hashes);
+    }
+
+    let walker = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|e| !(e.file_type().is_dir() && matches!(e.file_name().to_str(), Some(".git" | ".svn" | ".hg"))));
+
+    for entry in walker {
+        let entry = entry.map_err(std::io::Error::other)?;
+        if entry.file_type().is_dir() {
+            continue;
+        }
+
+        let relative = entry.path().strip_prefix(dir).unwrap_or(entry.path());
+        let key = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let mut hasher = Sha256::new();
+        if entry.path_is_symlink() {
+            hasher.update(std::fs::read_link(entry.path())?.to_string_lossy().as_bytes());
+        } else {
+            let mut file = std::fs::File::open(entry.path())?;
+            let mut buffer = [0u8; 8192];
+            loop {
+                let read = file.read(&mut buffer)?;
+                if read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..read]);
+            }
+        }
+
+        hashes.insert(key, format!("{:x}", hasher.finalize()));
+    }
+
+    Ok(hashes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_changed_files() {
+        let vendor = TempDir::new().unwrap();
+        let install_path = vendor.path().join("acme/lib");
+        std::fs::create_dir_all(install_path.join("src")).unwrap();
+        std::fs::create_dir_all(install_path.join(".git")).unwrap();
+        std::fs::write(install_path.join("src/Lib.php"), "<?php class Lib {}").unwrap();
+        std::fs::write(install_path.join("README.md"), "readme").unwrap();
+        std::fs::write(install_path.join(".git/HEAD"), "ref").unwrap();
+
+        let package = Package::new("acme/lib", "1.0.0.0");
+        let mut hashes = FileHashes::default();
+        hashes.insert(&package, hash_directory(&install_path).unwrap());
+        assert_eq!(hashes.get("acme/lib").unwrap().files.len(), 2);
+
+        hashes.save(vendor.path()).unwrap();
+        let hashes = FileHashes::load(vendor.path());
+        assert_eq!(hashes.changed_files(&package, &install_path).unwrap(), Some(Vec::new()));
+
+        std::fs::write(install_path.join("src/Lib.php"), "<?php class Patched {}").unwrap();
+        std::fs::remove_file(install_path.join("README.md")).unwrap();
+        std::fs::write(install_path.join("debug.php"), "<?php").unwrap();
+        std::fs::write(install_path.join(".git/HEAD"), "other").unwrap();
+
+        assert_eq!(
+            hashes.changed_files(&package, &install_path).unwrap(),
+            Some(vec![
+                FileChange::Removed("README.md".to_string()),
+                FileChange::Modified("src/Lib.php".to_string()),
+                FileChange::Added("debug.php".to_string()),
+            ])
+        );
+
+        // Hashes of another version say nothing about this one
+        let other_version = Package::new("acme/lib", "2.0.0.0");
+        assert_eq!(hashes.changed_files(&other_version, &install_path).unwrap(), None);
+    }
+}
@@ -5,25 +5,71 @@ use anyhow::{Context, Result};
 use console::style;
 use indicatif::{ProgressBar, ProgressStyle};
 use indexmap::IndexMap;
+use pox_semver::{Constraint, Operator, VersionParser};
 
 use crate::composer::Composer;
+use crate::io::{Io, Verbosity};
+use crate::config::LicensePolicyMode;
 use crate::event::{
     PostAutoloadDumpEvent, PostInstallEvent, PostUpdateEvent,
     PreAutoloadDumpEvent, PreInstallEvent, PreUpdateEvent,
 };
-use crate::json::{ComposerLock, ComposerJson, LockedPackage};
-use crate::package::{Package, Stability, Autoload, detect_root_version, RootVersion};
-use crate::solver::{Pool, Policy, Request, Solver, Transaction};
+use crate::json::{ComposerLock, ComposerJson, LockAlias, LockedPackage};
+use crate::progress::ProgressEvent;
+use crate::package::{AliasPackage, Package, Stability, Autoload, branch_alias, detect_root_version, parse_branch_aliases, parse_inline_alias, RootVersion, DEFAULT_BRANCH_ALIAS};
+use crate::solver::{matches_update_pattern, Operation, Pool, Policy, Request, Solver, Transaction};
 use crate::autoload::{AutoloadConfig, AutoloadGenerator, PackageAutoload, RootPackageInfo, get_head_commit};
 use crate::util::is_platform_package;
+use crate::plugin::symfony_flex::symfony_require;
+use crate::repository::{apply_platform_overrides, install_reasons, platform_override_differences, InstalledRepository, Repository, RepositoryManager, WritableRepository};
+use super::license_policy::{
+    find_license_incompatibilities, find_license_violations, policy_from_config, project_license, spdx_licenses,
+    LicenseViolation,
+};
+use super::suggestions::{new_suggestions, suggestions_summary};
+use super::manager::{InstallConfig, InstallResult};
 
 pub struct Installer {
     composer: Composer,
+    audit: bool,
+}
+
+/// Outcome of the planning phase of an update.
+#[derive(Debug, Clone)]
+pub struct UpdatePlan {
+    /// Operations needed to go from the current lock file to the resolved packages
+    pub transaction: Transaction,
+    /// Resolved packages, excluding platform packages
+    pub packages: Vec<Package>,
+    /// Lock file contents for the resolved packages
+    pub lock: ComposerLock,
+    /// Whether the lock file would change
+    pub lock_file_changed: bool,
+    /// Detected version of the root package
+    pub root_version: RootVersion,
+}
+
+/// Why an update could not be planned, the details have already been reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum PlanFailure {
+    /// The dependencies could not be resolved
+    #[error("Could not resolve dependencies")]
+    Unresolvable,
+    /// The resolved packages violate a license policy set to `fail`
+    #[error("The resolved packages violate the license policy")]
+    LicensePolicy,
 }
 
 impl Installer {
     pub fn new(composer: Composer) -> Self {
-        Self { composer }
+        Self { composer, audit: false }
+    }
+
+    /// Whether the packages are audited after installing, the audit then
+    /// reports abandoned packages instead of the installer
+    pub fn audit(mut self, audit: bool) -> Self {
+        self.audit = audit;
+        self
     }
 
     pub async fn update(&self, optimize_autoloader: bool, update_lock_only: bool, update_packages: Option<Vec<String>>) -> Result<i32> {
@@ -32,15 +78,13 @@ impl Installer {
         let install_config = self.composer.installation_manager.config();
         let dry_run = install_config.dry_run;
         let no_dev = install_config.no_dev;
-        let prefer_lowest = install_config.prefer_lowest;
-        let platform_packages = &self.composer.platform_packages;
 
         log::debug!("Reading {}/composer.json", working_dir.display());
 
-        println!("{} Updating dependencies", style("Composer").green().bold());
+        self.composer.io.write(&format!("{} Updating dependencies", style("Composer").green().bold()));
 
         if dry_run {
-            println!("{} Running in dry-run mode", style("Info:").cyan());
+            self.composer.io.write(&format!("{} Running in dry-run mode", style("Info:").cyan()));
         }
 
         // Dispatch pre-update event
@@ -49,15 +93,116 @@ impl Installer {
             return Ok(exit_code);
         }
 
-        // Create progress spinner
-        let spinner = ProgressBar::new_spinner();
-        spinner.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.green} {msg}")
-                .unwrap(),
-        );
-        spinner.enable_steady_tick(Duration::from_millis(100));
-        spinner.set_message("Loading repositories...");
+        let spinner = self.composer.io.progress("Loading repositories...");
+
+        let Ok(plan) = self.plan_update_with_progress(update_packages, &spinner).await? else {
+            spinner.finish_and_clear();
+            return Ok(1);
+        };
+        let UpdatePlan { transaction, packages, lock, lock_file_changed, root_version } = plan;
+
+        if dry_run {
+            spinner.finish_and_clear();
+            print_transaction_preview(self.composer.io.as_ref(), "Lock file operations", &transaction, &packages, composer_json);
+            return Ok(0);
+        }
+
+        spinner.set_message("Installing packages...");
+
+        // Only write lock file if there were changes, and never with `lock: false`
+        if lock_file_changed && !dry_run && self.composer.config.lock {
+            log::debug!("Writing lock file");
+            let mut lock_content = serde_json::to_string_pretty(&lock).context("Failed to serialize composer.lock")?;
+            // Add trailing newline to match Composer's format
+            lock_content.push('\n');
+            std::fs::write(working_dir.join(crate::config::lock_file_name()), lock_content).context("Failed to write composer.lock")?;
+        }
+
+        if update_lock_only {
+             spinner.finish_and_clear();
+             if lock_file_changed {
+                 self.composer.io.write(&format!("{} Lock file updated", style("Success:").green().bold()));
+             } else {
+                 self.composer.io.write(&format!("{} Lock file is up to date", style("Info:").cyan()));
+             }
+             return Ok(0);
+        }
+
+        log::debug!("Installing dependencies from lock file");
+        let summary = transaction.summary();
+        log::info!("Package operations: {} installs, {} updates, {} removals",
+            summary.installs, summary.updates, summary.uninstalls);
+
+        let result = self.install_locked_packages(&lock, &packages).await?;
+
+        spinner.finish_and_clear();
+
+        let actually_installed: Vec<_> = result.installed.iter()
+            .filter(|p| !is_platform_package(&p.name))
+            .collect();
+
+        for pkg in &actually_installed {
+            log::debug!("Installed {} ({})", pkg.name, pkg.version);
+            self.composer.io.write(&format!("  {} {} ({})", style("-").green(), style(&pkg.name).white().bold(), style(&pkg.version).yellow()));
+        }
+        for pkg in &result.removed {
+            self.composer.io.write(&format!("  {} Removing {} ({})", style("-").red(), style(&pkg.name).white().bold(), style(&pkg.version).yellow()));
+        }
+
+        if !dry_run {
+             self.composer.io.write(&format!("{} Generating autoload files", style("Info:").cyan()));
+             
+             self.generate_autoloader(&lock, &root_version, optimize_autoloader, !no_dev)?;
+
+             // Dispatch post-autoload-dump event (runs scripts and plugins)
+             let arc_packages: Vec<Arc<Package>> = packages.iter().map(|p| Arc::new(p.clone())).collect();
+             let event = PostAutoloadDumpEvent::new(arc_packages, !no_dev, optimize_autoloader);
+             let exit_code = self.composer.dispatch(&event)?;
+             if exit_code != 0 {
+                 return Ok(exit_code);
+             }
+        }
+
+        let total_changed = actually_installed.len() + result.updated.len() + result.removed.len();
+        if total_changed > 0 || lock_file_changed {
+            let summary = operations_summary(actually_installed.len(), result.updated.len(), result.removed.len());
+            self.composer.io.write(&format!("{} {}", style("Success:").green().bold(), summary));
+        } else {
+            self.composer.io.write(&format!("{} Nothing to update.", style("Info:").cyan()));
+        }
+
+        if !dry_run {
+            self.report_suggestions(&result.installed, &packages, !no_dev);
+            self.audit_abandoned_packages(&packages);
+        }
+
+        // Dispatch post-update event
+        if !dry_run {
+            let exit_code = self.composer.dispatch(&PostUpdateEvent::new(!no_dev))?;
+            if exit_code != 0 {
+                return Ok(exit_code);
+            }
+        }
+
+        Ok(0)
+    }
+
+    /// Resolve dependencies and compute the operations an update would perform,
+    /// without writing the lock file or touching the vendor directory.
+    ///
+    /// Returns a [`PlanFailure`] if dependencies could not be resolved or the
+    /// license policy rejects them; the problems have already been reported.
+    pub async fn plan_update(&self, update_packages: Option<Vec<String>>) -> Result<std::result::Result<UpdatePlan, PlanFailure>> {
+        self.plan_update_with_progress(update_packages, &ProgressBar::hidden()).await
+    }
+
+    async fn plan_update_with_progress(&self, update_packages: Option<Vec<String>>, spinner: &ProgressBar) -> Result<std::result::Result<UpdatePlan, PlanFailure>> {
+        let composer_json = &self.composer.composer_json;
+        let working_dir = &self.composer.working_dir;
+        let install_config = self.composer.installation_manager.config();
+        let no_dev = install_config.no_dev;
+        let prefer_lowest = install_config.prefer_lowest;
+        let platform_packages = &apply_platform_overrides(&self.composer.platform_packages, &self.composer.config.platform);
 
         // Setup repository manager
         let repo_manager = self.composer.repository_manager.clone();
@@ -82,7 +227,7 @@ impl Installer {
         // Add root package to pool (for replace/provide/conflict handling)
         // Use add_platform_package to bypass stability filtering (root is always installed)
         let root_pkg = create_root_package(composer_json, &root_version);
-        if !root_pkg.replace.is_empty() || !root_pkg.provide.is_empty() {
+        if has_root_links(&root_pkg) {
             log::debug!(
                 "Root package version: {} (normalized: {})",
                 root_pkg.pretty_version.as_deref().unwrap_or("N/A"),
@@ -96,6 +241,10 @@ impl Installer {
                 "Root package provides: {:?}",
                 root_pkg.provide
             );
+            log::debug!(
+                "Root package conflicts: {:?}",
+                root_pkg.conflict
+            );
             let root_id = pool.add_platform_package(root_pkg);
             log::debug!("Added root package to pool with id {}", root_id);
         }
@@ -116,22 +265,14 @@ impl Installer {
             );
         }
 
-        // Add stability flags - sort for deterministic order
-        let mut sorted_require: Vec<_> = composer_json.require.iter().collect();
-        sorted_require.sort_by(|a, b| a.0.cmp(b.0));
-        for (name, constraint) in sorted_require {
-            if let Some(stability) = extract_stability_flag(constraint) {
-                pool.add_stability_flag(name, stability);
-                log::trace!("Stability flag for {}: {:?}", name, stability);
-            }
-        }
-        let mut sorted_require_dev: Vec<_> = composer_json.require_dev.iter().collect();
-        sorted_require_dev.sort_by(|a, b| a.0.cmp(b.0));
-        for (name, constraint) in sorted_require_dev {
-            if let Some(stability) = extract_stability_flag(constraint) {
-                pool.add_stability_flag(name, stability);
-                log::trace!("Stability flag for {}: {:?}", name, stability);
-            }
+        // Add stability flags of the root requirements, e.g. `@dev` or `dev-main`
+        let stability_flags = extract_stability_flags(
+            composer_json.require.iter().chain(composer_json.require_dev.iter()),
+            minimum_stability,
+        );
+        for (name, stability) in &stability_flags {
+            pool.add_stability_flag(name, *stability);
+            log::trace!("Stability flag for {}: {:?}", name, stability);
         }
 
         // Add platform packages (bypass stability filtering - these are fixed system packages)
@@ -144,12 +285,14 @@ impl Installer {
         // This dramatically reduces the pool size by only loading versions that could
         // possibly be selected, similar to PHP Composer's demand-driven loading.
         let load_start = std::time::Instant::now();
+        let metadata_phase = self.composer.profiler.phase("loading metadata");
 
         // Track loaded packages and pending packages with their constraints
         // Key = lowercase package name, Value = merged constraint string
         let mut loaded_packages: HashSet<String> = root_replaced.clone();
         let mut pending_packages: HashMap<String, String> = HashMap::new();
         let mut http_request_count = 0usize;
+        let mut loaded_count = 0usize;
 
         // Collect all packages first, then sort and add to pool for deterministic order
         let mut all_packages: Vec<Arc<Package>> = Vec::new();
@@ -160,7 +303,7 @@ impl Installer {
         for (name, constraint) in sorted_require {
             if !is_platform_package(name) && !root_replaced.contains(&name.to_lowercase()) {
                 let name_lower = name.to_lowercase();
-                pending_packages.insert(name_lower, constraint.clone());
+                pending_packages.insert(name_lower, strip_inline_alias(constraint).to_string());
             }
         }
         if !no_dev {
@@ -170,15 +313,39 @@ impl Installer {
                 if !is_platform_package(name) && !root_replaced.contains(&name.to_lowercase()) {
                     let name_lower = name.to_lowercase();
                     // Merge constraints if already present
+                    let constraint = strip_inline_alias(constraint);
                     if let Some(existing) = pending_packages.get(&name_lower) {
                         pending_packages.insert(name_lower, format!("{} || {}", existing, constraint));
                     } else {
-                        pending_packages.insert(name_lower, constraint.clone());
+                        pending_packages.insert(name_lower, constraint.to_string());
                     }
                 }
             }
         }
 
+        // Symfony Flex projects pin the Symfony components to `extra.symfony.require`
+        let symfony_pin = match symfony_require(composer_json) {
+            Some(constraint) => {
+                let components = symfony_components(&repo_manager, &constraint).await;
+                if components.is_empty() {
+                    self.composer.io.write_error(&format!(
+                        "{} No version of symfony/symfony matches \"{}\", Symfony packages are not restricted",
+                        style("Warning:").yellow().bold(),
+                        constraint
+                    ));
+                    None
+                } else {
+                    self.composer.io.write(&format!(
+                        "{} Restricting packages listed in \"symfony/symfony\" to \"{}\"",
+                        style("Info:").cyan(),
+                        constraint
+                    ));
+                    Some((constraint, components))
+                }
+            }
+            None => None,
+        };
+
         // Process packages in parallel batches for performance
         // Determinism is ensured by:
         // 1. Processing batches in sorted order
@@ -214,8 +381,10 @@ impl Installer {
             let mut tasks = tokio::task::JoinSet::new();
             for (name, constraint) in to_load {
                 let repo_manager = repo_manager.clone();
+                // Dev versions are only loaded when the package may be installed as dev
+                let include_dev = stability_flags.get(&name).copied().unwrap_or(minimum_stability) == Stability::Dev;
                 tasks.spawn(async move {
-                    let packages = repo_manager.find_packages_with_constraint(&name, &constraint).await;
+                    let packages = repo_manager.find_matching_packages(&name, &constraint, include_dev).await;
                     (name, packages)
                 });
             }
@@ -225,6 +394,11 @@ impl Installer {
             let mut new_deps: Vec<(String, String)> = Vec::new();
 
             while let Some(result) = tasks.join_next().await {
+                loaded_count += 1;
+                self.composer.progress.report(ProgressEvent::MetadataLoading {
+                    done: loaded_count,
+                    total: http_request_count,
+                });
                 if let Ok((name, packages)) = result {
                     log::trace!("HTTP: {} ({} versions)", name, packages.len());
                     for pkg in packages {
@@ -262,6 +436,10 @@ impl Installer {
             all_packages.extend(batch_packages);
         }
 
+        if let Some((constraint, components)) = &symfony_pin {
+            all_packages = restrict_symfony_packages(all_packages, constraint, components);
+        }
+
         // Sort packages by name and version for deterministic pool order
         all_packages.sort_by(|a, b| {
             match a.name.cmp(&b.name) {
@@ -270,13 +448,16 @@ impl Installer {
             }
         });
 
-        // Add sorted packages to pool
+        // Add sorted packages to pool, each followed by its branch alias and
+        // the inline aliases of the root requirements
+        let root_aliases = extract_root_aliases(composer_json, no_dev);
         for pkg in all_packages {
-            pool.add_package_arc(pkg, None);
+            add_package_with_aliases(&mut pool, pkg, &root_aliases);
         }
 
         log::info!("Loaded {} packages ({} HTTP requests) in {:?}",
             pool.len(), http_request_count, load_start.elapsed());
+        drop(metadata_phase);
         log::debug!("Pool has {} packages after loading", pool.len());
 
         // Solver Request - sort for deterministic order
@@ -298,20 +479,34 @@ impl Installer {
             }
         }
 
-        // Add root package as fixed if it has replace/provide
-        // This ensures the solver knows the root package is always installed
-        // and its replaced/provided packages are available
+        // Add root package as fixed if it has replace/provide/conflict
+        // This ensures the solver knows the root package is always installed,
+        // its replaced/provided packages are available and its conflicts apply
         let root_pkg = create_root_package(composer_json, &root_version);
-        if !root_pkg.replace.is_empty() || !root_pkg.provide.is_empty() {
+        if has_root_links(&root_pkg) {
             request.fix(root_pkg);
         }
 
         let preferred_versions = match (&update_packages, &self.composer.composer_lock) {
             (Some(packages_to_update), Some(lock)) if !packages_to_update.is_empty() => {
-                let update_allowlist: HashSet<String> = packages_to_update
-                    .iter()
-                    .map(|p| p.to_lowercase())
+                request.update(packages_to_update.clone());
+                request.update_allow_transitive_deps(install_config.update_with_dependencies);
+
+                let locked: Vec<Arc<Package>> = lock.packages.iter()
+                    .chain(lock.packages_dev.iter())
+                    .map(|lp| Arc::new(Package::from(lp)))
                     .collect();
+                let update_allowlist = request.resolve_update_allowlist(&locked);
+
+                for pattern in packages_to_update {
+                    if !update_allowlist.iter().any(|name| matches_update_pattern(pattern, name)) {
+                        self.composer.io.write_error(&format!(
+                            "{} Package \"{}\" listed for update is not locked.",
+                            style("Warning:").yellow(),
+                            pattern
+                        ));
+                    }
+                }
 
                 let mut preferred = HashMap::new();
                 for pkg in lock.packages.iter().chain(lock.packages_dev.iter()) {
@@ -329,25 +524,32 @@ impl Installer {
             }
         };
 
+        let prefer_stable = install_config.prefer_stable || composer_json.prefer_stable.unwrap_or(false);
         let policy = Policy::new()
+            .prefer_stable(prefer_stable)
             .prefer_lowest(prefer_lowest)
-            .preferred_versions(preferred_versions);
+            .preferred_versions(preferred_versions)
+            .seed(install_config.solver_seed);
+        self.composer.progress.report(ProgressEvent::Solving { packages: pool.len() });
         let solver = Solver::new(&pool, &policy).with_optimization(true);
 
-        let solver_result = match solver.solve(&request) {
+        let solving_phase = self.composer.profiler.phase("solving");
+        let solve_result = solver.solve(&request);
+        drop(solving_phase);
+
+        let solver_result = match solve_result {
             Ok(result) => result,
             Err(problems) => {
                 spinner.finish_and_clear();
-                eprintln!("{} Could not resolve dependencies", style("Error:").red().bold());
+                let io = &self.composer.io;
+                io.write_error_at(&format!("{} Could not resolve dependencies", style("Error:").red().bold()), Verbosity::Quiet);
                 for problem in problems.problems() {
-                    eprintln!("  {}", problem.describe(&pool));
+                    io.write_error_at(&format!("  {}", problem.describe(&pool)), Verbosity::Quiet);
                 }
-                return Ok(1);
+                return Ok(Err(PlanFailure::Unresolvable));
             }
         };
 
-        spinner.set_message("Installing packages...");
-
         let present_packages = self.load_installed_packages();
         let transaction = Transaction::from_packages(
             present_packages,
@@ -360,6 +562,11 @@ impl Installer {
             .filter(|p| !is_platform_package(&p.name))
             .collect();
 
+        if !self.check_license_policy(&packages) {
+            spinner.finish_and_clear();
+            return Ok(Err(PlanFailure::LicensePolicy));
+        }
+
         let summary = transaction.summary();
         let lock_file_changed = summary.installs > 0 || summary.updates > 0 || summary.uninstalls > 0;
 
@@ -372,11 +579,8 @@ impl Installer {
         let (prod_packages, dev_packages): (Vec<_>, Vec<_>) = packages.iter()
             .partition(|p| non_dev_packages.contains(&p.name.to_lowercase()));
 
-        let install_count = packages.len();
-        let update_count = 0; // TODO: track updates vs installs properly
-        let removal_count = 0; // TODO: track removals
         log::info!("Lock file operations: {} installs, {} updates, {} removals",
-            install_count, update_count, removal_count);
+            summary.installs, summary.updates, summary.uninstalls);
 
         // Extract platform requirements while preserving order from composer.json
         let platform_reqs: IndexMap<String, String> = composer_json.require.iter()
@@ -389,122 +593,35 @@ impl Installer {
             .map(|(name, constraint)| (name.clone(), constraint.clone()))
             .collect();
 
+        let mut platform_overrides: IndexMap<String, String> = self.composer.config.platform.clone().into_iter().collect();
+        platform_overrides.sort_keys();
+
         let lock = ComposerLock {
             content_hash: crate::util::compute_content_hash(&serde_json::to_string(composer_json).unwrap_or_default()),
             packages: prod_packages.iter().map(|p| LockedPackage::from(*p)).collect(),
             packages_dev: dev_packages.iter().map(|p| LockedPackage::from(*p)).collect(),
             minimum_stability: composer_json.minimum_stability.clone().unwrap_or_else(|| "stable".to_string()),
-            prefer_stable: composer_json.prefer_stable.unwrap_or(false),
+            stability_flags: stability_flags
+                .iter()
+                .map(|(name, stability)| (name.clone(), stability.priority()))
+                .collect(),
+            prefer_stable,
             prefer_lowest,
             platform: platform_reqs,
             platform_dev: platform_dev_reqs,
+            platform_overrides,
             plugin_api_version: "2.9.0".to_string(),
+            aliases: root_aliases,
             ..Default::default()
         };
 
-        // Only write lock file if there were changes
-        if lock_file_changed && !dry_run {
-            log::debug!("Writing lock file");
-            let mut lock_content = serde_json::to_string_pretty(&lock).context("Failed to serialize composer.lock")?;
-            // Add trailing newline to match Composer's format
-            lock_content.push('\n');
-            std::fs::write(working_dir.join("composer.lock"), lock_content).context("Failed to write composer.lock")?;
-        }
-
-        if update_lock_only {
-             spinner.finish_and_clear();
-             if lock_file_changed {
-                 println!("{} Lock file updated", style("Success:").green().bold());
-             } else {
-                 println!("{} Lock file is up to date", style("Info:").cyan());
-             }
-             return Ok(0);
-        }
-
-        log::debug!("Installing dependencies from lock file");
-        log::info!("Package operations: {} installs, {} updates, {} removals",
-            install_count, update_count, removal_count);
-
-        let manager = &self.composer.installation_manager;
-        let result = manager.install_packages(&packages).await
-            .map_err(|e| anyhow::anyhow!("Failed to install packages: {}", e))?;
-
-        spinner.finish_and_clear();
-
-        let actually_installed: Vec<_> = result.installed.iter()
-            .filter(|p| !is_platform_package(&p.name))
-            .collect();
-
-        for pkg in &actually_installed {
-            log::debug!("Installed {} ({})", pkg.name, pkg.version);
-            println!("  {} {} ({})", style("-").green(), style(&pkg.name).white().bold(), style(&pkg.version).yellow());
-        }
-
-        if !dry_run {
-             println!("{} Generating autoload files", style("Info:").cyan());
-             
-             let aliases_map: HashMap<String, Vec<String>> = HashMap::new();
-             let dev_mode = !no_dev;
-
-             let mut package_autoloads: Vec<PackageAutoload> = lock.packages.iter()
-                .map(|lp| locked_package_to_autoload(lp, false, &aliases_map))
-                .collect();
-             if dev_mode {
-                 package_autoloads.extend(lock.packages_dev.iter().map(|lp| locked_package_to_autoload(lp, true, &aliases_map)));
-             }
-
-             let autoload_config = AutoloadConfig {
-                 vendor_dir: manager.config().vendor_dir.clone(),
-                 base_dir: working_dir.clone(),
-                 optimize: optimize_autoloader,
-                 suffix: Some(lock.content_hash.clone()),
-                 ..Default::default()
-             };
-
-             let generator = AutoloadGenerator::new(autoload_config);
-
-             let root_autoload: Option<Autoload> = Some(composer_json.autoload.clone().into());
-
-             let root_package = create_root_package_info(
-                 composer_json,
-                 &root_version,
-                 working_dir,
-                 Vec::new(),
-                 dev_mode,
-             );
-
-             generator.generate(&package_autoloads, root_autoload.as_ref(), Some(&root_package))
-                 .context("Failed to generate autoloader")?;
-
-             // Dispatch post-autoload-dump event (runs scripts and plugins)
-             let arc_packages: Vec<Arc<Package>> = packages.iter().map(|p| Arc::new(p.clone())).collect();
-             let event = PostAutoloadDumpEvent::new(arc_packages, !no_dev, optimize_autoloader);
-             let exit_code = self.composer.dispatch(&event)?;
-             if exit_code != 0 {
-                 return Ok(exit_code);
-             }
-        }
-
-        let total_changed = actually_installed.len() + result.updated.len();
-        if total_changed > 0 || lock_file_changed {
-            println!("{} {} packages updated", style("Success:").green().bold(), total_changed);
-        } else {
-            println!("{} Nothing to update.", style("Info:").cyan());
-        }
-
-        if !dry_run {
-            self.audit_abandoned_packages(&packages);
-        }
-
-        // Dispatch post-update event
-        if !dry_run {
-            let exit_code = self.composer.dispatch(&PostUpdateEvent::new(!no_dev))?;
-            if exit_code != 0 {
-                return Ok(exit_code);
-            }
-        }
-
-        Ok(0)
+        Ok(Ok(UpdatePlan {
+            transaction,
+            packages,
+            lock,
+            lock_file_changed,
+            root_version,
+        }))
     }
 
     pub async fn install(&self, no_scripts: bool, optimize_autoloader: bool, _classmap_authoritative: bool, _apcu_autoloader: bool, _ignore_platform_reqs: bool) -> Result<i32> {
@@ -531,27 +648,50 @@ impl Installer {
         }
 
         if packages.is_empty() {
-             println!("{} Nothing to install.", style("Info:").cyan());
+             self.composer.io.write(&format!("{} Nothing to install.", style("Info:").cyan()));
              return Ok(0);
         }
 
-        println!("{} Installing dependencies from lock file", style("Composer").green().bold());
-        if dry_run { println!("{} Running in dry-run mode", style("Info:").cyan()); }
+        self.composer.io.write(&format!("{} Installing dependencies from lock file", style("Composer").green().bold()));
+        if dry_run { self.composer.io.write(&format!("{} Running in dry-run mode", style("Info:").cyan())); }
 
-        let progress = ProgressBar::new(packages.len() as u64);
-        progress.set_style(ProgressStyle::default_bar().template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}").unwrap().progress_chars("#>-"));
-        progress.enable_steady_tick(Duration::from_millis(100));
+        if !self.check_locked_packages(&packages) {
+            return Ok(1);
+        }
 
-        let manager = &self.composer.installation_manager;
-        let result = manager.install_packages(&packages).await.context("Failed to install packages")?;
+        if dry_run {
+            let vendor_repo = InstalledRepository::new(install_config.vendor_dir.clone());
+            vendor_repo.load().await.ok();
+            let transaction = Transaction::from_packages(
+                vendor_repo.get_packages().await,
+                packages.iter().map(|p| Arc::new(p.clone())).collect(),
+                Vec::new(),
+            );
+            print_transaction_preview(self.composer.io.as_ref(), "Package operations", &transaction, &packages, composer_json);
+            return Ok(0);
+        }
+
+        let progress = if self.composer.io.is_quiet() {
+            ProgressBar::hidden()
+        } else {
+            let progress = ProgressBar::new(packages.len() as u64);
+            progress.set_style(ProgressStyle::default_bar().template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}").unwrap().progress_chars("#>-"));
+            progress.enable_steady_tick(Duration::from_millis(100));
+            progress
+        };
+
+        let result = self.install_locked_packages(lock, &packages).await?;
 
         progress.finish_and_clear();
 
         if !result.installed.is_empty() {
              for pkg in &result.installed {
-                 println!("  {} {} ({})", style("-").green(), style(&pkg.name).white().bold(), style(&pkg.version).yellow());
+                 self.composer.io.write(&format!("  {} {} ({})", style("-").green(), style(&pkg.name).white().bold(), style(&pkg.version).yellow()));
              }
         }
+        for pkg in &result.removed {
+            self.composer.io.write(&format!("  {} Removing {} ({})", style("-").red(), style(&pkg.name).white().bold(), style(&pkg.version).yellow()));
+        }
 
         if !dry_run {
              // Dispatch pre-autoload-dump event
@@ -560,44 +700,10 @@ impl Installer {
                  if exit_code != 0 { return Ok(exit_code); }
              }
 
-             println!("{} Generating autoload files", style("Info:").cyan());
+             self.composer.io.write(&format!("{} Generating autoload files", style("Info:").cyan()));
              
-             let mut aliases_map: HashMap<String, Vec<String>> = HashMap::new();
-             for alias in &lock.aliases {
-                 aliases_map.entry(alias.package.clone()).or_default().push(alias.alias.clone());
-             }
              let dev_mode = !no_dev;
-             let mut package_autoloads: Vec<PackageAutoload> = lock.packages.iter()
-                 .map(|lp| locked_package_to_autoload(lp, false, &aliases_map))
-                 .collect();
-             if dev_mode {
-                 package_autoloads.extend(lock.packages_dev.iter().map(|lp| locked_package_to_autoload(lp, true, &aliases_map)));
-             }
-             
-             let autoload_config = AutoloadConfig {
-                 vendor_dir: manager.config().vendor_dir.clone(),
-                 base_dir: working_dir.clone(),
-                 optimize: optimize_autoloader,
-                 suffix: if !lock.content_hash.is_empty() { Some(lock.content_hash.clone()) } else { None },
-                 ..Default::default()
-             };
-
-             let generator = AutoloadGenerator::new(autoload_config);
-             // Root autoload from json
-             let root_autoload: Option<Autoload> = Some(composer_json.autoload.clone().into());
-             let root_aliases = aliases_map
-                 .get(&composer_json.name.clone().unwrap_or_default())
-                 .cloned()
-                 .unwrap_or_default();
-             let root_package = create_root_package_info(
-                 composer_json,
-                 &root_version,
-                 working_dir,
-                 root_aliases,
-                 dev_mode,
-             );
-
-             generator.generate(&package_autoloads, root_autoload.as_ref(), Some(&root_package)).context("Failed to generate autoloader")?;
+             self.generate_autoloader(lock, &root_version, optimize_autoloader, dev_mode)?;
 
              // Dispatch post-autoload-dump event (runs scripts and plugins)
              if !no_scripts {
@@ -608,9 +714,11 @@ impl Installer {
              }
         }
 
-        println!("{} {} packages installed", style("Success:").green().bold(), result.installed.len());
+        let summary = operations_summary(result.installed.len(), result.updated.len(), result.removed.len());
+        self.composer.io.write(&format!("{} {}", style("Success:").green().bold(), summary));
 
         if !dry_run {
+            self.report_suggestions(&result.installed, &packages, !no_dev);
             self.audit_abandoned_packages(&packages);
         }
 
@@ -623,6 +731,151 @@ impl Installer {
         Ok(0)
     }
 
+    /// Check the packages of a lock file before installing them: the license
+    /// policy, and platform overrides differing from the runtime at hand.
+    ///
+    /// Returns false if the license policy forbids installing the packages.
+    pub fn check_locked_packages(&self, packages: &[Package]) -> bool {
+        if !self.check_license_policy(packages) {
+            return false;
+        }
+
+        // The lock was resolved against the overridden platform, not the one running now
+        if !self.composer.platform_packages.is_empty() {
+            for (name, version, detected) in platform_override_differences(&self.composer.platform_packages, &self.composer.config.platform) {
+                let detected = detected.unwrap_or_else(|| "missing".to_string());
+                self.composer.io.write_error(&format!(
+                    "{} {} is overridden to {} in config.platform, but the runtime provides {}",
+                    style("Warning:").yellow(), name, version, detected
+                ));
+            }
+        }
+
+        true
+    }
+
+    /// Install the resolved `packages` of `lock` into vendor, removing the dev
+    /// packages in no-dev mode, and record them in installed.json.
+    ///
+    /// The removed dev packages are part of [`InstallResult::removed`].
+    pub async fn install_locked_packages(&self, lock: &ComposerLock, packages: &[Package]) -> Result<InstallResult> {
+        let manager = &self.composer.installation_manager;
+        let no_dev = manager.config().no_dev;
+
+        let installing_phase = self.composer.profiler.phase("installing");
+        let mut result = manager.install_packages_with_events(packages, &self.composer).await
+            .context("Failed to install packages")?;
+        if no_dev {
+            result.removed.extend(self.remove_dev_packages(lock).await?);
+        }
+        manager.notify_installs(&result.installed).await;
+        drop(installing_phase);
+
+        self.write_installed_repository(lock, !no_dev).await?;
+        Ok(result)
+    }
+
+    /// Write the autoloader for the packages in `lock` without printing anything.
+    pub fn generate_autoloader(&self, lock: &ComposerLock, root_version: &RootVersion, optimize: bool, dev_mode: bool) -> Result<()> {
+        let _phase = self.composer.profiler.phase("autoload dump");
+        let composer_json = &self.composer.composer_json;
+        let working_dir = &self.composer.working_dir;
+
+        let mut aliases_map: HashMap<String, Vec<String>> = HashMap::new();
+        for alias in &lock.aliases {
+            aliases_map.entry(alias.package.clone()).or_default().push(alias.alias.clone());
+        }
+
+        let mut package_autoloads: Vec<PackageAutoload> = lock.packages.iter()
+            .map(|lp| locked_package_to_autoload(lp, false, &aliases_map))
+            .collect();
+        if dev_mode {
+            package_autoloads.extend(lock.packages_dev.iter().map(|lp| locked_package_to_autoload(lp, true, &aliases_map)));
+        }
+        apply_installer_paths(&mut package_autoloads, self.composer.installation_manager.config());
+
+        let autoload_config = AutoloadConfig {
+            vendor_dir: self.composer.installation_manager.config().vendor_dir.clone(),
+            base_dir: working_dir.clone(),
+            optimize,
+            suffix: if !lock.content_hash.is_empty() { Some(lock.content_hash.clone()) } else { None },
+            ..Default::default()
+        };
+
+        let generator = AutoloadGenerator::new(autoload_config);
+        let root_autoload = root_autoload(composer_json, dev_mode);
+        let root_aliases = aliases_map
+            .get(&composer_json.name.clone().unwrap_or_default())
+            .cloned()
+            .unwrap_or_default();
+        let root_package = create_root_package_info(
+            composer_json,
+            root_version,
+            working_dir,
+            root_aliases,
+            dev_mode,
+        );
+
+        generator.generate(&package_autoloads, Some(&root_autoload), Some(&root_package)).context("Failed to generate autoloader")
+    }
+
+    /// Remove the `packages-dev` of `lock` that are not also in `packages` from vendor,
+    /// returns the ones that were present and removed.
+    pub async fn remove_dev_packages(&self, lock: &ComposerLock) -> Result<Vec<Package>> {
+        let required: HashSet<String> = lock.packages.iter().map(|p| p.name.to_lowercase()).collect();
+        let dev_packages: Vec<Package> = lock.packages_dev.iter()
+            .filter(|p| !required.contains(&p.name.to_lowercase()))
+            .map(Package::from)
+            .collect();
+
+        self.composer.installation_manager.remove_packages_with_events(&dev_packages, &self.composer).await.context("Failed to remove dev packages")
+    }
+
+    /// Write vendor/composer/installed.json for the packages of `lock`.
+    pub async fn write_installed_repository(&self, lock: &ComposerLock, dev_mode: bool) -> Result<()> {
+        if self.composer.installation_manager.config().dry_run {
+            return Ok(());
+        }
+
+        let install_config = self.composer.installation_manager.config();
+        let mut repository = InstalledRepository::new(install_config.vendor_dir.clone())
+            .with_installer_paths(install_config.installer_paths.clone());
+        let mut packages: Vec<Package> = lock.packages.iter()
+            .filter(|p| !is_platform_package(&p.name))
+            .map(Package::from)
+            .collect();
+
+        let mut dev_package_names = Vec::new();
+        if dev_mode {
+            for package in &lock.packages_dev {
+                if !is_platform_package(&package.name) {
+                    dev_package_names.push(package.name.clone());
+                    packages.push(Package::from(package));
+                }
+            }
+        }
+
+        let composer_json = &self.composer.composer_json;
+        let reasons = install_reasons(composer_json.require.keys(), composer_json.require_dev.keys(), &packages, &dev_package_names);
+        for package in packages {
+            repository.add_package(package).await;
+        }
+
+        repository.set_install_reasons(reasons).await;
+        repository.set_dev_mode(dev_mode, dev_package_names).await;
+        repository.write().await.context("Failed to write installed.json")
+    }
+
+    /// The Composer instance the installer works on.
+    pub fn composer(&self) -> &Composer {
+        &self.composer
+    }
+
+    /// Give back the Composer instance the installer was created with.
+    pub fn into_composer(self) -> Composer {
+        self.composer
+    }
+
     pub fn dump_autoload(&self, optimize: bool, authoritative: bool, apcu: bool, no_dev: bool) -> Result<()> {
         let composer_json = &self.composer.composer_json;
         let working_dir = &self.composer.working_dir;
@@ -631,7 +884,7 @@ impl Installer {
         // Detect root package version
         let root_version = get_root_version(working_dir, composer_json);
 
-        println!("{} Generating autoload files", style("Info:").cyan());
+        self.composer.io.write(&format!("{} Generating autoload files", style("Info:").cyan()));
             
         let mut aliases_map: HashMap<String, Vec<String>> = HashMap::new();
         let mut package_autoloads: Vec<PackageAutoload> = Vec::new();
@@ -650,6 +903,7 @@ impl Installer {
             if dev_mode {
                 package_autoloads.extend(lock.packages_dev.iter().map(|lp| locked_package_to_autoload(lp, true, &aliases_map)));
             }
+            apply_installer_paths(&mut package_autoloads, manager.config());
             
             all_installed_packages = lock.packages.iter().map(Package::from).collect();
             if dev_mode {
@@ -673,7 +927,7 @@ impl Installer {
 
         let generator = AutoloadGenerator::new(autoload_config);
         // Root autoload from json
-        let root_autoload: Option<Autoload> = Some(composer_json.autoload.clone().into());
+        let root_autoload = Some(root_autoload(composer_json, dev_mode));
         let root_aliases = aliases_map
             .get(&composer_json.name.clone().unwrap_or_default())
             .cloned()
@@ -686,7 +940,9 @@ impl Installer {
             dev_mode,
         );
 
+        let autoload_phase = self.composer.profiler.phase("autoload dump");
         generator.generate(&package_autoloads, root_autoload.as_ref(), Some(&root_package)).context("Failed to generate autoloader")?;
+        drop(autoload_phase);
 
         // Dispatch post-autoload-dump event (runs scripts and plugins)
         let arc_packages: Vec<Arc<Package>> = all_installed_packages.iter().map(|p| Arc::new(p.clone())).collect();
@@ -694,9 +950,9 @@ impl Installer {
         self.composer.dispatch(&event)?;
 
         if optimize || authoritative {
-            println!("{} Generated optimized autoload files", style("Success:").green().bold());
+            self.composer.io.write(&format!("{} Generated optimized autoload files", style("Success:").green().bold()));
         } else {
-            println!("{} Generated autoload files", style("Success:").green().bold());
+            self.composer.io.write(&format!("{} Generated autoload files", style("Success:").green().bold()));
         }
 
         Ok(())
@@ -721,34 +977,99 @@ impl Installer {
         packages
     }
 
-    fn audit_abandoned_packages(&self, packages: &[Package]) {
-        let mut abandoned_packages: Vec<_> = packages
-            .iter()
-            .filter(|p| p.is_abandoned() && !p.is_platform_package())
-            .collect();
+    /// Check package licenses against the configured license policy.
+    ///
+    /// Returns false if violations were found and `license-policy` is set to `fail`.
+    fn check_license_policy(&self, packages: &[Package]) -> bool {
+        let config = &self.composer.config;
+        let spdx = spdx_licenses(config);
+        let mut passed = true;
+
+        if let Some(policy) = policy_from_config(config) {
+            let violations = find_license_violations(packages, &policy, &spdx);
+            if !violations.is_empty() {
+                let fail = config.license_policy == LicensePolicyMode::Fail;
+                let heading = format!("{} package(s) violate the license policy:", violations.len());
+                self.report_license_violations(&heading, &violations, fail, false);
+                passed &= !fail;
+            }
+        }
 
-        if abandoned_packages.is_empty() {
-            return;
+        if config.license_compatibility != LicensePolicyMode::Off {
+            if let Some(project) = project_license(&self.composer.composer_json.licenses(), &spdx) {
+                let violations = find_license_incompatibilities(packages, &project, config.license_usage, &spdx);
+                if !violations.is_empty() {
+                    let fail = config.license_compatibility == LicensePolicyMode::Fail;
+                    let heading = format!(
+                        "{} package(s) have a license incompatible with the project license {} ({} use):",
+                        violations.len(),
+                        project,
+                        config.license_usage
+                    );
+                    self.report_license_violations(&heading, &violations, fail, true);
+                    passed &= !fail;
+                }
+            }
         }
 
-        abandoned_packages.sort_by(|a, b| a.name.cmp(&b.name));
-
-        eprintln!();
-        for pkg in abandoned_packages {
-            if let Some(ref abandoned) = pkg.abandoned {
-                let replacement = match abandoned.replacement() {
-                    Some(repl) => format!("Use {} instead", repl),
-                    None => "No replacement was suggested".to_string(),
-                };
-                eprintln!(
-                    "{} Package {} is abandoned, you should avoid using it. {}.",
-                    style("Warning:").yellow(),
-                    pkg.name,
-                    replacement
+        passed
+    }
+
+    fn report_license_violations(&self, heading: &str, violations: &[LicenseViolation], fail: bool, explain: bool) {
+        let label = if fail { style("Error:").red().bold() } else { style("Warning:").yellow() };
+
+        // A failing policy aborts the install, so it is reported even in quiet mode
+        let verbosity = if fail { Verbosity::Quiet } else { Verbosity::Normal };
+        let io = &self.composer.io;
+        io.write_error_at("", verbosity);
+        io.write_error_at(&format!("{} {}", label, heading), verbosity);
+        for violation in violations {
+            if explain {
+                io.write_error_at(
+                    &format!("  - {} ({}) is licensed under {}", violation.package, violation.version, violation.license),
+                    verbosity,
+                );
+                for reason in &violation.reasons {
+                    io.write_error_at(&format!("      {}", reason), verbosity);
+                }
+            } else {
+                io.write_error_at(
+                    &format!(
+                        "  - {} ({}) is licensed under {}: {}",
+                        violation.package,
+                        violation.version,
+                        violation.license,
+                        violation.reasons.join(", ")
+                    ),
+                    verbosity,
                 );
             }
         }
     }
+
+    /// Print how many packages the freshly installed dependencies suggest
+    fn report_suggestions(&self, installed: &[Package], packages: &[Package], dev_mode: bool) {
+        let suggestions = new_suggestions(installed, packages, &self.composer.composer_json, dev_mode);
+        if let Some(summary) = suggestions_summary(&suggestions) {
+            self.composer.io.write(&style(summary).cyan().to_string());
+        }
+    }
+
+    fn audit_abandoned_packages(&self, packages: &[Package]) {
+        if self.audit || self.composer.config.audit.abandoned == "ignore" {
+            return;
+        }
+
+        let warnings = abandoned_warnings(packages);
+        if warnings.is_empty() {
+            return;
+        }
+
+        self.composer.io.write_error("");
+        for warning in warnings {
+            self.composer.io.write_error(&format!("{} {}", style("Warning:").yellow(), warning));
+        }
+    }
 }
 
 // Helpers
@@ -777,6 +1098,106 @@ fn get_root_version(working_dir: &std::path::Path, composer_json: &ComposerJson)
     root_version
 }
 
+/// Constraint without its inline alias, `dev-main as 2.0.x-dev` becomes `dev-main`.
+fn strip_inline_alias(constraint: &str) -> &str {
+    match constraint.split_once(" as ") {
+        Some((actual, _)) => actual.trim(),
+        None => constraint,
+    }
+}
+
+/// Collects the inline aliases of the root requirements, e.g. `"vendor/package": "dev-main as 2.0.x-dev"`.
+///
+/// They are added to the pool as root aliases and written to the lock file's `aliases` section.
+fn extract_root_aliases(composer_json: &ComposerJson, no_dev: bool) -> Vec<LockAlias> {
+    let parser = VersionParser::new();
+    let mut requires: Vec<_> = composer_json.require.iter().collect();
+    if !no_dev {
+        requires.extend(composer_json.require_dev.iter());
+    }
+    requires.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut aliases: Vec<LockAlias> = Vec::new();
+    for (name, constraint) in requires {
+        let Some((actual, alias)) = parse_inline_alias(constraint) else {
+            continue;
+        };
+        let (Ok(version), Ok(alias_normalized)) = (parser.normalize(&actual), parser.normalize(&alias)) else {
+            log::warn!("Ignoring invalid inline alias \"{}\" of {}", constraint, name);
+            continue;
+        };
+        let package = name.to_lowercase();
+        if aliases.iter().any(|a| a.package == package && a.version == version) {
+            continue;
+        }
+
+        aliases.push(LockAlias { package, version, alias, alias_normalized });
+    }
+
+    aliases
+}
+
+/// Names of the Symfony components: the packages `symfony/symfony` replaces in the
+/// versions matching `constraint`, and `symfony/symfony` itself.
+async fn symfony_components(repo_manager: &RepositoryManager, constraint: &str) -> HashSet<String> {
+    let mut components: HashSet<String> = repo_manager
+        .find_matching_packages("symfony/symfony", constraint, false)
+        .await
+        .iter()
+        .flat_map(|pkg| pkg.replace.keys())
+        .map(|name| name.to_lowercase())
+        .collect();
+    if !components.is_empty() {
+        components.insert("symfony/symfony".to_string());
+    }
+    components
+}
+
+/// Drops the versions of the Symfony components not matching `constraint`.
+///
+/// Branches are kept when their branch alias matches. Other packages,
+/// including independently versioned `symfony/*` ones like the polyfills, are
+/// left alone.
+fn restrict_symfony_packages(packages: Vec<Arc<Package>>, constraint: &str, components: &HashSet<String>) -> Vec<Arc<Package>> {
+    let parser = VersionParser::new();
+    let Ok(parsed) = parser.parse_constraints(constraint) else {
+        log::warn!("Ignoring invalid extra.symfony.require constraint \"{}\"", constraint);
+        return packages;
+    };
+    let matches = |version: &str| {
+        let normalized = parser.normalize(version).unwrap_or_else(|_| version.to_string());
+        Constraint::new(Operator::Equal, normalized).is_ok_and(|c| parsed.matches(&c))
+    };
+
+    packages
+        .into_iter()
+        .filter(|pkg| {
+            !components.contains(&pkg.name.to_lowercase())
+                || matches(&pkg.version)
+                || branch_alias(pkg).is_some_and(|alias| matches(alias.version()))
+        })
+        .collect()
+}
+
+/// Adds a package to the pool together with its branch alias and matching root aliases.
+fn add_package_with_aliases(pool: &mut Pool, pkg: Arc<Package>, root_aliases: &[LockAlias]) {
+    if pool.add_package_arc(Arc::clone(&pkg), None) == 0 {
+        return;
+    }
+
+    if let Some(alias) = branch_alias(&pkg) {
+        pool.add_alias_package_arc(Arc::new(alias), None);
+    }
+
+    for root_alias in root_aliases {
+        if root_alias.package == pkg.name.to_lowercase() && root_alias.version == pkg.version {
+            let mut alias = AliasPackage::new(Arc::clone(&pkg), root_alias.alias_normalized.clone(), root_alias.alias.clone());
+            alias.set_root_package_alias(true);
+            pool.add_alias_package_arc(Arc::new(alias), None);
+        }
+    }
+}
+
 /// Creates a root package that can be added to the solver pool.
 ///
 /// This creates a Package with the root's replace/provide/conflict declarations
@@ -802,6 +1223,11 @@ fn create_root_package(composer_json: &ComposerJson, root_version: &RootVersion)
     pkg
 }
 
+/// Whether the root package has links the solver has to know about
+fn has_root_links(root_pkg: &Package) -> bool {
+    !root_pkg.replace.is_empty() || !root_pkg.provide.is_empty() || !root_pkg.conflict.is_empty()
+}
+
 /// Creates a RootPackageInfo for autoload generation.
 fn create_root_package_info(
     composer_json: &ComposerJson,
@@ -824,15 +1250,127 @@ fn create_root_package_info(
     }
 }
 
-fn extract_stability_flag(constraint: &str) -> Option<Stability> {
-    if let Some(at_pos) = constraint.rfind('@') {
-        let stability_str = &constraint[at_pos + 1..];
-        let stability: Stability = stability_str.parse().ok()?;
-        if stability != Stability::Stable {
-            return Some(stability);
+/// Stability flags of the root requirements, like Composer's RootPackageLoader.
+///
+/// An explicit `@<stability>` flag sets the stability of its package, the most
+/// unstable flag wins. Without one, a requirement on an unstable version like
+/// `dev-main` or `^1.0-beta` lowers the stability below `minimum_stability`.
+fn extract_stability_flags<'a>(
+    requires: impl IntoIterator<Item = (&'a String, &'a String)>,
+    minimum_stability: Stability,
+) -> IndexMap<String, Stability> {
+    let mut flags: IndexMap<String, Stability> = IndexMap::new();
+
+    for (name, constraint) in requires {
+        let name = name.to_lowercase();
+        let constraints: Vec<&str> = constraint
+            .split('|')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .flat_map(|or_constraint| -> Vec<&str> {
+                // An inline alias like `dev-main as 2.0.x-dev` is a single constraint
+                if or_constraint.contains(" as ") {
+                    vec![or_constraint]
+                } else {
+                    or_constraint
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .filter(|c| !c.is_empty() && *c != "-")
+                        .collect()
+                }
+            })
+            .collect();
+
+        let mut explicit = false;
+        for constraint in &constraints {
+            let Some((_, flag)) = constraint.rsplit_once('@') else { continue };
+            let Ok(stability) = VersionParser::normalize_stability(flag) else { continue };
+            let stability: Stability = stability.as_str().parse().unwrap_or(Stability::Stable);
+            explicit = true;
+            if flags.get(&name).is_some_and(|f| f.priority() > stability.priority()) {
+                continue;
+            }
+            flags.insert(name.clone(), stability);
+        }
+        if explicit {
+            continue;
+        }
+
+        for constraint in &constraints {
+            let version = parse_inline_alias(constraint).map_or(constraint.to_string(), |(actual, _)| actual);
+            if version.contains(['@', ',']) || version.contains(char::is_whitespace) {
+                continue;
+            }
+            let stability: Stability = VersionParser::parse_stability(&version)
+                .as_str()
+                .parse()
+                .unwrap_or(Stability::Stable);
+            if stability == Stability::Stable
+                || minimum_stability.priority() > stability.priority()
+                || flags.get(&name).is_some_and(|f| f.priority() > stability.priority())
+            {
+                continue;
+            }
+            flags.insert(name.clone(), stability);
         }
     }
-    None
+
+    flags.sort_keys();
+    flags
+}
+
+/// Print the operations of a transaction together with why each package is needed.
+fn print_transaction_preview(io: &dyn Io, label: &str, transaction: &Transaction, packages: &[Package], composer_json: &ComposerJson) {
+    let summary = transaction.summary();
+    io.write(&format!(
+        "{} {}: {} installs, {} updates, {} removals",
+        style("Info:").cyan(),
+        label,
+        summary.installs,
+        summary.updates,
+        summary.uninstalls
+    ));
+
+    let mut transaction = transaction.clone();
+    transaction.sort();
+
+    for operation in &transaction.operations {
+        let (marker, reason) = match operation {
+            Operation::Install(pkg) => (style("+").green(), operation_reason(&pkg.name, packages, composer_json)),
+            Operation::Update { to, .. } if operation.is_downgrade() => {
+                (style("v").yellow(), operation_reason(&to.name, packages, composer_json))
+            }
+            Operation::Update { to, .. } => (style("^").cyan(), operation_reason(&to.name, packages, composer_json)),
+            Operation::Uninstall(_) => (style("-").red(), Some("no longer required".to_string())),
+            _ => continue,
+        };
+
+        match reason {
+            Some(reason) => io.write(&format!("  {} {} {}", marker, operation, style(format!("[{}]", reason)).dim())),
+            None => io.write(&format!("  {} {}", marker, operation)),
+        }
+    }
+
+    if transaction.is_empty() {
+        io.write(&format!("{} Nothing to install, update or remove", style("Info:").cyan()));
+    }
+}
+
+/// Explain why a package is part of the resolved set.
+fn operation_reason(name: &str, packages: &[Package], composer_json: &ComposerJson) -> Option<String> {
+    let name_lower = name.to_lowercase();
+    let requires = |map: &IndexMap<String, String>| map.keys().any(|k| k.to_lowercase() == name_lower);
+
+    if requires(&composer_json.require) {
+        return Some("root requirement".to_string());
+    }
+    if requires(&composer_json.require_dev) {
+        return Some("root dev requirement".to_string());
+    }
+
+    packages
+        .iter()
+        .find(|p| requires(&p.require))
+        .map(|p| format!("required by {}", p.name))
 }
 
 fn find_transitive_dependencies(packages: &[Package], roots: &HashSet<String>) -> HashSet<String> {
@@ -865,19 +1403,59 @@ fn find_transitive_dependencies(packages: &[Package], roots: &HashSet<String>) -
     result
 }
 
+/// Root package autoload rules, including `autoload-dev` unless installing with --no-dev.
+fn root_autoload(composer_json: &ComposerJson, dev_mode: bool) -> Autoload {
+    let mut autoload: Autoload = composer_json.autoload.clone().into();
+    if dev_mode {
+        autoload.merge(composer_json.autoload_dev.clone().into());
+    }
+    autoload
+}
+
+/// Warnings for abandoned packages, sorted by package name.
+pub(crate) fn abandoned_warnings(packages: &[Package]) -> Vec<String> {
+    let mut abandoned_packages: Vec<_> = packages
+        .iter()
+        .filter(|p| p.is_abandoned() && !p.is_platform_package())
+        .collect();
+    abandoned_packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+    abandoned_packages
+        .into_iter()
+        .filter_map(|pkg| {
+            let abandoned = pkg.abandoned.as_ref()?;
+            let replacement = match abandoned.replacement() {
+                Some(repl) => format!("Use {} instead", repl),
+                None => "No replacement was suggested".to_string(),
+            };
+            Some(format!("Package {} is abandoned, you should avoid using it. {}.", pkg.name, replacement))
+        })
+        .collect()
+}
+
 fn locked_package_to_autoload(lp: &LockedPackage, is_dev: bool, aliases_map: &HashMap<String, Vec<String>>) -> PackageAutoload {
     let autoload = Autoload::from(&lp.autoload);
     let requires: Vec<String> = lp.require.keys().filter(|k| !is_platform_package(k)).cloned().collect();
     let reference = lp.source.as_ref().map(|s| s.reference.clone()).or_else(|| lp.dist.as_ref().and_then(|d| d.reference.clone()));
-    let aliases = aliases_map.get(&lp.name).cloned().unwrap_or_default();
-    
+    let version = VersionParser::new().normalize(&lp.version).unwrap_or_else(|_| lp.version.clone());
+
+    // Inline aliases from the lock, then the branch alias of a dev version,
+    // InstalledVersions matches constraints against all of them
+    let mut aliases = aliases_map.get(&lp.name).cloned().unwrap_or_default();
+    match parse_branch_aliases(lp.extra.as_ref()).remove(&version) {
+        Some((_, alias_pretty)) => aliases.push(alias_pretty),
+        None if lp.default_branch == Some(true) => aliases.push(DEFAULT_BRANCH_ALIAS.to_string()),
+        None => {}
+    }
+    aliases.dedup();
+
     PackageAutoload {
         name: lp.name.clone(),
         autoload,
         install_path: lp.name.clone(),
         requires,
         pretty_version: Some(lp.version.clone()),
-        version: Some(lp.version.clone()),
+        version: Some(version),
         reference,
         package_type: lp.package_type.clone(),
         dev_requirement: is_dev,
@@ -887,3 +1465,306 @@ fn locked_package_to_autoload(lp: &LockedPackage, is_dev: bool, aliases_map: &Ha
     }
 }
 
+/// Counts of every kind of package operation, for the closing `Success:` line
+fn operations_summary(installs: usize, updates: usize, removals: usize) -> String {
+    format!("{} installs, {} updates, {} removals", installs, updates, removals)
+}
+
+/// Point the autoloader at the custom directory of packages matching `extra.installer-paths`
+fn apply_installer_paths(package_autoloads: &mut [PackageAutoload], install_config: &InstallConfig) {
+    if install_config.installer_paths.is_empty() {
+        return;
+    }
+
+    for pkg in package_autoloads {
+        pkg.install_path = install_config.installer_paths.relative_to_vendor(&pkg.name, &pkg.package_type, &install_config.vendor_dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restrict_symfony_packages() {
+        let package = |name: &str, version: &str| Arc::new(Package::new(name, version));
+        let mut branch = Package::new("symfony/console", "dev-main");
+        branch.extra = Some(serde_json::json!({"branch-alias": {"dev-main": "7.1.x-dev"}}));
+        let packages = vec![
+            package("symfony/console", "7.0.8.0"),
+            package("symfony/console", "7.1.2.0"),
+            Arc::new(branch),
+            package("symfony/console", "7.1.9999999.9999999-dev"),
+            package("symfony/Process", "6.4.0.0"),
+            package("symfony/polyfill-php83", "1.29.0.0"),
+            package("acme/app-kit", "6.4.0.0"),
+        ];
+        let components: HashSet<String> = ["symfony/console", "symfony/process"].iter().map(|s| s.to_string()).collect();
+
+        let kept: Vec<String> = restrict_symfony_packages(packages.clone(), "7.1.*", &components)
+            .iter()
+            .map(|p| format!("{} {}", p.name, p.version))
+            .collect();
+        assert_eq!(kept, vec![
+            "symfony/console 7.1.2.0",
+            "symfony/console dev-main",
+            "symfony/console 7.1.9999999.9999999-dev",
+            "symfony/polyfill-php83 1.29.0.0",
+            "acme/app-kit 6.4.0.0",
+        ]);
+
+        assert_eq!(restrict_symfony_packages(packages.clone(), "not a constraint", &components).len(), packages.len());
+    }
+
+    #[test]
+    fn test_locked_package_to_autoload_versions_and_aliases() {
+        let lp = LockedPackage {
+            name: "vendor/stable".to_string(),
+            version: "v1.2.3".to_string(),
+            ..Default::default()
+        };
+        let autoload = locked_package_to_autoload(&lp, false, &HashMap::new());
+        assert_eq!(autoload.pretty_version.as_deref(), Some("v1.2.3"));
+        assert_eq!(autoload.version.as_deref(), Some("1.2.3.0"));
+        assert!(autoload.aliases.is_empty());
+
+        let lp = LockedPackage {
+            name: "vendor/branch".to_string(),
+            version: "dev-main".to_string(),
+            extra: Some(serde_json::json!({"branch-alias": {"dev-main": "2.x-dev"}})),
+            default_branch: Some(true),
+            ..Default::default()
+        };
+        let aliases_map = HashMap::from([("vendor/branch".to_string(), vec!["1.0.0".to_string()])]);
+        let autoload = locked_package_to_autoload(&lp, true, &aliases_map);
+        assert_eq!(autoload.version.as_deref(), Some("dev-main"));
+        assert_eq!(autoload.aliases, vec!["1.0.0".to_string(), "2.x-dev".to_string()]);
+        assert!(autoload.dev_requirement);
+
+        let lp = LockedPackage {
+            name: "vendor/default".to_string(),
+            version: "dev-master".to_string(),
+            default_branch: Some(true),
+            ..Default::default()
+        };
+        let autoload = locked_package_to_autoload(&lp, false, &HashMap::new());
+        assert_eq!(autoload.aliases, vec![DEFAULT_BRANCH_ALIAS.to_string()]);
+    }
+
+    #[test]
+    fn test_extract_root_aliases() {
+        let composer_json: ComposerJson = serde_json::from_value(serde_json::json!({
+            "require": {
+                "vendor/lib": "dev-main as 2.0.x-dev",
+                "vendor/plain": "^1.0"
+            },
+            "require-dev": {
+                "Vendor/Tool": "1.2.x-dev as 1.2.0"
+            }
+        })).unwrap();
+
+        let aliases = extract_root_aliases(&composer_json, false);
+        assert_eq!(aliases.len(), 2);
+        assert_eq!(aliases[0].package, "vendor/tool");
+        assert_eq!(aliases[0].version, "1.2.9999999.9999999-dev");
+        assert_eq!(aliases[0].alias, "1.2.0");
+        assert_eq!(aliases[0].alias_normalized, "1.2.0.0");
+        assert_eq!(aliases[1].package, "vendor/lib");
+        assert_eq!(aliases[1].version, "dev-main");
+        assert_eq!(aliases[1].alias, "2.0.x-dev");
+        assert_eq!(aliases[1].alias_normalized, "2.0.9999999.9999999-dev");
+
+        assert_eq!(extract_root_aliases(&composer_json, true).len(), 1);
+
+        let require = IndexMap::from([
+            ("vendor/lib".to_string(), "dev-main as 2.0.x-dev".to_string()),
+            ("vendor/other".to_string(), "1.0.0 as 1.1.0".to_string()),
+        ]);
+        let flags = extract_stability_flags(&require, Stability::Stable);
+        assert_eq!(flags.get("vendor/lib"), Some(&Stability::Dev));
+        assert_eq!(flags.get("vendor/other"), None);
+        assert_eq!(strip_inline_alias("dev-main as 2.0.x-dev"), "dev-main");
+        assert_eq!(strip_inline_alias("^1.0"), "^1.0");
+    }
+
+    #[test]
+    fn test_aliases_satisfy_requirements() {
+        // vendor/lib only has dev-main, which vendor/app needs as ^2.0 through its branch alias
+        let mut lib = Package::new("vendor/lib", "dev-main");
+        lib.extra = Some(serde_json::json!({"branch-alias": {"dev-main": "2.0.x-dev"}}));
+        let mut app = Package::new("vendor/app", "1.0.0.0");
+        app.require.insert("vendor/lib".to_string(), "^2.0".to_string());
+
+        let mut pool = Pool::with_minimum_stability(Stability::Stable);
+        pool.add_stability_flag("vendor/lib", Stability::Dev);
+        add_package_with_aliases(&mut pool, Arc::new(lib.clone()), &[]);
+        add_package_with_aliases(&mut pool, Arc::new(app.clone()), &[]);
+
+        let mut request = Request::new();
+        request.require("vendor/app", "^1.0");
+        let policy = Policy::new();
+        let result = Solver::new(&pool, &policy).solve(&request).unwrap();
+        let names: Vec<_> = result.packages.iter().map(|p| (p.name.as_str(), p.version.as_str())).collect();
+        assert_eq!(names, vec![("vendor/app", "1.0.0.0"), ("vendor/lib", "dev-main")]);
+
+        // A root inline alias works the same for a branch without a branch alias
+        lib.extra = None;
+        app.require.insert("vendor/lib".to_string(), "^3.0".to_string());
+        let composer_json: ComposerJson = serde_json::from_value(serde_json::json!({
+            "require": {"vendor/app": "^1.0", "vendor/lib": "dev-main as 3.0.0"}
+        })).unwrap();
+        let root_aliases = extract_root_aliases(&composer_json, false);
+
+        let mut pool = Pool::with_minimum_stability(Stability::Stable);
+        pool.add_stability_flag("vendor/lib", Stability::Dev);
+        add_package_with_aliases(&mut pool, Arc::new(lib), &root_aliases);
+        add_package_with_aliases(&mut pool, Arc::new(app), &root_aliases);
+
+        let mut request = Request::new();
+        request.require("vendor/app", "^1.0");
+        request.require("vendor/lib", "dev-main as 3.0.0");
+        let result = Solver::new(&pool, &policy).solve(&request).unwrap();
+        let names: Vec<_> = result.packages.iter().map(|p| (p.name.as_str(), p.version.as_str())).collect();
+        assert_eq!(names, vec![("vendor/app", "1.0.0.0"), ("vendor/lib", "dev-main")]);
+        assert!(result.aliases.iter().any(|a| a.is_root_package_alias() && a.version() == "3.0.0.0"));
+    }
+
+    /// Update a project with the given composer.json fields against a mock registry
+    async fn update_with_root(root: serde_json::Value) -> anyhow::Result<Vec<String>> {
+        use crate::facade::{NullIo, UpdateOptions};
+        use crate::testing::MockRegistry;
+        use serde_json::json;
+
+        let server = MockRegistry::new()
+            .with_package("acme/lib", "1.0.0", json!({"require": {"acme/util": "^2.0"}}))
+            .with_package("acme/util", "2.0.0", json!({}))
+            .with_package("acme/util", "2.3.0", json!({}))
+            .start()
+            .await
+            .unwrap();
+
+        let mut fields = json!({"version": "1.0.0", "require": {"acme/lib": "^1.0"}});
+        for (key, value) in root.as_object().unwrap() {
+            fields[key] = value.clone();
+        }
+        let dir = server.project(fields).unwrap();
+
+        let mut composer = crate::Composer::open(dir.path()).unwrap();
+        let report = composer.update(None, &UpdateOptions::default(), &NullIo).await?;
+
+        let mut installed: Vec<_> = report
+            .installed
+            .iter()
+            .map(|p| format!("{}@{}", p.name, p.pretty_version.clone().unwrap_or_default()))
+            .collect();
+        installed.sort();
+        Ok(installed)
+    }
+
+    #[tokio::test]
+    async fn test_root_conflict_without_replace_forbids_versions() {
+        let installed = update_with_root(serde_json::json!({"conflict": {"acme/util": ">=2.1"}})).await.unwrap();
+        assert_eq!(installed, vec!["acme/lib@1.0.0", "acme/util@2.0.0"]);
+    }
+
+    #[tokio::test]
+    async fn test_root_replace_drops_replaced_package() {
+        let installed = update_with_root(serde_json::json!({"replace": {"acme/util": "2.5.0"}})).await.unwrap();
+        assert_eq!(installed, vec!["acme/lib@1.0.0"]);
+    }
+
+    #[test]
+    fn test_extract_stability_flags() {
+        let require = IndexMap::from([
+            ("Acme/Explicit".to_string(), "^1.0@beta".to_string()),
+            ("acme/branch".to_string(), "dev-main".to_string()),
+            ("acme/stable".to_string(), "^2.0".to_string()),
+            ("acme/or".to_string(), "1.0.0-RC1 || ^2.0".to_string()),
+            ("acme/most-unstable".to_string(), ">=1.0@alpha, <2.0@dev".to_string()),
+            ("acme/pinned".to_string(), "^1.0@stable".to_string()),
+        ]);
+
+        let flags = extract_stability_flags(&require, Stability::Stable);
+        assert_eq!(
+            flags.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("acme/branch".to_string(), Stability::Dev),
+                ("acme/explicit".to_string(), Stability::Beta),
+                ("acme/most-unstable".to_string(), Stability::Dev),
+                ("acme/or".to_string(), Stability::RC),
+                ("acme/pinned".to_string(), Stability::Stable),
+            ]
+        );
+
+        // Implicit flags only matter below the minimum stability
+        let flags = extract_stability_flags(&require, Stability::Beta);
+        assert_eq!(flags.get("acme/branch"), Some(&Stability::Dev));
+        assert_eq!(flags.get("acme/or"), None);
+        assert_eq!(flags.get("acme/pinned"), Some(&Stability::Stable));
+    }
+
+    async fn lock_with_root(root: serde_json::Value) -> ComposerLock {
+        use crate::facade::{InstallOptions, NullIo, UpdateOptions};
+        use crate::testing::MockRegistry;
+        use serde_json::json;
+
+        let server = MockRegistry::new()
+            .with_package("acme/lib", "1.0.0", json!({}))
+            .with_package("acme/lib", "1.1.0-beta1", json!({}))
+            .with_package("acme/lib", "dev-main", json!({}))
+            .start()
+            .await
+            .unwrap();
+
+        let mut fields = json!({"version": "1.0.0"});
+        for (key, value) in root.as_object().unwrap() {
+            fields[key] = value.clone();
+        }
+        let dir = server.project(fields).unwrap();
+
+        let mut composer = crate::Composer::open(dir.path()).unwrap();
+        let options = UpdateOptions {
+            install: InstallOptions { no_scripts: true, ..Default::default() },
+            lock_only: true,
+            ..Default::default()
+        };
+        composer.update(None, &options, &NullIo).await.unwrap();
+        composer.composer_lock.unwrap()
+    }
+
+    fn locked_version(lock: &ComposerLock) -> &str {
+        &lock.packages[0].version
+    }
+
+    #[tokio::test]
+    async fn test_stability_flags_end_to_end() {
+        use serde_json::json;
+
+        // Without a flag only the stable release is allowed
+        let lock = lock_with_root(json!({"require": {"acme/lib": "^1.0"}})).await;
+        assert_eq!(locked_version(&lock), "1.0.0");
+        assert!(lock.stability_flags.is_empty());
+
+        // A flag allows the newer beta and is written to the lock
+        let lock = lock_with_root(json!({"require": {"acme/lib": "^1.0@beta"}})).await;
+        assert_eq!(locked_version(&lock), "1.1.0-beta1");
+        assert_eq!(lock.stability_flags.get("acme/lib"), Some(&10));
+        assert!(!lock.prefer_stable);
+
+        // prefer-stable picks the stable release although the beta is allowed
+        let lock = lock_with_root(json!({"require": {"acme/lib": "^1.0@beta"}, "prefer-stable": true})).await;
+        assert_eq!(locked_version(&lock), "1.0.0");
+        assert!(lock.prefer_stable);
+
+        // A branch requirement implies the dev flag
+        let lock = lock_with_root(json!({"require": {"acme/lib": "dev-main"}})).await;
+        assert_eq!(locked_version(&lock), "dev-main");
+        assert_eq!(lock.stability_flags.get("acme/lib"), Some(&20));
+
+        // minimum-stability beta allows the beta without a flag
+        let lock = lock_with_root(json!({"require": {"acme/lib": "^1.0"}, "minimum-stability": "beta"})).await;
+        assert_eq!(locked_version(&lock), "1.1.0-beta1");
+        assert_eq!(lock.minimum_stability, "beta");
+        assert!(lock.stability_flags.is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+//! Custom install directories from the root `extra.installer-paths`.
+//!
+//! Follows the composer/installers convention: each path maps to a list of
+//! package names, `type:<package-type>` or `vendor:<vendor>` entries, the
+//! first path with a matching entry wins. Paths are relative to the project
+//! root and may contain the `{$name}`, `{$vendor}` and `{$type}` tokens:
+//!
+//! ```json
+//! "extra": {
+//!     "installer-paths": {
+//!         "web/app/plugins/{$name}/": ["type:wordpress-plugin"],
+//!         "custom/{$vendor}/{$name}/": ["acme/special-module"]
+//!     }
+//! }
+//! ```
+//!
+//! Packages of the generic `library`, `project`, `metapackage` and
+//! `composer-plugin` types always install into the vendor directory.
+
+use std::path::{Path, PathBuf};
+
+use crate::package::Package;
+
+/// Package types that are never moved out of the vendor directory
+const VENDOR_TYPES: &[&str] = &["library", "project", "metapackage", "composer-plugin"];
+
+/// Custom install directories configured in the root `extra.installer-paths`
+#[derive(Debug, Clone, Default)]
+pub struct InstallerPaths {
+    /// Directory the paths are relative to
+    base_dir: PathBuf,
+    /// Path templates with the entries they apply to, in configuration order
+    paths: Vec<(String, Vec<String>)>,
+}
+
+impl InstallerPaths {
+    /// Read `installer-paths` from the root package's `extra`
+    pub fn from_extra(base_dir: impl Into<PathBuf>, extra: &serde_json::Value) -> Self {
+        let paths = extra
+            .get("installer-paths")
+            .and_then(|v| v.as_object())
+            .map(|paths| {
+                paths
+                    .iter()
+                    .map(|(path, entries)| {
+                        let entries = entries
+                            .as_array()
+                            .map(|a| a.iter().filter_map(|e| e.as_str()).map(str::to_lowercase).collect())
+                            .unwrap_or_default();
+                        (path.clone(), entries)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            base_dir: base_dir.into(),
+            paths,
+        }
+    }
+
+    /// Whether no installer paths are configured
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// The custom directory of a package, `None` when it belongs in the vendor directory
+    pub fn install_path(&self, name: &str, package_type: &str) -> Option<PathBuf> {
+        let package_type = package_type.to_lowercase();
+        if VENDOR_TYPES.contains(&package_type.as_str()) {
+            return None;
+        }
+
+        let name = name.to_lowercase();
+        let (vendor, short_name) = name.split_once('/').unwrap_or(("", &name));
+        let type_entry = format!("type:{}", package_type);
+        let vendor_entry = format!("vendor:{}", vendor);
+
+        let (template, _) = self.paths.iter().find(|(_, entries)| {
+            entries.iter().any(|e| *e == name || *e == type_entry || *e == vendor_entry)
+        })?;
+
+        // `{$type}` is the type without its framework prefix, e.g. `plugin` for `wordpress-plugin`
+        let short_type = package_type.split_once('-').map_or(package_type.as_str(), |(_, t)| t);
+        let path = template
+            .replace("{$name}", short_name)
+            .replace("{$vendor}", vendor)
+            .replace("{$type}", short_type);
+
+        Some(self.base_dir.join(path.trim_end_matches('/')))
+    }
+
+    /// Where a package is installed, its custom directory or `vendor_dir/<name>`
+    pub fn resolve(&self, package: &Package, vendor_dir: &Path) -> PathBuf {
+        self.install_path(&package.name, &package.package_type)
+            .unwrap_or_else(|| vendor_dir.join(&package.name))
+    }
+
+    /// The install path of a package relative to `vendor_dir`, as used by the
+    /// autoloader and installed.json, e.g. `acme/lib` or `../web/app/plugins/seo`
+    pub fn relative_to_vendor(&self, name: &str, package_type: &str, vendor_dir: &Path) -> String {
+        match self.install_path(name, package_type) {
+            Some(path) => {
+                let vendor_dir = absolute(vendor_dir);
+                pathdiff::diff_paths(absolute(&path), &vendor_dir)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            }
+            None => name.to_string(),
+        }
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn installer_paths() -> InstallerPaths {
+        InstallerPaths::from_extra("/app", &json!({
+            "installer-paths": {
+                "custom/{$vendor}/{$name}/": ["acme/special-plugin"],
+                "web/app/plugins/{$name}/": ["type:wordpress-plugin"],
+                "web/modules/{$type}/{$name}": ["type:drupal-module", "vendor:drupal"],
+                "custom/plugins/{$name}": ["type:shopware-platform-plugin"]
+            }
+        }))
+    }
+
+    #[test]
+    fn test_install_path_by_type() {
+        let paths = installer_paths();
+
+        assert_eq!(
+            paths.install_path("wpackagist-plugin/akismet", "wordpress-plugin"),
+            Some(PathBuf::from("/app/web/app/plugins/akismet"))
+        );
+        assert_eq!(
+            paths.install_path("frosh/tools", "shopware-platform-plugin"),
+            Some(PathBuf::from("/app/custom/plugins/tools"))
+        );
+        assert_eq!(
+            paths.install_path("drupal/token", "drupal-module"),
+            Some(PathBuf::from("/app/web/modules/module/token"))
+        );
+    }
+
+    #[test]
+    fn test_install_path_first_match_wins() {
+        let paths = installer_paths();
+
+        // Listed by name before the type entry
+        assert_eq!(
+            paths.install_path("Acme/Special-Plugin", "wordpress-plugin"),
+            Some(PathBuf::from("/app/custom/acme/special-plugin"))
+        );
+        // Matched by vendor
+        assert_eq!(
+            paths.install_path("drupal/core-theme", "drupal-theme"),
+            Some(PathBuf::from("/app/web/modules/theme/core-theme"))
+        );
+    }
+
+    #[test]
+    fn test_install_path_vendor_types() {
+        let paths = InstallerPaths::from_extra("/app", &json!({
+            "installer-paths": { "libs/{$name}": ["vendor:acme"] }
+        }));
+
+        assert_eq!(paths.install_path("acme/lib", "library"), None);
+        assert_eq!(paths.install_path("acme/plugin", "composer-plugin"), None);
+        assert_eq!(paths.install_path("other/module", "drupal-module"), None);
+
+        let package = Package::new("acme/lib", "1.0.0.0");
+        assert_eq!(paths.resolve(&package, Path::new("/app/vendor")), PathBuf::from("/app/vendor/acme/lib"));
+    }
+
+    #[test]
+    fn test_relative_to_vendor() {
+        let paths = installer_paths();
+        let vendor_dir = Path::new("/app/vendor");
+
+        assert_eq!(paths.relative_to_vendor("acme/lib", "library", vendor_dir), "acme/lib");
+        assert_eq!(
+            paths.relative_to_vendor("wpackagist-plugin/akismet", "wordpress-plugin", vendor_dir),
+            "../web/app/plugins/akismet"
+        );
+    }
+
+    #[test]
+    fn test_without_installer_paths() {
+        let paths = InstallerPaths::from_extra("/app", &serde_json::Value::Null);
+
+        assert!(paths.is_empty());
+        assert_eq!(paths.install_path("wpackagist-plugin/akismet", "wordpress-plugin"), None);
+    }
+}
@@ -7,10 +7,13 @@ use crate::downloader::{DownloadManager, DownloadResult};
 use crate::package::Package;
 use crate::Result;
 
+use super::installer_paths::InstallerPaths;
+
 /// Library installer for standard Composer packages
 pub struct LibraryInstaller {
     download_manager: Arc<DownloadManager>,
     vendor_dir: PathBuf,
+    installer_paths: InstallerPaths,
 }
 
 impl LibraryInstaller {
@@ -19,12 +22,19 @@ impl LibraryInstaller {
         Self {
             download_manager,
             vendor_dir: vendor_dir.into(),
+            installer_paths: InstallerPaths::default(),
         }
     }
 
+    /// Install packages matching `extra.installer-paths` into their custom directory
+    pub fn with_installer_paths(mut self, installer_paths: InstallerPaths) -> Self {
+        self.installer_paths = installer_paths;
+        self
+    }
+
     /// Get the install path for a package
     pub fn get_install_path(&self, package: &Package) -> PathBuf {
-        self.vendor_dir.join(&package.name)
+        self.installer_paths.resolve(package, &self.vendor_dir)
     }
 
     /// Check if a package is installed
@@ -33,14 +43,20 @@ impl LibraryInstaller {
         install_path.exists()
     }
 
+    /// Check if a package is missing or its installed files are stale
+    pub fn needs_install(&self, package: &Package) -> bool {
+        !self.is_installed(package) || !self.download_manager.is_up_to_date(package)
+    }
+
     /// Install a package
     ///
     /// If the package is already installed, this is a no-op and returns Ok with skipped flag.
+    /// Mirrored path packages are copied again when their source changed.
     pub async fn install(&self, package: &Package) -> Result<DownloadResult> {
         let install_path = self.get_install_path(package);
 
         // Check if already installed - skip if so
-        if install_path.exists() {
+        if install_path.exists() && self.download_manager.is_up_to_date(package) {
             return Ok(DownloadResult {
                 path: install_path,
                 from_cache: false,
@@ -127,4 +143,25 @@ mod tests {
         let result = installer.uninstall(&package).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_reinstall_changed_mirror() {
+        let (installer, temp) = create_test_installer();
+        let source = temp.path().join("packages/package");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("composer.json"), r#"{"name": "vendor/package"}"#).unwrap();
+
+        let mut package = Package::new("vendor/package", "dev-main");
+        let options = [("symlink".to_string(), serde_json::Value::Bool(false))].into_iter().collect();
+        package.dist = Some(crate::package::Dist::new("path", source.to_string_lossy().as_ref()).with_transport_options(options));
+
+        assert!(!installer.install(&package).await.unwrap().skipped);
+        assert!(installer.install(&package).await.unwrap().skipped);
+        assert!(!installer.needs_install(&package));
+
+        std::fs::write(source.join("README.md"), "changed").unwrap();
+        assert!(installer.needs_install(&package));
+        assert!(!installer.install(&package).await.unwrap().skipped);
+        assert!(installer.get_install_path(&package).join("README.md").exists());
+    }
 }
@@ -0,0 +1,297 @@
+//! License policy checks - validates package licenses against the configured
+//! `allowed-licenses` / `denied-licenses` lists before packages are installed.
+//!
+//! With `license-compatibility` enabled, package licenses are also checked
+//! against the license of the root package, taking the `license-usage` of the
+//! project into account (a GPL dependency only matters once the project is
+//! distributed).
+
+use std::path::PathBuf;
+
+use pox_spdx::{LicenseExpr, LicensePolicy, LicenseUsage, SpdxLicenses};
+
+use crate::config::{Config, LicensePolicyMode};
+use crate::package::Package;
+
+/// A package whose license does not satisfy the policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LicenseViolation {
+    pub package: String,
+    pub version: String,
+    /// Declared license, or "none" if the package has no license
+    pub license: String,
+    /// Human readable reasons why the license is rejected
+    pub reasons: Vec<String>,
+}
+
+/// Directory in the cache that `pm licenses --update-spdx-data` writes SPDX data to.
+pub fn spdx_data_dir(config: &Config) -> Option<PathBuf> {
+    config.cache_dir.as_ref().map(|dir| dir.join("spdx"))
+}
+
+/// Load the refreshed SPDX data from the cache, or the data bundled with pox-spdx.
+pub fn spdx_licenses(config: &Config) -> SpdxLicenses {
+    match spdx_data_dir(config) {
+        Some(dir) => SpdxLicenses::from_dir(&dir),
+        None => SpdxLicenses::new(),
+    }
+}
+
+/// Build the license policy from config, or `None` if no policy is configured.
+pub fn policy_from_config(config: &Config) -> Option<LicensePolicy> {
+    if config.license_policy == LicensePolicyMode::Off
+        || (config.allowed_licenses.is_empty() && config.denied_licenses.is_empty())
+    {
+        return None;
+    }
+
+    Some(LicensePolicy::new(
+        config.allowed_licenses.clone(),
+        config.denied_licenses.clone(),
+    ))
+}
+
+/// Evaluate every non-platform package against the policy.
+///
+/// Multiple entries in a package's `license` array are treated as alternatives,
+/// the same way Composer interprets them.
+pub fn find_license_violations(
+    packages: &[Package],
+    policy: &LicensePolicy,
+    spdx: &SpdxLicenses,
+) -> Vec<LicenseViolation> {
+    let mut violations = Vec::new();
+
+    for pkg in packages {
+        if pkg.is_platform_package() || pkg.package_type == "metapackage" {
+            continue;
+        }
+
+        let licenses: Vec<&str> = pkg.license.iter().map(|l| l.as_str()).collect();
+        let version = pkg.pretty_version.clone().unwrap_or_else(|| pkg.version.clone());
+
+        if licenses.is_empty() {
+            if !policy.allowed.is_empty() {
+                violations.push(LicenseViolation {
+                    package: pkg.name.clone(),
+                    version,
+                    license: "none".to_string(),
+                    reasons: vec!["no license is declared".to_string()],
+                });
+            }
+            continue;
+        }
+
+        let license = licenses.join(" OR ");
+        let reasons = match spdx.parse_array(&licenses) {
+            Ok(expr) => match expr.check_policy(policy) {
+                Ok(()) => continue,
+                Err(v) => v.iter().map(|v| v.to_string()).collect(),
+            },
+            // Like a missing license, one that can't be read (e.g. `proprietary`)
+            // only violates an allow list, no deny list can name it
+            Err(_) if policy.allowed.is_empty() => continue,
+            Err(e) => vec![e.to_string()],
+        };
+
+        violations.push(LicenseViolation {
+            package: pkg.name.clone(),
+            version,
+            license,
+            reasons,
+        });
+    }
+
+    violations.sort_by(|a, b| a.package.cmp(&b.package));
+    violations
+}
+
+/// Parse the license of the root package, or `None` if it declares none.
+///
+/// Composer's `proprietary` is not an SPDX identifier and becomes a reference,
+/// which no copyleft license is compatible with.
+pub fn project_license(licenses: &[String], spdx: &SpdxLicenses) -> Option<LicenseExpr> {
+    let alternatives = licenses
+        .iter()
+        .map(|license| {
+            if license.eq_ignore_ascii_case("proprietary") {
+                Some(LicenseExpr::Ref(license.clone()))
+            } else {
+                spdx.parse(license).ok()
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    match alternatives.len() {
+        0 => None,
+        1 => alternatives.into_iter().next(),
+        _ => Some(LicenseExpr::Or(alternatives)),
+    }
+}
+
+/// Check every non-platform package license against the project license.
+///
+/// Packages without a license or with an unparsable one are left to the
+/// license policy.
+pub fn find_license_incompatibilities(
+    packages: &[Package],
+    project: &LicenseExpr,
+    usage: LicenseUsage,
+    spdx: &SpdxLicenses,
+) -> Vec<LicenseViolation> {
+    let mut violations = Vec::new();
+
+    for pkg in packages {
+        if pkg.is_platform_package() || pkg.package_type == "metapackage" {
+            continue;
+        }
+
+        let licenses: Vec<&str> = pkg.license.iter().map(|l| l.as_str()).collect();
+        let Ok(expr) = spdx.parse_array(&licenses) else {
+            continue;
+        };
+        let Err(incompatibilities) = expr.check_compatibility(project, usage) else {
+            continue;
+        };
+
+        violations.push(LicenseViolation {
+            package: pkg.name.clone(),
+            version: pkg.pretty_version.clone().unwrap_or_else(|| pkg.version.clone()),
+            license: licenses.join(" OR "),
+            reasons: incompatibilities.iter().map(|i| i.reason.clone()).collect(),
+        });
+    }
+
+    violations.sort_by(|a, b| a.package.cmp(&b.package));
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, licenses: &[&str]) -> Package {
+        let mut pkg = Package::new(name, "1.0.0");
+        pkg.license = licenses.iter().map(|l| l.to_string()).collect();
+        pkg
+    }
+
+    #[test]
+    fn test_policy_from_config() {
+        let mut config = Config::default();
+        assert!(policy_from_config(&config).is_none());
+
+        config.denied_licenses = vec!["GPL-3.0-only".to_string()];
+        let policy = policy_from_config(&config).unwrap();
+        assert!(policy.allowed.is_empty());
+        assert_eq!(policy.denied, vec!["GPL-3.0-only".to_string()]);
+    }
+
+    #[test]
+    fn test_denied_license_is_reported() {
+        let spdx = SpdxLicenses::new();
+        let policy = LicensePolicy::new(vec![], vec!["GPL-3.0-only".to_string()]);
+        let packages = vec![
+            package("vendor/ok", &["MIT"]),
+            package("vendor/gpl", &["GPL-3.0-only"]),
+            package("vendor/dual", &["GPL-3.0-only", "MIT"]),
+        ];
+
+        let violations = find_license_violations(&packages, &policy, &spdx);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].package, "vendor/gpl");
+        assert_eq!(violations[0].reasons, vec!["GPL-3.0-only is denied by policy".to_string()]);
+    }
+
+    #[test]
+    fn test_deny_list_ignores_unreadable_licenses() {
+        let spdx = SpdxLicenses::new();
+        let policy = LicensePolicy::new(vec![], vec!["GPL-3.0-only".to_string()]);
+        let packages = vec![
+            package("vendor/private", &["proprietary"]),
+            package("vendor/unknown", &["Not-A-License"]),
+        ];
+
+        assert!(find_license_violations(&packages, &policy, &spdx).is_empty());
+    }
+
+    #[test]
+    fn test_allow_list_reports_missing_and_invalid_licenses() {
+        let spdx = SpdxLicenses::new();
+        let policy = LicensePolicy::new(vec!["MIT".to_string()], vec![]);
+        let packages = vec![
+            package("vendor/none", &[]),
+            package("vendor/invalid", &["Not-A-License"]),
+            package("vendor/mit", &["mit"]),
+        ];
+
+        let violations = find_license_violations(&packages, &policy, &spdx);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].package, "vendor/invalid");
+        assert_eq!(
+            violations[0].reasons,
+            vec!["unknown license identifier \"Not-A-License\"".to_string()]
+        );
+        assert_eq!(violations[1].package, "vendor/none");
+        assert_eq!(violations[1].license, "none");
+    }
+
+    #[test]
+    fn test_policy_off() {
+        let mut config = Config::default();
+        config.denied_licenses = vec!["GPL-3.0-only".to_string()];
+        config.license_policy = LicensePolicyMode::Off;
+        assert!(policy_from_config(&config).is_none());
+    }
+
+    #[test]
+    fn test_project_license() {
+        let spdx = SpdxLicenses::new();
+        assert_eq!(project_license(&[], &spdx), None);
+        assert_eq!(project_license(&["Not-A-License".to_string()], &spdx), None);
+        assert_eq!(
+            project_license(&["proprietary".to_string()], &spdx),
+            Some(LicenseExpr::Ref("proprietary".to_string()))
+        );
+        assert_eq!(
+            project_license(&["MIT".to_string(), "GPL-3.0-or-later".to_string()], &spdx).unwrap().to_string(),
+            "MIT OR GPL-3.0-or-later"
+        );
+    }
+
+    #[test]
+    fn test_incompatible_dependencies_are_reported() {
+        let spdx = SpdxLicenses::new();
+        let project = spdx.parse("MIT").unwrap();
+        let packages = vec![
+            package("vendor/gpl", &["GPL-3.0-only"]),
+            package("vendor/agpl", &["AGPL-3.0-or-later"]),
+            package("vendor/dual", &["GPL-3.0-only", "MIT"]),
+            package("vendor/lgpl", &["LGPL-2.1-only"]),
+            package("vendor/none", &[]),
+        ];
+
+        let violations = find_license_incompatibilities(&packages, &project, LicenseUsage::Distribute, &spdx);
+        let names: Vec<&str> = violations.iter().map(|v| v.package.as_str()).collect();
+        assert_eq!(names, vec!["vendor/agpl", "vendor/gpl"]);
+        assert_eq!(
+            violations[1].reasons,
+            vec!["GPL-3.0-only is strong copyleft and requires the combined work to be distributed under GPL-3.0".to_string()]
+        );
+
+        let violations = find_license_incompatibilities(&packages, &project, LicenseUsage::Network, &spdx);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].package, "vendor/agpl");
+
+        assert!(find_license_incompatibilities(&packages, &project, LicenseUsage::Internal, &spdx).is_empty());
+    }
+
+    #[test]
+    fn test_platform_packages_are_skipped() {
+        let spdx = SpdxLicenses::new();
+        let policy = LicensePolicy::new(vec!["MIT".to_string()], vec![]);
+        let packages = vec![package("php", &[]), package("ext-json", &[])];
+
+        assert!(find_license_violations(&packages, &policy, &spdx).is_empty());
+    }
+}
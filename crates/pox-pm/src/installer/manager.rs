@@ -5,13 +5,25 @@ use std::sync::Arc;
 
 use futures_util::stream::{self, StreamExt};
 
-use crate::downloader::{DownloadConfig, DownloadManager};
+use crate::cache::Cache;
+use crate::composer::Composer;
+use crate::config::PreferredInstall;
+use crate::downloader::{DownloadConfig, DownloadManager, RepositorySigningKeys, DEFAULT_DOWNLOAD_RETRIES};
+use crate::event::{
+    ComposerEvent, PostPackageInstallEvent, PostPackageUninstallEvent, PreFileDownloadEvent,
+    PrePackageInstallEvent, PrePackageUninstallEvent,
+};
 use crate::http::HttpClient;
+use crate::io::{ConsoleIo, Io};
 use crate::package::Package;
-use crate::solver::{Operation, Transaction};
+use crate::profile::Profiler;
+use crate::progress::ProgressReporter;
+use crate::solver::{Operation, Transaction, UpdateAllowTransitiveDeps};
 use crate::Result;
 
-use super::binary::BinaryInstaller;
+use super::binary::{BinCompat, BinaryInstaller};
+use super::file_hashes::{hash_directory, FileHashes};
+use super::installer_paths::InstallerPaths;
 use super::library::LibraryInstaller;
 use super::metapackage::MetapackageInstaller;
 
@@ -24,16 +36,50 @@ pub struct InstallConfig {
     pub bin_dir: PathBuf,
     /// Cache directory
     pub cache_dir: PathBuf,
+    /// Directory of the bare git mirrors, none to clone sources directly
+    pub cache_vcs_dir: Option<PathBuf>,
+    /// TTL in seconds of cached dist archives
+    pub cache_files_ttl: u64,
+    /// Maximum size in bytes of the dist archive cache
+    pub cache_files_maxsize: u64,
     /// Prefer source over dist
     pub prefer_source: bool,
     /// Prefer dist over source
     pub prefer_dist: bool,
+    /// Per-package `preferred-install` overrides from config
+    pub preferred_install: Vec<(String, PreferredInstall)>,
+    /// Verify checksums and signatures of downloaded archives
+    pub verify: bool,
+    /// Minisign public keys of the configured repositories
+    pub signing_keys: Vec<RepositorySigningKeys>,
+    /// Reject plain http download URLs
+    pub secure_http: bool,
     /// Run in dry-run mode (no actual changes)
     pub dry_run: bool,
     /// Skip dev dependencies
     pub no_dev: bool,
     /// Prefer lowest versions (useful for testing compatibility)
     pub prefer_lowest: bool,
+    /// Prefer stable versions over newer unstable ones, in addition to composer.json `prefer-stable`
+    pub prefer_stable: bool,
+    /// Seed breaking ties between equally preferred packages when solving
+    pub solver_seed: Option<u64>,
+    /// Dependencies of the packages listed for a partial update that may be updated too
+    pub update_with_dependencies: UpdateAllowTransitiveDeps,
+    /// Report installs to the repositories' `notify-batch` URLs
+    pub notify_on_install: bool,
+    /// Hosts of non-default repositories that opted in to install notifications
+    pub notify_hosts: Vec<String>,
+    /// Number of archives extracted at once
+    pub max_parallel_extractions: usize,
+    /// Retries of an interrupted dist download
+    pub download_retries: u32,
+    /// How package binaries are exposed in the bin directory
+    pub bin_compat: BinCompat,
+    /// Custom install directories from the root `extra.installer-paths`
+    pub installer_paths: InstallerPaths,
+    /// Record file hashes of installed packages to detect local modifications
+    pub record_file_hashes: bool,
 }
 
 impl Default for InstallConfig {
@@ -44,11 +90,28 @@ impl Default for InstallConfig {
             cache_dir: dirs::cache_dir()
                 .unwrap_or_else(|| PathBuf::from(".composer"))
                 .join("cache"),
+            cache_vcs_dir: None,
+            cache_files_ttl: 15552000,
+            cache_files_maxsize: 300 * 1024 * 1024,
             prefer_source: false,
             prefer_dist: true,
+            preferred_install: Vec::new(),
+            verify: true,
+            signing_keys: Vec::new(),
+            secure_http: true,
             dry_run: false,
             no_dev: false,
             prefer_lowest: false,
+            prefer_stable: false,
+            solver_seed: None,
+            update_with_dependencies: UpdateAllowTransitiveDeps::OnlyListed,
+            notify_on_install: true,
+            notify_hosts: Vec::new(),
+            max_parallel_extractions: std::thread::available_parallelism().map_or(4, |n| n.get()),
+            download_retries: DEFAULT_DOWNLOAD_RETRIES,
+            bin_compat: BinCompat::Auto,
+            installer_paths: InstallerPaths::default(),
+            record_file_hashes: false,
         }
     }
 }
@@ -60,6 +123,7 @@ pub struct InstallationManager {
     library_installer: Arc<LibraryInstaller>,
     binary_installer: Arc<BinaryInstaller>,
     metapackage_installer: MetapackageInstaller,
+    http_client: Arc<HttpClient>,
     config: InstallConfig,
 }
 
@@ -79,24 +143,56 @@ pub struct InstallResult {
 impl InstallationManager {
     /// Create a new installation manager
     pub fn new(http_client: Arc<HttpClient>, config: InstallConfig) -> Self {
+        Self::with_io(http_client, config, Arc::new(ConsoleIo::default()))
+    }
+
+    /// Create an installation manager reporting download warnings through `io`
+    pub fn with_io(http_client: Arc<HttpClient>, config: InstallConfig, io: Arc<dyn Io>) -> Self {
+        Self::with_profiler(http_client, config, io, Arc::new(Profiler::disabled()))
+    }
+
+    /// Create an installation manager that also records download and extraction time in `profiler`
+    pub fn with_profiler(http_client: Arc<HttpClient>, config: InstallConfig, io: Arc<dyn Io>, profiler: Arc<Profiler>) -> Self {
+        Self::with_progress(http_client, config, io, profiler, Arc::new(ProgressReporter::new()))
+    }
+
+    /// Create an installation manager that also reports download and extraction progress to `progress`
+    pub fn with_progress(
+        http_client: Arc<HttpClient>,
+        config: InstallConfig,
+        io: Arc<dyn Io>,
+        profiler: Arc<Profiler>,
+        progress: Arc<ProgressReporter>,
+    ) -> Self {
         let download_config = DownloadConfig {
             vendor_dir: config.vendor_dir.clone(),
             cache_dir: config.cache_dir.clone(),
+            cache_vcs_dir: config.cache_vcs_dir.clone(),
             prefer_source: config.prefer_source,
             prefer_dist: config.prefer_dist,
+            preferred_install: config.preferred_install.clone(),
+            verify: config.verify,
+            signing_keys: config.signing_keys.clone(),
+            secure_http: config.secure_http,
+            max_parallel_extractions: config.max_parallel_extractions,
+            download_retries: config.download_retries,
+            installer_paths: config.installer_paths.clone(),
         };
 
-        let download_manager = Arc::new(DownloadManager::new(http_client, download_config));
+        let download_manager = Arc::new(DownloadManager::new(http_client.clone(), download_config)
+            .with_io(io)
+            .with_profiler(profiler)
+            .with_progress(progress));
 
         let library_installer = Arc::new(LibraryInstaller::new(
             download_manager,
             config.vendor_dir.clone(),
-        ));
+        ).with_installer_paths(config.installer_paths.clone()));
 
         let binary_installer = Arc::new(BinaryInstaller::new(
             config.bin_dir.clone(),
             config.vendor_dir.clone(),
-        ));
+        ).with_compat(config.bin_compat).with_installer_paths(config.installer_paths.clone()));
 
         let metapackage_installer = MetapackageInstaller::new();
 
@@ -104,6 +200,7 @@ impl InstallationManager {
             library_installer,
             binary_installer,
             metapackage_installer,
+            http_client,
             config,
         }
     }
@@ -248,6 +345,12 @@ impl InstallationManager {
             result.binaries.extend(bins);
         }
 
+        let mut changed = result.installed.clone();
+        changed.extend(result.updated.iter().map(|(_, to)| to.clone()));
+        self.record_file_hashes(&changed, &result.removed).await;
+
+        self.gc_files_cache();
+
         Ok(result)
     }
 
@@ -258,6 +361,18 @@ impl InstallationManager {
 
     /// Install from a list of packages (without a transaction)
     pub async fn install_packages(&self, packages: &[Package]) -> Result<InstallResult> {
+        self.install_packages_inner(packages, None).await
+    }
+
+    /// Install from a list of packages, firing the package and download events of `composer`
+    ///
+    /// Listeners of `pre-file-download` can rewrite the dist URL of a package,
+    /// e.g. to download it from a mirror.
+    pub async fn install_packages_with_events(&self, packages: &[Package], composer: &Composer) -> Result<InstallResult> {
+        self.install_packages_inner(packages, Some(composer)).await
+    }
+
+    async fn install_packages_inner(&self, packages: &[Package], composer: Option<&Composer>) -> Result<InstallResult> {
         let mut result = InstallResult {
             installed: Vec::new(),
             updated: Vec::new(),
@@ -298,8 +413,18 @@ impl InstallationManager {
                 let library_installer = self.library_installer.clone();
                 let binary_installer = self.binary_installer.clone();
                 async move {
-                    let download_result = library_installer.install(package).await?;
+                    let events = composer.filter(|_| library_installer.needs_install(package));
+                    let download_result = match events {
+                        Some(composer) => {
+                            let package = before_install(composer, package)?;
+                            library_installer.install(&package).await?
+                        }
+                        None => library_installer.install(package).await?,
+                    };
                     let bins = binary_installer.install(package).await?;
+                    if let Some(composer) = events {
+                        dispatch_package_event(composer, &PostPackageInstallEvent::new((*package).clone()))?;
+                    }
                     Ok::<_, crate::ComposerError>(((*package).clone(), bins, download_result.skipped))
                 }
             })
@@ -315,13 +440,181 @@ impl InstallationManager {
             result.binaries.extend(bins);
         }
 
+        self.record_file_hashes(&result.installed, &[]).await;
+
+        self.gc_files_cache();
+
         Ok(result)
     }
 
+    /// Send install notifications for freshly installed packages, unless disabled or in dry-run mode
+    pub async fn notify_installs(&self, packages: &[Package]) {
+        if !self.config.notify_on_install || self.config.dry_run {
+            return;
+        }
+        super::notify::notify_installs(&self.http_client, packages, &self.config.notify_hosts).await;
+    }
+
+    /// Remove packages and their binaries from vendor
+    ///
+    /// `packages` are lock entries, e.g. the dev packages of the lock, not a list
+    /// of installed packages: entries without an install directory are skipped.
+    /// Returns the packages that were present in vendor and removed.
+    pub async fn remove_packages(&self, packages: &[Package]) -> Result<Vec<Package>> {
+        self.remove_packages_inner(packages, None).await
+    }
+
+    /// Remove packages like [`remove_packages`](Self::remove_packages), firing the uninstall events of `composer`
+    pub async fn remove_packages_with_events(&self, packages: &[Package], composer: &Composer) -> Result<Vec<Package>> {
+        self.remove_packages_inner(packages, Some(composer)).await
+    }
+
+    async fn remove_packages_inner(&self, packages: &[Package], composer: Option<&Composer>) -> Result<Vec<Package>> {
+        let installed: Vec<&Package> = packages
+            .iter()
+            .filter(|p| !p.is_platform_package() && !p.is_metapackage())
+            .filter(|p| self.library_installer.is_installed(p))
+            .collect();
+
+        if self.config.dry_run {
+            return Ok(installed.into_iter().cloned().collect());
+        }
+
+        let mut removed = Vec::new();
+        for package in installed {
+            if let Some(composer) = composer {
+                dispatch_package_event(composer, &PrePackageUninstallEvent::new(package.clone()))?;
+            }
+            self.binary_installer.uninstall(package).await?;
+            self.uninstall_package(package).await?;
+            if let Some(composer) = composer {
+                dispatch_package_event(composer, &PostPackageUninstallEvent::new(package.clone()))?;
+            }
+            removed.push(package.clone());
+        }
+
+        self.record_file_hashes(&[], &removed).await;
+
+        Ok(removed)
+    }
+
+    /// Record the file hashes of installed packages and forget removed ones
+    ///
+    /// Hashing reads every installed file, so it only happens when the
+    /// `file-hashes` config is enabled. Failures only cost the ability to
+    /// detect local modifications, so they are logged.
+    async fn record_file_hashes(&self, installed: &[Package], removed: &[Package]) {
+        if !self.config.record_file_hashes || self.config.dry_run {
+            return;
+        }
+
+        let installed: Vec<&Package> = installed
+            .iter()
+            .filter(|p| !p.is_platform_package() && !p.is_metapackage())
+            .filter(|p| p.dist.as_ref().is_none_or(|d| d.dist_type != "path"))
+            .collect();
+        if installed.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        let hashed: Vec<_> = stream::iter(installed)
+            .map(|package| {
+                let install_path = self.library_installer.get_install_path(package);
+                async move {
+                    let files = tokio::task::spawn_blocking(move || hash_directory(&install_path)).await;
+                    (package, files)
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_INSTALLS)
+            .collect()
+            .await;
+
+        let vendor_dir = &self.config.vendor_dir;
+        let mut hashes = FileHashes::load(vendor_dir);
+        for package in removed {
+            hashes.remove(&package.name);
+        }
+        for (package, files) in hashed {
+            match files {
+                Ok(Ok(files)) => hashes.insert(package, files),
+                _ => {
+                    log::debug!("Failed to hash the files of {}", package.name);
+                    hashes.remove(&package.name);
+                }
+            }
+        }
+
+        if let Err(e) = hashes.save(vendor_dir) {
+            log::warn!("Failed to write {}: {}", super::file_hashes::FILE_HASHES_FILE, e);
+        }
+    }
+
+    /// Occasionally prune stale and least recently used archives from the files cache
+    fn gc_files_cache(&self) {
+        if !Cache::gc_is_necessary() {
+            return;
+        }
+
+        let cache = Cache::new(self.config.cache_dir.join("files"));
+        if !cache.root().is_dir() {
+            return;
+        }
+
+        let ttl = std::time::Duration::from_secs(self.config.cache_files_ttl);
+        let freed = cache.gc(ttl).unwrap_or(0) + cache.gc_size(self.config.cache_files_maxsize).unwrap_or(0);
+        if freed > 0 {
+            log::debug!("Removed {} bytes from the files cache", freed);
+        }
+    }
+
     /// Get the config
     pub fn config(&self) -> &InstallConfig {
         &self.config
     }
+
+    /// Directory a package is installed to, honoring `installer-paths`;
+    /// `None` for metapackages, which have no files
+    pub fn get_install_path(&self, package: &Package) -> Option<PathBuf> {
+        if package.is_metapackage() {
+            return self.metapackage_installer.get_install_path(package);
+        }
+        Some(self.library_installer.get_install_path(package))
+    }
+}
+
+/// Fire `pre-package-install` and `pre-file-download` for a package,
+/// returns the package with the dist URL chosen by the listeners.
+fn before_install(composer: &Composer, package: &Package) -> Result<Package> {
+    dispatch_package_event(composer, &PrePackageInstallEvent::new(package.clone()))?;
+
+    let mut package = package.clone();
+    let Some(dist) = package.dist.as_ref().filter(|d| d.dist_type != "path") else {
+        return Ok(package);
+    };
+
+    let event = PreFileDownloadEvent::new(package.clone(), dist.url.clone());
+    dispatch_package_event(composer, &event)?;
+
+    let url = event.processed_url();
+    if url != event.url {
+        log::debug!("Downloading {} from {}", package.name, url);
+        if let Some(dist) = package.dist.as_mut() {
+            dist.url = url;
+        }
+    }
+    Ok(package)
+}
+
+/// Dispatch a package event, a failing listener aborts the installation
+fn dispatch_package_event<E: ComposerEvent>(composer: &Composer, event: &E) -> Result<()> {
+    let name = event.event_type().script_name();
+    match composer.dispatch(event) {
+        Ok(0) => Ok(()),
+        Ok(code) => Err(crate::ComposerError::InstallationFailed(format!(
+            "{} script returned with error code {}", name, code
+        ))),
+        Err(e) => Err(crate::ComposerError::InstallationFailed(format!("{} failed: {}", name, e))),
+    }
 }
 
 /// Helper module for cache directory
@@ -377,6 +670,28 @@ mod tests {
         let _manager = InstallationManager::new(http_client, config);
     }
 
+    #[tokio::test]
+    async fn test_get_install_path_uses_installer_paths() {
+        let http_client = Arc::new(HttpClient::new().unwrap());
+        let config = InstallConfig {
+            installer_paths: InstallerPaths::from_extra(
+                "/project",
+                &serde_json::json!({"installer-paths": {"web/modules/{$name}": ["type:drupal-module"]}}),
+            ),
+            ..Default::default()
+        };
+        let manager = InstallationManager::new(http_client, config);
+
+        let mut module = Package::new("drupal/token", "1.0.0");
+        module.package_type = "drupal-module".to_string();
+        assert_eq!(manager.get_install_path(&module), Some(PathBuf::from("/project/web/modules/token")));
+        assert_eq!(manager.get_install_path(&Package::new("acme/lib", "1.0.0")), Some(PathBuf::from("vendor/acme/lib")));
+
+        let mut meta = Package::new("acme/meta", "1.0.0");
+        meta.package_type = "metapackage".to_string();
+        assert_eq!(manager.get_install_path(&meta), None);
+    }
+
     #[tokio::test]
     async fn test_dry_run_install() {
         let http_client = Arc::new(HttpClient::new().unwrap());
@@ -396,4 +711,113 @@ mod tests {
         assert!(result.updated.is_empty());
         assert!(result.removed.is_empty());
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_remove_packages_with_binaries() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let vendor_dir = temp.path().join("vendor");
+        let bin_dir = vendor_dir.join("bin");
+        std::fs::create_dir_all(vendor_dir.join("vendor/tool/bin")).unwrap();
+        std::fs::write(vendor_dir.join("vendor/tool/bin/tool"), "#!/usr/bin/env php").unwrap();
+        std::fs::create_dir_all(&bin_dir).unwrap();
+        std::os::unix::fs::symlink(vendor_dir.join("vendor/tool/bin/tool"), bin_dir.join("tool")).unwrap();
+
+        let http_client = Arc::new(HttpClient::new().unwrap());
+        let config = InstallConfig {
+            vendor_dir: vendor_dir.clone(),
+            bin_dir: bin_dir.clone(),
+            ..Default::default()
+        };
+        let manager = InstallationManager::new(http_client, config);
+
+        let mut tool = Package::new("vendor/tool", "1.0.0");
+        tool.bin = vec!["bin/tool".to_string()];
+        let missing = Package::new("vendor/missing", "1.0.0");
+
+        let removed = manager.remove_packages(&[tool, missing]).await.unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].name, "vendor/tool");
+        assert!(!vendor_dir.join("vendor/tool").exists());
+        assert!(!bin_dir.join("tool").exists());
+    }
+
+    /// Listener that records the script name of every event it sees
+    struct RecordingListener(Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl crate::event::EventListener for RecordingListener {
+        fn handle(&self, event: &dyn ComposerEvent, _: &Composer) -> anyhow::Result<i32> {
+            if let Some(event) = event.as_any().downcast_ref::<PreFileDownloadEvent>() {
+                event.set_processed_url(event.url.replace("example.org", "mirror.test"));
+            }
+            self.0.lock().unwrap().push(event.script_name().to_string());
+            Ok(0)
+        }
+    }
+
+    fn composer_recording_events(working_dir: &std::path::Path) -> (Composer, Arc<std::sync::Mutex<Vec<String>>>) {
+        use crate::event::EventType;
+
+        let mut composer = Composer::builder(working_dir.to_path_buf())
+            .with_composer_json(crate::json::ComposerJson::default())
+            .build()
+            .unwrap();
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        for event_type in [
+            EventType::PrePackageInstall,
+            EventType::PreFileDownload,
+            EventType::PostPackageInstall,
+            EventType::PrePackageUninstall,
+            EventType::PostPackageUninstall,
+        ] {
+            composer.event_dispatcher.add_listener(event_type, Arc::new(RecordingListener(log.clone())));
+        }
+        (composer, log)
+    }
+
+    #[test]
+    fn test_before_install_rewrites_dist_url() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let (composer, log) = composer_recording_events(temp.path());
+
+        let mut package = Package::new("vendor/a", "1.0.0");
+        package.dist = Some(crate::package::Dist::new("zip", "https://example.org/vendor/a.zip"));
+
+        let package = before_install(&composer, &package).unwrap();
+        assert_eq!(package.dist.unwrap().url, "https://mirror.test/vendor/a.zip");
+        assert_eq!(*log.lock().unwrap(), vec!["pre-package-install", "pre-file-download"]);
+    }
+
+    #[tokio::test]
+    async fn test_package_events() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let source = temp.path().join("packages/a");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("composer.json"), "{}").unwrap();
+
+        let vendor_dir = temp.path().join("vendor");
+        let config = InstallConfig {
+            vendor_dir: vendor_dir.clone(),
+            bin_dir: vendor_dir.join("bin"),
+            ..Default::default()
+        };
+        let manager = InstallationManager::new(Arc::new(HttpClient::new().unwrap()), config);
+        let (composer, log) = composer_recording_events(temp.path());
+
+        let mut package = Package::new("vendor/a", "1.0.0");
+        package.dist = Some(crate::package::Dist::new("path", source.to_string_lossy().as_ref()));
+        let packages = vec![package];
+
+        manager.install_packages_with_events(&packages, &composer).await.unwrap();
+        assert_eq!(*log.lock().unwrap(), vec!["pre-package-install", "post-package-install"]);
+
+        // Packages that are already installed don't fire events
+        manager.install_packages_with_events(&packages, &composer).await.unwrap();
+        assert_eq!(log.lock().unwrap().len(), 2);
+
+        log.lock().unwrap().clear();
+        manager.remove_packages_with_events(&packages, &composer).await.unwrap();
+        assert_eq!(*log.lock().unwrap(), vec!["pre-package-uninstall", "post-package-uninstall"]);
+        assert!(!vendor_dir.join("vendor/a").exists());
+    }
 }
@@ -4,13 +4,27 @@
 //! into the vendor directory.
 
 mod binary;
+mod file_hashes;
 mod library;
+mod license_policy;
 mod manager;
 mod metapackage;
+mod notify;
 mod installer;
+mod installer_paths;
+mod suggestions;
 
-pub use binary::BinaryInstaller;
+pub use binary::{BinCompat, BinaryInstaller};
+pub use file_hashes::{hash_directory, FileChange, FileHashes, PackageFileHashes, FILE_HASHES_FILE};
 pub use library::LibraryInstaller;
-pub use manager::{InstallConfig, InstallationManager};
+pub use license_policy::{
+    find_license_incompatibilities, find_license_violations, policy_from_config, project_license, spdx_data_dir,
+    spdx_licenses, LicenseViolation,
+};
+pub use manager::{InstallConfig, InstallResult, InstallationManager};
 pub use metapackage::{MetapackageInstaller, MetapackageResult};
-pub use installer::Installer;
+pub use notify::{batch_notifications, notify_installs};
+pub use installer::{Installer, PlanFailure, UpdatePlan};
+pub use installer_paths::InstallerPaths;
+pub use suggestions::{new_suggestions, suggestions_summary, SuggestedPackage};
+pub(crate) use installer::abandoned_warnings;
@@ -0,0 +1,103 @@
+//! Install notifications - report installed packages to their repository's `notify-batch` URL.
+
+use indexmap::IndexMap;
+
+use crate::http::HttpClient;
+use crate::package::Package;
+
+/// Hosts of the default repositories, which are notified unless `notify-on-install` is disabled
+const DEFAULT_NOTIFY_HOSTS: &[&str] = &["packagist.org", "repo.packagist.org"];
+
+/// Group installed packages by notification URL into the bodies Composer posts
+///
+/// Each body is `{"downloads": [{"name": ..., "version": ...}]}` with the
+/// normalized version. URLs on hosts that are neither a default repository
+/// nor listed in `allowed_hosts` are skipped.
+pub fn batch_notifications(packages: &[Package], allowed_hosts: &[String]) -> Vec<(String, serde_json::Value)> {
+    let mut batches: IndexMap<&str, Vec<serde_json::Value>> = IndexMap::new();
+
+    for package in packages {
+        let Some(url) = package.notification_url.as_deref() else {
+            continue;
+        };
+        if !is_notify_allowed(url, allowed_hosts) {
+            continue;
+        }
+
+        batches.entry(url).or_default().push(serde_json::json!({
+            "name": package.pretty_name.as_deref().unwrap_or(&package.name),
+            "version": package.version,
+        }));
+    }
+
+    batches
+        .into_iter()
+        .map(|(url, downloads)| (url.to_string(), serde_json::json!({ "downloads": downloads })))
+        .collect()
+}
+
+/// Post the install notifications, failures are logged and otherwise ignored
+pub async fn notify_installs(http_client: &HttpClient, packages: &[Package], allowed_hosts: &[String]) {
+    for (url, body) in batch_notifications(packages, allowed_hosts) {
+        match http_client.post_json(&url, &body).await {
+            Ok(_) => log::debug!("Notified {} of {} installs", url, body["downloads"].as_array().map_or(0, Vec::len)),
+            Err(e) => log::debug!("Failed to notify {}: {}", url, e),
+        }
+    }
+}
+
+fn is_notify_allowed(url: &str, allowed_hosts: &[String]) -> bool {
+    let Some(host) = url::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_lowercase)) else {
+        return false;
+    };
+
+    DEFAULT_NOTIFY_HOSTS.contains(&host.as_str())
+        || allowed_hosts.iter().any(|allowed| allowed.eq_ignore_ascii_case(&host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, version: &str, notification_url: Option<&str>) -> Package {
+        let mut package = Package::new(name, version);
+        package.notification_url = notification_url.map(str::to_string);
+        package
+    }
+
+    #[test]
+    fn test_batches_by_notification_url() {
+        let mut pretty = package("vendor/b", "2.0.0.0", Some("https://packagist.org/downloads/"));
+        pretty.pretty_name = Some("Vendor/B".to_string());
+        let packages = vec![
+            package("vendor/a", "1.0.0.0", Some("https://packagist.org/downloads/")),
+            package("acme/private", "1.0.0.0", Some("https://satis.example.com/downloads/")),
+            package("vendor/path", "dev-main", None),
+            pretty,
+        ];
+
+        let batches = batch_notifications(&packages, &[]);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].0, "https://packagist.org/downloads/");
+        assert_eq!(
+            batches[0].1,
+            serde_json::json!({
+                "downloads": [
+                    {"name": "vendor/a", "version": "1.0.0.0"},
+                    {"name": "Vendor/B", "version": "2.0.0.0"}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_non_default_repositories_need_opt_in() {
+        let packages = vec![package("acme/private", "1.0.0.0", Some("https://satis.example.com/downloads/"))];
+
+        assert!(batch_notifications(&packages, &[]).is_empty());
+
+        let batches = batch_notifications(&packages, &["satis.example.com".to_string()]);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].0, "https://satis.example.com/downloads/");
+    }
+}
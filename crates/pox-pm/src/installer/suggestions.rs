@@ -0,0 +1,109 @@
+//! Suggestions of freshly installed packages.
+//!
+//! Like Composer, installs and updates don't list suggestions, they only
+//! report how many packages were suggested by new dependencies and point to
+//! `pm suggests` for the details.
+
+use std::collections::HashSet;
+
+use crate::json::ComposerJson;
+use crate::package::Package;
+
+/// A package suggested by an installed package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestedPackage {
+    /// Package making the suggestion
+    pub source: String,
+    /// Suggested package
+    pub target: String,
+    /// Why the package is suggested
+    pub reason: String,
+}
+
+/// Suggestions made by the direct dependencies among `installed` for packages
+/// that are not part of `packages`, the complete set of locked packages
+pub fn new_suggestions(
+    installed: &[Package],
+    packages: &[Package],
+    composer_json: &ComposerJson,
+    dev_mode: bool,
+) -> Vec<SuggestedPackage> {
+    let mut direct: HashSet<String> = composer_json.require.keys().map(|k| k.to_lowercase()).collect();
+    if dev_mode {
+        direct.extend(composer_json.require_dev.keys().map(|k| k.to_lowercase()));
+    }
+
+    let present: HashSet<String> = packages.iter()
+        .flat_map(|p| std::iter::once(&p.name).chain(p.replace.keys()).chain(p.provide.keys()))
+        .map(|name| name.to_lowercase())
+        .collect();
+
+    installed.iter()
+        .filter(|p| direct.contains(&p.name.to_lowercase()))
+        .flat_map(|p| p.suggest.iter().map(move |(target, reason)| SuggestedPackage {
+            source: p.name.clone(),
+            target: target.clone(),
+            reason: reason.clone(),
+        }))
+        .filter(|s| !present.contains(&s.target.to_lowercase()))
+        .collect()
+}
+
+/// The line printed after an install or update, if anything was suggested
+pub fn suggestions_summary(suggestions: &[SuggestedPackage]) -> Option<String> {
+    match suggestions.len() {
+        0 => None,
+        1 => Some("1 package suggestion was added by new dependencies, use `pox pm suggests` to see details.".to_string()),
+        n => Some(format!("{} package suggestions were added by new dependencies, use `pox pm suggests` to see details.", n)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, suggest: &[(&str, &str)]) -> Package {
+        let mut package = Package::new(name, "1.0.0");
+        for (target, reason) in suggest {
+            package.suggest.insert(target.to_string(), reason.to_string());
+        }
+        package
+    }
+
+    #[test]
+    fn test_new_suggestions() {
+        let mut composer_json = ComposerJson::default();
+        composer_json.require.insert("acme/lib".to_string(), "^1.0".to_string());
+        composer_json.require_dev.insert("acme/tool".to_string(), "^1.0".to_string());
+
+        let lib = package("acme/lib", &[("acme/cache", "Faster lookups"), ("acme/util", "Helpers")]);
+        let tool = package("acme/tool", &[("acme/color", "Colored output")]);
+        let util = package("acme/util", &[("acme/transitive", "")]);
+        let packages = vec![lib.clone(), tool.clone(), util.clone()];
+
+        let suggestions = new_suggestions(&packages, &packages, &composer_json, true);
+        let targets: Vec<_> = suggestions.iter().map(|s| s.target.as_str()).collect();
+        assert_eq!(targets, vec!["acme/cache", "acme/color"]);
+        assert_eq!(suggestions[0].source, "acme/lib");
+        assert_eq!(suggestions[0].reason, "Faster lookups");
+
+        let suggestions = new_suggestions(&packages, &packages, &composer_json, false);
+        assert_eq!(suggestions.len(), 1);
+
+        // Packages installed before the operation are not new
+        assert!(new_suggestions(&[util], &packages, &composer_json, true).is_empty());
+    }
+
+    #[test]
+    fn test_suggestions_summary() {
+        let suggestion = SuggestedPackage {
+            source: "acme/lib".to_string(),
+            target: "acme/cache".to_string(),
+            reason: String::new(),
+        };
+
+        assert_eq!(suggestions_summary(&[]), None);
+        assert!(suggestions_summary(&[suggestion.clone()]).unwrap().starts_with("1 package suggestion was added"));
+        assert!(suggestions_summary(&[suggestion.clone(), suggestion]).unwrap().starts_with("2 package suggestions were added"));
+    }
+}
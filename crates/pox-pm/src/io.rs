@@ -0,0 +1,325 @@
+//! Input/output abstraction for the package manager.
+//!
+//! All user facing output of the installer, downloader and CLI commands goes
+//! through an [`Io`] implementation, which decides what is shown for the
+//! current verbosity and whether questions may be asked at all.
+
+use std::collections::VecDeque;
+use std::io::{BufRead, IsTerminal};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::progress::ProgressEvent;
+
+/// Output verbosity, mirrors Composer's `-q`, `-v`, `-vv` and `-vvv`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    Quiet,
+    #[default]
+    Normal,
+    Verbose,
+    VeryVerbose,
+    Debug,
+}
+
+impl Verbosity {
+    /// Verbosity from the `--quiet` flag and the number of `-v` flags
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            (false, 1) => Verbosity::Verbose,
+            (false, 2) => Verbosity::VeryVerbose,
+            (false, _) => Verbosity::Debug,
+        }
+    }
+}
+
+/// Console input/output used by the installer and commands.
+pub trait Io: Send + Sync {
+    /// Current verbosity
+    fn verbosity(&self) -> Verbosity;
+
+    /// Whether questions may be asked, false with `--no-interaction`
+    fn is_interactive(&self) -> bool;
+
+    /// Write a line to stdout if the verbosity is at least `verbosity`
+    fn write_at(&self, message: &str, verbosity: Verbosity);
+
+    /// Write a line to stderr if the verbosity is at least `verbosity`
+    fn write_error_at(&self, message: &str, verbosity: Verbosity);
+
+    /// Ask a question, returns the default when not interactive or the answer is empty
+    fn ask(&self, question: &str, default: Option<&str>) -> Option<String>;
+
+    /// A step of a facade operation, for callers rendering their own progress
+    fn on_progress(&self, _event: &ProgressEvent) {}
+
+    /// Write a line to stdout at normal verbosity
+    fn write(&self, message: &str) {
+        self.write_at(message, Verbosity::Normal);
+    }
+
+    /// Write a line to stderr at normal verbosity
+    fn write_error(&self, message: &str) {
+        self.write_error_at(message, Verbosity::Normal);
+    }
+
+    /// Ask a yes/no question
+    fn ask_confirmation(&self, question: &str, default: bool) -> bool {
+        let hint = if default { "[Y/n]" } else { "[y/N]" };
+        match self.ask(&format!("{} {}", question, hint), None) {
+            Some(answer) => match answer.trim().to_lowercase().as_str() {
+                "y" | "yes" => true,
+                "n" | "no" => false,
+                _ => default,
+            },
+            None => default,
+        }
+    }
+
+    /// Spinner for a long running step, hidden in quiet mode
+    fn progress(&self, message: &str) -> ProgressBar {
+        if self.is_quiet() {
+            return ProgressBar::hidden();
+        }
+
+        let spinner = ProgressBar::new_spinner();
+        spinner.set_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.green} {msg}")
+                .unwrap(),
+        );
+        spinner.set_message(message.to_string());
+        spinner.enable_steady_tick(Duration::from_millis(100));
+        spinner
+    }
+
+    fn is_quiet(&self) -> bool {
+        self.verbosity() == Verbosity::Quiet
+    }
+
+    fn is_verbose(&self) -> bool {
+        self.verbosity() >= Verbosity::Verbose
+    }
+
+    fn is_very_verbose(&self) -> bool {
+        self.verbosity() >= Verbosity::VeryVerbose
+    }
+
+    fn is_debug(&self) -> bool {
+        self.verbosity() >= Verbosity::Debug
+    }
+}
+
+/// Io writing to the terminal and reading answers from stdin
+#[derive(Debug, Clone, Default)]
+pub struct ConsoleIo {
+    verbosity: Verbosity,
+    interactive: bool,
+}
+
+impl ConsoleIo {
+    pub fn new(verbosity: Verbosity, interactive: bool) -> Self {
+        Self { verbosity, interactive }
+    }
+
+    /// Console io that is only interactive when stdin is a terminal
+    pub fn from_flags(quiet: bool, verbose: u8, no_interaction: bool) -> Self {
+        Self::new(
+            Verbosity::from_flags(quiet, verbose),
+            !no_interaction && std::io::stdin().is_terminal(),
+        )
+    }
+}
+
+impl Io for ConsoleIo {
+    fn verbosity(&self) -> Verbosity {
+        self.verbosity
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    fn write_at(&self, message: &str, verbosity: Verbosity) {
+        if self.verbosity >= verbosity {
+            println!("{}", message);
+        }
+    }
+
+    fn write_error_at(&self, message: &str, verbosity: Verbosity) {
+        if self.verbosity >= verbosity {
+            eprintln!("{}", message);
+        }
+    }
+
+    fn ask(&self, question: &str, default: Option<&str>) -> Option<String> {
+        if !self.interactive {
+            return default.map(String::from);
+        }
+
+        match default {
+            Some(default) => eprint!("{} [{}]: ", question, default),
+            None => eprint!("{} ", question),
+        }
+
+        let mut answer = String::new();
+        if std::io::stdin().lock().read_line(&mut answer).is_err() {
+            return default.map(String::from);
+        }
+
+        let answer = answer.trim();
+        if answer.is_empty() {
+            default.map(String::from)
+        } else {
+            Some(answer.to_string())
+        }
+    }
+}
+
+/// Io that shows nothing and never asks, the default when embedding the library
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullIo;
+
+impl Io for NullIo {
+    fn verbosity(&self) -> Verbosity {
+        Verbosity::Quiet
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+
+    fn write_at(&self, _message: &str, _verbosity: Verbosity) {}
+
+    fn write_error_at(&self, _message: &str, _verbosity: Verbosity) {}
+
+    fn ask(&self, _question: &str, default: Option<&str>) -> Option<String> {
+        default.map(String::from)
+    }
+
+    fn progress(&self, _message: &str) -> ProgressBar {
+        ProgressBar::hidden()
+    }
+}
+
+/// Io collecting output in memory with scripted answers, for tests and embedding
+#[derive(Debug, Default)]
+pub struct BufferIo {
+    verbosity: Verbosity,
+    output: Mutex<String>,
+    answers: Mutex<VecDeque<String>>,
+}
+
+impl BufferIo {
+    pub fn new(verbosity: Verbosity) -> Self {
+        Self {
+            verbosity,
+            ..Default::default()
+        }
+    }
+
+    /// Answers returned by `ask` in order, makes the io interactive
+    pub fn with_answers<I, S>(self, answers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        *self.answers.lock().unwrap() = answers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Everything written to stdout and stderr so far
+    pub fn output(&self) -> String {
+        self.output.lock().unwrap().clone()
+    }
+
+    fn push(&self, message: &str, verbosity: Verbosity) {
+        if self.verbosity >= verbosity {
+            let mut output = self.output.lock().unwrap();
+            output.push_str(message);
+            output.push('\n');
+        }
+    }
+}
+
+impl Io for BufferIo {
+    fn verbosity(&self) -> Verbosity {
+        self.verbosity
+    }
+
+    fn is_interactive(&self) -> bool {
+        !self.answers.lock().unwrap().is_empty()
+    }
+
+    fn write_at(&self, message: &str, verbosity: Verbosity) {
+        self.push(message, verbosity);
+    }
+
+    fn write_error_at(&self, message: &str, verbosity: Verbosity) {
+        self.push(message, verbosity);
+    }
+
+    fn ask(&self, question: &str, default: Option<&str>) -> Option<String> {
+        self.push(question, Verbosity::Normal);
+        match self.answers.lock().unwrap().pop_front() {
+            Some(answer) if !answer.is_empty() => Some(answer),
+            _ => default.map(String::from),
+        }
+    }
+
+    fn progress(&self, _message: &str) -> ProgressBar {
+        ProgressBar::hidden()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity_from_flags() {
+        assert_eq!(Verbosity::from_flags(false, 0), Verbosity::Normal);
+        assert_eq!(Verbosity::from_flags(false, 1), Verbosity::Verbose);
+        assert_eq!(Verbosity::from_flags(false, 2), Verbosity::VeryVerbose);
+        assert_eq!(Verbosity::from_flags(false, 5), Verbosity::Debug);
+        assert_eq!(Verbosity::from_flags(true, 3), Verbosity::Quiet);
+    }
+
+    #[test]
+    fn test_buffer_io_respects_verbosity() {
+        let io = BufferIo::new(Verbosity::Normal);
+        io.write("shown");
+        io.write_at("hidden", Verbosity::Verbose);
+        io.write_error_at("error", Verbosity::Quiet);
+        assert_eq!(io.output(), "shown\nerror\n");
+
+        let io = BufferIo::new(Verbosity::Quiet);
+        io.write("hidden");
+        io.write_error_at("error", Verbosity::Quiet);
+        assert_eq!(io.output(), "error\n");
+        assert!(io.is_quiet());
+    }
+
+    #[test]
+    fn test_non_interactive_uses_defaults() {
+        let io = ConsoleIo::new(Verbosity::Normal, false);
+        assert_eq!(io.ask("Name?", Some("acme/app")).as_deref(), Some("acme/app"));
+        assert_eq!(io.ask("Name?", None), None);
+        assert!(io.ask_confirmation("Continue?", true));
+        assert!(!io.ask_confirmation("Continue?", false));
+    }
+
+    #[test]
+    fn test_buffer_io_answers() {
+        let io = BufferIo::new(Verbosity::Normal).with_answers(["yes", "", "vendor/pkg"]);
+        assert!(io.is_interactive());
+        assert!(io.ask_confirmation("Continue?", false));
+        assert_eq!(io.ask("Name?", Some("default")).as_deref(), Some("default"));
+        assert_eq!(io.ask("Name?", None).as_deref(), Some("vendor/pkg"));
+        assert!(!io.is_interactive());
+    }
+}
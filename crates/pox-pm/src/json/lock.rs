@@ -2,29 +2,6 @@ use std::collections::HashMap;
 use indexmap::IndexMap;
 use serde::{Deserialize, Deserializer, Serialize};
 
-/// Deserializes a HashMap that might be represented as an empty array in JSON.
-/// Composer outputs `[]` for empty maps like stability-flags, platform-dev, etc.
-fn deserialize_map_or_empty_array<'de, D, K, V>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
-where
-    D: Deserializer<'de>,
-    K: Deserialize<'de> + std::hash::Hash + Eq,
-    V: Deserialize<'de>,
-{
-    #[derive(Deserialize)]
-    #[serde(untagged)]
-    #[serde(bound(deserialize = "K: Deserialize<'de> + std::hash::Hash + Eq, V: Deserialize<'de>"))]
-    enum MapOrArray<K, V> {
-        Map(HashMap<K, V>),
-        #[allow(dead_code)]
-        Array(Vec<serde_json::Value>),
-    }
-
-    match MapOrArray::deserialize(deserializer)? {
-        MapOrArray::Map(map) => Ok(map),
-        MapOrArray::Array(_) => Ok(HashMap::new()),
-    }
-}
-
 /// Deserializes an IndexMap that might be represented as an empty array in JSON.
 /// Composer outputs `[]` for empty maps like stability-flags, platform-dev, etc.
 fn deserialize_indexmap_or_empty_array<'de, D, K, V>(deserializer: D) -> Result<IndexMap<K, V>, D::Error>
@@ -130,8 +107,8 @@ pub struct ComposerLock {
     pub minimum_stability: String,
 
     /// Per-package stability flags
-    #[serde(default, deserialize_with = "deserialize_map_or_empty_array")]
-    pub stability_flags: HashMap<String, u8>,
+    #[serde(default, deserialize_with = "deserialize_indexmap_or_empty_array")]
+    pub stability_flags: IndexMap<String, u8>,
 
     /// Whether to prefer stable versions
     #[serde(default)]
@@ -175,7 +152,7 @@ impl Default for ComposerLock {
             packages_dev: Vec::new(),
             aliases: Vec::new(),
             minimum_stability: String::new(),
-            stability_flags: HashMap::new(),
+            stability_flags: IndexMap::new(),
             prefer_stable: false,
             prefer_lowest: false,
             platform: IndexMap::new(),
@@ -347,6 +324,10 @@ pub struct LockDist {
     /// SHA sum for verification (empty string when not available)
     #[serde(default, serialize_with = "serialize_shasum")]
     pub shasum: Option<String>,
+
+    /// Minisign signature of the archive (inline or URL)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
 }
 
 fn serialize_shasum<S>(shasum: &Option<String>, serializer: S) -> Result<S::Ok, S::Error>
@@ -0,0 +1,520 @@
+//! Format-preserving edits of JSON files such as composer.json.
+//!
+//! Only the edited member is rewritten, so indentation, key order and
+//! formatting of the rest of the document are kept as they are.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::loader::LoadError;
+
+/// Byte range of a JSON value in the document.
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    start: usize,
+    end: usize,
+}
+
+/// An object member: key position and value span.
+#[derive(Debug, Clone)]
+struct Member {
+    key: String,
+    key_start: usize,
+    value: Span,
+}
+
+/// Edits a JSON document in place, preserving its formatting.
+#[derive(Debug, Clone)]
+pub struct JsonManipulator {
+    contents: String,
+    indent: String,
+    newline: &'static str,
+}
+
+impl JsonManipulator {
+    /// Create a manipulator for a JSON object document. Empty input is
+    /// treated as an empty object.
+    pub fn new(contents: &str) -> Result<Self, LoadError> {
+        let contents = if contents.trim().is_empty() { "{\n}\n" } else { contents };
+
+        let value: Value = serde_json::from_str(contents)?;
+        if !value.is_object() {
+            return Err(LoadError::Validation("The JSON document must be an object".to_string()));
+        }
+
+        Ok(Self {
+            contents: contents.to_string(),
+            indent: detect_indent(contents),
+            newline: if contents.contains("\r\n") { "\r\n" } else { "\n" },
+        })
+    }
+
+    /// The current document.
+    pub fn contents(&self) -> &str {
+        &self.contents
+    }
+
+    /// Consume the manipulator and return the document.
+    pub fn into_contents(self) -> String {
+        self.contents
+    }
+
+    /// Read the value at a path of object keys.
+    pub fn get(&self, path: &[&str]) -> Option<Value> {
+        let span = self.locate(path)?;
+        serde_json::from_str(&self.contents[span.start..span.end]).ok()
+    }
+
+    /// Set the value at a path, creating intermediate objects as needed.
+    pub fn set<T: Serialize>(&mut self, path: &[&str], value: &T) -> Result<(), LoadError> {
+        let value = serde_json::to_value(value)?;
+        let mut object = self.root();
+
+        for (level, key) in path.iter().enumerate() {
+            let members = object_members(self.bytes(), object.start);
+            let rest = &path[level + 1..];
+
+            match members.iter().find(|m| m.key == *key) {
+                Some(member) if rest.is_empty() => {
+                    let formatted = self.format(&value, level + 1)?;
+                    self.splice(member.value.start, member.value.end, &formatted);
+                    return Ok(());
+                }
+                Some(member) if self.bytes()[member.value.start] == b'{' => {
+                    object = member.value;
+                }
+                Some(member) => {
+                    let formatted = self.format(&nest(rest, value), level + 1)?;
+                    self.splice(member.value.start, member.value.end, &formatted);
+                    return Ok(());
+                }
+                None => {
+                    return self.insert_member(object, &members, level, key, &nest(rest, value));
+                }
+            }
+        }
+
+        // Empty path replaces the whole document
+        let formatted = self.format(&value, 0)?;
+        self.splice(object.start, object.end, &formatted);
+        Ok(())
+    }
+
+    /// Remove the member at a path. Returns whether something was removed.
+    pub fn remove(&mut self, path: &[&str]) -> bool {
+        let Some((key, parent)) = path.split_last() else {
+            return false;
+        };
+        let Some(object) = self.locate(parent) else {
+            return false;
+        };
+        if self.bytes()[object.start] != b'{' {
+            return false;
+        }
+
+        let members = object_members(self.bytes(), object.start);
+        let Some(index) = members.iter().position(|m| m.key == *key) else {
+            return false;
+        };
+
+        let spans: Vec<Span> = members
+            .iter()
+            .map(|m| Span { start: m.key_start, end: m.value.end })
+            .collect();
+        self.remove_entry(object, &spans, index, "{}");
+        true
+    }
+
+    /// Append a value to the array at a path, creating the array if missing.
+    pub fn append<T: Serialize>(&mut self, path: &[&str], value: &T) -> Result<(), LoadError> {
+        let value = serde_json::to_value(value)?;
+        let array = match self.locate(path) {
+            Some(span) if self.bytes()[span.start] == b'[' => span,
+            _ => return self.set(path, &Value::Array(vec![value])),
+        };
+
+        let level = path.len();
+        let formatted = self.format(&value, level + 1)?;
+        let items = array_items(self.bytes(), array.start);
+
+        match items.last() {
+            Some(last) => {
+                let insert = format!(",{}{}{}", self.newline, self.indent.repeat(level + 1), formatted);
+                self.splice(last.end, last.end, &insert);
+            }
+            None => {
+                let replacement = format!(
+                    "[{nl}{}{}{nl}{}]",
+                    self.indent.repeat(level + 1),
+                    formatted,
+                    self.indent.repeat(level),
+                    nl = self.newline
+                );
+                self.splice(array.start, array.end, &replacement);
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the items of the array at a path that match the predicate.
+    /// Returns the number of removed items.
+    pub fn remove_array_items(&mut self, path: &[&str], predicate: impl Fn(&Value) -> bool) -> usize {
+        let mut removed = 0;
+
+        while let Some(array) = self.locate(path) {
+            if self.bytes()[array.start] != b'[' {
+                break;
+            }
+
+            let items = array_items(self.bytes(), array.start);
+            let index = items.iter().position(|item| {
+                serde_json::from_str::<Value>(&self.contents[item.start..item.end])
+                    .map(|v| predicate(&v))
+                    .unwrap_or(false)
+            });
+
+            let Some(index) = index else { break };
+            self.remove_entry(array, &items, index, "[]");
+            removed += 1;
+        }
+
+        removed
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.contents.as_bytes()
+    }
+
+    fn root(&self) -> Span {
+        let start = skip_ws(self.bytes(), 0);
+        Span { start, end: scan_value(self.bytes(), start) }
+    }
+
+    /// Span of the value at a path of object keys.
+    fn locate(&self, path: &[&str]) -> Option<Span> {
+        let mut span = self.root();
+        for key in path {
+            if self.bytes()[span.start] != b'{' {
+                return None;
+            }
+            span = object_members(self.bytes(), span.start)
+                .into_iter()
+                .find(|m| m.key == *key)?
+                .value;
+        }
+        Some(span)
+    }
+
+    /// Add a member at the end of an object whose members sit at `level + 1`.
+    fn insert_member(&mut self, object: Span, members: &[Member], level: usize, key: &str, value: &Value) -> Result<(), LoadError> {
+        let member = format!("{}: {}", serde_json::to_string(key)?, self.format(value, level + 1)?);
+
+        match members.last() {
+            Some(last) => {
+                let insert = format!(",{}{}{}", self.newline, self.indent.repeat(level + 1), member);
+                self.splice(last.value.end, last.value.end, &insert);
+            }
+            None => {
+                let replacement = format!(
+                    "{{{nl}{}{}{nl}{}}}",
+                    self.indent.repeat(level + 1),
+                    member,
+                    self.indent.repeat(level),
+                    nl = self.newline
+                );
+                self.splice(object.start, object.end, &replacement);
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove one entry (member or item) of a container, keeping the
+    /// surrounding whitespace and commas consistent.
+    fn remove_entry(&mut self, container: Span, entries: &[Span], index: usize, empty: &str) {
+        if entries.len() == 1 {
+            self.splice(container.start, container.end, empty);
+        } else if index + 1 < entries.len() {
+            self.splice(entries[index].start, entries[index + 1].start, "");
+        } else {
+            self.splice(entries[index - 1].end, entries[index].end, "");
+        }
+    }
+
+    /// Pretty print a value for insertion at the given nesting level.
+    fn format(&self, value: &Value, level: usize) -> Result<String, LoadError> {
+        let mut buf = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(self.indent.as_bytes());
+        let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+        value.serialize(&mut ser)?;
+
+        let formatted = String::from_utf8_lossy(&buf).into_owned();
+        let continuation = format!("{}{}", self.newline, self.indent.repeat(level));
+        Ok(formatted.replace('\n', &continuation))
+    }
+
+    fn splice(&mut self, start: usize, end: usize, replacement: &str) {
+        self.contents.replace_range(start..end, replacement);
+    }
+}
+
+/// Wrap a value in nested objects for the remaining path keys.
+fn nest(path: &[&str], value: Value) -> Value {
+    path.iter().rev().fold(value, |value, key| {
+        let mut map = serde_json::Map::new();
+        map.insert(key.to_string(), value);
+        Value::Object(map)
+    })
+}
+
+/// Indentation of the first indented line, four spaces by default.
+fn detect_indent(contents: &str) -> String {
+    contents
+        .lines()
+        .skip(1)
+        .find_map(|line| {
+            let trimmed = line.trim_start_matches([' ', '\t']);
+            let indent = &line[..line.len() - trimmed.len()];
+            (!indent.is_empty() && !trimmed.is_empty()).then(|| indent.to_string())
+        })
+        .unwrap_or_else(|| "    ".to_string())
+}
+
+fn skip_ws(s: &[u8], mut i: usize) -> usize {
+    while i < s.len() && s[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+/// End of the string starting at the opening quote.
+fn scan_string(s: &[u8], mut i: usize) -> usize {
+    i += 1;
+    while i < s.len() {
+        match s[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    i
+}
+
+/// End of the value starting at `i`. The document is known to be valid.
+fn scan_value(s: &[u8], i: usize) -> usize {
+    match s.get(i) {
+        Some(b'{') => container_end(s, i, b'}'),
+        Some(b'[') => container_end(s, i, b']'),
+        Some(b'"') => scan_string(s, i),
+        _ => {
+            let mut end = i;
+            while end < s.len() && !matches!(s[end], b',' | b'}' | b']') && !s[end].is_ascii_whitespace() {
+                end += 1;
+            }
+            end
+        }
+    }
+}
+
+fn container_end(s: &[u8], start: usize, close: u8) -> usize {
+    let mut i = skip_ws(s, start + 1);
+    while i < s.len() && s[i] != close {
+        if s[i] == b',' {
+            i = skip_ws(s, i + 1);
+            continue;
+        }
+        if close == b'}' {
+            // key, colon, value
+            i = skip_ws(s, scan_string(s, i));
+            i = skip_ws(s, i + 1);
+        }
+        i = skip_ws(s, scan_value(s, i));
+    }
+    i + 1
+}
+
+fn object_members(s: &[u8], start: usize) -> Vec<Member> {
+    let mut members = Vec::new();
+    let mut i = skip_ws(s, start + 1);
+
+    while i < s.len() && s[i] != b'}' {
+        if s[i] == b',' {
+            i = skip_ws(s, i + 1);
+            continue;
+        }
+        let key_start = i;
+        let key_end = scan_string(s, i);
+        let key = serde_json::from_slice(&s[key_start..key_end]).unwrap_or_default();
+
+        i = skip_ws(s, key_end);
+        i = skip_ws(s, i + 1);
+        let value_end = scan_value(s, i);
+        members.push(Member { key, key_start, value: Span { start: i, end: value_end } });
+        i = skip_ws(s, value_end);
+    }
+
+    members
+}
+
+fn array_items(s: &[u8], start: usize) -> Vec<Span> {
+    let mut items = Vec::new();
+    let mut i = skip_ws(s, start + 1);
+
+    while i < s.len() && s[i] != b']' {
+        if s[i] == b',' {
+            i = skip_ws(s, i + 1);
+            continue;
+        }
+        let end = scan_value(s, i);
+        items.push(Span { start: i, end });
+        i = skip_ws(s, end);
+    }
+
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const COMPOSER_JSON: &str = r#"{
+  "name": "vendor/project",
+  "require": {
+    "php": ">=8.1",   "vendor/a": "^1.0"
+  },
+  "config": {
+    "sort-packages": true
+  }
+}
+"#;
+
+    #[test]
+    fn test_get() {
+        let manipulator = JsonManipulator::new(COMPOSER_JSON).unwrap();
+        assert_eq!(manipulator.get(&["require", "vendor/a"]), Some(json!("^1.0")));
+        assert_eq!(manipulator.get(&["config"]), Some(json!({"sort-packages": true})));
+        assert_eq!(manipulator.get(&["config", "missing"]), None);
+        assert_eq!(manipulator.get(&["name", "nested"]), None);
+    }
+
+    #[test]
+    fn test_set_existing_preserves_formatting() {
+        let mut manipulator = JsonManipulator::new(COMPOSER_JSON).unwrap();
+        manipulator.set(&["config", "sort-packages"], &false).unwrap();
+
+        assert_eq!(
+            manipulator.contents(),
+            COMPOSER_JSON.replace("\"sort-packages\": true", "\"sort-packages\": false")
+        );
+    }
+
+    #[test]
+    fn test_set_new_nested_member() {
+        let mut manipulator = JsonManipulator::new(COMPOSER_JSON).unwrap();
+        manipulator.set(&["config", "platform", "php"], &"8.2").unwrap();
+
+        assert!(manipulator.contents().contains(
+            "    \"sort-packages\": true,\n    \"platform\": {\n      \"php\": \"8.2\"\n    }\n  }"
+        ));
+        // Untouched parts keep their odd formatting
+        assert!(manipulator.contents().contains("\"php\": \">=8.1\",   \"vendor/a\""));
+        assert_eq!(manipulator.get(&["config", "platform", "php"]), Some(json!("8.2")));
+    }
+
+    #[test]
+    fn test_set_creates_top_level_key() {
+        let mut manipulator = JsonManipulator::new("{\n    \"name\": \"vendor/project\"\n}\n").unwrap();
+        manipulator.set(&["config", "vendor-dir"], &"lib").unwrap();
+
+        assert_eq!(
+            manipulator.contents(),
+            "{\n    \"name\": \"vendor/project\",\n    \"config\": {\n        \"vendor-dir\": \"lib\"\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_set_into_empty_object() {
+        let mut manipulator = JsonManipulator::new("{\n    \"config\": {}\n}\n").unwrap();
+        manipulator.set(&["config", "lock"], &false).unwrap();
+        assert_eq!(
+            manipulator.contents(),
+            "{\n    \"config\": {\n        \"lock\": false\n    }\n}\n"
+        );
+
+        let mut manipulator = JsonManipulator::new("").unwrap();
+        manipulator.set(&["type"], &"project").unwrap();
+        assert_eq!(manipulator.contents(), "{\n    \"type\": \"project\"\n}\n");
+    }
+
+    #[test]
+    fn test_set_replaces_scalar_with_object() {
+        let mut manipulator = JsonManipulator::new("{\n    \"config\": {\n        \"preferred-install\": \"dist\"\n    }\n}\n").unwrap();
+        manipulator.set(&["config", "preferred-install", "vendor/*"], &"source").unwrap();
+        assert_eq!(
+            manipulator.get(&["config", "preferred-install"]),
+            Some(json!({"vendor/*": "source"}))
+        );
+    }
+
+    #[test]
+    fn test_remove_members() {
+        let mut manipulator = JsonManipulator::new(COMPOSER_JSON).unwrap();
+
+        assert!(manipulator.remove(&["require", "php"]));
+        assert!(manipulator.contents().contains("\"require\": {\n    \"vendor/a\": \"^1.0\"\n  }"));
+
+        assert!(manipulator.remove(&["require", "vendor/a"]));
+        assert!(manipulator.contents().contains("\"require\": {},"));
+
+        assert!(manipulator.remove(&["config"]));
+        assert_eq!(
+            manipulator.contents(),
+            "{\n  \"name\": \"vendor/project\",\n  \"require\": {}\n}\n"
+        );
+
+        assert!(!manipulator.remove(&["config", "lock"]));
+        assert!(!manipulator.remove(&[]));
+    }
+
+    #[test]
+    fn test_append_and_remove_array_items() {
+        let mut manipulator = JsonManipulator::new("{\n    \"repositories\": []\n}\n").unwrap();
+        manipulator
+            .append(&["repositories"], &json!({"type": "vcs", "url": "https://example.com/a.git"}))
+            .unwrap();
+        manipulator
+            .append(&["repositories"], &json!({"type": "path", "url": "../b"}))
+            .unwrap();
+
+        assert_eq!(
+            manipulator.contents(),
+            "{\n    \"repositories\": [\n        {\n            \"type\": \"vcs\",\n            \"url\": \"https://example.com/a.git\"\n        },\n        {\n            \"type\": \"path\",\n            \"url\": \"../b\"\n        }\n    ]\n}\n"
+        );
+
+        let removed = manipulator.remove_array_items(&["repositories"], |v| v["type"] == "vcs");
+        assert_eq!(removed, 1);
+        assert_eq!(
+            manipulator.get(&["repositories"]),
+            Some(json!([{"type": "path", "url": "../b"}]))
+        );
+
+        manipulator.remove_array_items(&["repositories"], |_| true);
+        assert_eq!(manipulator.contents(), "{\n    \"repositories\": []\n}\n");
+    }
+
+    #[test]
+    fn test_escaped_keys_and_strings() {
+        let mut manipulator = JsonManipulator::new("{\"a\\\"b\": \"x}\\\"y\", \"c\": [1, {\"d\": \"]\"}]}").unwrap();
+        assert_eq!(manipulator.get(&["a\"b"]), Some(json!("x}\"y")));
+        assert_eq!(manipulator.get(&["c"]), Some(json!([1, {"d": "]"}])));
+
+        manipulator.set(&["c"], &json!(true)).unwrap();
+        assert_eq!(manipulator.contents(), "{\"a\\\"b\": \"x}\\\"y\", \"c\": true}");
+    }
+
+    #[test]
+    fn test_rejects_non_object() {
+        assert!(JsonManipulator::new("[]").is_err());
+        assert!(JsonManipulator::new("{").is_err());
+    }
+}
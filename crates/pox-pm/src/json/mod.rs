@@ -1,7 +1,9 @@
 mod schema;
 mod loader;
 mod lock;
+mod manipulator;
 
 pub use schema::*;
 pub use loader::*;
 pub use lock::*;
+pub use manipulator::JsonManipulator;
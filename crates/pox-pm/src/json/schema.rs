@@ -136,6 +136,10 @@ pub struct ComposerJson {
     /// Non-feature branches regex patterns
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub non_feature_branches: Vec<String>,
+
+    /// Glob patterns of monorepo member directories
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workspace: Vec<String>,
 }
 
 impl ComposerJson {
@@ -347,42 +351,122 @@ pub enum Repository {
         url: String,
         #[serde(default, skip_serializing_if = "RepositoryOptions::is_empty")]
         options: RepositoryOptions,
+        /// Minisign public keys used to verify signed dist archives
+        #[serde(rename = "signing-keys", default, skip_serializing_if = "Vec::is_empty")]
+        signing_keys: Vec<String>,
+        /// Send install notifications to this repository's `notify-batch` URL
+        #[serde(rename = "notify-on-install", default, skip_serializing_if = "std::ops::Not::not")]
+        notify_on_install: bool,
+        #[serde(flatten)]
+        filter: RepositoryFilter,
     },
     Vcs {
         url: String,
+        #[serde(flatten)]
+        filter: RepositoryFilter,
     },
     Git {
         url: String,
+        #[serde(flatten)]
+        filter: RepositoryFilter,
     },
     #[serde(rename = "github")]
     GitHub {
         url: String,
+        #[serde(flatten)]
+        filter: RepositoryFilter,
     },
     #[serde(rename = "gitlab")]
     GitLab {
         url: String,
+        #[serde(flatten)]
+        filter: RepositoryFilter,
     },
     #[serde(rename = "bitbucket")]
     Bitbucket {
         url: String,
+        #[serde(flatten)]
+        filter: RepositoryFilter,
     },
     Path {
         url: String,
         #[serde(default, skip_serializing_if = "PathRepositoryOptions::is_empty")]
         options: PathRepositoryOptions,
+        #[serde(flatten)]
+        filter: RepositoryFilter,
     },
     Artifact {
         url: String,
+        #[serde(flatten)]
+        filter: RepositoryFilter,
     },
     Package {
         /// Package can be a single object or an array of package objects
         package: serde_json::Value,
+        #[serde(flatten)]
+        filter: RepositoryFilter,
     },
     /// Disable a repository by name
     #[serde(untagged)]
     Disabled(bool),
 }
 
+/// Package filtering and priority options shared by all repository types
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoryFilter {
+    /// Whether packages found here hide the same packages in lower priority repositories
+    #[serde(default = "default_canonical", skip_serializing_if = "is_canonical")]
+    pub canonical: bool,
+    /// Only load packages matching these names, `*` is a wildcard
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only: Vec<String>,
+    /// Never load packages matching these names, `*` is a wildcard
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+fn default_canonical() -> bool {
+    true
+}
+
+fn is_canonical(canonical: &bool) -> bool {
+    *canonical
+}
+
+impl Default for RepositoryFilter {
+    fn default() -> Self {
+        Self {
+            canonical: true,
+            only: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+}
+
+impl RepositoryFilter {
+    pub fn is_empty(&self) -> bool {
+        self.canonical && self.only.is_empty() && self.exclude.is_empty()
+    }
+}
+
+impl Repository {
+    /// The filter options of the repository, `None` for disabled entries
+    pub fn filter(&self) -> Option<&RepositoryFilter> {
+        match self {
+            Repository::Composer { filter, .. }
+            | Repository::Vcs { filter, .. }
+            | Repository::Git { filter, .. }
+            | Repository::GitHub { filter, .. }
+            | Repository::GitLab { filter, .. }
+            | Repository::Bitbucket { filter, .. }
+            | Repository::Path { filter, .. }
+            | Repository::Artifact { filter, .. }
+            | Repository::Package { filter, .. } => Some(filter),
+            Repository::Disabled(_) => None,
+        }
+    }
+}
+
 /// Repository options
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct RepositoryOptions {
@@ -1,4 +1,5 @@
 pub mod autoload;
+pub mod bench;
 pub mod cache;
 pub mod composer;
 pub mod config;
@@ -6,15 +7,23 @@ pub mod dependency_graph;
 pub mod downloader;
 pub mod error;
 pub mod event;
+pub mod facade;
 pub mod http;
 pub mod installer;
+pub mod io;
 pub mod json;
 pub mod package;
+pub mod phar;
 pub mod plugin;
+pub mod profile;
+pub mod progress;
 pub mod repository;
 pub mod scripts;
 pub mod solver;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
 pub mod util;
+pub mod workspace;
 
 pub use error::{ComposerError, Result};
 pub use package::Package;
@@ -24,13 +33,15 @@ pub use solver::{Pool, Request, Solver, Policy, Transaction};
 pub use downloader::{DownloadManager, DownloadResult};
 pub use installer::{InstallationManager, InstallConfig};
 pub use autoload::{AutoloadGenerator, AutoloadConfig};
-pub use plugin::{register_plugins, BinConfig};
+pub use plugin::{plugin_commands, register_plugins, BinConfig, PluginCommand, PluginCommandRegistry};
 pub use composer::{Composer, ComposerBuilder};
-pub use dependency_graph::{get_dependents, find_packages_with_replacers_and_providers, DependencyResult};
+pub use io::{ConsoleIo, Io, NullIo, Verbosity};
+pub use dependency_graph::{get_dependents, get_provider_dependents, find_packages_with_replacers_and_providers, dependency_chains, find_orphans, ChainLink, DependencyChain, DependencyGraph, DependencyResult, PackageMatch};
 pub use event::{
     ComposerEvent, EventDispatcher, EventListener, EventType,
-    PostAutoloadDumpEvent, PostInstallEvent, PostUpdateEvent,
-    PreAutoloadDumpEvent, PreInstallEvent, PreUpdateEvent,
+    PostAutoloadDumpEvent, PostInstallEvent, PostPackageInstallEvent, PostPackageUninstallEvent,
+    PostUpdateEvent, PreAutoloadDumpEvent, PreFileDownloadEvent, PreInstallEvent,
+    PrePackageInstallEvent, PrePackageUninstallEvent, PreUpdateEvent,
 };
 pub use util::{is_platform_package, compute_content_hash};
 #[cfg(test)] mod test_content_hash;
@@ -1,5 +1,6 @@
 use super::{Link, LinkType, Package, Stability};
 use indexmap::IndexMap;
+use pox_semver::VersionParser;
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -405,6 +406,32 @@ pub fn parse_branch_aliases(
     aliases
 }
 
+/// Creates the branch alias of a package
+///
+/// Uses the package's `extra.branch-alias` entry for its version and falls back
+/// to `9999999-dev` for the default branch, like Composer's array loader. The
+/// alias version is fully normalized (`2.0.x-dev` becomes `2.0.9999999.9999999-dev`)
+/// so constraints match it like any other version.
+pub fn branch_alias(package: &Arc<Package>) -> Option<AliasPackage> {
+    let mut aliases = parse_branch_aliases(package.extra.as_ref());
+    let alias = aliases
+        .remove(&package.version)
+        .or_else(|| package.pretty_version.as_ref().and_then(|pretty| aliases.remove(pretty)));
+
+    match alias {
+        Some((alias_normalized, alias_pretty)) => {
+            let alias_normalized = VersionParser::new().normalize(&alias_pretty).unwrap_or(alias_normalized);
+            Some(AliasPackage::new(Arc::clone(package), alias_normalized, alias_pretty))
+        }
+        None if package.default_branch == Some(true) => Some(AliasPackage::new(
+            Arc::clone(package),
+            DEFAULT_BRANCH_ALIAS.to_string(),
+            DEFAULT_BRANCH_ALIAS.to_string(),
+        )),
+        None => None,
+    }
+}
+
 fn normalize_pretty_dev_version(version: &str) -> String {
     if let Some(without_dev) = version.strip_suffix("-dev") {
         if without_dev.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false) {
@@ -586,6 +613,26 @@ mod tests {
         assert!(!aliases.is_empty());
     }
 
+    #[test]
+    fn test_branch_alias() {
+        let mut package = Package::new("vendor/package", "dev-main");
+        package.extra = Some(serde_json::json!({"branch-alias": {"dev-main": "2.0.x-dev"}}));
+        let alias = branch_alias(&Arc::new(package)).unwrap();
+        assert_eq!(alias.version(), "2.0.9999999.9999999-dev");
+        assert_eq!(alias.pretty_version(), "2.0.x-dev");
+        assert!(!alias.is_root_package_alias());
+
+        // Aliases of other branches don't apply
+        let mut package = Package::new("vendor/package", "dev-feature");
+        package.extra = Some(serde_json::json!({"branch-alias": {"dev-main": "2.0.x-dev"}}));
+        assert!(branch_alias(&Arc::new(package)).is_none());
+
+        let mut package = Package::new("vendor/package", "dev-trunk");
+        package.default_branch = Some(true);
+        let alias = branch_alias(&Arc::new(package)).unwrap();
+        assert_eq!(alias.version(), DEFAULT_BRANCH_ALIAS);
+    }
+
     #[test]
     fn test_parse_branch_aliases_empty() {
         let aliases = parse_branch_aliases(None);
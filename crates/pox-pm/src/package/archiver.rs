@@ -0,0 +1,381 @@
+//! Package archives for `pm archive`.
+//!
+//! Like Composer's ArchiveManager the archive holds the files of a package
+//! directory, minus VCS metadata, paths marked `export-ignore` in the root
+//! `.gitattributes` and paths matching the `archive.exclude` patterns of the
+//! package. Symlinks are stored as links in both formats, never followed.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use walkdir::WalkDir;
+
+use super::Package;
+use crate::{ComposerError, Result};
+
+/// Directories never put into an archive
+const VCS_DIRS: &[&str] = &[".git", ".svn", ".hg", "_darcs", "CVS", ".bzr"];
+
+/// Supported archive formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+}
+
+impl ArchiveFormat {
+    /// Parse a format name as given to `--format` or the `archive-format` config
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "zip" => Some(ArchiveFormat::Zip),
+            "tar" => Some(ArchiveFormat::Tar),
+            _ => None,
+        }
+    }
+
+    /// File extension, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::Tar => "tar",
+        }
+    }
+}
+
+/// A single exclude rule, later rules override earlier ones
+#[derive(Debug)]
+struct ExcludeRule {
+    pattern: Regex,
+    negate: bool,
+}
+
+impl ExcludeRule {
+    /// Translate a gitignore-like pattern, a leading `!` re-includes paths
+    fn parse(rule: &str) -> Option<Self> {
+        let (negate, rule) = match rule.strip_prefix('!') {
+            Some(rule) => (true, rule),
+            None => (false, rule),
+        };
+
+        // Patterns containing a slash are relative to the package root,
+        // others match a file or directory name at any depth
+        let anchored = rule.trim_end_matches('/').contains('/');
+        let rule = rule.trim_matches('/');
+        if rule.is_empty() {
+            return None;
+        }
+
+        let prefix = if anchored { "^/" } else { "/" };
+        let pattern = Regex::new(&format!("{}{}(?:$|/)", prefix, glob_to_regex(rule))).ok()?;
+        Some(Self { pattern, negate })
+    }
+}
+
+/// Convert a glob to a regex, `*` stays within a path segment and `**` does not
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = glob.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // "**/" also matches no directory at all
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    regex
+}
+
+/// Exclude rules of `.gitattributes` lines like `/tests export-ignore`
+fn gitattributes_rules(source: &Path) -> Vec<ExcludeRule> {
+    let Ok(content) = std::fs::read_to_string(source.join(".gitattributes")) else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [path, "export-ignore"] => ExcludeRule::parse(path),
+            [path, "-export-ignore"] => ExcludeRule::parse(&format!("!{}", path)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Files of `source` that belong into the archive, relative and sorted
+pub fn archivable_files(source: &Path, excludes: &[String]) -> Result<Vec<PathBuf>> {
+    let mut rules = gitattributes_rules(source);
+    rules.extend(excludes.iter().filter_map(|exclude| ExcludeRule::parse(exclude)));
+
+    let mut files = Vec::new();
+    let walker = WalkDir::new(source)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0 || !entry.file_type().is_dir() || !VCS_DIRS.iter().any(|d| entry.file_name() == *d)
+        });
+
+    for entry in walker {
+        let entry = entry.map_err(|e| ComposerError::Io(e.into()))?;
+        if entry.file_type().is_dir() {
+            continue;
+        }
+
+        let relative = entry.path().strip_prefix(source).unwrap_or(entry.path());
+        let path = format!("/{}", relative.to_string_lossy().replace('\\', "/"));
+        let excluded = rules
+            .iter()
+            .rev()
+            .find(|rule| rule.pattern.is_match(&path))
+            .is_some_and(|rule| !rule.negate);
+
+        if !excluded {
+            files.push(relative.to_path_buf());
+        }
+    }
+
+    Ok(files)
+}
+
+/// Archive file name of a package without extension, e.g. `vendor-package-1.2.0`
+///
+/// The name comes from package metadata, so it is reduced to a single path
+/// segment: separators become `-` and no `.` or `..` segments remain.
+pub fn archive_file_name(package: &Package) -> String {
+    let sanitize = |s: &str| {
+        let s: String = s
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '-' })
+            .collect();
+        s.split('.').filter(|part| !part.is_empty()).collect::<Vec<_>>().join(".")
+    };
+
+    let name = package
+        .archive
+        .as_ref()
+        .and_then(|archive| archive.name.as_deref())
+        .unwrap_or(&package.name);
+    let base = match sanitize(name) {
+        base if base.is_empty() => "archive".to_string(),
+        base => base,
+    };
+
+    match package.pretty_version.as_deref().map(sanitize) {
+        Some(version) if !version.is_empty() => format!("{}-{}", base, version),
+        _ => base,
+    }
+}
+
+/// Write the archivable files of `source` to `target`, returning the number of files
+pub fn create_archive(source: &Path, target: &Path, format: ArchiveFormat, excludes: &[String]) -> Result<usize> {
+    let mut files = archivable_files(source, excludes)?;
+
+    // Don't archive a previous archive written into the package itself
+    if let Ok(relative) = target.strip_prefix(source) {
+        files.retain(|file| file != relative);
+    }
+
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let writer = BufWriter::new(File::create(target)?);
+
+    match format {
+        ArchiveFormat::Zip => write_zip(source, &files, writer)?,
+        ArchiveFormat::Tar => write_tar(source, &files, writer)?,
+    }
+
+    Ok(files.len())
+}
+
+fn write_zip(source: &Path, files: &[PathBuf], writer: impl Write + std::io::Seek) -> Result<()> {
+    let zip_error = |e: zip::result::ZipError| ComposerError::InstallationFailed(format!("Failed to write zip: {}", e));
+    let mut zip = zip::ZipWriter::new(writer);
+
+    for file in files {
+        let path = source.join(file);
+        let name = file.to_string_lossy().replace('\\', "/");
+        let metadata = std::fs::symlink_metadata(&path)?;
+        let mut options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            options = options.unix_permissions(metadata.permissions().mode());
+        }
+
+        // Store links like the tar writer does instead of archiving their target
+        if metadata.file_type().is_symlink() {
+            let target = std::fs::read_link(&path)?;
+            zip.add_symlink(name, target.to_string_lossy().replace('\\', "/"), options)
+                .map_err(zip_error)?;
+            continue;
+        }
+
+        zip.start_file(name, options).map_err(zip_error)?;
+        std::io::copy(&mut File::open(&path)?, &mut zip)?;
+    }
+
+    zip.finish().map_err(zip_error)?;
+    Ok(())
+}
+
+fn write_tar(source: &Path, files: &[PathBuf], writer: impl Write) -> Result<()> {
+    let mut tar = tar::Builder::new(writer);
+    tar.follow_symlinks(false);
+
+    for file in files {
+        tar.append_path_with_name(source.join(file), file)?;
+    }
+
+    tar.into_inner()?.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::ArchiveConfig;
+    use tempfile::TempDir;
+
+    fn project() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for file in [
+            "composer.json",
+            "src/Foo.php",
+            "tests/FooTest.php",
+            "docs/index.md",
+            "docs/keep.md",
+            "build/cache.txt",
+            "src/build/Builder.php",
+            ".git/HEAD",
+        ] {
+            let path = dir.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, file).unwrap();
+        }
+        std::fs::write(dir.path().join(".gitattributes"), "# release\n/tests export-ignore\n/.gitattributes export-ignore\n* text=auto\n").unwrap();
+        dir
+    }
+
+    fn names(files: Vec<PathBuf>) -> Vec<String> {
+        files.iter().map(|f| f.to_string_lossy().replace('\\', "/")).collect()
+    }
+
+    #[test]
+    fn test_archivable_files() {
+        let dir = project();
+        let excludes = vec!["/docs".to_string(), "!/docs/keep.md".to_string(), "build".to_string()];
+
+        let files = names(archivable_files(dir.path(), &excludes).unwrap());
+        assert_eq!(files, vec!["composer.json", "docs/keep.md", "src/Foo.php"]);
+    }
+
+    #[test]
+    fn test_anchored_exclude() {
+        let dir = project();
+
+        let files = names(archivable_files(dir.path(), &["/build/".to_string()]).unwrap());
+        assert!(files.contains(&"src/build/Builder.php".to_string()));
+        assert!(!files.contains(&"build/cache.txt".to_string()));
+    }
+
+    #[test]
+    fn test_glob_to_regex() {
+        let rule = ExcludeRule::parse("/src/**/*.md").unwrap();
+        assert!(rule.pattern.is_match("/src/README.md"));
+        assert!(rule.pattern.is_match("/src/a/b/README.md"));
+        assert!(!rule.pattern.is_match("/docs/README.md"));
+
+        let rule = ExcludeRule::parse("*.log").unwrap();
+        assert!(rule.pattern.is_match("/var/app.log"));
+        assert!(!rule.pattern.is_match("/var/app.log.php"));
+    }
+
+    #[test]
+    fn test_archive_file_name() {
+        let mut package = Package::new("acme/my.lib", "1.2.0.0");
+        package.pretty_version = Some("v1.2.0".to_string());
+        assert_eq!(archive_file_name(&package), "acme-my.lib-v1.2.0");
+
+        package.archive = Some(ArchiveConfig { name: Some("my-lib".to_string()), exclude: vec![] });
+        assert_eq!(archive_file_name(&package), "my-lib-v1.2.0");
+
+        package.archive = Some(ArchiveConfig { name: Some("../../etc/passwd".to_string()), exclude: vec![] });
+        package.pretty_version = Some("1.0/../..".to_string());
+        assert_eq!(archive_file_name(&package), "-.-etc-passwd-1.0-.-");
+
+        package.archive = Some(ArchiveConfig { name: Some("..".to_string()), exclude: vec![] });
+        package.pretty_version = None;
+        assert_eq!(archive_file_name(&package), "archive");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_are_stored_as_links() {
+        let dir = project();
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("secret"), "secret").unwrap();
+        std::os::unix::fs::symlink(outside.path().join("secret"), dir.path().join("src/link")).unwrap();
+
+        let target = dir.path().join("package.zip");
+        create_archive(dir.path(), &target, ArchiveFormat::Zip, &[]).unwrap();
+        let mut zip = zip::ZipArchive::new(File::open(&target).unwrap()).unwrap();
+        let mut link = zip.by_name("src/link").unwrap();
+        assert!(link.is_symlink());
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut link, &mut contents).unwrap();
+        assert_eq!(contents, outside.path().join("secret").to_string_lossy());
+        drop(link);
+
+        let target = dir.path().join("package.tar");
+        create_archive(dir.path(), &target, ArchiveFormat::Tar, &[]).unwrap();
+        let mut tar = tar::Archive::new(File::open(&target).unwrap());
+        let link = tar
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap())
+            .find(|e| e.path().unwrap().to_string_lossy() == "src/link")
+            .unwrap();
+        assert!(link.header().entry_type().is_symlink());
+    }
+
+    #[test]
+    fn test_create_archive() {
+        let dir = project();
+        let target = dir.path().join("dist/package.zip");
+
+        let count = create_archive(dir.path(), &target, ArchiveFormat::Zip, &["/dist".to_string()]).unwrap();
+        let mut zip = zip::ZipArchive::new(File::open(&target).unwrap()).unwrap();
+        assert_eq!(count, zip.len());
+        assert!(zip.by_name("src/Foo.php").is_ok());
+        assert!(zip.by_name("tests/FooTest.php").is_err());
+        assert!(zip.by_name(".git/HEAD").is_err());
+
+        let target = dir.path().join("package.tar");
+        create_archive(dir.path(), &target, ArchiveFormat::Tar, &[]).unwrap();
+        let mut tar = tar::Archive::new(File::open(&target).unwrap());
+        let entries: Vec<String> = tar
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert!(entries.contains(&"src/Foo.php".to_string()));
+        assert!(!entries.contains(&"package.tar".to_string()));
+    }
+}
@@ -175,9 +175,24 @@ impl Autoload {
     }
 
     /// Merges another autoload configuration into this one
+    ///
+    /// Paths of namespaces defined in both are combined, like PHP's
+    /// `array_merge_recursive` does for `autoload` and `autoload-dev`.
     pub fn merge(&mut self, other: Autoload) {
-        self.psr4.extend(other.psr4);
-        self.psr0.extend(other.psr0);
+        for (map, other) in [(&mut self.psr4, other.psr4), (&mut self.psr0, other.psr0)] {
+            for (namespace, paths) in other {
+                match map.get_mut(&namespace) {
+                    Some(existing) => {
+                        let mut combined = existing.as_vec();
+                        combined.extend(paths.as_vec());
+                        *existing = AutoloadPath::Multiple(combined);
+                    }
+                    None => {
+                        map.insert(namespace, paths);
+                    }
+                }
+            }
+        }
         self.classmap.extend(other.classmap);
         self.files.extend(other.files);
         self.exclude_from_classmap.extend(other.exclude_from_classmap);
@@ -227,6 +242,15 @@ mod tests {
         assert_eq!(autoload1.files.len(), 1);
     }
 
+    #[test]
+    fn test_autoload_merge_combines_namespace_paths() {
+        let mut autoload = Autoload::new().add_psr4("App\\", "src/").add_file("a.php");
+        autoload.merge(Autoload::new().add_psr4("App\\", "tests/").add_file("b.php"));
+
+        assert_eq!(autoload.psr4["App\\"].as_vec(), vec!["src/", "tests/"]);
+        assert_eq!(autoload.files, vec!["a.php", "b.php"]);
+    }
+
     #[test]
     fn test_autoload_path_single() {
         let path = AutoloadPath::from("src/");
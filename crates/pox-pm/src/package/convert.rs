@@ -3,9 +3,9 @@
 use pox_semver::VersionParser;
 use indexmap::IndexMap;
 
-use super::{Autoload, AutoloadPath, Author, Dist, Funding, Package, Source, Support};
+use super::{ArchiveConfig, Autoload, AutoloadPath, Author, Dist, Funding, Package, Source, Support};
 use crate::json::{
-    LockAutoload, LockAuthor, LockDist, LockFunding, LockSource, LockedPackage,
+    LockArchive, LockAutoload, LockAuthor, LockDist, LockFunding, LockSource, LockedPackage,
 };
 
 /// Sort dependencies alphabetically by key (like PHP's ksort)
@@ -41,6 +41,10 @@ impl From<&LockedPackage> for Package {
         pkg.default_branch = lp.default_branch;
         pkg.authors = lp.authors.iter().map(Author::from).collect();
         pkg.funding = lp.funding.iter().map(Funding::from).collect();
+        pkg.archive = lp.archive.as_ref().map(|a| ArchiveConfig {
+            name: a.name.clone(),
+            exclude: a.exclude.clone(),
+        });
 
         if !lp.support.is_empty() {
             pkg.support = Some(Support::from(&lp.support));
@@ -64,6 +68,7 @@ impl From<&LockedPackage> for Package {
             if let Some(ref s) = dist.shasum {
                 d = d.with_shasum(s);
             }
+            d.signature = dist.signature.clone();
             pkg.dist = Some(d);
         }
 
@@ -131,7 +136,10 @@ impl From<&Package> for LockedPackage {
             support: pkg.support.as_ref().map(support_to_hashmap).unwrap_or_default(),
             funding: pkg.funding.iter().map(LockFunding::from).collect(),
             abandoned,
-            ..Default::default()
+            archive: pkg.archive.as_ref().map(|a| LockArchive {
+                name: a.name.clone(),
+                exclude: a.exclude.clone(),
+            }),
         }
     }
 }
@@ -159,6 +167,7 @@ impl From<&Dist> for LockDist {
             url: d.url.clone(),
             reference: d.reference.clone(),
             shasum: d.shasum.clone(),
+            signature: d.signature.clone(),
         }
     }
 }
@@ -417,6 +426,21 @@ mod tests {
         assert_eq!(support.forum, None);
     }
 
+    #[test]
+    fn test_archive_roundtrip() {
+        let mut original = Package::new("vendor/package", "1.0.0");
+        original.archive = Some(ArchiveConfig {
+            name: Some("package".to_string()),
+            exclude: vec!["/tests".to_string()],
+        });
+
+        let locked = LockedPackage::from(&original);
+        assert_eq!(locked.archive.as_ref().unwrap().exclude, vec!["/tests".to_string()]);
+
+        let converted = Package::from(&locked);
+        assert_eq!(converted.archive, original.archive);
+    }
+
     #[test]
     fn test_funding_roundtrip() {
         let mut original = Package::new("vendor/package", "1.0.0");
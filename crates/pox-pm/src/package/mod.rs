@@ -4,6 +4,7 @@
 // including dependencies, autoload configuration, source/dist information, etc.
 
 mod alias;
+pub mod archiver;
 mod autoload;
 mod convert;
 mod link;
@@ -11,9 +12,10 @@ mod package;
 mod root_version;
 mod source;
 pub mod version_bumper;
+pub mod version_selector;
 
 pub use alias::{
-    parse_branch_aliases, parse_inline_alias, AliasPackage, DEFAULT_BRANCH_ALIAS,
+    branch_alias, parse_branch_aliases, parse_inline_alias, AliasPackage, DEFAULT_BRANCH_ALIAS,
 };
 pub use autoload::{Autoload, AutoloadPath};
 pub use link::{Link, LinkType};
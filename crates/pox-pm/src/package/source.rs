@@ -60,19 +60,7 @@ impl Source {
 
     /// Returns all URLs (primary + mirrors) ordered by preference
     pub fn urls(&self) -> Vec<String> {
-        let mut urls = vec![self.url.clone()];
-
-        if let Some(mirrors) = &self.mirrors {
-            for mirror in mirrors {
-                if mirror.preferred {
-                    urls.insert(0, mirror.url.clone());
-                } else {
-                    urls.push(mirror.url.clone());
-                }
-            }
-        }
-
-        urls
+        ordered_urls(&self.url, self.mirrors.as_deref())
     }
 }
 
@@ -115,6 +103,10 @@ pub struct Dist {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mirrors: Option<Vec<Mirror>>,
 
+    /// Minisign signature of the archive, inline or as URL to a `.minisig` file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+
     /// Transport options (used for path repositories: symlink, relative)
     #[serde(rename = "transport-options", skip_serializing_if = "Option::is_none")]
     pub transport_options: Option<std::collections::HashMap<String, Value>>,
@@ -130,6 +122,7 @@ impl Dist {
             shasum: None,
             sha256: None,
             mirrors: None,
+            signature: None,
             transport_options: None,
         }
     }
@@ -173,6 +166,12 @@ impl Dist {
         self
     }
 
+    /// Sets the minisign signature
+    pub fn with_signature(mut self, signature: impl Into<String>) -> Self {
+        self.signature = Some(signature.into());
+        self
+    }
+
     /// Sets transport options (for path distributions)
     pub fn with_transport_options(mut self, options: std::collections::HashMap<String, Value>) -> Self {
         self.transport_options = Some(options);
@@ -181,19 +180,7 @@ impl Dist {
 
     /// Returns all URLs (primary + mirrors) ordered by preference
     pub fn urls(&self) -> Vec<String> {
-        let mut urls = vec![self.url.clone()];
-
-        if let Some(mirrors) = &self.mirrors {
-            for mirror in mirrors {
-                if mirror.preferred {
-                    urls.insert(0, mirror.url.clone());
-                } else {
-                    urls.push(mirror.url.clone());
-                }
-            }
-        }
-
-        urls
+        ordered_urls(&self.url, self.mirrors.as_deref())
     }
 }
 
@@ -206,11 +193,27 @@ impl Default for Dist {
             shasum: None,
             sha256: None,
             mirrors: None,
+            signature: None,
             transport_options: None,
         }
     }
 }
 
+/// Preferred mirrors in their configured order, then the primary URL, then
+/// the remaining mirrors
+fn ordered_urls(primary: &str, mirrors: Option<&[Mirror]>) -> Vec<String> {
+    let mirrors = mirrors.unwrap_or_default();
+    let mut urls: Vec<String> = mirrors
+        .iter()
+        .filter(|m| m.preferred)
+        .map(|m| m.url.clone())
+        .collect();
+    urls.push(primary.to_string());
+    urls.extend(mirrors.iter().filter(|m| !m.preferred).map(|m| m.url.clone()));
+    urls.dedup();
+    urls
+}
+
 /// Mirror configuration for source or dist
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Mirror {
@@ -301,4 +304,23 @@ mod tests {
         assert_eq!(urls[1], "https://example.com/package.zip");
         assert_eq!(urls[2], "https://mirror2.example.com/package.zip");
     }
+
+    #[test]
+    fn test_urls_keep_preferred_mirror_order() {
+        let dist = Dist::zip("https://example.com/package.zip").with_mirrors(vec![
+            Mirror::fallback("https://mirror3.example.com/package.zip"),
+            Mirror::preferred("https://mirror1.example.com/package.zip"),
+            Mirror::preferred("https://mirror2.example.com/package.zip"),
+        ]);
+
+        assert_eq!(
+            dist.urls(),
+            vec![
+                "https://mirror1.example.com/package.zip",
+                "https://mirror2.example.com/package.zip",
+                "https://example.com/package.zip",
+                "https://mirror3.example.com/package.zip",
+            ]
+        );
+    }
 }
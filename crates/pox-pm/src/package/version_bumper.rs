@@ -1,3 +1,4 @@
+use pox_semver::Semver;
 use regex::Regex;
 
 pub fn bump_requirement(constraint: &str, installed_version: &str) -> String {
@@ -104,11 +105,22 @@ fn bump_constraint_parts(constraint: &str, version: &str, major: &str) -> String
         let parts: Vec<&str> = constraint.split("||").collect();
         let bumped: Vec<String> = parts
             .into_iter()
-            .map(|p| bump_single_constraint(p.trim(), version, major))
+            .map(|p| bump_satisfied_constraint(p.trim(), version, major))
             .collect();
         return bumped.join(" || ");
     }
 
+    bump_satisfied_constraint(constraint, version, major)
+}
+
+/// Caret, tilde and wildcard parts are tied to the installed major, but a
+/// `>=` bound is not: only raise it when the installed version is inside the
+/// range, so ranges for other majors (or bounds above it) are left alone.
+fn bump_satisfied_constraint(constraint: &str, version: &str, major: &str) -> String {
+    if constraint.contains(">=") && !Semver::satisfies(version, constraint) {
+        return constraint.to_string();
+    }
+
     bump_single_constraint(constraint, version, major)
 }
 
@@ -411,6 +423,19 @@ mod tests {
         assert_eq!(bump_requirement("^0.3 || ^0.4", "0.4.3"), "^0.3 || ^0.4.3");
     }
 
+    #[test]
+    fn test_preserve_range_style() {
+        assert_eq!(bump_requirement(">=1.0 <2.0", "1.2.1"), ">=1.2.1 <2.0");
+        assert_eq!(bump_requirement(">=1.0,<2.0", "1.2.1"), ">=1.2.1,<2.0");
+        assert_eq!(bump_requirement("1.0 - 2.0", "1.2.1"), "1.0 - 2.0");
+    }
+
+    #[test]
+    fn test_leave_unsatisfied_parts_alone() {
+        assert_eq!(bump_requirement(">=1.0 <2.0 || ^3.0", "3.1.0"), ">=1.0 <2.0 || ^3.1");
+        assert_eq!(bump_requirement(">=2.0", "1.5.0"), ">=2.0");
+    }
+
     #[test]
     fn test_upgrade_full_wildcard() {
         assert_eq!(bump_requirement("*", "1.2.3"), ">=1.2.3");
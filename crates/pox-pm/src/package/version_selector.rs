@@ -0,0 +1,258 @@
+//! Version selection for packages required without a constraint.
+//!
+//! Like Composer's VersionSelector this finds the best version of a package
+//! that works with the current platform, and recommends the constraint to
+//! write into composer.json for it, e.g. `^2.3` for version 2.3.1.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use pox_semver::{Comparator, VersionParser};
+
+use super::{branch_alias, Package, Stability, DEFAULT_BRANCH_ALIAS};
+use crate::repository::RepositoryManager;
+use crate::util::is_platform_package;
+
+/// Selects the best version of a package from the repositories
+pub struct VersionSelector {
+    repository_manager: Arc<RepositoryManager>,
+    /// Platform package versions by lowercase name, empty to ignore platform requirements
+    platform: HashMap<String, String>,
+}
+
+impl VersionSelector {
+    pub fn new(repository_manager: Arc<RepositoryManager>) -> Self {
+        Self {
+            repository_manager,
+            platform: HashMap::new(),
+        }
+    }
+
+    /// Only select versions whose platform requirements are met by `packages`
+    pub fn with_platform_packages(mut self, packages: &[Package]) -> Self {
+        self.platform = packages
+            .iter()
+            .map(|p| (p.name.to_lowercase(), p.version.clone()))
+            .collect();
+        self
+    }
+
+    /// Find the best version of `name` matching `constraint`.
+    ///
+    /// Versions less stable than `minimum_stability` are never selected. Among
+    /// the rest the newest version of at least `preferred_stability` wins, a
+    /// less stable one is only picked if there is nothing more stable.
+    pub async fn find_best_candidate(
+        &self,
+        name: &str,
+        constraint: Option<&str>,
+        minimum_stability: Stability,
+        preferred_stability: Stability,
+    ) -> Option<Arc<Package>> {
+        let include_dev = minimum_stability == Stability::Dev;
+        let constraint = constraint.unwrap_or("*");
+        let candidates = self
+            .repository_manager
+            .find_matching_packages(&name.to_lowercase(), constraint, include_dev)
+            .await;
+
+        // Not every repository filters by the constraint itself
+        let parser = VersionParser::new();
+        let parsed = parser.parse_constraints_cached(constraint).ok();
+
+        let candidates = candidates
+            .into_iter()
+            .filter(|p| parsed.as_ref().is_none_or(|c| c.matches_normalized(&p.version)))
+            .filter(|p| p.stability().priority() <= minimum_stability.priority())
+            .filter(|p| self.is_platform_compatible(p));
+
+        select_preferred(candidates, preferred_stability)
+    }
+
+    /// Whether the platform requirements of `package` are met, always true without platform packages
+    fn is_platform_compatible(&self, package: &Package) -> bool {
+        if self.platform.is_empty() {
+            return true;
+        }
+
+        let parser = VersionParser::new();
+        package
+            .require
+            .iter()
+            .filter(|(name, _)| is_platform_package(name) && !name.starts_with("composer"))
+            .all(|(name, constraint)| {
+                let Some(version) = self.platform.get(&name.to_lowercase()) else {
+                    return false;
+                };
+                match parser.parse_constraints_cached(constraint) {
+                    Ok(parsed) => parsed.matches_normalized(version),
+                    Err(_) => true,
+                }
+            })
+    }
+}
+
+/// Pick the preferred package like Composer, the newest of the most stable candidates
+/// that are at least `preferred_stability`.
+fn select_preferred(candidates: impl IntoIterator<Item = Arc<Package>>, preferred_stability: Stability) -> Option<Arc<Package>> {
+    let preferred = preferred_stability.priority();
+    let mut best: Option<Arc<Package>> = None;
+
+    for candidate in candidates {
+        let Some(current) = &best else {
+            best = Some(candidate);
+            continue;
+        };
+
+        let candidate_priority = candidate.stability().priority();
+        let current_priority = current.stability().priority();
+
+        let replace = if preferred < candidate_priority {
+            // Less stable than preferred, only better than something even less stable
+            candidate_priority < current_priority
+        } else if preferred < current_priority {
+            true
+        } else {
+            Comparator::greater_than(&candidate.version, &current.version)
+        };
+
+        if replace {
+            best = Some(candidate);
+        }
+    }
+
+    best
+}
+
+/// Constraint to require `package` with, e.g. `^2.3` for 2.3.1, `^0.3.1` for 0.3.1,
+/// `^3.0@beta` for 3.0.0-beta2 and `^2.1@dev` for a branch aliased as 2.1.x-dev.
+pub fn find_recommended_require_version(package: &Arc<Package>) -> String {
+    let pretty_version = package.pretty_version.as_deref().unwrap_or(&package.version);
+
+    if package.is_dev() {
+        return match branch_alias(package) {
+            Some(alias) if alias.version() != DEFAULT_BRANCH_ALIAS => {
+                let version = alias.version().replace(".9999999", "");
+                transform_version(&version, pretty_version, Stability::Dev)
+            }
+            _ => pretty_version.to_string(),
+        };
+    }
+
+    let version = VersionParser::new().normalize(&package.version).unwrap_or_else(|_| package.version.clone());
+    transform_version(&version, pretty_version, package.stability())
+}
+
+fn transform_version(version: &str, pretty_version: &str, stability: Stability) -> String {
+    let parts: Vec<&str> = version.split('.').collect();
+    let semantic = match parts.len() {
+        4 => parts[3].starts_with(|c: char| c.is_ascii_digit()),
+        // A branch alias without its .x parts, e.g. 2.1-dev
+        1 | 2 => stability == Stability::Dev,
+        _ => false,
+    };
+    if !semantic {
+        return pretty_version.to_string();
+    }
+
+    let keep = if parts[0] == "0" && parts.len() == 4 { 3 } else { 2 };
+    let version = parts[..keep.min(parts.len())].join(".");
+    let version = version.trim_end_matches("-dev");
+
+    if stability == Stability::Stable {
+        format!("^{}", version)
+    } else {
+        format!("^{}@{}", version, stability)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(version: &str, pretty_version: &str) -> Arc<Package> {
+        let mut package = Package::new("acme/lib", version);
+        package.pretty_version = Some(pretty_version.to_string());
+        Arc::new(package)
+    }
+
+    #[test]
+    fn test_find_recommended_require_version() {
+        assert_eq!(find_recommended_require_version(&package("2.3.1.0", "v2.3.1")), "^2.3");
+        assert_eq!(find_recommended_require_version(&package("1.0.0.0", "1.0.0")), "^1.0");
+        assert_eq!(find_recommended_require_version(&package("0.3.1.0", "0.3.1")), "^0.3.1");
+        assert_eq!(find_recommended_require_version(&package("3.0.0.0-beta2", "3.0.0-beta2")), "^3.0@beta");
+        assert_eq!(find_recommended_require_version(&package("3.0.0.0-RC1", "3.0.0-RC1")), "^3.0@RC");
+        assert_eq!(find_recommended_require_version(&package("20240101", "20240101")), "20240101");
+        assert_eq!(find_recommended_require_version(&package("dev-main", "dev-main")), "dev-main");
+
+        let mut branch = Package::new("acme/lib", "dev-main");
+        branch.pretty_version = Some("dev-main".to_string());
+        branch.extra = Some(serde_json::json!({"branch-alias": {"dev-main": "2.1.x-dev"}}));
+        assert_eq!(find_recommended_require_version(&Arc::new(branch.clone())), "^2.1@dev");
+
+        branch.extra = Some(serde_json::json!({"branch-alias": {"dev-main": "0.4.x-dev"}}));
+        assert_eq!(find_recommended_require_version(&Arc::new(branch.clone())), "^0.4@dev");
+
+        branch.extra = Some(serde_json::json!({"branch-alias": {"dev-main": "3.x-dev"}}));
+        assert_eq!(find_recommended_require_version(&Arc::new(branch)), "^3@dev");
+    }
+
+    #[test]
+    fn test_select_preferred() {
+        let candidates = || {
+            vec![
+                package("1.0.0.0", "1.0.0"),
+                package("1.1.0.0", "1.1.0"),
+                package("2.0.0.0-beta1", "2.0.0-beta1"),
+                package("dev-main", "dev-main"),
+            ]
+        };
+
+        let best = select_preferred(candidates(), Stability::Stable).unwrap();
+        assert_eq!(best.version, "1.1.0.0");
+
+        let best = select_preferred(candidates(), Stability::Beta).unwrap();
+        assert_eq!(best.version, "2.0.0.0-beta1");
+
+        // Without a stable release the most stable candidate wins
+        let best = select_preferred(candidates().into_iter().skip(2), Stability::Stable).unwrap();
+        assert_eq!(best.version, "2.0.0.0-beta1");
+
+        assert!(select_preferred(Vec::new(), Stability::Stable).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_find_best_candidate() {
+        use crate::testing::MockRegistry;
+        use serde_json::json;
+
+        let server = MockRegistry::new()
+            .with_package("acme/lib", "1.4.0", json!({"require": {"php": ">=8.1"}}))
+            .with_package("acme/lib", "2.0.0", json!({"require": {"php": ">=8.4", "ext-intl": "*"}}))
+            .with_package("acme/lib", "2.1.0-beta1", json!({"require": {"php": ">=8.1"}}))
+            .start()
+            .await
+            .unwrap();
+
+        let repository: crate::json::Repository = serde_json::from_value(server.repository()).unwrap();
+        let mut manager = RepositoryManager::new();
+        manager.add_from_json_repositories(&[repository]);
+        let manager = Arc::new(manager);
+
+        let selector = VersionSelector::new(manager.clone());
+        let best = selector.find_best_candidate("acme/lib", None, Stability::Stable, Stability::Stable).await.unwrap();
+        assert_eq!(best.pretty_version.as_deref(), Some("2.0.0"));
+
+        // PHP 8.3 rules out 2.0.0
+        let php = Package::new("php", "8.3.0.0");
+        let selector = VersionSelector::new(manager.clone()).with_platform_packages(&[php]);
+        let best = selector.find_best_candidate("acme/lib", None, Stability::Stable, Stability::Stable).await.unwrap();
+        assert_eq!(find_recommended_require_version(&best), "^1.4");
+
+        let best = selector.find_best_candidate("acme/lib", None, Stability::Beta, Stability::Beta).await.unwrap();
+        assert_eq!(find_recommended_require_version(&best), "^2.1@beta");
+
+        assert!(selector.find_best_candidate("acme/lib", Some("^3.0"), Stability::Stable, Stability::Stable).await.is_none());
+    }
+}
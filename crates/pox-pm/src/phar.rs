@@ -0,0 +1,545 @@
+//! Reader for PHP archives (phar) in the native phar format.
+//!
+//! Used to inspect and extract phar tools and to check their embedded signature
+//! before they are executed. Tar and zip based phars are not supported.
+//!
+//! Layout: `<stub> __HALT_COMPILER(); ?>` followed by the manifest, the file
+//! contents and, for signed archives, the signature trailer ending in `GBMB`.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path};
+
+use flate2::read::DeflateDecoder;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use thiserror::Error;
+
+const HALT_COMPILER: &[u8] = b"__halt_compiler();";
+const SIGNATURE_MAGIC: &[u8] = b"GBMB";
+
+/// Global flag set when the archive carries a signature
+const PHAR_HDR_SIGNATURE: u32 = 0x0001_0000;
+
+const ENTRY_COMPRESSED_GZ: u32 = 0x0000_1000;
+const ENTRY_COMPRESSED_BZ2: u32 = 0x0000_2000;
+const ENTRY_PERMISSIONS: u32 = 0x0000_01FF;
+
+/// Errors raised while reading a phar archive
+#[derive(Debug, Error)]
+pub enum PharError {
+    #[error("not a phar archive: {0}")]
+    InvalidFormat(String),
+
+    #[error("unsupported phar archive: {0}")]
+    Unsupported(String),
+
+    #[error("corrupted entry {0}")]
+    CorruptedEntry(String),
+
+    #[error("phar signature does not match the archive contents")]
+    SignatureMismatch,
+
+    #[error("unsafe entry path {0}")]
+    UnsafePath(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Signature embedded into a phar archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PharSignatureType {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    /// OpenSSL signature, verified against the `.pubkey` file shipped next to the phar
+    OpenSsl,
+}
+
+impl PharSignatureType {
+    fn from_flags(flags: u32) -> Option<Self> {
+        match flags {
+            0x01 => Some(Self::Md5),
+            0x02 => Some(Self::Sha1),
+            0x04 => Some(Self::Sha256),
+            0x08 => Some(Self::Sha512),
+            0x10 => Some(Self::OpenSsl),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Sha1 => "SHA-1",
+            Self::Sha256 => "SHA-256",
+            Self::Sha512 => "SHA-512",
+            Self::OpenSsl => "OpenSSL",
+        }
+    }
+}
+
+/// A file stored in a phar archive
+#[derive(Debug, Clone)]
+pub struct PharEntry {
+    pub name: String,
+    pub size: u32,
+    pub compressed_size: u32,
+    pub timestamp: u32,
+    pub crc32: u32,
+    pub flags: u32,
+    offset: usize,
+}
+
+impl PharEntry {
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with('/')
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.flags & (ENTRY_COMPRESSED_GZ | ENTRY_COMPRESSED_BZ2) != 0
+    }
+
+    /// Unix permissions stored for the entry
+    pub fn permissions(&self) -> u32 {
+        self.flags & ENTRY_PERMISSIONS
+    }
+}
+
+/// A parsed phar archive
+#[derive(Debug, Clone)]
+pub struct PharArchive {
+    data: Vec<u8>,
+    stub_len: usize,
+    pub api_version: String,
+    pub alias: String,
+    pub entries: Vec<PharEntry>,
+    signature: Option<(PharSignatureType, std::ops::Range<usize>)>,
+}
+
+impl PharArchive {
+    /// Read and parse a phar file.
+    pub fn open(path: &Path) -> Result<Self, PharError> {
+        Self::from_bytes(fs::read(path)?)
+    }
+
+    /// Parse a phar archive from its raw bytes.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, PharError> {
+        if data.starts_with(b"PK\x03\x04") || data.get(257..262) == Some(b"ustar") {
+            return Err(PharError::Unsupported("zip and tar based phars".to_string()));
+        }
+
+        let stub_len = find_stub_end(&data)
+            .ok_or_else(|| PharError::InvalidFormat("missing __HALT_COMPILER(); stub".to_string()))?;
+
+        let mut reader = ByteReader::new(&data, stub_len);
+        let manifest_len = reader.u32()? as usize;
+        let manifest_end = stub_len + 4 + manifest_len;
+        if manifest_end > data.len() {
+            return Err(PharError::InvalidFormat("manifest exceeds file size".to_string()));
+        }
+
+        let count = reader.u32()?;
+        let api = reader.bytes(2)?;
+        let api_version = format!("{}.{}.{}", api[0] >> 4, api[0] & 0x0F, api[1] >> 4);
+        let global_flags = reader.u32()?;
+        let alias_len = reader.u32()? as usize;
+        let alias = String::from_utf8_lossy(reader.bytes(alias_len)?).into_owned();
+        let metadata_len = reader.u32()? as usize;
+        reader.bytes(metadata_len)?;
+
+        let mut entries = Vec::with_capacity(count.min(65536) as usize);
+        let mut offset = manifest_end;
+        for _ in 0..count {
+            let name_len = reader.u32()? as usize;
+            let name = String::from_utf8_lossy(reader.bytes(name_len)?).into_owned();
+            let size = reader.u32()?;
+            let timestamp = reader.u32()?;
+            let compressed_size = reader.u32()?;
+            let crc32 = reader.u32()?;
+            let flags = reader.u32()?;
+            let metadata_len = reader.u32()? as usize;
+            reader.bytes(metadata_len)?;
+
+            entries.push(PharEntry {
+                name,
+                size,
+                compressed_size,
+                timestamp,
+                crc32,
+                flags,
+                offset,
+            });
+            offset += compressed_size as usize;
+        }
+
+        if reader.pos > manifest_end || offset > data.len() {
+            return Err(PharError::InvalidFormat("manifest is truncated".to_string()));
+        }
+
+        let signature = if global_flags & PHAR_HDR_SIGNATURE != 0 {
+            Some(parse_signature(&data, offset)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            data,
+            stub_len,
+            api_version,
+            alias,
+            entries,
+            signature,
+        })
+    }
+
+    /// PHP stub executed when the phar is run
+    pub fn stub(&self) -> &[u8] {
+        &self.data[..self.stub_len]
+    }
+
+    /// Type of the embedded signature, if the archive is signed
+    pub fn signature_type(&self) -> Option<PharSignatureType> {
+        self.signature.as_ref().map(|(t, _)| *t)
+    }
+
+    /// Check the embedded hash signature against the archive contents.
+    ///
+    /// Returns the signature type that was checked, or `None` for unsigned archives.
+    /// OpenSSL signatures need the publisher's key and are reported as unsupported.
+    pub fn verify(&self) -> Result<Option<PharSignatureType>, PharError> {
+        let Some((sig_type, range)) = &self.signature else {
+            return Ok(None);
+        };
+
+        let signed = &self.data[..range.start];
+        let expected = &self.data[range.clone()];
+        let actual = match sig_type {
+            PharSignatureType::Md5 => Md5::digest(signed).to_vec(),
+            PharSignatureType::Sha1 => Sha1::digest(signed).to_vec(),
+            PharSignatureType::Sha256 => Sha256::digest(signed).to_vec(),
+            PharSignatureType::Sha512 => Sha512::digest(signed).to_vec(),
+            PharSignatureType::OpenSsl => {
+                return Err(PharError::Unsupported("OpenSSL signed phars".to_string()));
+            }
+        };
+
+        if actual != expected {
+            return Err(PharError::SignatureMismatch);
+        }
+
+        Ok(Some(*sig_type))
+    }
+
+    /// Find an entry by its path inside the archive
+    pub fn entry(&self, name: &str) -> Option<&PharEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    /// Read and decompress the contents of an entry, checking its CRC32.
+    pub fn read(&self, entry: &PharEntry) -> Result<Vec<u8>, PharError> {
+        let raw = &self.data[entry.offset..entry.offset + entry.compressed_size as usize];
+        let corrupted = || PharError::CorruptedEntry(entry.name.clone());
+
+        let contents = if entry.flags & ENTRY_COMPRESSED_GZ != 0 {
+            let mut out = Vec::with_capacity(entry.size as usize);
+            DeflateDecoder::new(raw).read_to_end(&mut out).map_err(|_| corrupted())?;
+            out
+        } else if entry.flags & ENTRY_COMPRESSED_BZ2 != 0 {
+            let mut out = Vec::with_capacity(entry.size as usize);
+            bzip2::read::BzDecoder::new(raw).read_to_end(&mut out).map_err(|_| corrupted())?;
+            out
+        } else {
+            raw.to_vec()
+        };
+
+        if contents.len() != entry.size as usize || crc32(&contents) != entry.crc32 {
+            return Err(corrupted());
+        }
+
+        Ok(contents)
+    }
+
+    /// Extract all entries into `dest_dir`, returning the number of files written.
+    pub fn extract_to(&self, dest_dir: &Path) -> Result<usize, PharError> {
+        fs::create_dir_all(dest_dir)?;
+
+        let mut written = 0;
+        for entry in &self.entries {
+            let relative = safe_relative_path(&entry.name)?;
+            let target = dest_dir.join(relative);
+
+            if entry.is_dir() {
+                fs::create_dir_all(&target)?;
+                continue;
+            }
+
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&target, self.read(entry)?)?;
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = entry.permissions();
+                if mode != 0 {
+                    fs::set_permissions(&target, fs::Permissions::from_mode(mode))?;
+                }
+            }
+
+            written += 1;
+        }
+
+        Ok(written)
+    }
+}
+
+/// Offset right after `__HALT_COMPILER(); ?>` and its optional newline
+fn find_stub_end(data: &[u8]) -> Option<usize> {
+    let start = data
+        .windows(HALT_COMPILER.len())
+        .position(|w| w.eq_ignore_ascii_case(HALT_COMPILER))?;
+    let mut pos = start + HALT_COMPILER.len();
+
+    while data.get(pos) == Some(&b' ') {
+        pos += 1;
+    }
+    if data[pos..].starts_with(b"?>") {
+        pos += 2;
+        if data[pos..].starts_with(b"\r\n") {
+            pos += 2;
+        } else if data[pos..].starts_with(b"\n") {
+            pos += 1;
+        }
+    }
+
+    Some(pos)
+}
+
+/// Locate the signature hash in the trailer following the file contents
+fn parse_signature(
+    data: &[u8],
+    contents_end: usize,
+) -> Result<(PharSignatureType, std::ops::Range<usize>), PharError> {
+    let invalid = |reason: &str| PharError::InvalidFormat(reason.to_string());
+
+    if data.len() < contents_end + 8 || !data.ends_with(SIGNATURE_MAGIC) {
+        return Err(invalid("signature trailer is missing"));
+    }
+
+    let flags_pos = data.len() - 8;
+    let flags = read_u32(data, flags_pos);
+    let sig_type = PharSignatureType::from_flags(flags).ok_or_else(|| invalid("unknown signature type"))?;
+
+    let (start, end) = match sig_type {
+        PharSignatureType::Md5 => (flags_pos.checked_sub(16), flags_pos),
+        PharSignatureType::Sha1 => (flags_pos.checked_sub(20), flags_pos),
+        PharSignatureType::Sha256 => (flags_pos.checked_sub(32), flags_pos),
+        PharSignatureType::Sha512 => (flags_pos.checked_sub(64), flags_pos),
+        PharSignatureType::OpenSsl => {
+            let len_pos = flags_pos.checked_sub(4).ok_or_else(|| invalid("signature is truncated"))?;
+            let len = read_u32(data, len_pos) as usize;
+            (len_pos.checked_sub(len), len_pos)
+        }
+    };
+
+    match start {
+        Some(start) if start >= contents_end => Ok((sig_type, start..end)),
+        _ => Err(invalid("signature is truncated")),
+    }
+}
+
+/// Reject absolute paths and `..` components so extraction stays inside the target
+fn safe_relative_path(name: &str) -> Result<&Path, PharError> {
+    let path = Path::new(name.trim_start_matches('/'));
+    let safe = path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+
+    if !safe || path.as_os_str().is_empty() {
+        return Err(PharError::UnsafePath(name.to_string()));
+    }
+
+    Ok(path)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(data);
+    crc.sum()
+}
+
+fn read_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+/// Cursor over the manifest with bounds checks
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], PharError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| PharError::InvalidFormat("manifest is truncated".to_string()))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, PharError> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::DeflateEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    /// Build a phar the way `Phar::buildFromDirectory` lays it out
+    fn build_phar(files: &[(&str, &[u8], bool)], signature: Option<u32>) -> Vec<u8> {
+        let mut manifest = Vec::new();
+        let mut contents = Vec::new();
+
+        manifest.extend_from_slice(&(files.len() as u32).to_le_bytes());
+        manifest.extend_from_slice(&[0x11, 0x10]);
+        let flags = if signature.is_some() { PHAR_HDR_SIGNATURE } else { 0 };
+        manifest.extend_from_slice(&flags.to_le_bytes());
+        manifest.extend_from_slice(&9u32.to_le_bytes());
+        manifest.extend_from_slice(b"tool.phar");
+        manifest.extend_from_slice(&0u32.to_le_bytes());
+
+        for (name, data, compress) in files {
+            let stored = if *compress {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            } else {
+                data.to_vec()
+            };
+
+            manifest.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            manifest.extend_from_slice(name.as_bytes());
+            manifest.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            manifest.extend_from_slice(&1_700_000_000u32.to_le_bytes());
+            manifest.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+            manifest.extend_from_slice(&crc32(data).to_le_bytes());
+            let entry_flags = 0o644 | if *compress { ENTRY_COMPRESSED_GZ } else { 0 };
+            manifest.extend_from_slice(&entry_flags.to_le_bytes());
+            manifest.extend_from_slice(&0u32.to_le_bytes());
+            contents.extend_from_slice(&stored);
+        }
+
+        let mut phar = b"<?php Phar::mapPhar('tool.phar'); require 'phar://tool.phar/bin/tool'; __HALT_COMPILER(); ?>\r\n".to_vec();
+        phar.extend_from_slice(&(manifest.len() as u32).to_le_bytes());
+        phar.extend_from_slice(&manifest);
+        phar.extend_from_slice(&contents);
+
+        if let Some(sig_flags) = signature {
+            let hash = match sig_flags {
+                0x02 => Sha1::digest(&phar).to_vec(),
+                0x04 => Sha256::digest(&phar).to_vec(),
+                _ => Sha512::digest(&phar).to_vec(),
+            };
+            phar.extend_from_slice(&hash);
+            phar.extend_from_slice(&sig_flags.to_le_bytes());
+            phar.extend_from_slice(SIGNATURE_MAGIC);
+        }
+
+        phar
+    }
+
+    #[test]
+    fn test_parse_and_read_entries() {
+        let data = build_phar(
+            &[("bin/tool", b"<?php echo 'hi';", false), ("src/App.php", b"<?php class App {}", true)],
+            None,
+        );
+        let phar = PharArchive::from_bytes(data).unwrap();
+
+        assert_eq!(phar.api_version, "1.1.1");
+        assert_eq!(phar.alias, "tool.phar");
+        assert_eq!(phar.entries.len(), 2);
+        assert!(phar.stub().ends_with(b"?>\r\n"));
+        assert_eq!(phar.signature_type(), None);
+        assert_eq!(phar.verify().unwrap(), None);
+
+        let app = phar.entry("src/App.php").unwrap();
+        assert!(app.is_compressed());
+        assert_eq!(app.permissions(), 0o644);
+        assert_eq!(phar.read(app).unwrap(), b"<?php class App {}");
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        for flags in [0x02, 0x04, 0x08] {
+            let data = build_phar(&[("bin/tool", b"<?php echo 1;", false)], Some(flags));
+            let phar = PharArchive::from_bytes(data.clone()).unwrap();
+            assert_eq!(phar.verify().unwrap(), PharSignatureType::from_flags(flags));
+
+            let mut tampered = data;
+            let pos = tampered.windows(5).position(|w| w == b"echo ").unwrap();
+            tampered[pos] = b'E';
+            let phar = PharArchive::from_bytes(tampered).unwrap();
+            assert!(matches!(phar.verify(), Err(PharError::SignatureMismatch)));
+        }
+    }
+
+    #[test]
+    fn test_crc_mismatch_is_reported() {
+        let mut data = build_phar(&[("a.php", b"<?php 1;", false)], None);
+        let last = data.len() - 1;
+        data[last] = b'2';
+
+        let phar = PharArchive::from_bytes(data).unwrap();
+        let entry = phar.entry("a.php").unwrap();
+        assert!(matches!(phar.read(entry), Err(PharError::CorruptedEntry(_))));
+    }
+
+    #[test]
+    fn test_extract_to() {
+        let data = build_phar(
+            &[("bin/", b"", false), ("bin/tool", b"<?php echo 1;", false), ("src/A.php", b"<?php", true)],
+            Some(0x04),
+        );
+        let phar = PharArchive::from_bytes(data).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        assert_eq!(phar.extract_to(dir.path()).unwrap(), 2);
+        assert_eq!(fs::read(dir.path().join("bin/tool")).unwrap(), b"<?php echo 1;");
+        assert_eq!(fs::read(dir.path().join("src/A.php")).unwrap(), b"<?php");
+    }
+
+    #[test]
+    fn test_rejects_invalid_archives() {
+        assert!(matches!(
+            PharArchive::from_bytes(b"<?php echo 1;".to_vec()),
+            Err(PharError::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            PharArchive::from_bytes(b"PK\x03\x04rest".to_vec()),
+            Err(PharError::Unsupported(_))
+        ));
+
+        let data = build_phar(&[("../evil.php", b"<?php", false)], None);
+        let phar = PharArchive::from_bytes(data).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(phar.extract_to(dir.path()), Err(PharError::UnsafePath(_))));
+    }
+}
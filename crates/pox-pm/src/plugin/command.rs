@@ -0,0 +1,133 @@
+//! Commands contributed by ported plugins.
+//!
+//! Composer plugins can provide additional CLI commands through
+//! `CommandProvider`. Ported plugins implement `PluginCommand` instead and
+//! are registered in a `PluginCommandRegistry`, which the CLI exposes as
+//! `pm` subcommands.
+
+use std::path::Path;
+use std::sync::Arc;
+
+/// A CLI command provided by a ported plugin.
+pub trait PluginCommand: Send + Sync {
+    /// The command name as typed on the command line (e.g. `recipes`).
+    fn name(&self) -> &'static str;
+
+    /// One-line description shown in the command listing.
+    fn description(&self) -> &'static str;
+
+    /// Alternative names for the command.
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Run the command with the arguments following its name.
+    /// Returns the exit code (0 for success).
+    fn execute(&self, args: &[String], working_dir: &Path) -> anyhow::Result<i32>;
+}
+
+/// Registry of commands contributed by plugins.
+#[derive(Default, Clone)]
+pub struct PluginCommandRegistry {
+    commands: Vec<Arc<dyn PluginCommand>>,
+}
+
+impl PluginCommandRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a command. Commands registered earlier win on name clashes.
+    pub fn register(&mut self, command: Arc<dyn PluginCommand>) {
+        self.commands.push(command);
+    }
+
+    /// Find a command by name or alias.
+    pub fn find(&self, name: &str) -> Option<Arc<dyn PluginCommand>> {
+        self.commands
+            .iter()
+            .find(|c| c.name() == name || c.aliases().contains(&name))
+            .cloned()
+    }
+
+    /// All registered commands, sorted by name.
+    pub fn commands(&self) -> Vec<Arc<dyn PluginCommand>> {
+        let mut commands = self.commands.clone();
+        commands.sort_by_key(|c| c.name());
+        commands
+    }
+
+    /// Whether no commands are registered.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoCommand;
+
+    impl PluginCommand for EchoCommand {
+        fn name(&self) -> &'static str {
+            "echo"
+        }
+
+        fn description(&self) -> &'static str {
+            "Echo the arguments"
+        }
+
+        fn aliases(&self) -> &'static [&'static str] {
+            &["say"]
+        }
+
+        fn execute(&self, args: &[String], _working_dir: &Path) -> anyhow::Result<i32> {
+            Ok(args.len() as i32)
+        }
+    }
+
+    struct AnotherCommand;
+
+    impl PluginCommand for AnotherCommand {
+        fn name(&self) -> &'static str {
+            "another"
+        }
+
+        fn description(&self) -> &'static str {
+            "Another command"
+        }
+
+        fn execute(&self, _args: &[String], _working_dir: &Path) -> anyhow::Result<i32> {
+            Ok(0)
+        }
+    }
+
+    #[test]
+    fn test_registry_find_by_name_and_alias() {
+        let mut registry = PluginCommandRegistry::new();
+        assert!(registry.is_empty());
+
+        registry.register(Arc::new(EchoCommand));
+        assert!(!registry.is_empty());
+
+        assert_eq!(registry.find("echo").unwrap().name(), "echo");
+        assert_eq!(registry.find("say").unwrap().name(), "echo");
+        assert!(registry.find("missing").is_none());
+
+        let args = vec!["a".to_string(), "b".to_string()];
+        let code = registry.find("say").unwrap().execute(&args, Path::new(".")).unwrap();
+        assert_eq!(code, 2);
+    }
+
+    #[test]
+    fn test_registry_commands_sorted() {
+        let mut registry = PluginCommandRegistry::new();
+        registry.register(Arc::new(EchoCommand));
+        registry.register(Arc::new(AnotherCommand));
+
+        let names: Vec<_> = registry.commands().iter().map(|c| c.name()).collect();
+        assert_eq!(names, vec!["another", "echo"]);
+    }
+}
@@ -0,0 +1,230 @@
+//! Composer patches plugin - apply patches to installed packages.
+//!
+//! This is a native Rust port of cweagans/composer-patches 1.x.
+//! Patches are declared in the root composer.json under `extra.patches`,
+//! or in the JSON file named by `extra.patches-file`, and are applied
+//! with `patch` right after a package is installed. Reinstalling a
+//! package therefore restores its patches.
+//!
+//! Only local patch files are supported, patches given by URL are skipped
+//! with a warning.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::composer::Composer;
+use crate::event::{ComposerEvent, EventListener, EventType, PostPackageInstallEvent};
+use crate::json::ComposerJson;
+
+/// The package name that triggers this plugin.
+pub const PACKAGE_NAME: &str = "cweagans/composer-patches";
+
+/// Strip levels tried in order, like composer-patches does
+const PATCH_LEVELS: [&str; 4] = ["-p1", "-p0", "-p2", "-p4"];
+
+/// A patch declared for a package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    pub description: String,
+    pub location: String,
+}
+
+/// Patches of the root package keyed by lowercase package name
+///
+/// `extra.patches` and the `patches` of `extra.patches-file` are merged.
+pub fn patches_from_extra(extra: &serde_json::Value, project_dir: &Path) -> anyhow::Result<BTreeMap<String, Vec<Patch>>> {
+    let mut patches = BTreeMap::new();
+    if let Some(declared) = extra.get("patches") {
+        collect_patches(declared, &mut patches);
+    }
+
+    if let Some(file) = extra.get("patches-file").and_then(|v| v.as_str()) {
+        let path = project_dir.join(file);
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read patches file {}: {}", path.display(), e))?;
+        let json: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse patches file {}: {}", path.display(), e))?;
+        if let Some(declared) = json.get("patches") {
+            collect_patches(declared, &mut patches);
+        }
+    }
+
+    Ok(patches)
+}
+
+fn collect_patches(declared: &serde_json::Value, patches: &mut BTreeMap<String, Vec<Patch>>) {
+    let Some(packages) = declared.as_object() else {
+        return;
+    };
+
+    for (name, list) in packages {
+        let Some(list) = list.as_object() else {
+            continue;
+        };
+        let entry: &mut Vec<Patch> = patches.entry(name.to_lowercase()).or_default();
+        for (description, location) in list {
+            if let Some(location) = location.as_str() {
+                entry.push(Patch { description: description.clone(), location: location.to_string() });
+            }
+        }
+    }
+}
+
+/// Whether composer-patches is required by the root package or locked
+fn is_active(composer: &Composer) -> bool {
+    let required = |json: &ComposerJson| {
+        json.require.contains_key(PACKAGE_NAME) || json.require_dev.contains_key(PACKAGE_NAME)
+    };
+    required(&composer.composer_json)
+        || composer.composer_lock.as_ref().is_some_and(|lock| {
+            lock.packages.iter().chain(lock.packages_dev.iter()).any(|p| p.name == PACKAGE_NAME)
+        })
+}
+
+/// Apply a patch file to a directory, trying each strip level
+///
+/// Returns whether one of the levels applied cleanly.
+pub fn apply_patch(install_path: &Path, patch_file: &Path) -> std::io::Result<bool> {
+    for level in PATCH_LEVELS {
+        let run = |dry_run: bool| {
+            let mut command = Command::new("patch");
+            command
+                .arg(level)
+                .arg("--forward")
+                .arg("--no-backup-if-mismatch")
+                .arg("--silent")
+                .arg("-d")
+                .arg(install_path)
+                .arg("-i")
+                .arg(patch_file);
+            if dry_run {
+                command.arg("--dry-run");
+            }
+            command.output()
+        };
+
+        if run(true)?.status.success() {
+            return Ok(run(false)?.status.success());
+        }
+    }
+
+    Ok(false)
+}
+
+/// Composer patches plugin - implements EventListener directly.
+pub struct ComposerPatchesPlugin;
+
+impl EventListener for ComposerPatchesPlugin {
+    fn handle(&self, event: &dyn ComposerEvent, composer: &Composer) -> anyhow::Result<i32> {
+        if event.event_type() != EventType::PostPackageInstall {
+            return Ok(0);
+        }
+
+        let Some(e) = event.as_any().downcast_ref::<PostPackageInstallEvent>() else {
+            return Ok(0);
+        };
+
+        if !is_active(composer) {
+            return Ok(0);
+        }
+
+        let extra = &composer.composer_json.extra;
+        let patches = patches_from_extra(extra, &composer.working_dir)?;
+        let Some(package_patches) = patches.get(&e.package.name.to_lowercase()) else {
+            return Ok(0);
+        };
+        let Some(install_path) = composer.installation_manager.get_install_path(&e.package) else {
+            return Ok(0);
+        };
+
+        let exit_on_failure = extra
+            .get("composer-exit-on-patch-failure")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        composer.io.write(&format!("  - Applying patches for {}", e.package.name));
+        for patch in package_patches {
+            if patch.location.contains("://") {
+                composer.io.write_error(&format!(
+                    "    Skipped {} ({}), patches from URLs are not supported",
+                    patch.location, patch.description
+                ));
+                continue;
+            }
+
+            composer.io.write(&format!("    {} ({})", patch.location, patch.description));
+            let applied = apply_patch(&install_path, &composer.working_dir.join(&patch.location))?;
+            if !applied {
+                let message = format!("Cannot apply patch {} ({}) to {}", patch.description, patch.location, e.package.name);
+                if exit_on_failure {
+                    anyhow::bail!(message);
+                }
+                composer.io.write_error(&format!("    {}", message));
+            }
+        }
+
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patches_from_extra() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("patches.json"),
+            r#"{"patches": {"acme/lib": {"Fix typo": "patches/typo.patch"}}}"#,
+        )
+        .unwrap();
+
+        let extra = serde_json::json!({
+            "patches": {
+                "Acme/Lib": {"Fix bug": "patches/bug.patch"},
+                "acme/other": {"Remote": "https://example.org/fix.patch"}
+            },
+            "patches-file": "patches.json"
+        });
+
+        let patches = patches_from_extra(&extra, dir.path()).unwrap();
+        assert_eq!(
+            patches["acme/lib"],
+            vec![
+                Patch { description: "Fix bug".to_string(), location: "patches/bug.patch".to_string() },
+                Patch { description: "Fix typo".to_string(), location: "patches/typo.patch".to_string() },
+            ]
+        );
+        assert_eq!(patches["acme/other"][0].location, "https://example.org/fix.patch");
+    }
+
+    #[test]
+    fn test_patches_file_missing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let extra = serde_json::json!({"patches-file": "missing.json"});
+        assert!(patches_from_extra(&extra, dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let install_path = dir.path().join("acme/lib");
+        std::fs::create_dir_all(install_path.join("src")).unwrap();
+        std::fs::write(install_path.join("src/Lib.php"), "<?php\necho 'helo';\n").unwrap();
+
+        let patch_file = dir.path().join("fix.patch");
+        std::fs::write(
+            &patch_file,
+            "--- a/src/Lib.php\n+++ b/src/Lib.php\n@@ -1,2 +1,2 @@\n <?php\n-echo 'helo';\n+echo 'hello';\n",
+        )
+        .unwrap();
+
+        assert!(apply_patch(&install_path, &patch_file).unwrap());
+        assert_eq!(std::fs::read_to_string(install_path.join("src/Lib.php")).unwrap(), "<?php\necho 'hello';\n");
+
+        // Already applied, --forward refuses to apply it again
+        assert!(!apply_patch(&install_path, &patch_file).unwrap());
+    }
+}
@@ -5,12 +5,17 @@
 //! ported and registered as event listeners.
 //!
 //! Each plugin implements `EventListener` directly and checks if its
-//! corresponding package is installed before taking action.
+//! corresponding package is installed before taking action. Plugins that
+//! contribute CLI commands implement `PluginCommand`.
 
+mod command;
 mod composer_bin;
+mod composer_patches;
 mod phpstan_extension_installer;
 mod registry;
+pub mod symfony_flex;
 mod symfony_runtime;
 
+pub use command::{PluginCommand, PluginCommandRegistry};
 pub use composer_bin::BinConfig;
-pub use registry::register_plugins;
+pub use registry::{plugin_commands, register_plugins};
@@ -7,8 +7,11 @@ use std::sync::Arc;
 
 use crate::event::{EventListener, EventType, EventDispatcher};
 
+use super::command::PluginCommandRegistry;
 use super::composer_bin::ComposerBinPlugin;
+use super::composer_patches::ComposerPatchesPlugin;
 use super::phpstan_extension_installer::PhpstanExtensionInstallerPlugin;
+use super::symfony_flex::{RecipesCommand, RecipesInstallCommand, SymfonyFlexPlugin};
 use super::symfony_runtime::SymfonyRuntimePlugin;
 
 /// Register all plugins with the event dispatcher.
@@ -19,4 +22,19 @@ pub fn register_plugins(dispatcher: &mut EventDispatcher) {
     dispatcher.add_listener(EventType::PostAutoloadDump, Arc::new(ComposerBinPlugin) as Arc<dyn EventListener>);
     dispatcher.add_listener(EventType::PostAutoloadDump, Arc::new(PhpstanExtensionInstallerPlugin) as Arc<dyn EventListener>);
     dispatcher.add_listener(EventType::PostAutoloadDump, Arc::new(SymfonyRuntimePlugin) as Arc<dyn EventListener>);
+    dispatcher.add_listener(EventType::PostPackageInstall, Arc::new(ComposerPatchesPlugin) as Arc<dyn EventListener>);
+
+    let flex = Arc::new(SymfonyFlexPlugin) as Arc<dyn EventListener>;
+    dispatcher.add_listener(EventType::PostInstall, flex.clone());
+    dispatcher.add_listener(EventType::PostUpdate, flex);
+}
+
+/// Collect the commands contributed by ported plugins.
+///
+/// Registered commands show up under `pox pm`.
+pub fn plugin_commands() -> PluginCommandRegistry {
+    let mut registry = PluginCommandRegistry::new();
+    registry.register(Arc::new(RecipesCommand));
+    registry.register(Arc::new(RecipesInstallCommand));
+    registry
 }
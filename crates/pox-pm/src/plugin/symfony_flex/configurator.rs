@@ -0,0 +1,659 @@
+//! Recipe configurators: apply (and revert) the parts of a recipe manifest.
+//!
+//! Supported manifest keys: `bundles`, `copy-from-recipe`,
+//! `copy-from-package`, `env` and `gitignore`.
+
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::path::{Component, Path, PathBuf};
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use crate::json::ComposerJson;
+use crate::{ComposerError, Result};
+
+use super::recipe::Recipe;
+
+/// Project directories recipes can refer to with `%NAME_DIR%` placeholders.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Absolute project directory
+    pub project_dir: PathBuf,
+    /// Absolute vendor directory
+    pub vendor_dir: PathBuf,
+    dirs: HashMap<String, String>,
+}
+
+impl Options {
+    /// Build options from composer.json `extra` (`config-dir`, `src-dir`, ...).
+    pub fn new(project_dir: &Path, vendor_dir: &Path, composer_json: &ComposerJson) -> Self {
+        let mut dirs: HashMap<String, String> = [
+            ("bin-dir", "bin"),
+            ("config-dir", "config"),
+            ("src-dir", "src"),
+            ("var-dir", "var"),
+            ("public-dir", "public"),
+            ("root-dir", "."),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        for key in ["bin-dir", "config-dir", "src-dir", "var-dir", "public-dir"] {
+            if let Some(dir) = composer_json.extra.get(key).and_then(|v| v.as_str()) {
+                dirs.insert(key.to_string(), dir.to_string());
+            }
+        }
+        if let Some(dir) = composer_json
+            .extra
+            .get("symfony")
+            .and_then(|s| s.get("root-dir"))
+            .and_then(|v| v.as_str())
+        {
+            dirs.insert("root-dir".to_string(), dir.to_string());
+        }
+
+        Self {
+            project_dir: project_dir.to_path_buf(),
+            vendor_dir: vendor_dir.to_path_buf(),
+            dirs,
+        }
+    }
+
+    /// Replace `%CONFIG_DIR%`-style placeholders with the configured directories.
+    pub fn expand_target_dir(&self, target: &str) -> String {
+        static PLACEHOLDER: std::sync::LazyLock<Regex> =
+            std::sync::LazyLock::new(|| Regex::new(r"%([A-Z_]+?)%").unwrap());
+
+        PLACEHOLDER
+            .replace_all(target, |caps: &regex::Captures| {
+                let option = caps[1].to_lowercase().replace('_', "-");
+                match self.dirs.get(&option) {
+                    Some(dir) => dir.trim_end_matches('/').to_string(),
+                    None => caps[0].to_string(),
+                }
+            })
+            .into_owned()
+    }
+
+    /// The root directory recipes write `.env` and `.gitignore` to.
+    fn root_dir(&self) -> PathBuf {
+        self.project_dir.join(self.expand_target_dir("%ROOT_DIR%"))
+    }
+}
+
+/// Outcome of applying a recipe.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ConfigureReport {
+    /// Files created or owned by the recipe, relative to the project directory
+    pub files: Vec<String>,
+    /// Files that differ from the recipe and were left untouched
+    pub conflicts: Vec<String>,
+    /// Files that differed from the recipe and were overwritten (force)
+    pub overwritten: Vec<String>,
+}
+
+/// Applies recipe manifests to a project.
+pub struct Configurator {
+    options: Options,
+    force: bool,
+}
+
+impl Configurator {
+    /// Create a configurator. With `force`, existing files and marked
+    /// blocks are replaced with the recipe's version.
+    pub fn new(options: Options, force: bool) -> Self {
+        Self { options, force }
+    }
+
+    /// Apply every supported part of the recipe.
+    pub fn install(&self, recipe: &Recipe) -> Result<ConfigureReport> {
+        let mut report = ConfigureReport::default();
+        let manifest = &recipe.manifest;
+
+        if let Some(bundles) = manifest.get("bundles").and_then(|b| b.as_object()) {
+            self.configure_bundles(bundles)?;
+        }
+        if let Some(copy) = manifest.get("copy-from-recipe").and_then(|c| c.as_object()) {
+            self.copy_from_recipe(recipe, copy, &mut report)?;
+        }
+        if let Some(copy) = manifest.get("copy-from-package").and_then(|c| c.as_object()) {
+            self.copy_from_package(&recipe.package, copy, &mut report)?;
+        }
+        if let Some(env) = manifest.get("env").and_then(|e| e.as_object()) {
+            self.configure_env(&recipe.package, env)?;
+        }
+        if let Some(lines) = manifest.get("gitignore").and_then(|g| g.as_array()) {
+            self.configure_gitignore(&recipe.package, lines)?;
+        }
+
+        Ok(report)
+    }
+
+    /// Revert the parts of a recipe that live in shared files
+    /// (bundles.php, .env and .gitignore).
+    ///
+    /// Copied files are left in place since they may have been edited.
+    pub fn uninstall(&self, package: &str, manifest: Option<&Map<String, Value>>) -> Result<()> {
+        if let Some(bundles) = manifest.and_then(|m| m.get("bundles")).and_then(|b| b.as_object()) {
+            self.unconfigure_bundles(bundles)?;
+        }
+        for file in self.env_files() {
+            remove_marked_block(&file, package)?;
+        }
+        remove_marked_block(&self.options.root_dir().join(".gitignore"), package)?;
+        Ok(())
+    }
+
+    fn bundles_file(&self) -> PathBuf {
+        self.options
+            .project_dir
+            .join(self.options.expand_target_dir("%CONFIG_DIR%/bundles.php"))
+    }
+
+    /// Register bundles in config/bundles.php. Bundles that are already
+    /// registered keep their environments.
+    fn configure_bundles(&self, bundles: &Map<String, Value>) -> Result<()> {
+        let path = self.bundles_file();
+        let mut contents = std::fs::read_to_string(&path)
+            .unwrap_or_else(|_| "<?php\n\nreturn [\n];\n".to_string());
+
+        let mut lines = String::new();
+        for (class, envs) in bundles {
+            let class = class.trim_start_matches('\\');
+            if registered_bundle_line(&contents, class).is_some() {
+                continue;
+            }
+            let envs: Vec<String> = envs
+                .as_array()
+                .map(|e| e.iter().filter_map(|e| e.as_str()).map(|e| format!("'{}' => true", e)).collect())
+                .unwrap_or_default();
+            lines.push_str(&format!("    {}::class => [{}],\n", class, envs.join(", ")));
+        }
+
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        let insert_at = contents.rfind("];").unwrap_or(contents.len());
+        contents.insert_str(insert_at, &lines);
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, contents)?;
+        Ok(())
+    }
+
+    fn unconfigure_bundles(&self, bundles: &Map<String, Value>) -> Result<()> {
+        let path = self.bundles_file();
+        let Ok(mut contents) = std::fs::read_to_string(&path) else {
+            return Ok(());
+        };
+
+        for class in bundles.keys() {
+            if let Some(range) = registered_bundle_line(&contents, class.trim_start_matches('\\')) {
+                contents.replace_range(range, "");
+            }
+        }
+
+        std::fs::write(&path, contents)?;
+        Ok(())
+    }
+
+    fn copy_from_recipe(&self, recipe: &Recipe, copy: &Map<String, Value>, report: &mut ConfigureReport) -> Result<()> {
+        for (source, target) in copy {
+            let Some(target) = target.as_str() else { continue };
+            let target = self.options.expand_target_dir(target);
+
+            for (path, file) in &recipe.files {
+                let destination = if source.ends_with('/') {
+                    match path.strip_prefix(source.as_str()) {
+                        Some(rest) => format!("{}/{}", target.trim_end_matches('/'), rest),
+                        None => continue,
+                    }
+                } else if path == source {
+                    target.clone()
+                } else {
+                    continue;
+                };
+
+                let contents = self.options.expand_target_dir(&file.contents);
+                self.write_file(&destination, contents.as_bytes(), file.executable, report)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn copy_from_package(&self, package: &str, copy: &Map<String, Value>, report: &mut ConfigureReport) -> Result<()> {
+        let package_dir = self.options.vendor_dir.join(package);
+
+        for (source, target) in copy {
+            let Some(target) = target.as_str() else { continue };
+            let target = self.options.expand_target_dir(target);
+            let from = package_dir.join(source);
+
+            if from.is_dir() {
+                for entry in walkdir::WalkDir::new(&from).into_iter().filter_map(|e| e.ok()) {
+                    if !entry.file_type().is_file() {
+                        continue;
+                    }
+                    let relative = entry.path().strip_prefix(&from).unwrap_or(entry.path());
+                    let destination = format!(
+                        "{}/{}",
+                        target.trim_end_matches('/'),
+                        relative.to_string_lossy().replace('\\', "/")
+                    );
+                    let contents = std::fs::read(entry.path())?;
+                    self.write_file(&destination, &contents, is_executable(entry.path()), report)?;
+                }
+            } else if from.is_file() {
+                let contents = std::fs::read(&from)?;
+                self.write_file(&target, &contents, is_executable(&from), report)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a recipe file, detecting local modifications.
+    fn write_file(&self, relative: &str, contents: &[u8], executable: bool, report: &mut ConfigureReport) -> Result<()> {
+        let relative = relative.trim_start_matches("./").to_string();
+        let escapes = Path::new(&relative)
+            .components()
+            .any(|c| matches!(c, Component::ParentDir | Component::RootDir | Component::Prefix(_)));
+        if escapes {
+            return Err(ComposerError::InstallationFailed(
+                format!("Recipe file {} is outside of the project directory", relative)
+            ));
+        }
+        let path = self.options.project_dir.join(&relative);
+
+        if let Ok(existing) = std::fs::read(&path) {
+            if existing != contents {
+                if !self.force {
+                    report.conflicts.push(relative.clone());
+                    report.files.push(relative);
+                    return Ok(());
+                }
+                report.overwritten.push(relative.clone());
+            }
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, contents)?;
+
+        #[cfg(unix)]
+        if executable {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        }
+        #[cfg(not(unix))]
+        let _ = executable;
+
+        report.files.push(relative);
+        Ok(())
+    }
+
+    /// The .env files recipes write to (`.env.dist` and `.env`, when present).
+    fn env_files(&self) -> Vec<PathBuf> {
+        let root = self.options.root_dir();
+        [".env.dist", ".env"]
+            .iter()
+            .map(|f| root.join(f))
+            .filter(|f| f.is_file())
+            .collect()
+    }
+
+    fn configure_env(&self, package: &str, vars: &Map<String, Value>) -> Result<()> {
+        let mut data = String::new();
+        for (key, value) in vars {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            let value = evaluate_env_value(&value);
+
+            if key.starts_with('#') && key[1..].parse::<u32>().is_ok() {
+                data.push_str(&format!("# {}\n", value));
+                continue;
+            }
+
+            let value = self.options.expand_target_dir(&value);
+            data.push_str(&format!("{}={}\n", key, quote_env_value(&value)));
+        }
+
+        // Like Flex, a project without any env file gets a new .env
+        let mut files = self.env_files();
+        if files.is_empty() {
+            files.push(self.options.root_dir().join(".env"));
+        }
+        for file in files {
+            self.write_marked_block(&file, package, &data)?;
+        }
+        Ok(())
+    }
+
+    fn configure_gitignore(&self, package: &str, lines: &[Value]) -> Result<()> {
+        let data: String = lines
+            .iter()
+            .filter_map(|l| l.as_str())
+            .map(|l| format!("{}\n", self.options.expand_target_dir(l)))
+            .collect();
+
+        self.write_marked_block(&self.options.root_dir().join(".gitignore"), package, &data)
+    }
+
+    /// Append a `###> package ###` block, or replace it when forced.
+    fn write_marked_block(&self, path: &Path, package: &str, data: &str) -> Result<()> {
+        let existing = std::fs::read_to_string(path).unwrap_or_default();
+        let block = mark_data(package, data);
+
+        let contents = match marked_block_range(&existing, package) {
+            Some(_) if !self.force => return Ok(()),
+            Some(range) => {
+                let mut contents = existing.clone();
+                contents.replace_range(range, &block);
+                contents
+            }
+            None => format!("{}\n{}", existing, block),
+        };
+
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+/// Wrap data in Flex's package markers.
+fn mark_data(package: &str, data: &str) -> String {
+    format!(
+        "###> {} ###\n{}\n###< {} ###\n",
+        package,
+        data.trim_end_matches(['\r', '\n']),
+        package
+    )
+}
+
+/// Byte range of the marked block for a package, including the trailing newline.
+fn marked_block_range(contents: &str, package: &str) -> Option<std::ops::Range<usize>> {
+    let pattern = format!(
+        r"(?s)###> {} ###.*?###< {} ###\n?",
+        regex::escape(package),
+        regex::escape(package)
+    );
+    Regex::new(&pattern).ok()?.find(contents).map(|m| m.range())
+}
+
+/// Remove a package's marked block and the blank line before it.
+fn remove_marked_block(path: &Path, package: &str) -> Result<()> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Ok(());
+    };
+    let Some(range) = marked_block_range(&contents, package) else {
+        return Ok(());
+    };
+
+    let start = if contents[..range.start].ends_with("\n\n") { range.start - 1 } else { range.start };
+    let mut contents = contents;
+    contents.replace_range(start..range.end, "");
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Byte range of the line registering a bundle class in bundles.php.
+fn registered_bundle_line(contents: &str, class: &str) -> Option<std::ops::Range<usize>> {
+    let pattern = format!(r"(?m)^[ \t]*\\?{}::class\s*=>.*\n?", regex::escape(class));
+    Regex::new(&pattern).ok()?.find(contents).map(|m| m.range())
+}
+
+/// Resolve `%generate(secret)%` values.
+fn evaluate_env_value(value: &str) -> String {
+    if value == "%generate(secret)%" {
+        return generate_secret();
+    }
+    value.to_string()
+}
+
+/// 32 random hex characters, like `bin2hex(random_bytes(16))`.
+fn generate_secret() -> String {
+    (0..2)
+        .map(|_| {
+            let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+            hasher.write_u128(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_nanos())
+                    .unwrap_or_default(),
+            );
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+/// Quote values the way Flex does when they contain special characters.
+fn quote_env_value(value: &str) -> String {
+    if value.contains([' ', '\t', '\n', '&', '!', '"']) {
+        let escaped = value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n");
+        format!("\"{}\"", escaped)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).map(|m| m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn options(dir: &Path) -> Options {
+        Options::new(dir, &dir.join("vendor"), &ComposerJson::default())
+    }
+
+    fn framework_recipe() -> Recipe {
+        let data = json!({
+            "manifests": {
+                "symfony/framework-bundle": {
+                    "manifest": {
+                        "bundles": {"Symfony\\Bundle\\FrameworkBundle\\FrameworkBundle": ["all"]},
+                        "copy-from-recipe": {"config/": "%CONFIG_DIR%/"},
+                        "env": {"#1": "Framework settings", "APP_ENV": "dev", "APP_SECRET": "%generate(secret)%"},
+                        "gitignore": ["/.env.local", "/%VAR_DIR%/"]
+                    },
+                    "files": {
+                        "config/packages/framework.yaml": {"contents": ["framework:", "    secret: '%env(APP_SECRET)%'"], "executable": false}
+                    },
+                    "ref": "abc123"
+                }
+            }
+        });
+        Recipe::from_json("symfony/framework-bundle", "7.0", "github.com/symfony/recipes", "main", &data).unwrap()
+    }
+
+    #[test]
+    fn test_expand_target_dir() {
+        let temp = TempDir::new().unwrap();
+        let composer_json = ComposerJson {
+            extra: json!({"config-dir": "etc/"}),
+            ..Default::default()
+        };
+
+        let options = Options::new(temp.path(), &temp.path().join("vendor"), &composer_json);
+        assert_eq!(options.expand_target_dir("%CONFIG_DIR%/bundles.php"), "etc/bundles.php");
+        assert_eq!(options.expand_target_dir("/%VAR_DIR%/"), "/var/");
+        assert_eq!(options.expand_target_dir("%UNKNOWN%"), "%UNKNOWN%");
+    }
+
+    #[test]
+    fn test_install_recipe() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join(".env"), "# env\n").unwrap();
+
+        let configurator = Configurator::new(options(temp.path()), false);
+        let report = configurator.install(&framework_recipe()).unwrap();
+
+        assert_eq!(report.files, vec!["config/packages/framework.yaml"]);
+        assert!(report.conflicts.is_empty());
+
+        let bundles = std::fs::read_to_string(temp.path().join("config/bundles.php")).unwrap();
+        assert_eq!(
+            bundles,
+            "<?php\n\nreturn [\n    Symfony\\Bundle\\FrameworkBundle\\FrameworkBundle::class => ['all' => true],\n];\n"
+        );
+
+        let env = std::fs::read_to_string(temp.path().join(".env")).unwrap();
+        assert!(env.starts_with("# env\n\n###> symfony/framework-bundle ###\n# Framework settings\nAPP_ENV=dev\nAPP_SECRET="));
+        assert!(env.ends_with("###< symfony/framework-bundle ###\n"));
+        assert!(!temp.path().join(".env.dist").exists());
+
+        let gitignore = std::fs::read_to_string(temp.path().join(".gitignore")).unwrap();
+        assert_eq!(
+            gitignore,
+            "\n###> symfony/framework-bundle ###\n/.env.local\n/var/\n###< symfony/framework-bundle ###\n"
+        );
+
+        // Applying again does not duplicate anything
+        configurator.install(&framework_recipe()).unwrap();
+        assert_eq!(std::fs::read_to_string(temp.path().join("config/bundles.php")).unwrap(), bundles);
+        assert_eq!(std::fs::read_to_string(temp.path().join(".env")).unwrap(), env);
+        assert_eq!(std::fs::read_to_string(temp.path().join(".gitignore")).unwrap(), gitignore);
+    }
+
+    #[test]
+    fn test_install_creates_env_file() {
+        let temp = TempDir::new().unwrap();
+
+        Configurator::new(options(temp.path()), false).install(&framework_recipe()).unwrap();
+
+        let env = std::fs::read_to_string(temp.path().join(".env")).unwrap();
+        assert!(env.contains("###> symfony/framework-bundle ###\n# Framework settings\nAPP_ENV=dev\n"));
+        assert!(!temp.path().join(".env.dist").exists());
+    }
+
+    #[test]
+    fn test_recipe_files_stay_in_project() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        let configurator = Configurator::new(options(&project), false);
+
+        for target in ["../escape.php", "config/../../escape.php", "/tmp/escape.php"] {
+            let mut report = ConfigureReport::default();
+            assert!(configurator.write_file(target, b"<?php", false, &mut report).is_err(), "{}", target);
+        }
+        assert!(!temp.path().join("escape.php").exists());
+
+        let mut report = ConfigureReport::default();
+        configurator.write_file("./config/app.php", b"<?php", false, &mut report).unwrap();
+        assert_eq!(report.files, vec!["config/app.php"]);
+    }
+
+    #[test]
+    fn test_install_detects_conflicts() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("config/packages/framework.yaml");
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, "framework: ~\n").unwrap();
+
+        let report = Configurator::new(options(temp.path()), false)
+            .install(&framework_recipe())
+            .unwrap();
+        assert_eq!(report.conflicts, vec!["config/packages/framework.yaml"]);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "framework: ~\n");
+
+        let report = Configurator::new(options(temp.path()), true)
+            .install(&framework_recipe())
+            .unwrap();
+        assert!(report.conflicts.is_empty());
+        assert_eq!(report.overwritten, vec!["config/packages/framework.yaml"]);
+        assert!(std::fs::read_to_string(&file).unwrap().starts_with("framework:\n    secret:"));
+    }
+
+    #[test]
+    fn test_force_replaces_marked_blocks() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join(".gitignore"),
+            "/node_modules\n\n###> symfony/framework-bundle ###\n/old/\n###< symfony/framework-bundle ###\n",
+        )
+        .unwrap();
+
+        Configurator::new(options(temp.path()), true).install(&framework_recipe()).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join(".gitignore")).unwrap(),
+            "/node_modules\n\n###> symfony/framework-bundle ###\n/.env.local\n/var/\n###< symfony/framework-bundle ###\n"
+        );
+    }
+
+    #[test]
+    fn test_existing_bundles_are_kept() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("config")).unwrap();
+        std::fs::write(
+            temp.path().join("config/bundles.php"),
+            "<?php\n\nreturn [\n    Symfony\\Bundle\\FrameworkBundle\\FrameworkBundle::class => ['dev' => true],\n    App\\AppBundle::class => ['all' => true],\n];\n",
+        )
+        .unwrap();
+
+        let configurator = Configurator::new(options(temp.path()), false);
+        configurator.install(&framework_recipe()).unwrap();
+        let bundles = std::fs::read_to_string(temp.path().join("config/bundles.php")).unwrap();
+        assert!(bundles.contains("FrameworkBundle::class => ['dev' => true]"));
+        assert_eq!(bundles.matches("FrameworkBundle::class").count(), 1);
+
+        let recipe = framework_recipe();
+        configurator.uninstall(&recipe.package, Some(&recipe.manifest)).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("config/bundles.php")).unwrap(),
+            "<?php\n\nreturn [\n    App\\AppBundle::class => ['all' => true],\n];\n"
+        );
+    }
+
+    #[test]
+    fn test_uninstall_removes_marked_blocks() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join(".env"), "APP_DEBUG=1\n").unwrap();
+
+        let configurator = Configurator::new(options(temp.path()), false);
+        let recipe = framework_recipe();
+        configurator.install(&recipe).unwrap();
+        configurator.uninstall(&recipe.package, Some(&recipe.manifest)).unwrap();
+
+        assert_eq!(std::fs::read_to_string(temp.path().join(".env")).unwrap(), "APP_DEBUG=1\n");
+        assert_eq!(std::fs::read_to_string(temp.path().join(".gitignore")).unwrap(), "\n");
+    }
+
+    #[test]
+    fn test_quote_env_value() {
+        assert_eq!(quote_env_value("dev"), "dev");
+        assert_eq!(quote_env_value("mysql://app@127.0.0.1:3306/app"), "mysql://app@127.0.0.1:3306/app");
+        assert_eq!(quote_env_value("a b"), "\"a b\"");
+        assert_eq!(quote_env_value("say \"hi\""), "\"say \\\"hi\\\"\"");
+    }
+
+    #[test]
+    fn test_generate_secret() {
+        let secret = generate_secret();
+        assert_eq!(secret.len(), 32);
+        assert!(secret.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(secret, generate_secret());
+    }
+}
@@ -0,0 +1,140 @@
+//! symfony.lock handling.
+//!
+//! The lock records which recipe was applied for each package and which
+//! files it created, so recipes are only applied once and can be
+//! re-applied or removed later.
+
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::Result;
+
+/// The symfony.lock file of a project.
+#[derive(Debug, Clone)]
+pub struct SymfonyLock {
+    path: PathBuf,
+    entries: IndexMap<String, Value>,
+    changed: bool,
+}
+
+impl SymfonyLock {
+    /// Load the lock from the project directory. A missing file yields an empty lock.
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join("symfony.lock");
+        let entries = if path.exists() {
+            let content = std::fs::read_to_string(&path)?;
+            if content.trim().is_empty() {
+                IndexMap::new()
+            } else {
+                serde_json::from_str(&content)?
+            }
+        } else {
+            IndexMap::new()
+        };
+
+        Ok(Self { path, entries, changed: false })
+    }
+
+    /// Whether the package has an entry.
+    pub fn has(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Get the entry for a package.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.entries.get(name)
+    }
+
+    /// Set the entry for a package.
+    pub fn set(&mut self, name: &str, entry: Value) {
+        self.entries.insert(name.to_string(), entry);
+        self.changed = true;
+    }
+
+    /// Remove the entry for a package.
+    pub fn remove(&mut self, name: &str) {
+        if self.entries.shift_remove(name).is_some() {
+            self.changed = true;
+        }
+    }
+
+    /// Names of all locked packages.
+    pub fn names(&self) -> Vec<String> {
+        self.entries.keys().cloned().collect()
+    }
+
+    /// Files recorded for a package's recipe.
+    pub fn files(&self, name: &str) -> Vec<String> {
+        self.get(name)
+            .and_then(|e| e.get("files"))
+            .and_then(|f| f.as_array())
+            .map(|files| files.iter().filter_map(|f| f.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    }
+
+    /// The recipe reference recorded for a package.
+    pub fn recipe_ref(&self, name: &str) -> Option<&str> {
+        self.get(name)
+            .and_then(|e| e.get("recipe"))
+            .and_then(|r| r.get("ref"))
+            .and_then(|r| r.as_str())
+    }
+
+    /// Write the lock if it changed. Keys are sorted and indented with
+    /// four spaces, like Flex does.
+    pub fn write(&mut self) -> Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+
+        self.entries.sort_keys();
+        std::fs::write(&self.path, to_json_4(&self.entries)? + "\n")?;
+        self.changed = false;
+        Ok(())
+    }
+}
+
+/// Serialize with a four space indent.
+fn to_json_4<T: Serialize>(value: &T) -> Result<String> {
+    let mut buf = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+    value.serialize(&mut ser)?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_roundtrip_sorted() {
+        let temp = TempDir::new().unwrap();
+
+        let mut lock = SymfonyLock::load(temp.path()).unwrap();
+        assert!(!lock.has("symfony/console"));
+
+        lock.set("symfony/framework-bundle", json!({
+            "version": "7.0",
+            "recipe": {"repo": "github.com/symfony/recipes", "branch": "main", "version": "7.0", "ref": "abc"},
+            "files": ["config/packages/framework.yaml"]
+        }));
+        lock.set("symfony/console", json!({"version": "v7.0.1"}));
+        lock.write().unwrap();
+
+        let content = std::fs::read_to_string(temp.path().join("symfony.lock")).unwrap();
+        assert!(content.starts_with("{\n    \"symfony/console\""));
+        assert!(content.ends_with("}\n"));
+
+        let lock = SymfonyLock::load(temp.path()).unwrap();
+        assert_eq!(lock.names(), vec!["symfony/console", "symfony/framework-bundle"]);
+        assert_eq!(lock.recipe_ref("symfony/framework-bundle"), Some("abc"));
+        assert_eq!(lock.files("symfony/framework-bundle"), vec!["config/packages/framework.yaml"]);
+        assert!(lock.files("symfony/console").is_empty());
+    }
+}
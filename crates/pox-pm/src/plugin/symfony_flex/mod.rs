@@ -0,0 +1,606 @@
+//! Symfony Flex plugin - applies recipes for installed packages.
+//!
+//! This is a native Rust port of the recipe engine of symfony/flex.
+//! When symfony/flex is installed, recipes are applied after install and
+//! update for packages missing from symfony.lock, and reverted for
+//! packages that were removed. The `recipes` and `recipes:install`
+//! commands expose the engine on the command line.
+
+mod configurator;
+mod lock;
+mod recipe;
+
+use std::path::Path;
+
+use console::style;
+use serde_json::{json, Value};
+
+use crate::composer::Composer;
+use crate::config::Config;
+use crate::event::{ComposerEvent, EventListener, EventType};
+use crate::http::ProxyConfig;
+use crate::json::ComposerJson;
+use crate::Result;
+
+use super::command::PluginCommand;
+
+pub use configurator::{ConfigureReport, Configurator, Options};
+pub use lock::SymfonyLock;
+pub use recipe::{Recipe, RecipeDownloader, RecipeFile, RecipeIndex, DEFAULT_ENDPOINTS};
+
+/// The package name that triggers this plugin.
+pub const PACKAGE_NAME: &str = "symfony/flex";
+
+/// Packages whose recipes are applied before all others, like Flex does.
+const PRIORITY_PACKAGES: &[&str] = &["symfony/flex", "symfony/framework-bundle"];
+
+/// An installed package as read from vendor/composer/installed.json.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledPackage {
+    pub name: String,
+    pub pretty_version: String,
+}
+
+/// Read the installed packages, ordered with Flex's priority packages first.
+pub fn installed_packages(vendor_dir: &Path) -> Vec<InstalledPackage> {
+    let Ok(content) = std::fs::read_to_string(vendor_dir.join("composer").join("installed.json")) else {
+        return Vec::new();
+    };
+    let Ok(data) = serde_json::from_str::<Value>(&content) else {
+        return Vec::new();
+    };
+
+    let entries = match &data {
+        Value::Array(packages) => packages.clone(),
+        _ => data.get("packages").and_then(|p| p.as_array()).cloned().unwrap_or_default(),
+    };
+
+    let mut packages: Vec<InstalledPackage> = entries
+        .iter()
+        .filter_map(|p| {
+            Some(InstalledPackage {
+                name: p.get("name")?.as_str()?.to_lowercase(),
+                pretty_version: p.get("version")?.as_str()?.to_string(),
+            })
+        })
+        .collect();
+
+    packages.sort_by_key(|p| {
+        PRIORITY_PACKAGES
+            .iter()
+            .position(|name| *name == p.name)
+            .unwrap_or(PRIORITY_PACKAGES.len())
+    });
+    packages
+}
+
+/// Recipe endpoints from `extra.symfony.endpoint`, or the defaults.
+///
+/// Contrib recipes are only used with `extra.symfony.allow-contrib`.
+pub fn endpoints(composer_json: &ComposerJson) -> Vec<String> {
+    let symfony = composer_json.extra.get("symfony");
+
+    match symfony.and_then(|s| s.get("endpoint")) {
+        Some(Value::String(endpoint)) => return vec![endpoint.clone()],
+        Some(Value::Array(endpoints)) => {
+            return endpoints.iter().filter_map(|e| e.as_str().map(String::from)).collect();
+        }
+        _ => {}
+    }
+
+    let allow_contrib = symfony
+        .and_then(|s| s.get("allow-contrib"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let count = if allow_contrib { DEFAULT_ENDPOINTS.len() } else { 1 };
+    DEFAULT_ENDPOINTS[..count].iter().map(|e| e.to_string()).collect()
+}
+
+/// Constraint the Symfony components are pinned to, from the `SYMFONY_REQUIRE`
+/// environment variable or `extra.symfony.require`.
+pub fn symfony_require(composer_json: &ComposerJson) -> Option<String> {
+    std::env::var("SYMFONY_REQUIRE")
+        .ok()
+        .or_else(|| {
+            composer_json.extra.get("symfony")
+                .and_then(|s| s.get("require"))
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        })
+        .map(|constraint| constraint.trim().to_string())
+        .filter(|constraint| !constraint.is_empty() && constraint != "*")
+}
+
+/// The recipe engine for one project.
+pub struct Flex {
+    options: Options,
+    downloader: RecipeDownloader,
+}
+
+impl Flex {
+    /// Create the engine for a project, using the endpoints from composer.json.
+    pub fn new(project_dir: &Path, vendor_dir: &Path, composer_json: &ComposerJson, proxy: &ProxyConfig) -> Self {
+        Self::with_downloader(
+            project_dir,
+            vendor_dir,
+            composer_json,
+            RecipeDownloader::new(endpoints(composer_json)).with_proxy(proxy.clone()),
+        )
+    }
+
+    /// Create the engine with a custom recipe downloader.
+    pub fn with_downloader(
+        project_dir: &Path,
+        vendor_dir: &Path,
+        composer_json: &ComposerJson,
+        downloader: RecipeDownloader,
+    ) -> Self {
+        Self {
+            options: Options::new(project_dir, vendor_dir, composer_json),
+            downloader,
+        }
+    }
+
+    /// Apply recipes for installed packages missing from symfony.lock and
+    /// revert recipes of packages that are no longer installed.
+    pub fn sync(&mut self) -> Result<()> {
+        let installed = installed_packages(&self.options.vendor_dir);
+        let mut lock = SymfonyLock::load(&self.options.project_dir)?;
+
+        let removed: Vec<String> = lock
+            .names()
+            .into_iter()
+            .filter(|name| !installed.iter().any(|p| &p.name == name))
+            .collect();
+        let added: Vec<&InstalledPackage> = installed.iter().filter(|p| !lock.has(&p.name)).collect();
+
+        let mut operations = Vec::new();
+        for package in added {
+            match self.downloader.fetch(&package.name, &package.pretty_version) {
+                Ok(recipe) => operations.push((package, recipe)),
+                Err(e) => eprintln!(
+                    "{} Could not load the recipe for {}: {}",
+                    style("Warning:").yellow().bold(),
+                    package.name,
+                    e
+                ),
+            }
+        }
+
+        let recipe_count = operations.iter().filter(|(_, r)| r.is_some()).count()
+            + removed.iter().filter(|name| lock.recipe_ref(name).is_some()).count();
+        if recipe_count > 0 {
+            println!(
+                "{} {} recipe{}",
+                style("Symfony operations:").green().bold(),
+                recipe_count,
+                if recipe_count == 1 { "" } else { "s" }
+            );
+        }
+
+        for name in &removed {
+            self.uninstall(&mut lock, name)?;
+        }
+
+        let configurator = Configurator::new(self.options.clone(), false);
+        for (package, recipe) in operations {
+            apply(&configurator, &mut lock, package, recipe.as_ref())?;
+        }
+
+        lock.write()
+    }
+
+    /// Apply (or with `force`, re-apply) the recipes of the given packages.
+    /// Without packages, every installed package missing from symfony.lock
+    /// is configured, or every installed package when forced.
+    pub fn install_recipes(&mut self, packages: &[String], force: bool) -> Result<()> {
+        let installed = installed_packages(&self.options.vendor_dir);
+        let mut lock = SymfonyLock::load(&self.options.project_dir)?;
+
+        let targets: Vec<&InstalledPackage> = if packages.is_empty() {
+            installed.iter().filter(|p| force || !lock.has(&p.name)).collect()
+        } else {
+            let mut targets = Vec::new();
+            for name in packages {
+                let name = name.to_lowercase();
+                let package = installed.iter().find(|p| p.name == name).ok_or_else(|| {
+                    crate::error::ComposerError::PackageNotFound { name: name.clone() }
+                })?;
+                targets.push(package);
+            }
+            targets
+        };
+
+        let configurator = Configurator::new(self.options.clone(), force);
+        let mut configured = 0;
+        for package in targets {
+            let recipe = self.downloader.fetch(&package.name, &package.pretty_version)?;
+            if recipe.is_none() && !packages.is_empty() {
+                println!("{} No recipe found for {}", style("Info:").cyan(), package.name);
+            }
+            if recipe.is_some() {
+                configured += 1;
+            }
+            apply(&configurator, &mut lock, package, recipe.as_ref())?;
+        }
+
+        if configured == 0 && packages.is_empty() {
+            println!("{} No recipes to install", style("Info:").cyan());
+        }
+
+        lock.write()
+    }
+
+    /// Revert the recipe of a removed package and drop it from the lock.
+    fn uninstall(&mut self, lock: &mut SymfonyLock, name: &str) -> Result<()> {
+        let recipe_version = lock
+            .get(name)
+            .and_then(|e| e.get("recipe"))
+            .and_then(|r| r.get("version"))
+            .and_then(|v| v.as_str())
+            .map(String::from);
+
+        if let Some(version) = recipe_version {
+            println!("  - Unconfiguring {}", style(name).green());
+
+            // The manifest is only needed to unregister bundles
+            let recipe = self.downloader.fetch_version(name, &version).unwrap_or(None);
+            Configurator::new(self.options.clone(), false)
+                .uninstall(name, recipe.as_ref().map(|r| &r.manifest))?;
+        }
+
+        lock.remove(name);
+        Ok(())
+    }
+}
+
+/// Apply one recipe and record it in the lock.
+fn apply(configurator: &Configurator, lock: &mut SymfonyLock, package: &InstalledPackage, recipe: Option<&Recipe>) -> Result<()> {
+    let Some(recipe) = recipe else {
+        if !lock.has(&package.name) {
+            lock.set(&package.name, json!({ "version": package.pretty_version }));
+        }
+        return Ok(());
+    };
+
+    println!(
+        "  - Configuring {} (>={}): From {}:{}",
+        style(&recipe.package).green(),
+        recipe.version,
+        recipe.repository,
+        recipe.branch
+    );
+
+    let report = configurator.install(recipe)?;
+    for file in &report.conflicts {
+        println!(
+            "    {} {} was modified locally and was kept (use recipes:install --force to overwrite)",
+            style("Conflict:").yellow().bold(),
+            file
+        );
+    }
+    for file in &report.overwritten {
+        println!("    {} {} was overwritten", style("Info:").cyan(), file);
+    }
+
+    lock.set(
+        &package.name,
+        json!({
+            "version": recipe::major_minor(&package.pretty_version),
+            "recipe": recipe.lock_data(),
+            "files": report.files,
+        }),
+    );
+    Ok(())
+}
+
+/// Whether symfony/flex is installed in the vendor directory.
+fn is_flex_installed(vendor_dir: &Path) -> bool {
+    installed_packages(vendor_dir).iter().any(|p| p.name == PACKAGE_NAME)
+}
+
+/// Symfony Flex plugin - implements EventListener directly.
+pub struct SymfonyFlexPlugin;
+
+impl EventListener for SymfonyFlexPlugin {
+    fn handle(&self, event: &dyn ComposerEvent, composer: &Composer) -> anyhow::Result<i32> {
+        if !matches!(event.event_type(), EventType::PostInstall | EventType::PostUpdate) {
+            return Ok(0);
+        }
+
+        let vendor_dir = composer.vendor_dir();
+        if !is_flex_installed(&vendor_dir) {
+            return Ok(0);
+        }
+
+        // Recipes must never fail the install itself
+        let mut flex = Flex::new(&composer.working_dir, &vendor_dir, &composer.composer_json, &composer.config.proxy);
+        if let Err(e) = flex.sync() {
+            eprintln!("{} Failed to apply Symfony recipes: {}", style("Warning:").yellow().bold(), e);
+        }
+
+        Ok(0)
+    }
+
+    fn priority(&self) -> i32 {
+        // Run before the post-install-cmd/post-update-cmd scripts
+        10
+    }
+}
+
+/// Load composer.json and the vendor directory for a recipes command.
+fn project(working_dir: &Path) -> anyhow::Result<(ComposerJson, Config)> {
+    let composer_json = crate::json::load_composer_json(&working_dir.join(crate::config::composer_file_name()))?;
+    let config = Config::build(Some(working_dir), true)?;
+    Ok((composer_json, config))
+}
+
+/// `recipes` - list the recipes recorded in symfony.lock.
+pub struct RecipesCommand;
+
+impl PluginCommand for RecipesCommand {
+    fn name(&self) -> &'static str {
+        "recipes"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows information about installed Symfony recipes"
+    }
+
+    fn execute(&self, args: &[String], working_dir: &Path) -> anyhow::Result<i32> {
+        let lock = SymfonyLock::load(working_dir)?;
+
+        if let Some(name) = args.first() {
+            let Some(entry) = lock.get(name).and_then(|e| e.get("recipe")) else {
+                eprintln!("{} No recipe installed for {}", style("Error:").red().bold(), name);
+                return Ok(1);
+            };
+
+            let field = |key: &str| entry.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            println!("{} {}", style("name").cyan(), name);
+            println!("{} {}", style("version").cyan(), field("version"));
+            println!("{} {}:{}", style("repo").cyan(), field("repo"), field("branch"));
+            println!("{} {}", style("ref").cyan(), field("ref"));
+
+            let files = lock.files(name);
+            if !files.is_empty() {
+                println!("{}", style("files").cyan());
+                for file in files {
+                    println!("  {}", file);
+                }
+            }
+            return Ok(0);
+        }
+
+        let names: Vec<String> = lock.names().into_iter().filter(|n| lock.recipe_ref(n).is_some()).collect();
+        if names.is_empty() {
+            println!("{} No recipes installed", style("Info:").cyan());
+            return Ok(0);
+        }
+
+        println!("{}", style("Installed recipes:").green().bold());
+        for name in names {
+            let version = lock
+                .get(&name)
+                .and_then(|e| e.get("recipe"))
+                .and_then(|r| r.get("version"))
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            println!(" * {} ({})", style(&name).green(), version);
+        }
+        Ok(0)
+    }
+}
+
+/// Arguments of `recipes:install`.
+#[derive(clap::Parser, Debug)]
+#[command(name = "recipes:install")]
+struct RecipesInstallArgs {
+    /// Packages to install recipes for (default: all missing ones)
+    packages: Vec<String>,
+
+    /// Overwrite existing files and re-apply already installed recipes
+    #[arg(long)]
+    force: bool,
+}
+
+/// `recipes:install` - apply recipes, optionally re-applying them.
+pub struct RecipesInstallCommand;
+
+impl PluginCommand for RecipesInstallCommand {
+    fn name(&self) -> &'static str {
+        "recipes:install"
+    }
+
+    fn description(&self) -> &'static str {
+        "Installs or reinstalls Symfony recipes for already installed packages"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["symfony:recipes:install"]
+    }
+
+    fn execute(&self, args: &[String], working_dir: &Path) -> anyhow::Result<i32> {
+        use clap::Parser;
+
+        let args = match RecipesInstallArgs::try_parse_from(std::iter::once(self.name().to_string()).chain(args.iter().cloned())) {
+            Ok(args) => args,
+            Err(e) => {
+                e.print()?;
+                return Ok(e.exit_code());
+            }
+        };
+
+        let (composer_json, config) = project(working_dir)?;
+        let vendor_dir = working_dir.join(&config.vendor_dir);
+        Flex::new(working_dir, &vendor_dir, &composer_json, &config.proxy).install_recipes(&args.packages, args.force)?;
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Write a local recipe endpoint with one framework-bundle recipe.
+    fn write_endpoint(dir: &Path) -> String {
+        let endpoint = dir.join("recipes");
+        std::fs::create_dir_all(&endpoint).unwrap();
+        std::fs::write(
+            endpoint.join("index.json"),
+            json!({
+                "recipes": {"symfony/framework-bundle": ["6.4", "7.0"]},
+                "branch": "main",
+                "_links": {
+                    "repository": "github.com/symfony/recipes",
+                    "recipe_template_relative": "{package_dotted}.{version}.json"
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        std::fs::write(
+            endpoint.join("symfony.framework-bundle.7.0.json"),
+            json!({
+                "manifests": {
+                    "symfony/framework-bundle": {
+                        "manifest": {
+                            "bundles": {"Symfony\\Bundle\\FrameworkBundle\\FrameworkBundle": ["all"]},
+                            "copy-from-recipe": {"config/": "%CONFIG_DIR%/"},
+                            "env": {"APP_ENV": "dev"}
+                        },
+                        "files": {"config/packages/framework.yaml": {"contents": ["framework: ~"], "executable": false}},
+                        "ref": "abc123"
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        endpoint.join("index.json").to_string_lossy().to_string()
+    }
+
+    fn write_installed(vendor_dir: &Path, packages: &[(&str, &str)]) {
+        std::fs::create_dir_all(vendor_dir.join("composer")).unwrap();
+        let packages: Vec<Value> = packages.iter().map(|(n, v)| json!({"name": n, "version": v})).collect();
+        std::fs::write(
+            vendor_dir.join("composer/installed.json"),
+            json!({"packages": packages}).to_string(),
+        )
+        .unwrap();
+    }
+
+    fn flex(project_dir: &Path, endpoint: String) -> Flex {
+        let composer_json = ComposerJson {
+            extra: json!({"symfony": {"endpoint": endpoint}}),
+            ..Default::default()
+        };
+        Flex::new(project_dir, &project_dir.join("vendor"), &composer_json, &ProxyConfig::default())
+    }
+
+    #[test]
+    fn test_endpoints() {
+        assert_eq!(endpoints(&ComposerJson::default()), vec![DEFAULT_ENDPOINTS[0]]);
+
+        let contrib = ComposerJson {
+            extra: json!({"symfony": {"allow-contrib": true}}),
+            ..Default::default()
+        };
+        assert_eq!(endpoints(&contrib).len(), 2);
+
+        let custom = ComposerJson {
+            extra: json!({"symfony": {"endpoint": ["https://a.example/index.json", "flex://defaults"]}}),
+            ..Default::default()
+        };
+        assert_eq!(endpoints(&custom), vec!["https://a.example/index.json", "flex://defaults"]);
+    }
+
+    #[test]
+    fn test_symfony_require() {
+        if std::env::var_os("SYMFONY_REQUIRE").is_some() {
+            return;
+        }
+        assert_eq!(symfony_require(&ComposerJson::default()), None);
+
+        let pinned = ComposerJson {
+            extra: json!({"symfony": {"require": " 7.1.* "}}),
+            ..Default::default()
+        };
+        assert_eq!(symfony_require(&pinned).as_deref(), Some("7.1.*"));
+
+        let any = ComposerJson {
+            extra: json!({"symfony": {"require": "*"}}),
+            ..Default::default()
+        };
+        assert_eq!(symfony_require(&any), None);
+    }
+
+    #[test]
+    fn test_installed_packages_priority_order() {
+        let temp = TempDir::new().unwrap();
+        write_installed(temp.path(), &[
+            ("symfony/console", "v7.0.1"),
+            ("symfony/framework-bundle", "v7.0.1"),
+            ("symfony/flex", "v2.4.0"),
+        ]);
+
+        let names: Vec<String> = installed_packages(temp.path()).into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["symfony/flex", "symfony/framework-bundle", "symfony/console"]);
+    }
+
+    #[test]
+    fn test_sync_installs_and_removes_recipes() {
+        let temp = TempDir::new().unwrap();
+        let endpoint = write_endpoint(temp.path());
+        std::fs::write(temp.path().join(".env"), "").unwrap();
+        let vendor_dir = temp.path().join("vendor");
+
+        write_installed(&vendor_dir, &[("symfony/framework-bundle", "v7.1.2"), ("symfony/console", "v7.1.0")]);
+        flex(temp.path(), endpoint.clone()).sync().unwrap();
+
+        let lock = SymfonyLock::load(temp.path()).unwrap();
+        assert_eq!(lock.recipe_ref("symfony/framework-bundle"), Some("abc123"));
+        assert_eq!(lock.get("symfony/framework-bundle").unwrap()["version"], "7.1");
+        assert_eq!(lock.files("symfony/framework-bundle"), vec!["config/packages/framework.yaml"]);
+        assert_eq!(lock.get("symfony/console").unwrap(), &json!({"version": "v7.1.0"}));
+        assert!(temp.path().join("config/packages/framework.yaml").exists());
+        assert!(std::fs::read_to_string(temp.path().join("config/bundles.php")).unwrap().contains("FrameworkBundle::class"));
+        assert!(std::fs::read_to_string(temp.path().join(".env")).unwrap().contains("APP_ENV=dev"));
+
+        write_installed(&vendor_dir, &[("symfony/console", "v7.1.0")]);
+        flex(temp.path(), endpoint).sync().unwrap();
+
+        let lock = SymfonyLock::load(temp.path()).unwrap();
+        assert!(!lock.has("symfony/framework-bundle"));
+        assert!(lock.has("symfony/console"));
+        assert!(!std::fs::read_to_string(temp.path().join("config/bundles.php")).unwrap().contains("FrameworkBundle"));
+        assert!(!std::fs::read_to_string(temp.path().join(".env")).unwrap().contains("APP_ENV"));
+    }
+
+    #[test]
+    fn test_install_recipes_force() {
+        let temp = TempDir::new().unwrap();
+        let endpoint = write_endpoint(temp.path());
+        let vendor_dir = temp.path().join("vendor");
+        write_installed(&vendor_dir, &[("symfony/framework-bundle", "v7.0.0")]);
+
+        let file = temp.path().join("config/packages/framework.yaml");
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, "framework: { secret: foo }").unwrap();
+
+        flex(temp.path(), endpoint.clone()).install_recipes(&[], false).unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "framework: { secret: foo }");
+
+        flex(temp.path(), endpoint.clone())
+            .install_recipes(&["symfony/framework-bundle".to_string()], true)
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "framework: ~");
+
+        assert!(flex(temp.path(), endpoint)
+            .install_recipes(&["symfony/missing".to_string()], false)
+            .is_err());
+    }
+}
@@ -0,0 +1,369 @@
+//! Recipe index and recipe manifests served by Flex endpoints.
+
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use serde_json::{json, Map, Value};
+
+use crate::error::ComposerError;
+use crate::http::ProxyConfig;
+use crate::Result;
+
+/// Default Flex endpoints (official recipes first, then contrib).
+pub const DEFAULT_ENDPOINTS: &[&str] = &[
+    "https://raw.githubusercontent.com/symfony/recipes/flex/main/index.json",
+    "https://raw.githubusercontent.com/symfony/recipes-contrib/flex/main/index.json",
+];
+
+/// A file shipped with a recipe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipeFile {
+    pub contents: String,
+    pub executable: bool,
+}
+
+/// A recipe for one package.
+#[derive(Debug, Clone)]
+pub struct Recipe {
+    /// Package name the recipe configures
+    pub package: String,
+    /// Recipe version (e.g. "7.0")
+    pub version: String,
+    /// Repository the recipe comes from (e.g. "github.com/symfony/recipes")
+    pub repository: String,
+    /// Branch of the recipe repository
+    pub branch: String,
+    /// Recipe reference (commit hash of the recipe)
+    pub reference: String,
+    /// Configurator manifest (bundles, env, copy-from-recipe, ...)
+    pub manifest: Map<String, Value>,
+    /// Files referenced by copy-from-recipe
+    pub files: IndexMap<String, RecipeFile>,
+}
+
+impl Recipe {
+    /// Parse a recipe from its JSON document.
+    pub fn from_json(package: &str, version: &str, repository: &str, branch: &str, data: &Value) -> Result<Self> {
+        let entry = data
+            .get("manifests")
+            .and_then(|m| m.get(package))
+            .ok_or_else(|| ComposerError::Repository(format!("Recipe for {} has no manifest", package)))?;
+
+        let manifest = entry
+            .get("manifest")
+            .and_then(|m| m.as_object())
+            .cloned()
+            .unwrap_or_default();
+
+        let mut files = IndexMap::new();
+        if let Some(map) = entry.get("files").and_then(|f| f.as_object()) {
+            for (path, file) in map {
+                let contents = match file.get("contents") {
+                    Some(Value::Array(lines)) => lines
+                        .iter()
+                        .map(|l| l.as_str().unwrap_or_default())
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    Some(Value::String(s)) => s.clone(),
+                    _ => String::new(),
+                };
+                let executable = file.get("executable").and_then(|e| e.as_bool()).unwrap_or(false);
+                files.insert(path.clone(), RecipeFile { contents, executable });
+            }
+        }
+
+        Ok(Self {
+            package: package.to_string(),
+            version: version.to_string(),
+            repository: repository.to_string(),
+            branch: branch.to_string(),
+            reference: entry.get("ref").and_then(|r| r.as_str()).unwrap_or_default().to_string(),
+            manifest,
+            files,
+        })
+    }
+
+    /// The `recipe` block stored in symfony.lock.
+    pub fn lock_data(&self) -> Value {
+        json!({
+            "repo": self.repository,
+            "branch": self.branch,
+            "version": self.version,
+            "ref": self.reference,
+        })
+    }
+
+    /// Human readable origin, e.g. `symfony/console:7.0@github.com/symfony/recipes:main`.
+    pub fn origin(&self) -> String {
+        format!("{}:{}@{}:{}", self.package, self.version, self.repository, self.branch)
+    }
+}
+
+/// The recipe index published by a Flex endpoint.
+#[derive(Debug, Clone, Default)]
+pub struct RecipeIndex {
+    /// Available recipe versions per package
+    pub recipes: HashMap<String, Vec<String>>,
+    /// Repository name (e.g. "github.com/symfony/recipes")
+    pub repository: String,
+    /// Branch of the repository
+    pub branch: String,
+    /// URL template with `{package_dotted}` and `{version}` placeholders
+    pub recipe_template: String,
+}
+
+impl RecipeIndex {
+    /// Parse an index.json document. `base_url` resolves relative templates.
+    pub fn from_json(data: &Value, base_url: &str) -> Self {
+        let recipes = data
+            .get("recipes")
+            .and_then(|r| r.as_object())
+            .map(|map| {
+                map.iter()
+                    .map(|(name, versions)| {
+                        let versions = versions
+                            .as_array()
+                            .map(|v| v.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                            .unwrap_or_default();
+                        (name.clone(), versions)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let links = data.get("_links");
+        let link = |key: &str| {
+            links
+                .and_then(|l| l.get(key))
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        };
+
+        let recipe_template = link("recipe_template").unwrap_or_else(|| {
+            let relative = link("recipe_template_relative").unwrap_or_else(|| "{package_dotted}.{version}.json".to_string());
+            let base = base_url.rsplit_once('/').map(|(b, _)| b).unwrap_or(base_url);
+            format!("{}/{}", base, relative)
+        });
+
+        Self {
+            recipes,
+            repository: link("repository").unwrap_or_default(),
+            branch: data.get("branch").and_then(|b| b.as_str()).unwrap_or("main").to_string(),
+            recipe_template,
+        }
+    }
+
+    /// Pick the highest recipe version that does not exceed the installed version.
+    pub fn best_version(&self, package: &str, installed_version: &str) -> Option<String> {
+        let installed = version_key(installed_version);
+        self.recipes
+            .get(package)?
+            .iter()
+            .filter(|v| version_key(v) <= installed)
+            .max_by_key(|v| version_key(v))
+            .cloned()
+    }
+
+    /// URL of a recipe document.
+    pub fn recipe_url(&self, package: &str, version: &str) -> String {
+        self.recipe_template
+            .replace("{package_dotted}", &package.replace('/', "."))
+            .replace("{package}", package)
+            .replace("{version}", version)
+    }
+}
+
+/// Numeric key for ordering versions like "v7.0.3", "7.0" or "dev-main".
+///
+/// Dev branches sort above every release so they pick the newest recipe.
+pub(crate) fn version_key(version: &str) -> Vec<u64> {
+    let version = version.trim_start_matches('v');
+    if version.starts_with("dev-") {
+        return vec![u64::MAX];
+    }
+    version
+        .split(['.', '-', '+'])
+        .map_while(|part| part.parse::<u64>().ok())
+        .collect()
+}
+
+/// The `major.minor` part of a version, as stored in symfony.lock.
+pub(crate) fn major_minor(version: &str) -> String {
+    let key = version_key(version);
+    match key.as_slice() {
+        [u64::MAX] => version.to_string(),
+        [major, minor, ..] => format!("{}.{}", major, minor),
+        [major] => format!("{}.0", major),
+        [] => version.to_string(),
+    }
+}
+
+/// Fetches recipe indexes and recipes from Flex endpoints.
+pub struct RecipeDownloader {
+    endpoints: Vec<String>,
+    indexes: Option<Vec<RecipeIndex>>,
+    proxy: ProxyConfig,
+}
+
+impl RecipeDownloader {
+    /// Create a downloader for the given endpoints.
+    pub fn new(endpoints: Vec<String>) -> Self {
+        Self { endpoints, indexes: None, proxy: ProxyConfig::default() }
+    }
+
+    /// Download through the proxy settings.
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    fn indexes(&mut self) -> Result<&[RecipeIndex]> {
+        if self.indexes.is_none() {
+            let mut indexes = Vec::new();
+            for endpoint in &self.endpoints {
+                let data = fetch_json(endpoint, &self.proxy)?;
+                indexes.push(RecipeIndex::from_json(&data, endpoint));
+            }
+            self.indexes = Some(indexes);
+        }
+        Ok(self.indexes.as_deref().unwrap_or_default())
+    }
+
+    /// Locate the recipe for a package without downloading it.
+    /// Returns the index it was found in and the recipe version.
+    pub fn locate(&mut self, package: &str, installed_version: &str) -> Result<Option<(RecipeIndex, String)>> {
+        for index in self.indexes()? {
+            if let Some(version) = index.best_version(package, installed_version) {
+                return Ok(Some((index.clone(), version)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Download a specific recipe version, e.g. the one recorded in symfony.lock.
+    pub fn fetch_version(&mut self, package: &str, version: &str) -> Result<Option<Recipe>> {
+        let Some(index) = self
+            .indexes()?
+            .iter()
+            .find(|i| i.recipes.get(package).is_some_and(|v| v.iter().any(|v| v == version)))
+            .cloned()
+        else {
+            return Ok(None);
+        };
+
+        let data = fetch_json(&index.recipe_url(package, version), &self.proxy)?;
+        Recipe::from_json(package, version, &index.repository, &index.branch, &data).map(Some)
+    }
+
+    /// Download the recipe for a package, if any endpoint has one.
+    pub fn fetch(&mut self, package: &str, installed_version: &str) -> Result<Option<Recipe>> {
+        let Some((index, version)) = self.locate(package, installed_version)? else {
+            return Ok(None);
+        };
+
+        let data = fetch_json(&index.recipe_url(package, &version), &self.proxy)?;
+        Recipe::from_json(package, &version, &index.repository, &index.branch, &data).map(Some)
+    }
+}
+
+/// Fetch a JSON document from a URL or a local path (`file://` or plain).
+///
+/// HTTP requests run on their own thread so the blocking client is safe to
+/// use from listeners dispatched inside the async installer.
+fn fetch_json(url: &str, proxy: &ProxyConfig) -> Result<Value> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        let path = url.strip_prefix("file://").unwrap_or(url);
+        return Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?);
+    }
+
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| -> Result<Value> {
+                let response = crate::http::blocking_client(proxy)
+                    .get(url)
+                    .send()?
+                    .error_for_status()?;
+                Ok(response.json()?)
+            })
+            .join()
+            .unwrap_or_else(|_| Err(ComposerError::Repository(format!("Failed to fetch {}", url))))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_key() {
+        assert_eq!(version_key("v7.0.3"), vec![7, 0, 3]);
+        assert_eq!(version_key("7.1"), vec![7, 1]);
+        assert_eq!(version_key("2.0.0-beta1"), vec![2, 0, 0]);
+        assert_eq!(version_key("dev-main"), vec![u64::MAX]);
+        assert_eq!(major_minor("v6.4.12"), "6.4");
+        assert_eq!(major_minor("3"), "3.0");
+    }
+
+    #[test]
+    fn test_index_best_version() {
+        let data = json!({
+            "recipes": {"symfony/framework-bundle": ["5.4", "6.2", "6.4", "7.0"]},
+            "branch": "main",
+            "_links": {
+                "repository": "github.com/symfony/recipes",
+                "recipe_template": "https://example.com/{package_dotted}.{version}.json"
+            }
+        });
+        let index = RecipeIndex::from_json(&data, "https://example.com/index.json");
+
+        assert_eq!(index.best_version("symfony/framework-bundle", "v6.4.3"), Some("6.4".to_string()));
+        assert_eq!(index.best_version("symfony/framework-bundle", "v6.3.0"), Some("6.2".to_string()));
+        assert_eq!(index.best_version("symfony/framework-bundle", "v7.2.0"), Some("7.0".to_string()));
+        assert_eq!(index.best_version("symfony/framework-bundle", "dev-main"), Some("7.0".to_string()));
+        assert_eq!(index.best_version("symfony/framework-bundle", "v5.0.0"), None);
+        assert_eq!(index.best_version("symfony/console", "v7.0.0"), None);
+
+        assert_eq!(
+            index.recipe_url("symfony/framework-bundle", "6.4"),
+            "https://example.com/symfony.framework-bundle.6.4.json"
+        );
+    }
+
+    #[test]
+    fn test_index_relative_template() {
+        let data = json!({"recipes": {}, "_links": {"recipe_template_relative": "{package_dotted}.{version}.json"}});
+        let index = RecipeIndex::from_json(&data, "https://example.com/flex/main/index.json");
+        assert_eq!(
+            index.recipe_url("vendor/package", "1.0"),
+            "https://example.com/flex/main/vendor.package.1.0.json"
+        );
+    }
+
+    #[test]
+    fn test_recipe_from_json() {
+        let data = json!({
+            "manifests": {
+                "symfony/framework-bundle": {
+                    "manifest": {"bundles": {"Symfony\\Bundle\\FrameworkBundle\\FrameworkBundle": ["all"]}},
+                    "files": {
+                        "config/packages/framework.yaml": {"contents": ["framework:", "    secret: '%env(APP_SECRET)%'"], "executable": false},
+                        "bin/console": {"contents": "#!/usr/bin/env php", "executable": true}
+                    },
+                    "ref": "abc123"
+                }
+            }
+        });
+
+        let recipe = Recipe::from_json("symfony/framework-bundle", "7.0", "github.com/symfony/recipes", "main", &data).unwrap();
+        assert_eq!(recipe.reference, "abc123");
+        assert!(recipe.manifest.contains_key("bundles"));
+        assert_eq!(
+            recipe.files["config/packages/framework.yaml"].contents,
+            "framework:\n    secret: '%env(APP_SECRET)%'"
+        );
+        assert!(recipe.files["bin/console"].executable);
+        assert_eq!(recipe.origin(), "symfony/framework-bundle:7.0@github.com/symfony/recipes:main");
+
+        assert!(Recipe::from_json("other/package", "1.0", "", "main", &data).is_err());
+    }
+}
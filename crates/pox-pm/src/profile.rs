@@ -0,0 +1,258 @@
+//! Per phase timings behind `--profile`.
+//!
+//! A [`Profiler`] reports the elapsed time and the peak resident memory after
+//! each phase of a command. Sequential phases are timed with the scoped
+//! [`Profiler::phase`] timer, work spread over concurrent tasks such as
+//! downloads is summed up with [`Profiler::record`]. A disabled profiler
+//! records nothing, so library code can time its phases unconditionally.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::io::Io;
+
+/// Timing of one profiled phase
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileEntry {
+    pub phase: String,
+    pub elapsed: Duration,
+    /// Whether `elapsed` sums up concurrent work rather than wall time
+    pub cumulative: bool,
+    /// Peak resident memory of the process in bytes when the phase ended
+    pub peak_memory: Option<u64>,
+    reported: bool,
+}
+
+impl ProfileEntry {
+    /// The line printed for this entry
+    pub fn message(&self) -> String {
+        format_line(&self.phase, self.elapsed, self.cumulative, self.peak_memory)
+    }
+}
+
+/// Collects and reports phase timings
+pub struct Profiler {
+    io: Option<Arc<dyn Io>>,
+    start: Instant,
+    entries: Mutex<Vec<ProfileEntry>>,
+}
+
+impl Profiler {
+    /// An enabled profiler reporting each finished phase to `io`
+    pub fn new(io: Arc<dyn Io>) -> Self {
+        Self {
+            io: Some(io),
+            start: Instant::now(),
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// A profiler that records nothing
+    pub fn disabled() -> Self {
+        Self {
+            io: None,
+            start: Instant::now(),
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.io.is_some()
+    }
+
+    /// Time a phase until the returned timer is dropped
+    pub fn phase(&self, name: &str) -> PhaseTimer<'_> {
+        PhaseTimer {
+            profiler: self,
+            name: name.to_string(),
+            start: Instant::now(),
+        }
+    }
+
+    /// Add `elapsed` to the cumulative time of a phase running on concurrent tasks;
+    /// it is reported together with the next finished phase
+    pub fn record(&self, name: &str, elapsed: Duration) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let peak_memory = peak_memory();
+        let mut entries = self.entries.lock().unwrap();
+        match entries.iter_mut().find(|e| e.cumulative && !e.reported && e.phase == name) {
+            Some(entry) => {
+                entry.elapsed += elapsed;
+                entry.peak_memory = entry.peak_memory.max(peak_memory);
+            }
+            None => entries.push(ProfileEntry {
+                phase: name.to_string(),
+                elapsed,
+                cumulative: true,
+                peak_memory,
+                reported: false,
+            }),
+        }
+    }
+
+    /// All phases recorded so far
+    pub fn entries(&self) -> Vec<ProfileEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    /// Report the phases not reported yet and the total time of the command
+    pub fn finish(&self) {
+        let Some(io) = &self.io else {
+            return;
+        };
+
+        self.report_pending();
+        io.write_error(&format_line("total", self.start.elapsed(), false, peak_memory()));
+    }
+
+    fn finish_phase(&self, name: String, elapsed: Duration) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.entries.lock().unwrap().push(ProfileEntry {
+            phase: name,
+            elapsed,
+            cumulative: false,
+            peak_memory: peak_memory(),
+            reported: false,
+        });
+        self.report_pending();
+    }
+
+    fn report_pending(&self) {
+        let Some(io) = &self.io else {
+            return;
+        };
+
+        let mut entries = self.entries.lock().unwrap();
+        for entry in entries.iter_mut().filter(|e| !e.reported) {
+            io.write_error(&entry.message());
+            entry.reported = true;
+        }
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+impl std::fmt::Debug for Profiler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Profiler")
+            .field("enabled", &self.is_enabled())
+            .field("entries", &self.entries())
+            .finish()
+    }
+}
+
+/// Scoped timer of one phase, reported when dropped
+#[must_use = "the phase ends when the timer is dropped"]
+pub struct PhaseTimer<'a> {
+    profiler: &'a Profiler,
+    name: String,
+    start: Instant,
+}
+
+impl Drop for PhaseTimer<'_> {
+    fn drop(&mut self) {
+        self.profiler.finish_phase(std::mem::take(&mut self.name), self.start.elapsed());
+    }
+}
+
+/// Peak resident memory of the current process in bytes, where the platform exposes it
+pub fn peak_memory() -> Option<u64> {
+    #[cfg(unix)]
+    {
+        let mut usage = std::mem::MaybeUninit::<libc::rusage>::zeroed();
+        if unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) } != 0 {
+            return None;
+        }
+        let max_rss = u64::try_from(unsafe { usage.assume_init() }.ru_maxrss).ok()?;
+
+        // macOS reports bytes, Linux and the BSDs kilobytes
+        if cfg!(target_os = "macos") {
+            Some(max_rss)
+        } else {
+            Some(max_rss * 1024)
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        None
+    }
+}
+
+fn format_line(phase: &str, elapsed: Duration, cumulative: bool, peak_memory: Option<u64>) -> String {
+    let mut line = format!("[profile] {}: {:.3}s", phase, elapsed.as_secs_f64());
+    if cumulative {
+        line.push_str(" (cumulative)");
+    }
+    if let Some(bytes) = peak_memory {
+        line.push_str(&format!(", peak memory {:.1} MiB", bytes as f64 / (1024.0 * 1024.0)));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::{BufferIo, Verbosity};
+
+    #[test]
+    fn test_phases_are_reported_in_order() {
+        let io = Arc::new(BufferIo::new(Verbosity::Normal));
+        let profiler = Profiler::new(io.clone());
+
+        {
+            let _timer = profiler.phase("solving");
+        }
+        profiler.record("downloading", Duration::from_millis(300));
+        profiler.record("downloading", Duration::from_millis(200));
+        {
+            let _timer = profiler.phase("installing");
+        }
+        profiler.finish();
+
+        let entries = profiler.entries();
+        let phases: Vec<_> = entries.iter().map(|e| e.phase.as_str()).collect();
+        assert_eq!(phases, vec!["solving", "downloading", "installing"]);
+        assert_eq!(entries[1].elapsed, Duration::from_millis(500));
+        assert!(entries[1].cumulative);
+
+        let output = io.output();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("[profile] solving: "));
+        assert!(lines[1].starts_with("[profile] downloading: 0.500s (cumulative)"));
+        assert!(lines[3].starts_with("[profile] total: "));
+    }
+
+    #[test]
+    fn test_disabled_profiler_records_nothing() {
+        let profiler = Profiler::disabled();
+        {
+            let _timer = profiler.phase("solving");
+        }
+        profiler.record("downloading", Duration::from_secs(1));
+        profiler.finish();
+
+        assert!(profiler.entries().is_empty());
+    }
+
+    #[test]
+    fn test_peak_memory() {
+        #[cfg(unix)]
+        assert!(peak_memory().is_some_and(|bytes| bytes > 1024 * 1024));
+        assert_eq!(
+            format_line("solving", Duration::from_millis(1500), false, Some(50 * 1024 * 1024)),
+            "[profile] solving: 1.500s, peak memory 50.0 MiB"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Structured progress of long running operations.
+//!
+//! GUI wrappers and IDE integrations render their own progress from
+//! [`ProgressEvent`]s instead of parsing CLI output. The facade operations
+//! forward them to [`Io::on_progress`](crate::io::Io::on_progress); code driving
+//! the [`Installer`](crate::installer::Installer) directly can subscribe to
+//! the [`ProgressReporter`] of its [`Composer`](crate::Composer), any number
+//! of subscribers receive every event:
+//!
+//! ```no_run
+//! # fn run(composer: &pox_pm::Composer) {
+//! let mut events = composer.progress.subscribe();
+//! tokio::spawn(async move {
+//!     while let Some(event) = events.recv().await {
+//!         println!("{:?}", event);
+//!     }
+//! });
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// Progress reported while an operation runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// Dependencies are being resolved
+    Resolving,
+    /// Package metadata was loaded from the repositories; `total` grows while
+    /// dependencies of the loaded packages are discovered
+    MetadataLoading { done: usize, total: usize },
+    /// The solver started on a pool of package versions
+    Solving { packages: usize },
+    /// Dependencies were resolved into a number of operations
+    Resolved { operations: usize },
+    /// The lock file was written
+    LockFileWritten { path: PathBuf },
+    /// Packages are being installed
+    Installing { packages: usize },
+    /// Bytes of a package archive were downloaded, `total` is unknown without a `Content-Length`
+    Downloading { package: String, bytes: u64, total: Option<u64> },
+    /// A package archive is being extracted
+    Extracting { package: String },
+    /// A package was installed
+    PackageInstalled { name: String, version: String },
+    /// A package was updated
+    PackageUpdated { name: String, from: String, to: String },
+    /// A package was removed
+    PackageRemoved { name: String, version: String },
+    /// The autoloader is being generated
+    GeneratingAutoload,
+    /// The operation finished
+    Finished,
+}
+
+/// Shortest time between two [`ProgressEvent::Downloading`] events of a package
+pub const DOWNLOAD_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Hands the progress of the repository, solver and download layers to its subscribers.
+///
+/// Without a subscriber events are dropped, so library code can report
+/// progress unconditionally. Downloads report every received chunk, their
+/// [`ProgressEvent::Downloading`] events are passed on at most once per
+/// [`DOWNLOAD_PROGRESS_INTERVAL`] and package, and always once complete.
+#[derive(Debug, Default)]
+pub struct ProgressReporter {
+    senders: Mutex<Vec<UnboundedSender<ProgressEvent>>>,
+    last_download: Mutex<HashMap<String, Instant>>,
+}
+
+impl ProgressReporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receive the events reported from now on, until the receiver is dropped
+    pub fn subscribe(&self) -> UnboundedReceiver<ProgressEvent> {
+        let (sender, receiver) = unbounded_channel();
+        self.senders.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Stop sending events, which closes the channels of all subscribers
+    pub fn unsubscribe_all(&self) {
+        self.senders.lock().unwrap().clear();
+    }
+
+    pub fn is_subscribed(&self) -> bool {
+        self.senders.lock().unwrap().iter().any(|s| !s.is_closed())
+    }
+
+    /// Send an event to the subscribers, if any
+    pub fn report(&self, event: ProgressEvent) {
+        let mut senders = self.senders.lock().unwrap();
+        if senders.is_empty() || self.is_throttled(&event) {
+            return;
+        }
+
+        // Receivers that were dropped are forgotten
+        senders.retain(|s| s.send(event.clone()).is_ok());
+    }
+
+    /// Whether a download event follows the previous one of its package too closely
+    fn is_throttled(&self, event: &ProgressEvent) -> bool {
+        let ProgressEvent::Downloading { package, bytes, total } = event else {
+            return false;
+        };
+
+        let mut last_download = self.last_download.lock().unwrap();
+        if total.is_some_and(|total| *bytes >= total) {
+            last_download.remove(package);
+            return false;
+        }
+
+        let now = Instant::now();
+        match last_download.get(package) {
+            Some(last) if now.duration_since(*last) < DOWNLOAD_PROGRESS_INTERVAL => true,
+            _ => {
+                last_download.insert(package.clone(), now);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_report_without_subscriber() {
+        let reporter = ProgressReporter::new();
+        assert!(!reporter.is_subscribed());
+        reporter.report(ProgressEvent::Resolving);
+
+        let mut events = reporter.subscribe();
+        assert!(reporter.is_subscribed());
+        reporter.report(ProgressEvent::Extracting { package: "vendor/a".to_string() });
+        reporter.unsubscribe_all();
+        reporter.report(ProgressEvent::Finished);
+
+        assert_eq!(events.recv().await, Some(ProgressEvent::Extracting { package: "vendor/a".to_string() }));
+        assert_eq!(events.recv().await, None);
+    }
+
+    #[test]
+    fn test_dropped_subscriber() {
+        let reporter = ProgressReporter::new();
+        drop(reporter.subscribe());
+        assert!(!reporter.is_subscribed());
+        reporter.report(ProgressEvent::Resolving);
+    }
+
+    #[tokio::test]
+    async fn test_multiple_subscribers() {
+        let reporter = ProgressReporter::new();
+        let mut first = reporter.subscribe();
+        let second = reporter.subscribe();
+        let mut third = reporter.subscribe();
+        drop(second);
+
+        reporter.report(ProgressEvent::Resolving);
+        assert_eq!(reporter.senders.lock().unwrap().len(), 2);
+        assert_eq!(first.try_recv(), Ok(ProgressEvent::Resolving));
+        assert_eq!(third.try_recv(), Ok(ProgressEvent::Resolving));
+    }
+
+    #[test]
+    fn test_download_events_are_throttled() {
+        let reporter = ProgressReporter::new();
+        let mut events = reporter.subscribe();
+        let downloading = |package: &str, bytes| ProgressEvent::Downloading {
+            package: package.to_string(),
+            bytes,
+            total: Some(300),
+        };
+
+        reporter.report(downloading("vendor/a", 100));
+        reporter.report(downloading("vendor/a", 200));
+        reporter.report(downloading("vendor/b", 100));
+        reporter.report(downloading("vendor/a", 300));
+
+        assert_eq!(events.try_recv(), Ok(downloading("vendor/a", 100)));
+        assert_eq!(events.try_recv(), Ok(downloading("vendor/b", 100)));
+        // The last chunk is never dropped
+        assert_eq!(events.try_recv(), Ok(downloading("vendor/a", 300)));
+        assert!(events.try_recv().is_err());
+    }
+}
@@ -0,0 +1,234 @@
+//! Filter repository - applies the `only`, `exclude` and `canonical` repository options.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use regex::Regex;
+
+use super::traits::{LoadResult, ProviderInfo, Repository, SearchMode, SearchResult};
+use crate::ComposerError;
+use crate::json::RepositoryFilter;
+use crate::package::Package;
+
+/// Wraps a repository to apply the `only`, `exclude` and `canonical` options
+///
+/// Packages whose names are filtered out are invisible, so a filtered
+/// repository never shadows another one for them. A non-canonical repository
+/// never reports names as found, so lower priority repositories are still
+/// asked for the same packages.
+pub struct FilterRepository {
+    repo: Arc<dyn Repository>,
+    only: Option<Regex>,
+    exclude: Option<Regex>,
+    canonical: bool,
+}
+
+impl FilterRepository {
+    /// Wrap a repository with the given filter options
+    pub fn new(repo: Arc<dyn Repository>, filter: &RepositoryFilter) -> crate::Result<Self> {
+        if !filter.only.is_empty() && !filter.exclude.is_empty() {
+            return Err(ComposerError::InvalidRepository {
+                repository: repo.name().to_string(),
+                reason: "only one of \"only\" and \"exclude\" can be specified".to_string(),
+            });
+        }
+
+        Ok(Self {
+            repo,
+            only: names_to_regex(&filter.only),
+            exclude: names_to_regex(&filter.exclude),
+            canonical: filter.canonical,
+        })
+    }
+
+    /// The wrapped repository
+    pub fn repository(&self) -> &Arc<dyn Repository> {
+        &self.repo
+    }
+
+    /// Whether a package name passes the `only` and `exclude` filters
+    pub fn is_allowed(&self, name: &str) -> bool {
+        if let Some(only) = &self.only {
+            return only.is_match(name);
+        }
+        if let Some(exclude) = &self.exclude {
+            return !exclude.is_match(name);
+        }
+        true
+    }
+
+    fn filter_packages(&self, packages: Vec<Arc<Package>>) -> Vec<Arc<Package>> {
+        packages.into_iter().filter(|p| self.is_allowed(&p.name)).collect()
+    }
+}
+
+/// Build one case-insensitive regex out of package name patterns, `*` is a wildcard
+fn names_to_regex(patterns: &[String]) -> Option<Regex> {
+    if patterns.is_empty() {
+        return None;
+    }
+
+    let alternatives: Vec<String> = patterns
+        .iter()
+        .map(|p| regex::escape(p).replace(r"\*", ".*"))
+        .collect();
+    Regex::new(&format!("(?i)^(?:{})$", alternatives.join("|"))).ok()
+}
+
+#[async_trait]
+impl Repository for FilterRepository {
+    fn name(&self) -> &str {
+        self.repo.name()
+    }
+
+    fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+
+    async fn has_package(&self, name: &str) -> bool {
+        self.is_allowed(name) && self.repo.has_package(name).await
+    }
+
+    async fn find_packages(&self, name: &str) -> Vec<Arc<Package>> {
+        if !self.is_allowed(name) {
+            return Vec::new();
+        }
+        self.repo.find_packages(name).await
+    }
+
+    async fn find_package(&self, name: &str, version: &str) -> Option<Arc<Package>> {
+        if !self.is_allowed(name) {
+            return None;
+        }
+        self.repo.find_package(name, version).await
+    }
+
+    async fn find_packages_with_constraint(&self, name: &str, constraint: &str) -> Vec<Arc<Package>> {
+        if !self.is_allowed(name) {
+            return Vec::new();
+        }
+        self.repo.find_packages_with_constraint(name, constraint).await
+    }
+
+    async fn find_matching_packages(&self, name: &str, constraint: &str, include_dev: bool) -> Vec<Arc<Package>> {
+        if !self.is_allowed(name) {
+            return Vec::new();
+        }
+        self.repo.find_matching_packages(name, constraint, include_dev).await
+    }
+
+    async fn get_packages(&self) -> Vec<Arc<Package>> {
+        self.filter_packages(self.repo.get_packages().await)
+    }
+
+    async fn search(&self, query: &str, mode: SearchMode) -> Vec<SearchResult> {
+        self.repo
+            .search(query, mode)
+            .await
+            .into_iter()
+            .filter(|r| self.is_allowed(&r.name))
+            .collect()
+    }
+
+    async fn get_providers(&self, package_name: &str) -> Vec<ProviderInfo> {
+        self.repo
+            .get_providers(package_name)
+            .await
+            .into_iter()
+            .filter(|p| self.is_allowed(&p.name))
+            .collect()
+    }
+
+    async fn load_packages_batch(&self, packages: &[(String, Option<String>)]) -> crate::Result<LoadResult> {
+        let allowed: Vec<(String, Option<String>)> = packages
+            .iter()
+            .filter(|(name, _)| self.is_allowed(name))
+            .cloned()
+            .collect();
+
+        if allowed.is_empty() {
+            return Ok(LoadResult {
+                packages: Vec::new(),
+                names_found: Vec::new(),
+            });
+        }
+
+        let mut result = self.repo.load_packages_batch(&allowed).await?;
+        // Providers and replacers of other names may come along, drop them too
+        result.packages = self.filter_packages(result.packages);
+        if !self.canonical {
+            result.names_found.clear();
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::PackageRepository;
+
+    fn repository() -> Arc<dyn Repository> {
+        let package = |name: &str| {
+            serde_json::json!({
+                "name": name,
+                "version": "1.0.0",
+                "dist": { "url": format!("https://example.com/{}.zip", name), "type": "zip" }
+            })
+        };
+        let packages = serde_json::json!([package("acme/private"), package("acme/other"), package("vendor/public")]);
+        Arc::new(PackageRepository::new(&packages).unwrap())
+    }
+
+    fn filter(only: &[&str], exclude: &[&str], canonical: bool) -> RepositoryFilter {
+        RepositoryFilter {
+            canonical,
+            only: only.iter().map(|s| s.to_string()).collect(),
+            exclude: exclude.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only() {
+        let repo = FilterRepository::new(repository(), &filter(&["acme/*"], &[], true)).unwrap();
+
+        assert!(repo.has_package("acme/private").await);
+        assert!(repo.has_package("ACME/Other").await);
+        assert!(!repo.has_package("vendor/public").await);
+        assert!(repo.find_packages("vendor/public").await.is_empty());
+        assert_eq!(repo.get_packages().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_exclude() {
+        let repo = FilterRepository::new(repository(), &filter(&[], &["acme/private"], true)).unwrap();
+
+        assert!(!repo.has_package("acme/private").await);
+        assert!(repo.has_package("acme/other").await);
+        assert!(repo.find_package("acme/private", "1.0.0").await.is_none());
+        assert_eq!(repo.find_packages_with_constraint("vendor/public", "^1.0").await.len(), 1);
+    }
+
+    #[test]
+    fn test_only_and_exclude_are_exclusive() {
+        assert!(FilterRepository::new(repository(), &filter(&["acme/*"], &["acme/other"], true)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_non_canonical_reports_no_names_found() {
+        let batch = vec![
+            ("acme/private".to_string(), None),
+            ("vendor/public".to_string(), None),
+        ];
+
+        let repo = FilterRepository::new(repository(), &filter(&["acme/*"], &[], true)).unwrap();
+        let result = repo.load_packages_batch(&batch).await.unwrap();
+        assert_eq!(result.packages.len(), 1);
+        assert_eq!(result.names_found, vec!["acme/private".to_string()]);
+
+        let repo = FilterRepository::new(repository(), &filter(&[], &[], false)).unwrap();
+        assert!(!repo.is_canonical());
+        let result = repo.load_packages_batch(&batch).await.unwrap();
+        assert_eq!(result.packages.len(), 2);
+        assert!(result.names_found.is_empty());
+    }
+}
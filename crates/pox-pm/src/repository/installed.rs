@@ -1,11 +1,14 @@
 use std::sync::Arc;
 use std::path::{Path, PathBuf};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
 use indexmap::IndexMap;
 use async_trait::async_trait;
 use tokio::sync::RwLock;
 
 use super::traits::{Repository, WritableRepository, SearchMode, SearchResult, ProviderInfo};
+use crate::ComposerError;
+use crate::installer::InstallerPaths;
 use crate::package::{Package, Source, Dist};
 
 /// Repository for installed packages (vendor/composer/installed.json)
@@ -16,6 +19,14 @@ pub struct InstalledRepository {
     packages: RwLock<HashMap<String, Arc<Package>>>,
     /// Whether the repository has been modified
     dirty: RwLock<bool>,
+    /// Whether dev packages were installed
+    dev_mode: RwLock<bool>,
+    /// Packages only installed for development
+    dev_package_names: RwLock<Vec<String>>,
+    /// Why each package is installed, keyed by lowercase package name
+    install_reasons: RwLock<HashMap<String, InstallReason>>,
+    /// Custom install directories, written as the packages' `install-path`
+    installer_paths: InstallerPaths,
 }
 
 impl InstalledRepository {
@@ -25,38 +36,86 @@ impl InstalledRepository {
             vendor_dir: vendor_dir.into(),
             packages: RwLock::new(HashMap::new()),
             dirty: RwLock::new(false),
+            dev_mode: RwLock::new(true),
+            dev_package_names: RwLock::new(Vec::new()),
+            install_reasons: RwLock::new(HashMap::new()),
+            installer_paths: InstallerPaths::default(),
         }
     }
 
+    /// Record the `install-path` of packages installed into `extra.installer-paths`
+    pub fn with_installer_paths(mut self, installer_paths: InstallerPaths) -> Self {
+        self.installer_paths = installer_paths;
+        self
+    }
+
     /// Get the path to installed.json
     pub fn installed_json_path(&self) -> PathBuf {
         self.vendor_dir.join("composer").join("installed.json")
     }
 
     /// Load packages from installed.json
-    pub async fn load(&self) -> Result<(), String> {
+    pub async fn load(&self) -> crate::Result<()> {
         let path = self.installed_json_path();
         if !path.exists() {
             return Ok(());
         }
 
-        let content = std::fs::read_to_string(&path)
-            .map_err(|e| format!("Failed to read installed.json: {}", e))?;
+        let content = std::fs::read_to_string(&path)?;
 
-        let data: InstalledJson = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse installed.json: {}", e))?;
+        let data: InstalledJson = serde_json::from_str(&content).map_err(|e| ComposerError::InvalidFile {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        })?;
 
         let mut packages = self.packages.write().await;
         packages.clear();
+        let mut install_reasons = self.install_reasons.write().await;
+        install_reasons.clear();
 
         for pkg_data in data.packages {
             let package = Package::from_installed_json(&pkg_data);
-            packages.insert(package.name.clone(), Arc::new(package));
+            if let Some(reason) = pkg_data.install_reason {
+                install_reasons.insert(package.name.to_lowercase(), reason);
+            }
+            packages.insert(package.name.to_lowercase(), Arc::new(package));
         }
 
+        *self.dev_mode.write().await = data.dev;
+        *self.dev_package_names.write().await = data.dev_package_names;
+
         Ok(())
     }
 
+    /// Record whether dev packages are installed and which packages are dev only
+    pub async fn set_dev_mode(&self, dev_mode: bool, dev_package_names: Vec<String>) {
+        *self.dev_mode.write().await = dev_mode;
+        *self.dev_package_names.write().await = dev_package_names;
+    }
+
+    /// Whether the packages were installed with dev dependencies
+    pub async fn is_dev_mode(&self) -> bool {
+        *self.dev_mode.read().await
+    }
+
+    /// Names of the packages only required for development
+    pub async fn dev_package_names(&self) -> Vec<String> {
+        self.dev_package_names.read().await.clone()
+    }
+
+    /// Record why the packages are installed, keyed by package name
+    pub async fn set_install_reasons(&self, install_reasons: HashMap<String, InstallReason>) {
+        *self.install_reasons.write().await = install_reasons
+            .into_iter()
+            .map(|(name, reason)| (name.to_lowercase(), reason))
+            .collect();
+    }
+
+    /// Why a package is installed, `None` for installed.json files written without reasons
+    pub async fn install_reason(&self, name: &str) -> Option<InstallReason> {
+        self.install_reasons.read().await.get(&name.to_lowercase()).cloned()
+    }
+
     /// Get the vendor directory path
     pub fn vendor_dir(&self) -> &Path {
         &self.vendor_dir
@@ -167,11 +226,30 @@ impl WritableRepository for InstalledRepository {
 
     async fn write(&self) -> std::io::Result<()> {
         let packages = self.packages.read().await;
+        let mut packages: Vec<&Arc<Package>> = packages.values().collect();
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut dev_package_names = self.dev_package_names().await;
+        dev_package_names.sort();
+        let install_reasons = self.install_reasons.read().await;
 
         let installed = InstalledJson {
-            packages: packages.values().map(|p| p.to_installed_json()).collect(),
-            dev: true,
-            dev_package_names: vec![],
+            packages: packages
+                .into_iter()
+                .map(|p| {
+                    let mut data = p.to_installed_json();
+                    data.install_reason = install_reasons.get(&p.name.to_lowercase()).cloned();
+                    if !p.is_metapackage() {
+                        data.install_path = Some(format!(
+                            "../{}",
+                            self.installer_paths.relative_to_vendor(&p.name, &p.package_type, &self.vendor_dir)
+                        ));
+                    }
+                    data
+                })
+                .collect(),
+            dev: self.is_dev_mode().await,
+            dev_package_names,
         };
 
         let content = serde_json::to_string_pretty(&installed)
@@ -193,9 +271,9 @@ impl WritableRepository for InstalledRepository {
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 struct InstalledJson {
     packages: Vec<InstalledPackage>,
-    #[serde(default)]
+    #[serde(default = "default_true")]
     dev: bool,
-    #[serde(default)]
+    #[serde(default, rename = "dev-package-names")]
     dev_package_names: Vec<String>,
 }
 
@@ -230,14 +308,130 @@ pub struct InstalledPackage {
     pub license: serde_json::Value,
     #[serde(default)]
     pub time: Option<String>,
-    #[serde(default)]
+    #[serde(default, rename = "install-path")]
     pub install_path: Option<String>,
+    #[serde(default, rename = "install-reason", skip_serializing_if = "Option::is_none")]
+    pub install_reason: Option<InstallReason>,
+}
+
+/// Why a package is installed, written to installed.json as `install-reason`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum InstallReason {
+    /// Required by the root package
+    Require,
+    /// Required by the root package for development
+    RequireDev,
+    /// Required by other installed packages, no longer required by any when `required_by` is empty
+    Dependency {
+        #[serde(default, rename = "required-by")]
+        required_by: Vec<String>,
+        /// Only installed for development
+        #[serde(default)]
+        dev: bool,
+    },
+}
+
+impl InstallReason {
+    /// Whether the root package requires the package itself
+    pub fn is_direct(&self) -> bool {
+        matches!(self, InstallReason::Require | InstallReason::RequireDev)
+    }
+}
+
+impl fmt::Display for InstallReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstallReason::Require => write!(f, "direct"),
+            InstallReason::RequireDev => write!(f, "direct (dev)"),
+            InstallReason::Dependency { required_by, dev } => {
+                if required_by.is_empty() {
+                    write!(f, "unused")?;
+                } else {
+                    write!(f, "dependency of {}", required_by.join(", "))?;
+                }
+                if *dev {
+                    write!(f, " (dev)")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Determine why each of `packages` is installed
+///
+/// `require` and `require_dev` are the requirements of the root package, a
+/// package also counts as required when it provides or replaces a required
+/// name. `dev_package_names` are the packages only installed for development.
+/// The reasons are keyed by lowercase package name.
+pub fn install_reasons<'a>(
+    require: impl IntoIterator<Item = &'a String>,
+    require_dev: impl IntoIterator<Item = &'a String>,
+    packages: &[Package],
+    dev_package_names: &[String],
+) -> HashMap<String, InstallReason> {
+    // Installed packages satisfying a requirement on a name
+    let mut satisfies: HashMap<String, Vec<&str>> = HashMap::new();
+    for package in packages {
+        let names = std::iter::once(&package.name)
+            .chain(package.provide.keys())
+            .chain(package.replace.keys());
+        for name in names {
+            satisfies.entry(name.to_lowercase()).or_default().push(&package.name);
+        }
+    }
+    let satisfying = |names: &mut dyn Iterator<Item = &String>| -> BTreeSet<String> {
+        names
+            .filter_map(|name| satisfies.get(&name.to_lowercase()))
+            .flatten()
+            .map(|name| name.to_lowercase())
+            .collect()
+    };
+
+    let direct = satisfying(&mut require.into_iter());
+    let direct_dev = satisfying(&mut require_dev.into_iter());
+
+    let mut required_by: HashMap<String, BTreeSet<&str>> = HashMap::new();
+    for package in packages {
+        for dependency in satisfying(&mut package.require.keys()) {
+            if dependency != package.name.to_lowercase() {
+                required_by.entry(dependency).or_default().insert(&package.name);
+            }
+        }
+    }
+
+    let dev: BTreeSet<String> = dev_package_names.iter().map(|name| name.to_lowercase()).collect();
+    packages
+        .iter()
+        .map(|package| {
+            let name = package.name.to_lowercase();
+            let reason = if direct.contains(&name) {
+                InstallReason::Require
+            } else if direct_dev.contains(&name) {
+                InstallReason::RequireDev
+            } else {
+                InstallReason::Dependency {
+                    required_by: required_by
+                        .get(&name)
+                        .map(|names| names.iter().map(|n| n.to_string()).collect())
+                        .unwrap_or_default(),
+                    dev: dev.contains(&name),
+                }
+            };
+            (name, reason)
+        })
+        .collect()
 }
 
 fn default_type() -> String {
     "library".to_string()
 }
 
+fn default_true() -> bool {
+    true
+}
+
 fn parse_license_value(value: &serde_json::Value) -> Vec<String> {
     match value {
         serde_json::Value::Array(arr) => arr
@@ -285,6 +479,7 @@ impl Package {
             shasum: d.shasum.clone(),
             sha256: None,
             mirrors: None,
+            signature: None,
             transport_options: None,
         });
 
@@ -338,6 +533,122 @@ impl Package {
             license: serde_json::Value::Null,
             time: self.time.map(|t| t.to_rfc3339()),
             install_path: None,
+            install_reason: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_write_and_load_dev_mode() {
+        let temp = TempDir::new().unwrap();
+        let mut repository = InstalledRepository::new(temp.path());
+        repository.add_package(Package::new("psr/log", "3.0.0.0")).await;
+        repository.add_package(Package::new("phpunit/phpunit", "11.0.0.0")).await;
+        repository.set_dev_mode(true, vec!["phpunit/phpunit".to_string()]).await;
+        repository.write().await.unwrap();
+
+        let content = std::fs::read_to_string(repository.installed_json_path()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(json["dev"], true);
+        assert_eq!(json["dev-package-names"], serde_json::json!(["phpunit/phpunit"]));
+        assert_eq!(json["packages"][0]["name"], "phpunit/phpunit");
+        assert_eq!(json["packages"][1]["install-path"], "../psr/log");
+
+        let loaded = InstalledRepository::new(temp.path());
+        loaded.load().await.unwrap();
+        assert!(loaded.is_dev_mode().await);
+        assert_eq!(loaded.dev_package_names().await, vec!["phpunit/phpunit"]);
+        assert!(loaded.has_package("PSR/Log").await);
+    }
+
+    #[tokio::test]
+    async fn test_write_without_dev_packages() {
+        let temp = TempDir::new().unwrap();
+        let mut repository = InstalledRepository::new(temp.path());
+        repository.add_package(Package::new("psr/log", "3.0.0.0")).await;
+        repository.set_dev_mode(false, Vec::new()).await;
+        repository.write().await.unwrap();
+
+        let loaded = InstalledRepository::new(temp.path());
+        loaded.load().await.unwrap();
+        assert!(!loaded.is_dev_mode().await);
+        assert!(loaded.dev_package_names().await.is_empty());
+        assert_eq!(loaded.count().await, 1);
+    }
+
+    fn package(name: &str, require: &[&str]) -> Package {
+        let mut package = Package::new(name, "1.0.0.0");
+        for dependency in require {
+            package.require.insert(dependency.to_string(), "*".to_string());
         }
+        package
+    }
+
+    #[test]
+    fn test_install_reasons() {
+        let mut logger = package("monolog/monolog", &["psr/log"]);
+        logger.provide.insert("psr/log-implementation".to_string(), "3.0.0".to_string());
+        let packages = vec![
+            package("symfony/console", &["psr/log", "php"]),
+            logger,
+            package("psr/log", &[]),
+            package("phpunit/phpunit", &["sebastian/diff"]),
+            package("sebastian/diff", &[]),
+            package("old/leftover", &[]),
+        ];
+        let require = ["symfony/console".to_string(), "psr/log-implementation".to_string()];
+        let require_dev = ["phpunit/phpunit".to_string()];
+        let dev = ["phpunit/phpunit".to_string(), "sebastian/diff".to_string()];
+
+        let reasons = install_reasons(&require, &require_dev, &packages, &dev);
+        assert_eq!(reasons["symfony/console"], InstallReason::Require);
+        assert_eq!(reasons["monolog/monolog"], InstallReason::Require);
+        assert_eq!(reasons["phpunit/phpunit"], InstallReason::RequireDev);
+        assert_eq!(
+            reasons["psr/log"],
+            InstallReason::Dependency {
+                required_by: vec!["monolog/monolog".to_string(), "symfony/console".to_string()],
+                dev: false,
+            }
+        );
+        assert_eq!(reasons["psr/log"].to_string(), "dependency of monolog/monolog, symfony/console");
+        assert_eq!(reasons["sebastian/diff"].to_string(), "dependency of phpunit/phpunit (dev)");
+        assert_eq!(reasons["old/leftover"].to_string(), "unused");
+    }
+
+    #[tokio::test]
+    async fn test_write_and_load_install_reasons() {
+        let temp = TempDir::new().unwrap();
+        let mut repository = InstalledRepository::new(temp.path());
+        repository.add_package(Package::new("psr/log", "3.0.0.0")).await;
+        repository.add_package(Package::new("old/leftover", "1.0.0.0")).await;
+        repository
+            .set_install_reasons(HashMap::from([(
+                "PSR/Log".to_string(),
+                InstallReason::Dependency { required_by: vec!["monolog/monolog".to_string()], dev: false },
+            )]))
+            .await;
+        repository.write().await.unwrap();
+
+        let content = std::fs::read_to_string(repository.installed_json_path()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert!(json["packages"][0].get("install-reason").is_none());
+        assert_eq!(
+            json["packages"][1]["install-reason"],
+            serde_json::json!({"type": "dependency", "required-by": ["monolog/monolog"], "dev": false})
+        );
+
+        let loaded = InstalledRepository::new(temp.path());
+        loaded.load().await.unwrap();
+        assert!(loaded.install_reason("old/leftover").await.is_none());
+        assert_eq!(
+            loaded.install_reason("psr/log").await,
+            Some(InstallReason::Dependency { required_by: vec!["monolog/monolog".to_string()], dev: false })
+        );
     }
 }
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use super::traits::{Repository, RepositoryConfig, RepositoryType, SearchMode, SearchResult};
@@ -6,13 +7,23 @@ use super::PlatformRepository;
 use super::path::{PathRepository, PathRepositoryOptions};
 use super::package::PackageRepository;
 use super::artifact::ArtifactRepository;
+use super::filter::FilterRepository;
 use super::vcs::{VcsRepository, VcsType};
+use crate::config::AuthConfig;
+use crate::http::ProxyConfig;
+use crate::json::RepositoryFilter;
 use crate::package::Package;
 
 /// Manages multiple repositories with priority ordering
 pub struct RepositoryManager {
     /// Repositories in priority order (first = highest priority)
     repositories: Vec<Arc<dyn Repository>>,
+    /// Authentication for composer repositories added from composer.json
+    auth: Option<Arc<AuthConfig>>,
+    /// Cache directory for composer repositories added from composer.json
+    cache_dir: Option<PathBuf>,
+    /// Proxy settings for repositories added from composer.json
+    proxy: ProxyConfig,
 }
 
 impl RepositoryManager {
@@ -20,9 +31,27 @@ impl RepositoryManager {
     pub fn new() -> Self {
         Self {
             repositories: Vec::new(),
+            auth: None,
+            cache_dir: None,
+            proxy: ProxyConfig::default(),
         }
     }
 
+    /// Authenticate composer repositories added after this call
+    pub fn set_auth(&mut self, auth: Arc<AuthConfig>) {
+        self.auth = Some(auth);
+    }
+
+    /// Cache the metadata of composer repositories added after this call
+    pub fn set_cache_dir(&mut self, cache_dir: PathBuf) {
+        self.cache_dir = Some(cache_dir);
+    }
+
+    /// Use the proxy settings for repositories added after this call
+    pub fn set_proxy(&mut self, proxy: ProxyConfig) {
+        self.proxy = proxy;
+    }
+
     /// Add a repository (will be added with lowest priority)
     pub fn add_repository(&mut self, repo: Arc<dyn Repository>) {
         self.repositories.push(repo);
@@ -39,18 +68,27 @@ impl RepositoryManager {
     }
 
     /// Find packages by name across all repositories
+    ///
+    /// Repositories are asked in priority order, the first canonical
+    /// repository that has the package hides it in all later ones.
     pub async fn find_packages(&self, name: &str) -> Vec<Arc<Package>> {
         let mut packages = Vec::new();
         let mut seen = std::collections::HashSet::new();
 
         for repo in &self.repositories {
-            for pkg in repo.find_packages(name).await {
+            let found = repo.find_packages(name).await;
+            let is_found = !found.is_empty();
+            for pkg in found {
                 let key = format!("{}@{}", pkg.name, pkg.version);
                 if !seen.contains(&key) {
                     seen.insert(key);
                     packages.push(pkg);
                 }
             }
+
+            if is_found && repo.is_canonical() {
+                break;
+            }
         }
 
         packages
@@ -62,23 +100,40 @@ impl RepositoryManager {
             if let Some(pkg) = repo.find_package(name, version).await {
                 return Some(pkg);
             }
+            if repo.is_canonical() && repo.has_package(name).await {
+                return None;
+            }
         }
         None
     }
 
     /// Find packages matching a version constraint across all repositories
+    ///
+    /// A canonical repository that has the package but no matching version
+    /// still hides it in later repositories.
     pub async fn find_packages_with_constraint(&self, name: &str, constraint: &str) -> Vec<Arc<Package>> {
+        self.find_matching_packages(name, constraint, false).await
+    }
+
+    /// Find packages matching a version constraint, with dev versions if `include_dev` is set
+    pub async fn find_matching_packages(&self, name: &str, constraint: &str, include_dev: bool) -> Vec<Arc<Package>> {
         let mut packages = Vec::new();
         let mut seen = std::collections::HashSet::new();
 
         for repo in &self.repositories {
-            for pkg in repo.find_packages_with_constraint(name, constraint).await {
+            let found = repo.find_matching_packages(name, constraint, include_dev).await;
+            let is_found = !found.is_empty();
+            for pkg in found {
                 let key = format!("{}@{}", pkg.name, pkg.version);
                 if !seen.contains(&key) {
                     seen.insert(key);
                     packages.push(pkg);
                 }
             }
+
+            if repo.is_canonical() && (is_found || repo.has_package(name).await) {
+                break;
+            }
         }
 
         packages
@@ -112,7 +167,7 @@ impl RepositoryManager {
     }
 
     /// Create a repository manager from configuration
-    pub async fn from_configs(configs: Vec<RepositoryConfig>) -> Result<Self, String> {
+    pub async fn from_configs(configs: Vec<RepositoryConfig>) -> crate::Result<Self> {
         let mut manager = Self::new();
 
         for config in configs {
@@ -152,7 +207,7 @@ impl RepositoryManager {
                         match PackageRepository::new(package_data) {
                             Ok(repo) => Arc::new(repo),
                             Err(e) => {
-                                eprintln!("Warning: Failed to create package repository: {}", e);
+                                eprintln!("Warning: {}", e);
                                 continue;
                             }
                         }
@@ -163,6 +218,13 @@ impl RepositoryManager {
                 }
             };
 
+            let filter = RepositoryFilter {
+                canonical: config.options.canonical,
+                only: config.options.only.clone(),
+                exclude: config.options.exclude.clone(),
+            };
+            let repo = with_filter(repo, &filter)?;
+
             manager.add_repository(repo);
         }
 
@@ -192,9 +254,17 @@ impl RepositoryManager {
         let result: Option<Arc<dyn Repository>> = match repo {
             JsonRepo::Composer { url, .. } => {
                 let name = extract_repo_name(url);
-                Some(Arc::new(ComposerRepository::new(name, url)))
+                let mut repo = ComposerRepository::new(name, url);
+                if let Some(auth) = &self.auth {
+                    repo.set_auth_shared(auth.clone());
+                }
+                if let Some(cache_dir) = &self.cache_dir {
+                    repo.set_cache_dir(cache_dir.clone());
+                }
+                repo.set_proxy(&self.proxy);
+                Some(Arc::new(repo))
             }
-            JsonRepo::Path { url, options } => {
+            JsonRepo::Path { url, options, .. } => {
                 let path_options = PathRepositoryOptions {
                     symlink: options.symlink,
                     relative: false,
@@ -203,31 +273,31 @@ impl RepositoryManager {
                 };
                 Some(Arc::new(PathRepository::new(url, path_options)))
             }
-            JsonRepo::Package { package } => {
+            JsonRepo::Package { package, .. } => {
                 match PackageRepository::new(package) {
                     Ok(repo) => Some(Arc::new(repo)),
                     Err(e) => {
-                        eprintln!("Warning: Failed to create package repository: {}", e);
+                        eprintln!("Warning: {}", e);
                         None
                     }
                 }
             }
-            JsonRepo::Vcs { url } => {
-                Some(Arc::new(VcsRepository::new(url, VcsType::Vcs)))
+            JsonRepo::Vcs { url, .. } => {
+                Some(Arc::new(VcsRepository::new(url, VcsType::Vcs).with_proxy(self.proxy.clone())))
             }
-            JsonRepo::Git { url } => {
-                Some(Arc::new(VcsRepository::new(url, VcsType::Git)))
+            JsonRepo::Git { url, .. } => {
+                Some(Arc::new(VcsRepository::new(url, VcsType::Git).with_proxy(self.proxy.clone())))
             }
-            JsonRepo::GitHub { url } => {
-                Some(Arc::new(VcsRepository::new(url, VcsType::GitHub)))
+            JsonRepo::GitHub { url, .. } => {
+                Some(Arc::new(VcsRepository::new(url, VcsType::GitHub).with_proxy(self.proxy.clone())))
             }
-            JsonRepo::GitLab { url } => {
-                Some(Arc::new(VcsRepository::new(url, VcsType::GitLab)))
+            JsonRepo::GitLab { url, .. } => {
+                Some(Arc::new(VcsRepository::new(url, VcsType::GitLab).with_proxy(self.proxy.clone())))
             }
-            JsonRepo::Bitbucket { url } => {
-                Some(Arc::new(VcsRepository::new(url, VcsType::Bitbucket)))
+            JsonRepo::Bitbucket { url, .. } => {
+                Some(Arc::new(VcsRepository::new(url, VcsType::Bitbucket).with_proxy(self.proxy.clone())))
             }
-            JsonRepo::Artifact { url } => {
+            JsonRepo::Artifact { url, .. } => {
                 Some(Arc::new(ArtifactRepository::new(url)))
             }
             JsonRepo::Disabled(_) => {
@@ -236,8 +306,13 @@ impl RepositoryManager {
             }
         };
 
-        if let Some(repo) = result {
-            self.add_repository(repo);
+        let (Some(repo), Some(filter)) = (result, repo.filter()) else {
+            return;
+        };
+
+        match with_filter(repo, filter) {
+            Ok(repo) => self.add_repository(repo),
+            Err(e) => eprintln!("Warning: {}", e),
         }
     }
 
@@ -249,6 +324,14 @@ impl RepositoryManager {
     }
 }
 
+/// Wrap a repository in a [`FilterRepository`] unless the filter options are the defaults
+fn with_filter(repo: Arc<dyn Repository>, filter: &RepositoryFilter) -> crate::Result<Arc<dyn Repository>> {
+    if filter.is_empty() {
+        return Ok(repo);
+    }
+    Ok(Arc::new(FilterRepository::new(repo, filter)?))
+}
+
 /// Extract a repository name from a URL
 fn extract_repo_name(url: &str) -> String {
     // Try to extract host from URL
@@ -276,3 +359,100 @@ impl Default for RepositoryManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package_repository(packages: &[(&str, &str)]) -> serde_json::Value {
+        let packages: Vec<serde_json::Value> = packages
+            .iter()
+            .map(|(name, version)| {
+                serde_json::json!({
+                    "name": name,
+                    "version": version,
+                    "dist": { "url": format!("https://example.com/{}.zip", name), "type": "zip" }
+                })
+            })
+            .collect();
+        serde_json::json!({ "type": "package", "package": packages })
+    }
+
+    fn manager(repositories: Vec<serde_json::Value>) -> RepositoryManager {
+        let repositories: Vec<crate::json::Repository> =
+            serde_json::from_value(serde_json::Value::Array(repositories)).unwrap();
+        let mut manager = RepositoryManager::new();
+        manager.add_from_json_repositories(&repositories);
+        manager
+    }
+
+    fn versions(packages: &[Arc<Package>]) -> Vec<&str> {
+        packages.iter().map(|p| p.version.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_canonical_repository_hides_later_repositories() {
+        let manager = manager(vec![
+            package_repository(&[("acme/lib", "1.0.0")]),
+            package_repository(&[("acme/lib", "2.0.0"), ("acme/other", "1.0.0")]),
+        ]);
+
+        assert_eq!(versions(&manager.find_packages("acme/lib").await), vec!["1.0.0"]);
+        assert_eq!(
+            versions(&manager.find_packages_with_constraint("acme/lib", "^2.0").await),
+            vec!["1.0.0"]
+        );
+        assert!(manager.find_package("acme/lib", "2.0.0").await.is_none());
+        assert_eq!(manager.find_packages("acme/other").await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_non_canonical_repository_is_merged() {
+        let mut first = package_repository(&[("acme/lib", "1.0.0")]);
+        first["canonical"] = serde_json::json!(false);
+        let manager = manager(vec![first, package_repository(&[("acme/lib", "2.0.0")])]);
+
+        assert_eq!(versions(&manager.find_packages("acme/lib").await), vec!["1.0.0", "2.0.0"]);
+        assert_eq!(
+            versions(&manager.find_packages_with_constraint("acme/lib", "^2.0").await),
+            vec!["1.0.0", "2.0.0"]
+        );
+        assert!(manager.find_package("acme/lib", "2.0.0").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_filtered_packages_do_not_shadow() {
+        let mut private = package_repository(&[("acme/lib", "1.0.0"), ("vendor/lib", "1.0.0")]);
+        private["only"] = serde_json::json!(["acme/*"]);
+        let mut excluding = package_repository(&[("vendor/lib", "2.0.0"), ("vendor/other", "1.0.0")]);
+        excluding["exclude"] = serde_json::json!(["vendor/other"]);
+        let manager = manager(vec![
+            private,
+            excluding,
+            package_repository(&[("acme/lib", "3.0.0"), ("vendor/other", "3.0.0")]),
+        ]);
+
+        assert_eq!(versions(&manager.find_packages("acme/lib").await), vec!["1.0.0"]);
+        assert_eq!(versions(&manager.find_packages("vendor/lib").await), vec!["2.0.0"]);
+        assert_eq!(versions(&manager.find_packages("vendor/other").await), vec!["3.0.0"]);
+    }
+
+    #[test]
+    fn test_filter_options_roundtrip() {
+        let json = serde_json::json!({
+            "type": "composer",
+            "url": "https://satis.example.com",
+            "canonical": false,
+            "only": ["acme/*"]
+        });
+        let repository: crate::json::Repository = serde_json::from_value(json.clone()).unwrap();
+        let filter = repository.filter().unwrap();
+        assert!(!filter.canonical);
+        assert_eq!(filter.only, vec!["acme/*".to_string()]);
+        assert_eq!(serde_json::to_value(&repository).unwrap(), json);
+
+        let repository: crate::json::Repository =
+            serde_json::from_value(serde_json::json!({ "type": "vcs", "url": "https://example.com/repo.git" })).unwrap();
+        assert!(repository.filter().unwrap().is_empty());
+    }
+}
@@ -5,6 +5,7 @@ use std::sync::Arc;
 use async_trait::async_trait;
 
 use super::traits::{Repository, SearchMode, SearchResult, ProviderInfo};
+use crate::ComposerError;
 use crate::package::{Package, Dist, Source, Autoload, AutoloadPath};
 
 /// Package repository - provides packages from inline definitions
@@ -57,7 +58,14 @@ impl PackageRepository {
     ///
     /// # Arguments
     /// * `package_config` - Either a single package object or an array of package objects
-    pub fn new(package_config: &serde_json::Value) -> Result<Self, String> {
+    pub fn new(package_config: &serde_json::Value) -> crate::Result<Self> {
+        Self::load(package_config).map_err(|reason| ComposerError::InvalidRepository {
+            repository: "package".to_string(),
+            reason,
+        })
+    }
+
+    fn load(package_config: &serde_json::Value) -> Result<Self, String> {
         let mut packages = Vec::new();
 
         // Handle both single package and array of packages
@@ -509,7 +517,7 @@ mod tests {
 
         let result = PackageRepository::new(&config);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("name"));
+        assert!(result.unwrap_err().to_string().contains("name"));
     }
 
     #[test]
@@ -521,7 +529,7 @@ mod tests {
 
         let result = PackageRepository::new(&config);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("version"));
+        assert!(result.unwrap_err().to_string().contains("version"));
     }
 
     #[test]
@@ -533,7 +541,7 @@ mod tests {
 
         let result = PackageRepository::new(&config);
         assert!(result.is_err());
-        let err = result.unwrap_err();
+        let err = result.unwrap_err().to_string();
         assert!(err.contains("dist") || err.contains("source"));
     }
 
@@ -549,7 +557,7 @@ mod tests {
 
         let result = PackageRepository::new(&config);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("type"));
+        assert!(result.unwrap_err().to_string().contains("type"));
     }
 
     #[test]
@@ -565,7 +573,7 @@ mod tests {
 
         let result = PackageRepository::new(&config);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("reference"));
+        assert!(result.unwrap_err().to_string().contains("reference"));
     }
 
     #[tokio::test]
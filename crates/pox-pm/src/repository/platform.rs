@@ -28,9 +28,13 @@ impl PlatformRepository {
 
     /// Create with platform overrides
     pub fn with_overrides(overrides: HashMap<String, String>) -> Self {
+        let overrides: HashMap<String, String> = overrides
+            .into_iter()
+            .map(|(name, version)| (name.to_lowercase(), version))
+            .collect();
         let disabled: Vec<String> = overrides
             .iter()
-            .filter(|(_, v)| *v == "false" || v.is_empty())
+            .filter(|(_, v)| is_disabled_override(v))
             .map(|(k, _)| k.clone())
             .collect();
 
@@ -41,6 +45,52 @@ impl PlatformRepository {
         }
     }
 
+    /// Use the detected platform packages with the overrides applied
+    ///
+    /// An override replaces the version of a detected package or adds a package
+    /// that was not detected, a `false` override removes the package.
+    pub fn set_detected(&mut self, packages: &[Package]) {
+        self.packages = packages
+            .iter()
+            .filter(|p| !self.overrides.contains_key(&p.name.to_lowercase()))
+            .map(|p| Arc::new(p.clone()))
+            .collect();
+
+        let mut names: Vec<&String> = self.overrides.keys().filter(|name| !self.disabled.contains(name)).collect();
+        names.sort();
+        for name in names {
+            let version = &self.overrides[name];
+            let mut pkg = Package::new(name, normalize_version(version));
+            pkg.pretty_version = Some(version.clone());
+            pkg.package_type = "platform".to_string();
+            pkg.stability = Some(Stability::Stable);
+            pkg.description = Some("Package overridden via config.platform".to_string());
+            self.packages.push(Arc::new(pkg));
+        }
+    }
+
+    /// Overrides that differ from the detected platform, as `(name, override, detected version)`
+    pub fn override_differences(&self, detected: &[Package]) -> Vec<(String, String, Option<String>)> {
+        let mut differences: Vec<_> = self
+            .overrides
+            .iter()
+            .filter_map(|(name, version)| {
+                let pkg = detected.iter().find(|p| p.name.eq_ignore_ascii_case(name));
+                let detected_version = pkg.map(|p| p.pretty_version.clone().unwrap_or_else(|| p.version.clone()));
+
+                let disabled = self.disabled.contains(name);
+                let same = match pkg {
+                    Some(pkg) => !disabled && normalize_version(version) == normalize_version(&pkg.version),
+                    None => disabled,
+                };
+                (!same).then(|| (name.clone(), version.clone(), detected_version))
+            })
+            .collect();
+
+        differences.sort();
+        differences
+    }
+
     /// Detect platform packages from the current PHP installation
     pub fn detect(&mut self) {
         // In a real implementation, this would:
@@ -111,6 +161,60 @@ impl PlatformRepository {
     }
 }
 
+/// Libraries PHP can be linked against, exposed as `lib-<name>` packages
+pub const PLATFORM_LIBRARIES: &[(&str, &str)] = &[
+    ("curl", "The curl library"),
+    ("icu", "The ICU unicode and globalization support library"),
+    ("libxml", "The libxml2 library"),
+    ("openssl", "The OpenSSL library"),
+    ("pcre", "The PCRE library"),
+    ("zlib", "The zlib library"),
+];
+
+/// The version of a library from the text it reports, like `OpenSSL 3.0.2 15 Mar 2022`,
+/// `10.42 2022-12-11` or `1.1.1k`.
+///
+/// OpenSSL patch letters become a fourth version part, `1.1.1k` is `1.1.1.11`.
+pub fn parse_library_version(version_text: &str) -> Option<String> {
+    let token = version_text
+        .split_whitespace()
+        .find(|t| t.starts_with(|c: char| c.is_ascii_digit()))?;
+
+    let numeric_end = token
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(token.len());
+    let version = token[..numeric_end].trim_end_matches('.');
+    if version.is_empty() {
+        return None;
+    }
+
+    let suffix = &token[numeric_end..];
+    let letters: String = suffix.chars().take_while(|c| c.is_ascii_lowercase()).collect();
+    if !letters.is_empty() && letters.len() <= 2 && version.split('.').count() == 3 {
+        let patch = letters.bytes().fold(0u32, |acc, b| acc * 26 + u32::from(b - b'a' + 1));
+        return Some(format!("{}.{}", version, patch));
+    }
+
+    Some(version.to_string())
+}
+
+/// A `lib-<name>` platform package, `None` if the version text has no version
+pub fn library_package(name: &str, version_text: &str) -> Option<Package> {
+    let version = parse_library_version(version_text)?;
+    let description = PLATFORM_LIBRARIES
+        .iter()
+        .find(|(lib, _)| *lib == name)
+        .map(|(_, desc)| desc.to_string())
+        .unwrap_or_else(|| format!("The {} library", name));
+
+    let mut pkg = Package::new(format!("lib-{}", name), normalize_version(&version));
+    pkg.pretty_version = Some(version);
+    pkg.package_type = "platform".to_string();
+    pkg.stability = Some(Stability::Stable);
+    pkg.description = Some(description);
+    Some(pkg)
+}
+
 /// Normalize a version string to Composer format
 fn normalize_version(version: &str) -> String {
     // Simple normalization - just ensure 4 parts
@@ -123,6 +227,28 @@ fn normalize_version(version: &str) -> String {
     }
 }
 
+/// Whether a `config.platform` value removes the package instead of overriding its version
+fn is_disabled_override(version: &str) -> bool {
+    version.is_empty() || version == "false"
+}
+
+/// The detected platform packages with the `config.platform` overrides applied.
+///
+/// See [`PlatformRepository::set_detected`].
+pub fn apply_platform_overrides(packages: &[Package], overrides: &HashMap<String, String>) -> Vec<Package> {
+    let mut repo = PlatformRepository::with_overrides(overrides.clone());
+    repo.set_detected(packages);
+    repo.packages.iter().map(|p| p.as_ref().clone()).collect()
+}
+
+/// Overrides that differ from the detected platform, as `(name, override, detected version)`
+pub fn platform_override_differences(
+    packages: &[Package],
+    overrides: &HashMap<String, String>,
+) -> Vec<(String, String, Option<String>)> {
+    PlatformRepository::with_overrides(overrides.clone()).override_differences(packages)
+}
+
 impl Default for PlatformRepository {
     fn default() -> Self {
         let mut repo = Self::new();
@@ -225,3 +351,71 @@ impl Repository for PlatformRepository {
         self.packages.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detected() -> Vec<Package> {
+        let mut php = Package::new("php", "8.4.2");
+        php.pretty_version = Some("8.4.2".to_string());
+        vec![php, Package::new("ext-json", "8.4.2"), Package::new("ext-intl", "8.4.2")]
+    }
+
+    fn overrides(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_apply_platform_overrides() {
+        let packages = apply_platform_overrides(
+            &detected(),
+            &overrides(&[("php", "8.1.0"), ("ext-intl", "false"), ("ext-redis", "6.0.2")]),
+        );
+
+        let versions: Vec<(&str, &str)> = packages.iter().map(|p| (p.name.as_str(), p.version.as_str())).collect();
+        assert_eq!(
+            versions,
+            vec![("ext-json", "8.4.2"), ("ext-redis", "6.0.2.0"), ("php", "8.1.0.0")]
+        );
+        assert_eq!(packages[2].pretty_version.as_deref(), Some("8.1.0"));
+    }
+
+    #[test]
+    fn test_parse_library_version() {
+        assert_eq!(parse_library_version("OpenSSL 3.0.2 15 Mar 2022").as_deref(), Some("3.0.2"));
+        assert_eq!(parse_library_version("OpenSSL 1.1.1k  25 Mar 2021").as_deref(), Some("1.1.1.11"));
+        assert_eq!(parse_library_version("LibreSSL 3.3.6").as_deref(), Some("3.3.6"));
+        assert_eq!(parse_library_version("10.42 2022-12-11").as_deref(), Some("10.42"));
+        assert_eq!(parse_library_version("1.2.13.1-motley").as_deref(), Some("1.2.13.1"));
+        assert_eq!(parse_library_version("74.2").as_deref(), Some("74.2"));
+        assert_eq!(parse_library_version("unknown"), None);
+    }
+
+    #[test]
+    fn test_library_package() {
+        let pkg = library_package("openssl", "OpenSSL 3.0.2 15 Mar 2022").unwrap();
+        assert_eq!(pkg.name, "lib-openssl");
+        assert_eq!(pkg.version, "3.0.2.0");
+        assert_eq!(pkg.description.as_deref(), Some("The OpenSSL library"));
+        assert_eq!(pkg.package_type, "platform");
+        assert!(library_package("pcre", "").is_none());
+    }
+
+    #[test]
+    fn test_platform_override_differences() {
+        let differences = platform_override_differences(
+            &detected(),
+            &overrides(&[("php", "8.1.0"), ("ext-json", "8.4.2"), ("ext-redis", "6.0.2"), ("ext-intl", "false")]),
+        );
+
+        assert_eq!(
+            differences,
+            vec![
+                ("ext-intl".to_string(), "false".to_string(), Some("8.4.2".to_string())),
+                ("ext-redis".to_string(), "6.0.2".to_string(), None),
+                ("php".to_string(), "8.1.0".to_string(), Some("8.4.2".to_string())),
+            ]
+        );
+    }
+}
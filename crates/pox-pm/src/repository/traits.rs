@@ -54,6 +54,11 @@ pub trait Repository: Send + Sync {
     /// Get a unique name for this repository
     fn name(&self) -> &str;
 
+    /// Whether packages found here hide the same packages in lower priority repositories
+    fn is_canonical(&self) -> bool {
+        true
+    }
+
     /// Check if the repository contains a package with the given name
     async fn has_package(&self, name: &str) -> bool;
 
@@ -70,6 +75,19 @@ pub trait Repository: Send + Sync {
         constraint: &str,
     ) -> Vec<Arc<Package>>;
 
+    /// Find packages matching a version constraint, with dev versions if `include_dev` is set
+    ///
+    /// Repositories keeping dev versions apart, like the `~dev` metadata of
+    /// Composer v2 repositories, only load them when asked to.
+    async fn find_matching_packages(
+        &self,
+        name: &str,
+        constraint: &str,
+        _include_dev: bool,
+    ) -> Vec<Arc<Package>> {
+        self.find_packages_with_constraint(name, constraint).await
+    }
+
     /// Get all packages in the repository
     async fn get_packages(&self) -> Vec<Arc<Package>>;
 
@@ -90,10 +108,11 @@ pub trait Repository: Send + Sync {
     /// as it allows repositories to optimize fetching (e.g., parallel HTTP requests).
     ///
     /// Returns packages found and names that were definitively found (to skip lower-priority repos).
+    /// Fails when the repository could not be queried, a package it does not have is not an error.
     async fn load_packages_batch(
         &self,
         packages: &[(String, Option<String>)], // (name, optional constraint)
-    ) -> LoadResult {
+    ) -> crate::Result<LoadResult> {
         let mut result = LoadResult {
             packages: Vec::new(),
             names_found: Vec::new(),
@@ -112,7 +131,7 @@ pub trait Repository: Send + Sync {
             }
         }
 
-        result
+        Ok(result)
     }
 }
 
@@ -169,7 +188,7 @@ pub enum RepositoryType {
 }
 
 /// Repository options
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct RepositoryOptions {
     /// Use symlinks for path repositories
     pub symlink: Option<bool>,
@@ -189,6 +208,21 @@ pub struct RepositoryOptions {
     pub only: Vec<String>,
 }
 
+impl Default for RepositoryOptions {
+    fn default() -> Self {
+        Self {
+            symlink: None,
+            relative: false,
+            reference: None,
+            versions: std::collections::HashMap::new(),
+            ssl_verify: None,
+            canonical: true,
+            exclude: Vec::new(),
+            only: Vec::new(),
+        }
+    }
+}
+
 /// Result of loading packages from a repository
 #[derive(Debug)]
 pub struct LoadResult {
@@ -4,6 +4,7 @@ use std::collections::HashMap;
 
 use super::driver::{VcsDriver, VcsDriverError, VcsInfo};
 use crate::config::AuthConfig;
+use crate::http::ProxyConfig;
 
 /// Bitbucket driver for Bitbucket repositories
 pub struct BitbucketDriver {
@@ -17,6 +18,8 @@ pub struct BitbucketDriver {
     oauth_token: Option<String>,
     /// App password (optional, alternative to OAuth)
     app_password: Option<(String, String)>, // (username, password)
+    /// Proxy settings for API requests
+    proxy: ProxyConfig,
 }
 
 impl BitbucketDriver {
@@ -33,6 +36,7 @@ impl BitbucketDriver {
             repo_slug,
             oauth_token: None,
             app_password: None,
+            proxy: ProxyConfig::default(),
         })
     }
 
@@ -49,6 +53,12 @@ impl BitbucketDriver {
         self
     }
 
+    /// Send API requests through the proxy settings
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
     /// Configure authentication from AuthConfig
     pub fn with_auth(mut self, auth: &AuthConfig) -> Self {
         // Try to get OAuth credentials for bitbucket.org
@@ -65,7 +75,7 @@ impl BitbucketDriver {
             self.workspace, self.repo_slug, endpoint
         );
 
-        let client = reqwest::blocking::Client::new();
+        let client = crate::http::blocking_client(&self.proxy);
         let mut request = client.get(&url);
 
         // Add authentication if available
@@ -109,7 +119,7 @@ impl BitbucketDriver {
             self.workspace, self.repo_slug, ref_name, file
         );
 
-        let client = reqwest::blocking::Client::new();
+        let client = crate::http::blocking_client(&self.proxy);
         let mut request = client.get(&url);
 
         // Add authentication if available
@@ -165,7 +175,7 @@ impl VcsDriver for BitbucketDriver {
         ));
 
         while let Some(url) = next_url.take() {
-            let client = reqwest::blocking::Client::new();
+            let client = crate::http::blocking_client(&self.proxy);
             let mut request = client.get(&url);
 
             // Add authentication if available
@@ -217,7 +227,7 @@ impl VcsDriver for BitbucketDriver {
         ));
 
         while let Some(url) = next_url.take() {
-            let client = reqwest::blocking::Client::new();
+            let client = crate::http::blocking_client(&self.proxy);
             let mut request = client.get(&url);
 
             // Add authentication if available
@@ -4,6 +4,7 @@ use std::collections::HashMap;
 
 use super::driver::{VcsDriver, VcsDriverError, VcsInfo, parse_github_url};
 use crate::config::AuthConfig;
+use crate::http::ProxyConfig;
 
 /// GitHub driver for GitHub repositories
 pub struct GitHubDriver {
@@ -20,6 +21,8 @@ pub struct GitHubDriver {
     /// Cached default branch (for future use)
     #[allow(dead_code)]
     default_branch: Option<String>,
+    /// Proxy settings for API requests
+    proxy: ProxyConfig,
 }
 
 impl GitHubDriver {
@@ -37,6 +40,7 @@ impl GitHubDriver {
             oauth_token: None,
             root_identifier: None,
             default_branch: None,
+            proxy: ProxyConfig::default(),
         })
     }
 
@@ -46,6 +50,12 @@ impl GitHubDriver {
         self
     }
 
+    /// Send API requests through the proxy settings
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
     /// Configure authentication from AuthConfig
     pub fn with_auth(mut self, auth: &AuthConfig) -> Self {
         // Try to get token for github.com or the specific domain
@@ -59,7 +69,7 @@ impl GitHubDriver {
     fn api_request(&self, endpoint: &str) -> Result<serde_json::Value, VcsDriverError> {
         let url = format!("https://api.github.com/repos/{}/{}{}", self.owner, self.repo, endpoint);
 
-        let client = reqwest::blocking::Client::new();
+        let client = crate::http::blocking_client(&self.proxy);
         let mut request = client.get(&url);
 
         // Add authentication if available
@@ -4,6 +4,7 @@ use std::collections::HashMap;
 
 use super::driver::{VcsDriver, VcsDriverError, VcsInfo, parse_gitlab_url};
 use crate::config::AuthConfig;
+use crate::http::ProxyConfig;
 
 /// GitLab driver for GitLab repositories
 pub struct GitLabDriver {
@@ -20,6 +21,8 @@ pub struct GitLabDriver {
     /// Cached default branch
     #[allow(dead_code)]
     default_branch: Option<String>,
+    /// Proxy settings for API requests
+    proxy: ProxyConfig,
 }
 
 impl GitLabDriver {
@@ -40,6 +43,7 @@ impl GitLabDriver {
             project_id,
             private_token: None,
             default_branch: None,
+            proxy: ProxyConfig::default(),
         })
     }
 
@@ -49,6 +53,12 @@ impl GitLabDriver {
         self
     }
 
+    /// Send API requests through the proxy settings
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
     /// Configure authentication from AuthConfig
     pub fn with_auth(mut self, auth: &AuthConfig) -> Self {
         // Try to get token for the specific domain first, then gitlab.com
@@ -67,7 +77,7 @@ impl GitLabDriver {
             self.api_host, self.project_id, endpoint
         );
 
-        let client = reqwest::blocking::Client::new();
+        let client = crate::http::blocking_client(&self.proxy);
         let mut request = client.get(&url);
 
         // Add authentication if available
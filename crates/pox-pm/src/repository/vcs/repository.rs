@@ -11,6 +11,7 @@ use super::github::GitHubDriver;
 use super::gitlab::GitLabDriver;
 use super::bitbucket::BitbucketDriver;
 use crate::config::AuthConfig;
+use crate::http::ProxyConfig;
 use crate::package::{Package, Dist, Source, Autoload, AutoloadPath};
 use crate::repository::traits::{Repository, SearchMode, SearchResult, ProviderInfo};
 
@@ -61,6 +62,8 @@ pub struct VcsRepository {
     vcs_type: VcsType,
     /// Authentication configuration
     auth: Option<AuthConfig>,
+    /// Proxy settings for API requests
+    proxy: ProxyConfig,
     /// Mutable state
     state: Mutex<VcsRepositoryState>,
 }
@@ -89,6 +92,7 @@ impl VcsRepository {
             url,
             vcs_type,
             auth: None,
+            proxy: ProxyConfig::default(),
             state: Mutex::new(VcsRepositoryState {
                 packages: Vec::new(),
                 loaded: false,
@@ -102,6 +106,12 @@ impl VcsRepository {
         self
     }
 
+    /// Send API requests through the proxy settings
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
     /// Create appropriate driver for the URL and type
     fn create_driver(&self) -> Result<Box<dyn VcsDriver>, VcsDriverError> {
         let vcs_type = if self.vcs_type == VcsType::Vcs {
@@ -112,21 +122,21 @@ impl VcsRepository {
 
         match vcs_type {
             VcsType::GitHub => {
-                let mut driver = GitHubDriver::new(&self.url)?;
+                let mut driver = GitHubDriver::new(&self.url)?.with_proxy(self.proxy.clone());
                 if let Some(ref auth) = self.auth {
                     driver = driver.with_auth(auth);
                 }
                 Ok(Box::new(driver))
             }
             VcsType::GitLab => {
-                let mut driver = GitLabDriver::new(&self.url)?;
+                let mut driver = GitLabDriver::new(&self.url)?.with_proxy(self.proxy.clone());
                 if let Some(ref auth) = self.auth {
                     driver = driver.with_auth(auth);
                 }
                 Ok(Box::new(driver))
             }
             VcsType::Bitbucket => {
-                let mut driver = BitbucketDriver::new(&self.url)?;
+                let mut driver = BitbucketDriver::new(&self.url)?.with_proxy(self.proxy.clone());
                 if let Some(ref auth) = self.auth {
                     driver = driver.with_auth(auth);
                 }
@@ -1,22 +1,34 @@
 //! Script execution utilities for composer scripts.
 
 use anyhow::{Context, Result};
-use console::style;
-use std::collections::HashMap;
-use std::path::Path;
-use std::process::Command;
+use console::{style, Style};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
+use crate::config::{Config, ConfigLoader};
 use crate::json::ComposerJson;
 
 /// Default process timeout in seconds (same as Composer)
 const DEFAULT_PROCESS_TIMEOUT: u64 = 300;
 
 /// Script execution context to track environment variables and timeout settings
+#[derive(Clone)]
 pub struct ScriptContext {
     env_vars: HashMap<String, String>,
     /// Process timeout in seconds, None means no timeout
     process_timeout: Option<u64>,
+    /// Directory prepended to PATH, `vendor/bin` of the working directory if unset
+    bin_dir: Option<PathBuf>,
+    /// `memory_limit` passed to `@php` commands (COMPOSER_MEMORY_LIMIT)
+    memory_limit: Option<String>,
+    /// Scripts whose commands run in parallel (`extra.pox.parallel-scripts`)
+    parallel_scripts: HashSet<String>,
+    /// Prefix of every output line, set for the commands of a parallel script
+    output_prefix: Option<String>,
 }
 
 impl ScriptContext {
@@ -36,13 +48,47 @@ impl ScriptContext {
         Self {
             env_vars: HashMap::new(),
             process_timeout,
+            bin_dir: None,
+            memory_limit: ConfigLoader::new(true).get_memory_limit(),
+            parallel_scripts: HashSet::new(),
+            output_prefix: None,
         }
     }
 
+    /// Create a context from the project configuration, so `process-timeout`
+    /// and `bin-dir` from composer.json and the environment are honored
+    pub fn for_project(working_dir: &Path) -> Self {
+        match Config::build(Some(working_dir), true) {
+            Ok(config) => Self::from_config(&config),
+            Err(_) => Self::new(),
+        }
+    }
+
+    /// Create a context that honors `process-timeout` and `bin-dir` of a loaded config
+    pub fn from_config(config: &Config) -> Self {
+        let mut ctx = Self::new();
+        ctx.process_timeout = (config.process_timeout != 0).then_some(config.process_timeout);
+        ctx.bin_dir = Some(config.get_bin_dir());
+        ctx
+    }
+
     /// Disable the process timeout
     pub fn disable_timeout(&mut self) {
         self.process_timeout = None;
     }
+
+    /// Run the commands of these scripts in parallel instead of one after another
+    pub fn set_parallel_scripts(&mut self, scripts: HashSet<String>) {
+        self.parallel_scripts = scripts;
+    }
+
+    /// Print a status line, behind the prefix of a parallel command
+    fn print(&self, message: &str) {
+        match &self.output_prefix {
+            Some(prefix) => println!("{} {}", prefix, message),
+            None => println!("{}", message),
+        }
+    }
 }
 
 impl Default for ScriptContext {
@@ -89,6 +135,28 @@ pub fn collect_scripts(composer_json: &ComposerJson) -> HashMap<&str, Vec<String
     scripts
 }
 
+/// Names of the scripts listed in `extra.pox.parallel-scripts`
+///
+/// ```json
+/// {
+///     "scripts": {
+///         "check": ["@phpstan", "@phpunit", "@cs"]
+///     },
+///     "extra": {
+///         "pox": {"parallel-scripts": ["check"]}
+///     }
+/// }
+/// ```
+pub fn parallel_scripts(composer_json: &ComposerJson) -> HashSet<String> {
+    composer_json
+        .extra
+        .get("pox")
+        .and_then(|pox| pox.get("parallel-scripts"))
+        .and_then(|scripts| scripts.as_array())
+        .map(|names| names.iter().filter_map(|n| n.as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
 /// Run a specific event script if it exists
 /// Returns Ok(0) if script doesn't exist or ran successfully
 pub fn run_event_script(
@@ -112,7 +180,8 @@ pub fn run_event_script(
         );
     }
 
-    let mut ctx = ScriptContext::new();
+    let mut ctx = ScriptContext::for_project(working_dir);
+    ctx.set_parallel_scripts(parallel_scripts(composer_json));
 
     for cmd in commands {
         if !quiet {
@@ -156,14 +225,33 @@ pub fn run_script(
         return Ok(1);
     };
 
+    let mut ctx = ScriptContext::for_project(working_dir);
+    ctx.set_parallel_scripts(parallel_scripts(composer_json));
+
+    if ctx.parallel_scripts.contains(script_name) {
+        println!("{} Running {} ({} command(s) in parallel)",
+            style(">").green().bold(),
+            style(script_name).cyan(),
+            commands.len()
+        );
+
+        let exit_code = run_parallel(commands, working_dir, args, &scripts, &ctx)?;
+        if exit_code != 0 {
+            eprintln!("{} Script '{}' returned exit code {}",
+                style("Error:").red().bold(),
+                script_name,
+                exit_code
+            );
+        }
+        return Ok(exit_code);
+    }
+
     println!("{} Running {} ({} command(s))",
         style(">").green().bold(),
         style(script_name).cyan(),
         commands.len()
     );
 
-    let mut ctx = ScriptContext::new();
-
     for cmd in commands {
         println!("{} {}", style(">").green(), style(cmd).dim());
 
@@ -210,6 +298,10 @@ pub fn run_command(
         let php_binary = std::env::current_exe()
             .map(|p| p.to_string_lossy().to_string())
             .unwrap_or_else(|_| "php".to_string());
+        let php_binary = match &ctx.memory_limit {
+            Some(limit) => format!("{} -d memory_limit={}", php_binary, limit),
+            None => php_binary,
+        };
 
         let full_cmd = if extra_args.is_empty() {
             format!("{} {}", php_binary, php_cmd)
@@ -246,9 +338,12 @@ pub fn run_command(
     if let Some(script_ref) = cmd.strip_prefix('@') {
         // Check if this references another script
         if let Some(ref_commands) = scripts.get(script_ref) {
-            println!("{} Running referenced script: {}", style(">").green(), style(script_ref).cyan());
+            ctx.print(&format!("{} Running referenced script: {}", style(">").green(), style(script_ref).cyan()));
+            if ctx.parallel_scripts.contains(script_ref) {
+                return run_parallel(ref_commands, working_dir, extra_args, scripts, ctx);
+            }
             for ref_cmd in ref_commands {
-                println!("{} {}", style(">").green(), style(ref_cmd).dim());
+                ctx.print(&format!("{} {}", style(">").green(), style(ref_cmd).dim()));
                 let exit_code = run_command(ref_cmd, working_dir, extra_args, scripts, ctx)?;
                 if exit_code != 0 {
                     return Ok(exit_code);
@@ -274,10 +369,142 @@ pub fn run_command(
     execute_shell_command(&full_cmd, working_dir, ctx)
 }
 
+/// Colors cycled through for the output prefixes of parallel commands
+fn prefix_styles() -> [Style; 5] {
+    [
+        Style::new().cyan(),
+        Style::new().magenta(),
+        Style::new().yellow(),
+        Style::new().blue(),
+        Style::new().green(),
+    ]
+}
+
+/// Short name of a command for its output prefix, `@phpstan` becomes `phpstan`
+/// and `vendor/bin/phpunit --testdox` becomes `phpunit`
+fn command_label(cmd: &str) -> String {
+    let cmd = cmd.trim();
+    let cmd = cmd
+        .strip_prefix("@php ")
+        .or_else(|| cmd.strip_prefix("@composer "))
+        .or_else(|| cmd.strip_prefix('@'))
+        .unwrap_or(cmd);
+    let program = cmd.split_whitespace().next().unwrap_or(cmd);
+
+    Path::new(program)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| program.to_string())
+}
+
+/// Labels of all commands of a parallel script, numbered when they repeat
+fn command_labels(commands: &[String]) -> Vec<String> {
+    let labels: Vec<String> = commands.iter().map(|cmd| command_label(cmd)).collect();
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+
+    labels
+        .iter()
+        .map(|label| {
+            let count = seen.entry(label.as_str()).or_insert(0);
+            *count += 1;
+            if labels.iter().filter(|l| *l == label).count() > 1 {
+                format!("{}#{}", label, count)
+            } else {
+                label.clone()
+            }
+        })
+        .collect()
+}
+
+/// Run all commands of a parallel script at once, prefixing each output line
+/// with the command's label like npm-run-all.
+///
+/// Every command runs to completion; the first non-zero exit code in
+/// declaration order is returned.
+fn run_parallel(
+    commands: &[String],
+    working_dir: &Path,
+    extra_args: &[String],
+    scripts: &HashMap<&str, Vec<String>>,
+    ctx: &ScriptContext,
+) -> Result<i32> {
+    let labels = command_labels(commands);
+    let width = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+    let styles = prefix_styles();
+
+    let results: Vec<Result<i32>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = commands
+            .iter()
+            .zip(&labels)
+            .enumerate()
+            .map(|(i, (cmd, label))| {
+                let mut ctx = ctx.clone();
+                let prefix = format!("[{:width$}]", label, width = width);
+                ctx.output_prefix = Some(styles[i % styles.len()].apply_to(prefix).to_string());
+                scope.spawn(move || run_command(cmd, working_dir, extra_args, scripts, &mut ctx))
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("Script command panicked")))
+            })
+            .collect()
+    });
+
+    let mut exit_code = 0;
+    for (label, result) in labels.iter().zip(results) {
+        let code = result.unwrap_or_else(|e| {
+            eprintln!("{} {}: {:#}", style("Error:").red().bold(), label, e);
+            1
+        });
+        if code != 0 {
+            eprintln!("{} {} returned exit code {}",
+                style("Error:").red().bold(),
+                style(label).cyan(),
+                code
+            );
+            if exit_code == 0 {
+                exit_code = code;
+            }
+        }
+    }
+
+    Ok(exit_code)
+}
+
+/// Print the stdout and stderr of `child` line by line behind `prefix`
+fn forward_output(child: &mut Child, prefix: &str) -> Vec<JoinHandle<()>> {
+    let mut handles = Vec::new();
+
+    if let Some(stdout) = child.stdout.take() {
+        let prefix = prefix.to_string();
+        handles.push(std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(std::io::Result::ok) {
+                println!("{} {}", prefix, line);
+            }
+        }));
+    }
+
+    if let Some(stderr) = child.stderr.take() {
+        let prefix = prefix.to_string();
+        handles.push(std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(std::io::Result::ok) {
+                eprintln!("{} {}", prefix, line);
+            }
+        }));
+    }
+
+    handles
+}
+
 /// Execute a shell command with optional timeout
 fn execute_shell_command(cmd: &str, working_dir: &Path, ctx: &ScriptContext) -> Result<i32> {
-    // Prepend vendor/bin to PATH so scripts can find vendored binaries
-    let vendor_bin = working_dir.join("vendor").join("bin");
+    // Prepend the bin dir to PATH so scripts can find vendored binaries
+    let vendor_bin = ctx.bin_dir.clone().unwrap_or_else(|| working_dir.join("vendor").join("bin"));
     let path_env = if vendor_bin.exists() {
         let current_path = std::env::var("PATH").unwrap_or_default();
         #[cfg(unix)]
@@ -311,22 +538,47 @@ fn execute_shell_command(cmd: &str, working_dir: &Path, ctx: &ScriptContext) ->
         command.env(key, value);
     }
 
+    // Capture the output of parallel commands to prefix it
+    if ctx.output_prefix.is_some() {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+
     // If no timeout, just run normally
-    if ctx.process_timeout.is_none() {
+    if ctx.process_timeout.is_none() && ctx.output_prefix.is_none() {
         let status = command
             .status()
             .with_context(|| format!("Failed to execute command: {}", cmd))?;
         return Ok(status.code().unwrap_or(1));
     }
 
-    // Run with timeout
-    let timeout_secs = ctx.process_timeout.unwrap();
-    let timeout = Duration::from_secs(timeout_secs);
-
     let mut child = command
         .spawn()
         .with_context(|| format!("Failed to execute command: {}", cmd))?;
 
+    let readers = match &ctx.output_prefix {
+        Some(prefix) => forward_output(&mut child, prefix),
+        None => Vec::new(),
+    };
+
+    let exit_code = wait_for_child(&mut child, ctx.process_timeout);
+
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    exit_code
+}
+
+/// Wait for a spawned command, killing it once `timeout_secs` have passed
+fn wait_for_child(child: &mut Child, timeout_secs: Option<u64>) -> Result<i32> {
+    let Some(timeout_secs) = timeout_secs else {
+        let status = child
+            .wait()
+            .map_err(|e| anyhow::anyhow!("Error waiting for process: {}", e))?;
+        return Ok(status.code().unwrap_or(1));
+    };
+
+    let timeout = Duration::from_secs(timeout_secs);
     let start = Instant::now();
 
     loop {
@@ -423,3 +675,73 @@ pub fn list_scripts(composer_json: &ComposerJson) -> Result<i32> {
 
     Ok(0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_context_from_config() {
+        let temp = TempDir::new().unwrap();
+        let mut config = Config::with_base_dir(temp.path());
+        config.process_timeout = 0;
+        config.bin_dir = PathBuf::from("bin");
+
+        let ctx = ScriptContext::from_config(&config);
+        assert_eq!(ctx.process_timeout, None);
+        assert_eq!(ctx.bin_dir, Some(temp.path().join("bin")));
+
+        config.process_timeout = 60;
+        assert_eq!(ScriptContext::from_config(&config).process_timeout, Some(60));
+    }
+
+    #[test]
+    fn test_parallel_scripts_from_extra() {
+        let composer_json: ComposerJson = serde_json::from_str(
+            r#"{"extra": {"pox": {"parallel-scripts": ["check", "lint"]}}}"#,
+        )
+        .unwrap();
+
+        let scripts = parallel_scripts(&composer_json);
+        assert_eq!(scripts.len(), 2);
+        assert!(scripts.contains("check"));
+        assert!(parallel_scripts(&ComposerJson::default()).is_empty());
+    }
+
+    #[test]
+    fn test_command_labels() {
+        let commands = vec![
+            "@phpstan".to_string(),
+            "vendor/bin/phpunit --testdox".to_string(),
+            "@php bin/console lint:yaml".to_string(),
+            "echo one".to_string(),
+            "echo two".to_string(),
+        ];
+        assert_eq!(
+            command_labels(&commands),
+            vec!["phpstan", "phpunit", "console", "echo#1", "echo#2"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_parallel_script_aggregates_exit_codes() {
+        let temp = TempDir::new().unwrap();
+        let composer_json: ComposerJson = serde_json::from_str(
+            r#"{
+                "scripts": {
+                    "check": ["touch first", "@fail", "touch last"],
+                    "fail": "exit 3"
+                },
+                "extra": {"pox": {"parallel-scripts": ["check"]}}
+            }"#,
+        )
+        .unwrap();
+
+        let exit_code = run_script("check", &composer_json, temp.path(), &[]).unwrap();
+        assert_eq!(exit_code, 3);
+        assert!(temp.path().join("first").exists());
+        assert!(temp.path().join("last").exists());
+    }
+}
@@ -0,0 +1,122 @@
+//! String interning for package names and constraints.
+//!
+//! A resolution touches the same few thousand names and constraint strings
+//! over and over. Interning them once gives every distinct string a
+//! [`Symbol`], a `u32` that is cheap to copy, hash and compare, so the pool
+//! indexes, the rule generator and the rules themselves do not carry their
+//! own copies of the strings.
+//!
+//! Every [`Pool`](super::Pool) owns its [`Interner`], strings are interned
+//! while packages are added and freed together with the pool. Queries only
+//! look symbols up, a string nothing in the pool mentions is never stored.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// An interned string, only meaningful for the interner that created it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+/// Table of interned strings
+#[derive(Debug, Default)]
+pub struct Interner {
+    symbols: HashMap<Arc<str>, Symbol>,
+    strings: Vec<Arc<str>>,
+}
+
+impl Interner {
+    /// Create an empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Intern a string, returning the existing symbol if it was interned before
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(symbol) = self.lookup(s) {
+            return symbol;
+        }
+
+        let s: Arc<str> = Arc::from(s);
+        let symbol = Symbol(self.strings.len() as u32);
+        self.strings.push(Arc::clone(&s));
+        self.symbols.insert(s, symbol);
+        symbol
+    }
+
+    /// The symbol of a string if it was interned, without interning it
+    pub fn lookup(&self, s: &str) -> Option<Symbol> {
+        self.symbols.get(s).copied()
+    }
+
+    /// Intern a package name, which is case-insensitive
+    pub fn intern_name(&mut self, name: &str) -> Symbol {
+        if name.bytes().any(|b| b.is_ascii_uppercase()) {
+            self.intern(&name.to_lowercase())
+        } else {
+            self.intern(name)
+        }
+    }
+
+    /// The symbol of a package name if it was interned
+    pub fn lookup_name(&self, name: &str) -> Option<Symbol> {
+        if name.bytes().any(|b| b.is_ascii_uppercase()) {
+            self.lookup(&name.to_lowercase())
+        } else {
+            self.lookup(name)
+        }
+    }
+
+    /// The interned string of a symbol
+    pub fn resolve(&self, symbol: Symbol) -> &Arc<str> {
+        &self.strings[symbol.0 as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_returns_same_symbol() {
+        let mut interner = Interner::new();
+        let a = interner.intern("vendor/package");
+        let b = interner.intern("vendor/package");
+        let c = interner.intern("vendor/other");
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(&**interner.resolve(a), "vendor/package");
+        assert_eq!(&**interner.resolve(c), "vendor/other");
+    }
+
+    #[test]
+    fn test_lookup_does_not_intern() {
+        let mut interner = Interner::new();
+        assert_eq!(interner.lookup("vendor/never-interned"), None);
+        assert_eq!(interner.lookup_name("Vendor/Never-Interned"), None);
+
+        let symbol = interner.intern("vendor/looked-up");
+        assert_eq!(interner.lookup("vendor/looked-up"), Some(symbol));
+    }
+
+    #[test]
+    fn test_names_are_case_insensitive() {
+        let mut interner = Interner::new();
+        let symbol = interner.intern_name("Vendor/Name");
+        assert_eq!(&**interner.resolve(symbol), "vendor/name");
+        assert_eq!(interner.lookup_name("VENDOR/NAME"), Some(symbol));
+        assert_eq!(interner.intern_name("vendor/name"), symbol);
+    }
+
+    #[test]
+    fn test_interners_are_independent() {
+        let mut first = Interner::new();
+        let mut second = Interner::new();
+        first.intern("vendor/a");
+
+        assert_eq!(second.lookup("vendor/a"), None);
+        let symbol = second.intern("vendor/b");
+        assert_eq!(&**second.resolve(symbol), "vendor/b");
+        assert_eq!(first.lookup("vendor/b"), None);
+    }
+}
@@ -9,6 +9,7 @@
 //! The solver consists of several key components:
 //!
 //! - [`Pool`]: Registry of all available packages with lookup by name/constraint
+//! - [`Symbol`]: Package names and constraints interned by the pool that owns them
 //! - [`PoolOptimizer`]: Reduces pool size before solving for better performance
 //! - [`Request`]: Specification of what needs to be resolved
 //! - [`RuleSet`]: Collection of SAT clauses representing dependencies
@@ -47,6 +48,7 @@
 //! let solver = Solver::new(&pool, &policy).with_optimization(false);
 //! ```
 
+mod interner;
 mod pool;
 mod pool_builder;
 mod pool_optimizer;
@@ -64,11 +66,13 @@ mod policy;
 #[cfg(test)]
 mod tests;
 
+pub use interner::Symbol;
 pub use pool::{Pool, PoolBuilder, PoolEntry, PackageId};
 pub use pool_builder::PoolBuilder as LazyPoolBuilder;
 pub use pool_optimizer::PoolOptimizer;
-pub use request::Request;
+pub use request::{matches_update_pattern, Request, UpdateAllowTransitiveDeps};
 pub use rule::{Rule, RuleType, Literal};
+pub use rule_generator::RuleGenerator;
 pub use rule_set::RuleSet;
 pub use decisions::Decisions;
 pub use solver::{Solver, SolverResult};
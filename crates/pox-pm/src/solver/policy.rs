@@ -17,6 +17,8 @@ pub struct Policy {
     /// Preferred versions for specific packages (package name -> normalized version)
     /// When a preferred version is available, it will be selected over newer versions
     pub preferred_versions: HashMap<String, String>,
+    /// Seed breaking ties between otherwise equal candidates, instead of the pool insertion order
+    pub seed: Option<u64>,
 }
 
 impl Policy {
@@ -27,6 +29,7 @@ impl Policy {
             prefer_lowest: false,
             prefer_dev_over_prerelease: false,
             preferred_versions: HashMap::new(),
+            seed: None,
         }
     }
 
@@ -49,6 +52,17 @@ impl Policy {
         self
     }
 
+    /// Break ties between equally preferred candidates with a seed
+    ///
+    /// Without a seed ties go to the package added to the pool first, which
+    /// depends on the order repositories return packages in. With a seed the
+    /// order only depends on package names and versions, so the same seed gives
+    /// the same lock file on every machine.
+    pub fn seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
     /// Set preferred versions for specific packages
     pub fn preferred_versions(mut self, versions: HashMap<String, String>) -> Self {
         self.preferred_versions = versions;
@@ -69,7 +83,7 @@ impl Policy {
     /// 2. Prefer original packages over replacers
     /// 3. Prefer same vendor as the required package
     /// 4. Prefer by version (highest/lowest based on policy)
-    /// 5. Fall back to package ID (pool insertion order)
+    /// 5. Fall back to the seed, if set, then package ID (pool insertion order)
     pub fn select_preferred(&self, pool: &Pool, candidates: &[PackageId]) -> Vec<PackageId> {
         self.select_preferred_for_requirement(pool, candidates, None)
     }
@@ -231,6 +245,14 @@ impl Policy {
                     return version_result;
                 }
 
+                if let Some(seed) = self.seed {
+                    let seed_cmp = tie_break_key(seed, &pa.name, &pa.version)
+                        .cmp(&tie_break_key(seed, &pb.name, &pb.version));
+                    if seed_cmp != std::cmp::Ordering::Equal {
+                        return seed_cmp;
+                    }
+                }
+
                 // Fall back to package ID (pool insertion order)
                 a.cmp(&b)
             }
@@ -369,6 +391,20 @@ impl Policy {
     }
 }
 
+/// Position of a package among equally preferred candidates for a seed
+///
+/// FNV-1a, which unlike the std hashers is stable across platforms and Rust releases.
+fn tie_break_key(seed: u64, name: &str, version: &str) -> u64 {
+    let bytes = seed
+        .to_le_bytes()
+        .into_iter()
+        .chain(name.bytes().map(|b| b.to_ascii_lowercase()))
+        .chain(std::iter::once(0))
+        .chain(version.bytes());
+
+    bytes.fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
 /// Simple version comparison.
 /// Returns Ordering::Greater if a > b (a is newer).
 fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
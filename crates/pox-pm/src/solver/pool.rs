@@ -1,7 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::cell::RefCell;
 
+use super::interner::{Interner, Symbol};
 use crate::package::{AliasPackage, Package, Stability};
 use pox_semver::{Constraint, ConstraintInterface, Operator, VersionParser};
 
@@ -74,6 +75,9 @@ impl PoolEntry {
     }
 }
 
+/// Name, constraint and whether providers/replacers are included
+type ProvidersKey = (Symbol, Option<Symbol>, bool);
+
 /// Pool of all available packages for dependency resolution.
 ///
 /// The pool indexes packages by ID (1-based) and by name for efficient lookup.
@@ -86,11 +90,14 @@ pub struct Pool {
     /// TODO: Remove this once all code uses entries
     packages: Vec<Arc<Package>>,
 
+    /// Names and constraints of the packages in the pool
+    interner: Interner,
+
     /// Package IDs indexed by name (lowercase)
-    packages_by_name: HashMap<String, Vec<PackageId>>,
+    packages_by_name: HashMap<Symbol, Vec<PackageId>>,
 
     /// Packages indexed by what they provide (virtual packages)
-    providers: HashMap<String, Vec<PackageId>>,
+    providers: HashMap<Symbol, Vec<PackageId>>,
 
     /// Priority of repositories (lower = higher priority)
     priorities: HashMap<String, i32>,
@@ -98,17 +105,18 @@ pub struct Pool {
     /// Repository name for each package (id -> repo name)
     package_repos: HashMap<PackageId, String>,
 
-    /// Cached normalized versions (id -> normalized version)
-    normalized_versions: RefCell<HashMap<PackageId, String>>,
-
     /// Cached parsed constraints (constraint string -> parsed constraint)
-    parsed_constraints: RefCell<HashMap<String, Option<Box<dyn ConstraintInterface>>>>,
+    parsed_constraints: RefCell<HashMap<Symbol, Option<Arc<dyn ConstraintInterface>>>>,
+
+    /// Cached `what_provides` results (name, constraint, include providers -> ids),
+    /// most requirements are shared by many versions of a package
+    what_provides_cache: RefCell<HashMap<ProvidersKey, Vec<PackageId>>>,
 
     /// Cached version constraints (package id -> constraint)
     version_constraints: RefCell<HashMap<PackageId, Option<Constraint>>>,
 
     /// Maps alias package IDs to their base package IDs
-    alias_map: HashMap<PackageId, PackageId>,
+    alias_map: BTreeMap<PackageId, PackageId>,
 
     /// Minimum stability for packages (default: Stable)
     minimum_stability: Stability,
@@ -149,10 +157,11 @@ impl Pool {
             providers: HashMap::new(),
             priorities: HashMap::new(),
             package_repos: HashMap::new(),
-            normalized_versions: RefCell::new(HashMap::new()),
+            interner: Interner::new(),
             parsed_constraints: RefCell::new(HashMap::new()),
+            what_provides_cache: RefCell::new(HashMap::new()),
             version_constraints: RefCell::new(HashMap::new()),
-            alias_map: HashMap::new(),
+            alias_map: BTreeMap::new(),
             minimum_stability,
             stability_flags: HashMap::new(),
         }
@@ -236,28 +245,35 @@ impl Pool {
         }
 
         let id = self.packages.len() as PackageId;
-        let name = package.name.to_lowercase();
+        self.what_provides_cache.get_mut().clear();
 
         // Index by name
         self.packages_by_name
-            .entry(name.clone())
+            .entry(self.interner.intern_name(&package.name))
             .or_default()
             .push(id);
 
         // Index by provides
-        for (provided, _constraint) in &package.provide {
+        for (provided, constraint) in &package.provide {
             self.providers
-                .entry(provided.to_lowercase())
+                .entry(self.interner.intern_name(provided))
                 .or_default()
                 .push(id);
+            self.interner.intern(constraint);
         }
 
         // Index by replaces
-        for (replaced, _constraint) in &package.replace {
+        for (replaced, constraint) in &package.replace {
             self.providers
-                .entry(replaced.to_lowercase())
+                .entry(self.interner.intern_name(replaced))
                 .or_default()
                 .push(id);
+            self.interner.intern(constraint);
+        }
+
+        // Rule generation queries the pool with these constraints
+        for constraint in package.require.values().chain(package.conflict.values()) {
+            self.interner.intern(constraint);
         }
 
         // Track repository source
@@ -312,28 +328,34 @@ impl Pool {
     /// Add an alias package to the pool (internal method)
     pub fn add_alias_package_arc(&mut self, alias: Arc<AliasPackage>, repo_name: Option<&str>) -> PackageId {
         let id = self.entries.len() as PackageId;
-        let name = alias.name().to_lowercase();
+        self.what_provides_cache.get_mut().clear();
 
         // Index by name (so the alias version can be found)
         self.packages_by_name
-            .entry(name.clone())
+            .entry(self.interner.intern_name(alias.name()))
             .or_default()
             .push(id);
 
         // Index by provides (aliases may have transformed provides)
-        for (provided, _constraint) in alias.provide() {
+        for (provided, constraint) in alias.provide() {
             self.providers
-                .entry(provided.to_lowercase())
+                .entry(self.interner.intern_name(provided))
                 .or_default()
                 .push(id);
+            self.interner.intern(constraint);
         }
 
         // Index by replaces
-        for (replaced, _constraint) in alias.replace() {
+        for (replaced, constraint) in alias.replace() {
             self.providers
-                .entry(replaced.to_lowercase())
+                .entry(self.interner.intern_name(replaced))
                 .or_default()
                 .push(id);
+            self.interner.intern(constraint);
+        }
+
+        for constraint in alias.require().values().chain(alias.conflict().values()) {
+            self.interner.intern(constraint);
         }
 
         // Find the base package ID
@@ -365,8 +387,7 @@ impl Pool {
 
     /// Find a package ID by name and version
     fn find_package_id(&self, name: &str, version: &str) -> Option<PackageId> {
-        let name_lower = name.to_lowercase();
-        if let Some(ids) = self.packages_by_name.get(&name_lower) {
+        if let Some(ids) = self.interner.lookup_name(name).and_then(|name| self.packages_by_name.get(&name)) {
             for &id in ids {
                 if let Some(entry) = self.entry(id) {
                     if entry.version() == version {
@@ -407,7 +428,7 @@ impl Pool {
         self.alias_map.get(&id).copied()
     }
 
-    /// Get all aliases for a package
+    /// Get all aliases for a package, in pool order
     pub fn get_aliases(&self, base_id: PackageId) -> Vec<PackageId> {
         self.alias_map
             .iter()
@@ -432,8 +453,9 @@ impl Pool {
 
     /// Get all packages with a given name
     pub fn packages_by_name(&self, name: &str) -> Vec<PackageId> {
-        self.packages_by_name
-            .get(&name.to_lowercase())
+        self.interner
+            .lookup_name(name)
+            .and_then(|name| self.packages_by_name.get(&name))
             .cloned()
             .unwrap_or_default()
     }
@@ -464,11 +486,37 @@ impl Pool {
 
     /// Internal implementation of what_provides with options
     fn what_provides_with_options(&self, name: &str, constraint: Option<&str>, include_providers: bool) -> Vec<PackageId> {
-        let name_lower = name.to_lowercase();
+        // Names nothing in the pool has are never interned
+        let Some(name_symbol) = self.interner.lookup_name(name) else {
+            return Vec::new();
+        };
+
+        // Only constraints of packages in the pool are cached, any other
+        // query is answered without growing the interner or the cache
+        let constraint_symbol = match constraint {
+            Some(constraint) => match self.interner.lookup(constraint) {
+                Some(symbol) => Some(symbol),
+                None => return self.find_providers(name_symbol, Some(constraint), include_providers),
+            },
+            None => None,
+        };
+
+        let key = (name_symbol, constraint_symbol, include_providers);
+        if let Some(ids) = self.what_provides_cache.borrow().get(&key) {
+            return ids.clone();
+        }
+
+        let result = self.find_providers(name_symbol, constraint, include_providers);
+        self.what_provides_cache.borrow_mut().insert(key, result.clone());
+        result
+    }
+
+    fn find_providers(&self, name_symbol: Symbol, constraint: Option<&str>, include_providers: bool) -> Vec<PackageId> {
+        let name_lower: &str = self.interner.resolve(name_symbol);
         let mut result = Vec::new();
 
         // Direct matches
-        if let Some(ids) = self.packages_by_name.get(&name_lower) {
+        if let Some(ids) = self.packages_by_name.get(&name_symbol) {
             for &id in ids {
                 if self.matches_constraint(id, constraint) {
                     result.push(id);
@@ -478,7 +526,7 @@ impl Pool {
 
         // Providers (provide/replace) - only include if requested
         if include_providers {
-            if let Some(ids) = self.providers.get(&name_lower) {
+            if let Some(ids) = self.providers.get(&name_symbol) {
                 for &id in ids {
                     // Check if the provider constraint matches
                     // Handle both regular packages and alias packages
@@ -486,32 +534,32 @@ impl Pool {
                         match entry {
                             PoolEntry::Package(pkg) => {
                                 pkg.provide.iter()
-                                    .find(|(k, _)| k.to_lowercase() == name_lower)
+                                    .find(|(k, _)| k.eq_ignore_ascii_case(name_lower))
                                     .map(|(_, v)| v.clone())
                                     .or_else(|| {
                                         pkg.replace.iter()
-                                            .find(|(k, _)| k.to_lowercase() == name_lower)
+                                            .find(|(k, _)| k.eq_ignore_ascii_case(name_lower))
                                             .map(|(_, v)| v.clone())
                                     })
                             }
                             PoolEntry::Alias(alias) => {
                                 alias.provide().iter()
-                                    .find(|(k, _)| k.to_lowercase() == name_lower)
+                                    .find(|(k, _)| k.eq_ignore_ascii_case(name_lower))
                                     .map(|(_, v)| v.clone())
                                     .or_else(|| {
                                         alias.replace().iter()
-                                            .find(|(k, _)| k.to_lowercase() == name_lower)
+                                            .find(|(k, _)| k.eq_ignore_ascii_case(name_lower))
                                             .map(|(_, v)| v.clone())
                                     })
                             }
                         }
                     } else if let Some(pkg) = self.package(id) {
                         pkg.provide.iter()
-                            .find(|(k, _)| k.to_lowercase() == name_lower)
+                            .find(|(k, _)| k.eq_ignore_ascii_case(name_lower))
                             .map(|(_, v)| v.clone())
                             .or_else(|| {
                                 pkg.replace.iter()
-                                    .find(|(k, _)| k.to_lowercase() == name_lower)
+                                    .find(|(k, _)| k.eq_ignore_ascii_case(name_lower))
                                     .map(|(_, v)| v.clone())
                             })
                     } else {
@@ -553,39 +601,13 @@ impl Pool {
         let parser = VersionParser::new();
 
         // Parse the required constraint
-        let parsed_required = {
-            let constraint_key = constraint_str.to_string();
-            let cache = self.parsed_constraints.borrow();
-            if let Some(cached) = cache.get(&constraint_key) {
-                cached.clone()
-            } else {
-                drop(cache);
-                let parsed = parser.parse_constraints(constraint_str).ok();
-                self.parsed_constraints.borrow_mut().insert(constraint_key, parsed.clone());
-                parsed
-            }
-        };
-
-        let Some(parsed_required) = parsed_required else {
+        let Some(parsed_required) = self.parsed_constraint(constraint_str) else {
             // If constraint parsing fails, accept (be permissive)
             return true;
         };
 
         // Parse the provided constraint
-        let parsed_provided = {
-            let constraint_key = provided_constraint_str.to_string();
-            let cache = self.parsed_constraints.borrow();
-            if let Some(cached) = cache.get(&constraint_key) {
-                cached.clone()
-            } else {
-                drop(cache);
-                let parsed = parser.parse_constraints(provided_constraint_str).ok();
-                self.parsed_constraints.borrow_mut().insert(constraint_key, parsed.clone());
-                parsed
-            }
-        };
-
-        let Some(parsed_provided) = parsed_provided else {
+        let Some(parsed_provided) = self.parsed_constraint(provided_constraint_str) else {
             // If provided looks like a version (not a constraint), try as exact version
             let normalized_version = match parser.normalize(provided_constraint_str) {
                 Ok(v) => v,
@@ -616,70 +638,68 @@ impl Pool {
             return true;
         }
 
+        // Get or parse constraint (cached)
+        let Some(parsed_constraint) = self.parsed_constraint(constraint_str) else {
+            // If constraint parsing fails, accept all versions (be permissive)
+            return true;
+        };
+
+        if let Some(cached) = self.version_constraints.borrow().get(&id) {
+            return match cached {
+                Some(vc) => parsed_constraint.matches(vc),
+                None => true, // Invalid version, permissive
+            };
+        }
+
         // Get the version from either package or alias entry
         let version = if let Some(entry) = self.entry(id) {
-            entry.version().to_string()
+            entry.version()
         } else if let Some(package) = self.package(id) {
-            package.version.clone()
+            package.version.as_str()
         } else {
             return false;
         };
 
-        // Get or compute normalized version (cached)
-        let normalized_version = {
-            let cache = self.normalized_versions.borrow();
-            if let Some(v) = cache.get(&id) {
-                v.clone()
-            } else {
-                drop(cache);
-                let parser = VersionParser::new();
-                let v = match parser.normalize(&version) {
-                    Ok(v) => v,
-                    Err(_) => version.clone(),
-                };
-                self.normalized_versions.borrow_mut().insert(id, v.clone());
-                v
-            }
+        let normalized_version = VersionParser::new().normalize(version).unwrap_or_else(|_| version.to_string());
+        let vc = Constraint::new(Operator::Equal, normalized_version).ok();
+        let matches = match &vc {
+            Some(c) => parsed_constraint.matches(c),
+            None => true,
         };
+        self.version_constraints.borrow_mut().insert(id, vc);
+        matches
+    }
 
-        // Get or parse constraint (cached)
-        let constraint_key = constraint_str.to_string();
-        let parsed_opt = {
-            let cache = self.parsed_constraints.borrow();
-            if let Some(cached) = cache.get(&constraint_key) {
-                cached.clone()
-            } else {
-                drop(cache);
-                let parser = VersionParser::new();
-                let parsed = parser.parse_constraints(constraint_str).ok();
-                self.parsed_constraints.borrow_mut().insert(constraint_key.clone(), parsed.clone());
-                parsed
-            }
-        };
+    /// Parse a constraint string, cached by its interned symbol
+    fn parsed_constraint(&self, constraint: &str) -> Option<Arc<dyn ConstraintInterface>> {
+        let key = self.interner.lookup(constraint);
+        if let Some(cached) = key.and_then(|key| self.parsed_constraints.borrow().get(&key).cloned()) {
+            return cached;
+        }
 
-        let Some(parsed_constraint) = parsed_opt else {
-            // If constraint parsing fails, accept all versions (be permissive)
-            return true;
-        };
+        let parsed: Option<Arc<dyn ConstraintInterface>> = VersionParser::new().parse_constraints(constraint).ok().map(Arc::from);
+        if let Some(key) = key {
+            self.parsed_constraints.borrow_mut().insert(key, parsed.clone());
+        }
+        parsed
+    }
 
-        let binding = self.version_constraints.borrow();
-        let result = if let Some(cached) = binding.get(&id) {
-            match cached {
-                Some(vc) => parsed_constraint.matches(vc),
-                None => true, // Invalid version, permissive
-            }
-        } else {
-            drop(binding);
-            let vc = Constraint::new(Operator::Equal, normalized_version.clone()).ok();
-            let matches = match &vc {
-                Some(c) => parsed_constraint.matches(c),
-                None => true,
-            };
-            self.version_constraints.borrow_mut().insert(id, vc);
-            matches
-        };
+    /// The symbol of a package name in this pool, without interning it
+    pub(crate) fn lookup_name(&self, name: &str) -> Option<Symbol> {
+        self.interner.lookup_name(name)
+    }
 
-        result
+    /// The string of a symbol of this pool
+    pub(crate) fn resolve(&self, symbol: Symbol) -> &Arc<str> {
+        self.interner.resolve(symbol)
+    }
+
+    /// A shared copy of a string, reusing the pool's copy when it has one
+    pub(crate) fn shared_str(&self, s: &str) -> Arc<str> {
+        match self.interner.lookup(s) {
+            Some(symbol) => Arc::clone(self.interner.resolve(symbol)),
+            None => Arc::from(s),
+        }
     }
 
     /// Get the total number of packages (excluding placeholder)
@@ -734,8 +754,7 @@ impl Pool {
     /// Get priority for a package's repository (looks up by package name/version)
     pub fn get_priority(&self, package: &Package) -> i32 {
         // Find the package ID by matching name and version
-        let name_lower = package.name.to_lowercase();
-        if let Some(ids) = self.packages_by_name.get(&name_lower) {
+        if let Some(ids) = self.interner.lookup_name(&package.name).and_then(|name| self.packages_by_name.get(&name)) {
             for &id in ids {
                 if let Some(pkg) = self.package(id) {
                     if pkg.version == package.version {
@@ -1317,6 +1336,40 @@ mod tests {
         assert_eq!(pool.package(filtered[0]).unwrap().version, "2.0.0");
     }
 
+    #[test]
+    fn test_what_provides_cache_is_cleared_when_packages_are_added() {
+        let mut pool = Pool::new();
+        pool.add_package(Package::new("Vendor/Cached", "1.0.0"));
+        assert_eq!(pool.what_provides("vendor/cached", Some("^1.0")).len(), 1);
+        assert!(pool.what_provides("vendor/cached-missing", None).is_empty());
+
+        pool.add_package(Package::new("vendor/cached", "1.1.0"));
+        let mut provider = Package::new("vendor/cached-provider", "1.0.0");
+        provider.provide.insert("vendor/cached".to_string(), "1.2.0".to_string());
+        pool.add_package(provider);
+
+        assert_eq!(pool.what_provides("VENDOR/cached", Some("^1.0")).len(), 3);
+        assert_eq!(pool.what_provides_direct_only("vendor/cached", Some("^1.0")).len(), 2);
+    }
+
+    #[test]
+    fn test_queries_do_not_intern_strings() {
+        let mut pool = Pool::new();
+        let mut package = Package::new("vendor/a", "1.0.0");
+        package.require.insert("vendor/b".to_string(), "^1.0".to_string());
+        pool.add_package(package);
+        pool.add_package(Package::new("vendor/b", "1.0.0"));
+
+        assert!(pool.interner.lookup("^1.0").is_some());
+        assert_eq!(pool.what_provides("vendor/b", Some("^1.0")).len(), 1);
+
+        assert_eq!(pool.what_provides("vendor/b", Some(">=0.5")).len(), 1);
+        assert!(pool.what_provides("vendor/unknown", Some("^2.0")).is_empty());
+        assert_eq!(pool.interner.lookup(">=0.5"), None);
+        assert_eq!(pool.interner.lookup_name("vendor/unknown"), None);
+        assert_eq!(pool.interner.lookup("^2.0"), None);
+    }
+
     /// Port of Composer's testPackageById
     #[test]
     fn test_package_by_id() {
@@ -62,11 +62,14 @@ impl PoolBuilder {
     }
 
     /// Build a pool from repositories using demand-driven loading.
+    ///
+    /// Fails when a repository could not be queried, as the pool would be
+    /// missing packages the request may need.
     pub async fn build_pool(
         &mut self,
         repositories: &[Arc<dyn Repository>],
         request: &Request,
-    ) -> Pool {
+    ) -> crate::Result<Pool> {
         let start = std::time::Instant::now();
 
         // Reset state
@@ -127,7 +130,7 @@ impl PoolBuilder {
                 iteration,
                 self.packages_to_load.len()
             );
-            self.load_packages_marked_for_loading(repositories).await;
+            self.load_packages_marked_for_loading(repositories).await?;
         }
 
         log::info!(
@@ -175,7 +178,7 @@ impl PoolBuilder {
             }
         }
 
-        pool
+        Ok(pool)
     }
 
     /// Mark a package name for loading with the given constraint.
@@ -222,7 +225,7 @@ impl PoolBuilder {
     }
 
     /// Load all packages marked for loading from repositories using batch loading.
-    async fn load_packages_marked_for_loading(&mut self, repositories: &[Arc<dyn Repository>]) {
+    async fn load_packages_marked_for_loading(&mut self, repositories: &[Arc<dyn Repository>]) -> crate::Result<()> {
         // Move packages_to_load to loaded_packages
         let mut packages_to_load: Vec<_> = self.packages_to_load.drain().collect();
 
@@ -252,9 +255,19 @@ impl PoolBuilder {
                 continue;
             }
 
-            // Load from each repository in priority order
+            // Load from each repository in priority order, skipping names a
+            // canonical repository before it already had
             for repo in repositories {
-                let result = repo.load_packages_batch(&batch_to_load).await;
+                let repo_batch: Vec<(String, Option<String>)> = batch_to_load
+                    .iter()
+                    .filter(|(name, _)| !self.names_found.contains(name))
+                    .cloned()
+                    .collect();
+                if repo_batch.is_empty() {
+                    break;
+                }
+
+                let result = repo.load_packages_batch(&repo_batch).await?;
 
                 // Track which names were found
                 for name in result.names_found {
@@ -267,6 +280,8 @@ impl PoolBuilder {
                 }
             }
         }
+
+        Ok(())
     }
 
     /// Load a package and mark its dependencies for loading.
@@ -21,7 +21,6 @@ use super::policy::Policy;
 use super::pool::{Pool, PoolEntry, PackageId};
 use super::request::Request;
 use crate::package::Package;
-use crate::util::is_platform_package;
 
 /// Optimizes a Pool by removing unnecessary packages before solving.
 ///
@@ -736,13 +735,6 @@ impl<'a> PoolOptimizer<'a> {
 
         let mut new_pool = Pool::with_minimum_stability(original_pool.minimum_stability());
 
-        // Copy stability flags
-        // TODO: Access private field stability_flags if possible, or add getter/setter
-        // Since we can't access private fields easily without modifying Pool, 
-        // we might be missing flags. But wait, we can add them via builder or setter.
-        // Assuming we rely on the fact that stability was checked during initial pool population ??
-        // Actually, optimization might lose stability flags which is bad for subsequent lookups.
-        
         // Copy packages that aren't marked for removal
         for id in original_pool.all_package_ids() {
             if self.packages_to_remove.contains(&id) {
@@ -755,25 +747,12 @@ impl<'a> PoolOptimizer<'a> {
                         let repo_name = original_pool.get_repository(id);
                         let priority = original_pool.get_priority_by_id(id);
 
-                        // Platform packages and packages with replace/provide should bypass
-                        // stability filtering. Platform packages are fixed system packages.
-                        // Packages with replace/provide are typically root or metapackages
-                        // that need to be preserved regardless of their version stability.
-                        let bypass_stability = is_platform_package(&pkg.name)
-                            || !pkg.replace.is_empty()
-                            || !pkg.provide.is_empty();
-
-                        if bypass_stability {
-                            new_pool.add_package_arc_bypass_stability(
-                                Arc::clone(pkg),
-                                repo_name,
-                            );
-                        } else {
-                            new_pool.add_package_arc(
-                                Arc::clone(pkg),
-                                repo_name,
-                            );
-                        }
+                        // Packages were already filtered by stability (including
+                        // per-package stability flags) when the original pool was built
+                        new_pool.add_package_arc_bypass_stability(
+                            Arc::clone(pkg),
+                            repo_name,
+                        );
 
                         // Preserve priority
                         if let Some(repo) = repo_name {
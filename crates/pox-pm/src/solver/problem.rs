@@ -1,6 +1,6 @@
 use std::fmt;
 
-use pox_semver::VersionParser;
+use pox_semver::{Dnf, VersionParser};
 
 use super::pool::{Pool, PackageId};
 use super::rule::{Rule, RuleType};
@@ -122,6 +122,21 @@ fn version_satisfies(version: &str, constraint: &str) -> bool {
     }
 }
 
+/// The constraint followed by the versions it allows when that reads differently,
+/// e.g. `^1.2 || ^1.4 (>=1.2 <2.0)`
+fn effective_constraint(constraint: &str) -> String {
+    let Ok(parsed) = VersionParser::new().parse_constraints_cached(constraint) else {
+        return constraint.to_string();
+    };
+
+    let range = Dnf::from_constraint(parsed.constraint()).to_string();
+    if range.replace(' ', "") == constraint.replace(' ', "") {
+        constraint.to_string()
+    } else {
+        format!("{} ({})", constraint, range)
+    }
+}
+
 /// Describe a problem rule in human-readable form
 fn describe_rule(pool: &Pool, rule: &ProblemRule) -> String {
     match rule.rule_type {
@@ -199,7 +214,12 @@ fn describe_rule(pool: &Pool, rule: &ProblemRule) -> String {
                                     .collect();
                                 reasons.push(format!(
                                     "{} {} requires {} {} -> found {}[{}] but it does not match the constraint",
-                                    target, version, dep_name, dep_constraint, dep_name, available_versions.join(", ")
+                                    target,
+                                    version,
+                                    dep_name,
+                                    effective_constraint(dep_constraint),
+                                    dep_name,
+                                    available_versions.join(", ")
                                 ));
                             }
                         }
@@ -210,7 +230,8 @@ fn describe_rule(pool: &Pool, rule: &ProblemRule) -> String {
             if reasons.is_empty() {
                 format!(
                     "Root composer.json requires {} {}, but no version satisfying the constraint can be installed",
-                    target, constraint
+                    target,
+                    effective_constraint(constraint)
                 )
             } else {
                 reasons.sort();
@@ -265,7 +286,11 @@ fn describe_rule(pool: &Pool, rule: &ProblemRule) -> String {
                         .collect();
                     format!(
                         "{} requires {} {} -> found {}[{}] but it does not match the constraint",
-                        source, target, constraint, target, available.join(", ")
+                        source,
+                        target,
+                        effective_constraint(constraint),
+                        target,
+                        available.join(", ")
                     )
                 } else {
                     format!("{} requires {} {}", source, target, constraint)
@@ -381,6 +406,32 @@ mod tests {
         assert!(description.contains("^1.0"));
     }
 
+    #[test]
+    fn test_effective_constraint() {
+        assert_eq!(effective_constraint("^1.2 || ^1.4"), "^1.2 || ^1.4 (>=1.2 <2.0)");
+        assert_eq!(effective_constraint(">=1.2 <2.0"), ">=1.2 <2.0");
+        assert_eq!(effective_constraint("not a constraint"), "not a constraint");
+    }
+
+    #[test]
+    fn test_problem_describe_shows_effective_range() {
+        let mut pool = Pool::new();
+        pool.add_package(crate::package::Package::new("vendor/dep", "3.0.0"));
+        let mut problem = Problem::new();
+
+        let rule = Rule::new(vec![], RuleType::PackageRequires)
+            .with_target("vendor/dep")
+            .with_constraint("^1.0, <1.5 || ^2.0");
+        problem.add_rule(&rule);
+
+        let description = problem.describe(&pool);
+        assert!(
+            description.contains("requires vendor/dep ^1.0, <1.5 || ^2.0 (>=1.0 <1.5 || >=2.0 <3.0) -> found vendor/dep[3.0.0]"),
+            "{}",
+            description
+        );
+    }
+
     #[test]
     fn test_problem_set() {
         let mut problems = ProblemSet::new();
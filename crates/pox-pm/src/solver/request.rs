@@ -1,8 +1,22 @@
+use std::collections::{HashSet, VecDeque};
 use std::sync::Arc;
 
 use indexmap::IndexMap;
 
 use crate::package::Package;
+use crate::util::is_platform_package;
+
+/// Which dependencies of the packages listed for a partial update may be updated too.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UpdateAllowTransitiveDeps {
+    /// Only the listed packages
+    #[default]
+    OnlyListed,
+    /// Listed packages and their dependencies, except root requirements (`--with-dependencies`)
+    NoRootRequire,
+    /// Listed packages and all of their dependencies (`--with-all-dependencies`)
+    All,
+}
 
 /// A request specifies what needs to be resolved.
 ///
@@ -23,9 +37,12 @@ pub struct Request {
     /// Locked packages from composer.lock
     pub locked_packages: Vec<Arc<Package>>,
 
-    /// Packages that must be updated (for partial updates)
+    /// Packages that must be updated (for partial updates), may contain `*` wildcards
     pub update_allowlist: Vec<String>,
 
+    /// Whether dependencies of the allowlisted packages may be updated as well
+    pub update_allow_transitive_deps: UpdateAllowTransitiveDeps,
+
     /// Whether this is a dev install
     pub install_dev: bool,
 
@@ -45,6 +62,7 @@ impl Request {
             fixed_packages: Vec::new(),
             locked_packages: Vec::new(),
             update_allowlist: Vec::new(),
+            update_allow_transitive_deps: UpdateAllowTransitiveDeps::default(),
             install_dev: true,
             prefer_stable: true,
             prefer_lowest: false,
@@ -81,6 +99,12 @@ impl Request {
         self
     }
 
+    /// Set whether dependencies of the allowlisted packages may be updated as well
+    pub fn update_allow_transitive_deps(&mut self, mode: UpdateAllowTransitiveDeps) -> &mut Self {
+        self.update_allow_transitive_deps = mode;
+        self
+    }
+
     /// Set whether to install dev dependencies
     pub fn with_dev(&mut self, install_dev: bool) -> &mut Self {
         self.install_dev = install_dev;
@@ -115,7 +139,70 @@ impl Request {
         if self.update_allowlist.is_empty() {
             return true; // Full update
         }
-        self.update_allowlist.iter().any(|n| n == &name.to_lowercase())
+        let name = name.to_lowercase();
+        self.update_allowlist.iter().any(|pattern| matches_update_pattern(pattern, &name))
+    }
+
+    /// Resolve the update allowlist into concrete package names.
+    ///
+    /// Patterns are matched against the locked packages and the root requirements.
+    /// Depending on `update_allow_transitive_deps`, the dependencies of matched packages
+    /// are followed through the locked packages, including packages that replace or
+    /// provide a required name.
+    pub fn resolve_update_allowlist(&self, locked: &[Arc<Package>]) -> HashSet<String> {
+        let root_requires: HashSet<&String> = self.requires.keys().chain(self.dev_requires.keys()).collect();
+
+        let mut allowed: HashSet<String> = HashSet::new();
+        let mut queue: VecDeque<String> = VecDeque::new();
+
+        let candidates = locked
+            .iter()
+            .map(|p| p.name.to_lowercase())
+            .chain(root_requires.iter().map(|n| n.to_string()));
+        for name in candidates {
+            if self.is_update_allowed(&name) && allowed.insert(name.clone()) {
+                queue.push_back(name);
+            }
+        }
+
+        if self.update_allow_transitive_deps == UpdateAllowTransitiveDeps::OnlyListed {
+            return allowed;
+        }
+
+        while let Some(name) = queue.pop_front() {
+            let Some(package) = locked.iter().find(|p| p.name.to_lowercase() == name) else {
+                continue;
+            };
+
+            for dep in package.require.keys() {
+                let dep = dep.to_lowercase();
+                if is_platform_package(&dep) {
+                    continue;
+                }
+                if self.update_allow_transitive_deps == UpdateAllowTransitiveDeps::NoRootRequire
+                    && root_requires.contains(&dep)
+                {
+                    continue;
+                }
+
+                let providers: Vec<String> = locked
+                    .iter()
+                    .filter(|p| {
+                        p.name.to_lowercase() == dep
+                            || p.replace.keys().chain(p.provide.keys()).any(|t| t.to_lowercase() == dep)
+                    })
+                    .map(|p| p.name.to_lowercase())
+                    .collect();
+
+                for provider in providers {
+                    if allowed.insert(provider.clone()) {
+                        queue.push_back(provider);
+                    }
+                }
+            }
+        }
+
+        allowed
     }
 
     /// Check if a package is fixed
@@ -137,6 +224,21 @@ impl Request {
     }
 }
 
+/// Match a package name against an update pattern, where `*` matches any sequence.
+pub fn matches_update_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let name = name.to_lowercase();
+    if !pattern.contains('*') {
+        return pattern == name;
+    }
+
+    let regex = format!(
+        "^{}$",
+        pattern.split('*').map(regex::escape).collect::<Vec<_>>().join(".*")
+    );
+    regex::Regex::new(&regex).map(|re| re.is_match(&name)).unwrap_or(false)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,4 +293,90 @@ mod tests {
         assert!(request.is_update_allowed("vendor/specific"));
         assert!(!request.is_update_allowed("vendor/other"));
     }
+
+    #[test]
+    fn test_request_update_allowlist_patterns() {
+        let mut request = Request::new();
+        request.update(vec!["Symfony/*".to_string()]);
+
+        assert!(request.is_update_allowed("symfony/console"));
+        assert!(request.is_update_allowed("symfony/http-kernel"));
+        assert!(!request.is_update_allowed("monolog/monolog"));
+        assert!(matches_update_pattern("*/log", "psr/log"));
+        assert!(!matches_update_pattern("psr/lo", "psr/log"));
+    }
+
+    fn locked(name: &str, requires: &[&str]) -> Arc<Package> {
+        let mut pkg = Package::new(name, "1.0.0.0");
+        for dep in requires {
+            pkg.require.insert(dep.to_string(), "*".to_string());
+        }
+        Arc::new(pkg)
+    }
+
+    fn transitive_fixture() -> (Request, Vec<Arc<Package>>) {
+        let mut request = Request::new();
+        request.require("vendor/a", "^1.0");
+        request.require("vendor/shared", "^1.0");
+        request.update(vec!["vendor/a".to_string()]);
+
+        let locked = vec![
+            locked("vendor/a", &["vendor/b", "vendor/shared", "php"]),
+            locked("vendor/b", &["vendor/c"]),
+            locked("vendor/c", &[]),
+            locked("vendor/shared", &["vendor/d"]),
+            locked("vendor/d", &[]),
+        ];
+        (request, locked)
+    }
+
+    fn sorted(set: HashSet<String>) -> Vec<String> {
+        let mut names: Vec<String> = set.into_iter().collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_resolve_update_allowlist_only_listed() {
+        let (request, locked) = transitive_fixture();
+        assert_eq!(sorted(request.resolve_update_allowlist(&locked)), vec!["vendor/a"]);
+    }
+
+    #[test]
+    fn test_resolve_update_allowlist_with_dependencies() {
+        let (mut request, locked) = transitive_fixture();
+        request.update_allow_transitive_deps(UpdateAllowTransitiveDeps::NoRootRequire);
+
+        assert_eq!(
+            sorted(request.resolve_update_allowlist(&locked)),
+            vec!["vendor/a", "vendor/b", "vendor/c"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_update_allowlist_with_all_dependencies() {
+        let (mut request, locked) = transitive_fixture();
+        request.update_allow_transitive_deps(UpdateAllowTransitiveDeps::All);
+
+        assert_eq!(
+            sorted(request.resolve_update_allowlist(&locked)),
+            vec!["vendor/a", "vendor/b", "vendor/c", "vendor/d", "vendor/shared"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_update_allowlist_follows_replacers() {
+        let mut request = Request::new();
+        request.update(vec!["vendor/a".to_string()]);
+        request.update_allow_transitive_deps(UpdateAllowTransitiveDeps::All);
+
+        let mut replacer = Package::new("vendor/fork", "1.0.0.0");
+        replacer.replace.insert("vendor/original".to_string(), "*".to_string());
+        let locked = vec![locked("vendor/a", &["vendor/original"]), Arc::new(replacer)];
+
+        assert_eq!(
+            sorted(request.resolve_update_allowlist(&locked)),
+            vec!["vendor/a", "vendor/fork"]
+        );
+    }
 }
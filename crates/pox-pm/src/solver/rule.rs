@@ -1,5 +1,6 @@
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 use super::pool::PackageId;
 
@@ -7,7 +8,7 @@ use super::pool::PackageId;
 pub type Literal = i32;
 
 /// Types of rules generated during dependency resolution
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum RuleType {
     /// Root composer.json requirement
     RootRequire,
@@ -73,9 +74,9 @@ pub struct Rule {
     /// Source package ID (for error messages)
     source_package: Option<PackageId>,
     /// Target package name (for error messages)
-    target_name: Option<String>,
+    target_name: Option<Arc<str>>,
     /// Constraint string (for error messages)
-    constraint: Option<String>,
+    constraint: Option<Arc<str>>,
     /// Whether this rule is disabled
     disabled: bool,
 }
@@ -163,13 +164,13 @@ impl Rule {
     }
 
     /// Set target name for error messages
-    pub fn with_target(mut self, name: impl Into<String>) -> Self {
+    pub fn with_target(mut self, name: impl Into<Arc<str>>) -> Self {
         self.target_name = Some(name.into());
         self
     }
 
     /// Set constraint for error messages
-    pub fn with_constraint(mut self, constraint: impl Into<String>) -> Self {
+    pub fn with_constraint(mut self, constraint: impl Into<Arc<str>>) -> Self {
         self.constraint = Some(constraint.into());
         self
     }
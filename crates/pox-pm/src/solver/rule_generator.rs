@@ -1,5 +1,7 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
+use super::interner::Symbol;
 use super::pool::{Pool, PackageId, PoolEntry};
 use super::request::Request;
 use super::rule::{Rule, RuleType};
@@ -22,12 +24,12 @@ pub struct RuleGenerator<'a> {
     added_packages: HashSet<PackageId>,
     /// Packages we've processed, grouped by name (for same-name conflict rules)
     /// This matches Composer's addedPackagesByNames
-    added_packages_by_name: std::collections::HashMap<String, Vec<PackageId>>,
+    added_packages_by_name: HashMap<Symbol, Vec<PackageId>>,
     /// Track which names have packages providing/replacing them (name -> package ids)
-    providers_by_name: std::collections::HashMap<String, Vec<PackageId>>,
+    providers_by_name: HashMap<Symbol, Vec<PackageId>>,
     /// Package names that are explicitly required by the user (root requirements)
     /// Providers/replacers of these packages can be auto-selected
-    root_required_names: HashSet<String>,
+    root_required_names: HashSet<Symbol>,
 }
 
 impl<'a> RuleGenerator<'a> {
@@ -37,8 +39,8 @@ impl<'a> RuleGenerator<'a> {
             pool,
             rules: RuleSet::new(),
             added_packages: HashSet::new(),
-            added_packages_by_name: std::collections::HashMap::new(),
-            providers_by_name: std::collections::HashMap::new(),
+            added_packages_by_name: HashMap::new(),
+            providers_by_name: HashMap::new(),
             root_required_names: HashSet::new(),
         }
     }
@@ -49,20 +51,23 @@ impl<'a> RuleGenerator<'a> {
 
         // Collect all root required package names first
         // This is used to determine if providers/replacers can be auto-selected
+        // Names nothing in the pool has cannot be provided by anything
         for (name, _) in request.all_requires() {
-            self.root_required_names.insert(name.to_lowercase());
+            if let Some(name) = self.pool.lookup_name(name) {
+                self.root_required_names.insert(name);
+            }
         }
 
         // Also add all packages that provide/replace root requirements
         // so that if user requires "D" which replaces "C", when A requires C,
         // D can be used to satisfy C
         for name in self.root_required_names.clone() {
-            let providers = self.pool.what_provides(&name, None);
+            let providers = self.pool.what_provides(self.pool.resolve(name), None);
             for id in providers {
                 if let Some(pkg) = self.pool.package(id) {
                     // Add all names this package provides/replaces to root_required_names
                     for provided_name in pkg.get_names(true) {
-                        self.root_required_names.insert(provided_name.to_lowercase());
+                        self.root_required_names.extend(self.pool.lookup_name(&provided_name));
                     }
                 }
             }
@@ -77,7 +82,7 @@ impl<'a> RuleGenerator<'a> {
                 if let Some(pkg) = self.pool.package(id) {
                     if pkg.version == fixed.version {
                         for provided_name in pkg.get_names(true) {
-                            self.root_required_names.insert(provided_name.to_lowercase());
+                            self.root_required_names.extend(self.pool.lookup_name(&provided_name));
                         }
                         break;
                     }
@@ -128,7 +133,7 @@ impl<'a> RuleGenerator<'a> {
                     if pkg.version == package.version {
                         let rule = Rule::fixed(id)
                             .with_source(id)
-                            .with_target(&package.name);
+                            .with_target(self.pool.shared_str(&package.name));
                         self.rules.add(rule);
                         self.add_package_rules(id);
                         break;
@@ -169,16 +174,16 @@ impl<'a> RuleGenerator<'a> {
                     .collect();
                 log::warn!("  All versions: {:?}", all_versions);
                 let rule = Rule::new(vec![], RuleType::RootRequire)
-                    .with_target(name)
-                    .with_constraint(constraint);
+                    .with_target(self.pool.shared_str(name))
+                    .with_constraint(self.pool.shared_str(constraint));
                 self.rules.add(rule);
                 continue;
             }
 
             // At least one of the providers must be installed
             let rule = Rule::root_require(providers.clone())
-                .with_target(name)
-                .with_constraint(constraint);
+                .with_target(self.pool.shared_str(name))
+                .with_constraint(self.pool.shared_str(constraint));
             self.rules.add(rule);
 
             // Add dependency rules for each provider
@@ -203,7 +208,7 @@ impl<'a> RuleGenerator<'a> {
                     // Rule: alias -> base (if alias is installed, base must be installed)
                     let rule = Rule::requires(package_id, vec![base_id])
                         .with_source(package_id)
-                        .with_target(alias.name());
+                        .with_target(self.pool.shared_str(alias.name()));
                     self.rules.add(rule);
 
                     // Also process the base package's rules
@@ -224,14 +229,14 @@ impl<'a> RuleGenerator<'a> {
                     if providers.is_empty() {
                         let rule = Rule::new(vec![-package_id], RuleType::PackageRequires)
                             .with_source(package_id)
-                            .with_target(dep_name)
-                            .with_constraint(constraint);
+                            .with_target(self.pool.shared_str(dep_name))
+                            .with_constraint(self.pool.shared_str(constraint));
                         self.rules.add(rule);
                     } else {
                         let rule = Rule::requires(package_id, providers.clone())
                             .with_source(package_id)
-                            .with_target(dep_name)
-                            .with_constraint(constraint);
+                            .with_target(self.pool.shared_str(dep_name))
+                            .with_constraint(self.pool.shared_str(constraint));
                         self.rules.add(rule);
 
                         for id in providers {
@@ -259,7 +264,7 @@ impl<'a> RuleGenerator<'a> {
         // Track this package by all its names (name + replaces, NOT provides)
         // for same-name conflict rules. This matches Composer's addedPackagesByNames pattern.
         // PHP: foreach ($package->getNames(false) as $name) { $this->addedPackagesByNames[$name][] = $package; }
-        for name in package.get_names(false) {
+        for name in package.get_names(false).iter().filter_map(|name| self.pool.lookup_name(name)) {
             self.added_packages_by_name
                 .entry(name)
                 .or_default()
@@ -267,7 +272,7 @@ impl<'a> RuleGenerator<'a> {
         }
 
         // Track all names this package provides/replaces for later conflict detection
-        for name in package.get_names(true) {
+        for name in package.get_names(true).iter().filter_map(|name| self.pool.lookup_name(name)) {
             self.providers_by_name
                 .entry(name)
                 .or_default()
@@ -291,7 +296,7 @@ impl<'a> RuleGenerator<'a> {
             //    or provided/replaced by a root-required package
             let direct_providers = self.pool.what_provides_direct_only(dep_name, Some(constraint));
             let has_direct = !direct_providers.is_empty();
-            let is_root_required = self.root_required_names.contains(&dep_name.to_lowercase());
+            let is_root_required = self.pool.lookup_name(dep_name).is_some_and(|name| self.root_required_names.contains(&name));
 
             // Get all providers (direct + provide/replace)
             let all_providers = self.pool.what_provides(dep_name, Some(constraint));
@@ -310,8 +315,8 @@ impl<'a> RuleGenerator<'a> {
                 // Dependency cannot be satisfied - if this package is installed, conflict
                 let rule = Rule::new(vec![-package_id], RuleType::PackageRequires)
                     .with_source(package_id)
-                    .with_target(dep_name)
-                    .with_constraint(constraint);
+                    .with_target(self.pool.shared_str(dep_name))
+                    .with_constraint(self.pool.shared_str(constraint));
                 self.rules.add(rule);
                 continue;
             }
@@ -319,8 +324,8 @@ impl<'a> RuleGenerator<'a> {
             // If package_id is installed, one of providers must be installed
             let rule = Rule::requires(package_id, providers.clone())
                 .with_source(package_id)
-                .with_target(dep_name)
-                .with_constraint(constraint);
+                .with_target(self.pool.shared_str(dep_name))
+                .with_constraint(self.pool.shared_str(constraint));
             self.rules.add(rule);
 
             // Recursively process dependencies (skip platform packages)
@@ -345,8 +350,8 @@ impl<'a> RuleGenerator<'a> {
     /// This matches Composer's behavior in RuleSetGenerator.php lines 242-246.
     fn add_same_name_conflict_rules(&mut self) {
         // Sort by name for deterministic rule order
-        let mut sorted_names: Vec<_> = self.added_packages_by_name.keys().cloned().collect();
-        sorted_names.sort();
+        let mut sorted_names: Vec<_> = self.added_packages_by_name.keys().copied().collect();
+        sorted_names.sort_by(|a, b| self.pool.resolve(*a).cmp(self.pool.resolve(*b)));
         for name in sorted_names {
             let package_ids = self.added_packages_by_name.get(&name).unwrap();
             if package_ids.len() <= 1 {
@@ -372,7 +377,7 @@ impl<'a> RuleGenerator<'a> {
             // Use a single multi-conflict rule instead of O(n²) pairwise conflicts
             // This is much more efficient for packages with many versions
             let rule = Rule::multi_conflict(non_alias_versions)
-                .with_target(name);
+                .with_target(Arc::clone(self.pool.resolve(name)));
             self.rules.add(rule);
         }
     }
@@ -405,11 +410,12 @@ impl<'a> RuleGenerator<'a> {
             sorted_conflicts.sort_by(|a, b| a.0.cmp(b.0));
 
             for (conflict_name, constraint) in sorted_conflicts {
-                let conflict_name_lower = conflict_name.to_lowercase();
-
                 // Skip if the conflict target is not in our processed packages
                 // PHP: if (!isset($this->addedPackagesByNames[$link->getTarget()])) { continue; }
-                if !self.added_packages_by_name.contains_key(&conflict_name_lower) {
+                let Some(conflict_symbol) = self.pool.lookup_name(conflict_name) else {
+                    continue;
+                };
+                if !self.added_packages_by_name.contains_key(&conflict_symbol) {
                     continue;
                 }
 
@@ -428,7 +434,7 @@ impl<'a> RuleGenerator<'a> {
                         if self.pool.is_alias(conflict_id) {
                             if let Some(entry) = self.pool.entry(conflict_id) {
                                 if let Some(alias) = entry.as_alias() {
-                                    if alias.name().to_lowercase() != conflict_name_lower {
+                                    if !alias.name().eq_ignore_ascii_case(conflict_name) {
                                         continue;
                                     }
                                 }
@@ -438,7 +444,7 @@ impl<'a> RuleGenerator<'a> {
                         conflict_count += 1;
                         let rule = Rule::conflict(vec![package_id, conflict_id])
                             .with_source(package_id)
-                            .with_target(conflict_name);
+                            .with_target(self.pool.shared_str(conflict_name));
                         self.rules.add(rule);
                     }
                 }
@@ -460,8 +466,7 @@ impl<'a> RuleGenerator<'a> {
     /// means "this package replaces another package entirely".
     fn add_provider_conflict_rules(&mut self) {
         // Build a map of name -> packages that REPLACE it (not just provide)
-        let mut replacers_by_name: std::collections::HashMap<String, Vec<PackageId>> =
-            std::collections::HashMap::new();
+        let mut replacers_by_name: HashMap<Symbol, Vec<PackageId>> = HashMap::new();
 
         // Sort package IDs for deterministic iteration order
         let mut sorted_packages: Vec<_> = self.added_packages.iter().copied().collect();
@@ -477,9 +482,11 @@ impl<'a> RuleGenerator<'a> {
             sorted_replaces.sort_by(|a, b| a.0.cmp(b.0));
 
             for (replaced_name, _) in sorted_replaces {
-                let name = replaced_name.to_lowercase();
+                let Some(replaced_name) = self.pool.lookup_name(replaced_name) else {
+                    continue;
+                };
                 replacers_by_name
-                    .entry(name)
+                    .entry(replaced_name)
                     .or_default()
                     .push(package_id);
             }
@@ -488,7 +495,7 @@ impl<'a> RuleGenerator<'a> {
         // Add multi-conflict rules for packages that replace the same name
         // Sort by name for deterministic rule order
         let mut sorted_replacers: Vec<_> = replacers_by_name.into_iter().collect();
-        sorted_replacers.sort_by(|a, b| a.0.cmp(&b.0));
+        sorted_replacers.sort_by(|(a, _), (b, _)| self.pool.resolve(*a).cmp(self.pool.resolve(*b)));
         for (name, replacer_ids) in sorted_replacers {
             if replacer_ids.len() <= 1 {
                 continue;
@@ -502,7 +509,7 @@ impl<'a> RuleGenerator<'a> {
 
             // Use multi-conflict rule: at most one replacer can be installed
             let rule = Rule::multi_conflict(replacer_ids)
-                .with_target(&name);
+                .with_target(Arc::clone(self.pool.resolve(name)));
             self.rules.add(rule);
         }
     }
@@ -525,12 +532,12 @@ impl RuleBuilder {
         self
     }
 
-    pub fn target(mut self, name: impl Into<String>) -> Self {
+    pub fn target(mut self, name: impl Into<Arc<str>>) -> Self {
         self.rule = self.rule.with_target(name);
         self
     }
 
-    pub fn constraint(mut self, constraint: impl Into<String>) -> Self {
+    pub fn constraint(mut self, constraint: impl Into<Arc<str>>) -> Self {
         self.rule = self.rule.with_constraint(constraint);
         self
     }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use super::rule::{Rule, RuleType, Literal};
 
@@ -13,8 +13,8 @@ pub struct RuleSet {
     /// All rules indexed by ID
     rules: Vec<Rule>,
 
-    /// Rules by type for iteration
-    rules_by_type: HashMap<RuleType, Vec<u32>>,
+    /// Rules by type for iteration, ordered so iteration does not depend on hashing
+    rules_by_type: BTreeMap<RuleType, Vec<u32>>,
 
     /// Hash map for deduplication
     rule_hashes: HashMap<u64, u32>,
@@ -28,7 +28,7 @@ impl RuleSet {
     pub fn new() -> Self {
         Self {
             rules: Vec::new(),
-            rules_by_type: HashMap::new(),
+            rules_by_type: BTreeMap::new(),
             rule_hashes: HashMap::new(),
             next_id: 0,
         }
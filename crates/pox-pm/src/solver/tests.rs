@@ -2485,3 +2485,189 @@ fn test_policy_repository_priority() {
     assert_eq!(selected_pkg.version, "1.1.0",
         "Should select highest version from highest priority repo");
 }
+
+// ============================================================================
+// Root Package Links (ported from Composer's RuleSetGenerator and installer fixtures)
+// ============================================================================
+
+/// Add the root package to the pool and fix it, like the installer does
+fn fix_root(pool: &mut Pool, request: &mut Request, root: Package) {
+    pool.add_platform_package(root.clone());
+    request.fix(root);
+}
+
+fn root_package() -> Package {
+    Package::new("root/project", "1.2.0.0")
+}
+
+/// A root conflict forbids the conflicting versions of a required package
+#[test]
+fn test_root_conflict_forbids_versions() {
+    let mut pool = Pool::new();
+    pool.add_package(pkg("a", "1.0.0"));
+    pool.add_package(pkg("a", "1.1.0"));
+
+    let mut request = Request::new();
+    request.require("a", "*");
+
+    let mut root = root_package();
+    root.conflict.insert("a".to_string(), ">=1.1".to_string());
+    fix_root(&mut pool, &mut request, root);
+
+    let policy = Policy::new();
+    let result = Solver::new(&pool, &policy).solve(&request).unwrap();
+    check_solver_result(&result, &request, vec![
+        ("install", "a", "1.0.0"),
+    ]);
+}
+
+/// A root conflict also applies to packages pulled in by dependencies
+#[test]
+fn test_root_conflict_forbids_transitive_versions() {
+    let mut pool = Pool::new();
+    pool.add_package(pkg_with_requires("b", "1.0.0", vec![("a", "^1.0")]));
+    pool.add_package(pkg("a", "1.0.0"));
+    pool.add_package(pkg("a", "1.1.0"));
+
+    let mut request = Request::new();
+    request.require("b", "*");
+
+    let mut root = root_package();
+    root.conflict.insert("a".to_string(), "1.1.0".to_string());
+    fix_root(&mut pool, &mut request, root);
+
+    let policy = Policy::new();
+    let result = Solver::new(&pool, &policy).solve(&request).unwrap();
+    check_solver_result(&result, &request, vec![
+        ("install", "b", "1.0.0"),
+        ("install", "a", "1.0.0"),
+    ]);
+}
+
+/// A root conflict covering every version of a requirement can't be solved
+#[test]
+fn test_root_conflict_with_requirement_fails() {
+    let mut pool = Pool::new();
+    pool.add_package(pkg("a", "1.0.0"));
+
+    let mut request = Request::new();
+    request.require("a", "*");
+
+    let mut root = root_package();
+    root.conflict.insert("a".to_string(), "*".to_string());
+    fix_root(&mut pool, &mut request, root);
+
+    let policy = Policy::new();
+    assert!(Solver::new(&pool, &policy).solve(&request).is_err());
+}
+
+/// A package replaced by the root satisfies dependencies without being installed
+#[test]
+fn test_root_replace_satisfies_dependency() {
+    let mut pool = Pool::new();
+    pool.add_package(pkg_with_requires("b", "1.0.0", vec![("monorepo/sub", "^1.0")]));
+    pool.add_package(pkg("monorepo/sub", "1.1.0"));
+
+    let mut request = Request::new();
+    request.require("b", "*");
+
+    let mut root = root_package();
+    root.replace.insert("monorepo/sub".to_string(), "self.version".to_string());
+    root.replace_self_version();
+    fix_root(&mut pool, &mut request, root);
+
+    let policy = Policy::new();
+    let result = Solver::new(&pool, &policy).solve(&request).unwrap();
+    check_solver_result(&result, &request, vec![
+        ("install", "b", "1.0.0"),
+    ]);
+}
+
+/// A package replaced by the root can't be installed next to it
+#[test]
+fn test_root_replace_conflicts_with_replaced_package() {
+    let mut pool = Pool::new();
+    pool.add_package(pkg_with_requires("b", "1.0.0", vec![("monorepo/sub", "^2.0")]));
+    pool.add_package(pkg("monorepo/sub", "2.0.0"));
+
+    let mut request = Request::new();
+    request.require("b", "*");
+
+    let mut root = root_package();
+    root.replace.insert("monorepo/sub".to_string(), "self.version".to_string());
+    root.replace_self_version();
+    fix_root(&mut pool, &mut request, root);
+
+    let policy = Policy::new();
+    assert!(Solver::new(&pool, &policy).solve(&request).is_err());
+}
+
+/// A virtual package provided by the root satisfies dependencies
+#[test]
+fn test_root_provide_satisfies_virtual_package() {
+    let mut pool = Pool::new();
+    pool.add_package(pkg_with_requires("b", "1.0.0", vec![("psr/log-implementation", "^1.0")]));
+
+    let mut request = Request::new();
+    request.require("b", "*");
+
+    let mut root = root_package();
+    root.provide.insert("psr/log-implementation".to_string(), "1.0.0".to_string());
+    fix_root(&mut pool, &mut request, root);
+
+    let policy = Policy::new();
+    let result = Solver::new(&pool, &policy).solve(&request).unwrap();
+    check_solver_result(&result, &request, vec![
+        ("install", "b", "1.0.0"),
+    ]);
+}
+
+/// Pool with two providers of a virtual package, added in the given order
+fn logger_pool(providers: &[&str]) -> Pool {
+    let mut pool = Pool::new();
+    pool.add_package(pkg_with_requires("app/core", "1.0.0", vec![("psr/log-implementation", "^1.0"), ("acme/util", "*")]));
+    pool.add_package(pkg("acme/util", "1.0.0"));
+    pool.add_package(pkg("acme/util", "1.1.0"));
+    for name in providers {
+        let mut provider = pkg(name, "1.0.0");
+        provider.provide.insert("psr/log-implementation".to_string(), "1.0.0".to_string());
+        pool.add_package(provider);
+    }
+    pool
+}
+
+fn solved_packages(pool: &Pool, policy: &Policy) -> Vec<(String, String)> {
+    let mut request = Request::new();
+    request.require("app/core", "*");
+    request.require("psr/log-implementation", "^1.0");
+
+    let result = Solver::new(pool, policy).solve(&request).unwrap();
+    result.packages.iter().map(|p| (p.name.clone(), p.version.clone())).collect()
+}
+
+/// Solving the same input again gives the same packages in the same order
+#[test]
+fn test_repeated_solves_are_identical() {
+    let pool = logger_pool(&["monolog/monolog", "acme/logger"]);
+    let policy = Policy::new();
+
+    let first = solved_packages(&pool, &policy);
+    for _ in 0..10 {
+        assert_eq!(solved_packages(&logger_pool(&["monolog/monolog", "acme/logger"]), &policy), first);
+    }
+}
+
+/// With a seed, ties between providers do not depend on the order repositories returned them in
+#[test]
+fn test_seed_ties_are_independent_of_pool_order() {
+    for seed in [0, 1, 42, u64::MAX] {
+        let policy = Policy::new().seed(Some(seed));
+
+        let mut forward = solved_packages(&logger_pool(&["monolog/monolog", "acme/logger"]), &policy);
+        let mut reverse = solved_packages(&logger_pool(&["acme/logger", "monolog/monolog"]), &policy);
+        forward.sort();
+        reverse.sort();
+        assert_eq!(forward, reverse, "seed {}", seed);
+        assert!(forward.contains(&("acme/util".to_string(), "1.1.0".to_string())));
+    }
+}
@@ -30,6 +30,44 @@ pub enum Operation {
     MarkAliasUninstalled(Arc<AliasPackage>),
 }
 
+impl Operation {
+    /// Returns true if this is an update to a lower version
+    pub fn is_downgrade(&self) -> bool {
+        match self {
+            Operation::Update { from, to } => {
+                pox_semver::Comparator::greater_than(&from.version, &to.version)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn version(pkg: &Package) -> &str {
+            pkg.pretty_version.as_deref().unwrap_or(&pkg.version)
+        }
+
+        match self {
+            Operation::Install(pkg) => write!(f, "Installing {} ({})", pkg.name, version(pkg)),
+            Operation::Update { from, to } => write!(
+                f,
+                "{} {} ({} => {})",
+                if self.is_downgrade() { "Downgrading" } else { "Upgrading" },
+                to.name,
+                version(from),
+                version(to)
+            ),
+            Operation::Uninstall(pkg) => write!(f, "Removing {} ({})", pkg.name, version(pkg)),
+            Operation::MarkUnneeded(pkg) => write!(f, "Marking {} ({}) as unneeded", pkg.name, version(pkg)),
+            Operation::MarkAliasInstalled(alias) => write!(f, "Marking {} ({}) as installed, alias of {} ({})",
+                alias.name(), alias.pretty_version(), alias.alias_of().name, version(alias.alias_of())),
+            Operation::MarkAliasUninstalled(alias) => write!(f, "Marking {} ({}) as uninstalled, alias of {} ({})",
+                alias.name(), alias.pretty_version(), alias.alias_of().name, version(alias.alias_of())),
+        }
+    }
+}
+
 impl Transaction {
     /// Create a new empty transaction
     pub fn new() -> Self {
@@ -497,6 +535,26 @@ mod tests {
         assert_eq!(summary.updates, 0);
     }
 
+    #[test]
+    fn test_operation_display() {
+        let from = Arc::new(Package::new("vendor/package", "1.0.0"));
+        let to = Arc::new(Package::new("vendor/package", "1.2.0"));
+
+        let install = Operation::Install(to.clone());
+        assert_eq!(install.to_string(), "Installing vendor/package (1.2.0)");
+
+        let upgrade = Operation::Update { from: from.clone(), to: to.clone() };
+        assert!(!upgrade.is_downgrade());
+        assert_eq!(upgrade.to_string(), "Upgrading vendor/package (1.0.0 => 1.2.0)");
+
+        let downgrade = Operation::Update { from: to, to: from.clone() };
+        assert!(downgrade.is_downgrade());
+        assert_eq!(downgrade.to_string(), "Downgrading vendor/package (1.2.0 => 1.0.0)");
+
+        let removal = Operation::Uninstall(from);
+        assert_eq!(removal.to_string(), "Removing vendor/package (1.0.0)");
+    }
+
     #[test]
     fn test_transaction_sort() {
         let mut tx = Transaction::new();
@@ -0,0 +1,473 @@
+//! Mock Composer repository for hermetic integration tests.
+//!
+//! [`MockRegistry`] serves a `packages.json` root file, Composer v2 `p2/`
+//! metadata and zip dists over HTTP on a random local port, so installs and
+//! updates run end to end without network access. Packages are either added
+//! with [`MockRegistry::with_package`], which also builds their dist archive,
+//! or loaded from a fixture directory whose files are served as they are.
+//!
+//! ```no_run
+//! use pox_pm::testing::MockRegistry;
+//! use serde_json::json;
+//!
+//! # async fn run() -> std::io::Result<()> {
+//! let server = MockRegistry::new()
+//!     .with_package("acme/lib", "1.0.0", json!({"require": {"acme/util": "^2.0"}}))
+//!     .with_package("acme/util", "2.1.0", json!({}))
+//!     .start()
+//!     .await?;
+//!
+//! // A project whose composer.json only uses the mock repository
+//! let project = server.project(json!({"require": {"acme/lib": "^1.0"}}))?;
+//! let composer = pox_pm::Composer::open(project.path());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only available with the `test-support` feature.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Map, Value};
+use sha1::{Digest, Sha1};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Placeholder in fixture files replaced by the URL of the running server
+pub const BASE_URL_PLACEHOLDER: &str = "%base_url%";
+
+/// Contents of a mock Composer repository
+#[derive(Debug, Clone, Default)]
+pub struct MockRegistry {
+    /// Files served as they are, by absolute request path
+    files: BTreeMap<String, Vec<u8>>,
+    /// Version metadata of the packages added with `with_package`
+    packages: BTreeMap<String, Vec<Map<String, Value>>>,
+}
+
+impl MockRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve every file below `dir` at its relative path, e.g. `dir/p2/acme/lib.json`
+    /// as `/p2/acme/lib.json`
+    pub fn from_fixtures(dir: impl AsRef<Path>) -> std::io::Result<Self> {
+        let dir = dir.as_ref();
+        let mut registry = Self::new();
+
+        for entry in walkdir::WalkDir::new(dir) {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = entry.path().strip_prefix(dir).unwrap_or(entry.path());
+            let path = relative.components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            registry.files.insert(format!("/{}", path), std::fs::read(entry.path())?);
+        }
+
+        Ok(registry)
+    }
+
+    /// Add a package version; `metadata` holds further fields of the version
+    /// such as `require` or `autoload`. Without a `dist`, a zip archive
+    /// containing the package's composer.json is served as its dist.
+    pub fn with_package(mut self, name: &str, version: &str, metadata: Value) -> Self {
+        let mut entry = match metadata {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        };
+        entry.insert("name".to_string(), Value::String(name.to_string()));
+        entry.insert("version".to_string(), Value::String(version.to_string()));
+
+        self.packages.entry(name.to_lowercase()).or_default().push(entry);
+        self
+    }
+
+    /// Serve `contents` at `path`
+    pub fn with_file(mut self, path: &str, contents: impl Into<Vec<u8>>) -> Self {
+        let path = if path.starts_with('/') { path.to_string() } else { format!("/{}", path) };
+        self.files.insert(path, contents.into());
+        self
+    }
+
+    /// Start serving on a random port of 127.0.0.1
+    pub async fn start(self) -> std::io::Result<MockServer> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let files = Arc::new(self.render(&url)?);
+        let requests = Arc::new(Mutex::new(RequestLog::default()));
+
+        let task = tokio::spawn(serve(listener, files, requests.clone()));
+
+        Ok(MockServer { url, requests, task })
+    }
+
+    /// All files served by a registry running at `url`
+    fn render(&self, url: &str) -> std::io::Result<BTreeMap<String, Vec<u8>>> {
+        let mut files: BTreeMap<String, Vec<u8>> = self.files.iter()
+            .map(|(path, contents)| {
+                let contents = match std::str::from_utf8(contents) {
+                    Ok(text) if text.contains(BASE_URL_PLACEHOLDER) => {
+                        text.replace(BASE_URL_PLACEHOLDER, url).into_bytes()
+                    }
+                    _ => contents.clone(),
+                };
+                (path.clone(), contents)
+            })
+            .collect();
+
+        for (name, versions) in &self.packages {
+            let (dev, stable): (Vec<_>, Vec<_>) = versions.iter()
+                .map(|version| self.render_version(url, name, version, &mut files))
+                .collect::<std::io::Result<Vec<_>>>()?
+                .into_iter()
+                .partition(|v| is_dev_version(v["version"].as_str().unwrap_or_default()));
+
+            for (path, versions) in [(format!("/p2/{}.json", name), stable), (format!("/p2/{}~dev.json", name), dev)] {
+                let body = json!({"packages": {name.as_str(): reset_inherited_fields(versions)}});
+                files.entry(path).or_insert_with(|| body.to_string().into_bytes());
+            }
+        }
+
+        if !files.contains_key("/packages.json") {
+            let available: Vec<&str> = files.keys()
+                .filter_map(|path| path.strip_prefix("/p2/")?.strip_suffix(".json"))
+                .filter(|name| !name.ends_with("~dev"))
+                .collect();
+            let root = json!({
+                "packages": [],
+                "metadata-url": "/p2/%package%.json",
+                "available-packages": available,
+            });
+            files.insert("/packages.json".to_string(), root.to_string().into_bytes());
+        }
+
+        Ok(files)
+    }
+
+    fn render_version(
+        &self,
+        url: &str,
+        name: &str,
+        version: &Map<String, Value>,
+        files: &mut BTreeMap<String, Vec<u8>>,
+    ) -> std::io::Result<Map<String, Value>> {
+        let mut version = version.clone();
+        if version.contains_key("dist") {
+            return Ok(version);
+        }
+
+        let mut composer_json = version.clone();
+        composer_json.remove("version");
+        let archive = zip_archive(&[("composer.json", &serde_json::to_vec_pretty(&composer_json)?)])?;
+
+        let path = format!("/dists/{}/{}.zip", name, version["version"].as_str().unwrap_or_default());
+        version.insert("dist".to_string(), json!({
+            "type": "zip",
+            "url": format!("{}{}", url, path),
+            "shasum": format!("{:x}", Sha1::digest(&archive)),
+        }));
+        files.insert(path, archive);
+
+        Ok(version)
+    }
+}
+
+/// A running [`MockRegistry`], stopped when dropped
+#[derive(Debug)]
+pub struct MockServer {
+    url: String,
+    requests: Arc<Mutex<RequestLog>>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Debug, Default)]
+struct RequestLog {
+    paths: Vec<String>,
+    not_modified: Vec<String>,
+}
+
+impl MockServer {
+    /// Base URL of the repository, e.g. `http://127.0.0.1:43567`
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Paths requested so far, in order
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().paths.clone()
+    }
+
+    /// Paths answered with `304 Not Modified` because the client sent a matching ETag
+    pub fn not_modified(&self) -> Vec<String> {
+        self.requests.lock().unwrap().not_modified.clone()
+    }
+
+    /// The repository entry of the server for the `repositories` of composer.json
+    pub fn repository(&self) -> Value {
+        json!({"type": "composer", "url": self.url})
+    }
+
+    /// Temporary project named `acme/app` whose composer.json only uses this repository
+    ///
+    /// The fields of `root`, such as `require`, are added to composer.json.
+    /// Packagist is disabled and the cache is kept in the project directory.
+    pub fn project(&self, root: Value) -> std::io::Result<tempfile::TempDir> {
+        let dir = tempfile::tempdir()?;
+        let mut composer_json = json!({
+            "name": "acme/app",
+            "repositories": {"mock": self.repository(), "packagist.org": false},
+            "config": {"secure-http": false, "cache-dir": dir.path().join("cache")},
+        });
+        if let Value::Object(root) = root {
+            for (key, value) in root {
+                composer_json[key] = value;
+            }
+        }
+        std::fs::write(dir.path().join("composer.json"), serde_json::to_vec_pretty(&composer_json)?)?;
+        Ok(dir)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Zip archive with the given files below a single top-level directory, like GitHub dists
+pub fn zip_archive(files: &[(&str, &[u8])]) -> std::io::Result<Vec<u8>> {
+    use std::io::Write;
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+    for (path, contents) in files {
+        writer.start_file(format!("package/{}", path), options).map_err(std::io::Error::other)?;
+        writer.write_all(contents)?;
+    }
+
+    Ok(writer.finish().map_err(std::io::Error::other)?.into_inner())
+}
+
+fn is_dev_version(version: &str) -> bool {
+    version.starts_with("dev-") || version.ends_with("-dev")
+}
+
+/// Versions are expanded like minified Packagist metadata, so fields missing
+/// from a version would be inherited from the previous one; reset them instead
+fn reset_inherited_fields(versions: Vec<Map<String, Value>>) -> Vec<Map<String, Value>> {
+    let mut previous: Option<Map<String, Value>> = None;
+    let mut result = Vec::with_capacity(versions.len());
+
+    for mut version in versions {
+        if let Some(previous) = &previous {
+            for (key, value) in previous {
+                if !version.contains_key(key) {
+                    let empty = match value {
+                        Value::Object(_) => json!({}),
+                        Value::Array(_) => json!([]),
+                        _ => Value::Null,
+                    };
+                    version.insert(key.clone(), empty);
+                }
+            }
+        }
+        previous = Some(version.clone());
+        result.push(version);
+    }
+
+    result
+}
+
+async fn serve(listener: TcpListener, files: Arc<BTreeMap<String, Vec<u8>>>, requests: Arc<Mutex<RequestLog>>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let files = files.clone();
+        let requests = requests.clone();
+        tokio::spawn(async move {
+            if let Err(e) = respond(stream, &files, &requests).await {
+                log::debug!("Mock registry failed to respond: {}", e);
+            }
+        });
+    }
+}
+
+async fn respond(
+    mut stream: TcpStream,
+    files: &BTreeMap<String, Vec<u8>>,
+    requests: &Mutex<RequestLog>,
+) -> std::io::Result<()> {
+    let mut head = Vec::new();
+    let mut chunk = [0u8; 4096];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        head.extend_from_slice(&chunk[..n]);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let target = request_line.next().unwrap_or("/");
+    let path = target.split('?').next().unwrap_or(target);
+    let path = urlencoding::decode(path).map(|p| p.into_owned()).unwrap_or_else(|_| path.to_string());
+    requests.lock().unwrap().paths.push(path.clone());
+
+    let if_none_match = head.lines().skip(1).find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim().eq_ignore_ascii_case("if-none-match").then(|| value.trim().to_string())
+    });
+
+    let (status, body, etag) = match files.get(&path) {
+        Some(body) => {
+            let etag = format!("\"{:x}\"", Sha1::digest(body));
+            if if_none_match.as_deref() == Some(etag.as_str()) {
+                requests.lock().unwrap().not_modified.push(path.clone());
+                ("304 Not Modified", [].as_slice(), Some(etag))
+            } else {
+                ("200 OK", body.as_slice(), Some(etag))
+            }
+        }
+        None => ("404 Not Found", b"Not Found".as_slice(), None),
+    };
+    let content_type = if path.ends_with(".json") { "application/json" } else { "application/octet-stream" };
+    let etag = etag.map(|etag| format!("ETag: {}\r\n", etag)).unwrap_or_default();
+
+    let header = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len(),
+        etag
+    );
+    stream.write_all(header.as_bytes()).await?;
+    if method != "HEAD" {
+        stream.write_all(body).await?;
+    }
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::facade::{NullIo, UpdateOptions};
+    use crate::repository::{ComposerRepository, Repository};
+    use crate::Composer;
+
+    #[tokio::test]
+    async fn test_repository_reads_mock_registry() {
+        let server = MockRegistry::new()
+            .with_package("acme/lib", "1.0.0", json!({"require": {"php": ">=8.1"}}))
+            .with_package("acme/lib", "1.1.0", json!({}))
+            .with_package("acme/lib", "dev-main", json!({}))
+            .start()
+            .await
+            .unwrap();
+
+        let repo = ComposerRepository::new("mock", server.url());
+        let packages = repo.find_packages("acme/lib").await;
+        let versions: Vec<_> = packages.iter().map(|p| p.pretty_version.clone().unwrap_or_default()).collect();
+        assert_eq!(versions, vec!["1.0.0", "1.1.0"]);
+        assert!(packages[0].require.contains_key("php"));
+        assert!(!packages[1].require.contains_key("php"));
+        assert!(packages[0].dist.as_ref().unwrap().url.starts_with(server.url()));
+
+        assert!(repo.find_packages("acme/missing").await.is_empty());
+        assert_eq!(server.requests(), vec!["/packages.json", "/p2/acme/lib.json"]);
+    }
+
+    #[tokio::test]
+    async fn test_repository_revalidates_cache_with_etag() {
+        let server = MockRegistry::new().with_package("acme/lib", "1.0.0", json!({})).start().await.unwrap();
+        let cache = tempfile::tempdir().unwrap();
+
+        let open = || {
+            let mut repo = ComposerRepository::new("mock", server.url());
+            repo.set_cache_dir(cache.path().to_path_buf());
+            repo.set_cache_ttl(std::time::Duration::ZERO);
+            repo
+        };
+
+        assert_eq!(open().find_packages("acme/lib").await.len(), 1);
+        // The second fetch only gets a 304 without a body, so the package comes from the cache
+        let packages = open().find_packages("acme/lib").await;
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].pretty_version.as_deref(), Some("1.0.0"));
+
+        let metadata_requests = server.requests().iter().filter(|path| *path == "/p2/acme/lib.json").count();
+        assert_eq!(metadata_requests, 2);
+        assert!(server.not_modified().contains(&"/p2/acme/lib.json".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_batch_load_failure_reaches_hint() {
+        let server = MockRegistry::new()
+            .with_package("acme/lib", "1.0.0", json!({}))
+            .with_file("p2/acme/broken.json", "not json")
+            .start()
+            .await
+            .unwrap();
+        let repo = ComposerRepository::new("mock", server.url());
+
+        // A package the repository does not have is not a failure
+        let batch = vec![("acme/lib".to_string(), None), ("acme/missing".to_string(), None)];
+        let result = repo.load_packages_batch(&batch).await.unwrap();
+        assert_eq!(result.names_found, vec!["acme/lib".to_string()]);
+
+        let batch = vec![("acme/lib".to_string(), None), ("acme/broken".to_string(), None)];
+        let error = anyhow::Error::from(repo.load_packages_batch(&batch).await.unwrap_err())
+            .context("Failed to resolve dependencies");
+        // The CLI looks for the hint the same way
+        let hint = error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<crate::ComposerError>())
+            .and_then(|error| error.hint());
+        assert!(hint.unwrap().contains("pox pm clear-cache"));
+    }
+
+    #[tokio::test]
+    async fn test_fixtures_are_served_with_base_url() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("p2/acme")).unwrap();
+        std::fs::write(
+            dir.path().join("p2/acme/lib.json"),
+            r#"{"packages": {"acme/lib": [{"name": "acme/lib", "version": "2.0.0", "dist": {"type": "zip", "url": "%base_url%/lib.zip"}}]}}"#,
+        ).unwrap();
+
+        let server = MockRegistry::from_fixtures(dir.path()).unwrap().start().await.unwrap();
+        let repo = ComposerRepository::new("mock", server.url());
+        let packages = repo.find_packages("acme/lib").await;
+
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].dist.as_ref().unwrap().url, format!("{}/lib.zip", server.url()));
+    }
+
+    #[tokio::test]
+    async fn test_update_installs_from_mock_registry() {
+        let server = MockRegistry::new()
+            .with_package("acme/lib", "1.0.0", json!({"require": {"acme/util": "^2.0"}}))
+            .with_package("acme/util", "2.0.0", json!({}))
+            .with_package("acme/util", "2.3.0", json!({}))
+            .start()
+            .await
+            .unwrap();
+
+        let dir = server.project(json!({"require": {"acme/lib": "^1.0"}})).unwrap();
+        let mut composer = Composer::open(dir.path()).unwrap();
+        let report = composer.update(None, &UpdateOptions::default(), &NullIo).await.unwrap();
+
+        let mut installed: Vec<_> = report.installed.iter().map(|p| format!("{}@{}", p.name, p.pretty_version.clone().unwrap_or_default())).collect();
+        installed.sort();
+        assert_eq!(installed, vec!["acme/lib@1.0.0", "acme/util@2.3.0"]);
+        assert!(dir.path().join("vendor/acme/util/composer.json").exists());
+        assert!(dir.path().join("composer.lock").exists());
+    }
+}
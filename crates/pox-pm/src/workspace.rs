@@ -0,0 +1,269 @@
+//! Workspaces - several packages of a monorepo developed together.
+//!
+//! The root composer.json lists its member directories as globs:
+//!
+//! ```json
+//! {
+//!     "workspace": ["packages/*", "apps/*"]
+//! }
+//! ```
+//!
+//! Each member is registered as a path repository for the root and for every
+//! other member, so requiring a sibling installs it from the working tree
+//! instead of Packagist.
+
+use std::path::{Path, PathBuf};
+
+use crate::config::composer_file_name;
+use crate::error::{ComposerError, Result};
+use crate::json::ComposerJson;
+use crate::repository::{PathRepository, PathRepositoryOptions};
+
+/// A package of the workspace.
+#[derive(Debug, Clone)]
+pub struct WorkspaceMember {
+    /// Absolute path of the member directory
+    pub path: PathBuf,
+    pub composer_json: ComposerJson,
+}
+
+impl WorkspaceMember {
+    /// Package name, or the directory for members without a name
+    pub fn display_name(&self) -> String {
+        self.composer_json
+            .name
+            .clone()
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+}
+
+/// A monorepo root together with its discovered members.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    /// Absolute path of the workspace root
+    pub root: PathBuf,
+    /// composer.json of the workspace root
+    pub composer_json: ComposerJson,
+    /// Members ordered by path
+    pub members: Vec<WorkspaceMember>,
+}
+
+impl Workspace {
+    /// Load the workspace declared by the composer.json in `root`.
+    ///
+    /// Returns `None` if the composer.json has no `workspace` globs.
+    pub fn load(root: &Path) -> Result<Option<Self>> {
+        let Some(composer_json) = read_composer_json(&root.join(composer_file_name()))? else {
+            return Ok(None);
+        };
+        if composer_json.workspace.is_empty() {
+            return Ok(None);
+        }
+
+        let root = root.canonicalize()?;
+        let mut members: Vec<WorkspaceMember> = Vec::new();
+
+        for pattern in &composer_json.workspace {
+            let full_pattern = root.join(pattern);
+            let paths = glob::glob(&full_pattern.to_string_lossy()).map_err(|e| {
+                ComposerError::Config(format!("Invalid workspace pattern \"{}\": {}", pattern, e))
+            })?;
+
+            for path in paths.flatten() {
+                let Ok(path) = path.canonicalize() else {
+                    continue;
+                };
+                if path == root
+                    || path.components().any(|c| c.as_os_str() == "vendor")
+                    || members.iter().any(|m| m.path == path)
+                {
+                    continue;
+                }
+                if let Some(composer_json) = read_composer_json(&path.join("composer.json"))? {
+                    members.push(WorkspaceMember { path, composer_json });
+                }
+            }
+        }
+
+        members.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(Some(Self { root, composer_json, members }))
+    }
+
+    /// Find the workspace `dir` is the root or a member of, looking at `dir` and its parents.
+    ///
+    /// The search stops at the nearest workspace root and does not cross into
+    /// another filesystem, so unrelated composer.json files further up are not read.
+    pub fn find(dir: &Path) -> Result<Option<Self>> {
+        let Ok(dir) = dir.canonicalize() else {
+            return Ok(None);
+        };
+
+        for candidate in dir.ancestors() {
+            if !same_filesystem(candidate, &dir) {
+                break;
+            }
+            if let Some(workspace) = Self::load(candidate)? {
+                let belongs = workspace.root == dir || workspace.member(&dir).is_some();
+                return Ok(belongs.then_some(workspace));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The root as a package of its own, `None` if it only declares the workspace
+    pub fn root_package(&self) -> Option<WorkspaceMember> {
+        let json = &self.composer_json;
+        if json.require.is_empty() && json.require_dev.is_empty() {
+            return None;
+        }
+
+        Some(WorkspaceMember {
+            path: self.root.clone(),
+            composer_json: json.clone(),
+        })
+    }
+
+    /// Whether `dir` is a member rather than the workspace root
+    pub fn is_member(&self, dir: &Path) -> bool {
+        self.member(dir).is_some()
+    }
+
+    /// The member located at `dir`
+    pub fn member(&self, dir: &Path) -> Option<&WorkspaceMember> {
+        self.members.iter().find(|m| m.path == dir)
+    }
+
+    /// Path repositories for all members except the package at `dir` itself.
+    pub fn path_repositories(&self, dir: &Path) -> Vec<PathRepository> {
+        self.members
+            .iter()
+            .filter(|m| m.path != dir)
+            .map(|m| {
+                let options = PathRepositoryOptions {
+                    symlink: Some(true),
+                    relative: false,
+                    reference: "none".to_string(),
+                    versions: Default::default(),
+                };
+                PathRepository::new(m.path.to_string_lossy(), options)
+            })
+            .collect()
+    }
+}
+
+#[cfg(unix)]
+fn same_filesystem(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (std::fs::metadata(a), std::fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn same_filesystem(_a: &Path, _b: &Path) -> bool {
+    true
+}
+
+fn read_composer_json(path: &Path) -> Result<Option<ComposerJson>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::Repository;
+    use tempfile::TempDir;
+
+    fn write_json(dir: &Path, json: &str) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join("composer.json"), json).unwrap();
+    }
+
+    fn monorepo() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        write_json(root, r#"{"name": "acme/monorepo", "workspace": ["packages/*", "apps/web"]}"#);
+        write_json(&root.join("packages/core"), r#"{"name": "acme/core", "version": "1.0.0"}"#);
+        write_json(&root.join("packages/http"), r#"{"name": "acme/http", "version": "1.0.0", "require": {"acme/core": "^1.0"}}"#);
+        write_json(&root.join("apps/web"), r#"{"name": "acme/web", "require": {"acme/http": "^1.0"}}"#);
+        std::fs::create_dir_all(root.join("packages/docs")).unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_load_discovers_members() {
+        let temp = monorepo();
+        let workspace = Workspace::load(temp.path()).unwrap().unwrap();
+
+        let names: Vec<String> = workspace.members.iter().map(|m| m.display_name()).collect();
+        assert_eq!(names, vec!["acme/web", "acme/core", "acme/http"]);
+        assert!(workspace.root_package().is_none());
+    }
+
+    #[test]
+    fn test_load_without_workspace() {
+        let temp = TempDir::new().unwrap();
+        write_json(temp.path(), r#"{"name": "acme/app"}"#);
+        assert!(Workspace::load(temp.path()).unwrap().is_none());
+        assert!(Workspace::find(temp.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_find_from_member() {
+        let temp = monorepo();
+        let member_dir = temp.path().join("packages/http");
+
+        let workspace = Workspace::find(&member_dir).unwrap().unwrap();
+        assert_eq!(workspace.root, temp.path().canonicalize().unwrap());
+        assert!(workspace.member(&member_dir.canonicalize().unwrap()).is_some());
+
+        // A directory that is not listed as a member does not belong to the workspace
+        assert!(Workspace::find(&temp.path().join("packages/docs")).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_find_stops_at_nearest_workspace() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("composer.json"), "{").unwrap();
+        write_json(&temp.path().join("outer"), r#"{"workspace": ["inner/packages/*"]}"#);
+        write_json(&temp.path().join("outer/inner"), r#"{"workspace": ["apps/*"]}"#);
+        write_json(&temp.path().join("outer/inner/packages/core"), r#"{"name": "acme/core"}"#);
+
+        // The inner workspace is the nearest one and does not list the package
+        assert!(Workspace::find(&temp.path().join("outer/inner/packages/core")).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_find_surfaces_parse_errors() {
+        let temp = monorepo();
+        std::fs::write(temp.path().join("packages/core/composer.json"), "{").unwrap();
+        assert!(Workspace::find(&temp.path().join("packages/http")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_path_repositories_skip_self() {
+        let temp = monorepo();
+        let workspace = Workspace::load(temp.path()).unwrap().unwrap();
+        let http_dir = temp.path().join("packages/http").canonicalize().unwrap();
+
+        let repositories = workspace.path_repositories(&http_dir);
+        assert_eq!(repositories.len(), 2);
+
+        let mut names = Vec::new();
+        for repository in &repositories {
+            for package in repository.get_packages().await {
+                names.push(package.name.clone());
+            }
+        }
+        names.sort();
+        assert_eq!(names, vec!["acme/core", "acme/web"]);
+    }
+}
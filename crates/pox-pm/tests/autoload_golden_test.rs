@@ -0,0 +1,156 @@
+/// Golden-file tests for the autoload generator
+///
+/// The expected files in `tests/fixtures/autoload` are what Composer dumps for
+/// the same set of packages, so the generated autoloader must match them byte
+/// for byte.
+
+use pox_pm::autoload::{AutoloadConfig, AutoloadGenerator, PackageAutoload, RootPackageInfo};
+use pox_pm::package::Autoload;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const SUFFIX: &str = "c9f3d6a7b0e1f2a3c4d5e6f7a8b9c0d1";
+
+const GOLDEN_FILES: &[&str] = &[
+    "autoload_psr4.php",
+    "autoload_namespaces.php",
+    "autoload_files.php",
+    "autoload_static.php",
+];
+
+fn package(name: &str, autoload: Autoload, requires: &[&str], dev: bool) -> PackageAutoload {
+    PackageAutoload {
+        name: name.to_string(),
+        autoload,
+        install_path: name.to_string(),
+        requires: requires.iter().map(|r| r.to_string()).collect(),
+        pretty_version: Some("1.0.0".to_string()),
+        version: Some("1.0.0.0".to_string()),
+        dev_requirement: dev,
+        ..Default::default()
+    }
+}
+
+/// Packages in deliberately unsorted order
+fn packages() -> Vec<PackageAutoload> {
+    vec![
+        package(
+            "symfony/console",
+            Autoload::new().add_psr4("Symfony\\Component\\Console\\", ""),
+            &["symfony/polyfill-mbstring", "symfony/deprecation-contracts"],
+            false,
+        ),
+        package(
+            "phpunit/phpunit",
+            Autoload::new()
+                .add_psr4("PHPUnit\\", "src/")
+                .add_file("src/Framework/Assert/Functions.php"),
+            &[],
+            true,
+        ),
+        package(
+            "symfony/polyfill-mbstring",
+            Autoload::new()
+                .add_psr4("Symfony\\Polyfill\\Mbstring\\", "")
+                .add_file("bootstrap.php"),
+            &[],
+            false,
+        ),
+        package(
+            "legacy/twig",
+            Autoload::new().add_psr0("Twig_", "lib/"),
+            &[],
+            false,
+        ),
+        package(
+            "guzzlehttp/guzzle",
+            Autoload::new()
+                .add_psr4("GuzzleHttp\\", "src/")
+                .add_file("src/functions_include.php"),
+            &["symfony/deprecation-contracts"],
+            false,
+        ),
+        package(
+            "symfony/deprecation-contracts",
+            Autoload::new().add_file("function.php"),
+            &[],
+            false,
+        ),
+    ]
+}
+
+fn root_autoload(dev_mode: bool) -> Autoload {
+    let mut autoload = Autoload::new()
+        .add_psr4("App\\", "./src/")
+        .add_psr4("", "fallback/")
+        .add_psr0("Legacy_", "lib/")
+        .add_file("src/helpers.php");
+
+    if dev_mode {
+        autoload.merge(
+            Autoload::new()
+                .add_psr4("App\\Tests\\", "tests/")
+                .add_psr4("App\\", "dev-src/")
+                .add_file("tests/functions.php"),
+        );
+    }
+
+    autoload
+}
+
+fn assert_golden(dev_mode: bool, fixture: &str) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = AutoloadConfig {
+        vendor_dir: temp_dir.path().join("vendor"),
+        base_dir: temp_dir.path().to_path_buf(),
+        suffix: Some(SUFFIX.to_string()),
+        ..Default::default()
+    };
+    let root = RootPackageInfo {
+        name: "acme/app".to_string(),
+        pretty_version: "dev-main".to_string(),
+        version: "dev-main".to_string(),
+        package_type: "project".to_string(),
+        dev_mode,
+        ..Default::default()
+    };
+
+    AutoloadGenerator::new(config)
+        .generate(&packages(), Some(&root_autoload(dev_mode)), Some(&root))
+        .unwrap();
+
+    let fixture_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/autoload").join(fixture);
+    for file in GOLDEN_FILES {
+        let expected = fs::read_to_string(fixture_dir.join(file)).unwrap();
+        let actual = fs::read_to_string(temp_dir.path().join("vendor/composer").join(file)).unwrap();
+        assert_eq!(actual, expected, "{}/{} differs from Composer output", fixture, file);
+    }
+}
+
+#[test]
+fn test_autoload_matches_composer_dev() {
+    assert_golden(true, "dev");
+}
+
+#[test]
+fn test_autoload_matches_composer_no_dev() {
+    assert_golden(false, "no-dev");
+}
+
+#[test]
+fn test_autoload_empty_psr4_is_valid_php() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = AutoloadConfig {
+        vendor_dir: temp_dir.path().join("vendor"),
+        base_dir: temp_dir.path().to_path_buf(),
+        suffix: Some(SUFFIX.to_string()),
+        ..Default::default()
+    };
+
+    AutoloadGenerator::new(config).generate(&[], None, None).unwrap();
+
+    let psr4 = fs::read_to_string(temp_dir.path().join("vendor/composer/autoload_psr4.php")).unwrap();
+    assert!(psr4.ends_with("return array(\n);\n"));
+    assert!(!temp_dir.path().join("vendor/composer/autoload_files.php").exists());
+}
@@ -3,12 +3,16 @@
 /// These tests verify that the configuration system works correctly
 /// when loading from files and environment variables.
 
-use pox_pm::config::{Config, ConfigLoader, PreferredInstall, StoreAuths};
+use pox_pm::config::{Config, ConfigLoader, ConfigSource, PreferredInstall, StoreAuths};
 use std::env;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Mutex;
 use tempfile::TempDir;
 
+/// Serializes tests that modify process environment variables
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
 #[test]
 fn test_config_defaults() {
     let config = Config::default();
@@ -58,6 +62,7 @@ fn test_config_loader_env_disabled() {
 
 #[test]
 fn test_config_loader_env_enabled() {
+    let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     // Set test environment variables
     env::set_var("COMPOSER_TEST_VAR", "test_value");
     env::set_var("COMPOSER_VENDOR_DIR", "/custom/vendor");
@@ -156,6 +161,7 @@ fn test_build_config_with_project_dir() {
 
 #[test]
 fn test_config_env_overrides() {
+    let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     env::set_var("COMPOSER_PROCESS_TIMEOUT", "900");
     env::set_var("COMPOSER_VENDOR_DIR", "/override/vendor");
 
@@ -258,3 +264,99 @@ fn test_cache_configuration() {
     assert!(config.cache_repo_dir.is_some());
     assert!(config.cache_vcs_dir.is_some());
 }
+
+#[test]
+fn test_config_precedence_env_over_project_over_global() {
+    let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let home = TempDir::new().unwrap();
+    fs::write(
+        home.path().join("config.json"),
+        r#"{"config": {"vendor-dir": "global-vendor", "bin-dir": "global-bin", "process-timeout": 100, "cache-files-dir": "/global/files"}}"#,
+    )
+    .unwrap();
+
+    let project = TempDir::new().unwrap();
+    fs::write(
+        project.path().join("composer.json"),
+        r#"{"config": {"vendor-dir": "project-vendor", "process-timeout": 200}}"#,
+    )
+    .unwrap();
+
+    env::set_var("COMPOSER_HOME", home.path());
+
+    // Project overrides global, global overrides defaults
+    let config = Config::build(Some(project.path()), true).unwrap();
+    assert_eq!(config.vendor_dir, PathBuf::from("project-vendor"));
+    assert_eq!(config.bin_dir, PathBuf::from("global-bin"));
+    assert_eq!(config.process_timeout, 200);
+    assert_eq!(config.get_source("vendor-dir"), Some(&ConfigSource::Project));
+    assert_eq!(config.get_source("bin-dir"), Some(&ConfigSource::Global));
+    assert_eq!(config.get_source("sort-packages"), Some(&ConfigSource::Default));
+    assert_eq!(config.home_dir.as_deref(), Some(home.path()));
+
+    // Environment overrides everything
+    env::set_var("COMPOSER_VENDOR_DIR", "env-vendor");
+    env::set_var("COMPOSER_PROCESS_TIMEOUT", "0");
+    env::set_var("COMPOSER_CACHE_FILES_DIR", "/env/files");
+    env::set_var("COMPOSER_DATA_DIR", "/env/data");
+    env::set_var("COMPOSER_AUDIT_ABANDONED", "report");
+
+    let config = Config::build(Some(project.path()), true).unwrap();
+    assert_eq!(config.vendor_dir, PathBuf::from("env-vendor"));
+    assert_eq!(config.process_timeout, 0);
+    assert_eq!(config.cache_files_dir, Some(PathBuf::from("/env/files")));
+    assert_eq!(config.data_dir, Some(PathBuf::from("/env/data")));
+    assert_eq!(config.audit.abandoned, "report");
+    assert_eq!(
+        config.get_source("vendor-dir"),
+        Some(&ConfigSource::Environment("COMPOSER_VENDOR_DIR".to_string()))
+    );
+    assert_eq!(
+        config.get_source("cache-files-dir"),
+        Some(&ConfigSource::Environment("COMPOSER_CACHE_FILES_DIR".to_string()))
+    );
+
+    // Without environment support only the files count
+    let config = Config::build(Some(project.path()), false).unwrap();
+    assert_eq!(config.vendor_dir, PathBuf::from("project-vendor"));
+
+    for var in [
+        "COMPOSER_HOME",
+        "COMPOSER_VENDOR_DIR",
+        "COMPOSER_PROCESS_TIMEOUT",
+        "COMPOSER_CACHE_FILES_DIR",
+        "COMPOSER_DATA_DIR",
+        "COMPOSER_AUDIT_ABANDONED",
+    ] {
+        env::remove_var(var);
+    }
+}
+
+#[test]
+fn test_composer_file_env() {
+    let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let project = TempDir::new().unwrap();
+    fs::write(project.path().join("composer.json"), r#"{"config": {"vendor-dir": "default-vendor"}}"#).unwrap();
+    fs::write(project.path().join("composer-other.json"), r#"{"config": {"vendor-dir": "other-vendor"}}"#).unwrap();
+
+    env::set_var("COMPOSER", "composer-other.json");
+    env::set_var("COMPOSER_MEMORY_LIMIT", "1G");
+
+    let loader = ConfigLoader::new(true);
+    assert_eq!(loader.get_composer_file(), "composer-other.json");
+    assert_eq!(loader.get_lock_file(), "composer-other.lock");
+    assert_eq!(loader.get_memory_limit(), Some("1G".to_string()));
+
+    let config = Config::build(Some(project.path()), true).unwrap();
+    assert_eq!(config.vendor_dir, PathBuf::from("other-vendor"));
+
+    env::set_var("COMPOSER", "manifest");
+    assert_eq!(ConfigLoader::new(true).get_lock_file(), "manifest.lock");
+
+    env::remove_var("COMPOSER");
+    env::remove_var("COMPOSER_MEMORY_LIMIT");
+
+    assert_eq!(ConfigLoader::new(true).get_composer_file(), "composer.json");
+}
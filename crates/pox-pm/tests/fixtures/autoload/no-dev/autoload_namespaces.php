@@ -0,0 +1,11 @@
+<?php
+
+// autoload_namespaces.php @generated by Composer
+
+$vendorDir = dirname(__DIR__);
+$baseDir = dirname($vendorDir);
+
+return array(
+    'Twig_' => array($vendorDir . '/legacy/twig/lib'),
+    'Legacy_' => array($baseDir . '/lib'),
+);
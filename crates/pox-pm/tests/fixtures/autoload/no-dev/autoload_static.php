@@ -0,0 +1,87 @@
+<?php
+
+// autoload_static.php @generated by Composer
+
+namespace Composer\Autoload;
+
+class ComposerStaticInitc9f3d6a7b0e1f2a3c4d5e6f7a8b9c0d1
+{
+    public static $files = array (
+        '6e3fae29631ef280660b3cdad06f25a8' => __DIR__ . '/..' . '/symfony/deprecation-contracts/function.php',
+        '0e6d7bf4a5811bfa5cf40c5ccd6fae6a' => __DIR__ . '/..' . '/symfony/polyfill-mbstring/bootstrap.php',
+        '37a3dc5111fe8f707ab4c132ef1dbc62' => __DIR__ . '/..' . '/guzzlehttp/guzzle/src/functions_include.php',
+        'c2c24b75c93a963f79f38fba75054e3d' => __DIR__ . '/../..' . '/src/helpers.php',
+    );
+
+    public static $prefixLengthsPsr4 = array (
+        'S' => 
+        array (
+            'Symfony\\Polyfill\\Mbstring\\' => 26,
+            'Symfony\\Component\\Console\\' => 26,
+        ),
+        'G' => 
+        array (
+            'GuzzleHttp\\' => 11,
+        ),
+        'A' => 
+        array (
+            'App\\' => 4,
+        ),
+    );
+
+    public static $prefixDirsPsr4 = array (
+        'Symfony\\Polyfill\\Mbstring\\' => 
+        array (
+            0 => __DIR__ . '/..' . '/symfony/polyfill-mbstring',
+        ),
+        'Symfony\\Component\\Console\\' => 
+        array (
+            0 => __DIR__ . '/..' . '/symfony/console',
+        ),
+        'GuzzleHttp\\' => 
+        array (
+            0 => __DIR__ . '/..' . '/guzzlehttp/guzzle/src',
+        ),
+        'App\\' => 
+        array (
+            0 => __DIR__ . '/../..' . '/src',
+        ),
+    );
+
+    public static $fallbackDirsPsr4 = array (
+        0 => __DIR__ . '/../..' . '/fallback',
+    );
+
+    public static $prefixesPsr0 = array (
+        'T' => 
+        array (
+            'Twig_' => 
+            array (
+                0 => __DIR__ . '/..' . '/legacy/twig/lib',
+            ),
+        ),
+        'L' => 
+        array (
+            'Legacy_' => 
+            array (
+                0 => __DIR__ . '/../..' . '/lib',
+            ),
+        ),
+    );
+
+    public static $classMap = array (
+        'Composer\\InstalledVersions' => __DIR__ . '/..' . '/composer/InstalledVersions.php',
+    );
+
+    public static function getInitializer(ClassLoader $loader)
+    {
+        return \Closure::bind(function () use ($loader) {
+            $loader->prefixLengthsPsr4 = ComposerStaticInitc9f3d6a7b0e1f2a3c4d5e6f7a8b9c0d1::$prefixLengthsPsr4;
+            $loader->prefixDirsPsr4 = ComposerStaticInitc9f3d6a7b0e1f2a3c4d5e6f7a8b9c0d1::$prefixDirsPsr4;
+            $loader->fallbackDirsPsr4 = ComposerStaticInitc9f3d6a7b0e1f2a3c4d5e6f7a8b9c0d1::$fallbackDirsPsr4;
+            $loader->prefixesPsr0 = ComposerStaticInitc9f3d6a7b0e1f2a3c4d5e6f7a8b9c0d1::$prefixesPsr0;
+            $loader->classMap = ComposerStaticInitc9f3d6a7b0e1f2a3c4d5e6f7a8b9c0d1::$classMap;
+
+        }, null, ClassLoader::class);
+    }
+}
@@ -0,0 +1,39 @@
+/*
+ * C ABI of pox-semver, Composer compatible version matching.
+ *
+ * Build with: cargo build -p pox-semver-capi --release
+ *
+ * Invalid input and NULL pointers make predicates return false and functions
+ * returning strings return NULL. Returned strings must be released with
+ * pox_semver_string_free().
+ */
+
+#ifndef POX_SEMVER_H
+#define POX_SEMVER_H
+
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Whether version satisfies constraint, e.g. "1.2.3" and "^1.2 || ^2.0" */
+bool pox_semver_satisfies(const char *version, const char *constraint);
+
+/* Compare two versions with an operator such as ">=" or "!=" */
+bool pox_semver_compare(const char *version1, const char *op, const char *version2);
+
+/* Normalize a version, "v1.2" becomes "1.2.0.0" */
+char *pox_semver_normalize(const char *version);
+
+/* Normalize a constraint, "^1.2" becomes "[>= 1.2.0.0-dev < 2.0.0.0-dev]" */
+char *pox_semver_normalize_constraint(const char *constraint);
+
+/* Release a string returned by this library, NULL is ignored */
+void pox_semver_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* POX_SEMVER_H */
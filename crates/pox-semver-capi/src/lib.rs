@@ -0,0 +1,151 @@
+//! C ABI of pox-semver for non-Rust tooling
+//!
+//! The functions mirror [`Semver`] and [`VersionParser`], the matching C
+//! declarations are in `include/pox_semver.h`. Invalid input, including null
+//! pointers and strings that are not UTF-8, makes predicates return `false`
+//! and functions returning strings return null. A panic is caught and handled
+//! like invalid input instead of unwinding into the caller, unless the library
+//! is built with `panic = "abort"`. Returned strings are owned by the caller
+//! and must be released with [`pox_semver_string_free`].
+
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, UnwindSafe};
+
+use pox_semver::{Comparator, Semver, VersionParser};
+
+/// Borrow a C string as `&str`, `None` for null pointers or invalid UTF-8
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+/// Hand a string to the caller, null if it contains a NUL byte
+fn into_raw(s: String) -> *mut c_char {
+    CString::new(s).map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// Run `f`, returning `fallback` if it panics so the panic never crosses the C boundary
+fn guard<T>(fallback: T, f: impl FnOnce() -> T + UnwindSafe) -> T {
+    panic::catch_unwind(f).unwrap_or(fallback)
+}
+
+/// Whether `version` satisfies `constraint`, e.g. `"1.2.3"` and `"^1.2 || ^2.0"`
+///
+/// # Safety
+///
+/// Both arguments must be null or point to NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn pox_semver_satisfies(version: *const c_char, constraint: *const c_char) -> bool {
+    guard(false, || match (to_str(version), to_str(constraint)) {
+        (Some(version), Some(constraint)) => Semver::satisfies(version, constraint),
+        _ => false,
+    })
+}
+
+/// Compare two versions with an operator such as `">="` or `"!="`
+///
+/// # Safety
+///
+/// All arguments must be null or point to NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn pox_semver_compare(
+    version1: *const c_char,
+    operator: *const c_char,
+    version2: *const c_char,
+) -> bool {
+    guard(false, || match (to_str(version1), to_str(operator), to_str(version2)) {
+        (Some(version1), Some(operator), Some(version2)) => Comparator::compare(version1, operator, version2),
+        _ => false,
+    })
+}
+
+/// Normalize a version, `"v1.2"` becomes `"1.2.0.0"`, null if it is invalid
+///
+/// # Safety
+///
+/// `version` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pox_semver_normalize(version: *const c_char) -> *mut c_char {
+    guard(std::ptr::null_mut(), || match to_str(version).map(|v| VersionParser::new().normalize(v)) {
+        Some(Ok(normalized)) => into_raw(normalized),
+        _ => std::ptr::null_mut(),
+    })
+}
+
+/// Normalize a constraint, `"^1.2"` becomes `"[>= 1.2.0.0-dev < 2.0.0.0-dev]"`, null if it is invalid
+///
+/// # Safety
+///
+/// `constraint` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pox_semver_normalize_constraint(constraint: *const c_char) -> *mut c_char {
+    guard(std::ptr::null_mut(), || match to_str(constraint).map(|c| VersionParser::new().parse_constraints(c)) {
+        Some(Ok(parsed)) => into_raw(parsed.to_string()),
+        _ => std::ptr::null_mut(),
+    })
+}
+
+/// Release a string returned by this library, null is ignored
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by this library that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn pox_semver_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        guard((), || drop(CString::from_raw(s)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take(s: *mut c_char) -> Option<String> {
+        if s.is_null() {
+            return None;
+        }
+        let owned = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+        unsafe { pox_semver_string_free(s) };
+        Some(owned)
+    }
+
+    #[test]
+    fn test_satisfies_and_compare() {
+        unsafe {
+            assert!(pox_semver_satisfies(c"1.2.3".as_ptr(), c"^1.2".as_ptr()));
+            assert!(!pox_semver_satisfies(c"2.0.0".as_ptr(), c"^1.2".as_ptr()));
+            assert!(!pox_semver_satisfies(c"1.2.3".as_ptr(), c"not a constraint".as_ptr()));
+            assert!(!pox_semver_satisfies(std::ptr::null(), c"^1.2".as_ptr()));
+
+            assert!(pox_semver_compare(c"1.10".as_ptr(), c">".as_ptr(), c"1.9".as_ptr()));
+            assert!(!pox_semver_compare(c"1.0.0".as_ptr(), c"!=".as_ptr(), c"1.0.0".as_ptr()));
+            assert!(!pox_semver_compare(c"1.0".as_ptr(), std::ptr::null(), c"1.0".as_ptr()));
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        unsafe {
+            assert_eq!(take(pox_semver_normalize(c"v1.2".as_ptr())).as_deref(), Some("1.2.0.0"));
+            assert_eq!(take(pox_semver_normalize(c"foo bar".as_ptr())), None);
+            assert_eq!(take(pox_semver_normalize(std::ptr::null())), None);
+
+            assert_eq!(
+                take(pox_semver_normalize_constraint(c"^1.2".as_ptr())).as_deref(),
+                Some("[>= 1.2.0.0-dev < 2.0.0.0-dev]")
+            );
+            assert_eq!(take(pox_semver_normalize_constraint(c"".as_ptr())), None);
+
+            pox_semver_string_free(std::ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_guard_catches_panics() {
+        assert!(!guard(false, || panic!("boom")));
+        assert!(guard(false, || true));
+    }
+}
@@ -1,6 +1,23 @@
 //! Version comparison utilities
+//!
+//! Like Composer's `Comparator`, but versions can be passed as written, e.g.
+//! `v1.2` or `2.0.0-RC1`. They are normalized like [`VersionParser::normalize`]
+//! before comparing, so `1.0` equals `1.0.0.0` and `v2.1` is greater than `2.0.5`.
+//! Strings that are not valid versions are compared as given.
+//!
+//! ```
+//! use pox_semver::Comparator;
+//!
+//! assert!(Comparator::greater_than("v1.10.0", "1.9"));
+//! assert!(Comparator::equal_to("1.0", "1.0.0.0"));
+//! assert!(Comparator::compare("2.0.0-beta1", "<", "2.0.0"));
+//! ```
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
 
 use crate::constraint::constraint::php_version_compare;
+use crate::VersionParser;
 
 /// Comparator for comparing version strings
 pub struct Comparator;
@@ -36,8 +53,17 @@ impl Comparator {
         Self::compare(version1, "!=", version2)
     }
 
-    /// Compare version1 to version2 using the given operator
+    /// Compare version1 to version2 using the given operator.
+    ///
+    /// Supported operators are `>`, `>=`, `<`, `<=`, `==`, `=`, `!=` and `<>`,
+    /// any other operator never matches.
     pub fn compare(version1: &str, operator: &str, version2: &str) -> bool {
+        Self::compare_normalized(&normalize(version1), operator, &normalize(version2))
+    }
+
+    /// Like [`Comparator::compare`] for versions that are already normalized,
+    /// e.g. `Package::version`, skipping the normalization
+    pub fn compare_normalized(version1: &str, operator: &str, version2: &str) -> bool {
         // Handle dev branches specially
         let v1_is_branch = version1.starts_with("dev-");
         let v2_is_branch = version2.starts_with("dev-");
@@ -62,6 +88,26 @@ impl Comparator {
 
         php_version_compare(version1, version2, operator)
     }
+
+    /// Ordering of version1 relative to version2, for sorting with `sort_by`
+    pub fn ordering(version1: &str, version2: &str) -> Ordering {
+        let (version1, version2) = (normalize(version1), normalize(version2));
+
+        if Self::compare_normalized(&version1, "<", &version2) {
+            Ordering::Less
+        } else if Self::compare_normalized(&version1, ">", &version2) {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }
+}
+
+fn normalize(version: &str) -> Cow<'_, str> {
+    match VersionParser::new().normalize(version) {
+        Ok(normalized) => Cow::Owned(normalized),
+        Err(_) => Cow::Borrowed(version),
+    }
 }
 
 #[cfg(test)]
@@ -163,6 +209,26 @@ mod tests {
         assert!(Comparator::compare("1.25.0", "<>", "1.26.0"));
     }
 
+    #[test]
+    fn test_compare_unnormalized() {
+        assert!(Comparator::equal_to("1.0", "1.0.0.0"));
+        assert!(Comparator::equal_to("v1.2.3", "1.2.3"));
+        assert!(Comparator::greater_than("v1.10.0", "1.9"));
+        assert!(Comparator::less_than("2.0.0-RC1", "2.0"));
+        assert!(Comparator::less_than("1.0.0", "1.x-dev"));
+        assert!(!Comparator::compare("1.0", "~", "1.0"));
+
+        // Without normalization PHP's version_compare() sees 1.0 before 1.0.0
+        assert!(Comparator::compare_normalized("1.0", "<", "1.0.0"));
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut versions = vec!["1.10.0", "v1.2", "2.0.0-beta1", "dev-main", "1.2.0.0", "2.0"];
+        versions.sort_by(|a, b| Comparator::ordering(a, b));
+        assert_eq!(versions, vec!["dev-main", "v1.2", "1.2.0.0", "1.10.0", "2.0.0-beta1", "2.0"]);
+    }
+
     #[test]
     fn test_compare_with_stability() {
         assert!(Comparator::compare("1.25.0-beta2.1", "<", "1.25.0-b.3"));
@@ -0,0 +1,434 @@
+//! The VersionParserTest and ConstraintTest fixtures of composer/semver.
+//!
+//! Each table pairs an input with the result composer/semver gives for it,
+//! [`check`] runs them all and reports where this crate answers differently.
+//! Other implementations of Composer's version rules can run the same suite
+//! against their own parser by calling the table checks with it:
+//!
+//! ```
+//! let divergences = pox_semver::conformance::check();
+//! assert!(divergences.is_empty(), "{:?}", divergences);
+//! ```
+
+use std::fmt;
+
+use crate::constraint::{Constraint, ConstraintInterface};
+use crate::{Stability, VersionParser};
+
+/// `VersionParser::normalize()` inputs with the normalized version, `None` if they are rejected
+pub const NORMALIZE: &[(&str, Option<&str>)] = &[
+    ("1.0.0", Some("1.0.0.0")),
+    ("1.2.3.4", Some("1.2.3.4")),
+    ("1.0.0RC1dev", Some("1.0.0.0-RC1-dev")),
+    ("1.0.0-rC15-dev", Some("1.0.0.0-RC15-dev")),
+    ("1.0.0.RC.15-dev", Some("1.0.0.0-RC15-dev")),
+    ("1.0.0-rc1", Some("1.0.0.0-RC1")),
+    ("1.0.0.pl3-dev", Some("1.0.0.0-patch3-dev")),
+    ("1.0-dev", Some("1.0.0.0-dev")),
+    ("0", Some("0.0.0.0")),
+    ("99999", Some("99999.0.0.0")),
+    ("10.4.13-beta", Some("10.4.13.0-beta")),
+    ("10.4.13beta2", Some("10.4.13.0-beta2")),
+    ("10.4.13beta.2", Some("10.4.13.0-beta2")),
+    ("v1.13.11-beta.0", Some("1.13.11.0-beta0")),
+    ("1.13.11.0-beta0", Some("1.13.11.0-beta0")),
+    ("10.4.13-b", Some("10.4.13.0-beta")),
+    ("10.4.13-b5", Some("10.4.13.0-beta5")),
+    ("v1.0.0", Some("1.0.0.0")),
+    ("2010.01", Some("2010.01.0.0")),
+    ("2010.01.02", Some("2010.01.02.0")),
+    ("2010.1.555", Some("2010.1.555.0")),
+    ("2010.10.200", Some("2010.10.200.0")),
+    ("v20100102", Some("20100102")),
+    ("2010-01-02", Some("2010.01.02")),
+    ("2010-01-02.5", Some("2010.01.02.5")),
+    ("20100102-203040", Some("20100102.203040")),
+    ("20100102203040-10", Some("20100102203040.10")),
+    ("20100102-203040-p1", Some("20100102.203040-patch1")),
+    ("dev-master", Some("dev-master")),
+    ("master", Some("dev-master")),
+    ("dev-trunk", Some("dev-trunk")),
+    ("1.x-dev", Some("1.9999999.9999999.9999999-dev")),
+    ("dev-feature-foo", Some("dev-feature-foo")),
+    ("DEV-FOOBAR", Some("dev-FOOBAR")),
+    ("dev-feature/foo", Some("dev-feature/foo")),
+    ("dev-feature+issue-1", Some("dev-feature+issue-1")),
+    ("dev-master as 1.0.0", Some("dev-master")),
+    ("dev-load-varnish-only-when-used as ^2.0", Some("dev-load-varnish-only-when-used")),
+    ("dev-load-varnish-only-when-used@dev as ^2.0@dev", Some("dev-load-varnish-only-when-used")),
+    ("1.0.0+foo@dev", Some("1.0.0.0")),
+    ("dev-load-varnish-only-when-used@stable", Some("dev-load-varnish-only-when-used")),
+    ("1.0.0-beta.5+foo", Some("1.0.0.0-beta5")),
+    ("1.0.0+foo", Some("1.0.0.0")),
+    ("1.0.0-alpha.3.1+foo", Some("1.0.0.0-alpha3.1")),
+    ("1.0.0-alpha2.1+foo", Some("1.0.0.0-alpha2.1")),
+    ("1.0.0-alpha-2.1-3+foo", Some("1.0.0.0-alpha2.1-3")),
+    ("1.0.0+foo as 2.0", Some("1.0.0.0")),
+    ("00.01.03.04", Some("00.01.03.04")),
+    ("000.001.003.004", Some("000.001.003.004")),
+    ("0.000.103.204", Some("0.000.103.204")),
+    ("0700", Some("0700.0.0.0")),
+    ("041.x-dev", Some("041.9999999.9999999.9999999-dev")),
+    ("dev-041.003", Some("dev-041.003")),
+    ("dev-1.0.0-dev<1.0.5-dev", Some("dev-1.0.0-dev<1.0.5-dev")),
+    ("dev-foo bar", Some("dev-foo bar")),
+    (" 1.0.0", Some("1.0.0.0")),
+    ("1.0.0 ", Some("1.0.0.0")),
+    ("", None),
+    ("a", None),
+    ("1.0.0-meh", None),
+    ("1.0.0.0.0", None),
+    ("feature-foo", None),
+    ("1.0.0+foo bar", None),
+    ("1.0.1-SNAPSHOT", None),
+    ("1.0.0<1.0.5-dev", None),
+    ("1.0.0-dev<1.0.5-dev", None),
+    ("foo bar-dev", None),
+    ("1.0 .2", None),
+    (" as ", None),
+    (" as 1.2", None),
+    ("^", None),
+    ("^8 || ^", None),
+    ("~", None),
+    ("~1 ~", None),
+    ("~1", None),
+    ("^1", None),
+    ("1.*", None),
+    ("20100102.0.3.4", None),
+];
+
+/// `VersionParser::normalize_branch()` inputs with the normalized branch version
+pub const NORMALIZE_BRANCH: &[(&str, &str)] = &[
+    ("v1.x", "1.9999999.9999999.9999999-dev"),
+    ("v1.*", "1.9999999.9999999.9999999-dev"),
+    ("v1.0", "1.0.9999999.9999999-dev"),
+    ("2.0", "2.0.9999999.9999999-dev"),
+    ("v1.0.x", "1.0.9999999.9999999-dev"),
+    ("v1.0.3.*", "1.0.3.9999999-dev"),
+    ("v2.4.0", "2.4.0.9999999-dev"),
+    ("2.4.4", "2.4.4.9999999-dev"),
+    ("master", "dev-master"),
+    ("trunk", "dev-trunk"),
+    ("feature-a", "dev-feature-a"),
+    ("FOOBAR", "dev-FOOBAR"),
+    ("feature+issue-1", "dev-feature+issue-1"),
+];
+
+/// `VersionParser::parse_stability()` inputs with their stability
+pub const PARSE_STABILITY: &[(&str, Stability)] = &[
+    ("1", Stability::Stable),
+    ("1.0", Stability::Stable),
+    ("3.2.1", Stability::Stable),
+    ("v3.2.1", Stability::Stable),
+    ("v2.0.x-dev", Stability::Dev),
+    ("v2.0.x-dev#abc123", Stability::Dev),
+    ("v2.0.x-dev#trunk/@123", Stability::Dev),
+    ("3.0-RC2", Stability::RC),
+    ("dev-master", Stability::Dev),
+    ("3.1.2-dev", Stability::Dev),
+    ("dev-feature+issue-1", Stability::Dev),
+    ("3.1.2-p1", Stability::Stable),
+    ("3.1.2-pl2", Stability::Stable),
+    ("3.1.2-patch", Stability::Stable),
+    ("3.1.2-alpha5", Stability::Alpha),
+    ("3.1.2-beta", Stability::Beta),
+    ("2.0B1", Stability::Beta),
+    ("1.2.0a1", Stability::Alpha),
+    ("1.2_a1", Stability::Alpha),
+    ("2.0.0rc1", Stability::RC),
+    ("1.0.0-alpha11+cs-1.1.0", Stability::Alpha),
+];
+
+/// `VersionParser::parse_constraints()` inputs with the string form of the
+/// parsed constraint, `None` if they are rejected
+pub const PARSE_CONSTRAINTS: &[(&str, Option<&str>)] = &[
+    // Simple constraints
+    ("*", Some("*")),
+    ("*.*", Some(">= 0.0.0.0-dev")),
+    ("v*.*", Some(">= 0.0.0.0-dev")),
+    ("*.x.*", Some(">= 0.0.0.0-dev")),
+    ("x.X.x.*", Some(">= 0.0.0.0-dev")),
+    ("<>1.0.0", Some("!= 1.0.0.0")),
+    ("!=1.0.0", Some("!= 1.0.0.0")),
+    (">1.0.0", Some("> 1.0.0.0")),
+    ("<1.2.3.4", Some("< 1.2.3.4-dev")),
+    ("<=1.2.3", Some("<= 1.2.3.0")),
+    (">=1.2.3", Some(">= 1.2.3.0-dev")),
+    ("=1.2.3", Some("== 1.2.3.0")),
+    ("==1.2.3", Some("== 1.2.3.0")),
+    ("1.2.3", Some("== 1.2.3.0")),
+    ("=1.0", Some("== 1.0.0.0")),
+    ("1.2.3b5", Some("== 1.2.3.0-beta5")),
+    ("1.2.3a1", Some("== 1.2.3.0-alpha1")),
+    ("1.2.3p1234", Some("== 1.2.3.0-patch1234")),
+    ("1.2.3pl1234", Some("== 1.2.3.0-patch1234")),
+    (">= 1.2.3", Some(">= 1.2.3.0-dev")),
+    ("< 1.2.3", Some("< 1.2.3.0-dev")),
+    ("> 1.2.3", Some("> 1.2.3.0")),
+    (">=dev-master", Some(">= dev-master")),
+    ("dev-master", Some("== dev-master")),
+    ("dev-feature-a", Some("== dev-feature-a")),
+    ("dev-some-fix", Some("== dev-some-fix")),
+    ("dev-CAPS", Some("== dev-CAPS")),
+    ("dev-master as 1.0.0", Some("== dev-master")),
+    ("<1.2.3.4-stable", Some("< 1.2.3.4")),
+    (">=1.2.3.4-stable", Some(">= 1.2.3.4")),
+    ("foobar-dev", Some("== dev-foobar")),
+    // Wildcards
+    ("v2.*", Some("[>= 2.0.0.0-dev < 3.0.0.0-dev]")),
+    ("2.*.*", Some("[>= 2.0.0.0-dev < 3.0.0.0-dev]")),
+    ("20.*", Some("[>= 20.0.0.0-dev < 21.0.0.0-dev]")),
+    ("20.*.*", Some("[>= 20.0.0.0-dev < 21.0.0.0-dev]")),
+    ("2.0.*", Some("[>= 2.0.0.0-dev < 2.1.0.0-dev]")),
+    ("2.x", Some("[>= 2.0.0.0-dev < 3.0.0.0-dev]")),
+    ("2.x.x", Some("[>= 2.0.0.0-dev < 3.0.0.0-dev]")),
+    ("2.2.x", Some("[>= 2.2.0.0-dev < 2.3.0.0-dev]")),
+    ("2.10.X", Some("[>= 2.10.0.0-dev < 2.11.0.0-dev]")),
+    ("2.1.3.*", Some("[>= 2.1.3.0-dev < 2.1.4.0-dev]")),
+    ("0.*", Some("< 1.0.0.0-dev")),
+    ("0.*.*", Some("< 1.0.0.0-dev")),
+    ("0.x", Some("< 1.0.0.0-dev")),
+    ("0.x.x", Some("< 1.0.0.0-dev")),
+    // Tilde
+    ("~v1", Some("[>= 1.0.0.0-dev < 2.0.0.0-dev]")),
+    ("~1.0", Some("[>= 1.0.0.0-dev < 2.0.0.0-dev]")),
+    ("~1.0.0", Some("[>= 1.0.0.0-dev < 1.1.0.0-dev]")),
+    ("~1.2", Some("[>= 1.2.0.0-dev < 2.0.0.0-dev]")),
+    ("~1.2.3", Some("[>= 1.2.3.0-dev < 1.3.0.0-dev]")),
+    ("~1.2.3.4", Some("[>= 1.2.3.4-dev < 1.2.4.0-dev]")),
+    ("~1.2-beta", Some("[>= 1.2.0.0-beta < 2.0.0.0-dev]")),
+    ("~1.2-b2", Some("[>= 1.2.0.0-beta2 < 2.0.0.0-dev]")),
+    ("~1.2-BETA2", Some("[>= 1.2.0.0-beta2 < 2.0.0.0-dev]")),
+    ("~1.2.2-dev", Some("[>= 1.2.2.0-dev < 1.3.0.0-dev]")),
+    ("~1.2.2-stable", Some("[>= 1.2.2.0 < 1.3.0.0-dev]")),
+    ("~1.2.3-beta", Some("[>= 1.2.3.0-beta < 1.3.0.0-dev]")),
+    ("~201", Some("[>= 201.0.0.0-dev < 202.0.0.0-dev]")),
+    // Caret
+    ("^v1", Some("[>= 1.0.0.0-dev < 2.0.0.0-dev]")),
+    ("^0", Some("[>= 0.0.0.0-dev < 1.0.0.0-dev]")),
+    ("^0.0", Some("[>= 0.0.0.0-dev < 0.1.0.0-dev]")),
+    ("^1.2", Some("[>= 1.2.0.0-dev < 2.0.0.0-dev]")),
+    ("^1.2.3-beta.2", Some("[>= 1.2.3.0-beta2 < 2.0.0.0-dev]")),
+    ("^1.2.3.4", Some("[>= 1.2.3.4-dev < 2.0.0.0-dev]")),
+    ("^1.2.3", Some("[>= 1.2.3.0-dev < 2.0.0.0-dev]")),
+    ("^0.2.3", Some("[>= 0.2.3.0-dev < 0.3.0.0-dev]")),
+    ("^0.2", Some("[>= 0.2.0.0-dev < 0.3.0.0-dev]")),
+    ("^0.2.0", Some("[>= 0.2.0.0-dev < 0.3.0.0-dev]")),
+    ("^0.0.3", Some("[>= 0.0.3.0-dev < 0.0.4.0-dev]")),
+    ("^0.0.3-alpha", Some("[>= 0.0.3.0-alpha < 0.0.4.0-dev]")),
+    ("^0.0.3-dev", Some("[>= 0.0.3.0-dev < 0.0.4.0-dev]")),
+    ("^0.0.3-stable", Some("[>= 0.0.3.0 < 0.0.4.0-dev]")),
+    // Hyphen ranges
+    ("v1 - v2", Some("[>= 1.0.0.0-dev < 3.0.0.0-dev]")),
+    ("1.2.3 - 2.3.4.5", Some("[>= 1.2.3.0-dev <= 2.3.4.5]")),
+    ("1.2-beta - 2.3", Some("[>= 1.2.0.0-beta < 2.4.0.0-dev]")),
+    ("1.2-beta - 2.3-dev", Some("[>= 1.2.0.0-beta <= 2.3.0.0-dev]")),
+    ("1.2-RC - 2.3.1", Some("[>= 1.2.0.0-RC <= 2.3.1.0]")),
+    ("1.2.3-alpha - 2.3-RC", Some("[>= 1.2.3.0-alpha <= 2.3.0.0-RC]")),
+    ("1 - 2.0", Some("[>= 1.0.0.0-dev < 2.1.0.0-dev]")),
+    ("1 - 2.1", Some("[>= 1.0.0.0-dev < 2.2.0.0-dev]")),
+    ("1.2 - 2.1.0", Some("[>= 1.2.0.0-dev <= 2.1.0.0]")),
+    ("1.3 - 2.1.3", Some("[>= 1.3.0.0-dev <= 2.1.3.0]")),
+    ("1.0 - 2.0", Some("[>= 1.0.0.0-dev < 2.1.0.0-dev]")),
+    // Conjunctive and disjunctive constraints
+    (">2.0,<=3.0", Some("[> 2.0.0.0 <= 3.0.0.0]")),
+    (">2.0 <=3.0", Some("[> 2.0.0.0 <= 3.0.0.0]")),
+    (">2.0  <=3.0", Some("[> 2.0.0.0 <= 3.0.0.0]")),
+    (">2.0, <=3.0", Some("[> 2.0.0.0 <= 3.0.0.0]")),
+    (">2.0 ,<=3.0", Some("[> 2.0.0.0 <= 3.0.0.0]")),
+    (">2.0 , <=3.0", Some("[> 2.0.0.0 <= 3.0.0.0]")),
+    (">2.0   , <=3.0", Some("[> 2.0.0.0 <= 3.0.0.0]")),
+    ("> 2.0   <=  3.0", Some("[> 2.0.0.0 <= 3.0.0.0]")),
+    ("> 2.0  ,  <=  3.0", Some("[> 2.0.0.0 <= 3.0.0.0]")),
+    ("  > 2.0  ,  <=  3.0 ", Some("[> 2.0.0.0 <= 3.0.0.0]")),
+    (">2.0@stable,<=3.0@dev", Some("[> 2.0.0.0 <= 3.0.0.0-dev]")),
+    (">=1.1.0-alpha4,<1.2.x-dev", Some("[>= 1.1.0.0-alpha4 < 1.2.9999999.9999999-dev]")),
+    (">=1.1.0-alpha4,<1.2-beta2", Some("[>= 1.1.0.0-alpha4 < 1.2.0.0-beta2]")),
+    (">2.0,<2.0.5 | >2.0.6", Some("[[> 2.0.0.0 < 2.0.5.0-dev] || > 2.0.6.0]")),
+    (">2.0,<2.0.5 || >2.0.6", Some("[[> 2.0.0.0 < 2.0.5.0-dev] || > 2.0.6.0]")),
+    ("> 2.0 , <2.0.5 | >  2.0.6", Some("[[> 2.0.0.0 < 2.0.5.0-dev] || > 2.0.6.0]")),
+    (">=1.0 <1.1 || >=1.2", Some("[[>= 1.0.0.0-dev < 1.1.0.0-dev] || >= 1.2.0.0-dev]")),
+    ("^2.5 || ^3.0", Some("[>= 2.5.0.0-dev < 4.0.0.0-dev]")),
+    ("^1.0 || ^2.0 || ^3.0", Some("[>= 1.0.0.0-dev < 4.0.0.0-dev]")),
+    ("^1.0 || ^3.0", Some("[[>= 1.0.0.0-dev < 2.0.0.0-dev] || [>= 3.0.0.0-dev < 4.0.0.0-dev]]")),
+    // Stability flags and references
+    ("1.0@dev", Some("== 1.0.0.0")),
+    ("dev-load-varnish-only-when-used as ^2.0@dev", Some("== dev-load-varnish-only-when-used")),
+    ("1.0.x-dev#abcd123", Some("== 1.0.9999999.9999999-dev")),
+    ("1.0.x-dev#trunk/@123", Some("== 1.0.9999999.9999999-dev")),
+    ("dev-feature#abc123", Some("== dev-feature")),
+    ("1.0#abcd123", None),
+    ("1.0#trunk/@123", None),
+    // Invalid constraints
+    ("", None),
+    ("1.0.0-meh", None),
+    (">2.0,,<=3.0", None),
+    (">2.0 ,, <=3.0", None),
+    (">2.0 ||| <=3.0", None),
+    (",^1@dev || ^4@dev", None),
+    (",^1@dev", None),
+    ("|| ^1@dev", None),
+    ("^1@dev ||", None),
+    ("^1@dev ,", None),
+    ("^", None),
+    ("^8 || ^", None),
+    ("~", None),
+    ("~>1.2", None),
+];
+
+/// `Constraint::matches()` cases from ConstraintTest: the operator and version
+/// of the required constraint, the operator and version of the provided one
+/// and whether they intersect
+pub const CONSTRAINT_MATCHES: &[(&str, &str, &str, &str, bool)] = &[
+    ("==", "1", "==", "1", true),
+    (">=", "1", ">=", "2", true),
+    (">=", "2", ">=", "1", true),
+    (">=", "2", ">", "1", true),
+    ("<=", "2", ">=", "1", true),
+    (">=", "1", "<=", "2", true),
+    ("==", "2", ">=", "2", true),
+    ("!=", "1", "!=", "1", true),
+    ("!=", "1", "==", "2", true),
+    ("!=", "1", "<", "1", true),
+    ("!=", "1", "<=", "1", true),
+    ("!=", "1", ">", "1", true),
+    ("!=", "1", ">=", "1", true),
+    ("==", "dev-foo-bar", "==", "dev-foo-bar", true),
+    ("==", "dev-events+issue-17", "==", "dev-events+issue-17", true),
+    ("==", "dev-foo-xyz", "==", "dev-foo-xyz", true),
+    ("!=", "dev-foo-bar", "==", "dev-foo-xyz", true),
+    ("==", "dev-foo-bar", "!=", "dev-foo-xyz", true),
+    ("!=", "dev-foo-bar", "!=", "dev-foo-xyz", true),
+    ("==", "1", "==", "2", false),
+    (">=", "2", "<=", "1", false),
+    (">=", "2", "<", "2", false),
+    ("<=", "2", ">", "2", false),
+    (">", "2", "<=", "2", false),
+    ("<=", "1", ">=", "2", false),
+    ("==", "2", "<", "2", false),
+    ("!=", "1", "==", "1", false),
+    ("==", "1", "!=", "1", false),
+    ("==", "dev-foo-dist", "==", "dev-foo-zist", false),
+    ("==", "dev-foo-bar", "==", "dev-foo-xyz", false),
+    ("<", "0.12", "==", "dev-foo", false),
+    (">", "0.12", "==", "dev-foo", false),
+    ("<=", "0.12", "==", "dev-foo", false),
+    (">=", "0.12", "==", "dev-foo", false),
+    ("<", "dev-foo", "==", "dev-foo", false),
+    (">=", "dev-foo-bar", ">=", "dev-foo-xyz", false),
+    ("<=", "dev-foo-bar", "<", "dev-foo-xyz", false),
+    (">=", "dev-foo-bar", "!=", "dev-foo-bar", false),
+    ("!=", "dev-foo-bar", "==", "dev-foo-bar", false),
+];
+
+/// A fixture this crate answers differently than composer/semver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The checked function, e.g. `normalize`
+    pub function: &'static str,
+    pub input: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}(\"{}\"): expected {}, got {}",
+            self.function, self.input, self.expected, self.actual
+        )
+    }
+}
+
+/// Run all fixture tables
+pub fn check() -> Vec<Divergence> {
+    let parser = VersionParser::new();
+    let mut divergences = check_normalize(&parser);
+    divergences.extend(check_normalize_branch(&parser));
+    divergences.extend(check_parse_stability());
+    divergences.extend(check_parse_constraints(&parser));
+    divergences.extend(check_constraint_matches());
+    divergences
+}
+
+/// Check the [`NORMALIZE`] table
+pub fn check_normalize(parser: &VersionParser) -> Vec<Divergence> {
+    NORMALIZE
+        .iter()
+        .filter_map(|(input, expected)| {
+            let actual = parser.normalize(input).ok();
+            diverges("normalize", input, expected.map(String::from), actual)
+        })
+        .collect()
+}
+
+/// Check the [`NORMALIZE_BRANCH`] table
+pub fn check_normalize_branch(parser: &VersionParser) -> Vec<Divergence> {
+    NORMALIZE_BRANCH
+        .iter()
+        .filter_map(|(input, expected)| {
+            let actual = parser.normalize_branch(input).ok();
+            diverges("normalize_branch", input, Some(expected.to_string()), actual)
+        })
+        .collect()
+}
+
+/// Check the [`PARSE_STABILITY`] table
+pub fn check_parse_stability() -> Vec<Divergence> {
+    PARSE_STABILITY
+        .iter()
+        .filter_map(|(input, expected)| {
+            let actual = VersionParser::parse_stability(input);
+            diverges(
+                "parse_stability",
+                input,
+                Some(expected.to_string()),
+                Some(actual.to_string()),
+            )
+        })
+        .collect()
+}
+
+/// Check the [`PARSE_CONSTRAINTS`] table
+pub fn check_parse_constraints(parser: &VersionParser) -> Vec<Divergence> {
+    PARSE_CONSTRAINTS
+        .iter()
+        .filter_map(|(input, expected)| {
+            let actual = parser.parse_constraints(input).ok().map(|c| c.to_string());
+            diverges("parse_constraints", input, expected.map(String::from), actual)
+        })
+        .collect()
+}
+
+/// Check the [`CONSTRAINT_MATCHES`] table
+pub fn check_constraint_matches() -> Vec<Divergence> {
+    CONSTRAINT_MATCHES
+        .iter()
+        .filter_map(|&(require_op, require_version, provide_op, provide_version, expected)| {
+            let input = format!("{} {}\" against \"{} {}", require_op, require_version, provide_op, provide_version);
+            let actual = match (
+                Constraint::from_str(require_op, require_version.to_string()),
+                Constraint::from_str(provide_op, provide_version.to_string()),
+            ) {
+                (Ok(require), Ok(provide)) => Some(require.matches(&provide).to_string()),
+                _ => None,
+            };
+            diverges("matches", &input, Some(expected.to_string()), actual)
+        })
+        .collect()
+}
+
+fn diverges(function: &'static str, input: &str, expected: Option<String>, actual: Option<String>) -> Option<Divergence> {
+    if expected == actual {
+        return None;
+    }
+
+    let describe = |result: Option<String>| result.map_or_else(|| "an error".to_string(), |r| format!("\"{}\"", r));
+    Some(Divergence {
+        function,
+        input: input.to_string(),
+        expected: describe(expected),
+        actual: describe(actual),
+    })
+}
@@ -1,9 +1,12 @@
 //! Single version constraint implementation
 
 use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
 use super::{Bound, ConstraintInterface, Operator};
+use crate::{VersionParser, VersionParserError};
 
 #[derive(Error, Debug)]
 pub enum ConstraintError {
@@ -265,6 +268,37 @@ impl fmt::Display for Constraint {
     }
 }
 
+/// Parses a constraint string that describes a single constraint, e.g. `>=1.0` or `== 1.0.0.0`
+impl FromStr for Constraint {
+    type Err = VersionParserError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parsed = VersionParser::new().parse_constraints(s)?;
+        let (operator, version) = parsed.as_constraint().ok_or_else(|| VersionParserError::ConstraintParseError {
+            constraint: s.to_string(),
+            reason: "expected a single constraint".to_string(),
+        })?;
+
+        let mut constraint = Constraint::new(*operator, version.to_string())?;
+        constraint.set_pretty_string(Some(s.to_string()));
+        Ok(constraint)
+    }
+}
+
+/// Serialized as the pretty string, which parses back into the same constraint
+impl Serialize for Constraint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.pretty_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Constraint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// PHP-compatible version_compare
 pub fn php_version_compare(a: &str, b: &str, operator: &str) -> bool {
     let cmp = compare_versions(a, b);
@@ -798,4 +832,29 @@ mod tests {
         assert!(require.match_specific(&provide, false),
             ">=6.7.2.0 should match =6.7.9999999.9999999-dev");
     }
+
+    #[test]
+    fn test_constraint_from_str_and_serde() {
+        let c: Constraint = ">=1.0".parse().unwrap();
+        assert_eq!(c.operator(), Operator::GreaterThanOrEqual);
+        assert_eq!(c.version(), "1.0.0.0-dev");
+        assert_eq!(c.to_string(), ">= 1.0.0.0-dev");
+        assert_eq!(c.pretty_string(), ">=1.0");
+
+        // The Display output parses back into the same constraint
+        let roundtrip: Constraint = c.to_string().parse().unwrap();
+        assert_eq!(roundtrip.to_string(), c.to_string());
+
+        assert!("^1.0".parse::<Constraint>().is_err());
+        assert!("foo bar".parse::<Constraint>().is_err());
+
+        let json = serde_json::to_string(&c).unwrap();
+        assert_eq!(json, "\">=1.0\"");
+        let c: Constraint = serde_json::from_str(&json).unwrap();
+        assert_eq!(c.to_string(), ">= 1.0.0.0-dev");
+
+        let constructed = Constraint::new(Operator::NotEqual, "2.0.0.0".to_string()).unwrap();
+        let c: Constraint = serde_json::from_value(serde_json::to_value(&constructed).unwrap()).unwrap();
+        assert_eq!(c.to_string(), "!= 2.0.0.0");
+    }
 }
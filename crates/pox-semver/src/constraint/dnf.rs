@@ -0,0 +1,329 @@
+//! Disjunctive normal form of constraint trees and their compact rendering
+
+use std::fmt;
+
+use super::{php_version_compare, Constraint, ConstraintInterface, MultiConstraint, Operator};
+
+/// A constraint tree flattened into an OR of ANDs of single constraints.
+///
+/// Every clause is simplified to its tightest bounds, and clauses no version
+/// can satisfy or that are covered by another clause are dropped, so `^1.2, <1.5 || ^3.0` becomes `>=1.2 <1.5 || >=3.0 <4.0`
+/// when displayed.
+#[derive(Debug, Clone)]
+pub struct Dnf {
+    clauses: Vec<Vec<Constraint>>,
+}
+
+impl Dnf {
+    /// Convert any constraint tree into disjunctive normal form
+    pub fn from_constraint(constraint: &dyn ConstraintInterface) -> Self {
+        let mut clauses: Vec<Vec<Constraint>> = Vec::new();
+        for clause in expand(constraint) {
+            let Some(clause) = simplify(clause) else {
+                continue;
+            };
+            if clause.is_empty() {
+                // One clause matching everything makes the others redundant
+                return Dnf { clauses: vec![Vec::new()] };
+            }
+            if clauses.iter().any(|c| same_clause(c, &clause) || contains(c, &clause)) {
+                continue;
+            }
+            clauses.retain(|c| !contains(&clause, c));
+            clauses.push(clause);
+        }
+        Dnf { clauses }
+    }
+
+    /// The clauses, any of which may be satisfied; each clause requires all of its constraints
+    pub fn clauses(&self) -> &[Vec<Constraint>] {
+        &self.clauses
+    }
+
+    /// True if every version matches
+    pub fn is_match_all(&self) -> bool {
+        self.clauses.len() == 1 && self.clauses[0].is_empty()
+    }
+
+    /// True if no version can match
+    pub fn is_match_none(&self) -> bool {
+        self.clauses.is_empty()
+    }
+}
+
+/// Rendered compactly, e.g. `>=1.2 <2.0 || >=3.0`, `*` for any version and `none` for no version
+impl fmt::Display for Dnf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_match_none() {
+            return write!(f, "none");
+        }
+        if self.is_match_all() {
+            return write!(f, "*");
+        }
+
+        let clauses: Vec<String> = self
+            .clauses
+            .iter()
+            .map(|clause| clause.iter().map(compact_constraint).collect::<Vec<_>>().join(" "))
+            .collect();
+        write!(f, "{}", clauses.join(" || "))
+    }
+}
+
+impl MultiConstraint {
+    /// This constraint in disjunctive normal form
+    pub fn to_dnf(&self) -> Dnf {
+        Dnf::from_constraint(self)
+    }
+}
+
+/// Distribute AND over OR without simplifying
+fn expand(constraint: &dyn ConstraintInterface) -> Vec<Vec<Constraint>> {
+    if constraint.is_match_all() {
+        return vec![Vec::new()];
+    }
+    if constraint.is_match_none() {
+        return Vec::new();
+    }
+    if let Some((operator, version)) = constraint.as_constraint() {
+        return Constraint::new(*operator, version.to_string()).map(|c| vec![vec![c]]).unwrap_or_default();
+    }
+
+    let Some((constraints, conjunctive)) = constraint.as_multi_constraint() else {
+        return vec![Vec::new()];
+    };
+
+    if !conjunctive {
+        return constraints.iter().flat_map(|c| expand(c.as_ref())).collect();
+    }
+
+    constraints.iter().fold(vec![Vec::new()], |clauses, c| {
+        let operand = expand(c.as_ref());
+        clauses
+            .iter()
+            .flat_map(|clause| {
+                operand.iter().map(move |other| {
+                    let mut combined = clause.clone();
+                    combined.extend(other.iter().cloned());
+                    combined
+                })
+            })
+            .collect()
+    })
+}
+
+fn satisfies(version: &str, constraint: &Constraint) -> bool {
+    php_version_compare(version, constraint.version(), constraint.operator().as_str())
+}
+
+/// Reduce a clause to its tightest bounds, `None` if no version satisfies it
+fn simplify(clause: Vec<Constraint>) -> Option<Vec<Constraint>> {
+    // Branch names do not order against versions, keep such clauses as written
+    if clause.iter().any(|c| c.version().starts_with("dev-")) {
+        let mut unique: Vec<Constraint> = Vec::new();
+        for c in clause {
+            if !unique.iter().any(|u| same_constraint(u, &c)) {
+                unique.push(c);
+            }
+        }
+        return Some(unique);
+    }
+
+    if let Some(exact) = clause.iter().find(|c| c.operator() == Operator::Equal) {
+        let exact = exact.clone();
+        return clause.iter().all(|c| satisfies(exact.version(), c)).then(|| vec![exact]);
+    }
+
+    let mut lower: Option<Constraint> = None;
+    let mut upper: Option<Constraint> = None;
+    let mut excluded: Vec<Constraint> = Vec::new();
+
+    for c in clause {
+        match c.operator() {
+            Operator::GreaterThan | Operator::GreaterThanOrEqual => {
+                let tighter = match &lower {
+                    None => true,
+                    Some(l) if l.version() == c.version() => c.operator() == Operator::GreaterThan,
+                    Some(l) => php_version_compare(c.version(), l.version(), ">"),
+                };
+                if tighter {
+                    lower = Some(c);
+                }
+            }
+            Operator::LessThan | Operator::LessThanOrEqual => {
+                let tighter = match &upper {
+                    None => true,
+                    Some(u) if u.version() == c.version() => c.operator() == Operator::LessThan,
+                    Some(u) => php_version_compare(c.version(), u.version(), "<"),
+                };
+                if tighter {
+                    upper = Some(c);
+                }
+            }
+            Operator::NotEqual => {
+                if !excluded.iter().any(|e| e.version() == c.version()) {
+                    excluded.push(c);
+                }
+            }
+            Operator::Equal => unreachable!("exact clauses are handled above"),
+        }
+    }
+
+    if let (Some(l), Some(u)) = (&lower, &upper) {
+        let both_inclusive = l.operator() == Operator::GreaterThanOrEqual && u.operator() == Operator::LessThanOrEqual;
+        let empty = if php_version_compare(l.version(), u.version(), "==") {
+            !both_inclusive
+        } else {
+            php_version_compare(l.version(), u.version(), ">")
+        };
+        if empty {
+            return None;
+        }
+    }
+
+    // Exclusions outside the range do not narrow it
+    excluded.retain(|e| {
+        lower.as_ref().is_none_or(|l| satisfies(e.version(), l)) && upper.as_ref().is_none_or(|u| satisfies(e.version(), u))
+    });
+
+    Some(lower.into_iter().chain(upper).chain(excluded).collect())
+}
+
+/// Whether every version matching `inner` also matches `outer`, for clauses made of bounds only
+fn contains(outer: &[Constraint], inner: &[Constraint]) -> bool {
+    let is_range = |clause: &[Constraint]| {
+        clause.iter().all(|c| c.operator() != Operator::NotEqual && !c.version().starts_with("dev-"))
+    };
+    if !is_range(outer) || !is_range(inner) {
+        return false;
+    }
+
+    if let [exact] = inner {
+        if exact.operator() == Operator::Equal {
+            return outer.iter().all(|c| satisfies(exact.version(), c));
+        }
+    }
+    if outer.iter().chain(inner).any(|c| c.operator() == Operator::Equal) {
+        return false;
+    }
+
+    let bound = |clause: &[Constraint], lower: bool| -> Option<Constraint> {
+        clause
+            .iter()
+            .find(|c| matches!(c.operator(), Operator::GreaterThan | Operator::GreaterThanOrEqual) == lower)
+            .cloned()
+    };
+
+    // An outer bound covers the inner one if it is further out, or equal and not stricter
+    let covers = |outer: Option<Constraint>, inner: Option<Constraint>, further: &str| match (outer, inner) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(o), Some(i)) if php_version_compare(o.version(), i.version(), "==") => {
+            matches!(o.operator(), Operator::GreaterThanOrEqual | Operator::LessThanOrEqual)
+                || matches!(i.operator(), Operator::GreaterThan | Operator::LessThan)
+        }
+        (Some(o), Some(i)) => php_version_compare(o.version(), i.version(), further),
+    };
+
+    covers(bound(outer, true), bound(inner, true), "<") && covers(bound(outer, false), bound(inner, false), ">")
+}
+
+fn same_constraint(a: &Constraint, b: &Constraint) -> bool {
+    a.operator() == b.operator() && a.version() == b.version()
+}
+
+fn same_clause(a: &[Constraint], b: &[Constraint]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_constraint(a, b))
+}
+
+/// `>= 1.2.0.0-dev` as `>=1.2`, `== 1.2.3.0` as `1.2.3`
+fn compact_constraint(constraint: &Constraint) -> String {
+    let operator = constraint.operator();
+    let version = constraint.version();
+    if version.starts_with("dev-") {
+        return match operator {
+            Operator::Equal => version.to_string(),
+            _ => format!("{}{}", operator.as_str(), version),
+        };
+    }
+
+    let (base, suffix) = match version.find('-') {
+        Some(pos) => version.split_at(pos),
+        None => (version, ""),
+    };
+    // `>= x-dev` includes the pre-releases of x and `< x-dev` excludes them, which is how `>=x` and `<x` read
+    let suffix = match (operator, suffix) {
+        (Operator::GreaterThanOrEqual | Operator::LessThan, "-dev") => "",
+        _ => suffix,
+    };
+
+    let mut parts: Vec<&str> = base.split('.').collect();
+    while parts.len() > 2 && parts.last() == Some(&"0") {
+        parts.pop();
+    }
+    let version = format!("{}{}", parts.join("."), suffix);
+
+    match operator {
+        Operator::Equal => version,
+        _ => format!("{}{}", operator.as_str(), version),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VersionParser;
+
+    fn dnf(constraint: &str) -> String {
+        let parsed = VersionParser::new().parse_constraints(constraint).unwrap();
+        Dnf::from_constraint(parsed.as_ref()).to_string()
+    }
+
+    #[test]
+    fn test_render_compact_ranges() {
+        assert_eq!(dnf("^1.2 || >=3.0"), ">=1.2 <2.0 || >=3.0");
+        assert_eq!(dnf("~1.2.3"), ">=1.2.3 <1.3");
+        assert_eq!(dnf("1.2.3"), "1.2.3");
+        assert_eq!(dnf(">1.0 !=1.5.0"), ">1.0 !=1.5");
+        assert_eq!(dnf("*"), "*");
+        assert_eq!(dnf("dev-main || ^2.0"), "dev-main || >=2.0 <3.0");
+        assert_eq!(dnf("2.0.0-beta1"), "2.0-beta1");
+    }
+
+    #[test]
+    fn test_distributes_and_over_or() {
+        let parser = VersionParser::new();
+        let and = |constraints: Vec<&str>| {
+            let constraints = constraints.into_iter().map(|c| parser.parse_constraints(c).unwrap()).collect();
+            MultiConstraint::new(constraints, true).unwrap()
+        };
+
+        assert_eq!(and(vec!["^1.0 || ^2.0", "<1.5"]).to_dnf().to_string(), ">=1.0 <1.5");
+        assert_eq!(
+            and(vec!["^1.0 || ^2.0", ">=1.2 || >=2.5"]).to_dnf().to_string(),
+            ">=1.2 <3.0"
+        );
+        assert_eq!(dnf("^1.0 ^1.0 || ^1.0"), ">=1.0 <2.0");
+        assert_eq!(dnf("1.5.0 || ^1.0 || >=1.2 <1.8 || ^3.0"), ">=1.0 <2.0 || >=3.0 <4.0");
+    }
+
+    #[test]
+    fn test_unsatisfiable_clauses() {
+        assert_eq!(dnf(">=2.0 <1.0"), "none");
+        assert_eq!(dnf(">=2.0 <1.0 || ^3.0"), ">=3.0 <4.0");
+        assert_eq!(dnf("1.5.0 ^2.0"), "none");
+        assert_eq!(dnf(">=1.0 <=1.0"), ">=1.0 <=1.0");
+        assert_eq!(dnf(">1.0 <=1.0"), "none");
+        assert!(Dnf::from_constraint(&crate::MatchNoneConstraint::new()).is_match_none());
+    }
+
+    #[test]
+    fn test_multi_constraint_to_dnf() {
+        let multi: MultiConstraint = "^1.2 <1.5 || ^3.0".parse().unwrap();
+        let dnf = multi.to_dnf();
+        assert_eq!(dnf.clauses().len(), 2);
+        assert_eq!(dnf.clauses()[0][1].version(), "1.5.0.0-dev");
+        assert_eq!(dnf.to_string(), ">=1.2 <1.5 || >=3.0 <4.0");
+        assert!(!dnf.is_match_all());
+    }
+}
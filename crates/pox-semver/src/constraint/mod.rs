@@ -3,6 +3,7 @@
 mod bound;
 pub mod constraint;
 mod constraint_interface;
+mod dnf;
 mod match_all;
 mod match_none;
 mod multi_constraint;
@@ -11,6 +12,7 @@ mod operator;
 pub use bound::Bound;
 pub use constraint::{Constraint, ConstraintError, php_version_compare};
 pub use constraint_interface::ConstraintInterface;
+pub use dnf::Dnf;
 pub use match_all::MatchAllConstraint;
 pub use match_none::MatchNoneConstraint;
 pub use multi_constraint::{MultiConstraint, MultiConstraintError};
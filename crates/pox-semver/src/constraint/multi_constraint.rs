@@ -1,9 +1,12 @@
 //! MultiConstraint - compound constraint combining multiple constraints
 
 use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
-use super::{Bound, ConstraintInterface, MatchAllConstraint};
+use super::{Bound, ConstraintInterface, MatchAllConstraint, Operator};
+use crate::{VersionParser, VersionParserError};
 
 #[derive(Error, Debug)]
 pub enum MultiConstraintError {
@@ -99,15 +102,54 @@ impl MultiConstraint {
         constraints: &[Box<dyn ConstraintInterface>],
         conjunctive: bool,
     ) -> Option<(Vec<Box<dyn ConstraintInterface>>, bool)> {
-        // Optimization for disjunctive constraints
+        // Contiguous OR groups collapse into one range:
         // [>= 1 < 2] || [>= 2 < 3] || [>= 3 < 4] => [>= 1 < 4]
-        if !conjunctive && constraints.len() >= 2 {
-            // Check if we can merge adjacent ranges
-            // This is a simplified version - full implementation would need more complex logic
+        if conjunctive {
             return None;
         }
 
-        None
+        let mut merged: Vec<Box<dyn ConstraintInterface>> = Vec::new();
+        let mut left = constraints[0].clone();
+        let mut optimized = false;
+
+        for right in &constraints[1..] {
+            match Self::contiguous_range(left.as_ref(), right.as_ref()) {
+                Some(range) => {
+                    optimized = true;
+                    left = range;
+                }
+                None => merged.push(std::mem::replace(&mut left, right.clone())),
+            }
+        }
+
+        if !optimized {
+            return None;
+        }
+        merged.push(left);
+        Some((merged, false))
+    }
+
+    /// `[>= a < b]` and `[>= b < c]` as `[>= a < c]`
+    fn contiguous_range(left: &dyn ConstraintInterface, right: &dyn ConstraintInterface) -> Option<Box<dyn ConstraintInterface>> {
+        let (left, true) = left.as_multi_constraint()? else { return None };
+        let (right, true) = right.as_multi_constraint()? else { return None };
+        if left.len() != 2 || right.len() != 2 {
+            return None;
+        }
+
+        let (left_lower, left_upper) = (left[0].as_constraint()?, left[1].as_constraint()?);
+        let (right_lower, right_upper) = (right[0].as_constraint()?, right[1].as_constraint()?);
+        if *left_lower.0 != Operator::GreaterThanOrEqual
+            || *left_upper.0 != Operator::LessThan
+            || *right_lower.0 != Operator::GreaterThanOrEqual
+            || *right_upper.0 != Operator::LessThan
+            || left_upper.1 != right_lower.1
+        {
+            return None;
+        }
+
+        let range = MultiConstraint::new(vec![left[0].clone(), right[1].clone()], true).ok()?;
+        Some(Box::new(range))
     }
 
     fn extract_bounds(&mut self) {
@@ -222,6 +264,52 @@ impl fmt::Display for MultiConstraint {
     }
 }
 
+/// Parses a constraint string that combines several constraints, e.g. `^1.2 || ^2.0`
+impl FromStr for MultiConstraint {
+    type Err = VersionParserError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parsed = VersionParser::new().parse_constraints(s)?;
+        let (constraints, conjunctive) = parsed.as_multi_constraint().ok_or_else(|| VersionParserError::ConstraintParseError {
+            constraint: s.to_string(),
+            reason: "expected multiple constraints".to_string(),
+        })?;
+
+        let mut multi = MultiConstraint::new(constraints.to_vec(), conjunctive)?;
+        multi.set_pretty_string(Some(s.to_string()));
+        Ok(multi)
+    }
+}
+
+/// Serialized as the pretty string, or for constructed constraints as a
+/// constraint string that parses back into the same constraints
+impl Serialize for MultiConstraint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.pretty_string {
+            Some(ref pretty) => serializer.serialize_str(pretty),
+            None => serializer.serialize_str(&constraint_string(self)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MultiConstraint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// `[>= 1.0 < 2.0]` is written as `>= 1.0, < 2.0`, `[a || b]` as `a || b`
+fn constraint_string(constraint: &dyn ConstraintInterface) -> String {
+    match constraint.as_multi_constraint() {
+        Some((constraints, conjunctive)) => {
+            let separator = if conjunctive { ", " } else { " || " };
+            constraints.iter().map(|c| constraint_string(c.as_ref())).collect::<Vec<_>>().join(separator)
+        }
+        None => constraint.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -267,4 +355,42 @@ mod tests {
         let result = MultiConstraint::create(vec![], true).unwrap();
         assert!(result.is_match_all());
     }
+
+    #[test]
+    fn test_create_collapses_contiguous_ranges() {
+        let range = |low: &str, high: &str| -> Box<dyn ConstraintInterface> {
+            let low = Box::new(Constraint::new(Operator::GreaterThanOrEqual, low.to_string()).unwrap());
+            let high = Box::new(Constraint::new(Operator::LessThan, high.to_string()).unwrap());
+            Box::new(MultiConstraint::new(vec![low, high], true).unwrap())
+        };
+
+        let result = MultiConstraint::create(
+            vec![range("1.0.0.0-dev", "2.0.0.0-dev"), range("2.0.0.0-dev", "3.0.0.0-dev"), range("5.0.0.0-dev", "6.0.0.0-dev")],
+            false,
+        )
+        .unwrap();
+        assert_eq!(result.to_string(), "[[>= 1.0.0.0-dev < 3.0.0.0-dev] || [>= 5.0.0.0-dev < 6.0.0.0-dev]]");
+    }
+
+    #[test]
+    fn test_multi_constraint_from_str_and_serde() {
+        let multi: MultiConstraint = "^1.2 || ~3.0".parse().unwrap();
+        assert!(multi.is_disjunctive());
+        assert_eq!(multi.to_string(), "[[>= 1.2.0.0-dev < 2.0.0.0-dev] || [>= 3.0.0.0-dev < 4.0.0.0-dev]]");
+        assert_eq!(serde_json::to_string(&multi).unwrap(), "\"^1.2 || ~3.0\"");
+
+        assert!(">=1.0".parse::<MultiConstraint>().is_err());
+
+        // Constructed constraints serialize to a string that parses back into the same tree
+        let c1 = Box::new(Constraint::new(Operator::GreaterThanOrEqual, "1.0.0.0-dev".to_string()).unwrap());
+        let c2 = Box::new(Constraint::new(Operator::LessThan, "2.0.0.0-dev".to_string()).unwrap());
+        let c3 = Box::new(Constraint::new(Operator::Equal, "3.0.0.0".to_string()).unwrap());
+        let range: Box<dyn ConstraintInterface> = Box::new(MultiConstraint::new(vec![c1, c2], true).unwrap());
+        let multi = MultiConstraint::new(vec![range, c3], false).unwrap();
+
+        let json = serde_json::to_string(&multi).unwrap();
+        assert_eq!(json, "\">= 1.0.0.0-dev, < 2.0.0.0-dev || == 3.0.0.0\"");
+        let roundtrip: MultiConstraint = serde_json::from_str(&json).unwrap();
+        assert_eq!(roundtrip.to_string(), multi.to_string());
+    }
 }
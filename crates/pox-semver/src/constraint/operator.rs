@@ -1,6 +1,7 @@
 //! Operator types for version constraints
 
 use std::fmt;
+use std::str::FromStr;
 use thiserror::Error;
 
 /// Comparison operators for version constraints
@@ -56,6 +57,14 @@ impl Operator {
     }
 }
 
+impl FromStr for Operator {
+    type Err = InvalidOperatorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Operator::from_str(s)
+    }
+}
+
 impl fmt::Display for Operator {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.as_str())
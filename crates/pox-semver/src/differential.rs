@@ -0,0 +1,456 @@
+//! Differential testing against PHP's composer/semver.
+//!
+//! Generates version/constraint pairs and checks that this crate answers
+//! them like the reference implementation. The reference is either PHP with
+//! composer/semver installed ([`PhpReference`]) or a fixture file recorded
+//! from it earlier ([`FixtureReference`]), so the check also runs where no
+//! PHP is available.
+//!
+//! ```no_run
+//! use pox_semver::differential::{compare, generate_cases, PhpReference};
+//!
+//! let cases = generate_cases(42, 5000);
+//! let mut reference = PhpReference::new("php", "vendor/autoload.php");
+//! for mismatch in compare(&cases, &mut reference).unwrap() {
+//!     println!("{}", mismatch);
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::constraint::{Constraint, Operator};
+use crate::VersionParser;
+
+/// Checks each line of stdin, `version<TAB>constraint`, like `Semver::satisfies()`
+/// but reporting invalid input instead of treating it as no match
+const PHP_SCRIPT: &str = r#"
+require $argv[1];
+$parser = new Composer\Semver\VersionParser();
+while (($line = fgets(STDIN)) !== false) {
+    [$version, $constraint] = explode("\t", rtrim($line, "\n"), 2);
+    try {
+        $provider = new Composer\Semver\Constraint\Constraint('==', $parser->normalize($version));
+        echo $parser->parseConstraints($constraint)->matches($provider) ? "match" : "no-match", "\n";
+    } catch (UnexpectedValueException $e) {
+        echo "invalid\n";
+    }
+}
+"#;
+
+#[derive(Error, Debug)]
+pub enum DifferentialError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid fixture file: {0}")]
+    Fixture(String),
+    #[error("No fixture for version \"{version}\" and constraint \"{constraint}\"")]
+    MissingFixture { version: String, constraint: String },
+    #[error("Reference implementation failed: {0}")]
+    Reference(String),
+}
+
+/// Result of checking a version against a constraint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Match,
+    NoMatch,
+    /// The version or the constraint could not be parsed
+    Invalid,
+}
+
+impl Outcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Match => "match",
+            Outcome::NoMatch => "no-match",
+            Outcome::Invalid => "invalid",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "match" => Some(Outcome::Match),
+            "no-match" => Some(Outcome::NoMatch),
+            "invalid" => Some(Outcome::Invalid),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A version to check against a constraint
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Case {
+    pub version: String,
+    pub constraint: String,
+}
+
+impl Case {
+    pub fn new(version: impl Into<String>, constraint: impl Into<String>) -> Self {
+        Self { version: version.into(), constraint: constraint.into() }
+    }
+}
+
+/// A case this crate answers differently than the reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub case: Case,
+    pub expected: Outcome,
+    pub actual: Outcome,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\" against \"{}\": expected {}, got {}",
+            self.case.version, self.case.constraint, self.expected, self.actual
+        )
+    }
+}
+
+/// Answers cases the way composer/semver does
+pub trait Reference {
+    fn evaluate(&mut self, cases: &[Case]) -> Result<Vec<Outcome>, DifferentialError>;
+}
+
+/// composer/semver run by a PHP binary
+pub struct PhpReference {
+    php: PathBuf,
+    autoload: PathBuf,
+}
+
+impl PhpReference {
+    /// `autoload` is the `vendor/autoload.php` of a project requiring composer/semver
+    pub fn new(php: impl Into<PathBuf>, autoload: impl Into<PathBuf>) -> Self {
+        Self { php: php.into(), autoload: autoload.into() }
+    }
+
+    /// Configured by `POX_SEMVER_AUTOLOAD` and optionally `POX_SEMVER_PHP`, `None` without an autoloader
+    pub fn from_env() -> Option<Self> {
+        let autoload = std::env::var_os("POX_SEMVER_AUTOLOAD")?;
+        let php = std::env::var_os("POX_SEMVER_PHP").unwrap_or_else(|| "php".into());
+        Some(Self::new(php, autoload))
+    }
+}
+
+impl Reference for PhpReference {
+    fn evaluate(&mut self, cases: &[Case]) -> Result<Vec<Outcome>, DifferentialError> {
+        let mut child = Command::new(&self.php)
+            .arg("-r")
+            .arg(PHP_SCRIPT)
+            .arg(&self.autoload)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let mut input = String::new();
+        for case in cases {
+            input.push_str(&case.version);
+            input.push('\t');
+            input.push_str(&case.constraint);
+            input.push('\n');
+        }
+
+        // Write from another thread so a full stdout pipe can't block us
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+        let output = child.wait_with_output()?;
+        writer.join().map_err(|_| DifferentialError::Reference("writer thread panicked".to_string()))??;
+
+        if !output.status.success() {
+            return Err(DifferentialError::Reference(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+        }
+
+        let outcomes: Vec<Outcome> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| {
+                Outcome::from_name(line.trim())
+                    .ok_or_else(|| DifferentialError::Reference(format!("unexpected output \"{}\"", line)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        if outcomes.len() != cases.len() {
+            return Err(DifferentialError::Reference(format!(
+                "expected {} results, got {}",
+                cases.len(),
+                outcomes.len()
+            )));
+        }
+        Ok(outcomes)
+    }
+}
+
+/// Outcomes recorded from composer/semver, stored as a JSON array of
+/// `{"version": ..., "constraint": ..., "outcome": "match" | "no-match" | "invalid"}`
+#[derive(Debug, Clone, Default)]
+pub struct FixtureReference {
+    outcomes: HashMap<Case, Outcome>,
+}
+
+impl FixtureReference {
+    pub fn load(path: &Path) -> Result<Self, DifferentialError> {
+        let content = std::fs::read_to_string(path)?;
+        let value: Value = serde_json::from_str(&content).map_err(|e| DifferentialError::Fixture(e.to_string()))?;
+        let entries = value
+            .as_array()
+            .ok_or_else(|| DifferentialError::Fixture("expected an array".to_string()))?;
+
+        let mut outcomes = HashMap::new();
+        for entry in entries {
+            let field = |name: &str| {
+                entry
+                    .get(name)
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| DifferentialError::Fixture(format!("missing \"{}\" in {}", name, entry)))
+            };
+            let outcome = Outcome::from_name(field("outcome")?)
+                .ok_or_else(|| DifferentialError::Fixture(format!("unknown outcome in {}", entry)))?;
+            outcomes.insert(Case::new(field("version")?, field("constraint")?), outcome);
+        }
+
+        Ok(Self { outcomes })
+    }
+
+    /// All recorded cases, in no particular order
+    pub fn cases(&self) -> Vec<Case> {
+        self.outcomes.keys().cloned().collect()
+    }
+}
+
+impl Reference for FixtureReference {
+    fn evaluate(&mut self, cases: &[Case]) -> Result<Vec<Outcome>, DifferentialError> {
+        cases
+            .iter()
+            .map(|case| {
+                self.outcomes.get(case).copied().ok_or_else(|| DifferentialError::MissingFixture {
+                    version: case.version.clone(),
+                    constraint: case.constraint.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Record the outcomes of `reference` as a fixture file for [`FixtureReference`]
+pub fn record_fixtures(path: &Path, cases: &[Case], reference: &mut dyn Reference) -> Result<(), DifferentialError> {
+    let outcomes = reference.evaluate(cases)?;
+    let entries: Vec<Value> = cases
+        .iter()
+        .zip(outcomes)
+        .map(|(case, outcome)| json!({"version": case.version, "constraint": case.constraint, "outcome": outcome.as_str()}))
+        .collect();
+
+    let mut content = serde_json::to_string_pretty(&entries).map_err(|e| DifferentialError::Fixture(e.to_string()))?;
+    content.push('\n');
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// Check a case with this crate
+pub fn evaluate(case: &Case) -> Outcome {
+    let parser = VersionParser::new();
+    let Ok(normalized) = parser.normalize(&case.version) else {
+        return Outcome::Invalid;
+    };
+    let Ok(constraint) = parser.parse_constraints(&case.constraint) else {
+        return Outcome::Invalid;
+    };
+    let Ok(provider) = Constraint::new(Operator::Equal, normalized) else {
+        return Outcome::Invalid;
+    };
+
+    if constraint.matches(&provider) {
+        Outcome::Match
+    } else {
+        Outcome::NoMatch
+    }
+}
+
+/// Cases where this crate disagrees with `reference`
+pub fn compare(cases: &[Case], reference: &mut dyn Reference) -> Result<Vec<Mismatch>, DifferentialError> {
+    let expected = reference.evaluate(cases)?;
+
+    Ok(cases
+        .iter()
+        .zip(expected)
+        .filter_map(|(case, expected)| {
+            let actual = evaluate(case);
+            (actual != expected).then(|| Mismatch { case: case.clone(), expected, actual })
+        })
+        .collect())
+}
+
+/// `count` pseudo-random cases, the same ones for the same `seed`
+pub fn generate_cases(seed: u64, count: usize) -> Vec<Case> {
+    let mut rng = Rng::new(seed);
+    (0..count)
+        .map(|_| Case::new(rng.version(), rng.constraint()))
+        .collect()
+}
+
+const SUFFIXES: &[&str] = &["", "", "", "-alpha1", "-beta2", "-b3", "-RC1", "-rc.2", "-dev", "-p1", "-patch2", "+build.5"];
+const BRANCHES: &[&str] = &["dev-main", "dev-master", "1.x-dev", "2.1.x-dev", "dev-feature/foo"];
+const OPERATORS: &[&str] = &["", "=", "==", "!=", "<>", ">", ">=", "<", "<=", "^", "~"];
+const STABILITY_FLAGS: &[&str] = &["@dev", "@alpha", "@beta", "@RC", "@stable"];
+/// Malformed input both implementations have to reject the same way
+const GARBAGE: &[&str] = &["foo", ">=", "~>1.0", "1.0 -", "^", "1..2", "=>1.0", "1.0,,2.0", "|| 1.0", "x.y"];
+
+/// xorshift64*, good enough to spread the cases without a dependency
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+
+    fn number(&mut self) -> usize {
+        // Mostly small numbers so versions and constraints overlap
+        if self.chance(90) { self.below(4) } else { self.below(20) }
+    }
+
+    /// A version with one to four numeric parts
+    fn numeric(&mut self) -> String {
+        let parts = 1 + self.below(4);
+        (0..parts).map(|_| self.number().to_string()).collect::<Vec<_>>().join(".")
+    }
+
+    fn version(&mut self) -> String {
+        if self.chance(8) {
+            return self.pick(BRANCHES).to_string();
+        }
+        if self.chance(2) {
+            return self.pick(GARBAGE).to_string();
+        }
+
+        let prefix = if self.chance(10) { "v" } else { "" };
+        format!("{}{}{}", prefix, self.numeric(), self.pick(SUFFIXES))
+    }
+
+    fn constraint(&mut self) -> String {
+        if self.chance(3) {
+            return self.pick(GARBAGE).to_string();
+        }
+
+        let mut constraint = self.conjunction();
+        while self.chance(20) {
+            let separator = if self.chance(80) { " || " } else { " | " };
+            constraint = format!("{}{}{}", constraint, separator, self.conjunction());
+        }
+        constraint
+    }
+
+    fn conjunction(&mut self) -> String {
+        let mut constraint = self.single();
+        while self.chance(25) {
+            let separator = if self.chance(50) { ", " } else { " " };
+            constraint = format!("{}{}{}", constraint, separator, self.single());
+        }
+        constraint
+    }
+
+    fn single(&mut self) -> String {
+        let constraint = match self.below(10) {
+            0 => "*".to_string(),
+            1 => {
+                let mut version = self.numeric();
+                version.truncate(version.rfind('.').unwrap_or(version.len()));
+                if version.is_empty() { "*".to_string() } else { format!("{}.*", version) }
+            }
+            2 => format!("{} - {}", self.numeric(), self.numeric()),
+            3 => self.pick(BRANCHES).to_string(),
+            _ => format!("{}{}{}", self.pick(OPERATORS), self.numeric(), self.pick(SUFFIXES)),
+        };
+
+        if self.chance(10) {
+            format!("{}{}", constraint, self.pick(STABILITY_FLAGS))
+        } else {
+            constraint
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_cases_is_deterministic() {
+        let cases = generate_cases(7, 200);
+        assert_eq!(cases, generate_cases(7, 200));
+        assert_ne!(cases, generate_cases(8, 200));
+
+        // Every kind of outcome is exercised
+        let outcomes: Vec<Outcome> = cases.iter().map(evaluate).collect();
+        assert!(outcomes.contains(&Outcome::Match));
+        assert!(outcomes.contains(&Outcome::NoMatch));
+        assert!(outcomes.contains(&Outcome::Invalid));
+    }
+
+    #[test]
+    fn test_compare_reports_mismatches() {
+        let cases = vec![Case::new("1.2.3", "^1.2"), Case::new("2.0.0", "^1.2")];
+        let mut reference = FixtureReference::default();
+        reference.outcomes.insert(cases[0].clone(), Outcome::Match);
+        reference.outcomes.insert(cases[1].clone(), Outcome::Match);
+
+        let mismatches = compare(&cases, &mut reference).unwrap();
+        assert_eq!(
+            mismatches,
+            vec![Mismatch { case: cases[1].clone(), expected: Outcome::Match, actual: Outcome::NoMatch }]
+        );
+
+        let missing = compare(&[Case::new("1.0", "*")], &mut reference);
+        assert!(matches!(missing, Err(DifferentialError::MissingFixture { .. })));
+    }
+
+    #[test]
+    fn test_record_and_load_fixtures() {
+        let dir = std::env::temp_dir().join(format!("pox-semver-fixtures-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("fixtures.json");
+
+        let cases = generate_cases(3, 50);
+        let mut source = FixtureReference::default();
+        for case in &cases {
+            source.outcomes.insert(case.clone(), evaluate(case));
+        }
+        record_fixtures(&path, &cases, &mut source).unwrap();
+
+        let mut loaded = FixtureReference::load(&path).unwrap();
+        assert!(compare(&cases, &mut loaded).unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -3,12 +3,16 @@
 //! This crate provides semantic version parsing, comparison, and constraint matching
 //! compatible with PHP's Composer package manager.
 
+#[cfg(feature = "conformance")]
+pub mod conformance;
 pub mod constraint;
+#[cfg(feature = "differential")]
+pub mod differential;
 mod comparator;
 mod semver;
 mod version_parser;
 
 pub use comparator::Comparator;
-pub use constraint::{Bound, Constraint, ConstraintInterface, MatchAllConstraint, MatchNoneConstraint, MultiConstraint, Operator};
+pub use constraint::{Bound, Constraint, ConstraintInterface, Dnf, MatchAllConstraint, MatchNoneConstraint, MultiConstraint, Operator};
 pub use semver::Semver;
 pub use version_parser::{ParsedConstraints, Stability, VersionParser, VersionParserError};
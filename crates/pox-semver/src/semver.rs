@@ -91,9 +91,9 @@ impl Semver {
 
         // Sort by normalized version
         normalized.sort_by(|(a, _), (b, _)| {
-            let cmp = if Comparator::less_than(a, b) {
+            let cmp = if Comparator::compare_normalized(a, "<", b) {
                 std::cmp::Ordering::Less
-            } else if Comparator::equal_to(a, b) {
+            } else if Comparator::compare_normalized(a, "==", b) {
                 std::cmp::Ordering::Equal
             } else {
                 std::cmp::Ordering::Greater
@@ -1,7 +1,11 @@
 //! Version parsing and normalization module
 
+use std::fmt;
+use std::str::FromStr;
+
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
 use crate::constraint::{Constraint, ConstraintInterface, MatchAllConstraint, MultiConstraint, Operator};
@@ -140,6 +144,12 @@ lazy_static! {
     // Hyphen Range
     static ref HYPHEN_RE: Regex = Regex::new(&format!(r"(?i)^(?P<from>{}) +- +(?P<to>{})($)$", *VERSION_REGEX, *VERSION_REGEX)).unwrap();
 
+    // Explicit stable suffix, e.g. `<1.2.3-stable`
+    static ref STABLE_SUFFIX_RE: Regex = Regex::new(r"(?i)-stable(?:[.-]?\d+)*$").unwrap();
+
+    // Two AND separators without a constraint between them
+    static ref DOUBLE_COMMA_RE: Regex = Regex::new(r",\s*,").unwrap();
+
     // Basic comparator
     static ref BASIC_COMPARATOR_RE: Regex = Regex::new(r"^(<>|!=|>=?|<=?|==?)?\s*(.*)").unwrap();
 }
@@ -727,6 +737,14 @@ impl VersionParser {
         let mut or_groups: Vec<Box<dyn ConstraintInterface>> = Vec::new();
 
         for or_constraint in or_constraints {
+            // Commas must separate two constraints
+            if or_constraint.starts_with(',') || or_constraint.ends_with(',') || DOUBLE_COMMA_RE.is_match(or_constraint) {
+                return Err(VersionParserError::ConstraintParseError {
+                    constraint: constraints.to_string(),
+                    reason: "misplaced comma".to_string(),
+                });
+            }
+
             // Split by AND (, or space) - manually handle since Rust regex doesn't support look-behind
             let and_constraints = self.split_and_constraints(or_constraint);
 
@@ -761,6 +779,9 @@ impl VersionParser {
         let mut parts = Vec::new();
         let mut current_start = 0;
         let chars: Vec<char> = input.chars().collect();
+        // Byte offset of each char, `i` and `current_start` count chars
+        let offsets: Vec<usize> = input.char_indices().map(|(offset, _)| offset).chain([input.len()]).collect();
+        let slice = |from: usize, to: usize| &input[offsets[from]..offsets[to]];
         let mut i = 0;
 
         while i < chars.len() {
@@ -784,7 +805,7 @@ impl VersionParser {
 
                 // Check if before this separator we only have operators (no version yet)
                 // This handles cases like ">=  1.0.0" where there are multiple spaces after operator
-                let before = input[current_start..i].trim();
+                let before = slice(current_start, i).trim();
                 if before.is_empty()
                     || before.chars().all(|c| c == '>' || c == '<' || c == '=' || c == '!' || c == '^' || c == '~')
                 {
@@ -823,7 +844,7 @@ impl VersionParser {
 
                 // Check for "as" after separator
                 if i + 2 < chars.len()
-                    && input[i..].starts_with("as ")
+                    && slice(i, chars.len()).starts_with("as ")
                 {
                     continue;
                 }
@@ -834,7 +855,7 @@ impl VersionParser {
                     end -= 1;
                 }
                 if end > current_start {
-                    let part = input[current_start..end].trim();
+                    let part = slice(current_start, end).trim();
                     if !part.is_empty() && part != "," {
                         parts.push(part);
                     }
@@ -846,8 +867,8 @@ impl VersionParser {
         }
 
         // Add remaining part
-        if current_start < input.len() {
-            let remaining = input[current_start..].trim();
+        if current_start < chars.len() {
+            let remaining = slice(current_start, chars.len()).trim();
             if !remaining.is_empty() {
                 parts.push(remaining);
             }
@@ -871,9 +892,9 @@ impl VersionParser {
         };
 
         // Strip @stability flags
-        let (constraint, _stability_modifier) = if let Some(caps) = CONSTRAINT_STABILITY_RE.captures(constraint) {
+        let (constraint, stability_modifier) = if let Some(caps) = CONSTRAINT_STABILITY_RE.captures(constraint) {
             let c = caps.get(1).map_or("*", |m| if m.as_str().is_empty() { "*" } else { m.as_str() });
-            let s = caps.get(2).map(|m| m.as_str());
+            let s = caps.get(2).map(|m| m.as_str()).filter(|s| !s.eq_ignore_ascii_case("stable"));
             (c, s)
         } else {
             (constraint, None)
@@ -961,14 +982,20 @@ impl VersionParser {
                 _ => return Err(VersionParserError::InvalidOperator(operator.to_string())),
             };
 
-            // Append -dev for < and >= operators on stable versions
-            let version = if (op == Operator::LessThan || op == Operator::GreaterThanOrEqual)
-                && !version.contains("-")
-                && !version.starts_with("dev-")
-            {
-                format!("{}-dev", version)
-            } else {
-                version
+            // A stability flag lowers the bound of comparisons with stable versions,
+            // otherwise < and >= include the dev versions unless a stability is given
+            let version = match stability_modifier {
+                Some(modifier) if op != Operator::Equal && Self::parse_stability(&version) == Stability::Stable => {
+                    format!("{}-{}", version, modifier)
+                }
+                _ if (op == Operator::LessThan || op == Operator::GreaterThanOrEqual)
+                    && !version.contains('-')
+                    && !version.starts_with("dev-")
+                    && !STABLE_SUFFIX_RE.is_match(version_str) =>
+                {
+                    format!("{}-dev", version)
+                }
+                _ => version,
             };
 
             return Ok(vec![Box::new(Constraint::new(op, version)?)]);
@@ -1187,11 +1214,19 @@ impl VersionParser {
 }
 
 /// Reusable, pre-parsed constraints for repeated checks.
+///
+/// Displayed and serialized as the original constraint string.
+#[derive(Debug, Clone)]
 pub struct ParsedConstraints {
     constraints: Box<dyn ConstraintInterface>,
 }
 
 impl ParsedConstraints {
+    /// The parsed constraint tree
+    pub fn constraint(&self) -> &dyn ConstraintInterface {
+        self.constraints.as_ref()
+    }
+
     /// Check a normalized version string against the parsed constraints.
     pub fn matches_normalized(&self, normalized_version: &str) -> bool {
         match Constraint::new(Operator::Equal, normalized_version.to_string()) {
@@ -1211,6 +1246,33 @@ impl ParsedConstraints {
     }
 }
 
+impl FromStr for ParsedConstraints {
+    type Err = VersionParserError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        VersionParser::new().parse_constraints_cached(s)
+    }
+}
+
+impl fmt::Display for ParsedConstraints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.constraints.pretty_string())
+    }
+}
+
+impl Serialize for ParsedConstraints {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.constraints.pretty_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for ParsedConstraints {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl Default for VersionParser {
     fn default() -> Self {
         Self::new()
@@ -1662,4 +1724,32 @@ mod tests {
         let v100 = Constraint::new(Operator::Equal, "1.0.0.0".to_string()).unwrap();
         assert!(!constraint.matches(&v100), "1.0.0.0 should NOT match ^2.3 || ^3.0");
     }
+
+    #[test]
+    fn test_parsed_constraints_from_str_and_serde() {
+        let parsed: ParsedConstraints = "^2.3 || ^3.0".parse().unwrap();
+        assert_eq!(parsed.to_string(), "^2.3 || ^3.0");
+        assert_eq!(parsed.constraint().to_string(), "[>= 2.3.0.0-dev < 4.0.0.0-dev]");
+        assert!(parsed.satisfies("3.1.0"));
+
+        #[derive(Serialize, Deserialize)]
+        struct Config {
+            php: ParsedConstraints,
+        }
+
+        let config: Config = serde_json::from_str(r#"{"php": ">=8.1 <8.4"}"#).unwrap();
+        assert!(config.php.satisfies("8.2.0"));
+        assert!(!config.php.satisfies("8.4.0"));
+        assert_eq!(serde_json::to_string(&config).unwrap(), r#"{"php":">=8.1 <8.4"}"#);
+
+        assert!(serde_json::from_str::<Config>(r#"{"php": "not a constraint"}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_constraints_with_multibyte_chars() {
+        let parser = VersionParser::new();
+        assert!(parser.parse_constraints("é, ^1.0").is_err());
+        assert!(parser.parse_constraints("^1.0 ü").is_err());
+        assert!(parser.parse_constraints(">=1.0 <2.0").unwrap().to_string().contains("< 2.0.0.0-dev"));
+    }
 }
@@ -0,0 +1,17 @@
+//! Run the composer/semver fixture suites of `pox_semver::conformance`.
+
+#![cfg(feature = "conformance")]
+
+use pox_semver::conformance::check;
+
+#[test]
+fn test_composer_semver_fixtures() {
+    let divergences = check();
+    let report: Vec<String> = divergences.iter().map(ToString::to_string).collect();
+    assert!(
+        divergences.is_empty(),
+        "{} fixtures differ from composer/semver:\n{}",
+        divergences.len(),
+        report.join("\n")
+    );
+}
@@ -0,0 +1,45 @@
+//! Compare constraint matching with PHP's composer/semver.
+//!
+//! The fixture cases always run. Set `POX_SEMVER_AUTOLOAD` to the
+//! `vendor/autoload.php` of a project requiring composer/semver (and
+//! `POX_SEMVER_PHP` if `php` is not on the path) to also check thousands of
+//! generated cases against PHP, `POX_SEMVER_CASES` changes how many.
+
+#![cfg(feature = "differential")]
+
+use std::path::Path;
+
+use pox_semver::differential::{compare, generate_cases, FixtureReference, Mismatch, PhpReference};
+
+fn assert_no_mismatches(mismatches: &[Mismatch]) {
+    let report: Vec<String> = mismatches.iter().take(50).map(ToString::to_string).collect();
+    assert!(
+        mismatches.is_empty(),
+        "{} cases differ from composer/semver:\n{}",
+        mismatches.len(),
+        report.join("\n")
+    );
+}
+
+#[test]
+fn test_fixtures() {
+    let mut reference = FixtureReference::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/composer_semver.json")).unwrap();
+    let cases = reference.cases();
+    assert!(!cases.is_empty());
+
+    assert_no_mismatches(&compare(&cases, &mut reference).unwrap());
+}
+
+#[test]
+fn test_generated_cases_against_php() {
+    let Some(mut reference) = PhpReference::from_env() else {
+        eprintln!("POX_SEMVER_AUTOLOAD is not set, skipping the comparison with PHP");
+        return;
+    };
+
+    let count = std::env::var("POX_SEMVER_CASES").ok().and_then(|c| c.parse().ok()).unwrap_or(5000);
+    for seed in [1, 2, 3] {
+        let cases = generate_cases(seed, count);
+        assert_no_mismatches(&compare(&cases, &mut reference).unwrap());
+    }
+}
@@ -0,0 +1,118 @@
+//! Property tests for the version parser and constraint matching.
+
+use proptest::prelude::*;
+
+use pox_semver::{Comparator, Semver, VersionParser};
+
+const SUFFIXES: &[&str] = &["", "-alpha1", "-beta2", "-RC1", "-dev", "-p1"];
+const OPERATORS: &[&str] = &["", "==", "!=", ">", ">=", "<", "<=", "^", "~"];
+
+fn stable_version() -> impl Strategy<Value = (u32, u32, u32)> {
+    (0..20u32, 0..20u32, 0..20u32)
+}
+
+fn version() -> impl Strategy<Value = String> {
+    (stable_version(), prop::sample::select(SUFFIXES))
+        .prop_map(|((major, minor, patch), suffix)| format!("{}.{}.{}{}", major, minor, patch, suffix))
+}
+
+fn simple_constraint() -> impl Strategy<Value = String> {
+    (prop::sample::select(OPERATORS), version()).prop_map(|(operator, version)| format!("{}{}", operator, version))
+}
+
+/// Whether `major.minor.patch` satisfies `constraint`
+fn satisfies((major, minor, patch): (u32, u32, u32), constraint: &str) -> bool {
+    Semver::satisfies(&format!("{}.{}.{}", major, minor, patch), constraint)
+}
+
+fn normalize(version: &str) -> String {
+    VersionParser::new().normalize(version).unwrap()
+}
+
+proptest! {
+    #[test]
+    fn parsing_never_panics(input in "\\PC{0,40}") {
+        let parser = VersionParser::new();
+        let _ = parser.normalize(&input);
+        let _ = parser.parse_constraints(&input);
+    }
+
+    #[test]
+    fn parsing_constraint_like_input_never_panics(input in "[0-9.*^~<>=!|, @a-zA-Z-]{0,30}") {
+        let parser = VersionParser::new();
+        let _ = parser.normalize(&input);
+        let _ = parser.parse_constraints(&input);
+    }
+
+    #[test]
+    fn normalize_is_idempotent(version in version()) {
+        let normalized = normalize(&version);
+        prop_assert_eq!(normalize(&normalized), normalized);
+    }
+
+    #[test]
+    fn version_satisfies_itself(version in version()) {
+        prop_assert!(Semver::satisfies(&version, &version));
+        let equal = format!("=={}", version);
+        prop_assert!(Semver::satisfies(&version, &equal));
+    }
+
+    #[test]
+    fn caret_allows_minor_and_patch_updates((major, minor, patch) in stable_version()) {
+        prop_assume!(major > 0);
+        let caret = format!("^{}.{}.{}", major, minor, patch);
+
+        prop_assert!(satisfies((major, minor, patch), &caret));
+        prop_assert!(satisfies((major, minor + 1, 0), &caret));
+        prop_assert!(!satisfies((major + 1, 0, 0), &caret));
+        if patch > 0 {
+            prop_assert!(!satisfies((major, minor, patch - 1), &caret));
+        }
+    }
+
+    #[test]
+    fn tilde_stays_within_major((major, minor, patch) in stable_version(), bump in 0..5u32) {
+        let tilde = format!("~{}.{}", major, minor);
+
+        prop_assert!(satisfies((major, minor + bump, patch), &tilde));
+        prop_assert!(!satisfies((major + 1, 0, 0), &tilde));
+    }
+
+    #[test]
+    fn range_matches_like_comparator(from in stable_version(), to in stable_version(), version in stable_version()) {
+        let format = |(major, minor, patch): (u32, u32, u32)| format!("{}.{}.{}", major, minor, patch);
+        let (from, to, version) = (format(from), format(to), format(version));
+
+        let expected = Comparator::greater_than_or_equal_to(&version, &from) && Comparator::less_than(&version, &to);
+        prop_assert_eq!(Semver::satisfies(&version, &format!(">={} <{}", from, to)), expected);
+    }
+
+    #[test]
+    fn or_and_and_combine_their_parts(version in version(), a in simple_constraint(), b in simple_constraint()) {
+        let matches_a = Semver::satisfies(&version, &a);
+        let matches_b = Semver::satisfies(&version, &b);
+
+        prop_assert_eq!(Semver::satisfies(&version, &format!("{} || {}", a, b)), matches_a || matches_b);
+        prop_assert_eq!(Semver::satisfies(&version, &format!("{}, {}", a, b)), matches_a && matches_b);
+    }
+
+    #[test]
+    fn comparison_is_a_total_order(a in version(), b in version()) {
+        let (a, b) = (normalize(&a), normalize(&b));
+        let outcomes = [Comparator::less_than(&a, &b), Comparator::equal_to(&a, &b), Comparator::greater_than(&a, &b)];
+
+        prop_assert_eq!(outcomes.iter().filter(|o| **o).count(), 1);
+        prop_assert_eq!(Comparator::less_than(&a, &b), Comparator::greater_than(&b, &a));
+    }
+
+    #[test]
+    fn sort_orders_versions(versions in prop::collection::vec(version(), 0..20)) {
+        let versions: Vec<&str> = versions.iter().map(String::as_str).collect();
+        let sorted = Semver::sort(&versions);
+
+        prop_assert_eq!(sorted.len(), versions.len());
+        for pair in sorted.windows(2) {
+            prop_assert!(Comparator::less_than_or_equal_to(&normalize(&pair[0]), &normalize(&pair[1])));
+        }
+    }
+}
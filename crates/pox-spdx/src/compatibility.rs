@@ -0,0 +1,281 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::LicenseExpr;
+
+/// How a project is used, which decides when copyleft obligations apply.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LicenseUsage {
+    /// The project is shipped to others, so every copyleft license applies
+    #[default]
+    Distribute,
+    /// The project is only offered as a network service, so only network copyleft applies
+    Network,
+    /// The project is never given to or served to third parties
+    Internal,
+}
+
+impl LicenseUsage {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "distribute" => Some(LicenseUsage::Distribute),
+            "network" => Some(LicenseUsage::Network),
+            "internal" => Some(LicenseUsage::Internal),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LicenseUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseUsage::Distribute => write!(f, "distribute"),
+            LicenseUsage::Network => write!(f, "network"),
+            LicenseUsage::Internal => write!(f, "internal"),
+        }
+    }
+}
+
+/// Coarse classification of a license used by the compatibility rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LicenseKind {
+    /// Only asks for attribution (MIT, BSD, Apache-2.0, ...)
+    Permissive,
+    /// Copyleft limited to the licensed files or library (LGPL, MPL, EPL, ...)
+    WeakCopyleft,
+    /// Copyleft extending to the whole distributed work (GPL, ...)
+    StrongCopyleft,
+    /// Strong copyleft that also applies to software offered over a network (AGPL, ...)
+    NetworkCopyleft,
+    /// Not covered by the ruleset, never reported as incompatible
+    Unknown,
+}
+
+impl fmt::Display for LicenseKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseKind::Permissive => write!(f, "permissive"),
+            LicenseKind::WeakCopyleft => write!(f, "weak copyleft"),
+            LicenseKind::StrongCopyleft => write!(f, "strong copyleft"),
+            LicenseKind::NetworkCopyleft => write!(f, "network copyleft"),
+            LicenseKind::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+const PERMISSIVE: &[&str] = &[
+    "0BSD", "AFL-3.0", "Apache-2.0", "Artistic-2.0", "BSD-2-Clause", "BSD-3-Clause", "BSL-1.0",
+    "CC-BY-4.0", "CC0-1.0", "ISC", "MIT", "MIT-0", "PHP-3.0", "PHP-3.01", "PostgreSQL",
+    "Unlicense", "UPL-1.0", "WTFPL", "Zlib",
+];
+
+const WEAK_COPYLEFT: &[&str] = &[
+    "CDDL-1.0", "CDDL-1.1", "EPL-1.0", "EPL-2.0", "LGPL-2.0", "LGPL-2.1", "LGPL-3.0",
+    "MPL-1.1", "MPL-2.0",
+];
+
+const STRONG_COPYLEFT: &[&str] = &["CECILL-2.1", "EUPL-1.1", "EUPL-1.2", "GPL-1.0", "GPL-2.0", "GPL-3.0", "OSL-3.0"];
+
+const NETWORK_COPYLEFT: &[&str] = &["AGPL-1.0", "AGPL-3.0", "SSPL-1.0"];
+
+/// Strip the `-only` / `-or-later` suffix, returning the base identifier and whether later versions apply.
+fn split_version(id: &str, or_later: bool) -> (&str, bool) {
+    if let Some(base) = id.strip_suffix("-or-later") {
+        (base, true)
+    } else if let Some(base) = id.strip_suffix("-only") {
+        (base, or_later)
+    } else {
+        (id, or_later)
+    }
+}
+
+/// Classify an SPDX license identifier.
+pub fn license_kind(id: &str) -> LicenseKind {
+    let (base, _) = split_version(id, false);
+    let is = |list: &[&str]| list.iter().any(|l| l.eq_ignore_ascii_case(base));
+
+    if is(PERMISSIVE) {
+        LicenseKind::Permissive
+    } else if is(WEAK_COPYLEFT) {
+        LicenseKind::WeakCopyleft
+    } else if is(STRONG_COPYLEFT) {
+        LicenseKind::StrongCopyleft
+    } else if is(NETWORK_COPYLEFT) {
+        LicenseKind::NetworkCopyleft
+    } else {
+        LicenseKind::Unknown
+    }
+}
+
+/// Versions of the GNU GPL the license can be distributed under.
+///
+/// The LGPL may be converted to the GPL and the AGPL-3.0 may be combined with
+/// the GPL-3.0, so both share the GPL versions.
+fn gpl_versions(id: &str, or_later: bool) -> &'static [u8] {
+    let (base, later) = split_version(id, or_later);
+    match base.to_ascii_uppercase().as_str() {
+        "GPL-1.0" if later => &[1, 2, 3],
+        "GPL-1.0" => &[1],
+        "GPL-2.0" if later => &[2, 3],
+        "GPL-2.0" => &[2],
+        "LGPL-2.0" | "LGPL-2.1" => &[2, 3],
+        "GPL-3.0" | "LGPL-3.0" | "AGPL-3.0" => &[3],
+        _ => &[],
+    }
+}
+
+fn format_versions(versions: &[u8]) -> String {
+    versions
+        .iter()
+        .map(|v| format!("GPL-{}.0", v))
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
+
+/// Why a dependency license cannot be combined with the project license.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Incompatibility {
+    /// License of the dependency
+    pub license: String,
+    /// License of the project it was checked against
+    pub project: String,
+    /// Explanation of the rule that failed
+    pub reason: String,
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is incompatible with {}: {}", self.license, self.project, self.reason)
+    }
+}
+
+impl LicenseExpr {
+    /// Check whether a dependency under this license can be used by a project under `project`.
+    ///
+    /// The project may pick any alternative of an OR, an AND requires every
+    /// operand. Licenses outside the ruleset and `LicenseRef-` references are
+    /// accepted, while a reference project license (e.g. proprietary) is never
+    /// compatible with copyleft.
+    pub fn check_compatibility(&self, project: &LicenseExpr, usage: LicenseUsage) -> Result<(), Vec<Incompatibility>> {
+        match project {
+            LicenseExpr::With(license, _) => self.check_compatibility(license, usage),
+            LicenseExpr::Or(items) => {
+                let mut incompatibilities = Vec::new();
+                for item in items {
+                    match self.check_compatibility(item, usage) {
+                        Ok(()) => return Ok(()),
+                        Err(i) => incompatibilities.extend(i),
+                    }
+                }
+                Err(incompatibilities)
+            }
+            LicenseExpr::And(items) => collect_all(items.iter().map(|i| self.check_compatibility(i, usage))),
+            LicenseExpr::Id { .. } | LicenseExpr::Ref(_) => self.check_against(project, usage),
+        }
+    }
+
+    fn check_against(&self, project: &LicenseExpr, usage: LicenseUsage) -> Result<(), Vec<Incompatibility>> {
+        match self {
+            LicenseExpr::Id { id, or_later } => {
+                incompatibility(id, *or_later, project, usage).map_or(Ok(()), |reason| {
+                    Err(vec![Incompatibility {
+                        license: self.to_string(),
+                        project: project.to_string(),
+                        reason,
+                    }])
+                })
+            }
+            LicenseExpr::Ref(_) => Ok(()),
+            LicenseExpr::With(license, _) => license.check_against(project, usage),
+            LicenseExpr::And(items) => collect_all(items.iter().map(|i| i.check_against(project, usage))),
+            LicenseExpr::Or(items) => {
+                let mut incompatibilities = Vec::new();
+                for item in items {
+                    match item.check_against(project, usage) {
+                        Ok(()) => return Ok(()),
+                        Err(i) => incompatibilities.extend(i),
+                    }
+                }
+                Err(incompatibilities)
+            }
+        }
+    }
+}
+
+fn collect_all(results: impl Iterator<Item = Result<(), Vec<Incompatibility>>>) -> Result<(), Vec<Incompatibility>> {
+    let incompatibilities: Vec<Incompatibility> = results.filter_map(|r| r.err()).flatten().collect();
+    if incompatibilities.is_empty() {
+        Ok(())
+    } else {
+        Err(incompatibilities)
+    }
+}
+
+/// Apply the ruleset to a single dependency license, returning the reason it is incompatible.
+fn incompatibility(id: &str, or_later: bool, project: &LicenseExpr, usage: LicenseUsage) -> Option<String> {
+    let kind = license_kind(id);
+    let applies = match kind {
+        LicenseKind::NetworkCopyleft => usage != LicenseUsage::Internal,
+        LicenseKind::StrongCopyleft | LicenseKind::Permissive => usage == LicenseUsage::Distribute,
+        LicenseKind::WeakCopyleft | LicenseKind::Unknown => false,
+    };
+    if !applies {
+        return None;
+    }
+
+    let (project_id, project_later) = match project {
+        LicenseExpr::Id { id, or_later } => (id.as_str(), *or_later),
+        _ => ("", false),
+    };
+    let (base, _) = split_version(id, or_later);
+    let (project_base, _) = split_version(project_id, project_later);
+    let project_versions = gpl_versions(project_id, project_later);
+
+    if kind == LicenseKind::Permissive {
+        // The patent clauses of the Apache-2.0 conflict with the GPL-2.0 only
+        if base.eq_ignore_ascii_case("Apache-2.0") && !project_versions.is_empty() && !project_versions.contains(&3) {
+            return Some(format!(
+                "Apache-2.0 can only be combined with GPL-3.0 compatible licenses, but the project can only be distributed under {}",
+                format_versions(project_versions)
+            ));
+        }
+        return None;
+    }
+
+    if !project_base.is_empty() && base.eq_ignore_ascii_case(project_base) {
+        return None;
+    }
+
+    if base.eq_ignore_ascii_case("AGPL-3.0") {
+        // GPL-3.0 section 13 allows the combination, the AGPL terms still cover the dependency
+        if project_versions.contains(&3) {
+            return None;
+        }
+    } else {
+        let versions = gpl_versions(id, or_later);
+        if !versions.is_empty() {
+            if versions.iter().any(|v| project_versions.contains(v)) {
+                return None;
+            }
+            return Some(format!(
+                "{} is {} and requires the combined work to be distributed under {}",
+                id,
+                kind,
+                format_versions(versions)
+            ));
+        }
+    }
+
+    Some(match usage {
+        LicenseUsage::Network => format!(
+            "{} is {} and requires the source of the whole network service to be offered under the same license",
+            id, kind
+        ),
+        _ => format!(
+            "{} is {} and requires the combined work to be distributed under the same license",
+            id, kind
+        ),
+    })
+}
@@ -0,0 +1,306 @@
+use std::fmt;
+
+use crate::SpdxLicenses;
+
+/// Parsed SPDX license expression.
+///
+/// Identifiers are stored with their canonical SPDX casing, so `mit or apache-2.0`
+/// parses to the same tree as `MIT OR Apache-2.0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenseExpr {
+    /// License identifier, optionally followed by `+` (or later)
+    Id { id: String, or_later: bool },
+    /// `LicenseRef-...` or `DocumentRef-...:LicenseRef-...` user defined reference
+    Ref(String),
+    /// License with an exception (`GPL-2.0-only WITH Classpath-exception-2.0`)
+    With(Box<LicenseExpr>, String),
+    /// All operands apply
+    And(Vec<LicenseExpr>),
+    /// Any operand may be chosen
+    Or(Vec<LicenseExpr>),
+}
+
+/// Error returned when a license expression cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The expression is empty
+    Empty,
+    /// Identifier is not a known SPDX license
+    UnknownLicense(String),
+    /// Identifier after WITH is not a known SPDX exception
+    UnknownException(String),
+    /// Parentheses or operators are malformed
+    InvalidSyntax(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty license expression"),
+            ParseError::UnknownLicense(id) => write!(f, "unknown license identifier \"{}\"", id),
+            ParseError::UnknownException(id) => write!(f, "unknown license exception \"{}\"", id),
+            ParseError::InvalidSyntax(expr) => write!(f, "invalid license expression \"{}\"", expr),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Allow/deny list of license identifiers used to evaluate expressions.
+///
+/// An empty allow list allows every license that is not explicitly denied.
+/// Matching is case-insensitive and entries may be plain identifiers or `LicenseRef-` names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LicensePolicy {
+    pub allowed: Vec<String>,
+    pub denied: Vec<String>,
+}
+
+/// Why a license in an expression does not satisfy a policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    /// License is on the deny list
+    Denied(String),
+    /// License is not on the allow list
+    NotAllowed(String),
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::Denied(id) => write!(f, "{} is denied by policy", id),
+            PolicyViolation::NotAllowed(id) => write!(f, "{} is not in the list of allowed licenses", id),
+        }
+    }
+}
+
+impl LicensePolicy {
+    pub fn new(allowed: Vec<String>, denied: Vec<String>) -> Self {
+        Self { allowed, denied }
+    }
+
+    /// Check a single license identifier against the policy.
+    pub fn check_license(&self, id: &str) -> Result<(), PolicyViolation> {
+        if self.denied.iter().any(|d| d.eq_ignore_ascii_case(id)) {
+            return Err(PolicyViolation::Denied(id.to_string()));
+        }
+        if !self.allowed.is_empty() && !self.allowed.iter().any(|a| a.eq_ignore_ascii_case(id)) {
+            return Err(PolicyViolation::NotAllowed(id.to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl LicenseExpr {
+    /// Flatten nested operators of the same kind and remove duplicate operands.
+    pub fn normalize(&self) -> LicenseExpr {
+        match self {
+            LicenseExpr::And(items) => Self::normalize_operands(items, true),
+            LicenseExpr::Or(items) => Self::normalize_operands(items, false),
+            LicenseExpr::With(license, exception) => {
+                LicenseExpr::With(Box::new(license.normalize()), exception.clone())
+            }
+            other => other.clone(),
+        }
+    }
+
+    fn normalize_operands(items: &[LicenseExpr], is_and: bool) -> LicenseExpr {
+        let mut operands: Vec<LicenseExpr> = Vec::new();
+        for item in items {
+            let normalized = item.normalize();
+            let nested = match (&normalized, is_and) {
+                (LicenseExpr::And(inner), true) | (LicenseExpr::Or(inner), false) => inner.clone(),
+                _ => vec![normalized],
+            };
+            for operand in nested {
+                if !operands.contains(&operand) {
+                    operands.push(operand);
+                }
+            }
+        }
+
+        if operands.len() == 1 {
+            return operands.remove(0);
+        }
+        if is_and {
+            LicenseExpr::And(operands)
+        } else {
+            LicenseExpr::Or(operands)
+        }
+    }
+
+    /// All license identifiers and references used in the expression.
+    pub fn license_ids(&self) -> Vec<&str> {
+        match self {
+            LicenseExpr::Id { id, .. } => vec![id.as_str()],
+            LicenseExpr::Ref(r) => vec![r.as_str()],
+            LicenseExpr::With(license, _) => license.license_ids(),
+            LicenseExpr::And(items) | LicenseExpr::Or(items) => {
+                items.iter().flat_map(|i| i.license_ids()).collect()
+            }
+        }
+    }
+
+    /// Returns true if the expression can be satisfied under the policy.
+    pub fn is_compatible_with(&self, policy: &LicensePolicy) -> bool {
+        self.check_policy(policy).is_ok()
+    }
+
+    /// Evaluate the expression against a policy.
+    ///
+    /// An OR is satisfied by any operand and an AND requires every operand. On failure
+    /// the violations of the operands that could not be satisfied are returned.
+    pub fn check_policy(&self, policy: &LicensePolicy) -> Result<(), Vec<PolicyViolation>> {
+        match self {
+            LicenseExpr::Id { id, .. } | LicenseExpr::Ref(id) => {
+                policy.check_license(id).map_err(|v| vec![v])
+            }
+            LicenseExpr::With(license, _) => license.check_policy(policy),
+            LicenseExpr::And(items) => {
+                let violations: Vec<PolicyViolation> = items
+                    .iter()
+                    .filter_map(|i| i.check_policy(policy).err())
+                    .flatten()
+                    .collect();
+                if violations.is_empty() {
+                    Ok(())
+                } else {
+                    Err(violations)
+                }
+            }
+            LicenseExpr::Or(items) => {
+                let mut violations = Vec::new();
+                for item in items {
+                    match item.check_policy(policy) {
+                        Ok(()) => return Ok(()),
+                        Err(v) => violations.extend(v),
+                    }
+                }
+                Err(violations)
+            }
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>, parent_is_and: bool) -> fmt::Result {
+        match self {
+            LicenseExpr::Or(_) if parent_is_and => write!(f, "({})", self),
+            _ => write!(f, "{}", self),
+        }
+    }
+}
+
+impl fmt::Display for LicenseExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseExpr::Id { id, or_later } => {
+                write!(f, "{}{}", id, if *or_later { "+" } else { "" })
+            }
+            LicenseExpr::Ref(r) => write!(f, "{}", r),
+            LicenseExpr::With(license, exception) => write!(f, "{} WITH {}", license, exception),
+            LicenseExpr::And(items) | LicenseExpr::Or(items) => {
+                let is_and = matches!(self, LicenseExpr::And(_));
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " {} ", if is_and { "AND" } else { "OR" })?;
+                    }
+                    item.fmt_operand(f, is_and)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl SpdxLicenses {
+    /// Parses a license expression into an AST with canonical identifier casing.
+    pub fn parse(&self, expr: &str) -> Result<LicenseExpr, ParseError> {
+        let expr = expr.trim();
+        if expr.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        if expr.eq_ignore_ascii_case("NONE") || expr.eq_ignore_ascii_case("NOASSERTION") {
+            return Ok(LicenseExpr::Id {
+                id: expr.to_uppercase(),
+                or_later: false,
+            });
+        }
+
+        self.parse_node(expr)
+    }
+
+    /// Parses a Composer license array, where multiple entries are alternatives.
+    pub fn parse_array(&self, licenses: &[&str]) -> Result<LicenseExpr, ParseError> {
+        match licenses {
+            [] => Err(ParseError::Empty),
+            [single] => self.parse(single),
+            many => {
+                let items = many
+                    .iter()
+                    .map(|l| self.parse(l))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(LicenseExpr::Or(items))
+            }
+        }
+    }
+
+    fn parse_node(&self, expr: &str) -> Result<LicenseExpr, ParseError> {
+        let expr = expr.trim();
+        if expr.is_empty() {
+            return Err(ParseError::InvalidSyntax(expr.to_string()));
+        }
+
+        if let Some((left, right)) = self.split_by_operator(expr, "OR") {
+            return Ok(LicenseExpr::Or(vec![self.parse_node(left)?, self.parse_node(right)?]));
+        }
+
+        if let Some((left, right)) = self.split_by_operator(expr, "AND") {
+            return Ok(LicenseExpr::And(vec![self.parse_node(left)?, self.parse_node(right)?]));
+        }
+
+        if expr.starts_with('(') && expr.ends_with(')') {
+            return self.parse_node(&expr[1..expr.len() - 1]);
+        }
+
+        if expr.contains('(') || expr.contains(')') {
+            return Err(ParseError::InvalidSyntax(expr.to_string()));
+        }
+
+        if let Some((license, exception)) = self.split_by_with(expr) {
+            let license = self.parse_simple(license)?;
+            let exception = self
+                .exceptions
+                .get(&exception.to_lowercase())
+                .map(|e| e.0.clone())
+                .ok_or_else(|| ParseError::UnknownException(exception.to_string()))?;
+            return Ok(LicenseExpr::With(Box::new(license), exception));
+        }
+
+        self.parse_simple(expr)
+    }
+
+    fn parse_simple(&self, expr: &str) -> Result<LicenseExpr, ParseError> {
+        let expr = expr.trim();
+        if expr.contains(char::is_whitespace) {
+            return Err(ParseError::InvalidSyntax(expr.to_string()));
+        }
+
+        let (id, or_later) = match expr.strip_suffix('+') {
+            Some(id) => (id, true),
+            None => (expr, false),
+        };
+
+        if let Some(license) = self.licenses.get(&id.to_lowercase()) {
+            return Ok(LicenseExpr::Id {
+                id: license.0.clone(),
+                or_later,
+            });
+        }
+
+        if self.is_valid_simple_expression(expr) {
+            return Ok(LicenseExpr::Ref(expr.to_string()));
+        }
+
+        Err(ParseError::UnknownLicense(expr.to_string()))
+    }
+}
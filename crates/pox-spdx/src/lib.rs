@@ -2,10 +2,27 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+mod compatibility;
+mod expression;
+mod refresh;
+
+pub use compatibility::{license_kind, Incompatibility, LicenseKind, LicenseUsage};
+pub use expression::{LicenseExpr, LicensePolicy, ParseError, PolicyViolation};
+pub use refresh::{compare_list_versions, SpdxData, EXCEPTIONS_URL, LICENSES_URL};
 
 const LICENSES_JSON: &str = include_str!("../res/spdx-licenses.json");
 const EXCEPTIONS_JSON: &str = include_str!("../res/spdx-exceptions.json");
 
+/// File name of the license list in a data directory, see [`SpdxLicenses::from_dir`]
+pub const LICENSES_FILE: &str = "spdx-licenses.json";
+/// File name of the exception list in a data directory, see [`SpdxLicenses::from_dir`]
+pub const EXCEPTIONS_FILE: &str = "spdx-exceptions.json";
+/// File name of the license list version in a data directory, see [`SpdxData::version_in`]
+pub const VERSION_FILE: &str = "spdx-version";
+
 /// License information: (identifier, full_name, osi_approved, deprecated)
 pub type LicenseInfo = (String, String, bool, bool);
 
@@ -23,6 +40,18 @@ pub struct SpdxLicenses {
     exceptions: HashMap<String, ExceptionInfo>,
 }
 
+/// Error returned when license or exception data cannot be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataError(String);
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid SPDX data: {}", self.0)
+    }
+}
+
+impl std::error::Error for DataError {}
+
 impl Default for SpdxLicenses {
     fn default() -> Self {
         Self::new()
@@ -30,14 +59,43 @@ impl Default for SpdxLicenses {
 }
 
 impl SpdxLicenses {
+    /// Load the license data bundled with the crate.
     pub fn new() -> Self {
+        Self::new_with_data(LICENSES_JSON, EXCEPTIONS_JSON).expect("bundled SPDX data is valid")
+    }
+
+    /// Load license data in the format of the bundled `res/spdx-licenses.json`
+    /// and `res/spdx-exceptions.json` files.
+    pub fn new_with_data(licenses_json: &str, exceptions_json: &str) -> Result<Self, DataError> {
         let mut instance = Self {
             licenses: HashMap::new(),
             exceptions: HashMap::new(),
         };
-        instance.load_licenses();
-        instance.load_exceptions();
-        instance
+        instance.load_licenses(licenses_json)?;
+        instance.load_exceptions(exceptions_json)?;
+        Ok(instance)
+    }
+
+    /// Load the data files written by a refresh into `dir`, falling back to the
+    /// bundled data if they are missing or invalid.
+    pub fn from_dir(dir: &Path) -> Self {
+        let read = |file: &str| std::fs::read_to_string(dir.join(file)).ok();
+        match (read(LICENSES_FILE), read(EXCEPTIONS_FILE)) {
+            (Some(licenses), Some(exceptions)) => {
+                Self::new_with_data(&licenses, &exceptions).unwrap_or_else(|_| Self::new())
+            }
+            _ => Self::new(),
+        }
+    }
+
+    /// Number of known license identifiers, including deprecated ones.
+    pub fn license_count(&self) -> usize {
+        self.licenses.len()
+    }
+
+    /// Number of known license exception identifiers.
+    pub fn exception_count(&self) -> usize {
+        self.exceptions.len()
     }
 
     /// Returns license metadata by license identifier.
@@ -126,34 +184,45 @@ impl SpdxLicenses {
         self.is_valid_license_string(&license)
     }
 
-    fn load_licenses(&mut self) {
+    fn load_licenses(&mut self, json: &str) -> Result<(), DataError> {
         let json: HashMap<String, Value> =
-            serde_json::from_str(LICENSES_JSON).expect("Failed to parse licenses JSON");
+            serde_json::from_str(json).map_err(|e| DataError(format!("licenses: {}", e)))?;
 
         for (identifier, license) in json {
-            let arr = license.as_array().expect("License should be an array");
-            let name = arr[0].as_str().expect("Name should be a string").to_string();
-            let osi_approved = arr[1].as_bool().expect("OSI approved should be a bool");
-            let deprecated = arr[2].as_bool().expect("Deprecated should be a bool");
+            let invalid = || DataError(format!("license {} is not a [name, osi, deprecated] array", identifier));
+            let arr = license.as_array().ok_or_else(invalid)?;
+            let name = arr.first().and_then(|v| v.as_str()).ok_or_else(invalid)?.to_string();
+            let osi_approved = arr.get(1).and_then(|v| v.as_bool()).ok_or_else(invalid)?;
+            let deprecated = arr.get(2).and_then(|v| v.as_bool()).ok_or_else(invalid)?;
 
             self.licenses.insert(
                 identifier.to_lowercase(),
                 (identifier, name, osi_approved, deprecated),
             );
         }
+
+        if self.licenses.is_empty() {
+            return Err(DataError("the license list is empty".to_string()));
+        }
+        Ok(())
     }
 
-    fn load_exceptions(&mut self) {
+    fn load_exceptions(&mut self, json: &str) -> Result<(), DataError> {
         let json: HashMap<String, Value> =
-            serde_json::from_str(EXCEPTIONS_JSON).expect("Failed to parse exceptions JSON");
+            serde_json::from_str(json).map_err(|e| DataError(format!("exceptions: {}", e)))?;
 
         for (identifier, exception) in json {
-            let arr = exception.as_array().expect("Exception should be an array");
-            let name = arr[0].as_str().expect("Name should be a string").to_string();
+            let name = exception
+                .as_array()
+                .and_then(|arr| arr.first())
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| DataError(format!("exception {} is not a [name] array", identifier)))?
+                .to_string();
 
             self.exceptions
                 .insert(identifier.to_lowercase(), (identifier, name));
         }
+        Ok(())
     }
 
     fn is_valid_license_string(&self, license: &str) -> bool {
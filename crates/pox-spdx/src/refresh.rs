@@ -0,0 +1,115 @@
+//! Conversion of the license lists published on spdx.org into the data format
+//! of the bundled `res/` files, so new SPDX releases can be picked up at
+//! runtime through [`SpdxLicenses::from_dir`](crate::SpdxLicenses::from_dir).
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{DataError, SpdxLicenses, EXCEPTIONS_FILE, LICENSES_FILE, VERSION_FILE};
+
+/// License list in the SPDX JSON format
+pub const LICENSES_URL: &str = "https://spdx.org/licenses/licenses.json";
+/// Exception list in the SPDX JSON format
+pub const EXCEPTIONS_URL: &str = "https://spdx.org/licenses/exceptions.json";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LicenseList {
+    license_list_version: String,
+    licenses: Vec<LicenseEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LicenseEntry {
+    license_id: String,
+    name: String,
+    #[serde(default)]
+    is_osi_approved: bool,
+    #[serde(default)]
+    is_deprecated_license_id: bool,
+}
+
+#[derive(Deserialize)]
+struct ExceptionList {
+    exceptions: Vec<ExceptionEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExceptionEntry {
+    license_exception_id: String,
+    name: String,
+}
+
+/// License and exception data converted from an SPDX release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpdxData {
+    /// Version of the SPDX license list, e.g. `3.25`
+    pub version: String,
+    pub licenses_json: String,
+    pub exceptions_json: String,
+}
+
+impl SpdxData {
+    /// Convert the `licenses.json` and `exceptions.json` documents of an SPDX release.
+    pub fn from_spdx_json(licenses: &str, exceptions: &str) -> Result<Self, DataError> {
+        let licenses: LicenseList =
+            serde_json::from_str(licenses).map_err(|e| DataError(format!("licenses: {}", e)))?;
+        let exceptions: ExceptionList =
+            serde_json::from_str(exceptions).map_err(|e| DataError(format!("exceptions: {}", e)))?;
+
+        let licenses_json: BTreeMap<String, Value> = licenses
+            .licenses
+            .into_iter()
+            .map(|l| (l.license_id, json!([l.name, l.is_osi_approved, l.is_deprecated_license_id])))
+            .collect();
+        let exceptions_json: BTreeMap<String, Value> = exceptions
+            .exceptions
+            .into_iter()
+            .map(|e| (e.license_exception_id, json!([e.name])))
+            .collect();
+
+        let data = Self {
+            version: licenses.license_list_version,
+            licenses_json: serde_json::to_string_pretty(&licenses_json).expect("maps serialize"),
+            exceptions_json: serde_json::to_string_pretty(&exceptions_json).expect("maps serialize"),
+        };
+        // Refuse data the loader would fall back from
+        data.load()?;
+        Ok(data)
+    }
+
+    /// Parse the converted data.
+    pub fn load(&self) -> Result<SpdxLicenses, DataError> {
+        SpdxLicenses::new_with_data(&self.licenses_json, &self.exceptions_json)
+    }
+
+    /// Write the data files read by [`SpdxLicenses::from_dir`], and the version.
+    pub fn write_to(&self, dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join(LICENSES_FILE), &self.licenses_json)?;
+        std::fs::write(dir.join(EXCEPTIONS_FILE), &self.exceptions_json)?;
+        std::fs::write(dir.join(VERSION_FILE), &self.version)
+    }
+
+    /// Version of the data written to `dir` by [`write_to`](Self::write_to), if any.
+    pub fn version_in(dir: &Path) -> Option<String> {
+        std::fs::read_to_string(dir.join(VERSION_FILE))
+            .ok()
+            .map(|version| version.trim().to_string())
+            .filter(|version| !version.is_empty())
+    }
+}
+
+/// Compare two SPDX license list versions like `3.9` and `3.25` part by part.
+pub fn compare_list_versions(a: &str, b: &str) -> Ordering {
+    let parts = |version: &str| -> Vec<u32> {
+        version.split('.').map(|part| part.trim().parse().unwrap_or(0)).collect()
+    };
+    parts(a).cmp(&parts(b))
+}
@@ -322,3 +322,247 @@ fn test_case_insensitivity_operators() {
     assert!(spdx.validate("(MIT and GPL-3.0-only)"));
     assert!(spdx.validate("(MIT AND GPL-3.0-only)"));
 }
+
+// Expression AST tests
+#[test]
+fn test_parse_simple_canonical_casing() {
+    let spdx = licenses();
+    assert_eq!(
+        spdx.parse("mit").unwrap(),
+        LicenseExpr::Id { id: "MIT".to_string(), or_later: false }
+    );
+    assert_eq!(
+        spdx.parse("gpl-2.0+").unwrap(),
+        LicenseExpr::Id { id: "GPL-2.0".to_string(), or_later: true }
+    );
+}
+
+#[test]
+fn test_parse_compound_expression() {
+    let spdx = licenses();
+    let expr = spdx.parse("(mit or apache-2.0) and bsd-3-clause").unwrap();
+    assert_eq!(expr.to_string(), "(MIT OR Apache-2.0) AND BSD-3-Clause");
+    assert_eq!(expr.license_ids(), vec!["MIT", "Apache-2.0", "BSD-3-Clause"]);
+}
+
+#[test]
+fn test_parse_with_exception_and_ref() {
+    let spdx = licenses();
+    let expr = spdx.parse("gpl-2.0-only with classpath-exception-2.0").unwrap();
+    assert_eq!(expr.to_string(), "GPL-2.0-only WITH Classpath-exception-2.0");
+
+    assert_eq!(
+        spdx.parse("LicenseRef-Proprietary").unwrap(),
+        LicenseExpr::Ref("LicenseRef-Proprietary".to_string())
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    let spdx = licenses();
+    assert_eq!(spdx.parse(""), Err(ParseError::Empty));
+    assert_eq!(
+        spdx.parse("MIT OR Foo-1.0"),
+        Err(ParseError::UnknownLicense("Foo-1.0".to_string()))
+    );
+    assert_eq!(
+        spdx.parse("MIT WITH Foo-exception"),
+        Err(ParseError::UnknownException("Foo-exception".to_string()))
+    );
+    assert!(matches!(spdx.parse("(MIT OR"), Err(ParseError::InvalidSyntax(_))));
+}
+
+#[test]
+fn test_parse_agrees_with_validate() {
+    let spdx = licenses();
+    for expr in [
+        "MIT",
+        "NONE",
+        "(MIT OR GPL-2.0-only) AND LGPL-2.1-only",
+        "GPL-2.0-only WITH Autoconf-exception-2.0",
+        "MIT OR",
+        "(MIT",
+        "Foo",
+    ] {
+        assert_eq!(spdx.parse(expr).is_ok(), spdx.validate(expr), "{}", expr);
+    }
+}
+
+#[test]
+fn test_normalize_flattens_and_dedups() {
+    let spdx = licenses();
+    let expr = spdx.parse("MIT OR (mit OR Apache-2.0) OR apache-2.0").unwrap();
+    assert_eq!(expr.normalize().to_string(), "MIT OR Apache-2.0");
+
+    let expr = spdx.parse("MIT AND MIT").unwrap();
+    assert_eq!(
+        expr.normalize(),
+        LicenseExpr::Id { id: "MIT".to_string(), or_later: false }
+    );
+}
+
+#[test]
+fn test_policy_or_expression() {
+    let spdx = licenses();
+    let policy = LicensePolicy::new(vec![], vec!["GPL-3.0-only".to_string()]);
+    let expr = spdx.parse("GPL-3.0-only OR MIT").unwrap();
+    assert!(expr.is_compatible_with(&policy));
+
+    let expr = spdx.parse("GPL-3.0-only").unwrap();
+    assert_eq!(
+        expr.check_policy(&policy),
+        Err(vec![PolicyViolation::Denied("GPL-3.0-only".to_string())])
+    );
+}
+
+#[test]
+fn test_policy_and_expression_explains_failure() {
+    let spdx = licenses();
+    let policy = LicensePolicy::new(vec!["mit".to_string()], vec![]);
+    let expr = spdx.parse("MIT AND (Apache-2.0 OR BSD-3-Clause)").unwrap();
+    let violations = expr.check_policy(&policy).unwrap_err();
+    assert_eq!(
+        violations,
+        vec![
+            PolicyViolation::NotAllowed("Apache-2.0".to_string()),
+            PolicyViolation::NotAllowed("BSD-3-Clause".to_string()),
+        ]
+    );
+    assert_eq!(
+        violations[0].to_string(),
+        "Apache-2.0 is not in the list of allowed licenses"
+    );
+}
+
+#[test]
+fn test_license_kind() {
+    assert_eq!(license_kind("MIT"), LicenseKind::Permissive);
+    assert_eq!(license_kind("LGPL-2.1-or-later"), LicenseKind::WeakCopyleft);
+    assert_eq!(license_kind("GPL-3.0-only"), LicenseKind::StrongCopyleft);
+    assert_eq!(license_kind("AGPL-3.0-or-later"), LicenseKind::NetworkCopyleft);
+    assert_eq!(license_kind("Beerware"), LicenseKind::Unknown);
+}
+
+#[test]
+fn test_compatibility_copyleft_in_permissive_project() {
+    let spdx = licenses();
+    let mit = spdx.parse("MIT").unwrap();
+    let gpl = spdx.parse("GPL-3.0-only").unwrap();
+
+    let incompatibilities = gpl.check_compatibility(&mit, LicenseUsage::Distribute).unwrap_err();
+    assert_eq!(
+        incompatibilities[0].to_string(),
+        "GPL-3.0-only is incompatible with MIT: GPL-3.0-only is strong copyleft and requires the combined work to be distributed under GPL-3.0"
+    );
+    assert!(gpl.check_compatibility(&mit, LicenseUsage::Network).is_ok());
+    assert!(gpl.check_compatibility(&mit, LicenseUsage::Internal).is_ok());
+
+    let agpl = spdx.parse("AGPL-3.0-only").unwrap();
+    assert!(agpl.check_compatibility(&mit, LicenseUsage::Network).is_err());
+    assert!(agpl.check_compatibility(&mit, LicenseUsage::Internal).is_ok());
+
+    for license in ["LGPL-3.0-or-later", "MPL-2.0", "BSD-3-Clause", "LicenseRef-Custom", "GPL-3.0-only OR MIT"] {
+        let dependency = spdx.parse(license).unwrap();
+        assert!(dependency.check_compatibility(&mit, LicenseUsage::Distribute).is_ok(), "{}", license);
+    }
+}
+
+#[test]
+fn test_compatibility_between_gpl_versions() {
+    let spdx = licenses();
+    let check = |dependency: &str, project: &str| {
+        spdx.parse(dependency)
+            .unwrap()
+            .check_compatibility(&spdx.parse(project).unwrap(), LicenseUsage::Distribute)
+            .is_ok()
+    };
+
+    assert!(check("GPL-2.0-or-later", "GPL-3.0-only"));
+    assert!(check("GPL-2.0+", "GPL-3.0-or-later"));
+    assert!(!check("GPL-2.0-only", "GPL-3.0-only"));
+    assert!(check("GPL-3.0-only", "LGPL-2.1-or-later"));
+    assert!(check("AGPL-3.0-only", "GPL-3.0-or-later"));
+    assert!(!check("AGPL-3.0-only", "GPL-2.0-only"));
+    assert!(!check("Apache-2.0", "GPL-2.0-only"));
+    assert!(check("Apache-2.0", "GPL-2.0-or-later"));
+    assert!(check("GPL-3.0-only", "MIT OR GPL-3.0-or-later"));
+    assert!(!check("GPL-3.0-only", "MIT AND GPL-3.0-or-later"));
+}
+
+#[test]
+fn test_compatibility_with_proprietary_project() {
+    let spdx = licenses();
+    let proprietary = LicenseExpr::Ref("proprietary".to_string());
+
+    assert!(spdx.parse("MIT").unwrap().check_compatibility(&proprietary, LicenseUsage::Distribute).is_ok());
+    assert!(spdx.parse("GPL-2.0-or-later").unwrap().check_compatibility(&proprietary, LicenseUsage::Distribute).is_err());
+    assert_eq!(LicenseUsage::parse("Network"), Some(LicenseUsage::Network));
+    assert_eq!(LicenseUsage::parse("saas"), None);
+}
+
+#[test]
+fn test_new_with_data() {
+    let spdx = SpdxLicenses::new_with_data(
+        r#"{"MIT": ["MIT License", true, false], "New-License-1.0": ["A New License", false, false]}"#,
+        r#"{"Classpath-exception-2.0": ["Classpath exception 2.0"]}"#,
+    )
+    .unwrap();
+
+    assert!(spdx.validate("New-License-1.0 WITH Classpath-exception-2.0"));
+    assert!(!spdx.validate("Apache-2.0"));
+    assert_eq!(spdx.license_count(), 2);
+    assert_eq!(spdx.exception_count(), 1);
+
+    assert!(SpdxLicenses::new_with_data("{}", "{}").is_err());
+    assert!(SpdxLicenses::new_with_data(r#"{"MIT": ["MIT License"]}"#, "{}").is_err());
+    assert!(SpdxLicenses::new_with_data(r#"{"MIT": ["MIT License", true, false]}"#, "[]").is_err());
+}
+
+#[test]
+fn test_from_spdx_json_and_dir() {
+    let licenses = r#"{
+        "licenseListVersion": "9.99",
+        "licenses": [
+            {"licenseId": "MIT", "name": "MIT License", "isOsiApproved": true, "isDeprecatedLicenseId": false},
+            {"licenseId": "Future-1.0", "name": "Future License 1.0", "isDeprecatedLicenseId": false}
+        ]
+    }"#;
+    let exceptions = r#"{
+        "licenseListVersion": "9.99",
+        "exceptions": [{"licenseExceptionId": "Future-exception", "name": "Future exception"}]
+    }"#;
+
+    let data = SpdxData::from_spdx_json(licenses, exceptions).unwrap();
+    assert_eq!(data.version, "9.99");
+    assert!(SpdxData::from_spdx_json("{}", exceptions).is_err());
+
+    let dir = std::env::temp_dir().join(format!("pox-spdx-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // Without data files the bundled lists are used
+    assert!(!SpdxLicenses::from_dir(&dir).validate("Future-1.0"));
+
+    assert_eq!(SpdxData::version_in(&dir), None);
+    data.write_to(&dir).unwrap();
+    assert_eq!(SpdxData::version_in(&dir).as_deref(), Some("9.99"));
+    let spdx = SpdxLicenses::from_dir(&dir);
+    assert!(spdx.validate("Future-1.0 WITH Future-exception"));
+    assert!(!spdx.is_osi_approved_by_identifier("Future-1.0"));
+    assert!(spdx.is_osi_approved_by_identifier("MIT"));
+
+    std::fs::write(dir.join(LICENSES_FILE), "not json").unwrap();
+    assert!(SpdxLicenses::from_dir(&dir).validate("Apache-2.0"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_compare_list_versions() {
+    use std::cmp::Ordering;
+
+    assert_eq!(compare_list_versions("3.25", "3.9"), Ordering::Greater);
+    assert_eq!(compare_list_versions("3.9", "3.25"), Ordering::Less);
+    assert_eq!(compare_list_versions("3.25", "3.25"), Ordering::Equal);
+    assert_eq!(compare_list_versions("4.0", "3.99"), Ordering::Greater);
+    assert_eq!(compare_list_versions("3.25.1", "3.25"), Ordering::Greater);
+}